@@ -0,0 +1,162 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use tonlib::address::TonAddress;
+use tonlib::cell::{
+    key_extractor_uint, value_extractor_cell, BagOfCells, Cell, CellBuilder, GenericDictLoader,
+};
+use tonlib::tl::{TvmCell, TvmNumber, TvmStack, TvmStackEntry};
+use tonlib::wallet::WalletVersion;
+
+const ADDRESS: &str = "EQDk2VTvn04SUKJrW7rXahzdF8_Qi6utb0wj43InCu9vdjrR";
+const DICT_SIZE: u32 = 1000;
+
+/// Builds dictionary with 32-bit keys and 32-bit values using short labels only.
+fn build_dict(keys: &[u32], depth: usize) -> Cell {
+    let remaining = 32 - depth;
+    let shifted = |k: u32, bits: usize| k.checked_shl(bits as u32).unwrap_or(0);
+    let first = shifted(keys[0], depth);
+    let common = keys
+        .iter()
+        .map(|k| (shifted(*k, depth) ^ first).leading_zeros() as usize)
+        .min()
+        .unwrap_or(32)
+        .min(remaining);
+    let mut builder = CellBuilder::new();
+    builder.store_bit(false).unwrap();
+    for _ in 0..common {
+        builder.store_bit(true).unwrap();
+    }
+    builder.store_bit(false).unwrap();
+    for i in 0..common {
+        builder
+            .store_bit(shifted(first, i) & 0x8000_0000 != 0)
+            .unwrap();
+    }
+    if common == remaining {
+        builder.store_u32(32, keys[0]).unwrap();
+    } else {
+        let bit = depth + common;
+        let (left, right): (Vec<u32>, Vec<u32>) = keys
+            .iter()
+            .partition(|k| shifted(**k, bit) & 0x8000_0000 == 0);
+        builder
+            .store_child(build_dict(&left, bit + 1))
+            .unwrap()
+            .store_child(build_dict(&right, bit + 1))
+            .unwrap();
+    }
+    builder.build().unwrap()
+}
+
+fn boc_benchmarks(c: &mut Criterion) {
+    let code = WalletVersion::V5R1.code().unwrap();
+    let serial = BagOfCells::new(std::slice::from_ref(code))
+        .serialize(false)
+        .unwrap();
+    // state inits of 100 wallets, which share the code
+    let serials: Vec<Vec<u8>> = (0..100)
+        .map(|i| {
+            let data = CellBuilder::new()
+                .store_u32(32, i)
+                .unwrap()
+                .build()
+                .unwrap();
+            let state_init = CellBuilder::new()
+                .store_bits(2, &[0])
+                .unwrap()
+                .store_bit(true)
+                .unwrap()
+                .store_bit(true)
+                .unwrap()
+                .store_bit(false)
+                .unwrap()
+                .store_reference(code)
+                .unwrap()
+                .store_child(data)
+                .unwrap()
+                .build()
+                .unwrap();
+            BagOfCells::from_root(state_init).serialize(false).unwrap()
+        })
+        .collect();
+
+    c.bench_function("boc_parse", |b| {
+        b.iter(|| BagOfCells::parse(black_box(&serial)).unwrap())
+    });
+    c.bench_function("boc_parse_100", |b| {
+        b.iter(|| {
+            black_box(&serials)
+                .iter()
+                .map(|s| BagOfCells::parse(s).unwrap())
+                .collect::<Vec<_>>()
+        })
+    });
+    c.bench_function("boc_parse_many_100", |b| {
+        b.iter(|| BagOfCells::parse_many(black_box(&serials)).unwrap())
+    });
+    c.bench_function("boc_hash_many_100", |b| {
+        b.iter(|| BagOfCells::hash_many(black_box(&serials)).unwrap())
+    });
+    c.bench_function("boc_serialize", |b| {
+        let boc = BagOfCells::parse(&serial).unwrap();
+        b.iter(|| black_box(&boc).serialize(false).unwrap())
+    });
+}
+
+fn dict_benchmarks(c: &mut Criterion) {
+    let keys: Vec<u32> = (0..DICT_SIZE)
+        .map(|i| i.wrapping_mul(2_654_435_761))
+        .collect();
+    let dict = build_dict(&keys, 0);
+    let loader = GenericDictLoader::new(key_extractor_uint, value_extractor_cell, 32);
+    assert_eq!(dict.load_generic_dict(&loader).unwrap().len(), keys.len());
+
+    c.bench_function("dict_load_1000", |b| {
+        b.iter(|| black_box(&dict).load_generic_dict(&loader).unwrap())
+    });
+}
+
+fn address_benchmarks(c: &mut Criterion) {
+    c.bench_function("address_parse", |b| {
+        b.iter(|| black_box(ADDRESS).parse::<TonAddress>().unwrap())
+    });
+}
+
+fn get_method_benchmarks(c: &mut Criterion) {
+    let address: TonAddress = ADDRESS.parse().unwrap();
+    let address_cell = CellBuilder::new()
+        .store_address(&address)
+        .unwrap()
+        .build()
+        .unwrap();
+    let stack = TvmStack::from(&[
+        TvmStackEntry::Number {
+            number: TvmNumber {
+                number: "1000000000000".to_string(),
+            },
+        },
+        TvmStackEntry::Cell {
+            cell: TvmCell {
+                bytes: BagOfCells::from_root(address_cell)
+                    .serialize(false)
+                    .unwrap(),
+            },
+        },
+    ]);
+    c.bench_function("get_method_decode", |b| {
+        b.iter(|| {
+            let stack = black_box(&stack);
+            (stack.get_biguint(0).unwrap(), stack.get_address(1).unwrap())
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    boc_benchmarks,
+    dict_benchmarks,
+    address_benchmarks,
+    get_method_benchmarks
+);
+criterion_main!(benches);
@@ -0,0 +1,84 @@
+use std::fmt::Write;
+use std::path::Path;
+
+#[path = "src/tl/scheme_parser.rs"]
+#[allow(dead_code)]
+mod scheme_parser;
+
+use scheme_parser::{TlCombinator, TlTypeRef};
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    generate_tl_scheme();
+    #[cfg(feature = "server")]
+    compile_protos();
+}
+
+#[cfg(feature = "server")]
+fn compile_protos() {
+    let proto = "scheme/ton_service.proto";
+    println!("cargo:rerun-if-changed={}", proto);
+    let fds = protox::compile([proto], ["scheme"]).expect("Failed to parse proto");
+    tonic_build::configure()
+        .build_client(true)
+        .build_server(true)
+        .compile_fds(fds)
+        .expect("Failed to compile proto");
+}
+
+fn generate_tl_scheme() {
+    let scheme_path = "scheme/tonlib_api.tl";
+    println!("cargo:rerun-if-changed={}", scheme_path);
+    println!("cargo:rerun-if-changed=src/tl/scheme_parser.rs");
+    let scheme = std::fs::read_to_string(scheme_path).expect("Failed to read tonlib_api.tl");
+    let combinators = scheme_parser::parse_combinators(&scheme)
+        .unwrap_or_else(|e| panic!("Invalid tonlib_api.tl: {}", e));
+
+    let mut code = String::from("fn bundled_combinators() -> Vec<TlCombinator> {\n    vec![\n");
+    for combinator in combinators.iter() {
+        write_combinator(&mut code, combinator);
+    }
+    code.push_str("    ]\n}\n");
+
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR is not set");
+    std::fs::write(Path::new(&out_dir).join("tonlib_api_scheme.rs"), code)
+        .expect("Failed to write generated TL scheme");
+}
+
+fn write_combinator(code: &mut String, combinator: &TlCombinator) {
+    let fields = combinator
+        .fields
+        .iter()
+        .map(|f| {
+            format!(
+                "TlField {{ name: {:?}.to_string(), type_ref: {} }}",
+                f.name,
+                type_ref_code(&f.type_ref)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let _ = writeln!(
+        code,
+        "        TlCombinator {{ name: {:?}.to_string(), id: {:#010x}, fields: vec![{}], \
+         result_type: {:?}.to_string(), is_function: {} }},",
+        combinator.name, combinator.id, fields, combinator.result_type, combinator.is_function
+    );
+}
+
+fn type_ref_code(type_ref: &TlTypeRef) -> String {
+    match type_ref {
+        TlTypeRef::Vector(inner) => {
+            format!("TlTypeRef::Vector(Box::new({}))", type_ref_code(inner))
+        }
+        TlTypeRef::Bare(name) => format!("TlTypeRef::Bare({:?}.to_string())", name),
+        TlTypeRef::Boxed(name) => format!("TlTypeRef::Boxed({:?}.to_string())", name),
+        TlTypeRef::Conditional { field, bit, inner } => format!(
+            "TlTypeRef::Conditional {{ field: {:?}.to_string(), bit: {}, inner: Box::new({}) }}",
+            field,
+            bit,
+            type_ref_code(inner)
+        ),
+        simple => format!("TlTypeRef::{:?}", simple),
+    }
+}
@@ -1,3 +1,4 @@
+mod classifier;
 mod error;
 
 use std::fmt::{Debug, Display, Formatter};
@@ -5,6 +6,7 @@ use std::str::FromStr;
 
 use base64::engine::general_purpose::{STANDARD_NO_PAD, URL_SAFE_NO_PAD};
 use base64::Engine;
+pub use classifier::*;
 use crc::Crc;
 pub use error::*;
 use lazy_static::lazy_static;
@@ -27,6 +29,42 @@ impl TonAddress {
         hash_part: [0; 32],
     };
 
+    /// Zero address of the basechain, commonly used as the destination of burned TON and NFTs.
+    pub const BURN: TonAddress = TonAddress::NULL;
+
+    /// Config contract, config param 0.
+    pub const CONFIG: TonAddress = TonAddress {
+        workchain: -1,
+        hash_part: [0x55; 32],
+    };
+
+    /// Elector contract, config param 1. Also collects fees (config param 3).
+    pub const ELECTOR: TonAddress = TonAddress {
+        workchain: -1,
+        hash_part: [0x33; 32],
+    };
+
+    /// Minter contract, config param 2, also used as the source of system messages.
+    pub const MINTER: TonAddress = TonAddress {
+        workchain: -1,
+        hash_part: [0; 32],
+    };
+
+    /// Returns true for the config, elector and minter contracts.
+    pub fn is_system(&self) -> bool {
+        self == &TonAddress::CONFIG || self == &TonAddress::ELECTOR || self == &TonAddress::MINTER
+    }
+
+    /// Returns true for the burn address.
+    pub fn is_burn(&self) -> bool {
+        self == &TonAddress::BURN
+    }
+
+    /// Returns true if all bits of the account id are zero, in any workchain.
+    pub fn is_zero(&self) -> bool {
+        self.hash_part == [0; 32]
+    }
+
     pub fn new(workchain: i32, hash_part: &[u8; 32]) -> TonAddress {
         TonAddress {
             workchain,
@@ -347,6 +385,44 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn well_known_addresses() -> anyhow::Result<()> {
+        let elector: TonAddress = "Ef8zMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzM0vF".parse()?;
+        assert_eq!(elector, TonAddress::ELECTOR);
+        let config: TonAddress =
+            "-1:5555555555555555555555555555555555555555555555555555555555555555".parse()?;
+        assert!(config.is_system());
+        assert!(TonAddress::MINTER.is_system() && TonAddress::MINTER.is_zero());
+        assert!(!TonAddress::BURN.is_system());
+
+        let burn: TonAddress = "UQAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAJKZ".parse()?;
+        assert!(burn.is_burn());
+        assert!(!TonAddress::MINTER.is_burn());
+        Ok(())
+    }
+
+    #[test]
+    fn masterchain_format_works() -> anyhow::Result<()> {
+        let addr = TonAddress::CONFIG;
+        assert_eq!(
+            addr.to_hex(),
+            "-1:5555555555555555555555555555555555555555555555555555555555555555"
+        );
+        let base64_url = addr.to_base64_url();
+        assert_eq!(
+            base64_url,
+            "Ef9VVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVbxn"
+        );
+        let (parsed, non_bounceable, _) = TonAddress::from_base64_url_flags(&base64_url)?;
+        assert_eq!((parsed, non_bounceable), (addr.clone(), false));
+
+        let non_bounceable = addr.to_base64_std_flags(true, false);
+        let (parsed, non_bounceable, _) = TonAddress::from_base64_std_flags(&non_bounceable)?;
+        assert_eq!((parsed, non_bounceable), (addr.clone(), true));
+        assert_eq!(addr.to_hex().parse::<TonAddress>()?, addr);
+        Ok(())
+    }
+
     #[test]
     fn parse_format_works() -> anyhow::Result<()> {
         let bytes: [u8; 32] =
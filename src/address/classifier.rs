@@ -0,0 +1,58 @@
+use crate::address::TonAddress;
+
+/// Role in which the address is encountered by decoders and monitors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AddressRole {
+    Account,
+    JettonMaster,
+    JettonWallet,
+    NftCollection,
+    NftItem,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AddressClass {
+    Trusted,
+    #[default]
+    Unknown,
+    Spam,
+    Scam,
+}
+
+impl AddressClass {
+    /// Returns true for addresses that should be dropped by filters.
+    pub fn is_suspicious(&self) -> bool {
+        matches!(self, AddressClass::Spam | AddressClass::Scam)
+    }
+}
+
+/// Hook for spam and scam filters, e.g. backed by a list of verified jetton masters.
+pub trait AddressClassifier: Send + Sync {
+    fn classify(&self, address: &TonAddress, role: AddressRole) -> AddressClass;
+}
+
+/// Classifier treating every address as unknown, so nothing is filtered out.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopAddressClassifier;
+
+impl AddressClassifier for NoopAddressClassifier {
+    fn classify(&self, _address: &TonAddress, _role: AddressRole) -> AddressClass {
+        AddressClass::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::address::{
+        AddressClass, AddressClassifier, AddressRole, NoopAddressClassifier, TonAddress,
+    };
+
+    #[test]
+    fn test_noop_address_classifier() {
+        let class = NoopAddressClassifier.classify(&TonAddress::NULL, AddressRole::JettonMaster);
+        assert_eq!(class, AddressClass::Unknown);
+        assert!(!class.is_suspicious());
+        assert!(AddressClass::Spam.is_suspicious());
+        assert!(AddressClass::Scam.is_suspicious());
+    }
+}
@@ -0,0 +1,248 @@
+//! Synchronous facade over `TonClient`, e.g. for CLI tools and scripts not running an async
+//! runtime.
+//!
+//! ```ignore
+//! let client = TonClientBlocking::new(TonClient::builder().with_config(MAINNET_CONFIG))?;
+//! let state = client.get_raw_account_state(&address)?;
+//! ```
+
+use std::future::Future;
+
+use tokio::runtime::{Builder, Runtime};
+
+use crate::address::TonAddress;
+use crate::client::{TonClient, TonClientBuilder, TonClientError, TonClientInterface};
+use crate::tl::{
+    BlockId, BlockIdExt, BlocksAccountTransactionId, BlocksHeader, BlocksMasterchainInfo,
+    BlocksShards, BlocksTransactions, BlocksTransactionsExt, ConfigInfo, FullAccountState,
+    InternalTransactionId, LiteServerInfo, RawFullAccountState, RawTransactions,
+    SmcLibraryQueryExt, SmcLibraryResult, SmcLibraryResultExt, SmcRunResult, TonFunction,
+    TonLibraryId, TonResult, TvmStackEntry,
+};
+use crate::types::TonMethodId;
+
+/// `TonClient` together with a single-threaded runtime, which runs the calls.
+///
+/// Methods mirror `TonClientInterface`. Calls of smart contracts loaded by `smc_load` are
+/// bound to the connection, so they are replaced by `smc_run_get_method`.
+pub struct TonClientBlocking {
+    // dropped before the runtime, which its background tasks run on
+    client: TonClient,
+    runtime: Runtime,
+}
+
+impl TonClientBlocking {
+    /// Builds the client on a new runtime.
+    pub fn new(builder: &TonClientBuilder) -> Result<TonClientBlocking, TonClientError> {
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        let client = runtime.block_on(builder.build())?;
+        Ok(TonClientBlocking { client, runtime })
+    }
+
+    pub fn client(&self) -> &TonClient {
+        &self.client
+    }
+
+    /// Runs the future on the runtime of the client, e.g. to call async APIs not mirrored
+    /// here.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    pub fn invoke(&self, function: &TonFunction) -> Result<TonResult, TonClientError> {
+        self.block_on(self.client.invoke(function))
+    }
+
+    pub fn get_raw_account_state(
+        &self,
+        account_address: &TonAddress,
+    ) -> Result<RawFullAccountState, TonClientError> {
+        self.block_on(self.client.get_raw_account_state(account_address))
+    }
+
+    pub fn get_raw_account_state_by_transaction(
+        &self,
+        account_address: &TonAddress,
+        transaction_id: &InternalTransactionId,
+    ) -> Result<RawFullAccountState, TonClientError> {
+        self.block_on(
+            self.client
+                .get_raw_account_state_by_transaction(account_address, transaction_id),
+        )
+    }
+
+    pub fn get_raw_transactions(
+        &self,
+        account_address: &TonAddress,
+        from_transaction_id: &InternalTransactionId,
+    ) -> Result<RawTransactions, TonClientError> {
+        self.block_on(
+            self.client
+                .get_raw_transactions(account_address, from_transaction_id),
+        )
+    }
+
+    pub fn get_raw_transactions_v2(
+        &self,
+        account_address: &TonAddress,
+        from_transaction_id: &InternalTransactionId,
+        count: usize,
+        try_decode_messages: bool,
+    ) -> Result<RawTransactions, TonClientError> {
+        self.block_on(self.client.get_raw_transactions_v2(
+            account_address,
+            from_transaction_id,
+            count,
+            try_decode_messages,
+        ))
+    }
+
+    pub fn send_raw_message(&self, body: &[u8]) -> Result<(), TonClientError> {
+        self.block_on(self.client.send_raw_message(body))
+    }
+
+    pub fn send_raw_message_return_hash(&self, body: &[u8]) -> Result<Vec<u8>, TonClientError> {
+        self.block_on(self.client.send_raw_message_return_hash(body))
+    }
+
+    pub fn sync(&self) -> Result<BlockIdExt, TonClientError> {
+        let (_, block_id) = self.block_on(self.client.sync())?;
+        Ok(block_id)
+    }
+
+    pub fn get_account_state(
+        &self,
+        account_address: &TonAddress,
+    ) -> Result<FullAccountState, TonClientError> {
+        self.block_on(self.client.get_account_state(account_address))
+    }
+
+    pub fn get_account_state_by_transaction(
+        &self,
+        account_address: &TonAddress,
+        transaction_id: &InternalTransactionId,
+    ) -> Result<FullAccountState, TonClientError> {
+        self.block_on(
+            self.client
+                .get_account_state_by_transaction(account_address, transaction_id),
+        )
+    }
+
+    /// Loads the smart contract, runs the get-method and forgets the contract.
+    pub fn smc_run_get_method(
+        &self,
+        account_address: &TonAddress,
+        method: &TonMethodId,
+        stack: &[TvmStackEntry],
+    ) -> Result<SmcRunResult, TonClientError> {
+        self.block_on(async {
+            let state = self.client.smc_load(account_address).await?;
+            let result = state.conn.smc_run_get_method(state.id, method, stack).await;
+            if let Err(e) = state.conn.smc_forget(state.id).await {
+                log::warn!("Failed to forget smc {}: {}", state.id, e);
+            }
+            result
+        })
+    }
+
+    pub fn smc_get_libraries(
+        &self,
+        library_list: &[TonLibraryId],
+    ) -> Result<SmcLibraryResult, TonClientError> {
+        self.block_on(self.client.smc_get_libraries(library_list))
+    }
+
+    pub fn smc_get_libraries_ext(
+        &self,
+        list: &[SmcLibraryQueryExt],
+    ) -> Result<SmcLibraryResultExt, TonClientError> {
+        self.block_on(self.client.smc_get_libraries_ext(list))
+    }
+
+    pub fn get_masterchain_info(&self) -> Result<BlocksMasterchainInfo, TonClientError> {
+        let (_, info) = self.block_on(self.client.get_masterchain_info())?;
+        Ok(info)
+    }
+
+    pub fn get_block_shards(&self, block_id: &BlockIdExt) -> Result<BlocksShards, TonClientError> {
+        self.block_on(self.client.get_block_shards(block_id))
+    }
+
+    pub fn lookup_block(
+        &self,
+        mode: i32,
+        block_id: &BlockId,
+        lt: i64,
+        utime: i32,
+    ) -> Result<BlockIdExt, TonClientError> {
+        self.block_on(self.client.lookup_block(mode, block_id, lt, utime))
+    }
+
+    pub fn get_block_transactions(
+        &self,
+        block_id: &BlockIdExt,
+        mode: u32,
+        count: u32,
+        after: &BlocksAccountTransactionId,
+    ) -> Result<BlocksTransactions, TonClientError> {
+        self.block_on(
+            self.client
+                .get_block_transactions(block_id, mode, count, after),
+        )
+    }
+
+    pub fn get_block_transactions_ext(
+        &self,
+        block_id: &BlockIdExt,
+        mode: u32,
+        count: u32,
+        after: &BlocksAccountTransactionId,
+    ) -> Result<BlocksTransactionsExt, TonClientError> {
+        self.block_on(
+            self.client
+                .get_block_transactions_ext(block_id, mode, count, after),
+        )
+    }
+
+    pub fn lite_server_get_info(&self) -> Result<LiteServerInfo, TonClientError> {
+        self.block_on(self.client.lite_server_get_info())
+    }
+
+    pub fn lite_server_get_version(&self) -> Result<i32, TonClientError> {
+        self.block_on(self.client.lite_server_get_version())
+    }
+
+    pub fn lite_server_get_time(&self) -> Result<i64, TonClientError> {
+        self.block_on(self.client.lite_server_get_time())
+    }
+
+    pub fn get_block_header(&self, block_id: &BlockIdExt) -> Result<BlocksHeader, TonClientError> {
+        self.block_on(self.client.get_block_header(block_id))
+    }
+
+    pub fn get_config_param(&self, mode: u32, param: u32) -> Result<ConfigInfo, TonClientError> {
+        self.block_on(self.client.get_config_param(mode, param))
+    }
+
+    pub fn get_config_all(&self, mode: u32) -> Result<ConfigInfo, TonClientError> {
+        self.block_on(self.client.get_config_all(mode))
+    }
+
+    pub fn get_log_verbosity_level(&self) -> Result<u32, TonClientError> {
+        self.block_on(self.client.get_log_verbosity_level())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::blocking::TonClientBlocking;
+    use crate::client::TonClient;
+
+    #[test]
+    fn test_blocking_client_outside_runtime() -> anyhow::Result<()> {
+        let client = TonClientBlocking::new(TonClient::builder().with_pool_size(2))?;
+        assert_eq!(client.client().pool_size(), 2);
+        assert_eq!(client.block_on(async { 42 }), 42);
+        Ok(())
+    }
+}
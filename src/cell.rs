@@ -1,3 +1,5 @@
+#![cfg_attr(not(test), deny(clippy::unwrap_used, clippy::expect_used))]
+
 use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
 use std::hash::Hash;
@@ -8,18 +10,19 @@ use std::{fmt, io};
 pub use bag_of_cells::*;
 use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use base64::Engine;
+pub use big_number::*;
 use bit_string::*;
 use bitstream_io::{BigEndian, BitWrite, BitWriter};
 pub use builder::*;
 pub use dict_loader::*;
+pub use embedded::*;
 pub use error::*;
-use hmac::digest::Digest;
+pub use hasher::*;
 use lazy_static::lazy_static;
 use num_bigint::BigUint;
 use num_traits::{One, ToPrimitive};
 pub use parser::*;
 pub use raw::*;
-use sha2::Sha256;
 pub use slice::*;
 pub use state_init::*;
 pub use util::*;
@@ -29,11 +32,14 @@ use crate::cell::level_mask::LevelMask;
 use crate::types::{TonHash, DEFAULT_CELL_HASH};
 
 mod bag_of_cells;
+mod big_number;
 mod bit_string;
 mod builder;
 mod cell_type;
 mod dict_loader;
+mod embedded;
 mod error;
+mod hasher;
 mod level_mask;
 mod parser;
 mod raw;
@@ -72,6 +78,19 @@ impl Cell {
         references: Vec<ArcCell>,
         is_exotic: bool,
     ) -> Result<Self, TonCellError> {
+        if bit_len > data.len() * 8 {
+            return Err(TonCellError::cell_builder_error(format!(
+                "Cell data is too short ({} bytes) for bit length {}",
+                data.len(),
+                bit_len
+            )));
+        }
+        if references.len() > 4 {
+            return Err(TonCellError::cell_builder_error(format!(
+                "Cell can't have more than 4 references, got {}",
+                references.len()
+            )));
+        }
         let cell_type = if is_exotic {
             CellType::determine_exotic_cell_type(&data)?
         } else {
@@ -190,7 +209,12 @@ impl Cell {
         let mut uri = String::new();
         loop {
             let parsed_cell = if first_cell {
-                String::from_utf8_lossy(&cell.data[1..]).to_string()
+                let data = cell.data.get(1..).ok_or_else(|| {
+                    TonCellError::boc_deserialization_error(
+                        "Invalid snake format string: empty cell",
+                    )
+                })?;
+                String::from_utf8_lossy(data).to_string()
             } else {
                 String::from_utf8_lossy(&cell.data).to_string()
             };
@@ -198,7 +222,7 @@ impl Cell {
             match cell.references.len() {
                 0 => return Ok(uri),
                 1 => {
-                    cell = cell.references[0].deref();
+                    cell = cell.reference(0)?.deref();
                     first_cell = false;
                 }
                 n => {
@@ -212,8 +236,14 @@ impl Cell {
     }
 
     fn parse_snake_data(&self, buffer: &mut Vec<u8>) -> Result<(), TonCellError> {
+        self.parse_snake_chain(buffer, true)
+    }
+
+    /// Appends the data of the cell and of the chain of continuation cells to the buffer.
+    /// The data of the first cell starts with zero byte prefix if `prefixed` is set.
+    fn parse_snake_chain(&self, buffer: &mut Vec<u8>, prefixed: bool) -> Result<(), TonCellError> {
         let mut cell = self;
-        let mut first_cell = true;
+        let mut first_cell = prefixed;
         loop {
             let mut parser = cell.parser();
             if first_cell {
@@ -231,7 +261,7 @@ impl Cell {
             match cell.references.len() {
                 0 => return Ok(()),
                 1 => {
-                    cell = cell.references[0].deref();
+                    cell = cell.reference(0)?.deref();
                     first_cell = false;
                 }
                 n => {
@@ -269,6 +299,10 @@ impl Cell {
 
         let lb0 = parser.load_bit()?;
         let mut pp = prefix;
+        let remaining_key_bits = dict_loader
+            .key_bit_len()
+            .checked_sub(pp.bit_len())
+            .ok_or_else(|| TonCellError::cell_parser_error("Dictionary key is too long"))?;
         let prefix_length;
         if !lb0 {
             // Short label detected
@@ -282,14 +316,7 @@ impl Cell {
             let lb1 = parser.load_bit()?;
             if !lb1 {
                 // Long label detected
-                prefix_length = parser
-                    .load_uint(
-                        ((dict_loader.key_bit_len() - pp.bit_len() + 1) as f32)
-                            .log2()
-                            .ceil() as usize,
-                    )?
-                    .to_usize()
-                    .unwrap();
+                prefix_length = Self::load_label_length(&mut parser, remaining_key_bits)?;
                 if prefix_length != 0 {
                     let val = parser.load_uint(prefix_length)?;
                     pp.shl_assign_and_add(prefix_length, val);
@@ -297,14 +324,7 @@ impl Cell {
             } else {
                 // Same label detected
                 let bit = parser.load_bit()?;
-                prefix_length = parser
-                    .load_uint(
-                        ((dict_loader.key_bit_len() - pp.bit_len() + 1) as f32)
-                            .log2()
-                            .ceil() as usize,
-                    )?
-                    .to_usize()
-                    .unwrap();
+                prefix_length = Self::load_label_length(&mut parser, remaining_key_bits)?;
                 if bit {
                     pp.shl_assign_and_fill(prefix_length);
                 } else {
@@ -313,7 +333,7 @@ impl Cell {
             }
         }
 
-        if dict_loader.key_bit_len() - pp.bit_len() == 0 {
+        if dict_loader.key_bit_len() == pp.bit_len() {
             let bytes = pp.get_value_as_bytes();
             let key = dict_loader.extract_key(bytes.as_slice())?;
             let offset = self.bit_len - parser.remaining_bits();
@@ -332,6 +352,16 @@ impl Cell {
         Ok(())
     }
 
+    fn load_label_length(
+        parser: &mut CellParser,
+        remaining_key_bits: usize,
+    ) -> Result<usize, TonCellError> {
+        let bit_len = ((remaining_key_bits + 1) as f32).log2().ceil() as usize;
+        parser.load_uint(bit_len)?.to_usize().ok_or_else(|| {
+            TonCellError::cell_parser_error("Dictionary label length doesn't fit into usize")
+        })
+    }
+
     pub fn to_arc(self) -> ArcCell {
         Arc::new(self)
     }
@@ -511,13 +541,7 @@ fn calculate_hashes_and_depths(
             level_i,
             cell_type,
         )?;
-        let hash = Sha256::new_with_prefix(repr).finalize()[..]
-            .try_into()
-            .map_err(|error| {
-                TonCellError::InternalError(format!(
-                    "Can't get [u8; 32] from finalized hash with error: {error}"
-                ))
-            })?;
+        let hash = cell_hasher().sha256(&repr);
 
         depths.push(depth);
         hashes.push(hash);
@@ -534,7 +558,7 @@ fn get_refs_descriptor(cell_type: CellType, references: &[ArcCell], level_mask:
 fn get_bits_descriptor(data: &[u8], bit_len: usize) -> u8 {
     let rest_bits = bit_len % 8;
     let full_bytes = rest_bits == 0;
-    data.len() as u8 * 2 - !full_bytes as u8 // subtract 1 if the last byte is not full
+    (data.len() * 2 - !full_bytes as usize) as u8 // subtract 1 if the last byte is not full
 }
 
 fn write_data(
@@ -1,9 +1,12 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use base64::engine::general_purpose::STANDARD;
 
+use crate::cell::raw::RawCell;
 use crate::cell::raw_boc_from_boc::convert_to_raw_boc;
 use crate::cell::*;
+use crate::types::TonHash;
 
 #[derive(PartialEq, Eq, Debug, Clone, Hash)]
 pub struct BagOfCells {
@@ -54,6 +57,24 @@ impl BagOfCells {
     }
 
     pub fn parse(serial: &[u8]) -> Result<BagOfCells, TonCellError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("boc_parse", len = serial.len()).entered();
+        Self::parse_with(serial, |raw_cell, references| {
+            Ok(Cell::new(
+                raw_cell.data,
+                raw_cell.bit_len,
+                references,
+                raw_cell.is_exotic,
+            )
+            .map_boc_deserialization_error()?
+            .to_arc())
+        })
+    }
+
+    fn parse_with<F>(serial: &[u8], mut new_cell: F) -> Result<BagOfCells, TonCellError>
+    where
+        F: FnMut(RawCell, Vec<ArcCell>) -> Result<ArcCell, TonCellError>,
+    {
         let raw = RawBagOfCells::parse(serial)?;
         let num_cells = raw.cells.len();
         let mut cells: Vec<ArcCell> = Vec::with_capacity(num_cells);
@@ -69,14 +90,7 @@ impl BagOfCells {
                 references.push(cells[num_cells - 1 - ref_index].clone());
             }
 
-            let cell = Cell::new(
-                raw_cell.data,
-                raw_cell.bit_len,
-                references,
-                raw_cell.is_exotic,
-            )
-            .map_boc_deserialization_error()?;
-            cells.push(cell.to_arc());
+            cells.push(new_cell(raw_cell, references)?);
         }
 
         let roots = raw
@@ -100,17 +114,101 @@ impl BagOfCells {
         Self::parse(&bin)
     }
 
+    /// Parses several BoCs.
+    ///
+    /// Cells are deduplicated across the BoCs, so a cell present in several of them (e.g.
+    /// contract code in state inits or a common message body) is built and hashed once and
+    /// shared by the results.
+    pub fn parse_many<T: AsRef<[u8]>>(serials: &[T]) -> Result<Vec<BagOfCells>, TonCellError> {
+        let mut cache = CellCache::default();
+        serials
+            .iter()
+            .map(|s| Self::parse_with(s.as_ref(), |raw, refs| cache.get_or_create(raw, refs)))
+            .collect()
+    }
+
+    /// Parses several base64-encoded BoCs, reusing the decoding buffer.
+    pub fn parse_base64_many<T: AsRef<str>>(
+        base64s: &[T],
+    ) -> Result<Vec<BagOfCells>, TonCellError> {
+        let mut bin = Vec::new();
+        base64s
+            .iter()
+            .map(|base64| {
+                bin.clear();
+                STANDARD
+                    .decode_vec(base64.as_ref(), &mut bin)
+                    .map_boc_deserialization_error()?;
+                Self::parse(&bin)
+            })
+            .collect()
+    }
+
+    /// Returns root hashes of several single-root BoCs. Cells are deduplicated across the BoCs
+    /// as in `parse_many`.
+    pub fn hash_many<T: AsRef<[u8]>>(serials: &[T]) -> Result<Vec<TonHash>, TonCellError> {
+        let mut cache = CellCache::default();
+        serials
+            .iter()
+            .map(|s| {
+                let boc = Self::parse_with(s.as_ref(), |raw, refs| cache.get_or_create(raw, refs))?;
+                Ok(boc.single_root()?.cell_hash())
+            })
+            .collect()
+    }
+
     pub fn serialize(&self, has_crc32: bool) -> Result<Vec<u8>, TonCellError> {
         let raw = convert_to_raw_boc(self)?;
         raw.serialize(has_crc32)
     }
 }
 
+/// Content of a cell with hashes of its references, which determines the cell.
+#[derive(PartialEq, Eq, Hash)]
+struct CellKey {
+    data: Vec<u8>,
+    bit_len: usize,
+    is_exotic: bool,
+    references: Vec<TonHash>,
+}
+
+/// Cells already built while parsing a batch of BoCs.
+#[derive(Default)]
+struct CellCache {
+    cells: HashMap<CellKey, ArcCell>,
+}
+
+impl CellCache {
+    fn get_or_create(
+        &mut self,
+        raw_cell: RawCell,
+        references: Vec<ArcCell>,
+    ) -> Result<ArcCell, TonCellError> {
+        let key = CellKey {
+            data: raw_cell.data,
+            bit_len: raw_cell.bit_len,
+            is_exotic: raw_cell.is_exotic,
+            references: references.iter().map(|r| r.cell_hash()).collect(),
+        };
+        if let Some(cell) = self.cells.get(&key) {
+            return Ok(cell.clone());
+        }
+        let cell = Cell::new(key.data.clone(), key.bit_len, references, key.is_exotic)
+            .map_boc_deserialization_error()?
+            .to_arc();
+        self.cells.insert(key, cell.clone());
+        Ok(cell)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
     use std::time::Instant;
 
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+
     use crate::cell::raw_boc_from_boc::convert_to_raw_boc;
     use crate::cell::{BagOfCells, CellBuilder, TonCellError};
     use crate::message::ZERO_COINS;
@@ -311,4 +409,52 @@ mod tests {
         let _raw = convert_to_raw_boc(&boc)?;
         Ok(())
     }
+
+    #[test]
+    fn it_parses_many() -> anyhow::Result<()> {
+        let roots = [
+            CellBuilder::new().store_byte(1)?.build()?,
+            CellBuilder::new().store_byte(2)?.build()?,
+        ];
+        let serials = roots
+            .iter()
+            .map(|root| BagOfCells::from_root(root.clone()).serialize(false))
+            .collect::<Result<Vec<_>, _>>()?;
+        let base64s: Vec<String> = serials.iter().map(|s| STANDARD.encode(s)).collect();
+
+        let expected: Vec<BagOfCells> = roots.iter().cloned().map(BagOfCells::from_root).collect();
+        assert_eq!(BagOfCells::parse_many(&serials)?, expected);
+        assert_eq!(BagOfCells::parse_base64_many(&base64s)?, expected);
+        assert_eq!(
+            BagOfCells::hash_many(&serials)?,
+            vec![roots[0].cell_hash(), roots[1].cell_hash()]
+        );
+        assert!(BagOfCells::parse_many(&[vec![0u8; 4]]).is_err());
+
+        // cells shared by the BoCs are shared by the results
+        let shared = CellBuilder::new().store_byte(3)?.build()?.to_arc();
+        let serials = (4..6)
+            .map(|i| {
+                let root = CellBuilder::new()
+                    .store_byte(i)?
+                    .store_reference(&shared)?
+                    .build()?;
+                BagOfCells::from_root(root).serialize(false)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let bocs = BagOfCells::parse_many(&serials)?;
+        assert_eq!(bocs[0].roots[0].reference(0)?.as_ref(), shared.as_ref());
+        assert!(Arc::ptr_eq(
+            bocs[0].roots[0].reference(0)?,
+            bocs[1].roots[0].reference(0)?
+        ));
+        assert_eq!(
+            bocs,
+            serials
+                .iter()
+                .map(|s| BagOfCells::parse(s))
+                .collect::<Result<Vec<_>, _>>()?
+        );
+        Ok(())
+    }
 }
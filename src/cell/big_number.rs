@@ -0,0 +1,242 @@
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+#[cfg(feature = "u256")]
+use primitive_types::U256;
+
+use crate::cell::{CellBuilder, CellParser, TonCellError};
+
+/// Unsigned integer, which can be stored in and loaded from cells, e.g. coins and balances.
+///
+/// `BigUint` can hold any value, but allocates. Fixed-size `u64`, `u128` and `U256` (`u256`
+/// feature) avoid heap allocations, loading a value, which does not fit, fails.
+pub trait BigNumber: Sized {
+    fn bits(&self) -> usize;
+
+    fn store(&self, builder: &mut CellBuilder, bit_len: usize) -> Result<(), TonCellError>;
+
+    fn load(parser: &mut CellParser, bit_len: usize) -> Result<Self, TonCellError>;
+
+    fn to_biguint(&self) -> BigUint;
+
+    /// Returns `None` if the value does not fit.
+    fn from_biguint(value: &BigUint) -> Option<Self>;
+}
+
+impl BigNumber for BigUint {
+    fn bits(&self) -> usize {
+        BigUint::bits(self) as usize
+    }
+
+    fn store(&self, builder: &mut CellBuilder, bit_len: usize) -> Result<(), TonCellError> {
+        builder.store_uint(bit_len, self)?;
+        Ok(())
+    }
+
+    fn load(parser: &mut CellParser, bit_len: usize) -> Result<Self, TonCellError> {
+        parser.load_uint(bit_len)
+    }
+
+    fn to_biguint(&self) -> BigUint {
+        self.clone()
+    }
+
+    fn from_biguint(value: &BigUint) -> Option<Self> {
+        Some(value.clone())
+    }
+}
+
+impl BigNumber for u64 {
+    fn bits(&self) -> usize {
+        (u64::BITS - self.leading_zeros()) as usize
+    }
+
+    fn store(&self, builder: &mut CellBuilder, bit_len: usize) -> Result<(), TonCellError> {
+        store_words(builder, bit_len, self.bits(), &[*self])
+    }
+
+    fn load(parser: &mut CellParser, bit_len: usize) -> Result<Self, TonCellError> {
+        let [word] = load_words(parser, bit_len)?;
+        Ok(word)
+    }
+
+    fn to_biguint(&self) -> BigUint {
+        BigUint::from(*self)
+    }
+
+    fn from_biguint(value: &BigUint) -> Option<Self> {
+        value.to_u64()
+    }
+}
+
+impl BigNumber for u128 {
+    fn bits(&self) -> usize {
+        (u128::BITS - self.leading_zeros()) as usize
+    }
+
+    fn store(&self, builder: &mut CellBuilder, bit_len: usize) -> Result<(), TonCellError> {
+        let words = [(*self >> 64) as u64, *self as u64];
+        store_words(builder, bit_len, self.bits(), &words)
+    }
+
+    fn load(parser: &mut CellParser, bit_len: usize) -> Result<Self, TonCellError> {
+        let [high, low] = load_words(parser, bit_len)?;
+        Ok(((high as u128) << 64) | low as u128)
+    }
+
+    fn to_biguint(&self) -> BigUint {
+        BigUint::from(*self)
+    }
+
+    fn from_biguint(value: &BigUint) -> Option<Self> {
+        value.to_u128()
+    }
+}
+
+#[cfg(feature = "u256")]
+impl BigNumber for U256 {
+    fn bits(&self) -> usize {
+        U256::bits(self)
+    }
+
+    fn store(&self, builder: &mut CellBuilder, bit_len: usize) -> Result<(), TonCellError> {
+        // limbs are little-endian
+        let words = [self.0[3], self.0[2], self.0[1], self.0[0]];
+        store_words(builder, bit_len, self.bits(), &words)
+    }
+
+    fn load(parser: &mut CellParser, bit_len: usize) -> Result<Self, TonCellError> {
+        let [w3, w2, w1, w0] = load_words(parser, bit_len)?;
+        Ok(U256([w0, w1, w2, w3]))
+    }
+
+    fn to_biguint(&self) -> BigUint {
+        BigUint::from_slice(&[
+            self.0[0] as u32,
+            (self.0[0] >> 32) as u32,
+            self.0[1] as u32,
+            (self.0[1] >> 32) as u32,
+            self.0[2] as u32,
+            (self.0[2] >> 32) as u32,
+            self.0[3] as u32,
+            (self.0[3] >> 32) as u32,
+        ])
+    }
+
+    fn from_biguint(value: &BigUint) -> Option<Self> {
+        if value.bits() > 256 {
+            return None;
+        }
+        Some(U256::from_big_endian(&value.to_bytes_be()))
+    }
+}
+
+/// Stores `bit_len` bits of the value given as big-endian 64-bit words.
+fn store_words(
+    builder: &mut CellBuilder,
+    bit_len: usize,
+    bits: usize,
+    words: &[u64],
+) -> Result<(), TonCellError> {
+    if bits > bit_len {
+        return Err(TonCellError::cell_builder_error(format!(
+            "Value doesn't fit in {} bits (takes {} bits)",
+            bit_len, bits
+        )));
+    }
+    let mut leading_zeros = bit_len.saturating_sub(words.len() * 64);
+    while leading_zeros > 0 {
+        let chunk = leading_zeros.min(64);
+        builder.store_u64(chunk, 0)?;
+        leading_zeros -= chunk;
+    }
+    for (i, word) in words.iter().enumerate() {
+        let low = (words.len() - 1 - i) * 64;
+        if bit_len > low {
+            builder.store_u64((bit_len - low).min(64), *word)?;
+        }
+    }
+    Ok(())
+}
+
+/// Loads `bit_len` bits as big-endian 64-bit words, failing if the value does not fit.
+fn load_words<const N: usize>(
+    parser: &mut CellParser,
+    bit_len: usize,
+) -> Result<[u64; N], TonCellError> {
+    let mut leading_bits = bit_len.saturating_sub(N * 64);
+    if parser.remaining_bits() < bit_len {
+        return Err(TonCellError::cell_parser_error(format!(
+            "Not enough bits to read {} bits",
+            bit_len
+        )));
+    }
+    while leading_bits > 0 {
+        let chunk = leading_bits.min(64);
+        if parser.load_u64(chunk)? != 0 {
+            return Err(TonCellError::cell_parser_error(format!(
+                "Value of {} bits doesn't fit in {} bits",
+                bit_len,
+                N * 64
+            )));
+        }
+        leading_bits -= chunk;
+    }
+    let mut words = [0; N];
+    for (i, word) in words.iter_mut().enumerate() {
+        let low = (N - 1 - i) * 64;
+        if bit_len > low {
+            *word = parser.load_u64((bit_len - low).min(64))?;
+        }
+    }
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use crate::cell::{BigNumber, CellBuilder};
+    use crate::types::TvmStackEntry;
+
+    #[test]
+    fn test_big_number_coins() -> anyhow::Result<()> {
+        let value: u128 = 0x0102_0304_0506_0708_090a_0b0c_0d0e;
+        let cell = CellBuilder::new()
+            .store_coins(&BigUint::from(value))?
+            .store_coins_number(&value)?
+            .store_coins_number(&0u64)?
+            .store_big_number(100, &u64::MAX)?
+            .build()?;
+        let mut parser = cell.parser();
+        assert_eq!(parser.load_coins_number::<u128>()?, value);
+        assert_eq!(parser.load_coins()?, BigUint::from(value));
+        assert_eq!(parser.load_coins_number::<u64>()?, 0);
+        assert_eq!(parser.load_big_number::<u64>(100)?, u64::MAX);
+
+        let mut parser = cell.parser();
+        assert!(parser.load_coins_number::<u64>().is_err());
+        assert!(CellBuilder::new().store_big_number(63, &u64::MAX).is_err());
+        assert_eq!(u128::from_biguint(&value.to_biguint()), Some(value));
+        assert_eq!(u64::from_biguint(&value.to_biguint()), None);
+        assert_eq!(TvmStackEntry::Int64(5).get_big_number::<u128>()?, 5);
+        assert!(TvmStackEntry::Int64(-5).get_big_number::<u64>().is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "u256")]
+    #[test]
+    fn test_big_number_u256() -> anyhow::Result<()> {
+        use primitive_types::U256;
+
+        let value = U256::MAX >> 4;
+        let cell = CellBuilder::new()
+            .store_big_number(256, &value)?
+            .store_coins_number(&U256::from(1_000_000_000u64))?
+            .build()?;
+        let mut parser = cell.parser();
+        assert_eq!(parser.load_big_number::<U256>(256)?, value);
+        assert_eq!(parser.load_coins()?, BigUint::from(1_000_000_000u64));
+        assert_eq!(U256::from_biguint(&value.to_biguint()), Some(value));
+        Ok(())
+    }
+}
@@ -7,10 +7,31 @@ use num_traits::{One, Zero};
 
 use crate::address::TonAddress;
 use crate::cell::error::{MapTonCellError, TonCellError};
-use crate::cell::{ArcCell, Cell, CellParser};
+use crate::cell::{ArcCell, BigNumber, Cell, CellParser};
 
 const MAX_CELL_BITS: usize = 1023;
 const MAX_CELL_REFERENCES: usize = 4;
+const MAX_CELL_BYTES: usize = MAX_CELL_BITS / 8;
+
+/// Layout of bytes spilled to continuation cells by `CellBuilder::store_slice_auto`.
+///
+/// Both layouts are chains, where each cell refers to the next one, so they are readable as
+/// snake data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SpillPolicy {
+    /// Each continuation cell is filled up, the last one takes the rest.
+    #[default]
+    Snake,
+    /// Bytes are spread evenly over the continuation cells.
+    Balanced,
+}
+
+/// State of `CellBuilder`, which it can be rolled back to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellBuilderCheckpoint {
+    bits: usize,
+    references: usize,
+}
 
 pub struct CellBuilder {
     bit_writer: BitWriter<Vec<u8>, BigEndian>,
@@ -163,6 +184,47 @@ impl CellBuilder {
         Ok(self)
     }
 
+    /// Stores as many bytes as fit and the rest in a chain of continuation cells, referenced
+    /// by the next reference of this builder.
+    pub fn store_slice_auto(
+        &mut self,
+        slice: &[u8],
+        policy: SpillPolicy,
+    ) -> Result<&mut Self, TonCellError> {
+        let inline_len = self.remaining_bits() / 8;
+        if slice.len() <= inline_len {
+            return self.store_slice(slice);
+        }
+        if self.remaining_refs() == 0 {
+            return Err(TonCellError::cell_builder_error(format!(
+                "No reference left to spill {} bytes",
+                slice.len() - inline_len
+            )));
+        }
+        let (head, tail) = slice.split_at(inline_len);
+        let chunk_len = match policy {
+            SpillPolicy::Snake => MAX_CELL_BYTES,
+            SpillPolicy::Balanced => {
+                let cell_count = tail.len().div_ceil(MAX_CELL_BYTES);
+                tail.len().div_ceil(cell_count)
+            }
+        };
+        let mut next: Option<ArcCell> = None;
+        for chunk in tail.chunks(chunk_len).rev() {
+            let mut builder = CellBuilder::new();
+            builder.store_slice(chunk)?;
+            if let Some(cell) = &next {
+                builder.store_reference(cell)?;
+            }
+            next = Some(Arc::new(builder.build()?));
+        }
+        self.store_slice(head)?;
+        if let Some(cell) = &next {
+            self.store_reference(cell)?;
+        }
+        Ok(self)
+    }
+
     pub fn store_bits(&mut self, bit_len: usize, slice: &[u8]) -> Result<&mut Self, TonCellError> {
         let full_bytes = bit_len / 8;
         self.store_slice(&slice[0..full_bytes])?;
@@ -178,6 +240,12 @@ impl CellBuilder {
         self.store_slice(val.as_bytes())
     }
 
+    /// Stores the string in snake format: bytes which don't fit go to a chain of continuation
+    /// cells referenced by the next reference. The string must be the last field of the cell.
+    pub fn store_string_snake(&mut self, val: &str) -> Result<&mut Self, TonCellError> {
+        self.store_slice_auto(val.as_bytes(), SpillPolicy::Snake)
+    }
+
     pub fn store_coins(&mut self, val: &BigUint) -> Result<&mut Self, TonCellError> {
         if val.is_zero() {
             self.store_u8(4, 0)
@@ -188,6 +256,29 @@ impl CellBuilder {
         }
     }
 
+    /// Stores `Coins` value of any number type.
+    pub fn store_coins_number<T: BigNumber>(&mut self, val: &T) -> Result<&mut Self, TonCellError> {
+        let num_bytes = val.bits().div_ceil(8);
+        if num_bytes > 15 {
+            return Err(TonCellError::cell_builder_error(format!(
+                "Coins value takes {} bytes, at most 15 bytes are allowed",
+                num_bytes
+            )));
+        }
+        self.store_u8(4, num_bytes as u8)?;
+        self.store_big_number(num_bytes * 8, val)
+    }
+
+    /// Stores unsigned value of any number type in `bit_len` bits.
+    pub fn store_big_number<T: BigNumber>(
+        &mut self,
+        bit_len: usize,
+        val: &T,
+    ) -> Result<&mut Self, TonCellError> {
+        val.store(self, bit_len)?;
+        Ok(self)
+    }
+
     /// Stores address without optimizing hole address
     pub fn store_raw_address(&mut self, val: &TonAddress) -> Result<&mut Self, TonCellError> {
         self.store_u8(2, 0b10u8)?;
@@ -267,7 +358,9 @@ impl CellBuilder {
         &mut self,
         cell: &ArcCell,
     ) -> Result<&mut Self, TonCellError> {
-        if cell.bit_len() < self.remaining_bits() {
+        if cell.bit_len() < self.remaining_bits()
+            && cell.references().len() <= self.remaining_refs()
+        {
             self.store_bit(false)?;
             self.store_cell(cell)?;
         } else {
@@ -294,7 +387,57 @@ impl CellBuilder {
     }
 
     pub fn remaining_bits(&self) -> usize {
-        MAX_CELL_BITS - self.bits_to_write
+        MAX_CELL_BITS.saturating_sub(self.bits_to_write)
+    }
+
+    pub fn remaining_refs(&self) -> usize {
+        MAX_CELL_REFERENCES.saturating_sub(self.references.len())
+    }
+
+    /// Returns current state, e.g. to try a layout and roll it back if it does not fit.
+    pub fn checkpoint(&self) -> CellBuilderCheckpoint {
+        CellBuilderCheckpoint {
+            bits: self.bits_to_write,
+            references: self.references.len(),
+        }
+    }
+
+    /// Drops bits and references stored after the checkpoint.
+    pub fn rollback(
+        &mut self,
+        checkpoint: CellBuilderCheckpoint,
+    ) -> Result<&mut Self, TonCellError> {
+        if checkpoint.bits > self.bits_to_write || checkpoint.references > self.references.len() {
+            return Err(TonCellError::cell_builder_error(format!(
+                "Can't roll back to checkpoint ({} bits, {} refs) after it (builder has {} bits, {} refs)",
+                checkpoint.bits,
+                checkpoint.references,
+                self.bits_to_write,
+                self.references.len()
+            )));
+        }
+        self.truncate_bits(checkpoint.bits)?;
+        self.references.truncate(checkpoint.references);
+        Ok(self)
+    }
+
+    fn truncate_bits(&mut self, bit_len: usize) -> Result<(), TonCellError> {
+        let empty = BitWriter::endian(Vec::new(), BigEndian);
+        let mut writer = std::mem::replace(&mut self.bit_writer, empty);
+        writer.byte_align().map_cell_builder_error()?;
+        let mut data = writer.into_writer();
+        let partial_byte = data.get(bit_len / 8).copied().unwrap_or(0);
+        data.truncate(bit_len / 8);
+        let mut writer = BitWriter::endian(data, BigEndian);
+        let partial_bits = bit_len % 8;
+        if partial_bits > 0 {
+            writer
+                .write(partial_bits as u32, partial_byte >> (8 - partial_bits))
+                .map_cell_builder_error()?;
+        }
+        self.bit_writer = writer;
+        self.bits_to_write = bit_len;
+        Ok(())
     }
 
     pub fn build(&mut self) -> Result<Cell, TonCellError> {
@@ -372,7 +515,90 @@ mod tests {
 
     use crate::address::TonAddress;
     use crate::cell::builder::extend_and_invert_bits;
-    use crate::cell::{CellBuilder, TonCellError};
+    use crate::cell::{CellBuilder, SpillPolicy, TonCellError};
+
+    #[test]
+    fn test_checkpoint_rollback() -> anyhow::Result<()> {
+        let mut builder = CellBuilder::new();
+        builder.store_u8(3, 0b101)?;
+        let checkpoint = builder.checkpoint();
+        builder
+            .store_u32(32, 0xdeadbeef)?
+            .store_child(CellBuilder::new().build()?)?;
+        assert_eq!(builder.remaining_bits(), 1023 - 35);
+        assert_eq!(builder.remaining_refs(), 3);
+        builder.rollback(checkpoint)?;
+        assert_eq!(builder.remaining_bits(), 1020);
+        assert_eq!(builder.remaining_refs(), 4);
+        assert!(builder
+            .rollback(CellBuilder::new().store_u8(8, 0)?.checkpoint())
+            .is_err());
+        let cell = builder.store_u8(5, 0b11111)?.build()?;
+        let expected = CellBuilder::new()
+            .store_u8(3, 0b101)?
+            .store_u8(5, 0b11111)?
+            .build()?;
+        assert_eq!(cell, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_string_snake() -> anyhow::Result<()> {
+        let text = "snake ".repeat(50);
+        let owner = TonAddress::new(0, &[4; 32]);
+        let cell = CellBuilder::new()
+            .store_u32(32, 0)?
+            .store_address(&owner)?
+            .store_coins(&BigUint::from(1_000_000_000u32))?
+            .store_string_snake(&text)?
+            .build()?;
+        assert_eq!(cell.references.len(), 1);
+
+        let mut parser = cell.parser();
+        assert_eq!(parser.load_u32(32)?, 0);
+        assert_eq!(parser.load_address()?, owner);
+        assert_eq!(parser.load_coins()?, BigUint::from(1_000_000_000u32));
+        assert_eq!(parser.load_string_snake()?, text);
+
+        let cell = CellBuilder::new().store_string_snake("short")?.build()?;
+        assert_eq!(cell.parser().load_string_snake()?, "short");
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_slice_auto() -> anyhow::Result<()> {
+        let text = "0123456789".repeat(30);
+        let cell = CellBuilder::new()
+            .store_u8(8, 0)?
+            .store_slice_auto(text.as_bytes(), SpillPolicy::Snake)?
+            .build()?;
+        assert_eq!(cell.data.len(), 127);
+        assert_eq!(cell.reference(0)?.data.len(), 127);
+        assert_eq!(cell.reference(0)?.reference(0)?.data.len(), 47);
+        assert_eq!(cell.load_snake_formatted_string()?, text);
+
+        let cell = CellBuilder::new()
+            .store_u8(8, 0)?
+            .store_slice_auto(text.as_bytes(), SpillPolicy::Balanced)?
+            .build()?;
+        assert_eq!(cell.reference(0)?.data.len(), 87);
+        assert_eq!(cell.reference(0)?.reference(0)?.data.len(), 87);
+        assert_eq!(cell.load_snake_formatted_string()?, text);
+
+        let cell = CellBuilder::new()
+            .store_slice_auto(b"short", SpillPolicy::Snake)?
+            .build()?;
+        assert!(cell.references.is_empty());
+
+        let mut builder = CellBuilder::new();
+        for _ in 0..4 {
+            builder.store_child(CellBuilder::new().build()?)?;
+        }
+        assert!(builder
+            .store_slice_auto(text.as_bytes(), SpillPolicy::Snake)
+            .is_err());
+        Ok(())
+    }
 
     #[test]
     fn test_extend_and_invert_bits() -> Result<(), TonCellError> {
@@ -0,0 +1,113 @@
+use crate::cell::{ArcCell, BagOfCells, TonCellError};
+
+const BOC_MAGIC: [u8; 4] = [0xb5, 0xee, 0x9c, 0x72];
+
+/// Embeds a bag of cells file into the binary and returns
+/// `Result<&'static ArcCell, TonCellError>` with its root.
+///
+/// The file may contain either raw BoC bytes or base64 encoded BoC (as `resources/wallet/*.code`).
+/// The path is resolved relative to the current file, as in `include_bytes!`.
+/// If the expected hex hash is given, its format is checked at compile time. The cell is parsed
+/// and its root hash is verified on first access, the result is cached.
+///
+/// ```ignore
+/// let code: &'static ArcCell = include_boc!(
+///     "../resources/wallet/wallet_v4r2.code",
+///     "feb5ff6820e2ff0d9483e7e0d62c817d846789fb4ae580c878866d959dabd5c0"
+/// )?;
+/// ```
+#[macro_export]
+macro_rules! include_boc {
+    ($path:literal) => {
+        $crate::include_boc!(@embed $path, ::std::option::Option::None)
+    };
+    ($path:literal, $hash:literal) => {{
+        const _: () = ::std::assert!(
+            $crate::cell::is_hex_cell_hash($hash),
+            "Cell hash must be 64 hex chars"
+        );
+        $crate::include_boc!(@embed $path, ::std::option::Option::Some($hash))
+    }};
+    (@embed $path:literal, $hash:expr) => {{
+        static CELL: ::std::sync::OnceLock<
+            ::std::result::Result<$crate::cell::ArcCell, ::std::string::String>,
+        > = ::std::sync::OnceLock::new();
+        CELL.get_or_init(|| {
+            $crate::cell::parse_embedded_boc(::std::include_bytes!($path), $hash)
+                .map_err(|e| ::std::format!("Invalid embedded BoC {}: {}", $path, e))
+        })
+        .as_ref()
+        .map_err(|e| $crate::cell::TonCellError::BagOfCellsDeserializationError(e.clone()))
+    }};
+}
+
+/// Checks that the string is a hex encoded cell hash. Used by `include_boc!` at compile time.
+pub const fn is_hex_cell_hash(hash: &str) -> bool {
+    let bytes = hash.as_bytes();
+    if bytes.len() != 64 {
+        return false;
+    }
+    let mut i = 0;
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_hexdigit() {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Parses the root cell of raw or base64 encoded BoC and verifies its hash. Used by `include_boc!`.
+pub fn parse_embedded_boc(
+    boc: &[u8],
+    expected_hash: Option<&str>,
+) -> Result<ArcCell, TonCellError> {
+    let boc = if boc.starts_with(&BOC_MAGIC) {
+        BagOfCells::parse(boc)?
+    } else {
+        let base64 = std::str::from_utf8(boc)
+            .map_err(|e| TonCellError::BagOfCellsDeserializationError(e.to_string()))?;
+        BagOfCells::parse_base64(base64.trim())?
+    };
+    let root = boc.single_root()?.clone();
+    if let Some(expected_hash) = expected_hash {
+        let hash = hex::encode(root.cell_hash());
+        if !hash.eq_ignore_ascii_case(expected_hash) {
+            return Err(TonCellError::BagOfCellsDeserializationError(format!(
+                "Unexpected root cell hash (expected: {}, actual: {})",
+                expected_hash, hash
+            )));
+        }
+    }
+    Ok(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cell::{is_hex_cell_hash, parse_embedded_boc, BagOfCells, TonCellError};
+
+    #[test]
+    fn test_include_boc() -> anyhow::Result<()> {
+        let code = crate::include_boc!(
+            "../../resources/wallet/wallet_v4r2.code",
+            "feb5ff6820e2ff0d9483e7e0d62c817d846789fb4ae580c878866d959dabd5c0"
+        )?;
+        let boc =
+            BagOfCells::parse_base64(include_str!("../../resources/wallet/wallet_v4r2.code"))?;
+        assert_eq!(code, boc.single_root()?);
+        let mismatch = crate::include_boc!(
+            "../../resources/wallet/wallet_v4r2.code",
+            "0000000000000000000000000000000000000000000000000000000000000000"
+        );
+        assert!(matches!(
+            mismatch,
+            Err(TonCellError::BagOfCellsDeserializationError(_))
+        ));
+        assert!(is_hex_cell_hash(&hex::encode([0xab; 32])));
+        assert!(!is_hex_cell_hash(&"x".repeat(64)));
+        let raw = boc.serialize(true)?;
+        assert_eq!(&parse_embedded_boc(&raw, None)?, code);
+        assert!(parse_embedded_boc(&raw, Some(&hex::encode([0u8; 32]))).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+use std::sync::OnceLock;
+
+use sha2::{Digest, Sha256};
+
+use crate::types::TonHash;
+
+/// SHA-256 implementation used for cell hashing.
+pub trait CellHasher: Send + Sync {
+    fn sha256(&self, data: &[u8]) -> TonHash;
+}
+
+/// Default hasher based on `sha2` crate. Enable `sha2_asm` feature to use its assembly backend.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sha2Hasher;
+
+impl CellHasher for Sha2Hasher {
+    fn sha256(&self, data: &[u8]) -> TonHash {
+        Sha256::digest(data).into()
+    }
+}
+
+#[cfg(feature = "ring_hasher")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RingHasher;
+
+#[cfg(feature = "ring_hasher")]
+impl CellHasher for RingHasher {
+    fn sha256(&self, data: &[u8]) -> TonHash {
+        let mut hash = [0; 32];
+        hash.copy_from_slice(ring::digest::digest(&ring::digest::SHA256, data).as_ref());
+        hash
+    }
+}
+
+#[cfg(feature = "openssl_hasher")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OpensslHasher;
+
+#[cfg(feature = "openssl_hasher")]
+impl CellHasher for OpensslHasher {
+    fn sha256(&self, data: &[u8]) -> TonHash {
+        openssl::sha::sha256(data)
+    }
+}
+
+static CELL_HASHER: OnceLock<Box<dyn CellHasher>> = OnceLock::new();
+
+/// Sets hasher used for all cells. Must be called before the first cell is hashed,
+/// returns the hasher back if another one is already in use.
+pub fn set_cell_hasher(hasher: Box<dyn CellHasher>) -> Result<(), Box<dyn CellHasher>> {
+    CELL_HASHER.set(hasher)
+}
+
+pub(crate) fn cell_hasher() -> &'static dyn CellHasher {
+    CELL_HASHER.get_or_init(|| Box::new(Sha2Hasher)).as_ref()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cell::{CellHasher, Sha2Hasher};
+
+    const ABC_HASH: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    #[test]
+    fn test_sha2_hasher() {
+        assert_eq!(hex::encode(Sha2Hasher.sha256(b"abc")), ABC_HASH);
+    }
+
+    #[cfg(feature = "ring_hasher")]
+    #[test]
+    fn test_ring_hasher() {
+        assert_eq!(
+            hex::encode(crate::cell::RingHasher.sha256(b"abc")),
+            ABC_HASH
+        );
+    }
+
+    #[cfg(feature = "openssl_hasher")]
+    #[test]
+    fn test_openssl_hasher() {
+        assert_eq!(
+            hex::encode(crate::cell::OpensslHasher.sha256(b"abc")),
+            ABC_HASH
+        );
+    }
+}
@@ -8,7 +8,7 @@ use num_traits::identities::Zero;
 use super::{ArcCell, Cell};
 use crate::address::TonAddress;
 use crate::cell::util::*;
-use crate::cell::{MapTonCellError, TonCellError};
+use crate::cell::{BigNumber, MapTonCellError, TonCellError};
 
 pub struct CellParser<'a> {
     pub(crate) bit_len: usize,
@@ -38,6 +38,11 @@ impl<'a> CellParser<'a> {
         }
     }
 
+    /// Returns number of references not loaded yet.
+    pub fn remaining_refs(&self) -> usize {
+        self.references.len() - self.next_ref
+    }
+
     /// Return number of full bytes remaining
     pub fn remaining_bytes(&mut self) -> usize {
         self.remaining_bits() / 8
@@ -82,6 +87,9 @@ impl<'a> CellParser<'a> {
 
     pub fn load_uint(&mut self, bit_len: usize) -> Result<BigUint, TonCellError> {
         self.ensure_enough_bits(bit_len)?;
+        if bit_len == 0 {
+            return Ok(BigUint::default());
+        }
         let num_words = (bit_len + 31) / 32;
         let high_word_bits = if bit_len % 32 == 0 { 32 } else { bit_len % 32 };
         let mut words: Vec<u32> = vec![0_u32; num_words];
@@ -97,6 +105,9 @@ impl<'a> CellParser<'a> {
 
     pub fn load_int(&mut self, bit_len: usize) -> Result<BigInt, TonCellError> {
         self.ensure_enough_bits(bit_len)?;
+        if bit_len == 0 {
+            return Ok(BigInt::default());
+        }
         let num_words = (bit_len + 31) / 32;
         let high_word_bits = if bit_len % 32 == 0 { 32 } else { bit_len % 32 };
         let mut words: Vec<u32> = vec![0_u32; num_words];
@@ -152,6 +163,24 @@ impl<'a> CellParser<'a> {
         String::from_utf8(bytes).map_cell_parser_error()
     }
 
+    /// Loads the rest of the cell and the chain of continuation cells, referenced by the next
+    /// reference, as a string stored by `CellBuilder::store_string_snake`.
+    pub fn load_string_snake(&mut self) -> Result<String, TonCellError> {
+        let remaining_bits = self.remaining_bits();
+        if remaining_bits % 8 != 0 {
+            return Err(TonCellError::CellParserError(format!(
+                "Invalid snake format string: {} bits is not a whole number of bytes",
+                remaining_bits
+            )));
+        }
+        let mut bytes = self.load_bytes(remaining_bits / 8)?;
+        if self.remaining_refs() > 0 {
+            self.next_reference()?
+                .parse_snake_chain(&mut bytes, false)?;
+        }
+        String::from_utf8(bytes).map_cell_parser_error()
+    }
+
     pub fn load_coins(&mut self) -> Result<BigUint, TonCellError> {
         let num_bytes = self.load_u8(4)?;
         if num_bytes == 0 {
@@ -161,6 +190,17 @@ impl<'a> CellParser<'a> {
         }
     }
 
+    /// Loads `Coins` value as any number type, failing if it does not fit.
+    pub fn load_coins_number<T: BigNumber>(&mut self) -> Result<T, TonCellError> {
+        let num_bytes = self.load_u8(4)?;
+        self.load_big_number(num_bytes as usize * 8)
+    }
+
+    /// Loads unsigned value of `bit_len` bits as any number type, failing if it does not fit.
+    pub fn load_big_number<T: BigNumber>(&mut self, bit_len: usize) -> Result<T, TonCellError> {
+        T::load(self, bit_len)
+    }
+
     pub fn load_address(&mut self) -> Result<TonAddress, TonCellError> {
         self.ensure_enough_bits(2)?;
         let tp = self.bit_reader.read::<u8>(2).map_cell_parser_error()?;
@@ -169,7 +209,7 @@ impl<'a> CellParser<'a> {
             2 => {
                 self.ensure_enough_bits(1 + 8 + 32 * 8)?;
                 let _res1 = self.bit_reader.read::<u8>(1).map_cell_parser_error()?;
-                let wc = self.bit_reader.read::<u8>(8).map_cell_parser_error()?;
+                let wc = self.bit_reader.read::<u8>(8).map_cell_parser_error()? as i8;
                 let mut hash_part = [0_u8; 32];
                 self.bit_reader
                     .read_bytes(&mut hash_part)
@@ -191,7 +231,7 @@ impl<'a> CellParser<'a> {
 
     pub fn ensure_empty(&mut self) -> Result<(), TonCellError> {
         let remaining_bits = self.remaining_bits();
-        let remaining_refs = self.references.len() - self.next_ref;
+        let remaining_refs = self.remaining_refs();
         // if remaining_bits == 0 && remaining_refs == 0 { // todo: We will restore reference checking in in 0.18
         if remaining_bits == 0 {
             Ok(())
@@ -203,6 +243,20 @@ impl<'a> CellParser<'a> {
         }
     }
 
+    /// Same as `ensure_empty`, but also fails if some references are not loaded.
+    pub fn ensure_fully_consumed(&mut self) -> Result<(), TonCellError> {
+        let remaining_bits = self.remaining_bits();
+        let remaining_refs = self.remaining_refs();
+        if remaining_bits == 0 && remaining_refs == 0 {
+            Ok(())
+        } else {
+            Err(TonCellError::NonEmptyReader {
+                remaining_bits,
+                remaining_refs,
+            })
+        }
+    }
+
     pub fn skip_bits(&mut self, num_bits: usize) -> Result<(), TonCellError> {
         self.ensure_enough_bits(num_bits)?;
         self.bit_reader
@@ -266,10 +320,12 @@ impl<'a> CellParser<'a> {
 #[cfg(test)]
 mod tests {
 
+    use std::sync::Arc;
+
     use num_bigint::{BigInt, BigUint};
 
     use crate::address::TonAddress;
-    use crate::cell::Cell;
+    use crate::cell::{Cell, CellBuilder, TonCellError};
 
     #[test]
     fn test_load_bit() {
@@ -282,6 +338,15 @@ mod tests {
         assert!(parser.load_bit().is_err());
     }
 
+    #[test]
+    fn test_load_zero_bits() {
+        let cell = Cell::new([0b10101010].to_vec(), 4, vec![], false).unwrap();
+        let mut parser = cell.parser();
+        assert_eq!(parser.load_uint(0).unwrap(), BigUint::from(0u32));
+        assert_eq!(parser.load_int(0).unwrap(), BigInt::from(0));
+        assert_eq!(parser.remaining_bits(), 4);
+    }
+
     #[test]
     fn test_load_u8() {
         let cell = Cell::new([0b10101010].to_vec(), 4, vec![], false).unwrap();
@@ -509,6 +574,17 @@ mod tests {
         assert!(parser.load_address().is_err());
     }
 
+    #[test]
+    fn test_load_masterchain_address() {
+        let mut builder = CellBuilder::new();
+        builder.store_address(&TonAddress::ELECTOR).unwrap();
+        builder.store_address(&TonAddress::MINTER).unwrap();
+        let cell = builder.build().unwrap();
+        let mut parser = cell.parser();
+        assert_eq!(parser.load_address().unwrap(), TonAddress::ELECTOR);
+        assert_eq!(parser.load_address().unwrap(), TonAddress::MINTER);
+    }
+
     #[test]
     fn test_ensure_empty() {
         let cell = Cell::new([0b10101010].to_vec(), 7, vec![], false).unwrap();
@@ -519,6 +595,27 @@ mod tests {
         assert!(parser.ensure_empty().is_ok());
     }
 
+    #[test]
+    fn test_ensure_fully_consumed() {
+        let child = Arc::new(Cell::default());
+        let cell = Cell::new([0b10100000].to_vec(), 3, vec![child], false).unwrap();
+        let mut parser = cell.parser();
+        assert_eq!(parser.remaining_bits(), 3);
+        assert_eq!(parser.remaining_refs(), 1);
+        parser.skip_bits(3).unwrap();
+        assert!(parser.ensure_empty().is_ok());
+        assert!(matches!(
+            parser.ensure_fully_consumed(),
+            Err(TonCellError::NonEmptyReader {
+                remaining_bits: 0,
+                remaining_refs: 1
+            })
+        ));
+        parser.next_reference().unwrap();
+        assert_eq!(parser.remaining_refs(), 0);
+        assert!(parser.ensure_fully_consumed().is_ok());
+    }
+
     #[test]
     fn test_skip_bits_not_enough_bits() {
         let cell = Cell::new([0b11111001, 0b00001010].to_vec(), 12, vec![], false).unwrap();
@@ -92,10 +92,26 @@ impl RawBagOfCells {
         let _absent = read_var_size(&mut reader, size)?;
         //   tot_cells_size:(##(off_bytes * 8))
         let _tot_cells_size = read_var_size(&mut reader, off_bytes)?;
+        // each cell takes at least 2 bytes, so bigger counts can't be valid
+        if roots == 0 || roots > cells || cells > serial.len() / 2 {
+            return Err(TonCellError::boc_deserialization_error(format!(
+                "Invalid cells count {} or roots count {} for {} bytes",
+                cells,
+                roots,
+                serial.len()
+            )));
+        }
         //   root_list:(roots * ##(size * 8))
         let mut root_list = vec![];
         for _ in 0..roots {
-            root_list.push(read_var_size(&mut reader, size)?)
+            let root = read_var_size(&mut reader, size)?;
+            if root >= cells {
+                return Err(TonCellError::boc_deserialization_error(format!(
+                    "Root index {} is out of range, cells count: {}",
+                    root, cells
+                )));
+            }
+            root_list.push(root)
         }
         //   index:has_idx?(cells * ##(off_bytes * 8))
         let mut index = vec![];
@@ -109,6 +125,12 @@ impl RawBagOfCells {
 
         for _ in 0..cells {
             let cell = read_cell(&mut reader, size)?;
+            if let Some(reference) = cell.references.iter().find(|r| **r >= cells) {
+                return Err(TonCellError::boc_deserialization_error(format!(
+                    "Reference index {} is out of range, cells count: {}",
+                    reference, cells
+                )));
+            }
             cell_vec.push(cell);
         }
         //   crc32c:has_crc32c?uint32
@@ -220,6 +242,12 @@ fn read_cell(
     let d2 = reader.read::<u8>().map_boc_deserialization_error()?;
 
     let ref_num = d1 & 0b111;
+    if ref_num > 4 {
+        return Err(TonCellError::boc_deserialization_error(format!(
+            "Cell can't have more than 4 references, got {}",
+            ref_num
+        )));
+    }
     let is_exotic = (d1 & 0b1000) != 0;
     let has_hashes = (d1 & 0b10000) != 0;
     let level_mask = (d1 >> 5) as u32;
@@ -339,4 +367,21 @@ mod tests {
         };
         let _res = assert_ok!(raw_bag.serialize(false));
     }
+
+    #[test]
+    fn test_raw_bag_parse_invalid_indices() {
+        let raw_bag = RawBagOfCells {
+            cells: vec![RawCell::new(vec![1], 8, vec![5], 255, false)],
+            roots: vec![0],
+        };
+        let serial = assert_ok!(raw_bag.serialize(false));
+        assert!(RawBagOfCells::parse(&serial).is_err());
+
+        let raw_bag = RawBagOfCells {
+            cells: vec![RawCell::new(vec![1], 8, vec![], 255, false)],
+            roots: vec![3],
+        };
+        let serial = assert_ok!(raw_bag.serialize(false));
+        assert!(RawBagOfCells::parse(&serial).is_err());
+    }
 }
@@ -1,35 +1,87 @@
+use std::collections::{BTreeSet, HashMap};
 use std::fs;
+use std::future::Future;
 use std::ops::Deref;
-use std::path::Path;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicUsize, Ordering};
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard, Weak};
 use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+pub use account_filter::*;
+pub use account_stream::*;
+pub use archive_routing::*;
 use async_trait::async_trait;
+pub use autoscaling::*;
 pub use block_functions::*;
+pub use block_header::*;
 pub use block_stream::*;
+pub use broadcast::*;
 pub use builder::*;
 pub use callback::*;
+pub use capabilities::*;
+pub use chain_tracker::*;
 pub use connection::*;
+pub use doctor::*;
 pub use error::*;
+pub use failover::*;
+pub use health::*;
 pub use interface::*;
+pub use masterchain_info::*;
+pub use message_functions::*;
+pub use middleware::*;
+pub use polling::*;
+pub use quota::*;
 use rand::Rng;
+pub use retry::*;
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "tracing")]
+use spans::call_span;
 use tokio::sync::Mutex;
-use tokio_retry::strategy::FixedInterval;
 use tokio_retry::RetryIf;
+pub use transaction_history::*;
 pub use types::*;
+pub use watch_set::*;
 
+use crate::message::ExtMessageHashes;
+#[cfg(feature = "metrics")]
+use crate::metrics::{
+    MetricsRegistry, METRIC_POOL_IN_FLIGHT, METRIC_POOL_SIZE, METRIC_REQUESTS_TOTAL,
+    METRIC_REQUEST_DURATION_SECONDS, METRIC_REQUEST_ERRORS_TOTAL,
+};
 use crate::tl::*;
+use crate::types::WithErrorContext;
 
+mod account_filter;
+mod account_stream;
+mod archive_routing;
+mod autoscaling;
 mod block_functions;
+mod block_header;
 mod block_stream;
+mod broadcast;
 mod builder;
 mod callback;
+mod capabilities;
+mod chain_tracker;
 mod connection;
+mod doctor;
 mod error;
+mod failover;
+mod health;
 mod interface;
+mod masterchain_info;
+mod message_functions;
+mod middleware;
+mod polling;
+mod quota;
+mod retry;
+#[cfg(feature = "tracing")]
+mod spans;
 
+mod transaction_history;
 mod types;
+mod watch_set;
 
 /// Check on perform upon connection
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
@@ -39,17 +91,58 @@ pub enum ConnectionCheck {
     None,
     /// Verify node aliveness
     Health,
-    /// Verify that connected to archive node
+    /// Verify that connected to archive node. To mix archive and regular connections in
+    /// one pool, see `ArchiveRouting`.
     Archive,
 }
 
+/// When pool connections are established.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionMode {
+    /// Connect on first use of the pool member.
+    #[default]
+    Lazy,
+    /// Connect and sync all pool members when the client is built.
+    Eager,
+}
+
+/// Optional pool features, set by `TonClientBuilder`.
+#[derive(Default)]
+pub(crate) struct PoolOptions {
+    pub(crate) autoscaling: Option<PoolAutoscaling>,
+    pub(crate) quotas: HashMap<CostClass, Quota>,
+    pub(crate) middlewares: Vec<Arc<dyn TonMiddleware>>,
+    pub(crate) health_check_interval: Option<Duration>,
+    pub(crate) retry_policy: Option<RetryPolicy>,
+    pub(crate) failover_configs: Vec<String>,
+    pub(crate) failover_strategy: Option<Arc<dyn FailoverStrategy>>,
+    pub(crate) archive_routing: Option<ArchiveRouting>,
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics: Option<Arc<dyn MetricsRegistry>>,
+}
+
 pub struct TonClient {
     inner: Arc<Inner>,
+    cost_class: Option<CostClass>,
 }
 
 struct Inner {
-    retry_strategy: RetryStrategy,
-    connections: Vec<PoolConnection>,
+    retry_policy: RetryPolicy,
+    params: TonConnectionParams,
+    callback: Arc<dyn TonConnectionCallback>,
+    connection_check: ConnectionCheck,
+    autoscaling: Option<PoolAutoscaling>,
+    quotas: QuotaManager,
+    middlewares: Vec<Arc<dyn TonMiddleware>>,
+    connections: RwLock<Vec<Arc<PoolConnection>>>,
+    keystore_slots: Arc<KeystoreSlots>,
+    failover: Arc<Failover>,
+    archive_routing: Option<ArchiveRouting>,
+    /// Last masterchain seqno seen in responses and health checks, zero if unknown.
+    last_mc_seqno: AtomicI32,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Arc<dyn MetricsRegistry>>,
 }
 
 impl TonClient {
@@ -61,33 +154,84 @@ impl TonClient {
         callback: Arc<dyn TonConnectionCallback>,
         connection_check: ConnectionCheck,
     ) -> Result<TonClient, TonClientError> {
-        let mut connections = Vec::with_capacity(pool_size);
-        for i in 0..pool_size {
-            let mut p = params.clone();
-            if let Some(dir) = &params.keystore_dir {
-                let keystore_prefix = Path::new(dir.as_str());
-                let keystore_dir = keystore_prefix.join(format!("{}", i));
-                fs::create_dir_all(&keystore_dir)?;
-                let path_str = keystore_dir.into_os_string().into_string().map_err(|_| {
-                    TonClientError::InternalError("Error constructing keystore path".to_string())
-                })?;
-                p.keystore_dir = Some(path_str)
-            };
-            let entry = PoolConnection {
-                params: p,
-                callback: callback.clone(),
-                conn: Mutex::new(None),
-                connection_check: connection_check.clone(),
-            };
-            connections.push(entry);
+        Self::new_with_pool_options(
+            pool_size,
+            params,
+            retry_strategy,
+            callback,
+            connection_check,
+            &PoolOptions::default(),
+        )
+    }
+
+    /// Creates a new TonClient. If autoscaling is set, the initial pool size is taken from it
+    /// and `pool_size` is ignored.
+    pub(crate) fn new_with_pool_options(
+        pool_size: usize,
+        params: &TonConnectionParams,
+        retry_strategy: &RetryStrategy,
+        callback: Arc<dyn TonConnectionCallback>,
+        connection_check: ConnectionCheck,
+        options: &PoolOptions,
+    ) -> Result<TonClient, TonClientError> {
+        let autoscaling = options.autoscaling.clone();
+        if let Some(autoscaling) = &autoscaling {
+            if autoscaling.max_size < autoscaling.initial_size() {
+                return Err(TonClientError::InternalError(format!(
+                    "Invalid pool autoscaling: max_size {} is less than min_size {}",
+                    autoscaling.max_size,
+                    autoscaling.initial_size()
+                )));
+            }
         }
+        let pool_size = autoscaling
+            .as_ref()
+            .map(|a| a.initial_size())
+            .unwrap_or(pool_size);
+        let retry_policy = options
+            .retry_policy
+            .clone()
+            .unwrap_or_else(|| RetryPolicy::from(retry_strategy));
+        let configs = std::iter::once(params.config.clone())
+            .chain(options.failover_configs.iter().cloned())
+            .collect();
+        let failover_strategy = options
+            .failover_strategy
+            .clone()
+            .unwrap_or_else(|| Arc::new(RoundRobinFailover::default()));
         let inner = Inner {
-            retry_strategy: retry_strategy.clone(),
-            connections,
+            retry_policy,
+            params: params.clone(),
+            callback,
+            connection_check,
+            autoscaling,
+            quotas: QuotaManager::new(&options.quotas),
+            middlewares: options.middlewares.clone(),
+            connections: RwLock::new(Vec::with_capacity(pool_size)),
+            keystore_slots: Arc::new(KeystoreSlots::default()),
+            failover: Arc::new(Failover::new(configs, failover_strategy)),
+            archive_routing: options.archive_routing.clone(),
+            last_mc_seqno: AtomicI32::new(0),
+            #[cfg(feature = "metrics")]
+            metrics: options.metrics.clone(),
         };
-        Ok(TonClient {
+        let client = TonClient {
             inner: Arc::new(inner),
-        })
+            cost_class: None,
+        };
+        let archive_connections = client
+            .inner
+            .archive_routing
+            .as_ref()
+            .map_or(0, |r| r.archive_connections);
+        for i in 0..pool_size {
+            let entry = client.new_pool_connection(i < archive_connections)?;
+            client.write_connections().push(entry);
+        }
+        if let Some(interval) = options.health_check_interval {
+            spawn_health_check(Arc::downgrade(&client.inner), interval);
+        }
+        Ok(client)
     }
 
     pub fn builder() -> TonClientBuilder {
@@ -103,33 +247,320 @@ impl TonClient {
         &self,
         function: &TonFunction,
     ) -> Result<(TonConnection, TonResult), TonClientError> {
-        let fi = FixedInterval::from_millis(self.inner.retry_strategy.interval_ms);
-        let strategy = fi.take(self.inner.retry_strategy.max_retries);
-        let result = RetryIf::spawn(strategy, || self.do_invoke(function), retry_condition).await;
+        let policy = &self.inner.retry_policy;
+        #[cfg(feature = "metrics")]
+        let start = Instant::now();
+        let archive = self.routes_to_archive(function);
+        let fallback = !archive
+            && self.inner.archive_routing.is_some()
+            && ArchiveRouting::may_need_archive(function);
+        let result = invoke_with_archive_fallback(policy, archive, fallback, |archive| {
+            self.do_invoke(function, archive)
+        })
+        .await;
+        #[cfg(feature = "metrics")]
+        self.record_invoke_metrics(function, start.elapsed(), result.is_ok());
         result
     }
 
     async fn do_invoke(
         &self,
         function: &TonFunction,
+        archive: bool,
     ) -> Result<(TonConnection, TonResult), TonClientError> {
-        let item = self.random_item();
+        self.autoscale()?;
+        let item = self.select_item(archive);
+        let _in_flight = InFlightGuard::new(&item);
+        #[cfg(feature = "metrics")]
+        self.record_pool_metrics();
         let conn = item.get_connection().await?;
-        let res = conn.invoke(function).await;
+        let start = Instant::now();
+        let res = conn.invoke(function).await.with_connection(conn.tag());
         match res {
-            Ok(result) => Ok((conn, result)),
-            Err(error) => Err(error),
+            Ok(result) => {
+                item.report_success(start.elapsed());
+                if let TonResult::BlocksMasterchainInfo(info) = &result {
+                    self.observe_mc_seqno(info.last.seqno);
+                }
+                Ok((conn, result))
+            }
+            Err(error) => {
+                if self.inner.retry_policy.is_retryable(&error) {
+                    item.report_failure(conn.tag()).await;
+                }
+                Err(error)
+            }
         }
     }
 
-    #[allow(clippy::let_and_return)]
-    fn random_item(&self) -> &PoolConnection {
-        let i = {
-            let mut rng = rand::thread_rng();
-            rng.gen_range(0..self.inner.connections.len())
+    #[cfg(feature = "metrics")]
+    fn record_invoke_metrics(&self, function: &TonFunction, duration: Duration, success: bool) {
+        let Some(metrics) = &self.inner.metrics else {
+            return;
+        };
+        let method: &'static str = function.into();
+        let labels = [("method", method)];
+        metrics.increment_counter(METRIC_REQUESTS_TOTAL, &labels);
+        if !success {
+            metrics.increment_counter(METRIC_REQUEST_ERRORS_TOTAL, &labels);
+        }
+        metrics.observe_histogram(
+            METRIC_REQUEST_DURATION_SECONDS,
+            &labels,
+            duration.as_secs_f64(),
+        );
+    }
+
+    #[cfg(feature = "metrics")]
+    fn record_pool_metrics(&self) {
+        let Some(metrics) = &self.inner.metrics else {
+            return;
+        };
+        let (pool_size, in_flight) = self.pool_load();
+        metrics.set_gauge(METRIC_POOL_SIZE, &[], pool_size as f64);
+        metrics.set_gauge(METRIC_POOL_IN_FLIGHT, &[], in_flight as f64);
+    }
+
+    /// Returns number of connections and number of calls in flight.
+    fn pool_load(&self) -> (usize, usize) {
+        let connections = self.read_connections();
+        let in_flight = connections.iter().map(|c| c.in_flight()).sum::<usize>();
+        (connections.len(), in_flight)
+    }
+
+    /// Returns random pool member, an archive one if `archive` is set and the pool is mixed.
+    fn select_item(&self, archive: bool) -> Arc<PoolConnection> {
+        let connections = self.read_connections();
+        let candidates: Vec<_> = match &self.inner.archive_routing {
+            Some(_) => connections
+                .iter()
+                .filter(|c| c.archive == archive)
+                .collect(),
+            None => vec![],
+        };
+        let mut rng = rand::thread_rng();
+        if candidates.is_empty() {
+            connections[rng.gen_range(0..connections.len())].clone()
+        } else {
+            candidates[rng.gen_range(0..candidates.len())].clone()
+        }
+    }
+
+    /// Checks if the function should go to an archive member of a mixed pool.
+    fn routes_to_archive(&self, function: &TonFunction) -> bool {
+        let Some(routing) = &self.inner.archive_routing else {
+            return false;
+        };
+        let last_mc_seqno = self.inner.last_mc_seqno.load(Ordering::SeqCst);
+        routing.is_historical(function, last_mc_seqno, unix_time())
+    }
+
+    fn observe_mc_seqno(&self, seqno: i32) {
+        self.inner.last_mc_seqno.fetch_max(seqno, Ordering::SeqCst);
+    }
+
+    /// Returns a client sharing the pool, which charges all its calls to the cost class.
+    ///
+    /// By default calls are charged to `CostClass::of(function)`.
+    pub fn with_cost_class(&self, cost_class: CostClass) -> TonClient {
+        TonClient {
+            inner: self.inner.clone(),
+            cost_class: Some(cost_class),
+        }
+    }
+
+    /// Number of calls of the cost class, which can be made now. `None` if the class has no quota.
+    pub fn remaining_quota(&self, cost_class: &CostClass) -> Option<usize> {
+        self.inner.quotas.remaining(cost_class)
+    }
+
+    /// Returns current number of connections in the pool.
+    pub fn pool_size(&self) -> usize {
+        self.read_connections().len()
+    }
+
+    fn read_connections(&self) -> RwLockReadGuard<'_, Vec<Arc<PoolConnection>>> {
+        self.inner
+            .connections
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write_connections(&self) -> RwLockWriteGuard<'_, Vec<Arc<PoolConnection>>> {
+        self.inner
+            .connections
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn new_pool_connection(&self, archive: bool) -> Result<Arc<PoolConnection>, TonClientError> {
+        let params = &self.inner.params;
+        let mut p = params.clone();
+        let mut keystore = None;
+        if let Some(dir) = &params.keystore_dir {
+            let slot = KeystoreSlot::acquire(&self.inner.keystore_slots, Path::new(dir.as_str()));
+            fs::create_dir_all(&slot.dir)?;
+            let path_str = slot
+                .dir
+                .clone()
+                .into_os_string()
+                .into_string()
+                .map_err(|_| {
+                    TonClientError::InternalError("Error constructing keystore path".to_string())
+                })?;
+            p.keystore_dir = Some(path_str);
+            keystore = Some(slot);
         };
-        let entry = &self.inner.connections[i];
-        entry
+        Ok(Arc::new(PoolConnection {
+            params: p,
+            keystore,
+            callback: self.inner.callback.clone(),
+            conn: Mutex::new(None),
+            connection_check: if archive {
+                ConnectionCheck::Archive
+            } else {
+                self.inner.connection_check.clone()
+            },
+            in_flight: AtomicUsize::new(0),
+            last_used: std::sync::Mutex::new(Instant::now()),
+            health: std::sync::Mutex::new(HealthState::default()),
+            failover: self.inner.failover.clone(),
+            endpoint: AtomicUsize::new(0),
+            failures: AtomicUsize::new(0),
+            archive,
+        }))
+    }
+
+    /// Adds a connection if the pool is overloaded or retires one idle connection.
+    fn autoscale(&self) -> Result<(), TonClientError> {
+        let autoscaling = match &self.inner.autoscaling {
+            Some(autoscaling) => autoscaling,
+            None => return Ok(()),
+        };
+        let (pool_size, in_flight) = self.pool_load();
+        if autoscaling.should_scale_up(pool_size, in_flight) {
+            let mut connections = self.write_connections();
+            // another call may have scaled the pool meanwhile
+            if connections.len() < autoscaling.max_size {
+                connections.push(self.new_pool_connection(false)?);
+                log::info!("Pool scaled up to {} connections", connections.len());
+            }
+        } else if pool_size > autoscaling.initial_size() {
+            let candidate = self
+                .read_connections()
+                .iter()
+                .rev()
+                .find(|c| !c.archive && autoscaling.can_retire(pool_size, c.idle_time()))
+                .cloned();
+            let Some(candidate) = candidate else {
+                return Ok(());
+            };
+            let mut connections = self.write_connections();
+            let pool_size = connections.len();
+            let retired = connections.iter().position(|c| {
+                Arc::ptr_eq(c, &candidate) && autoscaling.can_retire(pool_size, c.idle_time())
+            });
+            if let Some(i) = retired {
+                connections.remove(i).retire();
+                log::info!("Pool scaled down to {} connections", connections.len());
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns versions and capabilities of the linked tonlib and liteservers, available on
+    /// every pool member, see `TonCapabilities::common`.
+    ///
+    /// Capabilities are detected per connection and cached until it reconnects.
+    pub async fn capabilities(&self) -> Result<TonCapabilities, TonClientError> {
+        let connections = self.read_connections().clone();
+        let futures = connections.iter().map(|item| item.capabilities());
+        let members = futures::future::try_join_all(futures).await?;
+        TonCapabilities::common(&members)
+            .ok_or_else(|| TonClientError::InternalError("Connection pool is empty".to_string()))
+    }
+
+    /// Probes connected pool members and evicts dead and lagging ones. Evicted members are
+    /// reconnected on the next use.
+    ///
+    /// Called periodically if the health check interval is set in `TonClientBuilder`.
+    pub async fn check_pool_health(&self) {
+        let connections = self.read_connections().clone();
+        let probes = futures::future::join_all(connections.iter().map(|item| item.probe())).await;
+        let best_seqno = probes
+            .iter()
+            .filter_map(|p| p.as_ref().and_then(|(_, seqno)| *seqno))
+            .max()
+            .unwrap_or(0);
+        self.observe_mc_seqno(best_seqno);
+        for (item, probe) in connections.iter().zip(probes) {
+            if let Some((tag, seqno)) = probe {
+                let health = ConnectionHealth::of(seqno, best_seqno);
+                item.set_health(health, seqno);
+                if health.is_evicted() {
+                    log::warn!("Evicting {:?} connection {} from pool", health, tag);
+                    item.failover.report_failure(item.endpoint());
+                    item.evict(&tag).await;
+                }
+            }
+        }
+    }
+
+    /// Sends the external message via every pool member and reports the result of each
+    /// attempt. Unlike `send_raw_message`, failures of some members are not errors, check
+    /// `MessageBroadcast::is_accepted`.
+    pub async fn broadcast_message(&self, boc: &[u8]) -> Result<MessageBroadcast, TonClientError> {
+        let hashes = ExtMessageHashes::parse_boc(boc)
+            .map_err(|e| TonClientError::InternalError(format!("Invalid message: {}", e)))?;
+        let connections = self.read_connections().clone();
+        let attempts =
+            futures::future::join_all(connections.iter().map(|item| item.broadcast(boc))).await;
+        for attempt in attempts.iter().filter(|a| !a.is_accepted()) {
+            log::warn!(
+                "Message {} is not sent via {:?}: {:?}",
+                hex::encode(hashes.hash),
+                attempt.tag,
+                attempt.error
+            );
+        }
+        Ok(MessageBroadcast { hashes, attempts })
+    }
+
+    /// Diagnoses each pool member: its liteserver version, clock skew, head lag and whether
+    /// it keeps archive blocks. Members, which are not connected yet, are connected.
+    ///
+    /// See `ServerDiagnostics::issues` for problems, which may cause stale data.
+    pub async fn doctor(&self) -> Vec<ServerDiagnostics> {
+        let connections = self.read_connections().clone();
+        let mut diagnostics =
+            futures::future::join_all(connections.iter().map(|item| item.diagnose())).await;
+        fill_head_lag(&mut diagnostics);
+        diagnostics
+    }
+
+    /// Returns status of each pool member.
+    pub fn pool_status(&self) -> Vec<PoolConnectionStatus> {
+        self.read_connections()
+            .iter()
+            .map(|item| item.status())
+            .collect()
+    }
+
+    /// Returns status of each config the pool fails over between, the first one is the config
+    /// of `TonConnectionParams`.
+    pub fn failover_status(&self) -> Vec<FailoverEndpointStatus> {
+        self.inner.failover.statuses()
+    }
+
+    /// Connects and syncs all pool members, which are not connected yet.
+    pub async fn warm_up(&self) -> Result<(), TonClientError> {
+        let connections = self.read_connections().clone();
+        let futures = connections.iter().map(|item| async move {
+            let conn = item.get_connection().await?;
+            conn.sync().await.with_connection(conn.tag())
+        });
+        futures::future::try_join_all(futures).await?;
+        Ok(())
     }
 
     pub fn set_log_verbosity_level(verbosity_level: u32) {
@@ -140,7 +571,7 @@ impl TonClient {
 #[async_trait]
 impl TonClientInterface for TonClient {
     async fn get_connection(&self) -> Result<TonConnection, TonClientError> {
-        let item = self.random_item();
+        let item = self.select_item(false);
         let conn = item.get_connection().await?;
         Ok(conn)
     }
@@ -149,7 +580,36 @@ impl TonClientInterface for TonClient {
         &self,
         function: &TonFunction,
     ) -> Result<(TonConnection, TonResult), TonClientError> {
-        self.retrying_invoke(function).await
+        let invoke = self.invoke_on_pool(function);
+        #[cfg(feature = "tracing")]
+        let invoke = tracing::Instrument::instrument(invoke, call_span(function));
+        invoke.await
+    }
+
+    async fn ensure_supported(&self, feature: TonFeature) -> Result<(), TonClientError> {
+        self.capabilities().await?.ensure_supported(feature)
+    }
+}
+
+impl TonClient {
+    async fn invoke_on_pool(
+        &self,
+        function: &TonFunction,
+    ) -> Result<(TonConnection, TonResult), TonClientError> {
+        if !self.inner.quotas.is_empty() {
+            let cost_class = self
+                .cost_class
+                .clone()
+                .unwrap_or_else(|| CostClass::of(function));
+            self.inner.quotas.acquire(&cost_class)?;
+        }
+        if self.inner.middlewares.is_empty() {
+            self.retrying_invoke(function).await
+        } else {
+            Next::new(&self.inner.middlewares, &PoolEndpoint { client: self })
+                .run(function)
+                .await
+        }
     }
 }
 
@@ -157,60 +617,512 @@ impl Clone for TonClient {
     fn clone(&self) -> Self {
         TonClient {
             inner: self.inner.clone(),
+            cost_class: self.cost_class.clone(),
         }
     }
 }
 
-fn maybe_error_code(error: &TonClientError) -> Option<i32> {
-    if let TonClientError::TonlibError { code, .. } = error {
-        Some(*code)
-    } else {
-        None
+/// Checks if the block or the transaction is not found by the liteserver, e.g. since it is
+/// not an archive node.
+/// Invokes the call with retries, repeating it on archive connection if the data is not found
+/// and `fallback` is set.
+async fn invoke_with_archive_fallback<T, F, Fut>(
+    policy: &RetryPolicy,
+    archive: bool,
+    fallback: bool,
+    invoke: F,
+) -> Result<T, TonClientError>
+where
+    F: Fn(bool) -> Fut,
+    Fut: Future<Output = Result<T, TonClientError>>,
+{
+    let result = RetryIf::start(
+        policy.delays(),
+        || invoke(archive),
+        |e: &TonClientError| policy.is_retryable(e),
+    )
+    .await;
+    match result {
+        Err(e) if fallback && is_not_found(&e) => {
+            log::debug!("Repeating call on archive connection after error: {}", e);
+            RetryIf::start(
+                policy.delays(),
+                || invoke(true),
+                |e: &TonClientError| policy.is_retryable(e),
+            )
+            .await
+        }
+        result => result,
     }
 }
 
-fn retry_condition(error: &TonClientError) -> bool {
-    if let Some(code) = maybe_error_code(error) {
-        code == 500
-    } else {
-        false
-    }
+fn is_not_found(error: &TonClientError) -> bool {
+    matches!(
+        error.tonlib_error_kind(),
+        Some(TonlibErrorKind::NotInDb | TonlibErrorKind::BlockNotFound)
+    )
+}
+
+fn spawn_health_check(inner: Weak<Inner>, interval: Duration) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            // the check stops when the client is dropped
+            let client = match inner.upgrade() {
+                Some(inner) => TonClient {
+                    inner,
+                    cost_class: None,
+                },
+                None => break,
+            };
+            client.check_pool_health().await;
+        }
+    });
 }
 
 struct PoolConnection {
     params: TonConnectionParams,
+    keystore: Option<KeystoreSlot>,
     callback: Arc<dyn TonConnectionCallback>,
     conn: Mutex<Option<(TonConnection, JoinHandle<()>)>>,
     connection_check: ConnectionCheck,
+    in_flight: AtomicUsize,
+    last_used: std::sync::Mutex<Instant>,
+    health: std::sync::Mutex<HealthState>,
+    failover: Arc<Failover>,
+    /// Index of the config of the connection in `failover`.
+    endpoint: AtomicUsize,
+    /// Consecutive transient failures of the connection.
+    failures: AtomicUsize,
+    /// Archive member of a mixed pool, see `ArchiveRouting`.
+    archive: bool,
+}
+
+#[derive(Default)]
+struct HealthState {
+    health: ConnectionHealth,
+    mc_seqno: Option<i32>,
+    evictions: usize,
+    /// Capabilities of the current connection, reset on reconnect.
+    capabilities: Option<TonCapabilities>,
+}
+
+/// Numbers of keystore directories `<keystore_dir>/<slot>` used by pool members. The slot of
+/// a dropped member is reused, so the number of directories is bounded by the pool size.
+#[derive(Default)]
+struct KeystoreSlots {
+    used: std::sync::Mutex<BTreeSet<usize>>,
+}
+
+struct KeystoreSlot {
+    slot: usize,
+    dir: PathBuf,
+    slots: Arc<KeystoreSlots>,
+    /// Set when the member is retired by autoscaling, the directory is removed then.
+    retired: AtomicBool,
+}
+
+impl KeystoreSlot {
+    fn acquire(slots: &Arc<KeystoreSlots>, keystore_prefix: &Path) -> KeystoreSlot {
+        let mut used = slots.used.lock().unwrap_or_else(PoisonError::into_inner);
+        let slot = (0..used.len())
+            .find(|i| !used.contains(i))
+            .unwrap_or(used.len());
+        used.insert(slot);
+        KeystoreSlot {
+            slot,
+            dir: keystore_prefix.join(format!("{}", slot)),
+            slots: slots.clone(),
+            retired: AtomicBool::new(false),
+        }
+    }
+}
+
+impl Drop for KeystoreSlot {
+    fn drop(&mut self) {
+        if self.retired.load(Ordering::SeqCst) {
+            if let Err(e) = fs::remove_dir_all(&self.dir) {
+                log::warn!("Failed to remove keystore {}: {}", self.dir.display(), e);
+            }
+        }
+        self.slots
+            .used
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&self.slot);
+    }
 }
 
 impl PoolConnection {
-    async fn get_connection(&self) -> Result<TonConnection, TonClientError> {
+    /// Marks the member removed from the pool, its keystore directory is removed once the
+    /// member is dropped.
+    fn retire(&self) {
+        if let Some(keystore) = &self.keystore {
+            keystore.retired.store(true, Ordering::SeqCst);
+        }
+    }
+
+    fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Time since the last request has finished, zero if there are requests in flight.
+    fn idle_time(&self) -> Duration {
+        if self.in_flight() > 0 {
+            return Duration::ZERO;
+        }
+        self.last_used
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .elapsed()
+    }
+
+    fn health_state(&self) -> std::sync::MutexGuard<'_, HealthState> {
+        self.health.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn set_health(&self, health: ConnectionHealth, mc_seqno: Option<i32>) {
+        let mut state = self.health_state();
+        state.health = health;
+        if mc_seqno.is_some() {
+            state.mc_seqno = mc_seqno;
+        }
+    }
+
+    fn status(&self) -> PoolConnectionStatus {
+        // the lock is held while connecting, so such members are reported as not connected
+        let tag = self
+            .conn
+            .try_lock()
+            .ok()
+            .and_then(|guard| guard.as_ref().map(|(conn, _)| conn.tag().to_string()));
+        let state = self.health_state();
+        PoolConnectionStatus {
+            tag,
+            endpoint: self.endpoint(),
+            health: state.health,
+            mc_seqno: state.mc_seqno,
+            in_flight: self.in_flight(),
+            evictions: state.evictions,
+            archive: self.archive,
+        }
+    }
+
+    /// Returns tag of the connection and its masterchain seqno, or `None` seqno if the
+    /// connection is dead. Members, which are not connected or are connecting, are skipped.
+    async fn probe(&self) -> Option<(String, Option<i32>)> {
+        let (conn, is_dead) = match self.conn.try_lock().ok()?.deref() {
+            Some((conn, join_handle)) => (conn.clone(), join_handle.is_finished()),
+            None => return None,
+        };
+        if is_dead {
+            return Some((conn.tag().to_string(), None));
+        }
+        let probe = async {
+            conn.lite_server_get_info().await?;
+            conn.get_masterchain_info().await
+        };
+        let seqno = match tokio::time::timeout(DEFAULT_HEALTH_CHECK_TIMEOUT, probe).await {
+            Ok(Ok((_, info))) => Some(info.last.seqno),
+            Ok(Err(e)) => {
+                log::info!("Health check of connection {} failed: {}", conn.tag(), e);
+                None
+            }
+            Err(_) => {
+                log::info!("Health check of connection {} timed out", conn.tag());
+                None
+            }
+        };
+        Some((conn.tag().to_string(), seqno))
+    }
+
+    async fn broadcast(&self, boc: &[u8]) -> BroadcastAttempt {
+        let started = Instant::now();
+        let mut attempt = BroadcastAttempt {
+            tag: None,
+            endpoint: self.endpoint(),
+            hash: None,
+            error: None,
+            latency: Duration::ZERO,
+        };
+        let result = match self.get_connection().await {
+            Ok(conn) => {
+                attempt.tag = Some(conn.tag().to_string());
+                attempt.endpoint = self.endpoint();
+                conn.send_raw_message_return_hash(boc).await
+            }
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(hash) => attempt.hash = Some(hash),
+            Err(e) => attempt.error = Some(e.to_string()),
+        }
+        attempt.latency = started.elapsed();
+        attempt
+    }
+
+    async fn capabilities(&self) -> Result<TonCapabilities, TonClientError> {
+        if let Some(capabilities) = &self.health_state().capabilities {
+            return Ok(capabilities.clone());
+        }
+        let conn = self.get_connection().await?;
+        let capabilities = TonCapabilities::detect(&conn).await?;
+        log::info!(
+            "Detected capabilities of {}: {:?}",
+            conn.tag(),
+            capabilities
+        );
+        self.health_state().capabilities = Some(capabilities.clone());
+        Ok(capabilities)
+    }
+
+    async fn diagnose(&self) -> ServerDiagnostics {
+        let mut diagnostics = ServerDiagnostics::new(None, self.endpoint(), self.archive);
+        let conn = match self.get_connection().await {
+            Ok(conn) => conn,
+            Err(e) => {
+                diagnostics.error = Some(e.to_string());
+                return diagnostics;
+            }
+        };
+        diagnostics.tag = Some(conn.tag().to_string());
+        diagnostics.endpoint = self.endpoint();
+        let diagnose = async {
+            let started = Instant::now();
+            let info = conn.lite_server_get_info().await?;
+            diagnostics.latency = Some(started.elapsed());
+            diagnostics.version = Some(info.version);
+            diagnostics.capabilities = Some(info.capabilities);
+            diagnostics.clock_skew = Some(info.now - unix_time());
+            let (_, mc_info) = conn.get_masterchain_info().await?;
+            diagnostics.mc_seqno = Some(mc_info.last.seqno);
+            let header = conn.get_block_header(&mc_info.last).await?;
+            diagnostics.head_age = Some(unix_time() - header.gen_utime);
+            let first_block = BlockId {
+                workchain: -1,
+                shard: i64::MIN,
+                seqno: 1,
+            };
+            diagnostics.has_archive = match conn
+                .lookup_block(LOOKUP_BLOCK_BY_SEQNO, &first_block, 0, 0)
+                .await
+            {
+                Ok(_) => Some(true),
+                Err(e) => match e.tonlib_error_kind() {
+                    Some(TonlibErrorKind::BlockNotFound | TonlibErrorKind::NotInDb) => Some(false),
+                    _ => return Err(e),
+                },
+            };
+            Ok::<_, TonClientError>(())
+        };
+        match tokio::time::timeout(DEFAULT_HEALTH_CHECK_TIMEOUT, diagnose).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => diagnostics.error = Some(e.to_string()),
+            Err(_) => diagnostics.error = Some("diagnostics timed out".to_string()),
+        }
+        diagnostics
+    }
+
+    /// Drops the connection, unless it was already replaced by another one.
+    async fn evict(&self, tag: &str) {
         let mut guard = self.conn.lock().await;
-        match guard.deref() {
-            Some((conn, join_handle)) => {
-                if join_handle.is_finished() {
-                    // TODO: This is temporary implementation.
-                    // At the moment, only report dead connections, in the future need to recover
-                    log::warn!("Returning dead connection: {:?}", conn.tag());
+        let matches = matches!(guard.deref(), Some((conn, _)) if conn.tag() == tag);
+        if matches {
+            *guard = None;
+            self.health_state().evictions += 1;
+        }
+    }
+
+    fn endpoint(&self) -> usize {
+        self.endpoint.load(Ordering::SeqCst)
+    }
+
+    fn report_success(&self, latency: Duration) {
+        self.failures.store(0, Ordering::SeqCst);
+        self.failover.report_success(self.endpoint(), latency);
+    }
+
+    /// Drops the connection after `DEFAULT_FAILOVER_THRESHOLD` consecutive failures, if there
+    /// are other configs to fail over to.
+    async fn report_failure(&self, tag: &str) {
+        let failures = self.failures.fetch_add(1, Ordering::SeqCst) + 1;
+        if failures >= DEFAULT_FAILOVER_THRESHOLD && self.failover.len() > 1 {
+            log::warn!(
+                "Failing over connection {} after {} failures",
+                tag,
+                failures
+            );
+            self.failover.report_failure(self.endpoint());
+            self.failures.store(0, Ordering::SeqCst);
+            self.evict(tag).await;
+        }
+    }
+
+    async fn connect(&self) -> Result<(TonConnection, JoinHandle<()>), TonClientError> {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let (endpoint, config) = self.failover.select();
+            let mut params = self.params.clone();
+            params.config = config.to_string();
+            let result = match self.connection_check {
+                ConnectionCheck::None => {
+                    TonConnection::connect_joinable(&params, self.callback.clone()).await
+                }
+                ConnectionCheck::Health => {
+                    TonConnection::connect_healthy(&params, self.callback.clone()).await
+                }
+                ConnectionCheck::Archive => {
+                    TonConnection::connect_archive(&params, self.callback.clone()).await
                 }
-                Ok(conn.clone())
+            };
+            match result {
+                Ok(conn) => {
+                    self.endpoint.store(endpoint, Ordering::SeqCst);
+                    return Ok(conn);
+                }
+                Err(e) if attempt < self.failover.len() => {
+                    log::warn!("Failed to connect with config {}: {}", endpoint, e);
+                    self.failover.report_failure(endpoint);
+                }
+                Err(e) => {
+                    self.failover.report_failure(endpoint);
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    async fn get_connection(&self) -> Result<TonConnection, TonClientError> {
+        let mut guard = self.conn.lock().await;
+        if let Some((conn, join_handle)) = guard.deref() {
+            if join_handle.is_finished() {
+                log::warn!("Reconnecting dead connection: {:?}", conn.tag());
+                *guard = None;
             }
+        }
+        match guard.deref() {
+            Some((conn, _)) => Ok(conn.clone()),
             None => {
-                let (conn, join_handle) = match self.connection_check {
-                    ConnectionCheck::None => {
-                        TonConnection::connect_joinable(&self.params, self.callback.clone()).await?
-                    }
-                    ConnectionCheck::Health => {
-                        TonConnection::connect_healthy(&self.params, self.callback.clone()).await?
-                    }
-                    ConnectionCheck::Archive => {
-                        TonConnection::connect_archive(&self.params, self.callback.clone()).await?
-                    }
-                };
+                let (conn, join_handle) = self.connect().await?;
                 *guard = Some((conn.clone(), join_handle));
+                {
+                    let mut state = self.health_state();
+                    state.health = ConnectionHealth::Unknown;
+                    state.mc_seqno = None;
+                    state.capabilities = None;
+                }
                 Ok(conn)
             }
         }
     }
 }
+
+fn unix_time() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
+}
+
+struct InFlightGuard<'a> {
+    item: &'a PoolConnection,
+}
+
+impl<'a> InFlightGuard<'a> {
+    fn new(item: &'a PoolConnection) -> InFlightGuard<'a> {
+        item.in_flight.fetch_add(1, Ordering::SeqCst);
+        InFlightGuard { item }
+    }
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        *self
+            .item
+            .last_used
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Instant::now();
+        self.item.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// End of the middleware chain, which invokes the function on the pool.
+struct PoolEndpoint<'a> {
+    client: &'a TonClient,
+}
+
+#[async_trait]
+impl TonClientInterface for PoolEndpoint<'_> {
+    async fn get_connection(&self) -> Result<TonConnection, TonClientError> {
+        self.client.get_connection().await
+    }
+
+    async fn invoke_on_connection(
+        &self,
+        function: &TonFunction,
+    ) -> Result<(TonConnection, TonResult), TonClientError> {
+        self.client.retrying_invoke(function).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    use crate::client::{
+        invoke_with_archive_fallback, KeystoreSlot, KeystoreSlots, RetryPolicy, TonClientError,
+    };
+
+    #[tokio::test]
+    async fn test_not_in_db_falls_back_to_archive_without_retries() {
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_secs(1),
+            ..RetryPolicy::default()
+        };
+        let calls = Mutex::new(vec![]);
+        let started = Instant::now();
+        let result = invoke_with_archive_fallback(&policy, false, true, |archive| {
+            calls.lock().unwrap().push(archive);
+            async move {
+                match archive {
+                    true => Ok(1),
+                    false => Err(TonClientError::tonlib_error(
+                        "blocks.getBlockHeader",
+                        651,
+                        "LITE_SERVER_NOTREADY: block is not in db".to_string(),
+                    )),
+                }
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 1);
+        assert_eq!(*calls.lock().unwrap(), [false, true]);
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_keystore_slots() {
+        let prefix = std::env::temp_dir().join(format!("tonlib-keystore-{}", std::process::id()));
+        let slots = Arc::new(KeystoreSlots::default());
+        let first = KeystoreSlot::acquire(&slots, &prefix);
+        let second = KeystoreSlot::acquire(&slots, &prefix);
+        assert_eq!((first.slot, second.slot), (0, 1));
+        std::fs::create_dir_all(&first.dir).unwrap();
+        std::fs::create_dir_all(&second.dir).unwrap();
+
+        // dropped members keep their directory, retired ones remove it
+        drop(first);
+        assert!(prefix.join("0").exists());
+        second.retired.store(true, Ordering::SeqCst);
+        drop(second);
+        assert!(!prefix.join("1").exists());
+
+        let reused = KeystoreSlot::acquire(&slots, &prefix);
+        assert_eq!(reused.slot, 0);
+        assert_eq!(KeystoreSlot::acquire(&slots, &prefix).slot, 1);
+        drop(reused);
+        std::fs::remove_dir_all(&prefix).unwrap();
+    }
+}
@@ -0,0 +1,79 @@
+use crate::address::TonAddress;
+use crate::client::WatchSet;
+
+/// Filter of accounts applied when fetching shard transactions.
+#[derive(Debug, Clone, Default)]
+pub enum AccountFilter {
+    /// Accepts all accounts.
+    #[default]
+    All,
+    /// Accepts only accounts of the workchain. Shards of other workchains are not requested at all.
+    Workchain(i32),
+    /// Accepts only the watched accounts. Full transactions are fetched only for matching tx ids.
+    Addresses(WatchSet),
+}
+
+impl AccountFilter {
+    /// Checks if any account of the workchain may be accepted.
+    pub fn accepts_workchain(&self, workchain: i32) -> bool {
+        match self {
+            AccountFilter::All => true,
+            AccountFilter::Workchain(wc) => *wc == workchain,
+            AccountFilter::Addresses(watch_set) => {
+                watch_set.addresses().any(|a| a.workchain == workchain)
+            }
+        }
+    }
+
+    /// Checks if the account id (hash part of the address) in the workchain is accepted.
+    pub fn accepts_account(&self, workchain: i32, account: &[u8]) -> bool {
+        match self {
+            AccountFilter::All => true,
+            AccountFilter::Workchain(wc) => *wc == workchain,
+            AccountFilter::Addresses(watch_set) => watch_set.contains_account(workchain, account),
+        }
+    }
+
+    pub fn accepts(&self, address: &TonAddress) -> bool {
+        self.accepts_account(address.workchain, &address.hash_part)
+    }
+}
+
+impl From<WatchSet> for AccountFilter {
+    fn from(watch_set: WatchSet) -> Self {
+        AccountFilter::Addresses(watch_set)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::address::TonAddress;
+    use crate::client::{AccountFilter, WatchSet};
+
+    #[test]
+    fn test_account_filter() {
+        let address = TonAddress::new(0, &[1; 32]);
+        let other = TonAddress::new(0, &[2; 32]);
+        let master = TonAddress::new(-1, &[1; 32]);
+
+        let filter = AccountFilter::All;
+        assert!(filter.accepts_workchain(-1));
+        assert!(filter.accepts(&other));
+
+        let filter = AccountFilter::Workchain(0);
+        assert!(filter.accepts_workchain(0));
+        assert!(!filter.accepts_workchain(-1));
+        assert!(filter.accepts(&other));
+        assert!(!filter.accepts(&master));
+
+        let filter: AccountFilter = vec![address.clone()]
+            .into_iter()
+            .collect::<WatchSet>()
+            .into();
+        assert!(filter.accepts_workchain(0));
+        assert!(!filter.accepts_workchain(-1));
+        assert!(filter.accepts(&address));
+        assert!(!filter.accepts(&other));
+        assert!(!filter.accepts(&master));
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::VecDeque;
+use std::mem;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures::stream::{self, BoxStream};
+use futures::{Stream, StreamExt};
+
+use crate::address::{AddressClassifier, AddressRole, TonAddress};
+use crate::client::{AdaptivePolling, TonClient, TonClientError, TonClientInterface};
+use crate::export::{store_transaction, BocStore};
+use crate::tl::{InternalTransactionId, RawTransaction};
+
+const ACCOUNT_STREAM_POLL_INTERVAL_MS: u64 = 1000;
+const ACCOUNT_STREAM_MAX_POLL_INTERVAL_MS: u64 = 30000;
+const ACCOUNT_STREAM_PAGE_SIZE: usize = 16;
+
+/// Endless stream of new transactions of the account in the order of their logical time.
+///
+/// The account state is polled and transactions after the cursor (the last yielded
+/// transaction) are loaded page by page with `get_raw_transactions_v2`, so every transaction
+/// is yielded exactly once. Errors are logged and the load is retried from the same cursor.
+/// Polling of idle accounts backs off exponentially up to 30 seconds, and returns to every
+/// second once new transactions are found. Transactions initiated by suspicious senders can be
+/// dropped with `with_address_classifier`.
+pub struct AccountTransactionStream {
+    inner: BoxStream<'static, RawTransaction>,
+}
+
+impl AccountTransactionStream {
+    /// Creates the stream of transactions following `after`.
+    pub fn new<C>(
+        client: &C,
+        address: &TonAddress,
+        after: &InternalTransactionId,
+    ) -> AccountTransactionStream
+    where
+        C: TonClientInterface + Clone + 'static,
+    {
+        let cursor = Cursor {
+            client: client.clone(),
+            address: address.clone(),
+            after: after.clone(),
+            pending: VecDeque::new(),
+            polling: AdaptivePolling::new(
+                Duration::from_millis(ACCOUNT_STREAM_POLL_INTERVAL_MS),
+                Duration::from_millis(ACCOUNT_STREAM_MAX_POLL_INTERVAL_MS),
+            ),
+        };
+        let inner = stream::unfold(cursor, |mut cursor| async move {
+            while cursor.pending.is_empty() {
+                if let Err(e) = cursor.load_new().await {
+                    log::warn!(
+                        "[AccountTransactionStream] Failed to load transactions of {}: {}",
+                        cursor.address,
+                        e
+                    );
+                }
+                if cursor.pending.is_empty() {
+                    cursor.polling.on_idle();
+                    cursor.polling.wait().await;
+                } else {
+                    cursor.polling.on_event();
+                }
+            }
+            let tx = cursor.pending.pop_front()?;
+            Some((tx, cursor))
+        });
+        AccountTransactionStream {
+            inner: inner.boxed(),
+        }
+    }
+
+    /// Creates the stream of transactions following the current last transaction of the
+    /// account.
+    pub async fn from_latest<C>(
+        client: &C,
+        address: &TonAddress,
+    ) -> Result<AccountTransactionStream, TonClientError>
+    where
+        C: TonClientInterface + Clone + 'static,
+    {
+        let state = client.get_raw_account_state(address).await?;
+        Ok(Self::new(client, address, &state.last_transaction_id))
+    }
+
+    /// Stores raw transactions before yielding them, see `BocStore`.
+    pub fn with_boc_store(&mut self, boc_store: Arc<dyn BocStore>) -> &mut Self {
+        let inner = mem::replace(&mut self.inner, stream::empty().boxed());
+        self.inner = inner
+            .then(move |tx| {
+                let boc_store = boc_store.clone();
+                async move {
+                    store_transaction(boc_store.as_ref(), &tx).await;
+                    tx
+                }
+            })
+            .boxed();
+        self
+    }
+
+    /// Drops transactions, whose incoming internal message is sent by an account classified as
+    /// suspicious, e.g. spam transfers and notifications of fake jettons.
+    pub fn with_address_classifier(&mut self, classifier: Arc<dyn AddressClassifier>) -> &mut Self {
+        let inner = mem::replace(&mut self.inner, stream::empty().boxed());
+        self.inner = inner
+            .filter(move |tx| {
+                let suspicious = in_msg_source(tx).is_some_and(|source| {
+                    classifier
+                        .classify(&source, AddressRole::Account)
+                        .is_suspicious()
+                });
+                futures::future::ready(!suspicious)
+            })
+            .boxed();
+        self
+    }
+}
+
+/// Returns sender of the incoming internal message of the transaction.
+fn in_msg_source(tx: &RawTransaction) -> Option<TonAddress> {
+    let source = &tx.in_msg.as_ref()?.source.account_address;
+    if source.is_empty() {
+        return None;
+    }
+    source.parse().ok()
+}
+
+impl Stream for AccountTransactionStream {
+    type Item = RawTransaction;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_unpin(cx)
+    }
+}
+
+impl TonClient {
+    /// Returns stream of transactions of the account made after this call.
+    pub async fn subscribe_account(
+        &self,
+        address: &TonAddress,
+    ) -> Result<AccountTransactionStream, TonClientError> {
+        AccountTransactionStream::from_latest(self, address).await
+    }
+
+    /// Returns stream of transactions of the account following `after`, e.g. the last
+    /// transaction processed before restart.
+    pub fn subscribe_account_after(
+        &self,
+        address: &TonAddress,
+        after: &InternalTransactionId,
+    ) -> AccountTransactionStream {
+        AccountTransactionStream::new(self, address, after)
+    }
+}
+
+struct Cursor<C> {
+    client: C,
+    address: TonAddress,
+    after: InternalTransactionId,
+    pending: VecDeque<RawTransaction>,
+    polling: AdaptivePolling,
+}
+
+impl<C: TonClientInterface> Cursor<C> {
+    async fn load_new(&mut self) -> Result<(), TonClientError> {
+        let last = self
+            .client
+            .get_raw_account_state(&self.address)
+            .await?
+            .last_transaction_id;
+        let after = &self.after;
+        if last.lt <= after.lt {
+            return Ok(());
+        }
+
+        let mut loaded = vec![];
+        let mut next = last;
+        'pages: while next.lt > after.lt {
+            let txs = self
+                .client
+                .get_raw_transactions_v2(&self.address, &next, ACCOUNT_STREAM_PAGE_SIZE, false)
+                .await?;
+            for tx in txs.transactions {
+                if tx.transaction_id.lt <= after.lt {
+                    if tx.transaction_id.lt == after.lt && tx.transaction_id.hash != after.hash {
+                        log::warn!(
+                            "[AccountTransactionStream] Transaction {} of {} has unexpected hash",
+                            after.lt,
+                            self.address
+                        );
+                    }
+                    break 'pages;
+                }
+                loaded.push(tx);
+            }
+            next = txs.previous_transaction_id;
+        }
+        loaded.reverse();
+        if let Some(newest) = loaded.last() {
+            self.after = newest.transaction_id.clone();
+        }
+        self.pending.extend(loaded);
+        Ok(())
+    }
+}
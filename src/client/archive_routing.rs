@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::{LOOKUP_BLOCK_BY_SEQNO, LOOKUP_BLOCK_BY_UTIME};
+use crate::tl::TonFunction;
+
+/// Age of blocks, which regular liteservers are not expected to keep.
+pub const DEFAULT_ARCHIVE_ROUTING_MIN_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+/// Expected interval between masterchain blocks, used to convert the age to seqnos.
+const MASTERCHAIN_BLOCK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Mixed pool of regular and archive connections.
+///
+/// The first `archive_connections` pool members are connected with
+/// `ConnectionCheck::Archive`, other members use the check of the builder. Block lookups
+/// older than `min_age` go to archive members. Other queries of blocks and transactions go to
+/// regular members, and are repeated on archive members if the block or the transaction is
+/// not found, since their lt or seqno can't be dated without a request.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ArchiveRouting {
+    pub archive_connections: usize,
+    #[serde(default = "default_min_age")]
+    pub min_age: Duration,
+}
+
+impl ArchiveRouting {
+    pub fn new(archive_connections: usize) -> ArchiveRouting {
+        ArchiveRouting {
+            archive_connections,
+            min_age: DEFAULT_ARCHIVE_ROUTING_MIN_AGE,
+        }
+    }
+
+    /// Checks if the function looks up a block older than `min_age`, given the last known
+    /// masterchain seqno and the current time.
+    pub fn is_historical(&self, function: &TonFunction, last_mc_seqno: i32, now: i64) -> bool {
+        let TonFunction::BlocksLookupBlock {
+            mode, id, utime, ..
+        } = function
+        else {
+            return false;
+        };
+        let min_age = self.min_age.as_secs() as i64;
+        if mode & LOOKUP_BLOCK_BY_UTIME != 0 {
+            return (*utime as i64) < now - min_age;
+        }
+        if mode & LOOKUP_BLOCK_BY_SEQNO != 0 && id.workchain == -1 && last_mc_seqno > 0 {
+            let depth = min_age / MASTERCHAIN_BLOCK_INTERVAL.as_secs() as i64;
+            return (id.seqno as i64) < last_mc_seqno as i64 - depth;
+        }
+        false
+    }
+
+    /// Checks if the function refers to a block or a transaction, which archive members may
+    /// have, if regular members don't.
+    pub fn may_need_archive(function: &TonFunction) -> bool {
+        matches!(
+            function,
+            TonFunction::BlocksLookupBlock { .. }
+                | TonFunction::BlocksGetShards { .. }
+                | TonFunction::BlocksGetTransactions { .. }
+                | TonFunction::BlocksGetTransactionsExt { .. }
+                | TonFunction::GetBlockHeader { .. }
+                | TonFunction::RawGetTransactions { .. }
+                | TonFunction::RawGetTransactionsV2 { .. }
+                | TonFunction::RawGetAccountStateByTransaction { .. }
+                | TonFunction::GetAccountStateByTransaction { .. }
+                | TonFunction::SmcLoadByTransaction { .. }
+        )
+    }
+}
+
+fn default_min_age() -> Duration {
+    DEFAULT_ARCHIVE_ROUTING_MIN_AGE
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::ArchiveRouting;
+    use crate::tl::{BlockId, TonFunction};
+
+    fn lookup(mode: i32, workchain: i32, seqno: i32, utime: i32) -> TonFunction {
+        TonFunction::BlocksLookupBlock {
+            mode,
+            id: BlockId {
+                workchain,
+                shard: i64::MIN,
+                seqno,
+            },
+            lt: 0,
+            utime,
+        }
+    }
+
+    #[test]
+    fn test_archive_routing_is_historical() {
+        let routing = ArchiveRouting::new(1);
+        let now = 1_700_000_000;
+        let last_mc_seqno = 40_000_000;
+        // one day is 17280 masterchain blocks
+        assert!(routing.is_historical(&lookup(1, -1, 39_980_000, 0), last_mc_seqno, now));
+        assert!(!routing.is_historical(&lookup(1, -1, 39_990_000, 0), last_mc_seqno, now));
+        assert!(!routing.is_historical(&lookup(1, -1, 1, 0), 0, now));
+        assert!(!routing.is_historical(&lookup(1, 0, 1, 0), last_mc_seqno, now));
+        assert!(routing.is_historical(&lookup(4, 0, 0, now as i32 - 100_000), 0, now));
+        assert!(!routing.is_historical(&lookup(4, 0, 0, now as i32 - 1_000), 0, now));
+        assert!(!routing.is_historical(&TonFunction::BlocksGetMasterchainInfo {}, 0, now));
+    }
+}
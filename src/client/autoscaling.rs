@@ -0,0 +1,74 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_AUTOSCALING_QUEUE_THRESHOLD: usize = 10;
+pub const DEFAULT_AUTOSCALING_IDLE_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Pool autoscaling parameters.
+///
+/// A connection is added when the number of in-flight requests per connection reaches
+/// `queue_threshold`, and a connection, which has been idle for `idle_cooldown`, is retired.
+/// The pool size is kept within `min_size..=max_size`. Scaling is checked on each request.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PoolAutoscaling {
+    pub min_size: usize,
+    pub max_size: usize,
+    #[serde(default = "default_queue_threshold")]
+    pub queue_threshold: usize,
+    #[serde(default = "default_idle_cooldown")]
+    pub idle_cooldown: Duration,
+}
+
+impl PoolAutoscaling {
+    pub fn new(min_size: usize, max_size: usize) -> PoolAutoscaling {
+        PoolAutoscaling {
+            min_size,
+            max_size,
+            queue_threshold: DEFAULT_AUTOSCALING_QUEUE_THRESHOLD,
+            idle_cooldown: DEFAULT_AUTOSCALING_IDLE_COOLDOWN,
+        }
+    }
+
+    /// Initial pool size. The pool always has at least one connection.
+    pub fn initial_size(&self) -> usize {
+        self.min_size.max(1)
+    }
+
+    pub fn should_scale_up(&self, pool_size: usize, in_flight: usize) -> bool {
+        pool_size < self.max_size && in_flight >= pool_size * self.queue_threshold.max(1)
+    }
+
+    pub fn can_retire(&self, pool_size: usize, idle: Duration) -> bool {
+        pool_size > self.initial_size() && idle >= self.idle_cooldown
+    }
+}
+
+fn default_queue_threshold() -> usize {
+    DEFAULT_AUTOSCALING_QUEUE_THRESHOLD
+}
+
+fn default_idle_cooldown() -> Duration {
+    DEFAULT_AUTOSCALING_IDLE_COOLDOWN
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::client::PoolAutoscaling;
+
+    #[test]
+    fn test_pool_autoscaling() {
+        let autoscaling = PoolAutoscaling::new(0, 3);
+        assert_eq!(autoscaling.initial_size(), 1);
+        assert!(!autoscaling.should_scale_up(1, 9));
+        assert!(autoscaling.should_scale_up(1, 10));
+        assert!(!autoscaling.should_scale_up(2, 19));
+        assert!(!autoscaling.should_scale_up(3, 100));
+
+        assert!(!autoscaling.can_retire(1, Duration::from_secs(3600)));
+        assert!(!autoscaling.can_retire(2, Duration::from_secs(59)));
+        assert!(autoscaling.can_retire(2, Duration::from_secs(60)));
+    }
+}
@@ -1,21 +1,110 @@
+use std::collections::HashSet;
+
 use async_trait::async_trait;
-use futures::future::try_join_all;
+use futures::future::{join_all, try_join_all};
 use futures::FutureExt;
 
 use crate::address::TonAddress;
-use crate::client::{TonClientError, TonClientInterface, TxId};
+use crate::client::{
+    AccountFilter, BlockHeader, MasterchainInfoExt, TonClientError, TonClientInterface,
+    TonlibErrorKind, TxId, WatchSet, LOOKUP_BLOCK_BY_LT, LOOKUP_BLOCK_BY_SEQNO,
+    LOOKUP_BLOCK_BY_UTIME,
+};
 use crate::tl::{
-    BlockIdExt, BlocksAccountTransactionId, BlocksTransactions, RawTransaction,
-    NULL_BLOCKS_ACCOUNT_TRANSACTION_ID,
+    AccountAddress, BlockId, BlockIdExt, BlocksAccountTransactionId, BlocksTransactions,
+    RawTransaction, NULL_BLOCKS_ACCOUNT_TRANSACTION_ID,
 };
 
+/// Blocks of the source shard scanned for the transaction creating a message.
+const LOCATE_SOURCE_TX_MAX_BLOCKS: usize = 2;
+/// Blocks of the destination shard scanned for the transaction processing a message.
+const LOCATE_RESULT_TX_MAX_BLOCKS: usize = 16;
+
 /// High-level functions for working with blocks & shards
 #[async_trait]
 pub trait TonBlockFunctions: TonClientInterface + Send + Sync {
+    /// Returns typed header of the block.
+    async fn get_typed_block_header(
+        &self,
+        block_id: &BlockIdExt,
+    ) -> Result<BlockHeader, TonClientError> {
+        Ok(self.get_block_header(block_id).await?.into())
+    }
+
+    /// Returns ids of the previous blocks: one, or two if the block follows a merge.
+    async fn get_prev_blocks(
+        &self,
+        block_id: &BlockIdExt,
+    ) -> Result<Vec<BlockIdExt>, TonClientError> {
+        Ok(self.get_typed_block_header(block_id).await?.prev_blocks)
+    }
+
+    /// Returns all shard blocks committed by masterchain blocks after `from_mc_seqno` up to
+    /// `to_mc_seqno` inclusive, ordered by logical time.
+    ///
+    /// Shard blocks are found by following `prev_blocks` from the shards of `to_mc_seqno`
+    /// back to the shards of `from_mc_seqno`, so splits and merges are followed and no block
+    /// is missed.
+    async fn get_shard_blocks_between(
+        &self,
+        from_mc_seqno: i32,
+        to_mc_seqno: i32,
+    ) -> Result<Vec<BlockIdExt>, TonClientError> {
+        if to_mc_seqno <= from_mc_seqno {
+            return Ok(vec![]);
+        }
+        let from = self.masterchain_block_by_seqno(from_mc_seqno).await?;
+        let known: HashSet<BlockId> = self
+            .get_block_shards(&from)
+            .await?
+            .shards
+            .iter()
+            .map(BlockIdExt::to_block_id)
+            .collect();
+        let to = self.masterchain_block_by_seqno(to_mc_seqno).await?;
+        let mut pending = self.get_block_shards(&to).await?.shards;
+        let mut visited: HashSet<BlockId> = HashSet::new();
+        let mut headers = vec![];
+        while !pending.is_empty() {
+            let level: Vec<BlockIdExt> = pending
+                .into_iter()
+                .filter(|block| {
+                    let id = block.to_block_id();
+                    !known.contains(&id) && visited.insert(id)
+                })
+                .collect();
+            let level_headers =
+                try_join_all(level.iter().map(|block| self.get_typed_block_header(block))).await?;
+            pending = level_headers
+                .iter()
+                .filter(|header| header.id.seqno > 0)
+                .flat_map(|header| header.prev_blocks.iter().cloned())
+                .collect();
+            headers.extend(level_headers);
+        }
+        headers.sort_by(BlockHeader::cmp_by_lt);
+        Ok(headers.into_iter().map(|header| header.id).collect())
+    }
+
     /// Returns the list of all transaction IDs in specified shard.
     async fn get_shard_tx_ids(&self, shard_id: &BlockIdExt) -> Result<Vec<TxId>, TonClientError> {
-        let mut after: BlocksAccountTransactionId = NULL_BLOCKS_ACCOUNT_TRANSACTION_ID.clone();
+        self.get_shard_tx_ids_filtered(shard_id, &AccountFilter::All)
+            .await
+    }
+
+    /// Returns the list of transaction IDs in specified shard of accounts accepted by the filter.
+    ///
+    /// Shards of workchains not accepted by the filter are skipped without querying.
+    async fn get_shard_tx_ids_filtered(
+        &self,
+        shard_id: &BlockIdExt,
+        filter: &AccountFilter,
+    ) -> Result<Vec<TxId>, TonClientError> {
         let mut transactions: Vec<TxId> = Vec::new();
+        if !filter.accepts_workchain(shard_id.workchain) {
+            return Ok(transactions);
+        }
+        let mut after: BlocksAccountTransactionId = NULL_BLOCKS_ACCOUNT_TRANSACTION_ID.clone();
         loop {
             let mode = if after.lt == 0 { 7 } else { 128 + 7 };
             let txs: BlocksTransactions = self
@@ -27,9 +116,10 @@ pub trait TonBlockFunctions: TonClientInterface + Send + Sync {
                     lt: last.lt,
                 };
             }
-            transactions.reserve(txs.transactions.len());
             for tx in txs.transactions {
-                transactions.push(TxId::new(shard_id.workchain, &tx)?)
+                if filter.accepts_account(shard_id.workchain, &tx.account) {
+                    transactions.push(TxId::new(shard_id.workchain, &tx)?)
+                }
             }
             if !txs.incomplete {
                 break;
@@ -55,6 +145,39 @@ pub trait TonBlockFunctions: TonClientInterface + Send + Sync {
         &self,
         shard_id: &BlockIdExt,
     ) -> Result<Vec<RawTransaction>, TonClientError> {
+        self.get_shard_transactions_filtered(shard_id, &AccountFilter::All)
+            .await
+    }
+
+    /// Returns transactions from specified shard of accounts accepted by the filter.
+    ///
+    /// For `AccountFilter::Addresses` only the transaction ids are listed for the whole shard,
+    /// full transactions are fetched for the watched accounts only.
+    async fn get_shard_transactions_filtered(
+        &self,
+        shard_id: &BlockIdExt,
+        filter: &AccountFilter,
+    ) -> Result<Vec<RawTransaction>, TonClientError> {
+        if !filter.accepts_workchain(shard_id.workchain) {
+            return Ok(vec![]);
+        }
+        if let AccountFilter::Addresses(_) = filter {
+            let tx_ids = self.get_shard_tx_ids_filtered(shard_id, filter).await?;
+            let f = tx_ids.iter().map(|tx_id| {
+                self.get_raw_transactions_v2(
+                    &tx_id.address,
+                    &tx_id.internal_transaction_id,
+                    1,
+                    false,
+                )
+            });
+            let raw_txs = try_join_all(f)
+                .await?
+                .into_iter()
+                .flat_map(|txs| txs.transactions.into_iter().take(1))
+                .collect();
+            return Ok(raw_txs);
+        }
         let mut after: BlocksAccountTransactionId = NULL_BLOCKS_ACCOUNT_TRANSACTION_ID.clone();
         let mut raw_txs: Vec<RawTransaction> = Vec::new();
         loop {
@@ -72,7 +195,7 @@ pub trait TonBlockFunctions: TonClientInterface + Send + Sync {
                 let lt = last.transaction_id.lt;
                 after = BlocksAccountTransactionId { account, lt };
             }
-            raw_txs.reserve(txs.transactions.len());
+            // the remaining filters accept whole workchains, which is checked above
             raw_txs.extend(txs.transactions);
             if !txs.incomplete {
                 break;
@@ -80,18 +203,372 @@ pub trait TonBlockFunctions: TonClientInterface + Send + Sync {
         }
         Ok(raw_txs)
     }
-    /// Returns all transactions from specified shards
+    /// Returns transactions from specified shards.
+    ///
+    /// Shards are fetched in parallel (bounded by the connection concurrency limit)
+    /// and the result is returned per shard, so a failure of one shard doesn't discard the others.
     async fn get_shards_transactions(
         &self,
         shards: &[BlockIdExt],
-    ) -> Result<Vec<(BlockIdExt, Vec<RawTransaction>)>, TonClientError> {
+    ) -> Vec<(BlockIdExt, Result<Vec<RawTransaction>, TonClientError>)> {
         let f = shards.iter().map(|shard| {
             self.get_shard_transactions(shard)
-                .map(move |res| res.map(|txs| (shard.clone(), txs)))
+                .map(move |res| (shard.clone(), res))
         });
-        let txs: Vec<_> = try_join_all(f).await?;
-        Ok(txs)
+        join_all(f).await
+    }
+
+    /// Re-fetches transactions of the shards that failed in `results`, keeping the successful ones.
+    async fn retry_failed_shards_transactions(
+        &self,
+        results: Vec<(BlockIdExt, Result<Vec<RawTransaction>, TonClientError>)>,
+    ) -> Vec<(BlockIdExt, Result<Vec<RawTransaction>, TonClientError>)> {
+        let f = results.into_iter().map(|(shard, res)| async move {
+            match res {
+                Ok(txs) => (shard, Ok(txs)),
+                Err(e) => {
+                    log::warn!(
+                        "Retrying transactions of shard {:?} after error: {}",
+                        shard,
+                        e
+                    );
+                    let res = self.get_shard_transactions(&shard).await;
+                    (shard, res)
+                }
+            }
+        });
+        join_all(f).await
+    }
+
+    /// Returns the last masterchain block generated at or before `utime`.
+    ///
+    /// Block found by `lookupBlock` with utime is used as a hint, which is verified (or refined)
+    /// by galloping and then binary search over masterchain seqnos using block header times.
+    /// Only blocks next to the hint are requested, so non-archive liteservers can serve
+    /// recent times.
+    async fn find_block_by_time(&self, utime: i64) -> Result<BlockIdExt, TonClientError> {
+        let (_, info) = self.get_masterchain_info().await?;
+        let last_utime = self.get_block_header(&info.last).await?.gen_utime;
+        if utime >= last_utime {
+            return Ok(info.last);
+        }
+        // Invariant: time(lo) <= utime < time(hi)
+        let mut hi = info.last.seqno;
+        let mut hi_utime = last_utime;
+        let mut lo = None;
+        let hint = self
+            .lookup_block_by_utime(info.last.workchain, info.last.shard, utime as i32)
+            .await;
+        if let Ok(hint) = hint {
+            if hint.seqno < hi {
+                let hint_utime = self.get_block_header(&hint).await?.gen_utime;
+                if hint_utime <= utime {
+                    lo = Some(hint);
+                } else {
+                    hi = hint.seqno;
+                    hi_utime = hint_utime;
+                }
+            }
+        }
+        let mut step = 1;
+        let mut lo = match lo {
+            Some(mut lo) => {
+                // gallop up from the hint to narrow hi
+                while lo.seqno + step < hi {
+                    let block = self.masterchain_block_by_seqno(lo.seqno + step).await?;
+                    if self.get_block_header(&block).await?.gen_utime <= utime {
+                        lo = block;
+                        step *= 2;
+                    } else {
+                        hi = block.seqno;
+                        break;
+                    }
+                }
+                lo
+            }
+            None => loop {
+                // gallop down from hi, the oldest found block bounds the available history
+                let seqno = (hi - step).max(1);
+                let block = match self.masterchain_block_by_seqno(seqno).await {
+                    Ok(block) => block,
+                    Err(e)
+                        if matches!(
+                            e.tonlib_error_kind(),
+                            Some(TonlibErrorKind::BlockNotFound | TonlibErrorKind::NotInDb)
+                        ) =>
+                    {
+                        return Err(TonClientError::BlockByTimeNotFound {
+                            utime,
+                            first_available_utime: hi_utime,
+                        });
+                    }
+                    Err(e) => return Err(e),
+                };
+                let block_utime = self.get_block_header(&block).await?.gen_utime;
+                if block_utime <= utime {
+                    break block;
+                }
+                if seqno == 1 {
+                    return Err(TonClientError::BlockByTimeNotFound {
+                        utime,
+                        first_available_utime: block_utime,
+                    });
+                }
+                hi = seqno;
+                hi_utime = block_utime;
+                step *= 2;
+            },
+        };
+        while hi - lo.seqno > 1 {
+            let mid = self
+                .masterchain_block_by_seqno(lo.seqno + (hi - lo.seqno) / 2)
+                .await?;
+            if self.get_block_header(&mid).await?.gen_utime <= utime {
+                lo = mid;
+            } else {
+                hi = mid.seqno;
+            }
+        }
+        Ok(lo)
+    }
+
+    /// Returns masterchain info extended with the time of the last block and the liteserver
+    /// version. All parts are requested from the same liteserver.
+    async fn get_masterchain_info_ext(&self) -> Result<MasterchainInfoExt, TonClientError> {
+        let (conn, info) = self.get_masterchain_info().await?;
+        let (header, server_info) = futures::try_join!(
+            conn.get_block_header(&info.last),
+            conn.lite_server_get_info()
+        )?;
+        Ok(MasterchainInfoExt::new(info, &header, &server_info))
+    }
+
+    /// Returns masterchain block id by its seqno.
+    async fn masterchain_block_by_seqno(&self, seqno: i32) -> Result<BlockIdExt, TonClientError> {
+        let block_id = BlockId {
+            workchain: -1,
+            shard: i64::MIN,
+            seqno,
+        };
+        self.lookup_block(LOOKUP_BLOCK_BY_SEQNO, &block_id, 0, 0)
+            .await
+    }
+
+    /// Returns the block of the shard containing logical time `lt`.
+    async fn lookup_block_by_lt(
+        &self,
+        workchain: i32,
+        shard: i64,
+        lt: i64,
+    ) -> Result<BlockIdExt, TonClientError> {
+        let block_id = BlockId {
+            workchain,
+            shard,
+            seqno: 0,
+        };
+        self.lookup_block(LOOKUP_BLOCK_BY_LT, &block_id, lt, 0)
+            .await
+    }
+
+    /// Returns the block of the shard found by generation time `utime`. Liteservers may return
+    /// a neighbouring block, `find_block_by_time` finds the exact masterchain block.
+    async fn lookup_block_by_utime(
+        &self,
+        workchain: i32,
+        shard: i64,
+        utime: i32,
+    ) -> Result<BlockIdExt, TonClientError> {
+        let block_id = BlockId {
+            workchain,
+            shard,
+            seqno: 0,
+        };
+        self.lookup_block(LOOKUP_BLOCK_BY_UTIME, &block_id, 0, utime)
+            .await
+    }
+
+    /// Returns the transaction of `source`, which created the internal message to
+    /// `destination` at logical time `created_lt`, `None` if it is not found.
+    async fn try_locate_source_tx(
+        &self,
+        source: &TonAddress,
+        destination: &TonAddress,
+        created_lt: i64,
+    ) -> Result<Option<RawTransaction>, TonClientError> {
+        let matches = |tx: &RawTransaction| {
+            tx.transaction_id.lt < created_lt
+                && tx.out_msgs.iter().any(|msg| {
+                    msg.created_lt == created_lt && is_address(&msg.destination, destination)
+                })
+        };
+        locate_account_tx(
+            self,
+            source,
+            created_lt,
+            LOCATE_SOURCE_TX_MAX_BLOCKS,
+            matches,
+        )
+        .await
+    }
+
+    /// Returns the transaction of `destination`, which processed the internal message from
+    /// `source` created at logical time `created_lt`, `None` if it is not found (yet).
+    ///
+    /// Blocks of the destination shard are scanned with `getTransactionsExt` starting from the
+    /// block containing `created_lt`, so messages crossing shards are found as well.
+    async fn try_locate_result_tx(
+        &self,
+        source: &TonAddress,
+        destination: &TonAddress,
+        created_lt: i64,
+    ) -> Result<Option<RawTransaction>, TonClientError> {
+        let matches = |tx: &RawTransaction| {
+            tx.in_msg
+                .as_ref()
+                .is_some_and(|msg| msg.created_lt == created_lt && is_address(&msg.source, source))
+        };
+        locate_account_tx(
+            self,
+            destination,
+            created_lt,
+            LOCATE_RESULT_TX_MAX_BLOCKS,
+            matches,
+        )
+        .await
     }
 }
 
 impl<T> TonBlockFunctions for T where T: TonClientInterface + Send + Sync {}
+
+/// Scans blocks of the shard of the account, starting from the block containing `lt`, for a
+/// transaction of the account accepted by `matches`.
+async fn locate_account_tx<C, F>(
+    client: &C,
+    address: &TonAddress,
+    lt: i64,
+    max_blocks: usize,
+    matches: F,
+) -> Result<Option<RawTransaction>, TonClientError>
+where
+    C: TonBlockFunctions + ?Sized,
+    F: Fn(&RawTransaction) -> bool + Send + Sync,
+{
+    let shard = account_shard_prefix(address);
+    let filter = AccountFilter::Addresses([address.clone()].into_iter().collect::<WatchSet>());
+    let mut lt = lt;
+    for _ in 0..max_blocks {
+        let block = match client
+            .lookup_block_by_lt(address.workchain, shard, lt)
+            .await
+        {
+            Ok(block) => block,
+            Err(e)
+                if matches!(
+                    e.tonlib_error_kind(),
+                    Some(TonlibErrorKind::BlockNotFound | TonlibErrorKind::NotReady)
+                ) =>
+            {
+                return Ok(None)
+            }
+            Err(e) => return Err(e),
+        };
+        // only the transaction ids are listed for the shard, full transactions of the account
+        let txs = client
+            .get_shard_transactions_filtered(&block, &filter)
+            .await?;
+        if let Some(tx) = txs.into_iter().find(|tx| matches(tx)) {
+            return Ok(Some(tx));
+        }
+        let header = client.get_block_header(&block).await?;
+        lt = header.end_lt.max(lt + 1);
+    }
+    Ok(None)
+}
+
+/// Account id prefix, which `lookupBlock` resolves to the shard containing the account.
+fn account_shard_prefix(address: &TonAddress) -> i64 {
+    let mut prefix = [0; 8];
+    prefix.copy_from_slice(&address.hash_part[..8]);
+    i64::from_be_bytes(prefix) | 1
+}
+
+fn is_address(address: &AccountAddress, expected: &TonAddress) -> bool {
+    address
+        .account_address
+        .parse::<TonAddress>()
+        .is_ok_and(|a| &a == expected)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+
+    use crate::address::TonAddress;
+    use crate::client::block_functions::{account_shard_prefix, is_address};
+    use crate::client::{TonBlockFunctions, TonClientError, TonClientInterface, TonConnection};
+    use crate::tl::{AccountAddress, BlockId, TonFunction, TonResult};
+
+    #[derive(Default)]
+    struct RecordingClient {
+        functions: Mutex<Vec<TonFunction>>,
+    }
+
+    #[async_trait]
+    impl TonClientInterface for RecordingClient {
+        async fn get_connection(&self) -> Result<TonConnection, TonClientError> {
+            Err(TonClientError::InternalError("No connection".to_string()))
+        }
+
+        async fn invoke_on_connection(
+            &self,
+            function: &TonFunction,
+        ) -> Result<(TonConnection, TonResult), TonClientError> {
+            self.functions.lock().unwrap().push(function.clone());
+            Err(TonClientError::InternalError("Recorded".to_string()))
+        }
+    }
+
+    #[test]
+    fn test_account_shard_prefix() {
+        let mut hash = [0; 32];
+        hash[0] = 0x80;
+        let address = TonAddress::new(0, &hash);
+        assert_eq!(account_shard_prefix(&address), i64::MIN | 1);
+        let raw = AccountAddress {
+            account_address: address.to_hex(),
+        };
+        assert!(is_address(&raw, &address));
+        assert!(!is_address(&raw, &TonAddress::NULL));
+        let empty = AccountAddress {
+            account_address: String::new(),
+        };
+        assert!(!is_address(&empty, &address));
+    }
+
+    #[tokio::test]
+    async fn test_lookup_block_modes() {
+        let client = RecordingClient::default();
+        let _ = client.lookup_block_by_lt(0, i64::MIN, 100).await;
+        let _ = client.lookup_block_by_utime(-1, i64::MIN, 200).await;
+        let block_id = BlockId {
+            workchain: 0,
+            shard: i64::MIN,
+            seqno: 1,
+        };
+        let r = client.lookup_block(3, &block_id, 0, 0).await;
+        assert!(matches!(r, Err(TonClientError::InvalidLookupBlockMode(3))));
+
+        let functions = client.functions.lock().unwrap();
+        assert_eq!(functions.len(), 2);
+        assert!(matches!(
+            &functions[0],
+            TonFunction::BlocksLookupBlock { mode: 2, id, lt: 100, utime: 0 } if id.workchain == 0
+        ));
+        assert!(matches!(
+            &functions[1],
+            TonFunction::BlocksLookupBlock { mode: 4, id, lt: 0, utime: 200 } if id.workchain == -1
+        ));
+    }
+}
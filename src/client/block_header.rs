@@ -0,0 +1,149 @@
+use std::cmp::Ordering;
+
+use crate::tl::{BlockIdExt, BlocksHeader};
+
+/// Validator set which signed the block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlockValidatorsInfo {
+    pub validator_list_hash_short: i32,
+    pub catchain_seqno: u32,
+}
+
+/// Typed header of the block, built from `get_block_header` result.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BlockHeader {
+    pub id: BlockIdExt,
+    pub global_id: i32,
+    pub version: u32,
+    pub gen_utime: u32,
+    pub start_lt: u64,
+    pub end_lt: u64,
+    /// one block, or two blocks if the shard is merged.
+    pub prev_blocks: Vec<BlockIdExt>,
+    pub is_key_block: bool,
+    pub prev_key_block_seqno: u32,
+    pub min_ref_mc_seqno: u32,
+    /// incremented by hardforks, 0 if the header has no vertical seqno.
+    pub vert_seqno: u32,
+    pub after_merge: bool,
+    pub after_split: bool,
+    pub before_split: bool,
+    pub want_merge: bool,
+    pub want_split: bool,
+    pub validators: BlockValidatorsInfo,
+}
+
+impl BlockHeader {
+    pub fn workchain(&self) -> i32 {
+        self.id.workchain
+    }
+
+    pub fn shard(&self) -> i64 {
+        self.id.shard
+    }
+
+    pub fn seqno(&self) -> u32 {
+        self.id.seqno as u32
+    }
+
+    pub fn is_masterchain(&self) -> bool {
+        self.id.workchain == -1
+    }
+
+    /// Checks if a transaction with logical time `lt` belongs to the block.
+    pub fn contains_lt(&self, lt: u64) -> bool {
+        self.start_lt <= lt && lt < self.end_lt
+    }
+
+    /// Orders blocks by the time of their generation: by `start_lt`, then by workchain, shard
+    /// and seqno, so that the order is total.
+    pub fn cmp_by_lt(&self, other: &BlockHeader) -> Ordering {
+        self.start_lt
+            .cmp(&other.start_lt)
+            .then(self.id.workchain.cmp(&other.id.workchain))
+            .then((self.id.shard as u64).cmp(&(other.id.shard as u64)))
+            .then(self.id.seqno.cmp(&other.id.seqno))
+    }
+}
+
+impl From<BlocksHeader> for BlockHeader {
+    fn from(header: BlocksHeader) -> Self {
+        BlockHeader {
+            id: header.id,
+            global_id: header.global_id,
+            version: header.version as u32,
+            gen_utime: header.gen_utime as u32,
+            start_lt: header.start_lt as u64,
+            end_lt: header.end_lt as u64,
+            prev_blocks: header.prev_blocks.unwrap_or_default(),
+            is_key_block: header.is_key_block,
+            prev_key_block_seqno: header.prev_key_block_seqno as u32,
+            min_ref_mc_seqno: header.min_ref_mc_seqno as u32,
+            vert_seqno: header.vert_seqno.unwrap_or_default() as u32,
+            after_merge: header.after_merge,
+            after_split: header.after_split,
+            before_split: header.before_split,
+            want_merge: header.want_merge,
+            want_split: header.want_split,
+            validators: BlockValidatorsInfo {
+                validator_list_hash_short: header.validator_list_hash_short,
+                catchain_seqno: header.catchain_seqno as u32,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use crate::client::BlockHeader;
+    use crate::tl::{BlockIdExt, BlocksHeader};
+
+    fn header(shard: i64, seqno: i32, start_lt: i64) -> BlocksHeader {
+        BlocksHeader {
+            id: BlockIdExt {
+                workchain: 0,
+                shard,
+                seqno,
+                root_hash: "".to_string(),
+                file_hash: "".to_string(),
+            },
+            global_id: -239,
+            version: 0,
+            flags: 1,
+            after_merge: false,
+            after_split: false,
+            before_split: false,
+            want_merge: false,
+            want_split: true,
+            validator_list_hash_short: -1,
+            catchain_seqno: 12,
+            min_ref_mc_seqno: 100,
+            is_key_block: false,
+            prev_key_block_seqno: 90,
+            start_lt,
+            end_lt: start_lt + 4,
+            gen_utime: 1_700_000_000,
+            vert_seqno: Some(1),
+            prev_blocks: None,
+        }
+    }
+
+    #[test]
+    fn test_block_header() {
+        let first = BlockHeader::from(header(i64::MIN, 10, 1000));
+        assert_eq!(first.gen_utime, 1_700_000_000);
+        assert_eq!((first.start_lt, first.end_lt), (1000, 1004));
+        assert_eq!(first.vert_seqno, 1);
+        assert_eq!(first.validators.catchain_seqno, 12);
+        assert!(first.prev_blocks.is_empty());
+        assert!(first.contains_lt(1003) && !first.contains_lt(1004));
+
+        let left = BlockHeader::from(header(0x4000000000000000, 11, 2000));
+        let right = BlockHeader::from(header(i64::MIN | 0x4000000000000000, 11, 2000));
+        assert_eq!(first.cmp_by_lt(&left), Ordering::Less);
+        assert_eq!(left.cmp_by_lt(&right), Ordering::Less);
+        assert_eq!(right.cmp_by_lt(&right), Ordering::Equal);
+    }
+}
@@ -1,16 +1,114 @@
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
+use std::pin::Pin;
+use std::task::{Context, Poll};
 use std::time::Duration;
 
 use futures::future::try_join_all;
+use futures::stream::{self, BoxStream};
+use futures::{Stream, StreamExt};
 use tokio::time;
 
-use crate::client::{TonClientError, TonClientInterface, TonConnection};
+use crate::client::{
+    AdaptivePolling, BlockHeader, ChainTracker, Reorg, TonClientError, TonClientInterface,
+    TonConnection,
+};
 use crate::tl::{BlockId, BlockIdExt, BlocksHeader, BlocksShards};
 
 #[derive(Debug, Clone)]
 pub struct BlockStreamItem {
     pub master_shard: BlockIdExt,
+    /// shard blocks in the order of `BlockHeader::cmp_by_lt`.
     pub shards: Vec<BlockIdExt>,
+    /// changes of shard configuration since the previous masterchain block.
+    pub shard_events: Vec<ShardEvent>,
+    /// set on the masterchain block completing backfill of a gap.
+    pub gap_recovered: Option<GapRecovered>,
+    /// set on the first masterchain block delivered after a reorg, which is the block at
+    /// `Reorg::from_seqno`. Requires chain tracking, see `BlockStream::with_chain_tracker`.
+    pub reorg: Option<Reorg>,
+}
+
+/// Range of masterchain blocks, which the stream fell behind by, e.g. after downtime, and
+/// which was backfilled before resuming live processing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GapRecovered {
+    pub from_seqno: i32,
+    pub to_seqno: i32,
+}
+
+/// Number of masterchain blocks the stream may fall behind the head before backfilling.
+pub const BLOCK_STREAM_GAP_THRESHOLD: i32 = 16;
+
+/// Returns the gap if the stream at `next_seqno` fell behind the masterchain head too far.
+pub fn detect_gap(next_seqno: i32, last_seqno: i32) -> Option<GapRecovered> {
+    (last_seqno - next_seqno > BLOCK_STREAM_GAP_THRESHOLD).then_some(GapRecovered {
+        from_seqno: next_seqno,
+        to_seqno: last_seqno,
+    })
+}
+
+/// Change of shard configuration of a workchain. Shards are identified by shard prefix,
+/// as in `BlockIdExt::shard`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShardEvent {
+    ShardSplit {
+        workchain: i32,
+        parent: i64,
+        children: [i64; 2],
+    },
+    ShardMerge {
+        workchain: i32,
+        children: [i64; 2],
+        parent: i64,
+    },
+}
+
+/// Returns the left and right child shards of the shard prefix, or `None` for the shard of
+/// the maximal depth.
+pub fn shard_children(shard: i64) -> Option<[i64; 2]> {
+    let shard = shard as u64;
+    let low_bit = shard & shard.wrapping_neg();
+    if low_bit <= 1 {
+        return None;
+    }
+    let half = low_bit >> 1;
+    Some([(shard - half) as i64, (shard + half) as i64])
+}
+
+/// Compares shards of two consecutive masterchain blocks given as `(workchain, shard)`.
+pub fn detect_shard_events(
+    prev: &HashSet<(i32, i64)>,
+    next: &HashSet<(i32, i64)>,
+) -> Vec<ShardEvent> {
+    let has_children = |shards: &HashSet<(i32, i64)>, workchain: i32, shard: i64| {
+        shard_children(shard).filter(|c| c.iter().all(|c| shards.contains(&(workchain, *c))))
+    };
+    let mut events: Vec<ShardEvent> = prev
+        .difference(next)
+        .filter_map(|&(workchain, parent)| {
+            has_children(next, workchain, parent).map(|children| ShardEvent::ShardSplit {
+                workchain,
+                parent,
+                children,
+            })
+        })
+        .chain(next.difference(prev).filter_map(|&(workchain, parent)| {
+            has_children(prev, workchain, parent).map(|children| ShardEvent::ShardMerge {
+                workchain,
+                children,
+                parent,
+            })
+        }))
+        .collect();
+    events.sort_by_key(|e| match e {
+        ShardEvent::ShardSplit {
+            workchain, parent, ..
+        }
+        | ShardEvent::ShardMerge {
+            workchain, parent, ..
+        } => (*workchain, *parent as u64),
+    });
+    events
 }
 
 /// Allows to sequentially retrieve all shards in all workchains.
@@ -22,6 +120,11 @@ pub struct BlockStream<C: TonClientInterface + Clone> {
     client: C,
     next_seqno: i32,
     prev_block_set: HashSet<BlockId>,
+    polling: AdaptivePolling,
+    archive_client: Option<C>,
+    gap: Option<GapRecovered>,
+    chain_tracker: Option<ChainTracker>,
+    pending_reorg: Option<Reorg>,
 }
 
 impl<C: TonClientInterface + Clone> BlockStream<C> {
@@ -30,31 +133,98 @@ impl<C: TonClientInterface + Clone> BlockStream<C> {
             client: client.clone(),
             next_seqno: from_seqno,
             prev_block_set: Default::default(),
+            polling: default_block_polling(),
+            archive_client: None,
+            gap: None,
+            chain_tracker: None,
+            pending_reorg: None,
+        }
+    }
+
+    /// Sets client used to backfill gaps, e.g. client with `ConnectionCheck::Archive`, since
+    /// old blocks may be already pruned by regular liteservers.
+    pub fn with_archive_client(&mut self, archive_client: &C) -> &mut Self {
+        self.archive_client = Some(archive_client.clone());
+        self
+    }
+
+    /// Checks hashes of the last `depth` masterchain blocks. If a block is replaced, the
+    /// stream rewinds to it and sets `reorg` on the next item. Costs one block header request
+    /// per masterchain block.
+    pub fn with_chain_tracker(&mut self, depth: usize) -> &mut Self {
+        self.chain_tracker = Some(ChainTracker::new(depth));
+        self
+    }
+
+    /// Client loading the blocks, the archive client while backfilling a gap.
+    fn source(&self) -> &C {
+        match (&self.gap, &self.archive_client) {
+            (Some(_), Some(archive_client)) => archive_client,
+            _ => &self.client,
         }
     }
 
     /// Retrieves the next masterchain block together with all shards finalized in this block
     ///
     /// If the next block is not yet available, the returned future resolves when it's added to masterchain.
+    /// Waiting for new blocks, the masterchain is polled when the next block is due according
+    /// to the observed block cadence.
+    ///
+    /// If the stream falls behind the head by more than `BLOCK_STREAM_GAP_THRESHOLD` blocks,
+    /// the missed blocks are loaded with the archive client (if set) and the item completing
+    /// the backfill has `gap_recovered` set.
     pub async fn next(&mut self) -> Result<BlockStreamItem, TonClientError> {
-        if self.prev_block_set.is_empty() {
-            let (prev_block_shards, _) =
-                get_master_block_shards(&self.client, self.next_seqno - 1).await?;
-            for shard in prev_block_shards.shards {
-                self.prev_block_set.insert(shard.to_block_id());
+        loop {
+            if let Some(item) = self.try_next().await? {
+                return Ok(item);
             }
-        };
-        let connection = loop {
+        }
+    }
+
+    /// Returns `None` if the stream is rewound after a reorg.
+    async fn try_next(&mut self) -> Result<Option<BlockStreamItem>, TonClientError> {
+        let (connection, last_seqno) = loop {
             let (conn, masterchain_info) = self.client.get_masterchain_info().await?;
             if masterchain_info.last.seqno < self.next_seqno {
-                time::sleep(Duration::from_millis(100)).await;
+                self.polling.on_idle();
+                self.polling.wait().await;
             } else {
-                break conn;
+                // the cadence is observed only at the head of the chain
+                if masterchain_info.last.seqno == self.next_seqno {
+                    self.polling.on_event();
+                }
+                break (conn, masterchain_info.last.seqno);
+            }
+        };
+        if self.gap.is_none() {
+            self.gap = detect_gap(self.next_seqno, last_seqno);
+            if let Some(gap) = &self.gap {
+                log::warn!(
+                    "[BlockStream] Backfilling gap of masterchain blocks {}..={}",
+                    gap.from_seqno,
+                    gap.to_seqno
+                );
+            }
+        }
+        let connection = match (&self.gap, &self.archive_client) {
+            (Some(_), Some(archive_client)) => archive_client.get_connection().await?,
+            _ => connection,
+        };
+        if self.prev_block_set.is_empty() {
+            let (prev_block_shards, _) =
+                get_master_block_shards(self.source(), self.next_seqno - 1).await?;
+            for shard in prev_block_shards.shards {
+                self.prev_block_set.insert(shard.to_block_id());
             }
         };
         let (block_shards, master_block) =
             get_master_block_shards(&connection, self.next_seqno).await?;
+        if let Some(reorg) = self.detect_reorg(&connection, &master_block).await? {
+            self.rewind(reorg);
+            return Ok(None);
+        }
         let mut result_shards: HashSet<BlockIdExt> = Default::default();
+        let mut result_headers: Vec<BlockHeader> = Default::default();
         let mut unprocessed_shards: Vec<BlockIdExt> = Default::default();
         unprocessed_shards.extend(block_shards.shards.clone());
         while !unprocessed_shards.is_empty() {
@@ -74,22 +244,88 @@ impl<C: TonClientInterface + Clone> BlockStream<C> {
                 .get_block_headers(&connection, &shards_to_process)
                 .await?;
             for h in headers {
-                if let Some(prev_blocks) = h.prev_blocks {
-                    unprocessed_shards.extend(prev_blocks)
-                }
+                let header = BlockHeader::from(h);
+                unprocessed_shards.extend(header.prev_blocks.iter().cloned());
+                result_headers.push(header);
             }
         }
+        result_headers.sort_by(BlockHeader::cmp_by_lt);
 
         self.next_seqno += 1;
         let new_prev_seq_shards = block_shards.shards;
+        let prev_shards = self
+            .prev_block_set
+            .iter()
+            .map(|b| (b.workchain, b.shard))
+            .collect();
+        let next_shards = new_prev_seq_shards
+            .iter()
+            .map(|b| (b.workchain, b.shard))
+            .collect();
+        let shard_events = detect_shard_events(&prev_shards, &next_shards);
+        for event in shard_events.iter() {
+            log::info!(
+                "[BlockStream] {:?} in masterchain block {}",
+                event,
+                master_block.seqno
+            );
+        }
         self.prev_block_set = new_prev_seq_shards
             .into_iter()
             .map(|shard| shard.to_block_id())
             .collect();
-        Ok(BlockStreamItem {
-            shards: result_shards.into_iter().collect(),
+        let gap_recovered = match self.gap {
+            Some(gap) if gap.to_seqno <= master_block.seqno => {
+                log::info!(
+                    "[BlockStream] Gap of masterchain blocks {}..={} recovered",
+                    gap.from_seqno,
+                    gap.to_seqno
+                );
+                self.gap.take()
+            }
+            _ => None,
+        };
+        Ok(Some(BlockStreamItem {
+            shards: result_headers.into_iter().map(|h| h.id).collect(),
             master_shard: master_block,
-        })
+            shard_events,
+            gap_recovered,
+            reorg: self.pending_reorg.take(),
+        }))
+    }
+
+    async fn detect_reorg(
+        &mut self,
+        conn: &TonConnection,
+        master_block: &BlockIdExt,
+    ) -> Result<Option<Reorg>, TonClientError> {
+        if self.chain_tracker.is_none() {
+            return Ok(None);
+        }
+        let header = BlockHeader::from(conn.get_block_header(master_block).await?);
+        let prev = header.prev_blocks.first();
+        Ok(self
+            .chain_tracker
+            .as_mut()
+            .and_then(|tracker| tracker.observe(master_block, prev)))
+    }
+
+    /// Restarts the stream from the first replaced block.
+    fn rewind(&mut self, reorg: Reorg) {
+        log::warn!(
+            "[BlockStream] Masterchain reorg from block {}, {} delivered blocks replaced",
+            reorg.from_seqno,
+            reorg.dropped.len()
+        );
+        self.next_seqno = reorg.from_seqno;
+        self.prev_block_set.clear();
+        match &mut self.pending_reorg {
+            Some(pending) => {
+                pending.from_seqno = pending.from_seqno.min(reorg.from_seqno);
+                pending.dropped.extend(reorg.dropped);
+            }
+            None => self.pending_reorg = Some(reorg),
+        }
     }
 
     async fn get_block_headers(
@@ -114,11 +350,99 @@ impl<C: TonClientInterface + Clone> BlockStream<C> {
         // Fallback to random connection on client
         match r {
             Ok(bh) => Ok(bh),
-            Err(_) => self.client.get_block_header(block_id).await,
+            Err(_) => self.source().get_block_header(block_id).await,
         }
     }
 }
 
+const BLOCK_STREAM_RETRY_DELAY_MS: u64 = 1000;
+/// Number of consecutive failed attempts to load a masterchain block before the error is
+/// yielded by `TonBlockStream`.
+const BLOCK_STREAM_MAX_ATTEMPTS: usize = 5;
+const BLOCK_STREAM_MIN_POLL_INTERVAL_MS: u64 = 100;
+const BLOCK_STREAM_MAX_POLL_INTERVAL_MS: u64 = 1000;
+/// Expected interval between masterchain blocks.
+const MASTERCHAIN_BLOCK_CADENCE_MS: u64 = 5000;
+
+fn default_block_polling() -> AdaptivePolling {
+    let mut polling = AdaptivePolling::new(
+        Duration::from_millis(BLOCK_STREAM_MIN_POLL_INTERVAL_MS),
+        Duration::from_millis(BLOCK_STREAM_MAX_POLL_INTERVAL_MS),
+    );
+    polling.with_cadence(Duration::from_millis(MASTERCHAIN_BLOCK_CADENCE_MS));
+    polling
+}
+
+/// Endless stream of sealed blocks: shard blocks finalized by each masterchain block followed
+/// by the masterchain block itself.
+///
+/// Masterchain blocks are never skipped and shard splits and merges are followed through
+/// `prev_blocks` of the shard block headers. Failed loads of a masterchain block are logged
+/// and retried, after 5 consecutive failures the error is yielded. Polling the stream after
+/// the error requests the same block again, so the consumer decides whether to continue. To
+/// resume after restart, create the stream from the seqno following the last processed
+/// masterchain block.
+pub struct TonBlockStream {
+    inner: BoxStream<'static, Result<BlockIdExt, TonClientError>>,
+}
+
+impl TonBlockStream {
+    pub fn new<C>(client: &C, from_seqno: i32) -> TonBlockStream
+    where
+        C: TonClientInterface + Clone + 'static,
+    {
+        let block_stream = BlockStream::new(client, from_seqno);
+        let inner = stream::unfold(
+            (block_stream, VecDeque::new()),
+            |(mut block_stream, mut pending)| async move {
+                let mut attempts = 0;
+                while pending.is_empty() {
+                    match block_stream.next().await {
+                        Ok(item) => {
+                            pending.extend(item.shards);
+                            pending.push_back(item.master_shard);
+                        }
+                        Err(e) => {
+                            attempts += 1;
+                            if attempts >= BLOCK_STREAM_MAX_ATTEMPTS {
+                                return Some((Err(e), (block_stream, pending)));
+                            }
+                            log::warn!(
+                                "[TonBlockStream] Failed to load masterchain block {}: {}",
+                                block_stream.next_seqno,
+                                e
+                            );
+                            time::sleep(Duration::from_millis(BLOCK_STREAM_RETRY_DELAY_MS)).await;
+                        }
+                    }
+                }
+                let block = pending.pop_front()?;
+                Some((Ok(block), (block_stream, pending)))
+            },
+        );
+        TonBlockStream {
+            inner: inner.boxed(),
+        }
+    }
+
+    /// Creates the stream starting from the next masterchain block.
+    pub async fn from_latest<C>(client: &C) -> Result<TonBlockStream, TonClientError>
+    where
+        C: TonClientInterface + Clone + 'static,
+    {
+        let (_, info) = client.get_masterchain_info().await?;
+        Ok(Self::new(client, info.last.seqno + 1))
+    }
+}
+
+impl Stream for TonBlockStream {
+    type Item = Result<BlockIdExt, TonClientError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_unpin(cx)
+    }
+}
+
 async fn get_master_block_shards<C: TonClientInterface>(
     conn: &C,
     seqno: i32,
@@ -134,3 +458,64 @@ async fn get_master_block_shards<C: TonClientInterface>(
         master_block_ext,
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::client::{
+        detect_gap, detect_shard_events, shard_children, GapRecovered, ShardEvent,
+    };
+
+    const ROOT: i64 = i64::MIN;
+    const LEFT: i64 = 0x4000000000000000;
+    const RIGHT: i64 = i64::MIN | 0x4000000000000000;
+
+    #[test]
+    fn test_shard_children() {
+        assert_eq!(shard_children(ROOT), Some([LEFT, RIGHT]));
+        assert_eq!(
+            shard_children(LEFT),
+            Some([0x2000000000000000, 0x6000000000000000])
+        );
+        assert_eq!(shard_children(1), None);
+    }
+
+    #[test]
+    fn test_detect_shard_events() {
+        let root: HashSet<_> = [(0, ROOT)].into();
+        let split: HashSet<_> = [(0, LEFT), (0, RIGHT)].into();
+        assert_eq!(
+            detect_shard_events(&root, &split),
+            [ShardEvent::ShardSplit {
+                workchain: 0,
+                parent: ROOT,
+                children: [LEFT, RIGHT]
+            }]
+        );
+        assert_eq!(
+            detect_shard_events(&split, &root),
+            [ShardEvent::ShardMerge {
+                workchain: 0,
+                children: [LEFT, RIGHT],
+                parent: ROOT
+            }]
+        );
+        assert!(detect_shard_events(&split, &split).is_empty());
+        let other_workchain: HashSet<_> = [(1, LEFT), (1, RIGHT)].into();
+        assert!(detect_shard_events(&root, &other_workchain).is_empty());
+    }
+
+    #[test]
+    fn test_detect_gap() {
+        assert_eq!(detect_gap(100, 100), None);
+        assert_eq!(detect_gap(100, 116), None);
+        assert_eq!(
+            detect_gap(100, 117),
+            Some(GapRecovered {
+                from_seqno: 100,
+                to_seqno: 117
+            })
+        );
+    }
+}
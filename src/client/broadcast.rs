@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+use crate::message::ExtMessageHashes;
+
+/// Result of sending an external message to one pool member.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BroadcastAttempt {
+    /// Tag of the connection, `None` if the member failed to connect.
+    pub tag: Option<String>,
+    /// Index of the config of the connection, see `TonClient::failover_status`.
+    pub endpoint: usize,
+    /// Message hash returned by the liteserver.
+    pub hash: Option<Vec<u8>>,
+    pub error: Option<String>,
+    pub latency: Duration,
+}
+
+impl BroadcastAttempt {
+    pub fn is_accepted(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Result of `TonClient::broadcast_message`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MessageBroadcast {
+    pub hashes: ExtMessageHashes,
+    pub attempts: Vec<BroadcastAttempt>,
+}
+
+impl MessageBroadcast {
+    /// Whether at least one liteserver accepted the message.
+    pub fn is_accepted(&self) -> bool {
+        self.attempts.iter().any(BroadcastAttempt::is_accepted)
+    }
+
+    /// Attempts, where the liteserver returned a hash different from the local one, e.g. due to
+    /// different normalization.
+    pub fn hash_mismatches(&self) -> impl Iterator<Item = &BroadcastAttempt> {
+        self.attempts.iter().filter(|a| {
+            a.hash
+                .as_ref()
+                .is_some_and(|h| h[..] != self.hashes.hash[..])
+        })
+    }
+}
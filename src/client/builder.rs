@@ -1,17 +1,34 @@
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use super::TonConnectionCallback;
 use crate::client::{
-    error, ConnectionCheck, MultiConnectionCallback, RetryStrategy, TonClient, TonConnectionParams,
+    error, ArchiveRouting, ConnectionCheck, ConnectionMode, CostClass, FailoverStrategy,
+    MultiConnectionCallback, PoolAutoscaling, PoolOptions, Quota, RetryPolicy, RetryStrategy,
+    TonClient, TonConnectionParams, TonMiddleware, DEFAULT_RETRY_STRATEGY,
     LOGGING_CONNECTION_CALLBACK, NOOP_CONNECTION_CALLBACK,
 };
+use crate::config::TonConfig;
+#[cfg(feature = "metrics")]
+use crate::metrics::MetricsRegistry;
 
 pub struct TonClientBuilder {
     pool_size: usize,
     connection_params: TonConnectionParams,
-    retry_strategy: RetryStrategy,
+    retry_policy: RetryPolicy,
     callback: Arc<dyn TonConnectionCallback>,
     connection_check: ConnectionCheck,
+    connection_mode: ConnectionMode,
+    autoscaling: Option<PoolAutoscaling>,
+    quotas: HashMap<CostClass, Quota>,
+    middlewares: Vec<Arc<dyn TonMiddleware>>,
+    health_check_interval: Option<Duration>,
+    failover_configs: Vec<String>,
+    failover_strategy: Option<Arc<dyn FailoverStrategy>>,
+    archive_routing: Option<ArchiveRouting>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Arc<dyn MetricsRegistry>>,
 }
 
 impl TonClientBuilder {
@@ -19,9 +36,19 @@ impl TonClientBuilder {
         TonClientBuilder {
             pool_size: 1,
             connection_params: TonConnectionParams::default(),
-            retry_strategy: RetryStrategy::default(),
+            retry_policy: RetryPolicy::default(),
             callback: LOGGING_CONNECTION_CALLBACK.clone(),
             connection_check: ConnectionCheck::None,
+            connection_mode: ConnectionMode::Lazy,
+            autoscaling: None,
+            quotas: HashMap::new(),
+            middlewares: vec![],
+            health_check_interval: None,
+            failover_configs: vec![],
+            failover_strategy: None,
+            archive_routing: None,
+            #[cfg(feature = "metrics")]
+            metrics: None,
         }
     }
 
@@ -40,8 +67,21 @@ impl TonClientBuilder {
         self
     }
 
+    pub fn with_ton_config(&mut self, config: &TonConfig) -> &mut Self {
+        self.connection_params.config = config.to_string();
+        self
+    }
+
+    /// Sets retry of tonlib errors with code 500 at fixed interval.
     pub fn with_retry_strategy(&mut self, retry_strategy: &RetryStrategy) -> &mut Self {
-        self.retry_strategy = retry_strategy.clone();
+        self.retry_policy = RetryPolicy::from(retry_strategy);
+        self
+    }
+
+    /// Sets retry policy applied to all calls of the client. Default is `RetryPolicy::default()`,
+    /// which retries transient liteserver errors with exponential backoff.
+    pub fn with_retry_policy(&mut self, retry_policy: &RetryPolicy) -> &mut Self {
+        self.retry_policy = retry_policy.clone();
         self
     }
 
@@ -80,15 +120,124 @@ impl TonClientBuilder {
         self
     }
 
+    /// Sets when pool connections are established. Default is `ConnectionMode::Lazy`.
+    pub fn with_connection_mode(&mut self, connection_mode: ConnectionMode) -> &mut Self {
+        self.connection_mode = connection_mode;
+        self
+    }
+
+    /// Enables pool autoscaling. Pool size set by `with_pool_size` is ignored then.
+    pub fn with_autoscaling(&mut self, autoscaling: &PoolAutoscaling) -> &mut Self {
+        self.autoscaling = Some(autoscaling.clone());
+        self
+    }
+
+    pub fn without_autoscaling(&mut self) -> &mut Self {
+        self.autoscaling = None;
+        self
+    }
+
+    /// Limits calls of the cost class. Calls over the quota fail with `QuotaExceeded` error.
+    pub fn with_quota(&mut self, cost_class: CostClass, quota: &Quota) -> &mut Self {
+        self.quotas.insert(cost_class, quota.clone());
+        self
+    }
+
+    pub fn without_quotas(&mut self) -> &mut Self {
+        self.quotas.clear();
+        self
+    }
+
+    /// Adds middleware applied to all calls of the client, including calls made by contracts.
+    /// Middlewares are applied in the order they were added.
+    pub fn with_middleware(&mut self, middleware: Arc<dyn TonMiddleware>) -> &mut Self {
+        self.middlewares.push(middleware);
+        self
+    }
+
+    pub fn without_middlewares(&mut self) -> &mut Self {
+        self.middlewares.clear();
+        self
+    }
+
+    /// Enables periodic health checks of the pool. Dead connections and connections lagging
+    /// behind the pool are dropped and rebuilt on the next use. See `TonClient::pool_status`.
+    pub fn with_health_check_interval(&mut self, interval: Duration) -> &mut Self {
+        self.health_check_interval = Some(interval);
+        self
+    }
+
+    pub fn without_health_check(&mut self) -> &mut Self {
+        self.health_check_interval = None;
+        self
+    }
+
+    /// Adds config, which pool connections fail over to when the config set by `with_config`
+    /// and earlier added ones become unreachable.
+    pub fn with_failover_config(&mut self, config: &str) -> &mut Self {
+        self.failover_configs.push(config.to_string());
+        self
+    }
+
+    pub fn with_failover_ton_config(&mut self, config: &TonConfig) -> &mut Self {
+        self.with_failover_config(&config.to_string())
+    }
+
+    pub fn without_failover_configs(&mut self) -> &mut Self {
+        self.failover_configs.clear();
+        self
+    }
+
+    /// Sets how configs are selected for new connections. Default is `RoundRobinFailover`.
+    pub fn with_failover_strategy(&mut self, strategy: Arc<dyn FailoverStrategy>) -> &mut Self {
+        self.failover_strategy = Some(strategy);
+        self
+    }
+
+    /// Keeps archive connections in the pool and routes historical queries to them. Pool size
+    /// should exceed the number of archive connections, so that regular queries are not routed
+    /// to archive nodes.
+    pub fn with_archive_routing(&mut self, routing: &ArchiveRouting) -> &mut Self {
+        self.archive_routing = Some(routing.clone());
+        self
+    }
+
+    pub fn without_archive_routing(&mut self) -> &mut Self {
+        self.archive_routing = None;
+        self
+    }
+
+    /// Reports call counts, latencies, errors and pool utilization to the registry.
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics(&mut self, metrics: Arc<dyn MetricsRegistry>) -> &mut Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
     pub async fn build(&self) -> Result<TonClient, error::TonClientError> {
-        TonClient::new(
+        let client = TonClient::new_with_pool_options(
             self.pool_size,
             &self.connection_params,
-            &self.retry_strategy,
+            &DEFAULT_RETRY_STRATEGY,
             self.callback.clone(),
             self.connection_check.clone(),
-        )
-        .await
+            &PoolOptions {
+                autoscaling: self.autoscaling.clone(),
+                quotas: self.quotas.clone(),
+                middlewares: self.middlewares.clone(),
+                health_check_interval: self.health_check_interval,
+                retry_policy: Some(self.retry_policy.clone()),
+                failover_configs: self.failover_configs.clone(),
+                failover_strategy: self.failover_strategy.clone(),
+                archive_routing: self.archive_routing.clone(),
+                #[cfg(feature = "metrics")]
+                metrics: self.metrics.clone(),
+            },
+        )?;
+        if self.connection_mode == ConnectionMode::Eager {
+            client.warm_up().await?;
+        }
+        Ok(client)
     }
 }
 
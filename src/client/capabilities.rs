@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+use strum::IntoStaticStr;
+
+use crate::client::{TonClientError, TonClientInterface, TonConnection, TonlibErrorKind};
+use crate::tl::{LiteServerInfo, SmcLibraryQueryExt, TonFunction};
+
+/// Version of tonlib linked via `tonlib-sys`, must match the pinned `tonlib-sys` dependency.
+pub const TONLIB_VERSION: &str = "2024.6.1";
+
+/// Calls that are not supported by every liteserver and are probed before use.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum TonFeature {
+    /// `smc.getLibrariesExt`, backed by `liteServer.getLibrariesWithProof`.
+    GetLibrariesExt,
+}
+
+impl TonFeature {
+    pub const ALL: [TonFeature; 1] = [TonFeature::GetLibrariesExt];
+
+    /// Cheap call, which fails on liteservers not supporting the feature.
+    pub fn probe(&self) -> TonFunction {
+        match self {
+            TonFeature::GetLibrariesExt => TonFunction::SmcGetLibrariesExt {
+                list: vec![SmcLibraryQueryExt::One { hash: [0; 32] }],
+            },
+        }
+    }
+}
+
+/// Versions and capabilities of the tonlib and liteserver backing the client.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TonCapabilities {
+    pub tonlib_version: String,
+    pub liteserver_version: i32,
+    pub liteserver_capabilities: i64,
+    /// Features, which passed the probe.
+    pub features: Vec<TonFeature>,
+}
+
+impl TonCapabilities {
+    pub fn new(info: &LiteServerInfo, features: Vec<TonFeature>) -> TonCapabilities {
+        TonCapabilities {
+            tonlib_version: TONLIB_VERSION.to_string(),
+            liteserver_version: info.version,
+            liteserver_capabilities: info.capabilities,
+            features,
+        }
+    }
+
+    /// Requests liteserver info and probes all features on the connection.
+    pub async fn detect(conn: &TonConnection) -> Result<TonCapabilities, TonClientError> {
+        let info = conn.lite_server_get_info().await?;
+        let mut features = Vec::new();
+        for feature in TonFeature::ALL {
+            match conn.invoke(&feature.probe()).await {
+                Ok(_) => features.push(feature),
+                Err(e) if e.tonlib_error_kind() == Some(TonlibErrorKind::Request) => {
+                    log::debug!("{:?} is not supported by {}: {}", feature, conn.tag(), e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(TonCapabilities::new(&info, features))
+    }
+
+    /// Capabilities available on every member of a pool: the lowest version, common
+    /// capability bits and features. `None` if there are no members.
+    pub fn common<'a, I>(members: I) -> Option<TonCapabilities>
+    where
+        I: IntoIterator<Item = &'a TonCapabilities>,
+    {
+        members
+            .into_iter()
+            .cloned()
+            .reduce(|acc, c| TonCapabilities {
+                tonlib_version: acc.tonlib_version,
+                liteserver_version: acc.liteserver_version.min(c.liteserver_version),
+                liteserver_capabilities: acc.liteserver_capabilities & c.liteserver_capabilities,
+                features: acc
+                    .features
+                    .into_iter()
+                    .filter(|f| c.features.contains(f))
+                    .collect(),
+            })
+    }
+
+    pub fn supports(&self, feature: TonFeature) -> bool {
+        self.features.contains(&feature)
+    }
+
+    pub fn ensure_supported(&self, feature: TonFeature) -> Result<(), TonClientError> {
+        if self.supports(feature) {
+            Ok(())
+        } else {
+            Err(TonClientError::UnsupportedByBackend {
+                feature: feature.into(),
+                liteserver_version: self.liteserver_version,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::{TonCapabilities, TonClientError, TonFeature, TONLIB_VERSION};
+
+    /// Returns version of `tonlib-sys` from either `tonlib-sys = "..."` or normalized
+    /// `[dependencies.tonlib-sys]` layout of the manifest.
+    fn tonlib_sys_version(manifest: &str) -> Option<String> {
+        let mut in_table = false;
+        for line in manifest.lines().map(str::trim) {
+            if line.starts_with('[') {
+                in_table = line == "[dependencies.tonlib-sys]";
+                continue;
+            }
+            let value = if in_table {
+                line.strip_prefix("version")
+            } else {
+                line.strip_prefix("tonlib-sys")
+            };
+            if let Some(value) = value.and_then(|v| v.trim_start().strip_prefix('=')) {
+                let version = value.split('"').nth(1)?;
+                return Some(version.trim_start_matches('=').to_string());
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn test_tonlib_version_matches_manifest() -> anyhow::Result<()> {
+        let manifest = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"))?;
+        assert_eq!(
+            tonlib_sys_version(&manifest).as_deref(),
+            Some(TONLIB_VERSION)
+        );
+
+        let normalized = "[dependencies.tonlib-sys]\nversion = \"=2024.6.1\"\n";
+        assert_eq!(tonlib_sys_version(normalized).as_deref(), Some("2024.6.1"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_capabilities_gating() {
+        let capabilities = TonCapabilities {
+            tonlib_version: TONLIB_VERSION.to_string(),
+            liteserver_version: 0x101,
+            liteserver_capabilities: 7,
+            features: vec![TonFeature::GetLibrariesExt],
+        };
+        assert!(capabilities.supports(TonFeature::GetLibrariesExt));
+        assert!(capabilities
+            .ensure_supported(TonFeature::GetLibrariesExt)
+            .is_ok());
+
+        let outdated = TonCapabilities {
+            liteserver_version: 0x100,
+            liteserver_capabilities: 3,
+            features: vec![],
+            ..capabilities.clone()
+        };
+        let common = TonCapabilities::common([&capabilities, &outdated]).unwrap();
+        assert_eq!(common.liteserver_version, 0x100);
+        assert_eq!(common.liteserver_capabilities, 3);
+        assert!(!common.supports(TonFeature::GetLibrariesExt));
+        match common.ensure_supported(TonFeature::GetLibrariesExt) {
+            Err(TonClientError::UnsupportedByBackend {
+                feature,
+                liteserver_version,
+            }) => {
+                assert_eq!(feature, "get_libraries_ext");
+                assert_eq!(liteserver_version, 0x100);
+            }
+            r => panic!("Unexpected result: {:?}", r),
+        }
+        assert_eq!(TonCapabilities::common([]), None);
+        assert_eq!(TONLIB_VERSION, "2024.6.1");
+    }
+}
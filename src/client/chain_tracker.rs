@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+
+use crate::tl::BlockIdExt;
+
+/// Number of recent masterchain blocks remembered by default.
+pub const DEFAULT_CHAIN_TRACKER_DEPTH: usize = 64;
+
+/// Masterchain blocks replaced by another chain. Blocks from `from_seqno` on, which were
+/// delivered before, must be rolled back.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Reorg {
+    pub from_seqno: i32,
+    /// Remembered blocks, which are not in the chain anymore.
+    pub dropped: Vec<BlockIdExt>,
+}
+
+/// Remembers hashes of recent masterchain blocks and detects reorganizations: a block with
+/// another hash at a remembered seqno, or a block whose previous block is not the remembered
+/// one.
+#[derive(Debug, Clone)]
+pub struct ChainTracker {
+    depth: usize,
+    blocks: BTreeMap<i32, BlockIdExt>,
+}
+
+impl ChainTracker {
+    pub fn new(depth: usize) -> ChainTracker {
+        ChainTracker {
+            depth: depth.max(1),
+            blocks: BTreeMap::new(),
+        }
+    }
+
+    /// Checks the block against the remembered chain and remembers it, given its previous
+    /// block if known. On reorg the block is not remembered, and the replaced blocks are
+    /// forgotten.
+    pub fn observe(&mut self, block: &BlockIdExt, prev: Option<&BlockIdExt>) -> Option<Reorg> {
+        let from_seqno = if self.conflicts(block) {
+            Some(block.seqno)
+        } else {
+            prev.filter(|prev| self.conflicts(prev))
+                .map(|prev| prev.seqno)
+        };
+        if let Some(from_seqno) = from_seqno {
+            let dropped = self.blocks.split_off(&from_seqno).into_values().collect();
+            return Some(Reorg {
+                from_seqno,
+                dropped,
+            });
+        }
+        self.blocks.insert(block.seqno, block.clone());
+        while self.blocks.len() > self.depth {
+            self.blocks.pop_first();
+        }
+        None
+    }
+
+    pub fn get(&self, seqno: i32) -> Option<&BlockIdExt> {
+        self.blocks.get(&seqno)
+    }
+
+    fn conflicts(&self, block: &BlockIdExt) -> bool {
+        self.blocks.get(&block.seqno).is_some_and(|known| {
+            known.root_hash != block.root_hash || known.file_hash != block.file_hash
+        })
+    }
+}
+
+impl Default for ChainTracker {
+    fn default() -> Self {
+        ChainTracker::new(DEFAULT_CHAIN_TRACKER_DEPTH)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::{ChainTracker, Reorg};
+    use crate::tl::BlockIdExt;
+
+    fn block(seqno: i32, hash: &str) -> BlockIdExt {
+        BlockIdExt {
+            workchain: -1,
+            shard: i64::MIN,
+            seqno,
+            root_hash: hash.to_string(),
+            file_hash: hash.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_chain_tracker() {
+        let mut tracker = ChainTracker::new(3);
+        for seqno in 1..=4 {
+            let prev = block(seqno - 1, "a");
+            assert_eq!(tracker.observe(&block(seqno, "a"), Some(&prev)), None);
+        }
+        assert_eq!(tracker.get(1), None);
+        assert!(tracker.get(2).is_some());
+
+        // the same block again
+        assert_eq!(tracker.observe(&block(4, "a"), None), None);
+
+        // block 5 refers to another block 3
+        let reorg = tracker.observe(&block(5, "b"), Some(&block(3, "b")));
+        assert_eq!(
+            reorg,
+            Some(Reorg {
+                from_seqno: 3,
+                dropped: vec![block(3, "a"), block(4, "a")],
+            })
+        );
+        assert_eq!(tracker.get(4), None);
+        assert_eq!(tracker.observe(&block(3, "b"), Some(&block(2, "a"))), None);
+
+        // another block at a remembered seqno
+        let reorg = tracker.observe(&block(3, "c"), None).unwrap();
+        assert_eq!(reorg.from_seqno, 3);
+        assert_eq!(reorg.dropped, vec![block(3, "b")]);
+    }
+}
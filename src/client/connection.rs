@@ -328,11 +328,7 @@ fn run_loop(tag: String, weak_inner: Weak<Inner>, callback: Arc<dyn TonConnectio
                             .as_ref()
                             .map(|d| d.1.method)
                             .unwrap_or(NOT_AVAILABLE);
-                        Err(TonClientError::TonlibError {
-                            method,
-                            code,
-                            message,
-                        })
+                        Err(TonClientError::tonlib_error(method, code, message))
                     }
                     Err(e) => Err(e.into()),
                     Ok(r) => Ok(r),
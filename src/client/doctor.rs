@@ -0,0 +1,152 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::DEFAULT_HEALTH_CHECK_MAX_LAG;
+
+/// Clock difference between a liteserver and the local host, which is reported as an issue.
+pub const DEFAULT_DOCTOR_MAX_CLOCK_SKEW: Duration = Duration::from_secs(30);
+/// Age of the last masterchain block of a liteserver, which is reported as an issue.
+pub const DEFAULT_DOCTOR_MAX_HEAD_AGE: Duration = Duration::from_secs(60);
+
+/// Diagnostics of a pool member, returned by `TonClient::doctor`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ServerDiagnostics {
+    /// Tag of the connection, `None` if the member failed to connect.
+    pub tag: Option<String>,
+    /// Index of the config of the connection, see `TonClient::failover_status`.
+    pub endpoint: usize,
+    /// Archive member of a mixed pool, see `ArchiveRouting`.
+    pub archive: bool,
+    /// Liteserver version, as reported by `liteServer.getInfo`.
+    pub version: Option<i32>,
+    pub capabilities: Option<i64>,
+    /// Liteserver time minus local time, in seconds.
+    pub clock_skew: Option<i64>,
+    /// Last masterchain seqno known to the liteserver.
+    pub mc_seqno: Option<i32>,
+    /// Number of masterchain blocks the liteserver is behind the best pool member.
+    pub head_lag: Option<i32>,
+    /// Seconds since the last masterchain block known to the liteserver, by local time.
+    pub head_age: Option<i64>,
+    /// Whether the liteserver keeps the first masterchain block, i.e. is an archive node.
+    pub has_archive: Option<bool>,
+    /// Round-trip time of `liteServer.getInfo`.
+    pub latency: Option<Duration>,
+    /// First error, which interrupted the diagnostics.
+    pub error: Option<String>,
+}
+
+/// Problem found by `TonClient::doctor`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticIssue {
+    /// The member failed to connect or to answer.
+    Unreachable(String),
+    /// Liteserver clock differs from the local one by more than `DEFAULT_DOCTOR_MAX_CLOCK_SKEW`.
+    ClockSkew(i64),
+    /// The member is behind the pool by more than `DEFAULT_HEALTH_CHECK_MAX_LAG` blocks.
+    Lagging(i32),
+    /// The last masterchain block is older than `DEFAULT_DOCTOR_MAX_HEAD_AGE`.
+    StaleHead(i64),
+    /// Archive member of a mixed pool doesn't keep old blocks.
+    NoArchive,
+}
+
+impl ServerDiagnostics {
+    pub(crate) fn new(tag: Option<String>, endpoint: usize, archive: bool) -> ServerDiagnostics {
+        ServerDiagnostics {
+            tag,
+            endpoint,
+            archive,
+            version: None,
+            capabilities: None,
+            clock_skew: None,
+            mc_seqno: None,
+            head_lag: None,
+            head_age: None,
+            has_archive: None,
+            latency: None,
+            error: None,
+        }
+    }
+
+    /// Returns problems, which may cause stale or missing data.
+    pub fn issues(&self) -> Vec<DiagnosticIssue> {
+        let mut issues = Vec::new();
+        if let Some(error) = &self.error {
+            issues.push(DiagnosticIssue::Unreachable(error.clone()));
+        }
+        if let Some(skew) = self.clock_skew {
+            if skew.unsigned_abs() > DEFAULT_DOCTOR_MAX_CLOCK_SKEW.as_secs() {
+                issues.push(DiagnosticIssue::ClockSkew(skew));
+            }
+        }
+        if let Some(lag) = self.head_lag {
+            if lag > DEFAULT_HEALTH_CHECK_MAX_LAG {
+                issues.push(DiagnosticIssue::Lagging(lag));
+            }
+        }
+        if let Some(age) = self.head_age {
+            if age > DEFAULT_DOCTOR_MAX_HEAD_AGE.as_secs() as i64 {
+                issues.push(DiagnosticIssue::StaleHead(age));
+            }
+        }
+        if self.archive && self.has_archive == Some(false) {
+            issues.push(DiagnosticIssue::NoArchive);
+        }
+        issues
+    }
+}
+
+/// Sets head lag of each diagnosed member relative to the best one.
+pub(crate) fn fill_head_lag(diagnostics: &mut [ServerDiagnostics]) {
+    let best_seqno = diagnostics.iter().filter_map(|d| d.mc_seqno).max();
+    if let Some(best_seqno) = best_seqno {
+        for d in diagnostics.iter_mut() {
+            d.head_lag = d.mc_seqno.map(|seqno| best_seqno - seqno);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::{fill_head_lag, DiagnosticIssue, ServerDiagnostics};
+
+    #[test]
+    fn test_server_diagnostics_issues() {
+        let mut diagnostics = vec![
+            ServerDiagnostics::new(Some("a".to_string()), 0, false),
+            ServerDiagnostics::new(Some("b".to_string()), 0, true),
+            ServerDiagnostics::new(None, 1, false),
+        ];
+        diagnostics[0].mc_seqno = Some(100);
+        diagnostics[0].clock_skew = Some(-2);
+        diagnostics[0].head_age = Some(5);
+        diagnostics[1].mc_seqno = Some(90);
+        diagnostics[1].clock_skew = Some(-45);
+        diagnostics[1].head_age = Some(120);
+        diagnostics[1].has_archive = Some(false);
+        diagnostics[2].error = Some("connection refused".to_string());
+        fill_head_lag(&mut diagnostics);
+
+        assert_eq!(diagnostics[0].head_lag, Some(0));
+        assert!(diagnostics[0].issues().is_empty());
+        assert_eq!(
+            diagnostics[1].issues(),
+            vec![
+                DiagnosticIssue::ClockSkew(-45),
+                DiagnosticIssue::Lagging(10),
+                DiagnosticIssue::StaleHead(120),
+                DiagnosticIssue::NoArchive,
+            ]
+        );
+        assert_eq!(diagnostics[2].head_lag, None);
+        assert_eq!(
+            diagnostics[2].issues(),
+            vec![DiagnosticIssue::Unreachable(
+                "connection refused".to_string()
+            )]
+        );
+    }
+}
@@ -1,20 +1,35 @@
 use std::io;
+use std::time::Duration;
 
 use thiserror::Error;
 
 use crate::address::TonAddressParseError;
+use crate::cell::TonCellError;
+use crate::client::CostClass;
 use crate::tl::{TlError, TonResult, TonResultDiscriminants};
+use crate::types::{ContextualError, ErrorContext};
 
 #[derive(Error, Debug)]
 pub enum TonClientError {
     #[error("Internal error ({0})")]
     InternalError(String),
 
-    #[error("Tonlib error (Method: {method}, code: {code}, message: {message})")]
+    #[error(
+        "Tonlib error (Method: {method}, code: {code}, message: {message}){}",
+        context_suffix(.context)
+    )]
     TonlibError {
         method: &'static str,
         code: i32,
         message: String,
+        kind: TonlibErrorKind,
+        context: Box<ErrorContext>,
+    },
+
+    #[error("Cell error ({error}){}", context_suffix(.context))]
+    CellError {
+        error: TonCellError,
+        context: Box<ErrorContext>,
     },
 
     #[error("Unexpected TonResult (Actual: {actual}, expected: {expected})")]
@@ -31,9 +46,90 @@ pub enum TonClientError {
 
     #[error("TonAddressParseError: ({0})")]
     TonAddressParseError(#[from] TonAddressParseError),
+
+    #[error(
+        "Unsupported by backend (Feature: {feature}, liteserver version: {liteserver_version})"
+    )]
+    UnsupportedByBackend {
+        feature: &'static str,
+        liteserver_version: i32,
+    },
+
+    #[error("Invalid lookup block mode {0}, expected 1 (seqno), 2 (lt) or 4 (utime)")]
+    InvalidLookupBlockMode(i32),
+
+    #[error("No masterchain block generated at or before {utime} (first available block time: {first_available_utime})")]
+    BlockByTimeNotFound {
+        utime: i64,
+        first_available_utime: i64,
+    },
+
+    #[error("Message {message_hash} is not confirmed within {timeout:?}")]
+    MessageConfirmationTimeout {
+        message_hash: String,
+        timeout: Duration,
+    },
+
+    #[error("Message {message_hash} is expired at {valid_until} without a transaction")]
+    MessageExpired {
+        message_hash: String,
+        valid_until: u32,
+    },
+
+    #[error("Quota exceeded (Cost class: {class:?}, limit: {limit} per {period:?})")]
+    QuotaExceeded {
+        class: CostClass,
+        limit: usize,
+        period: Duration,
+    },
 }
 
 impl TonClientError {
+    /// Returns tonlib error of the method classified by its code and message.
+    pub fn tonlib_error(method: &'static str, code: i32, message: String) -> TonClientError {
+        let kind = TonlibErrorKind::classify(code, &message);
+        TonClientError::TonlibError {
+            method,
+            code,
+            message,
+            kind,
+            context: Box::default(),
+        }
+    }
+
+    /// Returns kind of the underlying tonlib error, if any.
+    pub fn tonlib_error_kind(&self) -> Option<TonlibErrorKind> {
+        match self {
+            TonClientError::TonlibError { kind, .. } => Some(*kind),
+            _ => None,
+        }
+    }
+
+    /// Checks if the call may succeed when repeated, e.g. on another liteserver.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            TonClientError::TonlibError { kind, .. } => kind.is_retryable(),
+            TonClientError::Io(e) => matches!(
+                e.kind(),
+                io::ErrorKind::ConnectionRefused
+                    | io::ErrorKind::ConnectionReset
+                    | io::ErrorKind::ConnectionAborted
+                    | io::ErrorKind::BrokenPipe
+                    | io::ErrorKind::TimedOut
+            ),
+            _ => false,
+        }
+    }
+
+    /// Returns context of the error. Only tonlib and cell errors carry context.
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            TonClientError::TonlibError { context, .. }
+            | TonClientError::CellError { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
     pub fn unexpected_ton_result(
         expected: TonResultDiscriminants,
         actual: TonResult,
@@ -44,3 +140,86 @@ impl TonClientError {
         }
     }
 }
+
+/// Kind of tonlib error. Tonlib reports liteserver and network failures as error messages, which
+/// are classified once here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TonlibErrorKind {
+    /// Block is not known to the liteserver, e.g. it is not yet applied.
+    BlockNotFound,
+    /// Requested data is not in the database of the liteserver, e.g. it is lagging or pruned.
+    NotInDb,
+    /// Liteserver is not synchronized yet.
+    NotReady,
+    Timeout,
+    /// Liteserver is unreachable.
+    Network,
+    /// Other internal error of the liteserver (code 500).
+    Server,
+    /// Error caused by the request, e.g. invalid address.
+    Request,
+}
+
+impl TonlibErrorKind {
+    pub fn classify(code: i32, message: &str) -> TonlibErrorKind {
+        let message = message.to_lowercase();
+        if message.contains("not in db") {
+            TonlibErrorKind::NotInDb
+        } else if message.contains("block not found") || message.contains("block is not applied") {
+            TonlibErrorKind::BlockNotFound
+        } else if message.contains("timeout") || message.contains("timed out") {
+            TonlibErrorKind::Timeout
+        } else if message.contains("connection refused") || message.contains("connection closed") {
+            TonlibErrorKind::Network
+        } else if message.contains("lite_server_notready") {
+            TonlibErrorKind::NotReady
+        } else if code == 500 {
+            TonlibErrorKind::Server
+        } else {
+            TonlibErrorKind::Request
+        }
+    }
+
+    /// Checks if the error is transient. Missing blocks and data are not retried, as they are
+    /// usually missing for long, e.g. pruned, and callers and the archive fallback expect to
+    /// learn about them quickly.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            TonlibErrorKind::NotReady
+                | TonlibErrorKind::Timeout
+                | TonlibErrorKind::Network
+                | TonlibErrorKind::Server
+        )
+    }
+}
+
+impl From<TonCellError> for TonClientError {
+    fn from(error: TonCellError) -> Self {
+        TonClientError::CellError {
+            error,
+            context: Box::default(),
+        }
+    }
+}
+
+impl ContextualError for TonClientError {
+    fn add_context(mut self, outer: ErrorContext) -> Self {
+        match &mut self {
+            TonClientError::TonlibError { context, .. }
+            | TonClientError::CellError { context, .. } => {
+                **context = std::mem::take(&mut **context).merge(outer);
+            }
+            _ => {}
+        }
+        self
+    }
+}
+
+fn context_suffix(context: &ErrorContext) -> String {
+    if context.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", context)
+    }
+}
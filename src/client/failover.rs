@@ -0,0 +1,192 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+/// Number of consecutive failures after which a config is considered unreachable.
+pub const DEFAULT_FAILOVER_THRESHOLD: usize = 3;
+
+/// Status of a network config, which pool connections fail over between.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct FailoverEndpointStatus {
+    /// Consecutive failures of connecting or calling with the config.
+    pub failures: usize,
+    /// Average latency of calls, `None` if no calls succeeded yet.
+    pub latency: Option<Duration>,
+}
+
+impl FailoverEndpointStatus {
+    pub fn is_available(&self) -> bool {
+        self.failures < DEFAULT_FAILOVER_THRESHOLD
+    }
+}
+
+/// Selects the config used for a new pool connection.
+pub trait FailoverStrategy: Send + Sync {
+    /// Returns index of the config in `endpoints`, which is never empty.
+    fn select(&self, endpoints: &[FailoverEndpointStatus]) -> usize;
+}
+
+/// Uses available configs in turn.
+#[derive(Default)]
+pub struct RoundRobinFailover {
+    counter: AtomicUsize,
+}
+
+impl FailoverStrategy for RoundRobinFailover {
+    fn select(&self, endpoints: &[FailoverEndpointStatus]) -> usize {
+        let start = self.counter.fetch_add(1, Ordering::SeqCst);
+        (start..start + endpoints.len())
+            .map(|i| i % endpoints.len())
+            .find(|i| endpoints[*i].is_available())
+            .unwrap_or(start % endpoints.len())
+    }
+}
+
+/// Uses the available config with the lowest latency. Configs without measured latency are
+/// tried first.
+#[derive(Default)]
+pub struct LowestLatencyFailover;
+
+impl FailoverStrategy for LowestLatencyFailover {
+    fn select(&self, endpoints: &[FailoverEndpointStatus]) -> usize {
+        endpoints
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.is_available())
+            .min_by_key(|(_, e)| e.latency.unwrap_or(Duration::ZERO))
+            .or_else(|| endpoints.iter().enumerate().min_by_key(|(_, e)| e.failures))
+            .map(|(i, _)| i)
+            .unwrap_or(0)
+    }
+}
+
+/// Uses the same config until it becomes unavailable, then switches to the next one.
+#[derive(Default)]
+pub struct StickyFailover {
+    current: AtomicUsize,
+}
+
+impl FailoverStrategy for StickyFailover {
+    fn select(&self, endpoints: &[FailoverEndpointStatus]) -> usize {
+        let current = self.current.load(Ordering::SeqCst) % endpoints.len();
+        let selected = (current..current + endpoints.len())
+            .map(|i| i % endpoints.len())
+            .find(|i| endpoints[*i].is_available())
+            .unwrap_or((current + 1) % endpoints.len());
+        self.current.store(selected, Ordering::SeqCst);
+        selected
+    }
+}
+
+/// Configs of the pool together with their statuses.
+pub(crate) struct Failover {
+    configs: Vec<String>,
+    statuses: Mutex<Vec<FailoverEndpointStatus>>,
+    strategy: Arc<dyn FailoverStrategy>,
+}
+
+impl Failover {
+    pub(crate) fn new(configs: Vec<String>, strategy: Arc<dyn FailoverStrategy>) -> Failover {
+        let statuses = vec![FailoverEndpointStatus::default(); configs.len()];
+        Failover {
+            configs,
+            statuses: Mutex::new(statuses),
+            strategy,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.configs.len()
+    }
+
+    pub(crate) fn select(&self) -> (usize, &str) {
+        let i = self.strategy.select(&self.statuses()) % self.configs.len();
+        (i, &self.configs[i])
+    }
+
+    pub(crate) fn statuses(&self) -> Vec<FailoverEndpointStatus> {
+        self.statuses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    pub(crate) fn report_failure(&self, index: usize) {
+        let mut statuses = self.statuses.lock().unwrap_or_else(PoisonError::into_inner);
+        statuses[index].failures += 1;
+    }
+
+    pub(crate) fn report_success(&self, index: usize, latency: Duration) {
+        let mut statuses = self.statuses.lock().unwrap_or_else(PoisonError::into_inner);
+        let status = &mut statuses[index];
+        status.failures = 0;
+        // exponential moving average
+        status.latency = Some(match status.latency {
+            Some(avg) => (avg * 4 + latency) / 5,
+            None => latency,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::client::failover::Failover;
+    use crate::client::{
+        FailoverEndpointStatus, FailoverStrategy, LowestLatencyFailover, RoundRobinFailover,
+        StickyFailover, DEFAULT_FAILOVER_THRESHOLD,
+    };
+
+    fn endpoint(failures: usize, latency_ms: Option<u64>) -> FailoverEndpointStatus {
+        FailoverEndpointStatus {
+            failures,
+            latency: latency_ms.map(Duration::from_millis),
+        }
+    }
+
+    #[test]
+    fn test_round_robin_failover() {
+        let strategy = RoundRobinFailover::default();
+        let endpoints = vec![endpoint(0, None), endpoint(5, None), endpoint(0, None)];
+        let selected: Vec<_> = (0..4).map(|_| strategy.select(&endpoints)).collect();
+        assert_eq!(selected, vec![0, 2, 2, 0]);
+    }
+
+    #[test]
+    fn test_lowest_latency_failover() {
+        let strategy = LowestLatencyFailover;
+        let endpoints = vec![
+            endpoint(0, Some(30)),
+            endpoint(0, Some(10)),
+            endpoint(5, Some(1)),
+        ];
+        assert_eq!(strategy.select(&endpoints), 1);
+        let endpoints = vec![endpoint(0, Some(30)), endpoint(0, None)];
+        assert_eq!(strategy.select(&endpoints), 1);
+        let endpoints = vec![endpoint(7, None), endpoint(5, None)];
+        assert_eq!(strategy.select(&endpoints), 1);
+    }
+
+    #[test]
+    fn test_sticky_failover() {
+        let failover = Failover::new(
+            vec!["a".to_string(), "b".to_string()],
+            Arc::new(StickyFailover::default()),
+        );
+        assert_eq!(failover.select(), (0, "a"));
+        failover.report_success(0, Duration::from_millis(10));
+        assert_eq!(failover.select(), (0, "a"));
+        for _ in 0..DEFAULT_FAILOVER_THRESHOLD {
+            failover.report_failure(0);
+        }
+        assert_eq!(failover.select(), (1, "b"));
+        failover.report_success(0, Duration::from_millis(20));
+        assert_eq!(failover.select(), (1, "b"));
+        assert_eq!(
+            failover.statuses()[0].latency,
+            Some(Duration::from_millis(12))
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+/// Number of masterchain blocks a connection may fall behind the best connection of the pool.
+pub const DEFAULT_HEALTH_CHECK_MAX_LAG: i32 = 5;
+
+/// Health of a pool connection as seen by the last health check.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionHealth {
+    /// Not checked since the connection was established.
+    #[default]
+    Unknown,
+    Healthy,
+    /// The connection is behind the pool by more than `DEFAULT_HEALTH_CHECK_MAX_LAG` blocks.
+    Lagging,
+    /// The connection thread exited, or the probe failed or timed out.
+    Dead,
+}
+
+impl ConnectionHealth {
+    /// Returns health of the probed connection, given masterchain seqno of the best connection.
+    pub fn of(probe: Option<i32>, best_seqno: i32) -> ConnectionHealth {
+        match probe {
+            None => ConnectionHealth::Dead,
+            Some(seqno) if best_seqno - seqno > DEFAULT_HEALTH_CHECK_MAX_LAG => {
+                ConnectionHealth::Lagging
+            }
+            Some(_) => ConnectionHealth::Healthy,
+        }
+    }
+
+    /// Whether the connection is dropped and rebuilt on the next use.
+    pub fn is_evicted(&self) -> bool {
+        matches!(self, ConnectionHealth::Lagging | ConnectionHealth::Dead)
+    }
+}
+
+/// Status of a pool member, returned by `TonClient::pool_status`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PoolConnectionStatus {
+    /// Tag of the connection, `None` if the member is not connected.
+    pub tag: Option<String>,
+    /// Index of the config of the connection, see `TonClient::failover_status`.
+    pub endpoint: usize,
+    pub health: ConnectionHealth,
+    /// Last masterchain seqno seen by the health check.
+    pub mc_seqno: Option<i32>,
+    pub in_flight: usize,
+    /// Number of times the connection was evicted by health checks.
+    pub evictions: usize,
+    /// Archive member of a mixed pool, see `ArchiveRouting`.
+    #[serde(default)]
+    pub archive: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::ConnectionHealth;
+
+    #[test]
+    fn test_connection_health() {
+        assert_eq!(ConnectionHealth::of(None, 100), ConnectionHealth::Dead);
+        assert_eq!(
+            ConnectionHealth::of(Some(100), 100),
+            ConnectionHealth::Healthy
+        );
+        assert_eq!(
+            ConnectionHealth::of(Some(95), 100),
+            ConnectionHealth::Healthy
+        );
+        assert_eq!(
+            ConnectionHealth::of(Some(94), 100),
+            ConnectionHealth::Lagging
+        );
+        assert!(ConnectionHealth::Dead.is_evicted());
+        assert!(ConnectionHealth::Lagging.is_evicted());
+        assert!(!ConnectionHealth::Unknown.is_evicted());
+    }
+}
@@ -2,7 +2,7 @@ use async_trait::async_trait;
 
 use super::{SmcLibraryQueryExt, SmcLibraryResult, SmcLibraryResultExt, TonLibraryId};
 use crate::address::TonAddress;
-use crate::client::{TonClientError, TonConnection};
+use crate::client::{TonClientError, TonConnection, TonFeature};
 use crate::contract::LoadedSmcState;
 use crate::tl::{
     AccountAddress, BlockId, BlockIdExt, BlocksAccountTransactionId, BlocksHeader,
@@ -10,6 +10,14 @@ use crate::tl::{
     FullAccountState, InternalTransactionId, LiteServerInfo, RawFullAccountState, RawTransactions,
     TonFunction, TonResult, TonResultDiscriminants, TvmCell,
 };
+use crate::types::WithErrorContext;
+
+/// `lookup_block` mode to find block by `block_id.seqno`.
+pub const LOOKUP_BLOCK_BY_SEQNO: i32 = 1;
+/// `lookup_block` mode to find block containing logical time `lt`.
+pub const LOOKUP_BLOCK_BY_LT: i32 = 2;
+/// `lookup_block` mode to find block generated at `utime`.
+pub const LOOKUP_BLOCK_BY_UTIME: i32 = 4;
 
 #[async_trait]
 pub trait TonClientInterface: Send + Sync {
@@ -24,6 +32,13 @@ pub trait TonClientInterface: Send + Sync {
         self.invoke_on_connection(function).await.map(|(_, r)| r)
     }
 
+    /// Checks that the backend supports the feature before invoking the call depending on it.
+    ///
+    /// Default implementation doesn't perform any checks.
+    async fn ensure_supported(&self, _feature: TonFeature) -> Result<(), TonClientError> {
+        Ok(())
+    }
+
     async fn get_raw_account_state(
         &self,
         account_address: &TonAddress,
@@ -33,7 +48,7 @@ pub trait TonClientInterface: Send + Sync {
                 account_address: account_address.to_hex(),
             },
         };
-        let result = self.invoke(&func).await?;
+        let result = self.invoke(&func).await.with_address(account_address)?;
         match result {
             TonResult::RawFullAccountState(state) => Ok(state),
             r => Err(TonClientError::unexpected_ton_result(
@@ -54,7 +69,7 @@ pub trait TonClientInterface: Send + Sync {
             },
             transaction_id: transaction_id.clone(),
         };
-        let result = self.invoke(&func).await?;
+        let result = self.invoke(&func).await.with_address(account_address)?;
         match result {
             TonResult::RawFullAccountState(state) => Ok(state),
             r => Err(TonClientError::unexpected_ton_result(
@@ -75,7 +90,7 @@ pub trait TonClientInterface: Send + Sync {
             },
             from_transaction_id: from_transaction_id.clone(),
         };
-        let result = self.invoke(&func).await?;
+        let result = self.invoke(&func).await.with_address(account_address)?;
         match result {
             TonResult::RawTransactions(state) => Ok(state),
             r => Err(TonClientError::unexpected_ton_result(
@@ -100,7 +115,7 @@ pub trait TonClientInterface: Send + Sync {
             count: count as u32,
             try_decode_messages,
         };
-        let result = self.invoke(&func).await?;
+        let result = self.invoke(&func).await.with_address(account_address)?;
         match result {
             TonResult::RawTransactions(state) => Ok(state),
             r => Err(TonClientError::unexpected_ton_result(
@@ -152,7 +167,29 @@ pub trait TonClientInterface: Send + Sync {
                 account_address: account_address.to_hex(),
             },
         };
-        let result = self.invoke(&func).await?;
+        let result = self.invoke(&func).await.with_address(account_address)?;
+        match result {
+            TonResult::FullAccountState(state) => Ok(state),
+            r => Err(TonClientError::unexpected_ton_result(
+                TonResultDiscriminants::FullAccountState,
+                r,
+            )),
+        }
+    }
+
+    /// Returns parsed state of the account right after the transaction.
+    async fn get_account_state_by_transaction(
+        &self,
+        account_address: &TonAddress,
+        transaction_id: &InternalTransactionId,
+    ) -> Result<FullAccountState, TonClientError> {
+        let func = TonFunction::GetAccountStateByTransaction {
+            account_address: AccountAddress {
+                account_address: account_address.to_hex(),
+            },
+            transaction_id: transaction_id.clone(),
+        };
+        let result = self.invoke(&func).await.with_address(account_address)?;
         match result {
             TonResult::FullAccountState(state) => Ok(state),
             r => Err(TonClientError::unexpected_ton_result(
@@ -162,6 +199,27 @@ pub trait TonClientInterface: Send + Sync {
         }
     }
 
+    /// Returns `ShardAccount` of the account, including its storage stat, as stored in the
+    /// shard state.
+    async fn get_shard_account_cell(
+        &self,
+        account_address: &TonAddress,
+    ) -> Result<TvmCell, TonClientError> {
+        let func = TonFunction::GetShardAccountCell {
+            account_address: AccountAddress {
+                account_address: account_address.to_hex(),
+            },
+        };
+        let result = self.invoke(&func).await.with_address(account_address)?;
+        match result {
+            TonResult::TvmCell(cell) => Ok(cell),
+            r => Err(TonClientError::unexpected_ton_result(
+                TonResultDiscriminants::TvmCell,
+                r,
+            )),
+        }
+    }
+
     async fn smc_load(
         &self,
         account_address: &TonAddress,
@@ -270,6 +328,7 @@ pub trait TonClientInterface: Send + Sync {
         &self,
         list: &[SmcLibraryQueryExt],
     ) -> Result<SmcLibraryResultExt, TonClientError> {
+        self.ensure_supported(TonFeature::GetLibrariesExt).await?;
         let func = TonFunction::SmcGetLibrariesExt {
             list: list.to_vec(),
         };
@@ -304,7 +363,7 @@ pub trait TonClientInterface: Send + Sync {
         let func = TonFunction::BlocksGetShards {
             id: block_id.clone(),
         };
-        let result = self.invoke(&func).await?;
+        let result = self.invoke(&func).await.with_block(block_id)?;
         match result {
             TonResult::BlocksShards(result) => Ok(result),
             r => Err(TonClientError::unexpected_ton_result(
@@ -316,7 +375,11 @@ pub trait TonClientInterface: Send + Sync {
 
     /// Attempts to find block by specified query.
     ///
-    /// * `mode`: Lookup mode: `1` - by `block_id.seqno`, `2` - by `lt`, `4` - by `utime`.
+    /// * `mode`: Lookup mode: `1` - by `block_id.seqno`, `2` - by `lt`, `4` - by `utime`,
+    ///   see `LOOKUP_BLOCK_BY_SEQNO`, `LOOKUP_BLOCK_BY_LT` and `LOOKUP_BLOCK_BY_UTIME`.
+    ///   Exactly one mode must be set.
+    ///
+    /// `TonBlockFunctions::lookup_block_by_lt` and `lookup_block_by_utime` are more convenient.
     async fn lookup_block(
         &self,
         mode: i32,
@@ -324,6 +387,12 @@ pub trait TonClientInterface: Send + Sync {
         lt: i64,
         utime: i32,
     ) -> Result<BlockIdExt, TonClientError> {
+        if !matches!(
+            mode,
+            LOOKUP_BLOCK_BY_SEQNO | LOOKUP_BLOCK_BY_LT | LOOKUP_BLOCK_BY_UTIME
+        ) {
+            return Err(TonClientError::InvalidLookupBlockMode(mode));
+        }
         let func = TonFunction::BlocksLookupBlock {
             mode,
             id: block_id.clone(),
@@ -361,7 +430,7 @@ pub trait TonClientInterface: Send + Sync {
             count,
             after: after.clone(),
         };
-        let result = self.invoke(&func).await?;
+        let result = self.invoke(&func).await.with_block(block_id)?;
         match result {
             TonResult::BlocksTransactions(result) => Ok(result),
             r => Err(TonClientError::unexpected_ton_result(
@@ -384,7 +453,7 @@ pub trait TonClientInterface: Send + Sync {
             count,
             after: after.clone(),
         };
-        let result = self.invoke(&func).await?;
+        let result = self.invoke(&func).await.with_block(block_id)?;
         match result {
             TonResult::BlocksTransactionsExt(result) => Ok(result),
             r => Err(TonClientError::unexpected_ton_result(
@@ -406,6 +475,16 @@ pub trait TonClientInterface: Send + Sync {
         }
     }
 
+    /// Returns liteserver version, as reported by `liteServer.getInfo`.
+    async fn lite_server_get_version(&self) -> Result<i32, TonClientError> {
+        Ok(self.lite_server_get_info().await?.version)
+    }
+
+    /// Returns current unix time of the liteserver.
+    async fn lite_server_get_time(&self) -> Result<i64, TonClientError> {
+        Ok(self.lite_server_get_info().await?.now)
+    }
+
     async fn get_block_header(
         &self,
         block_id: &BlockIdExt,
@@ -413,7 +492,7 @@ pub trait TonClientInterface: Send + Sync {
         let func = TonFunction::GetBlockHeader {
             id: block_id.clone(),
         };
-        let result = self.invoke(&func).await?;
+        let result = self.invoke(&func).await.with_block(block_id)?;
         match result {
             TonResult::BlocksHeader(header) => Ok(header),
             r => Err(TonClientError::unexpected_ton_result(
@@ -0,0 +1,41 @@
+use crate::tl::{BlockIdExt, BlocksHeader, BlocksMasterchainInfo, LiteServerInfo};
+
+/// Masterchain info of a liteserver together with the time of its last block, the analog of
+/// `liteServer.masterchainInfoExt`, which tonlib doesn't expose.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MasterchainInfoExt {
+    /// Liteserver version, as reported by `liteServer.getInfo`.
+    pub version: i32,
+    pub capabilities: i64,
+    pub last: BlockIdExt,
+    /// Generation time of the last block.
+    pub last_utime: i64,
+    /// Current time of the liteserver.
+    pub now: i64,
+    /// Hash of the masterchain state of the last block.
+    pub state_root_hash: Vec<u8>,
+    pub init: BlockIdExt,
+}
+
+impl MasterchainInfoExt {
+    pub fn new(
+        info: BlocksMasterchainInfo,
+        last_header: &BlocksHeader,
+        server_info: &LiteServerInfo,
+    ) -> MasterchainInfoExt {
+        MasterchainInfoExt {
+            version: server_info.version,
+            capabilities: server_info.capabilities,
+            last: info.last,
+            last_utime: last_header.gen_utime,
+            now: server_info.now,
+            state_root_hash: info.state_root_hash,
+            init: info.init,
+        }
+    }
+
+    /// Returns seconds since the last block was generated, by the liteserver clock.
+    pub fn last_block_age(&self) -> i64 {
+        self.now - self.last_utime
+    }
+}
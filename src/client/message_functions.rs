@@ -0,0 +1,290 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use tokio::time::{self, Instant};
+
+use crate::address::TonAddress;
+use crate::cell::BagOfCells;
+use crate::client::{TonClientError, TonClientInterface, TransactionHistoryStream};
+use crate::tl::{InternalTransactionId, RawTransaction};
+use crate::transaction::{ParsedTx, TxMessageInfo};
+use crate::types::{TonHash, WithErrorContext, TON_HASH_BYTES};
+
+const MESSAGE_POLL_INTERVAL_MS: u64 = 1000;
+
+/// High-level functions for sending messages.
+#[async_trait]
+pub trait TonMessageFunctions: TonClientInterface + Send + Sync {
+    /// Sends the external message (BoC) and returns its hash computed by tonlib.
+    ///
+    /// This is the hash of the message cell, which is `ParsedTx::in_msg_hash` of the
+    /// transaction processing the message.
+    async fn send_message_return_hash(&self, boc: &[u8]) -> Result<TonHash, TonClientError> {
+        let hash = self.send_raw_message_return_hash(boc).await?;
+        message_hash(hash)
+    }
+
+    /// Sends the external message (BoC) and waits for the transaction of the destination
+    /// account which processed it.
+    ///
+    /// Contracts may reject external messages (e.g. with a wrong seqno) without a transaction.
+    /// `TonClientError::MessageExpired` is returned once the account state is synced past
+    /// `valid_until` of the message without the transaction, so the message can no longer be
+    /// processed. `TonClientError::MessageConfirmationTimeout` is returned if neither happens
+    /// within `timeout`, the message may still be processed after this error.
+    async fn send_message_and_wait(
+        &self,
+        boc: &[u8],
+        valid_until: u32,
+        timeout: Duration,
+    ) -> Result<RawTransaction, TonClientError>
+    where
+        Self: Clone + 'static,
+    {
+        let dest = external_message_destination(boc)?;
+        let start_lt = self
+            .get_raw_account_state(&dest)
+            .await?
+            .last_transaction_id
+            .lt;
+        let hash = self.send_message_return_hash(boc).await?;
+        let deadline = Instant::now() + timeout;
+        let mut scanned_lt = start_lt;
+        loop {
+            let state = self.get_raw_account_state(&dest).await?;
+            let last_tx_id = state.last_transaction_id;
+            if last_tx_id.lt > scanned_lt {
+                if let Some(tx) = self
+                    .find_transaction_by_in_msg_hash(&dest, &last_tx_id, scanned_lt, &hash)
+                    .await?
+                {
+                    return Ok(tx);
+                }
+                scanned_lt = last_tx_id.lt;
+            }
+            if state.sync_utime > valid_until as i64 {
+                return Err(TonClientError::MessageExpired {
+                    message_hash: hex::encode(hash),
+                    valid_until,
+                });
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(TonClientError::MessageConfirmationTimeout {
+                    message_hash: hex::encode(hash),
+                    timeout,
+                });
+            }
+            time::sleep((deadline - now).min(Duration::from_millis(MESSAGE_POLL_INTERVAL_MS)))
+                .await;
+        }
+    }
+
+    /// Looks for the transaction of the account processing the incoming message with the hash,
+    /// among transactions from `from_transaction_id` back to `after_lt` (exclusive).
+    async fn find_transaction_by_in_msg_hash(
+        &self,
+        address: &TonAddress,
+        from_transaction_id: &InternalTransactionId,
+        after_lt: i64,
+        in_msg_hash: &[u8],
+    ) -> Result<Option<RawTransaction>, TonClientError>
+    where
+        Self: Clone + 'static,
+    {
+        let mut txs =
+            TransactionHistoryStream::new(self, address, Some(from_transaction_id.clone()));
+        while let Some(tx) = txs.try_next().await? {
+            if tx.transaction_id.lt <= after_lt {
+                break;
+            }
+            let parsed = ParsedTx::try_from(&tx)
+                .map_err(TonClientError::from)
+                .with_address(address)?;
+            if parsed.in_msg_hash.as_ref().map(|h| h.as_slice()) == Some(in_msg_hash) {
+                return Ok(Some(tx));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<T> TonMessageFunctions for T where T: TonClientInterface + Send + Sync {}
+
+fn message_hash(hash: Vec<u8>) -> Result<TonHash, TonClientError> {
+    let len = hash.len();
+    hash.try_into().map_err(|_| {
+        TonClientError::InternalError(format!(
+            "Invalid message hash length {}, expected {}",
+            len, TON_HASH_BYTES
+        ))
+    })
+}
+
+fn external_message_destination(boc: &[u8]) -> Result<TonAddress, TonClientError> {
+    let info = BagOfCells::parse(boc)
+        .and_then(|boc| TxMessageInfo::parse(boc.single_root()?))
+        .map_err(|e| TonClientError::InternalError(format!("Invalid message: {}", e)))?;
+    match info {
+        TxMessageInfo::ExternalIn { dest, .. } => Ok(dest),
+        _ => Err(TonClientError::InternalError(
+            "Message is not an external inbound message".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use num_bigint::BigUint;
+
+    use crate::address::TonAddress;
+    use crate::cell::{BagOfCells, Cell, CellBuilder};
+    use crate::client::message_functions::{external_message_destination, message_hash};
+    use crate::client::{TonClientError, TonClientInterface, TonConnection, TonMessageFunctions};
+    use crate::tl::{
+        AccountAddress, InternalTransactionId, RawTransaction, RawTransactions, TonFunction,
+        TonResult, NULL_TRANSACTION_ID,
+    };
+
+    #[derive(Clone)]
+    struct TransactionsClient {
+        transactions: Vec<RawTransaction>,
+    }
+
+    #[async_trait]
+    impl TonClientInterface for TransactionsClient {
+        async fn get_connection(&self) -> Result<TonConnection, TonClientError> {
+            Err(TonClientError::InternalError("No connection".to_string()))
+        }
+
+        async fn invoke_on_connection(
+            &self,
+            _function: &TonFunction,
+        ) -> Result<(TonConnection, TonResult), TonClientError> {
+            Err(TonClientError::InternalError("No connection".to_string()))
+        }
+
+        async fn invoke(&self, function: &TonFunction) -> Result<TonResult, TonClientError> {
+            match function {
+                TonFunction::RawGetTransactionsV2 { .. } => {
+                    Ok(TonResult::RawTransactions(RawTransactions {
+                        transactions: self.transactions.clone(),
+                        previous_transaction_id: NULL_TRANSACTION_ID.clone(),
+                    }))
+                }
+                _ => Err(TonClientError::InternalError("Unexpected call".to_string())),
+            }
+        }
+    }
+
+    fn external_message(dest: &TonAddress, body: u32) -> anyhow::Result<Cell> {
+        let message = CellBuilder::new()
+            .store_u8(2, 0b10)?
+            .store_u8(2, 0)?
+            .store_address(dest)?
+            .store_coins(&BigUint::from(0u32))?
+            .store_bit(false)?
+            .store_bit(false)?
+            .store_u32(32, body)?
+            .build()?;
+        Ok(message)
+    }
+
+    /// Builds transaction of the account processing the message, with split-prepare
+    /// description to skip the phases.
+    fn transaction(account: &TonAddress, lt: i64, in_msg: Cell) -> anyhow::Result<RawTransaction> {
+        let msgs = CellBuilder::new()
+            .store_bit(true)?
+            .store_child(in_msg)?
+            .store_bit(false)?
+            .build()?;
+        let description = CellBuilder::new().store_u8(4, 0b0100)?.build()?;
+        let tx = CellBuilder::new()
+            .store_u8(4, 0b0111)?
+            .store_slice(&account.hash_part)?
+            .store_u64(64, lt as u64)?
+            .store_slice(&[0; 32])?
+            .store_u64(64, 0)?
+            .store_u32(32, 1_700_000_000)?
+            .store_u32(19, 0)?
+            .store_child(msgs)?
+            .store_coins(&BigUint::from(0u32))?
+            .store_bit(false)?
+            .store_child(Cell::default())?
+            .store_child(description)?
+            .build()?;
+        Ok(RawTransaction {
+            address: AccountAddress {
+                account_address: account.to_hex(),
+            },
+            utime: 1_700_000_000,
+            data: BagOfCells::from_root(tx).serialize(true)?,
+            transaction_id: InternalTransactionId {
+                lt,
+                hash: vec![0; 32],
+            },
+            storage_fee: 0,
+            other_fee: 0,
+            in_msg: None,
+            out_msgs: vec![],
+        })
+    }
+
+    #[tokio::test]
+    async fn test_find_transaction_by_in_msg_hash() -> anyhow::Result<()> {
+        let dest = TonAddress::new(0, &[1; 32]);
+        let sent = external_message(&dest, 1)?;
+        let hash = sent.cell_hash();
+        let client = TransactionsClient {
+            transactions: vec![
+                transaction(&dest, 30, external_message(&dest, 2)?)?,
+                transaction(&dest, 20, sent)?,
+                transaction(&dest, 10, external_message(&dest, 3)?)?,
+            ],
+        };
+        let from = InternalTransactionId {
+            lt: 30,
+            hash: vec![0; 32],
+        };
+
+        let tx = client
+            .find_transaction_by_in_msg_hash(&dest, &from, 0, &hash)
+            .await?;
+        assert_eq!(tx.map(|tx| tx.transaction_id.lt), Some(20));
+        // transactions up to after_lt are not scanned
+        let tx = client
+            .find_transaction_by_in_msg_hash(&dest, &from, 20, &hash)
+            .await?;
+        assert!(tx.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_external_message_destination() -> anyhow::Result<()> {
+        let dest = TonAddress::new(0, &[1; 32]);
+        let message = CellBuilder::new()
+            .store_u8(2, 0b10)?
+            .store_u8(2, 0)?
+            .store_address(&dest)?
+            .store_u8(4, 0)?
+            .store_bit(false)?
+            .store_bit(false)?
+            .build()?;
+        let boc = BagOfCells::from_root(message).serialize(true)?;
+        assert_eq!(external_message_destination(&boc)?, dest);
+
+        let internal = CellBuilder::new().store_bit(false)?.build()?;
+        let boc = BagOfCells::from_root(internal).serialize(true)?;
+        assert!(external_message_destination(&boc).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_message_hash() {
+        assert_eq!(message_hash(vec![7; 32]).unwrap(), [7; 32]);
+        assert!(message_hash(vec![7; 31]).is_err());
+        assert!(message_hash(Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,181 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::client::{TonClientError, TonClientInterface, TonConnection, TonFeature};
+use crate::tl::{TonFunction, TonResult};
+
+/// Middleware intercepting calls of `TonClientInterface`.
+///
+/// Middleware may inspect or modify the function, short-circuit the call or post-process the
+/// result. To proceed with the call it has to invoke `next.run(function)`.
+#[async_trait]
+pub trait TonMiddleware: Send + Sync {
+    async fn handle(
+        &self,
+        function: &TonFunction,
+        next: Next<'_>,
+    ) -> Result<(TonConnection, TonResult), TonClientError>;
+}
+
+/// Rest of the middleware chain.
+pub struct Next<'a> {
+    middlewares: &'a [Arc<dyn TonMiddleware>],
+    endpoint: &'a dyn TonClientInterface,
+}
+
+impl<'a> Next<'a> {
+    pub(crate) fn new(
+        middlewares: &'a [Arc<dyn TonMiddleware>],
+        endpoint: &'a dyn TonClientInterface,
+    ) -> Next<'a> {
+        Next {
+            middlewares,
+            endpoint,
+        }
+    }
+
+    pub async fn run(
+        self,
+        function: &TonFunction,
+    ) -> Result<(TonConnection, TonResult), TonClientError> {
+        match self.middlewares.split_first() {
+            Some((middleware, rest)) => {
+                middleware
+                    .handle(function, Next::new(rest, self.endpoint))
+                    .await
+            }
+            None => self.endpoint.invoke_on_connection(function).await,
+        }
+    }
+}
+
+/// Client wrapped with middlewares. Middlewares are applied in the order they were added, so the
+/// first added one sees the call first.
+#[derive(Clone)]
+pub struct MiddlewareClient<C> {
+    inner: C,
+    middlewares: Vec<Arc<dyn TonMiddleware>>,
+}
+
+impl<C: TonClientInterface> MiddlewareClient<C> {
+    pub fn new(inner: C) -> MiddlewareClient<C> {
+        MiddlewareClient {
+            inner,
+            middlewares: vec![],
+        }
+    }
+
+    pub fn with_middleware(mut self, middleware: Arc<dyn TonMiddleware>) -> MiddlewareClient<C> {
+        self.middlewares.push(middleware);
+        self
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+}
+
+#[async_trait]
+impl<C: TonClientInterface> TonClientInterface for MiddlewareClient<C> {
+    async fn get_connection(&self) -> Result<TonConnection, TonClientError> {
+        self.inner.get_connection().await
+    }
+
+    async fn invoke_on_connection(
+        &self,
+        function: &TonFunction,
+    ) -> Result<(TonConnection, TonResult), TonClientError> {
+        Next::new(&self.middlewares, &self.inner)
+            .run(function)
+            .await
+    }
+
+    async fn ensure_supported(&self, feature: TonFeature) -> Result<(), TonClientError> {
+        self.inner.ensure_supported(feature).await
+    }
+}
+
+pub trait TonMiddlewareExt: TonClientInterface + Sized {
+    fn with_middleware(self, middleware: Arc<dyn TonMiddleware>) -> MiddlewareClient<Self> {
+        MiddlewareClient::new(self).with_middleware(middleware)
+    }
+}
+
+impl<T: TonClientInterface + Sized> TonMiddlewareExt for T {}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+
+    use crate::client::{
+        Next, TonClientError, TonClientInterface, TonConnection, TonMiddleware, TonMiddlewareExt,
+    };
+    use crate::tl::{TonFunction, TonResult};
+
+    struct FailingClient;
+
+    #[async_trait]
+    impl TonClientInterface for FailingClient {
+        async fn get_connection(&self) -> Result<TonConnection, TonClientError> {
+            Err(TonClientError::InternalError("No connection".to_string()))
+        }
+
+        async fn invoke_on_connection(
+            &self,
+            _function: &TonFunction,
+        ) -> Result<(TonConnection, TonResult), TonClientError> {
+            Err(TonClientError::InternalError("Endpoint".to_string()))
+        }
+    }
+
+    #[derive(Default)]
+    struct CountingMiddleware {
+        count: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl TonMiddleware for CountingMiddleware {
+        async fn handle(
+            &self,
+            function: &TonFunction,
+            next: Next<'_>,
+        ) -> Result<(TonConnection, TonResult), TonClientError> {
+            self.count.fetch_add(1, Ordering::SeqCst);
+            next.run(function).await
+        }
+    }
+
+    struct ChaosMiddleware;
+
+    #[async_trait]
+    impl TonMiddleware for ChaosMiddleware {
+        async fn handle(
+            &self,
+            _function: &TonFunction,
+            _next: Next<'_>,
+        ) -> Result<(TonConnection, TonResult), TonClientError> {
+            Err(TonClientError::InternalError("Chaos".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_middleware_chain() {
+        let counter = Arc::new(CountingMiddleware::default());
+        let client = FailingClient.with_middleware(counter.clone());
+        let func = TonFunction::BlocksGetMasterchainInfo {};
+        let r = client.invoke(&func).await;
+        assert!(matches!(r, Err(TonClientError::InternalError(m)) if m == "Endpoint"));
+        assert_eq!(counter.count.load(Ordering::SeqCst), 1);
+
+        let client = client
+            .with_middleware(Arc::new(ChaosMiddleware))
+            .with_middleware(counter.clone());
+        let r = client.invoke(&func).await;
+        assert!(matches!(r, Err(TonClientError::InternalError(m)) if m == "Chaos"));
+        assert_eq!(counter.count.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,135 @@
+use std::time::{Duration, Instant};
+
+use tokio::time;
+
+/// Polling interval adapting to observed activity.
+///
+/// The interval is reset to `min_interval` on activity and grows exponentially up to
+/// `max_interval` while polls find nothing new. If events are expected at a regular cadence
+/// (e.g. masterchain blocks), polls are postponed until the next event is due, and the
+/// cadence is re-estimated from the observed events.
+#[derive(Debug, Clone)]
+pub struct AdaptivePolling {
+    min_interval: Duration,
+    max_interval: Duration,
+    interval: Duration,
+    cadence: Option<Duration>,
+    last_event: Option<Instant>,
+}
+
+impl AdaptivePolling {
+    pub fn new(min_interval: Duration, max_interval: Duration) -> AdaptivePolling {
+        AdaptivePolling {
+            min_interval,
+            max_interval: max_interval.max(min_interval),
+            interval: min_interval,
+            cadence: None,
+            last_event: None,
+        }
+    }
+
+    /// Expects events at the cadence, which is then adjusted to the observed one.
+    pub fn with_cadence(&mut self, cadence: Duration) -> &mut Self {
+        self.cadence = Some(cadence);
+        self
+    }
+
+    pub fn cadence(&self) -> Option<Duration> {
+        self.cadence
+    }
+
+    /// Records that the poll found new events.
+    pub fn on_event(&mut self) {
+        self.on_event_at(Instant::now())
+    }
+
+    /// Records that the poll found nothing new.
+    pub fn on_idle(&mut self) {
+        self.interval = (self.interval * 2).min(self.max_interval);
+    }
+
+    /// Returns delay before the next poll.
+    pub fn delay(&self) -> Duration {
+        self.delay_at(Instant::now())
+    }
+
+    pub async fn wait(&self) {
+        time::sleep(self.delay()).await
+    }
+
+    fn on_event_at(&mut self, now: Instant) {
+        self.interval = self.min_interval;
+        if let (Some(cadence), Some(last_event)) = (self.cadence, self.last_event) {
+            // outliers, e.g. caused by a slow consumer, are limited
+            let observed = now
+                .saturating_duration_since(last_event)
+                .clamp(cadence / 2, cadence * 2);
+            self.cadence = Some((cadence * 3 + observed) / 4);
+        }
+        self.last_event = Some(now);
+    }
+
+    fn delay_at(&self, now: Instant) -> Duration {
+        let until_due = match (self.cadence, self.last_event) {
+            // wake up slightly earlier than the event is due
+            (Some(cadence), Some(last_event)) => {
+                (last_event + cadence * 9 / 10).saturating_duration_since(now)
+            }
+            _ => Duration::ZERO,
+        };
+        until_due.max(self.interval)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::client::AdaptivePolling;
+
+    #[test]
+    fn test_adaptive_polling_backoff() {
+        let mut polling = AdaptivePolling::new(Duration::from_secs(1), Duration::from_secs(5));
+        let delays: Vec<_> = (0..4)
+            .map(|_| {
+                polling.on_idle();
+                polling.delay().as_secs()
+            })
+            .collect();
+        assert_eq!(delays, vec![2, 4, 5, 5]);
+        polling.on_event();
+        assert_eq!(polling.delay(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_adaptive_polling_cadence() {
+        let mut polling =
+            AdaptivePolling::new(Duration::from_millis(100), Duration::from_millis(1000));
+        polling.with_cadence(Duration::from_secs(5));
+        let start = Instant::now();
+        assert_eq!(polling.delay_at(start), Duration::from_millis(100));
+        polling.on_event_at(start);
+        assert_eq!(polling.delay_at(start), Duration::from_millis(4500));
+        assert_eq!(
+            polling.delay_at(start + Duration::from_secs(4)),
+            Duration::from_millis(500)
+        );
+        assert_eq!(
+            polling.delay_at(start + Duration::from_secs(6)),
+            Duration::from_millis(100)
+        );
+
+        // blocks come every 3 seconds
+        let mut now = start;
+        for _ in 0..20 {
+            now += Duration::from_secs(3);
+            polling.on_event_at(now);
+        }
+        let cadence = polling.cadence().unwrap().as_millis();
+        assert!((3000..3100).contains(&cadence), "{}", cadence);
+
+        // long pause does not inflate the cadence much
+        polling.on_event_at(now + Duration::from_secs(60));
+        assert!(polling.cadence().unwrap() < Duration::from_millis(4000));
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::TonClientError;
+use crate::tl::TonFunction;
+
+/// Cost class of a call, used to apply quotas.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum CostClass {
+    Regular,
+    /// Calls that may require an archive liteserver.
+    Archive,
+    Custom(String),
+}
+
+impl CostClass {
+    /// Default cost class of the function.
+    pub fn of(function: &TonFunction) -> CostClass {
+        match function {
+            TonFunction::BlocksLookupBlock { .. }
+            | TonFunction::RawGetAccountStateByTransaction { .. }
+            | TonFunction::GetAccountStateByTransaction { .. }
+            | TonFunction::SmcLoadByTransaction { .. } => CostClass::Archive,
+            _ => CostClass::Regular,
+        }
+    }
+}
+
+/// Max number of calls per period.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Quota {
+    pub limit: usize,
+    pub period: Duration,
+}
+
+impl Quota {
+    pub fn new(limit: usize, period: Duration) -> Quota {
+        Quota { limit, period }
+    }
+
+    pub fn per_minute(limit: usize) -> Quota {
+        Quota::new(limit, Duration::from_secs(60))
+    }
+}
+
+/// Per cost class quotas with sliding window accounting. Classes without quota are unlimited.
+#[derive(Debug, Default)]
+pub struct QuotaManager {
+    quotas: HashMap<CostClass, (Quota, Mutex<VecDeque<Instant>>)>,
+}
+
+impl QuotaManager {
+    pub fn new(quotas: &HashMap<CostClass, Quota>) -> QuotaManager {
+        let quotas = quotas
+            .iter()
+            .map(|(class, quota)| (class.clone(), (quota.clone(), Mutex::new(VecDeque::new()))))
+            .collect();
+        QuotaManager { quotas }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.quotas.is_empty()
+    }
+
+    /// Charges a call of the class, returns `QuotaExceeded` error if the quota is exhausted.
+    pub fn acquire(&self, class: &CostClass) -> Result<(), TonClientError> {
+        let (quota, calls) = match self.quotas.get(class) {
+            Some(entry) => entry,
+            None => return Ok(()),
+        };
+        let now = Instant::now();
+        let mut calls = calls.lock().unwrap_or_else(PoisonError::into_inner);
+        while calls
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= quota.period)
+        {
+            calls.pop_front();
+        }
+        if calls.len() >= quota.limit {
+            return Err(TonClientError::QuotaExceeded {
+                class: class.clone(),
+                limit: quota.limit,
+                period: quota.period,
+            });
+        }
+        calls.push_back(now);
+        Ok(())
+    }
+
+    /// Number of calls of the class, which can be made now.
+    pub fn remaining(&self, class: &CostClass) -> Option<usize> {
+        let (quota, calls) = self.quotas.get(class)?;
+        let now = Instant::now();
+        let calls = calls.lock().unwrap_or_else(PoisonError::into_inner);
+        let used = calls
+            .iter()
+            .filter(|t| now.duration_since(**t) < quota.period)
+            .count();
+        Some(quota.limit.saturating_sub(used))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    use crate::client::{CostClass, Quota, QuotaManager, TonClientError};
+    use crate::tl::{BlockId, TonFunction};
+
+    #[test]
+    fn test_quota_manager() {
+        let quotas = HashMap::from([
+            (CostClass::Archive, Quota::per_minute(2)),
+            (
+                CostClass::Custom("burst".to_string()),
+                Quota::new(1, Duration::ZERO),
+            ),
+        ]);
+        let manager = QuotaManager::new(&quotas);
+        assert!(manager.acquire(&CostClass::Archive).is_ok());
+        assert!(manager.acquire(&CostClass::Archive).is_ok());
+        assert_eq!(manager.remaining(&CostClass::Archive), Some(0));
+        assert!(matches!(
+            manager.acquire(&CostClass::Archive),
+            Err(TonClientError::QuotaExceeded { limit: 2, .. })
+        ));
+        for _ in 0..10 {
+            assert!(manager.acquire(&CostClass::Regular).is_ok());
+            assert!(manager
+                .acquire(&CostClass::Custom("burst".to_string()))
+                .is_ok());
+        }
+        assert_eq!(manager.remaining(&CostClass::Regular), None);
+    }
+
+    #[test]
+    fn test_cost_class_of() {
+        let func = TonFunction::BlocksLookupBlock {
+            mode: 1,
+            id: BlockId {
+                workchain: -1,
+                shard: i64::MIN,
+                seqno: 1,
+            },
+            lt: 0,
+            utime: 0,
+        };
+        assert_eq!(CostClass::of(&func), CostClass::Archive);
+        assert_eq!(
+            CostClass::of(&TonFunction::BlocksGetMasterchainInfo {}),
+            CostClass::Regular
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::client::{RetryStrategy, TonClientError};
+
+pub const DEFAULT_RETRY_MAX_ATTEMPTS: usize = 10;
+pub const DEFAULT_RETRY_INITIAL_BACKOFF: Duration = Duration::from_millis(10);
+pub const DEFAULT_RETRY_MAX_BACKOFF: Duration = Duration::from_secs(2);
+pub const DEFAULT_RETRY_MULTIPLIER: u32 = 2;
+
+/// Decides whether a failed call should be retried.
+pub trait RetryClassifier: Send + Sync {
+    fn is_retryable(&self, error: &TonClientError) -> bool;
+}
+
+impl<F> RetryClassifier for F
+where
+    F: Fn(&TonClientError) -> bool + Send + Sync,
+{
+    fn is_retryable(&self, error: &TonClientError) -> bool {
+        self(error)
+    }
+}
+
+/// Retries errors caused by an overloaded liteserver or by network failures, e.g. timeouts,
+/// see `TonClientError::is_retryable`.
+pub struct TransientErrorClassifier;
+
+impl RetryClassifier for TransientErrorClassifier {
+    fn is_retryable(&self, error: &TonClientError) -> bool {
+        error.is_retryable()
+    }
+}
+
+/// Retries only tonlib errors with code 500, as `RetryStrategy` does.
+struct ServerErrorClassifier;
+
+impl RetryClassifier for ServerErrorClassifier {
+    fn is_retryable(&self, error: &TonClientError) -> bool {
+        matches!(error, TonClientError::TonlibError { code: 500, .. })
+    }
+}
+
+/// Retry policy applied by `TonClient` to every call.
+///
+/// A call is made at most `max_attempts` times. The delay before retry `n` is
+/// `initial_backoff * multiplier^n` limited by `max_backoff`, with jitter it is randomized
+/// within its upper half.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: usize,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub multiplier: u32,
+    pub jitter: bool,
+    pub classifier: Arc<dyn RetryClassifier>,
+}
+
+impl RetryPolicy {
+    /// Policy which does not retry.
+    pub fn none() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        }
+    }
+
+    pub fn with_classifier(&self, classifier: Arc<dyn RetryClassifier>) -> RetryPolicy {
+        RetryPolicy {
+            classifier,
+            ..self.clone()
+        }
+    }
+
+    pub fn is_retryable(&self, error: &TonClientError) -> bool {
+        self.classifier.is_retryable(error)
+    }
+
+    /// Delays between the attempts.
+    pub fn delays(&self) -> impl Iterator<Item = Duration> + '_ {
+        let mut backoff = self.initial_backoff.min(self.max_backoff);
+        (1..self.max_attempts).map(move |_| {
+            let delay = backoff;
+            backoff = backoff
+                .saturating_mul(self.multiplier)
+                .min(self.max_backoff);
+            if self.jitter && !delay.is_zero() {
+                rand::thread_rng().gen_range(delay / 2..=delay)
+            } else {
+                delay
+            }
+        })
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: DEFAULT_RETRY_MAX_ATTEMPTS,
+            initial_backoff: DEFAULT_RETRY_INITIAL_BACKOFF,
+            max_backoff: DEFAULT_RETRY_MAX_BACKOFF,
+            multiplier: DEFAULT_RETRY_MULTIPLIER,
+            jitter: true,
+            classifier: Arc::new(TransientErrorClassifier),
+        }
+    }
+}
+
+impl From<&RetryStrategy> for RetryPolicy {
+    fn from(strategy: &RetryStrategy) -> Self {
+        let interval = Duration::from_millis(strategy.interval_ms);
+        RetryPolicy {
+            max_attempts: strategy.max_retries + 1,
+            initial_backoff: interval,
+            max_backoff: interval,
+            multiplier: 1,
+            jitter: false,
+            classifier: Arc::new(ServerErrorClassifier),
+        }
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("multiplier", &self.multiplier)
+            .field("jitter", &self.jitter)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::client::{RetryPolicy, RetryStrategy, TonClientError, TonlibErrorKind};
+
+    fn tonlib_error(code: i32, message: &str) -> TonClientError {
+        TonClientError::tonlib_error("raw.getAccountState", code, message.to_string())
+    }
+
+    #[test]
+    fn test_retry_policy_delays() {
+        let policy = RetryPolicy {
+            max_attempts: 6,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(500),
+            jitter: false,
+            ..RetryPolicy::default()
+        };
+        let delays: Vec<_> = policy.delays().map(|d| d.as_millis()).collect();
+        assert_eq!(delays, vec![100, 200, 400, 500, 500]);
+
+        let policy = RetryPolicy {
+            jitter: true,
+            ..policy
+        };
+        for (delay, max) in policy.delays().zip([100, 200, 400, 500, 500]) {
+            assert!(delay.as_millis() >= max / 2 && delay.as_millis() <= max);
+        }
+        assert_eq!(RetryPolicy::none().delays().count(), 0);
+
+        let policy = RetryPolicy::from(&RetryStrategy {
+            interval_ms: 5,
+            max_retries: 3,
+        });
+        let delays: Vec<_> = policy.delays().map(|d| d.as_millis()).collect();
+        assert_eq!(delays, vec![5, 5, 5]);
+    }
+
+    #[test]
+    fn test_retry_classifier() {
+        let policy = RetryPolicy::default();
+        assert!(policy.is_retryable(&tonlib_error(500, "LITE_SERVER_UNKNOWN")));
+        assert!(!policy.is_retryable(&tonlib_error(
+            651,
+            "LITE_SERVER_NOTREADY: block is not in db"
+        )));
+        assert!(!policy.is_retryable(&tonlib_error(500, "block not found")));
+        assert!(policy.is_retryable(&tonlib_error(652, "adnl query timeout")));
+        assert!(!policy.is_retryable(&tonlib_error(400, "invalid address")));
+        assert!(!policy.is_retryable(&TonClientError::InternalError("timeout".to_string())));
+        assert_eq!(
+            tonlib_error(500, "LITE_SERVER_UNKNOWN: block not found").tonlib_error_kind(),
+            Some(TonlibErrorKind::BlockNotFound)
+        );
+        assert_eq!(
+            tonlib_error(651, "LITE_SERVER_NOTREADY: block is not in db").tonlib_error_kind(),
+            Some(TonlibErrorKind::NotInDb)
+        );
+        assert_eq!(
+            tonlib_error(400, "invalid address").tonlib_error_kind(),
+            Some(TonlibErrorKind::Request)
+        );
+        let refused = std::io::Error::from(std::io::ErrorKind::ConnectionRefused);
+        assert!(policy.is_retryable(&TonClientError::Io(refused)));
+
+        let strategy_policy = RetryPolicy::from(&RetryStrategy::default());
+        assert!(!strategy_policy.is_retryable(&tonlib_error(652, "adnl query timeout")));
+
+        let policy = policy.with_classifier(Arc::new(|e: &TonClientError| {
+            matches!(e, TonClientError::InternalError(_))
+        }));
+        assert!(policy.is_retryable(&TonClientError::InternalError("timeout".to_string())));
+    }
+}
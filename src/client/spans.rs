@@ -0,0 +1,54 @@
+use tracing::{field, Span};
+
+use crate::tl::TonFunction;
+
+/// Returns span of the call with the account or block it refers to.
+pub(crate) fn call_span(function: &TonFunction) -> Span {
+    let method: &'static str = function.into();
+    let span = tracing::debug_span!(
+        "ton_call",
+        method,
+        address = field::Empty,
+        workchain = field::Empty,
+        shard = field::Empty,
+        seqno = field::Empty,
+    );
+    match function {
+        TonFunction::RawGetAccountState { account_address }
+        | TonFunction::RawGetAccountStateByTransaction {
+            account_address, ..
+        }
+        | TonFunction::RawGetTransactions {
+            account_address, ..
+        }
+        | TonFunction::RawGetTransactionsV2 {
+            account_address, ..
+        }
+        | TonFunction::GetAccountState { account_address }
+        | TonFunction::GetAccountStateByTransaction {
+            account_address, ..
+        }
+        | TonFunction::GetShardAccountCell { account_address }
+        | TonFunction::SmcLoad { account_address }
+        | TonFunction::SmcLoadByTransaction {
+            account_address, ..
+        } => {
+            span.record("address", account_address.account_address.as_str());
+        }
+        TonFunction::BlocksGetShards { id }
+        | TonFunction::BlocksGetTransactions { id, .. }
+        | TonFunction::BlocksGetTransactionsExt { id, .. }
+        | TonFunction::GetBlockHeader { id } => {
+            span.record("workchain", id.workchain);
+            span.record("shard", id.shard);
+            span.record("seqno", id.seqno);
+        }
+        TonFunction::BlocksLookupBlock { id, .. } => {
+            span.record("workchain", id.workchain);
+            span.record("shard", id.shard);
+            span.record("seqno", id.seqno);
+        }
+        _ => {}
+    }
+    span
+}
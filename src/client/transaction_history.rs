@@ -0,0 +1,120 @@
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::stream::{self, BoxStream};
+use futures::{Stream, StreamExt};
+
+use crate::address::TonAddress;
+use crate::client::{TonClient, TonClientError, TonClientInterface};
+use crate::tl::{InternalTransactionId, RawTransaction};
+
+const TRANSACTION_HISTORY_PAGE_SIZE: usize = 16;
+
+/// Stream of transactions of the account from the newest one back to the first one.
+///
+/// Pages are loaded with `get_raw_transactions_v2` following `previous_transaction_id`, so
+/// partial pages are handled. The stream ends after the first transaction of the account or
+/// after an error, which is yielded.
+pub struct TransactionHistoryStream {
+    inner: BoxStream<'static, Result<RawTransaction, TonClientError>>,
+}
+
+impl TransactionHistoryStream {
+    /// Creates the stream starting from `from` inclusive, or from the current last
+    /// transaction of the account if `from` is `None`.
+    pub fn new<C>(
+        client: &C,
+        address: &TonAddress,
+        from: Option<InternalTransactionId>,
+    ) -> TransactionHistoryStream
+    where
+        C: TonClientInterface + Clone + 'static,
+    {
+        let pager = Pager {
+            client: client.clone(),
+            address: address.clone(),
+            next: from,
+            started: false,
+            pending: VecDeque::new(),
+        };
+        let inner = stream::unfold(Some(pager), |pager| async move {
+            let mut pager = pager?;
+            while pager.pending.is_empty() {
+                match pager.load_page().await {
+                    Ok(true) => {}
+                    Ok(false) => return None,
+                    Err(e) => return Some((Err(e), None)),
+                }
+            }
+            let tx = pager.pending.pop_front()?;
+            Some((Ok(tx), Some(pager)))
+        });
+        TransactionHistoryStream {
+            inner: inner.boxed(),
+        }
+    }
+}
+
+impl Stream for TransactionHistoryStream {
+    type Item = Result<RawTransaction, TonClientError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_unpin(cx)
+    }
+}
+
+impl TonClient {
+    /// Returns stream of all transactions of the account, the newest first.
+    pub fn iter_raw_transactions(&self, address: &TonAddress) -> TransactionHistoryStream {
+        TransactionHistoryStream::new(self, address, None)
+    }
+
+    /// Returns stream of transactions of the account from `from` inclusive back to the first
+    /// one, e.g. to resume a scrape.
+    pub fn iter_raw_transactions_from(
+        &self,
+        address: &TonAddress,
+        from: &InternalTransactionId,
+    ) -> TransactionHistoryStream {
+        TransactionHistoryStream::new(self, address, Some(from.clone()))
+    }
+}
+
+struct Pager<C> {
+    client: C,
+    address: TonAddress,
+    next: Option<InternalTransactionId>,
+    started: bool,
+    pending: VecDeque<RawTransaction>,
+}
+
+impl<C: TonClientInterface> Pager<C> {
+    /// Loads the next page, returns `false` if the history is exhausted.
+    async fn load_page(&mut self) -> Result<bool, TonClientError> {
+        let next = match self.next.take() {
+            Some(next) => next,
+            None if !self.started => {
+                self.client
+                    .get_raw_account_state(&self.address)
+                    .await?
+                    .last_transaction_id
+            }
+            None => return Ok(false),
+        };
+        self.started = true;
+        if next.lt == 0 {
+            return Ok(false);
+        }
+        let txs = self
+            .client
+            .get_raw_transactions_v2(&self.address, &next, TRANSACTION_HISTORY_PAGE_SIZE, false)
+            .await?;
+        if txs.transactions.is_empty() {
+            return Ok(false);
+        }
+        self.pending.extend(txs.transactions);
+        self.next = Some(txs.previous_transaction_id);
+        Ok(true)
+    }
+}
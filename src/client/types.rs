@@ -22,19 +22,12 @@ pub struct TxId {
 
 impl TxId {
     pub fn new(workchain: i32, tx_id: &BlocksShortTxId) -> Result<TxId, TonClientError> {
-        let addr = TonAddress::new(
-            workchain,
-            tx_id.account.as_slice().try_into().map_err(|_| {
-                TonClientError::InternalError(format!("Invalid BlocksShortTxId: {:?}", tx_id))
-            })?,
-        );
-        let id = InternalTransactionId {
-            lt: tx_id.lt,
-            hash: tx_id.hash.clone(),
-        };
+        let address = tx_id.address(workchain).map_err(|_| {
+            TonClientError::InternalError(format!("Invalid BlocksShortTxId: {:?}", tx_id))
+        })?;
         Ok(TxId {
-            address: addr,
-            internal_transaction_id: id,
+            address,
+            internal_transaction_id: tx_id.transaction_id(),
         })
     }
 }
@@ -0,0 +1,228 @@
+use std::collections::HashSet;
+
+use crate::address::TonAddress;
+use crate::tl::{BlocksShortTxId, BlocksTransactions};
+
+/// Set of watched addresses used to pre-filter block transactions by account id
+/// before fetching full transactions.
+///
+/// The exact set is always checked. An opt-in bloom filter (see `with_bloom_filter`) rejects most
+/// of the non-watched account ids by a few bit tests of the raw id, without building and hashing
+/// a `TonAddress` for every transaction of the block.
+#[derive(Debug, Clone, Default)]
+pub struct WatchSet {
+    addresses: HashSet<TonAddress>,
+    bloom: Option<BloomFilter>,
+}
+
+impl WatchSet {
+    pub fn new() -> WatchSet {
+        WatchSet::default()
+    }
+
+    /// Enables bloom filter sized for `expected_items` addresses with the given false positive rate.
+    pub fn with_bloom_filter(
+        &mut self,
+        expected_items: usize,
+        false_positive_rate: f64,
+    ) -> &mut Self {
+        let mut bloom = BloomFilter::new(expected_items, false_positive_rate);
+        for address in self.addresses.iter() {
+            bloom.insert(address.workchain, &address.hash_part);
+        }
+        self.bloom = Some(bloom);
+        self
+    }
+
+    pub fn insert(&mut self, address: &TonAddress) -> bool {
+        if let Some(bloom) = self.bloom.as_mut() {
+            bloom.insert(address.workchain, &address.hash_part);
+        }
+        self.addresses.insert(address.clone())
+    }
+
+    /// Removes address from the watch set.
+    ///
+    /// Bloom filter (if enabled) is rebuilt, since it doesn't support removal.
+    pub fn remove(&mut self, address: &TonAddress) -> bool {
+        let removed = self.addresses.remove(address);
+        if removed {
+            if let Some(bloom) = self.bloom.as_mut() {
+                bloom.clear();
+                for address in self.addresses.iter() {
+                    bloom.insert(address.workchain, &address.hash_part);
+                }
+            }
+        }
+        removed
+    }
+
+    pub fn contains(&self, address: &TonAddress) -> bool {
+        self.contains_account(address.workchain, &address.hash_part)
+    }
+
+    /// Checks if the account id (hash part of the address) in the workchain is watched.
+    pub fn contains_account(&self, workchain: i32, account: &[u8]) -> bool {
+        if let Some(bloom) = self.bloom.as_ref() {
+            if !bloom.may_contain(workchain, account) {
+                return false;
+            }
+        }
+        match account.try_into() {
+            Ok(hash_part) => self
+                .addresses
+                .contains(&TonAddress::new(workchain, hash_part)),
+            Err(_) => false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.addresses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.addresses.is_empty()
+    }
+
+    pub fn addresses(&self) -> impl Iterator<Item = &TonAddress> {
+        self.addresses.iter()
+    }
+
+    /// Returns transaction ids of watched accounts.
+    pub fn filter_tx_ids(&self, txs: &BlocksTransactions) -> Vec<BlocksShortTxId> {
+        txs.transactions
+            .iter()
+            .filter(|tx| self.contains_account(txs.id.workchain, &tx.account))
+            .cloned()
+            .collect()
+    }
+}
+
+impl FromIterator<TonAddress> for WatchSet {
+    fn from_iter<T: IntoIterator<Item = TonAddress>>(iter: T) -> Self {
+        WatchSet {
+            addresses: iter.into_iter().collect(),
+            bloom: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct BloomFilter {
+    bits: Vec<u64>,
+    bit_count: u64,
+    hash_count: u32,
+}
+
+impl BloomFilter {
+    fn new(expected_items: usize, false_positive_rate: f64) -> BloomFilter {
+        let n = expected_items.max(1) as f64;
+        let p = false_positive_rate.clamp(f64::MIN_POSITIVE, 0.5);
+        let ln2 = std::f64::consts::LN_2;
+        let bit_count = (-(n * p.ln()) / (ln2 * ln2)).ceil().max(64.0) as u64;
+        let hash_count = ((bit_count as f64 / n) * ln2).round().clamp(1.0, 16.0) as u32;
+        BloomFilter {
+            bits: vec![0; bit_count.div_ceil(64) as usize],
+            bit_count,
+            hash_count,
+        }
+    }
+
+    fn insert(&mut self, workchain: i32, account: &[u8]) {
+        for idx in self.indexes(workchain, account) {
+            self.bits[(idx / 64) as usize] |= 1 << (idx % 64);
+        }
+    }
+
+    fn may_contain(&self, workchain: i32, account: &[u8]) -> bool {
+        self.indexes(workchain, account)
+            .all(|idx| self.bits[(idx / 64) as usize] & (1 << (idx % 64)) != 0)
+    }
+
+    fn clear(&mut self) {
+        self.bits.iter_mut().for_each(|b| *b = 0);
+    }
+
+    /// Account ids are hashes, so their bytes are used directly for double hashing.
+    fn indexes(&self, workchain: i32, account: &[u8]) -> impl Iterator<Item = u64> {
+        let mut h1_bytes = [0u8; 8];
+        let mut h2_bytes = [0u8; 8];
+        for (i, b) in account.iter().enumerate() {
+            if i < 16 {
+                h1_bytes[i % 8] ^= b;
+            } else {
+                h2_bytes[i % 8] ^= b;
+            }
+        }
+        let h1 = u64::from_le_bytes(h1_bytes) ^ (workchain as u64);
+        let h2 = u64::from_le_bytes(h2_bytes) | 1;
+        let bit_count = self.bit_count;
+        (0..self.hash_count as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % bit_count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::address::TonAddress;
+    use crate::client::WatchSet;
+    use crate::tl::{BlockIdExt, BlocksShortTxId, BlocksTransactions};
+
+    fn address(i: u32) -> TonAddress {
+        let mut hash_part = [0u8; 32];
+        for (j, b) in hash_part.iter_mut().enumerate() {
+            *b = (i.wrapping_mul(2654435761).rotate_left(j as u32) & 0xff) as u8;
+        }
+        TonAddress::new(0, &hash_part)
+    }
+
+    #[test]
+    fn test_watch_set_contains() {
+        for bloom in [false, true] {
+            let mut watch_set = WatchSet::new();
+            if bloom {
+                watch_set.with_bloom_filter(100, 0.01);
+            }
+            for i in 0..100 {
+                watch_set.insert(&address(i));
+            }
+            assert_eq!(watch_set.len(), 100);
+            for i in 0..100 {
+                assert!(watch_set.contains(&address(i)));
+            }
+            for i in 100..1000 {
+                assert!(!watch_set.contains(&address(i)));
+            }
+            let other_wc = TonAddress::new(-1, &address(1).hash_part);
+            assert!(!watch_set.contains(&other_wc));
+
+            assert!(watch_set.remove(&address(5)));
+            assert!(!watch_set.contains(&address(5)));
+            assert!(watch_set.contains(&address(6)));
+        }
+    }
+
+    #[test]
+    fn test_watch_set_filter_tx_ids() {
+        let watch_set: WatchSet = vec![address(1), address(3)].into_iter().collect();
+        let tx = |i: u32| BlocksShortTxId {
+            mode: 7,
+            account: address(i).hash_part.to_vec(),
+            lt: i as i64,
+            hash: vec![0; 32],
+        };
+        let txs = BlocksTransactions {
+            id: BlockIdExt {
+                workchain: 0,
+                shard: i64::MIN,
+                seqno: 1,
+                root_hash: String::new(),
+                file_hash: String::new(),
+            },
+            req_count: 256,
+            incomplete: false,
+            transactions: (0..5).map(tx).collect(),
+        };
+        let filtered = watch_set.filter_tx_ids(&txs);
+        assert_eq!(filtered, vec![tx(1), tx(3)]);
+    }
+}
@@ -0,0 +1,91 @@
+//! Deterministic test vectors shared with other TON SDKs.
+//!
+//! Every vector cites where it comes from. Vectors, which would only record the current output
+//! of this library (e.g. signed transfers), are not included, since they can't catch a
+//! serialization bug.
+
+use crate::wallet::WalletVersion;
+
+pub struct AddressVector {
+    pub hex: &'static str,
+    pub base64_url: &'static str,
+    pub base64_std: &'static str,
+}
+
+/// Jetton master deployed on mainnet, the one used by the mainnet integration tests.
+pub const ADDRESS_VECTORS: [AddressVector; 1] = [AddressVector {
+    hex: "0:e4d954ef9f4e1250a26b5bbad76a1cdd17cfd08babad6f4c23e372270aef6f76",
+    base64_url: "EQDk2VTvn04SUKJrW7rXahzdF8_Qi6utb0wj43InCu9vdjrR",
+    base64_std: "EQDk2VTvn04SUKJrW7rXahzdF8/Qi6utb0wj43InCu9vdjrR",
+}];
+
+/// Mnemonic used by wallet vectors.
+pub const TEST_MNEMONIC: &str = "fancy carpet hello mandate penalty trial consider property top \
+    vicious exit rebuild tragic profit urban major total month holiday sudden rib gather media \
+    vicious";
+
+/// Mnemonic used by V5R1 wallet vector.
+pub const TEST_MNEMONIC_V5: &str = "section garden tomato dinner season dice renew length useful \
+    spin trade intact use universe what post spike keen mandate behind concert egg doll rug";
+
+/// Address of the wallet derived with default wallet id in workchain 0.
+pub struct WalletVector {
+    pub mnemonic: &'static str,
+    pub version: WalletVersion,
+    pub address: &'static str,
+}
+
+/// Wallet addresses of the test mnemonics, the same as in the wallet derivation test
+/// (`wallet::tests::derive_wallet_works`).
+pub const WALLET_VECTORS: [WalletVector; 4] = [
+    WalletVector {
+        mnemonic: TEST_MNEMONIC,
+        version: WalletVersion::V3R1,
+        address: "EQBiMfDMivebQb052Z6yR3jHrmwNhw1kQ5bcAUOBYsK_VPuK",
+    },
+    WalletVector {
+        mnemonic: TEST_MNEMONIC,
+        version: WalletVersion::V3R2,
+        address: "EQA-RswW9QONn88ziVm4UKnwXDEot5km7GEEXsfie_0TFOCO",
+    },
+    WalletVector {
+        mnemonic: TEST_MNEMONIC,
+        version: WalletVersion::V4R2,
+        address: "EQCDM_QGggZ3qMa_f3lRPk4_qLDnLTqdi6OkMAV2NB9r5TG3",
+    },
+    WalletVector {
+        mnemonic: TEST_MNEMONIC_V5,
+        version: WalletVersion::V5R1,
+        address: "UQDv2YSmlrlLH3hLNOVxC8FcQf4F9eGNs4vb2zKma4txo6i3",
+    },
+];
+
+/// BoC with its root hash. BoC must be reserialized byte-to-byte (without crc).
+pub struct BocVector {
+    pub boc_hex: &'static str,
+    pub root_hash: &'static str,
+}
+
+/// Library cell, the code of the account of `STATE_INIT_VECTORS`.
+pub const BOC_VECTORS: [BocVector; 1] = [BocVector {
+    boc_hex: "b5ee9c7201010201002d00010eff0088d0ed1ed801084202e70a306c00272796243f569ce0c928ea4cfc9f1b65c5b0066e382159f5e80df5",
+    root_hash: "5cc5d521116fedc7bfd5eeb26c16785f024d93678b99f4fb237bb4e1b0795223",
+}];
+
+/// Address of the contract with the code (BoC) and data consisting of two addresses and two
+/// zero coins.
+pub struct StateInitVector {
+    pub code_boc_hex: &'static str,
+    pub data_addresses: [&'static str; 2],
+    pub address: &'static str,
+}
+
+/// Account deployed on mainnet, the same as in `tests/boc.rs` (`library_cell`).
+pub const STATE_INIT_VECTORS: [StateInitVector; 1] = [StateInitVector {
+    code_boc_hex: "b5ee9c7201010201002d00010eff0088d0ed1ed801084202e70a306c00272796243f569ce0c928ea4cfc9f1b65c5b0066e382159f5e80df5",
+    data_addresses: [
+        "UQAO9JsDEbOjnb8AZRyxNHiODjVeAvgR2n03T0utYgkpx-K0",
+        "EQDMk-2P8ziShAYGcnYq-z_U33zA_Ynt88iav4PwkSGRru2B",
+    ],
+    address: "EQBWxdw3leOoaHqcK3ATf0T7ae5M8XS6jiP_Din4mh7o7gj2",
+}];
@@ -1,2 +1,43 @@
+pub use error::*;
+pub use local_node::*;
+pub use ton_config::*;
+
+mod error;
+mod local_node;
+mod ton_config;
+
 pub const MAINNET_CONFIG: &str = include_str!("../resources/config/global.config.json");
 pub const TESTNET_CONFIG: &str = include_str!("../resources/config/testnet-global.config.json");
+
+pub const MAINNET_CONFIG_URL: &str = "https://ton.org/global.config.json";
+pub const TESTNET_CONFIG_URL: &str = "https://ton.org/testnet-global.config.json";
+
+/// Downloads network config using default http client.
+pub async fn load_config(url: &str) -> Result<String, ConfigLoaderError> {
+    let http_client = reqwest::Client::builder().build()?;
+    load_config_with_client(&http_client, url).await
+}
+
+/// Downloads network config using provided http client,
+/// e.g. the one configured with proxy or custom DNS resolver.
+pub async fn load_config_with_client(
+    http_client: &reqwest::Client,
+    url: &str,
+) -> Result<String, ConfigLoaderError> {
+    log::trace!("Downloading config from {}", url);
+    let resp = http_client.get(url).send().await?;
+    if !resp.status().is_success() {
+        return Err(ConfigLoaderError::LoadConfigFailed {
+            url: url.to_string(),
+            status: resp.status(),
+        });
+    }
+    let config = resp.text().await?;
+    serde_json::from_str::<serde_json::Value>(&config).map_err(|error| {
+        ConfigLoaderError::InvalidConfig {
+            url: url.to_string(),
+            error,
+        }
+    })?;
+    Ok(config)
+}
@@ -0,0 +1,34 @@
+use std::io;
+
+use reqwest::StatusCode;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ConfigLoaderError {
+    #[error("Failed to load config (URL: {url}, response status code: {status})")]
+    LoadConfigFailed { url: String, status: StatusCode },
+
+    #[error("Invalid config (URL: {url}, error: {error})")]
+    InvalidConfig {
+        url: String,
+        error: serde_json::Error,
+    },
+
+    #[error("Transport error ({0})")]
+    TransportError(#[from] reqwest::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum TonConfigError {
+    #[error("Invalid config ({0})")]
+    InvalidConfig(String),
+
+    #[error("Invalid liteserver ({0})")]
+    InvalidLiteServer(String),
+
+    #[error("Serde_json Error ({0})")]
+    SerdeJsonError(#[from] serde_json::Error),
+
+    #[error("IO error ({0})")]
+    Io(#[from] io::Error),
+}
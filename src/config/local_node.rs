@@ -0,0 +1,137 @@
+use std::fs;
+use std::net::Ipv4Addr;
+use std::path::Path;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::Deserialize;
+
+use crate::config::{LiteServerConfig, TonConfig, TonConfigError};
+
+/// TL constructor id of `pub.ed25519`, prepended to the key in `liteserver.pub` file.
+const PUB_ED25519_PREFIX: [u8; 4] = [0xc6, 0xb4, 0x13, 0x48];
+
+/// Locally running ton node, accessed via its liteserver port.
+///
+/// tonlib connects to liteservers over network only, so the node has to have liteserver
+/// enabled and bound to `ip:port` (`127.0.0.1` by default).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LocalNode {
+    liteserver: LiteServerConfig,
+}
+
+#[derive(Deserialize)]
+struct NodeConfigJson {
+    #[serde(default)]
+    liteservers: Vec<NodeLiteServerJson>,
+}
+
+#[derive(Deserialize)]
+struct NodeLiteServerJson {
+    port: u16,
+}
+
+impl LocalNode {
+    pub fn new(ip: Ipv4Addr, port: u16, key: &str) -> Result<LocalNode, TonConfigError> {
+        Ok(LocalNode {
+            liteserver: LiteServerConfig::new(ip, port, key)?,
+        })
+    }
+
+    /// Reads liteserver port from the node config (`<db>/config.json`) and its public key
+    /// from `liteserver.pub` file generated by `generate-random-id`.
+    pub fn from_files<P: AsRef<Path>, K: AsRef<Path>>(
+        node_config_path: P,
+        liteserver_pub_path: K,
+    ) -> Result<LocalNode, TonConfigError> {
+        let port = read_liteserver_port(&fs::read_to_string(node_config_path)?)?;
+        let key = read_liteserver_key(&fs::read(liteserver_pub_path)?)?;
+        LocalNode::new(Ipv4Addr::LOCALHOST, port, &key)
+    }
+
+    /// Overrides the address the liteserver is bound to.
+    pub fn with_ip(&mut self, ip: Ipv4Addr) -> &mut Self {
+        self.liteserver.ip = ip;
+        self
+    }
+
+    pub fn with_port(&mut self, port: u16) -> &mut Self {
+        self.liteserver.port = port;
+        self
+    }
+
+    pub fn liteserver(&self) -> &LiteServerConfig {
+        &self.liteserver
+    }
+
+    /// Returns network config with the local node as the only liteserver.
+    pub fn ton_config(&self, global_config: &str) -> Result<TonConfig, TonConfigError> {
+        let mut config = TonConfig::parse(global_config)?;
+        config.with_liteservers(std::slice::from_ref(&self.liteserver));
+        Ok(config)
+    }
+}
+
+/// Returns port of the first liteserver in the node config.
+pub fn read_liteserver_port(node_config: &str) -> Result<u16, TonConfigError> {
+    let config: NodeConfigJson = serde_json::from_str(node_config)?;
+    config
+        .liteservers
+        .first()
+        .map(|l| l.port)
+        .ok_or_else(|| TonConfigError::InvalidConfig("Liteserver is not enabled".to_string()))
+}
+
+/// Returns base64-encoded liteserver key. Accepts `liteserver.pub` file contents
+/// (raw key with or without `pub.ed25519` prefix) or base64 text.
+pub fn read_liteserver_key(data: &[u8]) -> Result<String, TonConfigError> {
+    let key = match data.len() {
+        36 if data[..4] == PUB_ED25519_PREFIX => &data[4..],
+        32 => data,
+        _ => {
+            let text = String::from_utf8_lossy(data);
+            return Ok(text.trim().to_string());
+        }
+    };
+    Ok(STANDARD.encode(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use crate::config::{read_liteserver_key, read_liteserver_port, LocalNode, MAINNET_CONFIG};
+
+    #[test]
+    fn test_read_liteserver_key() -> anyhow::Result<()> {
+        let mut data = vec![0xc6, 0xb4, 0x13, 0x48];
+        data.extend_from_slice(&[1; 32]);
+        let expected = "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE=";
+        assert_eq!(read_liteserver_key(&data)?, expected);
+        assert_eq!(read_liteserver_key(&data[4..])?, expected);
+        assert_eq!(
+            read_liteserver_key(format!("{}\n", expected).as_bytes())?,
+            expected
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_local_node_config() -> anyhow::Result<()> {
+        let node_config = r#"{"@type":"engine.validator.config",
+            "liteservers":[{"@type":"engine.liteServer","id":"hash","port":30004}]}"#;
+        let port = read_liteserver_port(node_config)?;
+        assert_eq!(port, 30004);
+        assert!(read_liteserver_port(r#"{"liteservers":[]}"#).is_err());
+
+        let mut node = LocalNode::new(
+            Ipv4Addr::LOCALHOST,
+            port,
+            "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE=",
+        )?;
+        node.with_ip(Ipv4Addr::new(10, 0, 0, 1));
+        let config = node.ton_config(MAINNET_CONFIG)?;
+        assert_eq!(config.liteservers()?, vec![node.liteserver().clone()]);
+        Ok(())
+    }
+}
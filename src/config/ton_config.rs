@@ -0,0 +1,182 @@
+use std::fmt;
+use std::net::Ipv4Addr;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::TonConfigError;
+
+const LITESERVERS_KEY: &str = "liteservers";
+const ED25519_KEY_TYPE: &str = "pub.ed25519";
+
+/// Liteserver entry of the network config.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LiteServerConfig {
+    pub ip: Ipv4Addr,
+    pub port: u16,
+    /// Base64-encoded ed25519 public key of the liteserver.
+    pub key: String,
+}
+
+impl LiteServerConfig {
+    pub fn new(ip: Ipv4Addr, port: u16, key: &str) -> Result<LiteServerConfig, TonConfigError> {
+        let decoded = STANDARD.decode(key).map_err(|e| {
+            TonConfigError::InvalidLiteServer(format!("Invalid key {}: {}", key, e))
+        })?;
+        if decoded.len() != 32 {
+            return Err(TonConfigError::InvalidLiteServer(format!(
+                "Invalid key {}: expected 32 bytes, got {}",
+                key,
+                decoded.len()
+            )));
+        }
+        Ok(LiteServerConfig {
+            ip,
+            port,
+            key: key.to_string(),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct LiteServerJson {
+    ip: i64,
+    port: u16,
+    id: LiteServerIdJson,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LiteServerIdJson {
+    #[serde(rename = "@type")]
+    key_type: String,
+    key: String,
+}
+
+impl TryFrom<LiteServerJson> for LiteServerConfig {
+    type Error = TonConfigError;
+
+    fn try_from(value: LiteServerJson) -> Result<Self, Self::Error> {
+        if value.id.key_type != ED25519_KEY_TYPE {
+            return Err(TonConfigError::InvalidLiteServer(format!(
+                "Unsupported key type: {}",
+                value.id.key_type
+            )));
+        }
+        // ip is stored as signed 32-bit integer
+        let ip = Ipv4Addr::from(value.ip as u32);
+        LiteServerConfig::new(ip, value.port, &value.id.key)
+    }
+}
+
+impl From<&LiteServerConfig> for LiteServerJson {
+    fn from(value: &LiteServerConfig) -> Self {
+        LiteServerJson {
+            ip: u32::from(value.ip) as i32 as i64,
+            port: value.port,
+            id: LiteServerIdJson {
+                key_type: ED25519_KEY_TYPE.to_string(),
+                key: value.key.clone(),
+            },
+        }
+    }
+}
+
+/// Network config, which can be modified before passing it to `TonClientBuilder::with_ton_config`.
+///
+/// Sections other than liteservers are kept as is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TonConfig {
+    value: Value,
+}
+
+impl TonConfig {
+    pub fn parse(config: &str) -> Result<TonConfig, TonConfigError> {
+        let value: Value = serde_json::from_str(config)?;
+        if !value.is_object() {
+            return Err(TonConfigError::InvalidConfig(
+                "Config must be a json object".to_string(),
+            ));
+        }
+        let config = TonConfig { value };
+        config.liteservers()?;
+        Ok(config)
+    }
+
+    pub fn liteservers(&self) -> Result<Vec<LiteServerConfig>, TonConfigError> {
+        match self.value.get(LITESERVERS_KEY) {
+            Some(liteservers) => {
+                let liteservers: Vec<LiteServerJson> = serde_json::from_value(liteservers.clone())?;
+                liteservers.into_iter().map(TryInto::try_into).collect()
+            }
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Adds a liteserver, e.g. a private node.
+    pub fn with_liteserver(&mut self, liteserver: &LiteServerConfig) -> &mut Self {
+        let entry = serde_json::to_value(LiteServerJson::from(liteserver)).unwrap_or_default();
+        match self.value.get_mut(LITESERVERS_KEY) {
+            Some(Value::Array(liteservers)) => liteservers.push(entry),
+            _ => self.value[LITESERVERS_KEY] = Value::Array(vec![entry]),
+        }
+        self
+    }
+
+    /// Replaces all liteservers, so that the client connects only to the given ones.
+    pub fn with_liteservers(&mut self, liteservers: &[LiteServerConfig]) -> &mut Self {
+        self.value[LITESERVERS_KEY] = Value::Array(vec![]);
+        for liteserver in liteservers {
+            self.with_liteserver(liteserver);
+        }
+        self
+    }
+}
+
+impl fmt::Display for TonConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use crate::config::{LiteServerConfig, TonConfig, MAINNET_CONFIG};
+
+    #[test]
+    fn test_ton_config_liteservers() -> anyhow::Result<()> {
+        let mut config = TonConfig::parse(MAINNET_CONFIG)?;
+        let liteservers = config.liteservers()?;
+        assert_eq!(
+            liteservers[0],
+            LiteServerConfig::new(
+                Ipv4Addr::new(5, 9, 10, 47),
+                19949,
+                "n4VDnSCUuSpjnCyUk9e3QOOd6o0ItSWYbTnW3Wnn8wk="
+            )?
+        );
+
+        let private = LiteServerConfig::new(
+            Ipv4Addr::new(192, 168, 1, 10),
+            30303,
+            "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=",
+        )?;
+        config.with_liteserver(&private);
+        let parsed = TonConfig::parse(&config.to_string())?;
+        assert_eq!(parsed.liteservers()?.len(), liteservers.len() + 1);
+        assert_eq!(parsed.liteservers()?.last(), Some(&private));
+
+        config.with_liteservers(std::slice::from_ref(&private));
+        assert_eq!(config.liteservers()?, vec![private]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_lite_server_config_invalid_key() {
+        assert!(LiteServerConfig::new(Ipv4Addr::LOCALHOST, 1, "AAAA").is_err());
+        assert!(LiteServerConfig::new(Ipv4Addr::LOCALHOST, 1, "not base64").is_err());
+    }
+}
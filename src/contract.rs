@@ -1,27 +1,41 @@
 use std::sync::Arc;
 
+pub use account_summary::*;
 use async_trait::async_trait;
+pub use elector::*;
 pub use error::*;
 pub use factory::*;
 pub use interface::*;
 pub use jetton::*;
 pub use latest_transactions_cache::*;
 pub use nft::*;
+pub use portfolio::*;
+pub use registry::*;
+pub use revision::*;
 pub use state::*;
+pub use state_diff::*;
+pub use unfreeze::*;
 pub use wallet::*;
 
 use crate::address::TonAddress;
 use crate::client::TonClientInterface;
 use crate::tl::{InternalTransactionId, RawFullAccountState};
-use crate::types::{TonMethodId, TvmStackEntry, TvmSuccess};
+use crate::types::{TonMethodId, TvmStackEntry, TvmSuccess, WithErrorContext};
 
+mod account_summary;
+mod elector;
 mod error;
 mod factory;
 mod interface;
 mod jetton;
 mod latest_transactions_cache;
 mod nft;
+mod portfolio;
+mod registry;
+mod revision;
 mod state;
+mod state_diff;
+mod unfreeze;
 mod wallet;
 
 pub struct TonContract {
@@ -89,7 +103,7 @@ impl TonContractInterface for TonContract {
         M: Into<TonMethodId> + Send + Copy,
         S: AsRef<[TvmStackEntry]> + Send,
     {
-        let state = self.get_state().await?;
+        let state = self.get_state().await.with_address(self.address())?;
         let result = state.run_get_method(method, stack).await?;
         Ok(result)
     }
@@ -0,0 +1,208 @@
+use futures::join;
+
+use crate::address::TonAddress;
+use crate::cell::BagOfCells;
+use crate::client::TonClientInterface;
+use crate::contract::{
+    ContractKind, JettonMasterContract, JettonWalletContract, MapCellError, NftCollectionContract,
+    NftItemContract, TonContractError, TonContractFactory, STANDARD_CODE_REGISTRY,
+};
+use crate::tl::{InternalTransactionId, RawFullAccountState};
+use crate::types::TonHash;
+use crate::wallet::WalletVersion;
+
+/// Default number of latest transactions scanned by `account_summary`.
+pub const DEFAULT_SUMMARY_SCAN_LIMIT: usize = 256;
+
+const TRANSACTIONS_PAGE_SIZE: usize = 16;
+
+/// Status of the account by its raw state: active accounts have code, frozen accounts have
+/// only the hash of their state (`frozen_hash`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AccountStatus {
+    Uninit,
+    Active,
+    Frozen,
+}
+
+impl AccountStatus {
+    pub fn of(state: &RawFullAccountState) -> AccountStatus {
+        if !state.code.is_empty() {
+            AccountStatus::Active
+        } else if !state.frozen_hash.is_empty() {
+            AccountStatus::Frozen
+        } else {
+            AccountStatus::Uninit
+        }
+    }
+}
+
+/// Interfaces of the contract, detected by code hash in `STANDARD_CODE_REGISTRY` or by probing
+/// get-methods.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ContractInterface {
+    Wallet(WalletVersion),
+    JettonMaster,
+    JettonWallet,
+    NftCollection,
+    NftItem,
+    Multisig,
+}
+
+impl From<ContractKind> for ContractInterface {
+    fn from(kind: ContractKind) -> Self {
+        match kind {
+            ContractKind::Wallet(version) => ContractInterface::Wallet(version),
+            ContractKind::JettonMinter => ContractInterface::JettonMaster,
+            ContractKind::JettonWallet => ContractInterface::JettonWallet,
+            ContractKind::NftCollection => ContractInterface::NftCollection,
+            ContractKind::NftItem => ContractInterface::NftItem,
+            ContractKind::Multisig => ContractInterface::Multisig,
+        }
+    }
+}
+
+/// One-call overview of the account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountSummary {
+    pub address: TonAddress,
+    pub status: AccountStatus,
+    pub balance: i64,
+    pub code_hash: Option<TonHash>,
+    pub last_transaction_id: InternalTransactionId,
+    /// Time of the last transaction.
+    pub last_activity: Option<i64>,
+    /// Time of the first transaction, known only if the whole history was scanned.
+    pub created_at: Option<i64>,
+    /// Number of transactions. It is a lower bound if `tx_count_exact` is false.
+    pub tx_count: usize,
+    pub tx_count_exact: bool,
+    pub interfaces: Vec<ContractInterface>,
+}
+
+impl TonContractFactory {
+    pub async fn account_summary(
+        &self,
+        address: &TonAddress,
+    ) -> Result<AccountSummary, TonContractError> {
+        self.account_summary_with_scan_limit(address, DEFAULT_SUMMARY_SCAN_LIMIT)
+            .await
+    }
+
+    /// Returns account summary, scanning at most `scan_limit` latest transactions.
+    pub async fn account_summary_with_scan_limit(
+        &self,
+        address: &TonAddress,
+        scan_limit: usize,
+    ) -> Result<AccountSummary, TonContractError> {
+        const METHOD: &str = "account_summary";
+        let state = self.get_latest_account_state(address).await?;
+        let status = AccountStatus::of(&state);
+        let code_hash = if state.code.is_empty() {
+            None
+        } else {
+            let boc = BagOfCells::parse(&state.code).map_cell_error(METHOD, address)?;
+            let code = boc.single_root().map_cell_error(METHOD, address)?;
+            Some(code.cell_hash())
+        };
+
+        let mut last_activity = None;
+        let mut created_at = None;
+        let mut tx_count = 0;
+        let mut tx_count_exact = state.last_transaction_id.lt == 0;
+        let mut next = state.last_transaction_id.clone();
+        while next.lt != 0 && tx_count < scan_limit {
+            let count = TRANSACTIONS_PAGE_SIZE.min(scan_limit - tx_count);
+            let txs = self
+                .client()
+                .get_raw_transactions_v2(address, &next, count, false)
+                .await?;
+            if txs.transactions.is_empty() {
+                break;
+            }
+            if last_activity.is_none() {
+                last_activity = txs.transactions.first().map(|tx| tx.utime);
+            }
+            tx_count += txs.transactions.len();
+            next = txs.previous_transaction_id;
+            if next.lt == 0 {
+                created_at = txs.transactions.last().map(|tx| tx.utime);
+                tx_count_exact = true;
+            }
+        }
+
+        let interfaces = match code_hash.as_ref() {
+            Some(code_hash) => self.detect_interfaces(address, code_hash).await,
+            None => vec![],
+        };
+
+        Ok(AccountSummary {
+            address: address.clone(),
+            status,
+            balance: state.balance,
+            code_hash,
+            last_transaction_id: state.last_transaction_id.clone(),
+            last_activity,
+            created_at,
+            tx_count,
+            tx_count_exact,
+            interfaces,
+        })
+    }
+
+    async fn detect_interfaces(
+        &self,
+        address: &TonAddress,
+        code_hash: &TonHash,
+    ) -> Vec<ContractInterface> {
+        if let Some(kind) = STANDARD_CODE_REGISTRY.kind_of(code_hash) {
+            return vec![kind.clone().into()];
+        }
+        let contract = self.get_contract(address);
+        let (jetton_master, jetton_wallet, nft_collection, nft_item) = join!(
+            contract.get_jetton_data(),
+            contract.get_wallet_data(),
+            contract.get_collection_data(),
+            contract.get_nft_data()
+        );
+        [
+            (jetton_master.is_ok(), ContractInterface::JettonMaster),
+            (jetton_wallet.is_ok(), ContractInterface::JettonWallet),
+            (nft_collection.is_ok(), ContractInterface::NftCollection),
+            (nft_item.is_ok(), ContractInterface::NftItem),
+        ]
+        .into_iter()
+        .filter_map(|(detected, interface)| detected.then_some(interface))
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::contract::AccountStatus;
+    use crate::tl::{BlockIdExt, RawFullAccountState, NULL_TRANSACTION_ID};
+
+    #[test]
+    fn test_account_status() {
+        let mut state = RawFullAccountState {
+            balance: 0,
+            code: vec![],
+            data: vec![],
+            last_transaction_id: NULL_TRANSACTION_ID.clone(),
+            block_id: BlockIdExt {
+                workchain: -1,
+                shard: i64::MIN,
+                seqno: 1,
+                root_hash: String::new(),
+                file_hash: String::new(),
+            },
+            frozen_hash: vec![],
+            sync_utime: 0,
+        };
+        assert_eq!(AccountStatus::of(&state), AccountStatus::Uninit);
+        state.frozen_hash = vec![1; 32];
+        assert_eq!(AccountStatus::of(&state), AccountStatus::Frozen);
+        state.code = vec![1];
+        assert_eq!(AccountStatus::of(&state), AccountStatus::Active);
+    }
+}
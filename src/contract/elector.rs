@@ -0,0 +1,46 @@
+use async_trait::async_trait;
+use num_bigint::{BigInt, BigUint, Sign};
+use strum::IntoStaticStr;
+
+use crate::address::TonAddress;
+use crate::contract::{MapStackError, TonContractError, TonContractInterface};
+use crate::types::TvmStackEntry;
+
+/// Address of the elector contract, config param 1 of the mainnet and testnet.
+pub const ELECTOR_ADDRESS: TonAddress = TonAddress::ELECTOR;
+
+#[derive(IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
+enum ElectorMethods {
+    ComputeReturnedStake,
+}
+
+#[async_trait]
+pub trait ElectorContract: TonContractInterface {
+    /// Returns the stake and bonuses of the validator wallet that are frozen no more and can be
+    /// recovered by `recover_stake` request.
+    async fn compute_returned_stake(
+        &self,
+        wallet_address: &TonAddress,
+    ) -> Result<BigUint, TonContractError> {
+        let method: &'static str = ElectorMethods::ComputeReturnedStake.into();
+        let address = self.address().clone();
+        let arg = BigInt::from_bytes_be(Sign::Plus, &wallet_address.hash_part);
+        let res = self
+            .run_get_method(method, [TvmStackEntry::Int257(arg)])
+            .await?;
+        let stack = res.stack;
+        if stack.len() == 1 {
+            stack[0].get_biguint().map_stack_error(method, &address)
+        } else {
+            Err(TonContractError::InvalidMethodResultStackSize {
+                method: method.to_string(),
+                address,
+                actual: stack.len(),
+                expected: 1,
+            })
+        }
+    }
+}
+
+impl<T> ElectorContract for T where T: TonContractInterface {}
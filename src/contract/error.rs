@@ -8,7 +8,12 @@ use crate::cell::TonCellError;
 use crate::client::TonClientError;
 use crate::emulator::TvmEmulatorError;
 use crate::tl::TvmStackError;
-use crate::types::{StackParseError, TonMethodId, TvmStackEntry};
+use crate::types::{
+    ContextualError, ErrorContext, StackParseError, TonMethodId, TvmError, TvmStackEntry,
+};
+
+/// TVM exit code of invalid or unsupported opcode.
+const TVM_EXIT_INVALID_OPCODE: i32 = 6;
 
 #[derive(Error, Debug)]
 pub enum TonContractError {
@@ -80,13 +85,14 @@ pub enum TonContractError {
     },
 
     #[error(
-        "Tvm run error (Method: {method}, address: {address}, exit code: {exit_code}, gas used: {gas_used}, stack: {stack:?}, vm_log: {vm_log:?}, missing_library: {missing_library:?})"
+        "Tvm run error (Method: {method}, address: {address}, exit code: {exit_code} ({tvm_error}), gas used: {gas_used}, stack: {stack:?}, vm_log: {vm_log:?}, missing_library: {missing_library:?})"
     )]
     TvmRunError {
         method: TonMethodId,
         address: TonAddress,
         vm_log: Option<String>,
         exit_code: i32,
+        tvm_error: TvmError,
         stack: Vec<TvmStackEntry>,
         missing_library: Option<String>,
         gas_used: i64,
@@ -98,6 +104,68 @@ pub enum TonContractError {
     CacheError(#[from] Arc<TonContractError>),
 }
 
+impl TonContractError {
+    /// Returns context of the underlying client error, if any. Other variants carry the method
+    /// and the address themselves.
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            TonContractError::ClientError(e) => e.context(),
+            _ => None,
+        }
+    }
+
+    /// Checks if the error is caused by the local emulator rather than by the contract, e.g.
+    /// by a missing library or an instruction unsupported by the emulator, so the get-method
+    /// may still succeed on a liteserver.
+    pub fn is_emulation_failure(&self) -> bool {
+        match self {
+            TonContractError::MethodEmulationError { .. }
+            | TonContractError::MissingLibrary { .. }
+            | TonContractError::LibraryNotFound { .. } => true,
+            TonContractError::TvmRunError {
+                exit_code,
+                missing_library,
+                ..
+            } => missing_library.is_some() || *exit_code == TVM_EXIT_INVALID_OPCODE,
+            #[cfg(feature = "state_cache")]
+            TonContractError::CacheError(e) => e.is_emulation_failure(),
+            _ => false,
+        }
+    }
+
+    /// Checks if the call may succeed when repeated. Client errors are classified by
+    /// `TonClientError::is_retryable`, TVM exit codes and decoding errors are deterministic.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            TonContractError::ClientError(e) => e.is_retryable(),
+            #[cfg(feature = "state_cache")]
+            TonContractError::CacheError(e) => e.is_retryable(),
+            _ => false,
+        }
+    }
+
+    /// Returns the error of the exit code, if the get-method failed.
+    pub fn tvm_error(&self) -> Option<TvmError> {
+        match self {
+            TonContractError::TvmRunError { tvm_error, .. } => Some(*tvm_error),
+            #[cfg(feature = "state_cache")]
+            TonContractError::CacheError(e) => e.tvm_error(),
+            _ => None,
+        }
+    }
+}
+
+impl ContextualError for TonContractError {
+    fn add_context(self, context: ErrorContext) -> Self {
+        match self {
+            TonContractError::ClientError(e) => {
+                TonContractError::ClientError(e.add_context(context))
+            }
+            error => error,
+        }
+    }
+}
+
 pub trait MapStackError<R> {
     fn map_stack_error(
         self,
@@ -155,3 +223,60 @@ impl<R> MapCellError<R> for Result<R, TonCellError> {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::address::TonAddress;
+    use crate::client::TonClientError;
+    use crate::contract::TonContractError;
+    use crate::types::{TonMethodId, TvmError, WithErrorContext};
+
+    fn run_error(exit_code: i32, missing_library: Option<String>) -> TonContractError {
+        TonContractError::TvmRunError {
+            method: TonMethodId::from("get_wallet_data"),
+            address: TonAddress::NULL,
+            vm_log: None,
+            exit_code,
+            tvm_error: TvmError::from_exit_code(exit_code).unwrap(),
+            stack: vec![],
+            missing_library,
+            gas_used: 0,
+        }
+    }
+
+    #[test]
+    fn test_is_emulation_failure() {
+        assert!(!run_error(11, None).is_emulation_failure());
+        assert!(run_error(6, None).is_emulation_failure());
+        assert!(run_error(9, Some("lib".to_string())).is_emulation_failure());
+        let not_found = TonContractError::LibraryNotFound {
+            address: TonAddress::NULL,
+            missing_library: "lib".to_string(),
+        };
+        assert!(not_found.is_emulation_failure());
+        assert!(!TonContractError::IllegalArgument("arg".to_string()).is_emulation_failure());
+    }
+
+    #[test]
+    fn test_tvm_error() {
+        let error = run_error(11, None);
+        assert_eq!(error.tvm_error(), Some(TvmError::UnknownMethod));
+        assert!(error.to_string().contains("exit code: 11 (unknown method)"));
+        assert_eq!(run_error(-14, None).tvm_error(), Some(TvmError::OutOfGas));
+        assert_eq!(
+            TonContractError::InternalError("e".to_string()).tvm_error(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        let timeout =
+            TonClientError::tonlib_error("smc.runGetMethod", 652, "adnl query timeout".to_string());
+        let error = Err::<(), _>(TonContractError::ClientError(timeout))
+            .with_method("seqno")
+            .unwrap_err();
+        assert!(error.is_retryable());
+        assert!(!run_error(-14, None).is_retryable());
+    }
+}
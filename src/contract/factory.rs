@@ -1,10 +1,13 @@
 use std::sync::Arc;
+#[cfg(feature = "metrics")]
+use std::sync::OnceLock;
 #[cfg(feature = "state_cache")]
 use std::time::Duration;
 
 pub use builder::*;
 #[cfg(feature = "state_cache")]
 pub use cache::*;
+pub use get_method_cache::*;
 pub use library_loader::*;
 pub use library_provider::*;
 use tokio::sync::OnceCell;
@@ -12,11 +15,14 @@ use tokio::sync::OnceCell;
 use crate::address::TonAddress;
 use crate::client::{TonClient, TonClientError, TonClientInterface};
 use crate::contract::{LoadedSmcState, TonContract, TonContractError, TonContractState};
+#[cfg(feature = "metrics")]
+use crate::metrics::{MetricsRegistry, METRIC_CACHE_REQUESTS_TOTAL};
 use crate::tl::{ConfigInfo, InternalTransactionId, RawFullAccountState};
 
 mod builder;
 #[cfg(feature = "state_cache")]
 mod cache;
+mod get_method_cache;
 mod library_loader;
 mod library_provider;
 
@@ -29,8 +35,12 @@ struct Inner {
     client: TonClient,
     config_info: OnceCell<ConfigInfo>,
     library_provider: LibraryProvider,
+    resolve_library_code: bool,
+    get_method_cache: Option<GetMethodCache>,
     #[cfg(feature = "state_cache")]
     cache: Option<ContractFactoryCache>,
+    #[cfg(feature = "metrics")]
+    metrics: OnceLock<Arc<dyn MetricsRegistry>>,
 }
 
 impl TonContractFactory {
@@ -48,7 +58,10 @@ impl TonContractFactory {
         txid_cache_capacity: u64,
         txid_cache_time_to_live: Duration,
         presync_blocks: i32,
+        memory_usage_log_interval: Option<Duration>,
         library_provider: LibraryProvider,
+        resolve_library_code: bool,
+        get_method_cache: Option<GetMethodCache>,
     ) -> Result<TonContractFactory, TonContractError> {
         let cache = if with_cache {
             let cache = ContractFactoryCache::new(
@@ -60,6 +73,9 @@ impl TonContractFactory {
                 presync_blocks,
             )
             .await?;
+            if let Some(interval) = memory_usage_log_interval {
+                cache.spawn_memory_usage_logging(interval);
+            }
             Some(cache)
         } else {
             None
@@ -70,6 +86,10 @@ impl TonContractFactory {
             config_info,
             cache,
             library_provider,
+            resolve_library_code,
+            get_method_cache,
+            #[cfg(feature = "metrics")]
+            metrics: OnceLock::new(),
         };
 
         Ok(TonContractFactory {
@@ -80,12 +100,18 @@ impl TonContractFactory {
     pub(crate) async fn new(
         client: &TonClient,
         library_provider: &LibraryProvider,
+        resolve_library_code: bool,
+        get_method_cache: Option<GetMethodCache>,
     ) -> Result<TonContractFactory, TonContractError> {
         let config_info = OnceCell::const_new();
         let inner = Inner {
             client: client.clone(),
             config_info,
             library_provider: library_provider.clone(),
+            resolve_library_code,
+            get_method_cache,
+            #[cfg(feature = "metrics")]
+            metrics: OnceLock::new(),
         };
         Ok(TonContractFactory {
             inner: Arc::new(inner),
@@ -109,6 +135,24 @@ impl TonContractFactory {
         self.inner.library_provider.clone()
     }
 
+    pub fn get_method_cache(&self) -> Option<&GetMethodCache> {
+        self.inner.get_method_cache.as_ref()
+    }
+
+    #[cfg(feature = "metrics")]
+    pub(crate) fn set_metrics(&self, metrics: Arc<dyn MetricsRegistry>) {
+        let _ = self.inner.metrics.set(metrics);
+    }
+
+    #[cfg(feature = "metrics")]
+    pub(crate) fn record_cache_access(&self, cache: &str, hit: bool) {
+        if let Some(metrics) = self.inner.metrics.get() {
+            let result = if hit { "hit" } else { "miss" };
+            let labels = [("cache", cache), ("result", result)];
+            metrics.increment_counter(METRIC_CACHE_REQUESTS_TOTAL, &labels);
+        }
+    }
+
     pub fn get_contract(&self, address: &TonAddress) -> TonContract {
         TonContract::new(self, address)
     }
@@ -118,13 +162,15 @@ impl TonContractFactory {
         &self,
         address: &TonAddress,
     ) -> Result<Arc<RawFullAccountState>, TonContractError> {
-        if let Some(cache) = self.inner.cache.as_ref() {
-            cache.get_account_state(address).await
+        let state = if let Some(cache) = self.inner.cache.as_ref() {
+            let (state, _hit) = cache.get_account_state_with_hit(address).await?;
+            #[cfg(feature = "metrics")]
+            self.record_cache_access("account_state", _hit);
+            state
         } else {
-            Ok(Arc::new(
-                self.client().get_raw_account_state(address).await?,
-            ))
-        }
+            Arc::new(self.client().get_raw_account_state(address).await?)
+        };
+        self.with_library_code(address, state).await
     }
 
     #[cfg(not(feature = "state_cache"))]
@@ -132,9 +178,8 @@ impl TonContractFactory {
         &self,
         address: &TonAddress,
     ) -> Result<Arc<RawFullAccountState>, TonContractError> {
-        Ok(Arc::new(
-            self.client().get_raw_account_state(address).await?,
-        ))
+        let state = Arc::new(self.client().get_raw_account_state(address).await?);
+        self.with_library_code(address, state).await
     }
 
     pub async fn get_account_state_by_transaction(
@@ -147,7 +192,32 @@ impl TonContractFactory {
             .client
             .get_raw_account_state_by_transaction(address, transaction_id)
             .await?;
-        Ok(state)
+        let state = self.with_library_code(address, Arc::new(state)).await?;
+        Ok(Arc::unwrap_or_clone(state))
+    }
+
+    /// Replaces library reference code of the account with the library code, unless disabled
+    /// by `TonContractFactoryBuilder::without_library_code_resolution`.
+    async fn with_library_code(
+        &self,
+        address: &TonAddress,
+        state: Arc<RawFullAccountState>,
+    ) -> Result<Arc<RawFullAccountState>, TonContractError> {
+        if !self.inner.resolve_library_code {
+            return Ok(state);
+        }
+        let library_provider = &self.inner.library_provider;
+        match library_provider
+            .resolve_library_code(address, &state.code)
+            .await?
+        {
+            Some(code) => {
+                let mut state = Arc::unwrap_or_clone(state);
+                state.code = code.to_vec();
+                Ok(Arc::new(state))
+            }
+            None => Ok(state),
+        }
     }
 
     #[cfg(feature = "state_cache")]
@@ -212,4 +282,13 @@ impl TonContractFactory {
             ContractFactoryCacheStats::default()
         }
     }
+
+    #[cfg(feature = "state_cache")]
+    pub fn get_factory_cache_memory_usage(&self) -> ContractFactoryCacheMemoryUsage {
+        if let Some(cache) = &self.inner.cache {
+            cache.get_memory_usage()
+        } else {
+            ContractFactoryCacheMemoryUsage::default()
+        }
+    }
 }
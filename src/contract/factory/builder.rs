@@ -2,9 +2,11 @@ use std::sync::Arc;
 #[cfg(feature = "state_cache")]
 use std::time::Duration;
 
-use super::{DefaultLibraryLoader, LibraryProvider};
+use super::{DefaultLibraryLoader, GetMethodCache, LibraryProvider};
 use crate::client::TonClient;
 use crate::contract::{TonContractError, TonContractFactory};
+#[cfg(feature = "metrics")]
+use crate::metrics::MetricsRegistry;
 
 #[cfg(feature = "state_cache")]
 pub struct TonContractFactoryBuilder {
@@ -15,7 +17,12 @@ pub struct TonContractFactoryBuilder {
     txid_cache_capacity: u64,
     txid_cache_time_to_live: Duration,
     presync_blocks: i32,
+    memory_usage_log_interval: Option<Duration>,
     library_provider: LibraryProvider,
+    resolve_library_code: bool,
+    get_method_cache: Option<GetMethodCache>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Arc<dyn MetricsRegistry>>,
 }
 
 #[cfg(feature = "state_cache")]
@@ -39,7 +46,12 @@ impl TonContractFactoryBuilder {
             txid_cache_capacity: 0,
             txid_cache_time_to_live: Duration::default(),
             presync_blocks: Self::DEFAULT_PRESYNC_BLOCKS,
+            memory_usage_log_interval: None,
             library_provider,
+            resolve_library_code: true,
+            get_method_cache: None,
+            #[cfg(feature = "metrics")]
+            metrics: None,
         }
     }
 
@@ -87,8 +99,14 @@ impl TonContractFactoryBuilder {
         self
     }
 
+    /// Enables periodic logging of cache memory usage.
+    pub fn with_memory_usage_logging(&mut self, interval: Duration) -> &mut Self {
+        self.memory_usage_log_interval = Some(interval);
+        self
+    }
+
     pub async fn build(&self) -> Result<TonContractFactory, TonContractError> {
-        TonContractFactory::new(
+        let factory = TonContractFactory::new(
             &self.client,
             self.with_cache,
             self.account_state_cache_capacity,
@@ -96,9 +114,13 @@ impl TonContractFactoryBuilder {
             self.txid_cache_capacity,
             self.txid_cache_time_to_live,
             self.presync_blocks,
+            self.memory_usage_log_interval,
             self.library_provider.clone(),
+            self.resolve_library_code,
+            self.get_method_cache.clone(),
         )
-        .await
+        .await?;
+        Ok(self.instrument(factory))
     }
 }
 
@@ -106,6 +128,10 @@ impl TonContractFactoryBuilder {
 pub struct TonContractFactoryBuilder {
     client: TonClient,
     library_provider: LibraryProvider,
+    resolve_library_code: bool,
+    get_method_cache: Option<GetMethodCache>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Arc<dyn MetricsRegistry>>,
 }
 
 #[cfg(not(feature = "state_cache"))]
@@ -116,11 +142,22 @@ impl TonContractFactoryBuilder {
         TonContractFactoryBuilder {
             client: client.clone(),
             library_provider,
+            resolve_library_code: true,
+            get_method_cache: None,
+            #[cfg(feature = "metrics")]
+            metrics: None,
         }
     }
 
     pub async fn build(&self) -> Result<TonContractFactory, TonContractError> {
-        TonContractFactory::new(&self.client, &self.library_provider).await
+        let factory = TonContractFactory::new(
+            &self.client,
+            &self.library_provider,
+            self.resolve_library_code,
+            self.get_method_cache.clone(),
+        )
+        .await?;
+        Ok(self.instrument(factory))
     }
 }
 
@@ -136,4 +173,39 @@ impl TonContractFactoryBuilder {
         self.library_provider = library_provider.clone();
         self
     }
+
+    /// Keeps library reference cells as account code instead of replacing them with the
+    /// library code.
+    pub fn without_library_code_resolution(&mut self) -> &mut Self {
+        self.resolve_library_code = false;
+        self
+    }
+
+    /// Enables caching of get-method results, invalidated when the account gets a new transaction.
+    /// While the cache is enabled, get-methods of states without pinned time run with `now`
+    /// equal to the sync time of the state, see `TonContractState::with_unix_time`.
+    pub fn with_get_method_cache(&mut self, capacity: u64) -> &mut Self {
+        self.get_method_cache = Some(GetMethodCache::new(capacity));
+        self
+    }
+
+    pub fn without_get_method_cache(&mut self) -> &mut Self {
+        self.get_method_cache = None;
+        self
+    }
+
+    /// Reports hits and misses of the account state and get-method caches to the registry.
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics(&mut self, metrics: Arc<dyn MetricsRegistry>) -> &mut Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    fn instrument(&self, factory: TonContractFactory) -> TonContractFactory {
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            factory.set_metrics(metrics.clone());
+        }
+        factory
+    }
 }
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::mem::size_of;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Weak};
 use std::time::Duration;
 
@@ -78,18 +79,31 @@ impl ContractFactoryCache {
         &self,
         address: &TonAddress,
     ) -> Result<Arc<RawFullAccountState>, TonContractError> {
+        let (state, _) = self.get_account_state_with_hit(address).await?;
+        Ok(state)
+    }
+
+    /// Returns account state and whether it was found in the cache.
+    pub(crate) async fn get_account_state_with_hit(
+        &self,
+        address: &TonAddress,
+    ) -> Result<(Arc<RawFullAccountState>, bool), TonContractError> {
         self.inner
             .account_state_cache_counters
             .hits
             .fetch_add(1, Ordering::Relaxed);
+        let loaded = AtomicBool::new(false);
         let state_result = self
             .inner
             .account_state_cache
-            .try_get_with_by_ref(address, self.load_account_state(address))
+            .try_get_with_by_ref(address, async {
+                loaded.store(true, Ordering::Relaxed);
+                self.load_account_state(address).await
+            })
             .await;
 
         match state_result {
-            Ok(state) => Ok(state),
+            Ok(state) => Ok((state, !loaded.load(Ordering::Relaxed))),
             Err(e) => Err(TonContractError::CacheError(e.clone())),
         }
     }
@@ -209,6 +223,62 @@ impl ContractFactoryCache {
             account_state_cache_entry_count: self.inner.account_state_cache.entry_count(),
         }
     }
+
+    /// Returns approximate heap and stack memory taken by cache entries.
+    pub fn get_memory_usage(&self) -> ContractFactoryCacheMemoryUsage {
+        let tx_id_cache_bytes = self
+            .inner
+            .tx_id_cache
+            .iter()
+            .map(|(_, tx_id)| CACHE_ENTRY_OVERHEAD + tx_id_size(&tx_id))
+            .sum();
+        let account_state_cache_bytes = self
+            .inner
+            .account_state_cache
+            .iter()
+            .map(|(_, state)| CACHE_ENTRY_OVERHEAD + account_state_size(&state))
+            .sum();
+        ContractFactoryCacheMemoryUsage {
+            tx_id_cache_bytes,
+            account_state_cache_bytes,
+        }
+    }
+
+    /// Periodically logs memory usage (at info level) until the cache is dropped.
+    pub fn spawn_memory_usage_logging(&self, interval: Duration) {
+        let weak_inner = Arc::downgrade(&self.inner);
+        tokio::task::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                let Some(inner) = weak_inner.upgrade() else {
+                    break;
+                };
+                let usage = ContractFactoryCache { inner }.get_memory_usage();
+                log::info!(
+                    "[ContractFactoryCache] Memory usage: tx_id_cache: {} bytes, account_state_cache: {} bytes",
+                    usage.tx_id_cache_bytes,
+                    usage.account_state_cache_bytes
+                );
+            }
+        });
+    }
+}
+
+/// Key and `Arc` pointer and counters of a cache entry.
+const CACHE_ENTRY_OVERHEAD: u64 = (size_of::<TonAddress>() + 3 * size_of::<usize>()) as u64;
+
+fn tx_id_size(tx_id: &InternalTransactionId) -> u64 {
+    (size_of::<InternalTransactionId>() + tx_id.hash.capacity()) as u64
+}
+
+fn account_state_size(state: &RawFullAccountState) -> u64 {
+    (size_of::<RawFullAccountState>()
+        + state.code.capacity()
+        + state.data.capacity()
+        + state.frozen_hash.capacity()
+        + state.last_transaction_id.hash.capacity()
+        + state.block_id.root_hash.capacity()
+        + state.block_id.file_hash.capacity()) as u64
 }
 
 struct Inner {
@@ -278,8 +348,54 @@ pub struct ContractFactoryCacheStats {
     pub account_state_cache_entry_count: u64,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContractFactoryCacheMemoryUsage {
+    pub tx_id_cache_bytes: u64,
+    pub account_state_cache_bytes: u64,
+}
+
+impl ContractFactoryCacheMemoryUsage {
+    pub fn total_bytes(&self) -> u64 {
+        self.tx_id_cache_bytes + self.account_state_cache_bytes
+    }
+}
+
 #[derive(Default)]
 struct ContractFactoryCacheCounters {
     hits: AtomicU64,
     misses: AtomicU64,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::mem::size_of;
+
+    use super::account_state_size;
+    use crate::tl::{BlockIdExt, InternalTransactionId, RawFullAccountState};
+
+    #[test]
+    fn test_account_state_size() {
+        let state = RawFullAccountState {
+            balance: 0,
+            code: vec![0; 100],
+            data: vec![0; 50],
+            last_transaction_id: InternalTransactionId {
+                lt: 0,
+                hash: vec![0; 32],
+            },
+            block_id: BlockIdExt {
+                workchain: -1,
+                shard: 0,
+                seqno: 0,
+                root_hash: String::new(),
+                file_hash: String::new(),
+            },
+            frozen_hash: vec![],
+            sync_utime: 0,
+        };
+        assert_eq!(
+            account_state_size(&state),
+            (size_of::<RawFullAccountState>() + 182) as u64
+        );
+    }
+}
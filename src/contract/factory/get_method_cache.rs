@@ -0,0 +1,209 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use moka::future::Cache;
+use num_bigint::BigInt;
+
+use crate::address::TonAddress;
+use crate::types::{TonHash, TonMethodId, TvmStackEntry, TvmSuccess};
+
+/// Cache of get-method results keyed by account address, method, arguments, the logical
+/// time of the last account transaction and `now` of the emulation.
+///
+/// Results are invalidated implicitly: once the account changes, its state has a new
+/// `last_transaction_id.lt`, so stale entries are never hit and are eventually evicted.
+/// Results depend on `now` of c7, so get-methods of states without pinned time run at the sync
+/// time of the state. Runs with a custom `rand_seed` are not cached.
+#[derive(Clone)]
+pub struct GetMethodCache {
+    cache: Cache<GetMethodCacheKey, Arc<TvmSuccess>>,
+    counters: Arc<GetMethodCacheCounters>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct GetMethodCacheKey {
+    address: TonAddress,
+    method_id: i32,
+    stack: Vec<StackKeyEntry>,
+    lt: i64,
+    now: u64,
+}
+
+/// Hashable representation of a stack entry, cells are represented by their hashes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum StackKeyEntry {
+    Null,
+    Nan,
+    Int64(i64),
+    Int257(BigInt),
+    Cell(TonHash),
+    Slice {
+        cell: TonHash,
+        start_bit: usize,
+        end_bit: usize,
+        start_ref: usize,
+        end_ref: usize,
+    },
+    Tuple(Vec<StackKeyEntry>),
+    List(Vec<StackKeyEntry>),
+    Unsupported,
+}
+
+impl From<&TvmStackEntry> for StackKeyEntry {
+    fn from(value: &TvmStackEntry) -> Self {
+        match value {
+            TvmStackEntry::Null => StackKeyEntry::Null,
+            TvmStackEntry::Nan => StackKeyEntry::Nan,
+            TvmStackEntry::Int64(n) => StackKeyEntry::Int64(*n),
+            TvmStackEntry::Int257(n) => StackKeyEntry::Int257(n.clone()),
+            TvmStackEntry::Cell(cell) => StackKeyEntry::Cell(cell.cell_hash()),
+            TvmStackEntry::Slice(slice) => StackKeyEntry::Slice {
+                cell: slice.cell.cell_hash(),
+                start_bit: slice.start_bit,
+                end_bit: slice.end_bit,
+                start_ref: slice.start_ref,
+                end_ref: slice.end_ref,
+            },
+            TvmStackEntry::Tuple(elements) => {
+                StackKeyEntry::Tuple(elements.iter().map(StackKeyEntry::from).collect())
+            }
+            TvmStackEntry::List(elements) => {
+                StackKeyEntry::List(elements.iter().map(StackKeyEntry::from).collect())
+            }
+            TvmStackEntry::Unsupported => StackKeyEntry::Unsupported,
+        }
+    }
+}
+
+#[derive(Default)]
+struct GetMethodCacheCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GetMethodCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entry_count: u64,
+}
+
+impl GetMethodCache {
+    pub fn new(capacity: u64) -> GetMethodCache {
+        GetMethodCache {
+            cache: Cache::builder().max_capacity(capacity).build(),
+            counters: Arc::new(GetMethodCacheCounters::default()),
+        }
+    }
+
+    pub async fn get(
+        &self,
+        address: &TonAddress,
+        lt: i64,
+        now: u64,
+        method: &TonMethodId,
+        stack: &[TvmStackEntry],
+    ) -> Option<TvmSuccess> {
+        let key = GetMethodCacheKey::new(address, lt, now, method, stack);
+        match self.cache.get(&key).await {
+            Some(result) => {
+                self.counters.hits.fetch_add(1, Ordering::Relaxed);
+                Some(result.as_ref().clone())
+            }
+            None => {
+                self.counters.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    pub async fn insert(
+        &self,
+        address: &TonAddress,
+        lt: i64,
+        now: u64,
+        method: &TonMethodId,
+        stack: &[TvmStackEntry],
+        result: &TvmSuccess,
+    ) {
+        let key = GetMethodCacheKey::new(address, lt, now, method, stack);
+        self.cache.insert(key, Arc::new(result.clone())).await
+    }
+
+    pub fn get_stats(&self) -> GetMethodCacheStats {
+        GetMethodCacheStats {
+            hits: self.counters.hits.load(Ordering::Relaxed),
+            misses: self.counters.misses.load(Ordering::Relaxed),
+            entry_count: self.cache.entry_count(),
+        }
+    }
+}
+
+impl GetMethodCacheKey {
+    fn new(
+        address: &TonAddress,
+        lt: i64,
+        now: u64,
+        method: &TonMethodId,
+        stack: &[TvmStackEntry],
+    ) -> GetMethodCacheKey {
+        GetMethodCacheKey {
+            address: address.clone(),
+            method_id: method.to_id(),
+            stack: stack.iter().map(StackKeyEntry::from).collect(),
+            lt,
+            now,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigInt;
+
+    use crate::address::TonAddress;
+    use crate::contract::GetMethodCache;
+    use crate::types::{TonMethodId, TvmRunSource, TvmStackEntry, TvmSuccess};
+
+    #[tokio::test]
+    async fn test_get_method_cache_invalidated_by_lt() {
+        let cache = GetMethodCache::new(100);
+        let address = TonAddress::NULL;
+        let method = TonMethodId::from("get_wallet_data");
+        let stack = vec![TvmStackEntry::Int257(BigInt::from(1))];
+        let result = TvmSuccess {
+            vm_log: None,
+            vm_exit_code: 0,
+            stack: vec![TvmStackEntry::Int64(42)],
+            missing_library: None,
+            gas_used: 100,
+            source: TvmRunSource::Emulator,
+        };
+
+        let now = 1_700_000_000;
+        assert!(cache
+            .get(&address, 10, now, &method, &stack)
+            .await
+            .is_none());
+        cache
+            .insert(&address, 10, now, &method, &stack, &result)
+            .await;
+        let cached = cache.get(&address, 10, now, &method, &stack).await.unwrap();
+        assert_eq!(cached.stack, result.stack);
+
+        let by_id = TonMethodId::from(method.to_id());
+        assert!(cache.get(&address, 10, now, &by_id, &stack).await.is_some());
+        assert!(cache
+            .get(&address, 11, now, &method, &stack)
+            .await
+            .is_none());
+        assert!(cache.get(&address, 10, now, &method, &[]).await.is_none());
+        assert!(cache
+            .get(&address, 10, now + 1, &method, &stack)
+            .await
+            .is_none());
+
+        let stats = cache.get_stats();
+        assert_eq!((stats.hits, stats.misses), (2, 4));
+    }
+}
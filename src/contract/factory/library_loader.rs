@@ -8,6 +8,7 @@ use crate::address::TonAddress;
 use crate::client::{TonClient, TonClientInterface};
 use crate::contract::TonContractError;
 use crate::tl::{SmcLibraryQueryExt, TonLibraryId};
+use crate::types::TonHash;
 
 pub struct ContractLibraryDict {
     pub dict_boc: Vec<u8>,
@@ -21,6 +22,15 @@ pub trait LibraryLoader: Send + Sync {
         address: &TonAddress,
         code: &[u8],
     ) -> Result<Arc<ContractLibraryDict>, TonContractError>;
+
+    /// Returns BoC of the library with the hash, `None` if it is not found.
+    async fn load_library(
+        &self,
+        _address: &TonAddress,
+        _hash: &TonHash,
+    ) -> Result<Option<Vec<u8>>, TonContractError> {
+        Ok(None)
+    }
 }
 
 pub struct DefaultLibraryLoader {
@@ -66,4 +76,19 @@ impl LibraryLoader for DefaultLibraryLoader {
         let contract_libraies = ContractLibraryDict { dict_boc, keys };
         Ok(Arc::new(contract_libraies))
     }
+
+    async fn load_library(
+        &self,
+        _address: &TonAddress,
+        hash: &TonHash,
+    ) -> Result<Option<Vec<u8>>, TonContractError> {
+        let library_id = TonLibraryId { id: hash.to_vec() };
+        let library_result = self.client.smc_get_libraries(&[library_id]).await?;
+        let library = library_result
+            .result
+            .into_iter()
+            .find(|entry| entry.hash == hash.as_slice())
+            .map(|entry| entry.data);
+        Ok(library)
+    }
 }
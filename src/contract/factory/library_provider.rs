@@ -1,18 +1,31 @@
 use std::sync::Arc;
 
+use moka::future::Cache;
+
 use super::{ContractLibraryDict, LibraryLoader};
 use crate::address::TonAddress;
+use crate::cell::BagOfCells;
 use crate::contract::TonContractError;
 use crate::tl::RawFullAccountState;
+use crate::types::TonHash;
+
+const LIBRARY_CACHE_CAPACITY: u64 = 1024;
+/// Library reference cell takes 33 bytes, its BoC is about 50 bytes.
+const MAX_LIBRARY_REFERENCE_BOC_LEN: usize = 128;
+const LIBRARY_CELL_TYPE: u8 = 2;
 
 #[derive(Clone)]
 pub struct LibraryProvider {
     loader: Arc<dyn LibraryLoader>,
+    library_cache: Cache<TonHash, Arc<Vec<u8>>>,
 }
 
 impl LibraryProvider {
     pub fn new(loader: Arc<dyn LibraryLoader>) -> LibraryProvider {
-        LibraryProvider { loader }
+        LibraryProvider {
+            loader,
+            library_cache: Cache::new(LIBRARY_CACHE_CAPACITY),
+        }
     }
 
     pub async fn get_contract_libraries(
@@ -32,4 +45,72 @@ impl LibraryProvider {
         // TODO cache
         self.loader.load_contract_libraries(address, code).await
     }
+
+    /// Returns BoC of the library code if the contract code is a library reference cell,
+    /// `None` for other codes and for libraries the loader can't find.
+    pub async fn resolve_library_code(
+        &self,
+        address: &TonAddress,
+        code: &[u8],
+    ) -> Result<Option<Arc<Vec<u8>>>, TonContractError> {
+        let Some(hash) = library_reference_hash(code) else {
+            return Ok(None);
+        };
+        if let Some(library) = self.library_cache.get(&hash).await {
+            return Ok(Some(library));
+        }
+        match self.loader.load_library(address, &hash).await? {
+            Some(library) => {
+                let library = Arc::new(library);
+                self.library_cache.insert(hash, library.clone()).await;
+                Ok(Some(library))
+            }
+            None => {
+                log::warn!(
+                    "Library {} referenced by code of {} is not found",
+                    hex::encode(hash),
+                    address
+                );
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Returns hash of the library if the code is a library reference cell.
+fn library_reference_hash(code: &[u8]) -> Option<TonHash> {
+    if code.len() > MAX_LIBRARY_REFERENCE_BOC_LEN {
+        return None;
+    }
+    let boc = BagOfCells::parse(code).ok()?;
+    let root = boc.single_root().ok()?;
+    let data = root.data();
+    if !root.is_exotic() || data.first() != Some(&LIBRARY_CELL_TYPE) {
+        return None;
+    }
+    data.get(1..33)?.try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cell::{BagOfCells, CellBuilder};
+    use crate::contract::factory::library_provider::library_reference_hash;
+
+    #[test]
+    fn test_library_reference_hash() -> anyhow::Result<()> {
+        let mut builder = CellBuilder::new();
+        builder.set_cell_is_exotic(true);
+        let library_ref = builder.store_u8(8, 2)?.store_slice(&[7; 32])?.build()?;
+        let code = BagOfCells::from_root(library_ref).serialize(false)?;
+        assert_eq!(library_reference_hash(&code), Some([7; 32]));
+
+        let ordinary = CellBuilder::new()
+            .store_u8(8, 2)?
+            .store_slice(&[7; 32])?
+            .build()?;
+        let code = BagOfCells::from_root(ordinary).serialize(false)?;
+        assert_eq!(library_reference_hash(&code), None);
+        assert_eq!(library_reference_hash(&[]), None);
+        Ok(())
+    }
 }
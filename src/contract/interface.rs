@@ -7,7 +7,7 @@ use crate::address::TonAddress;
 use crate::client::TonConnection;
 use crate::contract::TonContractFactory;
 use crate::tl::RawFullAccountState;
-use crate::types::{TonMethodId, TvmStackEntry, TvmSuccess};
+use crate::types::{FromTvmStack, ToTvmStack, TonMethodId, TvmStackEntry, TvmSuccess};
 
 pub struct LoadedSmcState {
     pub conn: TonConnection,
@@ -30,4 +30,32 @@ pub trait TonContractInterface {
     where
         M: Into<TonMethodId> + Send + Copy,
         S: AsRef<[TvmStackEntry]> + Send;
+
+    /// Runs the get-method with typed arguments and result, e.g.
+    /// `let wallet: TonAddress = contract.call_get_method("get_wallet_address", (&owner,)).await?`.
+    async fn call_get_method<M, A, R>(&self, method: M, args: A) -> Result<R, TonContractError>
+    where
+        M: Into<TonMethodId> + Send + Copy,
+        A: ToTvmStack + Send,
+        R: FromTvmStack,
+    {
+        let method_id: TonMethodId = method.into();
+        let address = self.address().clone();
+        let stack_error = |error| TonContractError::TvmStackParseError {
+            method: method_id.clone(),
+            address: address.clone(),
+            error,
+        };
+        let stack = args.to_tvm_stack().map_err(stack_error)?;
+        let res = self.run_get_method(method, stack).await?;
+        if let Some(expected) = R::STACK_LEN.filter(|len| *len != res.stack.len()) {
+            return Err(TonContractError::InvalidMethodResultStackSize {
+                method: method_id.to_string(),
+                address,
+                actual: res.stack.len(),
+                expected,
+            });
+        }
+        R::from_tvm_stack(&res.stack).map_err(stack_error)
+    }
 }
@@ -1,5 +1,9 @@
+mod amount;
+mod error;
 mod master_contract;
 mod wallet_contract;
 
+pub use amount::*;
+pub use error::*;
 pub use master_contract::*;
 pub use wallet_contract::*;
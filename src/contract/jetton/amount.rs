@@ -0,0 +1,200 @@
+use std::fmt;
+use std::str::FromStr;
+
+use num_bigint::BigUint;
+use num_traits::{ToPrimitive, Zero};
+
+use crate::contract::JettonAmountError;
+use crate::meta::JettonMetaData;
+
+/// Decimals used if jetton metadata doesn't specify them (TEP-64).
+pub const DEFAULT_JETTON_DECIMALS: u8 = 9;
+
+/// Jetton amount in minimal units together with the number of decimals of the jetton.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct JettonAmount {
+    pub raw: BigUint,
+    pub decimals: u8,
+}
+
+impl JettonAmount {
+    pub fn new(raw: BigUint, decimals: u8) -> JettonAmount {
+        JettonAmount { raw, decimals }
+    }
+
+    /// Creates amount using decimals from the metadata or `DEFAULT_JETTON_DECIMALS`.
+    pub fn from_meta(raw: BigUint, meta: &JettonMetaData) -> JettonAmount {
+        JettonAmount::new(raw, meta.decimals.unwrap_or(DEFAULT_JETTON_DECIMALS))
+    }
+
+    /// Parses user representation of the amount, e.g. `"12.5"`.
+    pub fn parse(amount: &str, decimals: u8) -> Result<JettonAmount, JettonAmountError> {
+        let invalid = || JettonAmountError::InvalidAmount(amount.to_string());
+        let (int_part, frac_part) = match amount.trim().split_once('.') {
+            Some((int_part, frac_part)) => (int_part, frac_part),
+            None => (amount.trim(), ""),
+        };
+        let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        if (int_part.is_empty() && frac_part.is_empty())
+            || !is_digits(int_part)
+            || !is_digits(frac_part)
+        {
+            return Err(invalid());
+        }
+        let frac_part = frac_part.trim_end_matches('0');
+        if frac_part.len() > decimals as usize {
+            return Err(JettonAmountError::TooManyDecimals {
+                amount: amount.to_string(),
+                decimals,
+            });
+        }
+        let digits = format!(
+            "{}{}{}",
+            int_part,
+            frac_part,
+            "0".repeat(decimals as usize - frac_part.len())
+        );
+        let raw = BigUint::from_str(&digits).map_err(|_| invalid())?;
+        Ok(JettonAmount::new(raw, decimals))
+    }
+
+    /// Returns user representation of the amount without trailing zeros, e.g. `"12.5"`.
+    pub fn format(&self) -> String {
+        let digits = self.raw.to_string();
+        let decimals = self.decimals as usize;
+        if decimals == 0 {
+            return digits;
+        }
+        let digits = format!("{:0>width$}", digits, width = decimals + 1);
+        let (int_part, frac_part) = digits.split_at(digits.len() - decimals);
+        let frac_part = frac_part.trim_end_matches('0');
+        if frac_part.is_empty() {
+            int_part.to_string()
+        } else {
+            format!("{}.{}", int_part, frac_part)
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.raw.is_zero()
+    }
+
+    pub fn checked_add(&self, other: &JettonAmount) -> Result<JettonAmount, JettonAmountError> {
+        self.check_decimals(other)?;
+        Ok(JettonAmount::new(&self.raw + &other.raw, self.decimals))
+    }
+
+    /// Returns `None` if the result is negative.
+    pub fn checked_sub(
+        &self,
+        other: &JettonAmount,
+    ) -> Result<Option<JettonAmount>, JettonAmountError> {
+        self.check_decimals(other)?;
+        Ok((self.raw >= other.raw)
+            .then(|| JettonAmount::new(&self.raw - &other.raw, self.decimals)))
+    }
+
+    /// Converts the amount to other decimals. Returns `None` if precision would be lost.
+    pub fn with_decimals(&self, decimals: u8) -> Option<JettonAmount> {
+        if decimals >= self.decimals {
+            let scale = BigUint::from(10u32).pow((decimals - self.decimals) as u32);
+            Some(JettonAmount::new(&self.raw * scale, decimals))
+        } else {
+            let scale = BigUint::from(10u32).pow((self.decimals - decimals) as u32);
+            (&self.raw % &scale)
+                .is_zero()
+                .then(|| JettonAmount::new(&self.raw / &scale, decimals))
+        }
+    }
+
+    /// Returns raw amount if it fits into `u64`.
+    pub fn to_raw_u64(&self) -> Option<u64> {
+        self.raw.to_u64()
+    }
+
+    /// Returns raw amount if it fits into `u128`.
+    pub fn to_raw_u128(&self) -> Option<u128> {
+        self.raw.to_u128()
+    }
+
+    /// Returns approximate user representation of the amount. Use only for display or estimates.
+    pub fn to_f64_lossy(&self) -> f64 {
+        self.format().parse().unwrap_or(f64::NAN)
+    }
+
+    fn check_decimals(&self, other: &JettonAmount) -> Result<(), JettonAmountError> {
+        if self.decimals == other.decimals {
+            Ok(())
+        } else {
+            Err(JettonAmountError::DecimalsMismatch(
+                self.decimals,
+                other.decimals,
+            ))
+        }
+    }
+}
+
+impl fmt::Display for JettonAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.format())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use crate::contract::{JettonAmount, JettonAmountError};
+
+    #[test]
+    fn test_jetton_amount_format() {
+        assert_eq!(
+            JettonAmount::new(BigUint::from(12_500_000_000u64), 9).format(),
+            "12.5"
+        );
+        assert_eq!(
+            JettonAmount::new(BigUint::from(1u32), 9).format(),
+            "0.000000001"
+        );
+        assert_eq!(JettonAmount::new(BigUint::from(0u32), 6).format(), "0");
+        assert_eq!(JettonAmount::new(BigUint::from(120u32), 0).format(), "120");
+    }
+
+    #[test]
+    fn test_jetton_amount_parse() -> anyhow::Result<()> {
+        assert_eq!(
+            JettonAmount::parse("12.5", 9)?.raw,
+            BigUint::from(12_500_000_000u64)
+        );
+        assert_eq!(JettonAmount::parse(".5", 1)?.raw, BigUint::from(5u32));
+        assert_eq!(JettonAmount::parse("7.10", 1)?.raw, BigUint::from(71u32));
+        assert_eq!(
+            JettonAmount::parse("0.0000001", 6),
+            Err(JettonAmountError::TooManyDecimals {
+                amount: "0.0000001".to_string(),
+                decimals: 6
+            })
+        );
+        assert!(JettonAmount::parse("-1", 9).is_err());
+        assert!(JettonAmount::parse("1.2.3", 9).is_err());
+        assert!(JettonAmount::parse(".", 9).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_jetton_amount_arithmetic() -> anyhow::Result<()> {
+        let a = JettonAmount::parse("1.5", 6)?;
+        let b = JettonAmount::parse("2", 6)?;
+        assert_eq!(a.checked_add(&b)?.format(), "3.5");
+        assert_eq!(a.checked_sub(&b)?, None);
+        assert_eq!(b.checked_sub(&a)?.unwrap().format(), "0.5");
+        assert!(a.checked_add(&JettonAmount::parse("1", 9)?).is_err());
+        assert_eq!(
+            a.with_decimals(9).unwrap().raw,
+            BigUint::from(1_500_000_000u64)
+        );
+        assert_eq!(a.with_decimals(0), None);
+        assert_eq!(b.with_decimals(0).unwrap().to_raw_u64(), Some(2));
+        Ok(())
+    }
+}
@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum JettonAmountError {
+    #[error("Invalid jetton amount ({0})")]
+    InvalidAmount(String),
+
+    #[error("Too many fractional digits (Amount: {amount}, decimals: {decimals})")]
+    TooManyDecimals { amount: String, decimals: u8 },
+
+    #[error("Decimals mismatch ({0} != {1})")]
+    DecimalsMismatch(u8, u8),
+}
@@ -3,10 +3,10 @@ use num_bigint::BigUint;
 use strum::IntoStaticStr;
 
 use crate::address::TonAddress;
-use crate::cell::{ArcCell, BagOfCells, CellBuilder, CellSlice, TonCellError};
+use crate::cell::{ArcCell, BagOfCells, TonCellError};
 use crate::contract::{MapCellError, MapStackError, TonContractError, TonContractInterface};
 use crate::meta::MetaDataContent;
-use crate::types::TvmStackEntry;
+use crate::types::{StackDecodeMode, StackDecodeWarning, StackDecoder};
 
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct JettonData {
@@ -17,6 +17,17 @@ pub struct JettonData {
     pub wallet_code: ArcCell,
 }
 
+/// Jetton data decoded in lax mode: entries not matching the standard layout are `None`.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct PartialJettonData {
+    pub total_supply: Option<BigUint>,
+    pub mintable: Option<bool>,
+    pub admin_address: Option<TonAddress>,
+    pub content: Option<MetaDataContent>,
+    pub wallet_code: Option<ArcCell>,
+    pub warnings: Vec<StackDecodeWarning>,
+}
+
 #[derive(IntoStaticStr)]
 #[strum(serialize_all = "snake_case")]
 enum JettonMasterMethods {
@@ -58,31 +69,48 @@ pub trait JettonMasterContract: TonContractInterface {
         }
     }
 
+    /// Same as `get_jetton_data`, but tolerates non-standard result stack of the contract.
+    async fn get_jetton_data_lax(&self) -> Result<PartialJettonData, TonContractError> {
+        const JETTON_DATA_STACK_ELEMENTS: usize = 5;
+        let method = JettonMasterMethods::GetJettonData.into();
+        let address = self.address().clone();
+
+        let res = self.run_get_method(method, Vec::new()).await?;
+
+        let mut decoder =
+            StackDecoder::new(&res.stack, JETTON_DATA_STACK_ELEMENTS, StackDecodeMode::Lax)
+                .map_stack_error(method, &address)?;
+        let total_supply = decoder
+            .decode(0, |e| e.get_biguint())
+            .map_stack_error(method, &address)?;
+        let mintable = decoder
+            .decode(1, |e| e.get_bool())
+            .map_stack_error(method, &address)?;
+        let admin_address = decoder
+            .decode(2, |e| e.get_address())
+            .map_stack_error(method, &address)?;
+        let content = decoder
+            .decode(3, |e| Ok(read_jetton_metadata_content(e.get_cell()?)?))
+            .map_stack_error(method, &address)?;
+        let wallet_code = decoder
+            .decode(4, |e| e.get_cell())
+            .map_stack_error(method, &address)?;
+        Ok(PartialJettonData {
+            total_supply,
+            mintable,
+            admin_address,
+            content,
+            wallet_code,
+            warnings: decoder.into_warnings(),
+        })
+    }
+
     async fn get_wallet_address(
         &self,
         owner_address: &TonAddress,
     ) -> Result<TonAddress, TonContractError> {
         let method: &'static str = JettonMasterMethods::GetWalletAddress.into();
-        let address = self.address().clone();
-        let cell = CellBuilder::new()
-            .store_address(owner_address)
-            .map_cell_error(method, owner_address)?
-            .build()
-            .map_cell_error(method, owner_address)?;
-        let cell_slice = CellSlice::full_cell(cell).map_cell_error(method, owner_address)?;
-        let slice = TvmStackEntry::Slice(cell_slice);
-        let res = self.run_get_method(method, &vec![slice]).await?;
-        let stack = res.stack;
-        if stack.len() == 1 {
-            stack[0].get_address().map_stack_error(method, &address)
-        } else {
-            Err(TonContractError::InvalidMethodResultStackSize {
-                method: method.to_string(),
-                address: self.address().clone(),
-                actual: stack.len(),
-                expected: 1,
-            })
-        }
+        self.call_get_method(method, (owner_address,)).await
     }
 }
 
@@ -5,6 +5,7 @@ use strum::IntoStaticStr;
 use crate::address::TonAddress;
 use crate::cell::ArcCell;
 use crate::contract::{MapStackError, TonContractError, TonContractInterface};
+use crate::types::{StackDecodeMode, StackDecodeWarning, StackDecoder};
 
 #[derive(Debug, Clone)]
 pub struct WalletData {
@@ -14,6 +15,16 @@ pub struct WalletData {
     pub wallet_code: ArcCell,
 }
 
+/// Jetton wallet data decoded in lax mode: entries not matching the standard layout are `None`.
+#[derive(Debug, Clone)]
+pub struct PartialWalletData {
+    pub balance: Option<BigUint>,
+    pub owner_address: Option<TonAddress>,
+    pub master_address: Option<TonAddress>,
+    pub wallet_code: Option<ArcCell>,
+    pub warnings: Vec<StackDecodeWarning>,
+}
+
 #[derive(IntoStaticStr)]
 #[strum(serialize_all = "snake_case")]
 enum JettonWalletMethods {
@@ -50,6 +61,38 @@ pub trait JettonWalletContract: TonContractInterface {
             })
         }
     }
+
+    /// Same as `get_wallet_data`, but tolerates non-standard result stack of the contract.
+    async fn get_wallet_data_lax(&self) -> Result<PartialWalletData, TonContractError> {
+        const WALLET_DATA_STACK_ELEMENTS: usize = 4;
+        let method = JettonWalletMethods::GetWalletData.into();
+        let address = self.address().clone();
+
+        let res = self.run_get_method(method, Vec::new()).await?;
+
+        let mut decoder =
+            StackDecoder::new(&res.stack, WALLET_DATA_STACK_ELEMENTS, StackDecodeMode::Lax)
+                .map_stack_error(method, &address)?;
+        let balance = decoder
+            .decode(0, |e| e.get_biguint())
+            .map_stack_error(method, &address)?;
+        let owner_address = decoder
+            .decode(1, |e| e.get_address())
+            .map_stack_error(method, &address)?;
+        let master_address = decoder
+            .decode(2, |e| e.get_address())
+            .map_stack_error(method, &address)?;
+        let wallet_code = decoder
+            .decode(3, |e| e.get_cell())
+            .map_stack_error(method, &address)?;
+        Ok(PartialWalletData {
+            balance,
+            owner_address,
+            master_address,
+            wallet_code,
+            warnings: decoder.into_warnings(),
+        })
+    }
 }
 
 impl<T> JettonWalletContract for T where T: TonContractInterface {}
@@ -10,7 +10,7 @@ use crate::contract::{
     MapCellError, MapStackError, NftItemContract, TonContractError, TonContractInterface,
 };
 use crate::meta::MetaDataContent;
-use crate::types::TvmStackEntry;
+use crate::types::{StackParseError, TvmStackEntry};
 
 /// Data returned by get_collection_data according to TEP-62
 #[derive(Debug, Clone)]
@@ -26,11 +26,21 @@ pub struct NftCollectionData {
     pub owner_address: TonAddress,
 }
 
+/// Royalty parameters returned by royalty_params according to TEP-66.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NftRoyaltyParams {
+    /// Royalty share is `numerator / denominator` of the sale price.
+    pub numerator: u16,
+    pub denominator: u16,
+    pub destination: TonAddress,
+}
+
 #[derive(IntoStaticStr)]
 #[strum(serialize_all = "snake_case")]
 enum NftCollectionMethods {
     GetCollectionData,
     GetNftAddressByIndex,
+    RoyaltyParams,
 }
 
 #[async_trait]
@@ -84,6 +94,40 @@ pub trait NftCollectionContract: TonContractInterface {
             })
         }
     }
+
+    /// Returns royalty parameters, supported by collections of `NftCollectionRevision::Royalty`.
+    async fn get_royalty_params(&self) -> Result<NftRoyaltyParams, TonContractError> {
+        const NFT_ROYALTY_STACK_ELEMENTS: usize = 3;
+        let method = NftCollectionMethods::RoyaltyParams.into();
+        let address = self.address();
+
+        let stack = self.run_get_method(method, Vec::new()).await?.stack;
+        if stack.len() == NFT_ROYALTY_STACK_ELEMENTS {
+            let numerator = stack[0].get_i64().map_stack_error(method, address)?;
+            let denominator = stack[1].get_i64().map_stack_error(method, address)?;
+            let destination = stack[2].get_address().map_stack_error(method, address)?;
+            let to_u16 = |value: i64| {
+                u16::try_from(value).map_err(|_| {
+                    StackParseError::InvalidEntryValue(format!(
+                        "Royalty value {} exceeds u16",
+                        value
+                    ))
+                })
+            };
+            Ok(NftRoyaltyParams {
+                numerator: to_u16(numerator).map_stack_error(method, address)?,
+                denominator: to_u16(denominator).map_stack_error(method, address)?,
+                destination,
+            })
+        } else {
+            Err(TonContractError::InvalidMethodResultStackSize {
+                method: method.to_string(),
+                address: address.clone(),
+                actual: stack.len(),
+                expected: NFT_ROYALTY_STACK_ELEMENTS,
+            })
+        }
+    }
 }
 
 impl<T> NftCollectionContract for T where T: TonContractInterface {}
@@ -97,10 +141,9 @@ async fn read_collection_metadata_content(
         .load_byte()
         .map_cell_error("get_collection_data", collection_address)?;
     match content_representation {
-        // Off-chain content layout
-        // The first byte is 0x01 and the rest is the URI pointing to the JSON document containing the token metadata.
-        // The URI is encoded as ASCII. If the URI does not fit into one cell, then it uses the "Snake format"
-        //  described in the "Data serialization" paragraph, the snake-format-prefix 0x00 is dropped.
+        // On-chain content layout
+        // The first byte is 0x00 and the rest is key/value dictionary.
+        // Key is sha256 hash of string. Value is data encoded as described in "Data serialization" paragraph.
         0 => {
             let reference = cell
                 .reference(0)
@@ -110,9 +153,10 @@ async fn read_collection_metadata_content(
                 .map_cell_error("get_collection_data", collection_address)?;
             Ok(MetaDataContent::Internal { dict })
         }
-        // On-chain content layout
-        // The first byte is 0x00 and the rest is key/value dictionary.
-        // Key is sha256 hash of string. Value is data encoded as described in "Data serialization" paragraph.
+        // Off-chain content layout
+        // The first byte is 0x01 and the rest is the URI pointing to the JSON document containing the token metadata.
+        // The URI is encoded as ASCII. If the URI does not fit into one cell, then it uses the "Snake format"
+        //  described in the "Data serialization" paragraph, the snake-format-prefix 0x00 is dropped.
         1 => {
             let remaining_bytes = parser.remaining_bytes();
             let uri = parser
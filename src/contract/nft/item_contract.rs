@@ -155,6 +155,12 @@ async fn read_item_metadata_content(
             Ok(MetaDataContent::External { uri })
         }
 
+        // Collection-less NFT must have the content in TEP-64 format, there is no collection to
+        // complete it
+        _ if collection_address == &TonAddress::NULL => Ok(MetaDataContent::Unsupported {
+            boc: BagOfCells::from_root(cell.as_ref().clone()),
+        }),
+
         // Semi-chain content layout
         // Data encoded as described in "2. On-chain content layout".
         // The dictionary must have uri key with a value containing the URI pointing to the JSON document with token metadata.
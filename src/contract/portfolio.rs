@@ -0,0 +1,372 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use futures::future::{join_all, try_join_all};
+use futures::FutureExt;
+use num_bigint::BigUint;
+
+use crate::address::{AddressClassifier, AddressRole, NoopAddressClassifier, TonAddress};
+use crate::contract::{
+    JettonAmount, JettonMasterContract, JettonWalletContract, LatestContractTransactionsCache,
+    TonContractError, TonContractFactory, DEFAULT_JETTON_DECIMALS,
+};
+use crate::message::{
+    RawMessageUtils, JETTON_BURN, JETTON_EXCESSES, JETTON_TRANSFER, JETTON_TRANSFER_NOTIFICATION,
+};
+use crate::meta::{JettonMetaData, JettonMetaLoader, LoadMeta, TokenInfo, TokenRegistry};
+use crate::tl::{RawMessage, RawTransaction};
+
+/// Balance of a single jetton held by the owner.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JettonBalance {
+    pub master_address: TonAddress,
+    pub wallet_address: TonAddress,
+    pub balance: BigUint,
+    /// `None` if meta loader is not configured, the token is found in the registry or
+    /// metadata failed to load.
+    pub metadata: Option<JettonMetaData>,
+    /// `None` if token registry is not configured or doesn't contain the jetton.
+    pub token: Option<TokenInfo>,
+}
+
+impl JettonBalance {
+    /// Returns balance with decimals from metadata or token registry,
+    /// or `DEFAULT_JETTON_DECIMALS` if unknown.
+    pub fn amount(&self) -> JettonAmount {
+        let decimals = self
+            .metadata
+            .as_ref()
+            .and_then(|meta| meta.decimals)
+            .or(self.token.as_ref().map(|token| token.decimals))
+            .unwrap_or(DEFAULT_JETTON_DECIMALS);
+        JettonAmount::new(self.balance.clone(), decimals)
+    }
+}
+
+/// TON balance and jetton balances of the address.
+#[derive(Debug)]
+pub struct Portfolio {
+    pub address: TonAddress,
+    /// TON balance in nanotons.
+    pub balance: i64,
+    pub jettons: Vec<JettonBalance>,
+    /// Jetton masters, which balances failed to load, so a failure of one jetton doesn't
+    /// discard the others.
+    pub failed_jettons: Vec<(TonAddress, TonContractError)>,
+}
+
+/// Loads portfolio of the address: TON balance and balances of known jettons.
+///
+/// Jettons are taken from the supplied list of jetton masters and (optionally) discovered
+/// by scanning latest transactions of the address for jetton transfers. Discovered masters
+/// classified as suspicious by the address classifier are skipped.
+pub struct PortfolioLoader {
+    factory: TonContractFactory,
+    jetton_masters: Vec<TonAddress>,
+    scan_transactions: usize,
+    meta_loader: Option<Arc<JettonMetaLoader>>,
+    token_registry: Option<Arc<TokenRegistry>>,
+    classifier: Arc<dyn AddressClassifier>,
+}
+
+impl PortfolioLoader {
+    pub fn new(factory: &TonContractFactory) -> PortfolioLoader {
+        PortfolioLoader {
+            factory: factory.clone(),
+            jetton_masters: vec![],
+            scan_transactions: 0,
+            meta_loader: None,
+            token_registry: None,
+            classifier: Arc::new(NoopAddressClassifier),
+        }
+    }
+
+    pub fn with_jetton_masters(&mut self, jetton_masters: &[TonAddress]) -> &mut Self {
+        self.jetton_masters.extend_from_slice(jetton_masters);
+        self
+    }
+
+    /// Enables discovery of jettons in up to `limit` latest transactions of the address.
+    pub fn with_transaction_scan(&mut self, limit: usize) -> &mut Self {
+        self.scan_transactions = limit;
+        self
+    }
+
+    pub fn with_meta_loader(&mut self, meta_loader: Arc<JettonMetaLoader>) -> &mut Self {
+        self.meta_loader = Some(meta_loader);
+        self
+    }
+
+    /// Tokens found in the registry don't require metadata loading.
+    pub fn with_token_registry(&mut self, token_registry: Arc<TokenRegistry>) -> &mut Self {
+        self.token_registry = Some(token_registry);
+        self
+    }
+
+    pub fn with_address_classifier(&mut self, classifier: Arc<dyn AddressClassifier>) -> &mut Self {
+        self.classifier = classifier;
+        self
+    }
+
+    pub async fn portfolio(&self, address: &TonAddress) -> Result<Portfolio, TonContractError> {
+        let state = self.factory.get_latest_account_state(address).await?;
+
+        let mut masters: Vec<TonAddress> = vec![];
+        let mut seen: HashSet<TonAddress> = HashSet::new();
+        let discovered = self.discover_jetton_masters(address).await?;
+        for master in self.jetton_masters.iter().chain(discovered.iter()) {
+            if seen.insert(master.clone()) {
+                masters.push(master.clone());
+            }
+        }
+
+        let f = masters.iter().map(|master| {
+            self.jetton_balance(master, address)
+                .map(move |res| (master.clone(), res))
+        });
+        let mut jettons = vec![];
+        let mut failed_jettons = vec![];
+        for (master, res) in join_all(f).await {
+            match res {
+                Ok(Some(jetton)) => jettons.push(jetton),
+                Ok(None) => {}
+                Err(e) => {
+                    log::warn!("Failed to load balance of jetton {}: {}", master, e);
+                    failed_jettons.push((master, e));
+                }
+            }
+        }
+
+        Ok(Portfolio {
+            address: address.clone(),
+            balance: state.balance,
+            jettons,
+            failed_jettons,
+        })
+    }
+
+    /// Returns `None` if the jetton wallet of the owner is not deployed.
+    async fn jetton_balance(
+        &self,
+        master_address: &TonAddress,
+        owner: &TonAddress,
+    ) -> Result<Option<JettonBalance>, TonContractError> {
+        let master = self.factory.get_contract(master_address);
+        let wallet_address = master.get_wallet_address(owner).await?;
+        let wallet_state = self
+            .factory
+            .get_latest_account_state(&wallet_address)
+            .await?;
+        if wallet_state.code.is_empty() {
+            return Ok(None);
+        }
+        let wallet_data = self
+            .factory
+            .get_contract(&wallet_address)
+            .get_wallet_data()
+            .await?;
+        let token = self
+            .token_registry
+            .as_ref()
+            .and_then(|registry| registry.get(master_address))
+            .cloned();
+        let metadata = match &self.meta_loader {
+            Some(meta_loader) if token.is_none() => {
+                let jetton_data = master.get_jetton_data().await?;
+                match meta_loader.load(&jetton_data.content).await {
+                    Ok(metadata) => Some(metadata),
+                    Err(e) => {
+                        log::warn!(
+                            "Failed to load metadata of jetton {}: {}",
+                            master_address,
+                            e
+                        );
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+        Ok(Some(JettonBalance {
+            master_address: master_address.clone(),
+            wallet_address,
+            balance: wallet_data.balance,
+            metadata,
+            token,
+        }))
+    }
+
+    async fn discover_jetton_masters(
+        &self,
+        owner: &TonAddress,
+    ) -> Result<Vec<TonAddress>, TonContractError> {
+        if self.scan_transactions == 0 {
+            return Ok(vec![]);
+        }
+        let txs = LatestContractTransactionsCache::new(
+            &self.factory,
+            owner,
+            self.scan_transactions,
+            true,
+            None,
+        )
+        .get_all()
+        .await?;
+        let candidates: HashSet<TonAddress> = txs
+            .iter()
+            .flat_map(|tx| jetton_wallet_candidates(tx))
+            .filter(|wallet| {
+                !self
+                    .classifier
+                    .classify(wallet, AddressRole::JettonWallet)
+                    .is_suspicious()
+            })
+            .collect();
+
+        let f = candidates
+            .iter()
+            .map(|candidate| self.verified_jetton_master(candidate, owner));
+        let masters = try_join_all(f)
+            .await?
+            .into_iter()
+            .flatten()
+            .filter(|master| {
+                !self
+                    .classifier
+                    .classify(master, AddressRole::JettonMaster)
+                    .is_suspicious()
+            })
+            .collect();
+        Ok(masters)
+    }
+
+    /// Returns master of the jetton wallet if the wallet belongs to the owner and is
+    /// recognized by its master, i.e. is not a look-alike contract.
+    async fn verified_jetton_master(
+        &self,
+        wallet_address: &TonAddress,
+        owner: &TonAddress,
+    ) -> Result<Option<TonAddress>, TonContractError> {
+        let wallet_data = match self
+            .factory
+            .get_contract(wallet_address)
+            .get_wallet_data()
+            .await
+        {
+            Ok(wallet_data) => wallet_data,
+            Err(e) => {
+                log::debug!("{} is not a jetton wallet: {}", wallet_address, e);
+                return Ok(None);
+            }
+        };
+        if &wallet_data.owner_address != owner {
+            return Ok(None);
+        }
+        let expected_wallet = self
+            .factory
+            .get_contract(&wallet_data.master_address)
+            .get_wallet_address(owner)
+            .await;
+        match expected_wallet {
+            Ok(expected_wallet) if &expected_wallet == wallet_address => {
+                Ok(Some(wallet_data.master_address))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Addresses of possible jetton wallets of the transaction account: senders of incoming
+/// transfer notifications and excesses, and receivers of outgoing transfers and burns.
+fn jetton_wallet_candidates(tx: &RawTransaction) -> Vec<TonAddress> {
+    let incoming = tx
+        .in_msg
+        .iter()
+        .filter(|msg| {
+            matches!(
+                message_opcode(msg),
+                Some(JETTON_TRANSFER_NOTIFICATION | JETTON_EXCESSES)
+            )
+        })
+        .filter_map(|msg| msg.source.account_address.parse::<TonAddress>().ok());
+    let outgoing = tx
+        .out_msgs
+        .iter()
+        .filter(|msg| matches!(message_opcode(msg), Some(JETTON_TRANSFER | JETTON_BURN)))
+        .filter_map(|msg| msg.destination.account_address.parse::<TonAddress>().ok());
+    incoming.chain(outgoing).collect()
+}
+
+fn message_opcode(msg: &RawMessage) -> Option<u32> {
+    let cell = msg.get_raw_data_cell().ok()?;
+    cell.parser().load_u32(32).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::address::TonAddress;
+    use crate::cell::{BagOfCells, CellBuilder};
+    use crate::contract::portfolio::jetton_wallet_candidates;
+    use crate::message::{JETTON_TRANSFER, JETTON_TRANSFER_NOTIFICATION};
+    use crate::tl::{AccountAddress, MsgData, RawMessage, RawTransaction, NULL_TRANSACTION_ID};
+
+    fn message(source: &str, destination: &str, opcode: u32) -> RawMessage {
+        let body = CellBuilder::new()
+            .store_u32(32, opcode)
+            .unwrap()
+            .build()
+            .unwrap();
+        RawMessage {
+            source: AccountAddress {
+                account_address: source.to_string(),
+            },
+            destination: AccountAddress {
+                account_address: destination.to_string(),
+            },
+            value: 0,
+            fwd_fee: 0,
+            ihr_fee: 0,
+            created_lt: 0,
+            body_hash: vec![],
+            msg_data: MsgData::Raw {
+                body: BagOfCells::from_root(body).serialize(false).unwrap(),
+                init_state: vec![],
+            },
+        }
+    }
+
+    #[test]
+    fn test_jetton_wallet_candidates() {
+        let owner = TonAddress::new(0, &[1; 32]);
+        let incoming_wallet = TonAddress::new(0, &[2; 32]);
+        let outgoing_wallet = TonAddress::new(0, &[3; 32]);
+        let other = TonAddress::new(0, &[4; 32]);
+        let tx = RawTransaction {
+            address: AccountAddress {
+                account_address: owner.to_string(),
+            },
+            utime: 0,
+            data: vec![],
+            transaction_id: NULL_TRANSACTION_ID.clone(),
+            storage_fee: 0,
+            other_fee: 0,
+            in_msg: Some(message(
+                &incoming_wallet.to_string(),
+                &owner.to_string(),
+                JETTON_TRANSFER_NOTIFICATION,
+            )),
+            out_msgs: vec![
+                message(
+                    &owner.to_string(),
+                    &outgoing_wallet.to_string(),
+                    JETTON_TRANSFER,
+                ),
+                message(&owner.to_string(), &other.to_string(), 0),
+            ],
+        };
+        assert_eq!(
+            jetton_wallet_candidates(&tx),
+            vec![incoming_wallet, outgoing_wallet]
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+
+use crate::cell::{ArcCell, Cell, TonCellError};
+use crate::types::TonHash;
+use crate::wallet::WalletVersion;
+
+/// Wallet versions whose codes are shipped in `resources/wallet`.
+const STANDARD_WALLET_VERSIONS: [WalletVersion; 15] = [
+    WalletVersion::V1R1,
+    WalletVersion::V1R2,
+    WalletVersion::V1R3,
+    WalletVersion::V2R1,
+    WalletVersion::V2R2,
+    WalletVersion::V3R1,
+    WalletVersion::V3R2,
+    WalletVersion::V4R1,
+    WalletVersion::V4R2,
+    WalletVersion::V5R1,
+    WalletVersion::HighloadV1R1,
+    WalletVersion::HighloadV1R2,
+    WalletVersion::HighloadV2,
+    WalletVersion::HighloadV2R1,
+    WalletVersion::HighloadV2R2,
+];
+
+lazy_static! {
+    /// Registry of wallet and highload wallet codes shipped with the library, see `CodeRegistry`.
+    pub static ref STANDARD_CODE_REGISTRY: CodeRegistry = CodeRegistry::standard().unwrap();
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ContractKind {
+    Wallet(WalletVersion),
+    JettonMinter,
+    JettonWallet,
+    NftCollection,
+    NftItem,
+    Multisig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KnownCode {
+    pub kind: ContractKind,
+    pub code_hash: TonHash,
+    /// Code cell, if it is known. Entries registered by hash only can be detected, but not deployed.
+    pub code: Option<ArcCell>,
+}
+
+/// Known contract codes indexed by code hash.
+///
+/// `CodeRegistry::standard()` contains only the wallet codes shipped in `resources/wallet`,
+/// verified against the hashes pinned in `WalletVersion::code`. It is the single source of wallet detection
+/// (`WalletVersion::from_code_hash`) and deployment (`WalletVersion::code`).
+///
+/// Jetton, NFT and multisig contracts are split out of the standard registry: every minter and
+/// collection may deploy its own code, so there is no canonical code to ship. Jettons and NFTs
+/// are detected by their get-methods instead (see `TonContractFactory::account_summary`), and
+/// applications register the codes they trust with `register_code` or `register_hash`.
+#[derive(Debug, Clone, Default)]
+pub struct CodeRegistry {
+    by_hash: HashMap<TonHash, KnownCode>,
+}
+
+impl CodeRegistry {
+    pub fn new() -> CodeRegistry {
+        CodeRegistry::default()
+    }
+
+    pub fn standard() -> Result<CodeRegistry, TonCellError> {
+        let mut registry = CodeRegistry::new();
+        for version in STANDARD_WALLET_VERSIONS.iter() {
+            registry.register_code(
+                ContractKind::Wallet(version.clone()),
+                version.code()?.clone(),
+            );
+        }
+        Ok(registry)
+    }
+
+    pub fn register_code(&mut self, kind: ContractKind, code: ArcCell) -> &mut Self {
+        let code_hash = code.cell_hash();
+        self.by_hash.insert(
+            code_hash,
+            KnownCode {
+                kind,
+                code_hash,
+                code: Some(code),
+            },
+        );
+        self
+    }
+
+    pub fn register_hash(&mut self, kind: ContractKind, code_hash: TonHash) -> &mut Self {
+        self.by_hash.insert(
+            code_hash,
+            KnownCode {
+                kind,
+                code_hash,
+                code: None,
+            },
+        );
+        self
+    }
+
+    pub fn find_by_hash(&self, code_hash: &TonHash) -> Option<&KnownCode> {
+        self.by_hash.get(code_hash)
+    }
+
+    pub fn find_by_code(&self, code: &Cell) -> Option<&KnownCode> {
+        self.find_by_hash(&code.cell_hash())
+    }
+
+    pub fn kind_of(&self, code_hash: &TonHash) -> Option<&ContractKind> {
+        self.find_by_hash(code_hash).map(|known| &known.kind)
+    }
+
+    /// Returns code cell of the given kind to deploy. If several codes of the kind are
+    /// registered, an arbitrary one is returned.
+    pub fn code(&self, kind: &ContractKind) -> Option<&ArcCell> {
+        self.by_hash
+            .values()
+            .filter(|known| &known.kind == kind)
+            .find_map(|known| known.code.as_ref())
+    }
+
+    /// Checks that the code is registered as the given kind.
+    pub fn verify(&self, kind: &ContractKind, code: &Cell) -> bool {
+        self.find_by_code(code)
+            .is_some_and(|known| &known.kind == kind)
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_hash.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_hash.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cell::CellBuilder;
+    use crate::contract::{CodeRegistry, ContractKind, STANDARD_CODE_REGISTRY};
+    use crate::wallet::WalletVersion;
+
+    #[test]
+    fn test_standard_code_registry() -> anyhow::Result<()> {
+        assert_eq!(STANDARD_CODE_REGISTRY.len(), 15);
+        let code = WalletVersion::V5R1.code()?;
+        let kind = ContractKind::Wallet(WalletVersion::V5R1);
+        assert_eq!(
+            STANDARD_CODE_REGISTRY.kind_of(&code.cell_hash()),
+            Some(&kind)
+        );
+        assert_eq!(STANDARD_CODE_REGISTRY.code(&kind), Some(code));
+        assert!(STANDARD_CODE_REGISTRY.verify(&kind, code));
+        assert!(!STANDARD_CODE_REGISTRY.verify(&ContractKind::Wallet(WalletVersion::V4R2), code));
+        Ok(())
+    }
+
+    #[test]
+    fn test_register_code() -> anyhow::Result<()> {
+        let code = CellBuilder::new()
+            .store_u32(32, 0xdeadbeef)?
+            .build()?
+            .to_arc();
+        let mut registry = CodeRegistry::new();
+        registry
+            .register_code(ContractKind::JettonWallet, code.clone())
+            .register_hash(ContractKind::NftItem, [1; 32]);
+        assert_eq!(
+            registry.kind_of(&code.cell_hash()),
+            Some(&ContractKind::JettonWallet)
+        );
+        assert_eq!(registry.kind_of(&[1; 32]), Some(&ContractKind::NftItem));
+        assert_eq!(registry.code(&ContractKind::NftItem), None);
+        assert_eq!(registry.find_by_code(&code).unwrap().code, Some(code));
+        Ok(())
+    }
+}
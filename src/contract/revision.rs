@@ -0,0 +1,144 @@
+use crate::address::TonAddress;
+use crate::cell::{BagOfCells, Cell, CellBuilder, TonCellError};
+use crate::contract::{
+    MapCellError, NftCollectionContract, TonContractError, TonContractInterface, TonContractState,
+};
+use crate::emulator::parse_out_messages;
+use crate::message::{JETTON_PROVIDE_WALLET_ADDRESS, JETTON_TAKE_WALLET_ADDRESS};
+use crate::types::TvmError;
+use crate::wallet::WalletVersion;
+
+/// Value attached to emulated probe messages, enough for any sane contract to respond.
+const PROBE_MESSAGE_AMOUNT: u64 = 1_000_000_000;
+
+/// Revision of the jetton standard implemented by the jetton master.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JettonMasterRevision {
+    /// TEP-74, wallet addresses are available via get-method only.
+    Standard,
+    /// TEP-74 with TEP-89 `provide_wallet_address`, so contracts can discover wallet addresses.
+    Discoverable,
+}
+
+/// Revision of the NFT standard implemented by the NFT collection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NftCollectionRevision {
+    /// TEP-62.
+    Standard,
+    /// TEP-62 with TEP-66 royalty parameters.
+    Royalty,
+}
+
+impl TonContractState {
+    /// Returns version of the wallet by code hash, `None` for unknown codes.
+    #[allow(clippy::result_large_err)]
+    pub fn wallet_version(&self) -> Result<Option<WalletVersion>, TonContractError> {
+        const METHOD: &str = "wallet_version";
+        let code = &self.get_account_state().code;
+        if code.is_empty() {
+            return Ok(None);
+        }
+        let boc = BagOfCells::parse(code).map_cell_error(METHOD, self.address())?;
+        let code = boc.single_root().map_cell_error(METHOD, self.address())?;
+        Ok(WalletVersion::from_code_hash(&code.cell_hash()))
+    }
+
+    /// Detects TEP-89 support by emulating `provide_wallet_address` on the jetton master.
+    pub async fn jetton_master_revision(&self) -> Result<JettonMasterRevision, TonContractError> {
+        let owner = self.address().clone();
+        let revision = match self.provide_wallet_address(&owner).await? {
+            Some(_) => JettonMasterRevision::Discoverable,
+            None => JettonMasterRevision::Standard,
+        };
+        Ok(revision)
+    }
+
+    /// Returns jetton wallet address of the owner as the jetton master reports it to other
+    /// contracts, `None` if the master is not `JettonMasterRevision::Discoverable`.
+    pub async fn provide_wallet_address(
+        &self,
+        owner: &TonAddress,
+    ) -> Result<Option<TonAddress>, TonContractError> {
+        const METHOD: &str = "provide_wallet_address";
+        let message = build_provide_wallet_address(owner).map_cell_error(METHOD, self.address())?;
+        let result = self
+            .emulate_internal_message(message, PROBE_MESSAGE_AMOUNT)
+            .await?;
+        if TvmError::from_exit_code(result.vm_exit_code).is_some() {
+            return Ok(None);
+        }
+        let actions = match result.actions {
+            Some(actions) => actions,
+            None => return Ok(None),
+        };
+        let out_msgs = parse_out_messages(&actions).map_cell_error(METHOD, self.address())?;
+        for out_msg in out_msgs {
+            if let Some(wallet) =
+                parse_take_wallet_address(&out_msg.body).map_cell_error(METHOD, self.address())?
+            {
+                return Ok(Some(wallet));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Detects TEP-66 support by probing `royalty_params` get-method of the NFT collection.
+    pub async fn nft_collection_revision(&self) -> Result<NftCollectionRevision, TonContractError> {
+        match self.get_royalty_params().await {
+            Ok(_) => Ok(NftCollectionRevision::Royalty),
+            Err(e) if e.tvm_error() == Some(TvmError::UnknownMethod) => {
+                Ok(NftCollectionRevision::Standard)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+fn build_provide_wallet_address(owner: &TonAddress) -> Result<Cell, TonCellError> {
+    CellBuilder::new()
+        .store_u32(32, JETTON_PROVIDE_WALLET_ADDRESS)?
+        .store_u64(64, 0)?
+        .store_address(owner)?
+        .store_bit(false)?
+        .build()
+}
+
+/// Returns wallet address of `take_wallet_address` body, `None` for other messages.
+fn parse_take_wallet_address(body: &Cell) -> Result<Option<TonAddress>, TonCellError> {
+    let mut parser = body.parser();
+    if parser.remaining_bits() < 32 + 64 || parser.load_u32(32)? != JETTON_TAKE_WALLET_ADDRESS {
+        return Ok(None);
+    }
+    let _query_id = parser.load_u64(64)?;
+    let wallet = parser.load_address()?;
+    Ok(Some(wallet))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::address::TonAddress;
+    use crate::cell::CellBuilder;
+    use crate::contract::revision::{build_provide_wallet_address, parse_take_wallet_address};
+    use crate::message::{JETTON_PROVIDE_WALLET_ADDRESS, JETTON_TAKE_WALLET_ADDRESS};
+
+    #[test]
+    fn test_take_wallet_address() -> anyhow::Result<()> {
+        let owner = TonAddress::new(0, &[1; 32]);
+        let wallet = TonAddress::new(0, &[2; 32]);
+        let provide = build_provide_wallet_address(&owner)?;
+        let mut parser = provide.parser();
+        assert_eq!(parser.load_u32(32)?, JETTON_PROVIDE_WALLET_ADDRESS);
+        assert_eq!(parser.load_u64(64)?, 0);
+        assert_eq!(parser.load_address()?, owner);
+
+        let take = CellBuilder::new()
+            .store_u32(32, JETTON_TAKE_WALLET_ADDRESS)?
+            .store_u64(64, 0)?
+            .store_address(&wallet)?
+            .store_bit(false)?
+            .build()?;
+        assert_eq!(parse_take_wallet_address(&take)?, Some(wallet));
+        assert_eq!(parse_take_wallet_address(&provide)?, None);
+        Ok(())
+    }
+}
@@ -6,15 +6,17 @@ use crate::address::TonAddress;
 use crate::cell::Cell;
 use crate::client::{TonClientError, TonClientInterface};
 use crate::contract::{TonContractError, TonContractFactory, TonContractInterface};
-use crate::emulator::{TvmEmulator, TvmEmulatorC7Builder};
+use crate::emulator::{seed_from_u64, TvmEmulator, TvmEmulatorC7, TvmEmulatorC7Builder};
 use crate::tl::RawFullAccountState;
-use crate::types::{TonMethodId, TvmMsgSuccess, TvmStackEntry, TvmSuccess};
+use crate::types::{TonMethodId, TvmMsgSuccess, TvmRunSource, TvmStack, TvmStackEntry, TvmSuccess};
 
 #[derive(Clone)]
 pub struct TonContractState {
     factory: TonContractFactory,
     address: TonAddress,
     account_state: Arc<RawFullAccountState>,
+    seed: [u8; 32],
+    unix_time: Option<u64>,
 }
 
 impl TonContractState {
@@ -27,9 +29,41 @@ impl TonContractState {
             factory: factory.clone(),
             address: address.clone(),
             account_state: account_state.clone(),
+            seed: [0; 32],
+            unix_time: None,
         }
     }
 
+    /// Sets `rand_seed` used by emulation, zero by default.
+    pub fn with_seed(&mut self, seed: [u8; 32]) -> &mut Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets `now` used by emulation. Current time by default, or the sync time of the account
+    /// state if the get-method cache of the factory is enabled.
+    pub fn with_unix_time(&mut self, unix_time: u64) -> &mut Self {
+        self.unix_time = Some(unix_time);
+        self
+    }
+
+    /// Makes emulation reproducible: `rand_seed` is derived from `seed` and `now` is the sync
+    /// time of the account state.
+    pub fn with_deterministic_mode(&mut self, seed: u64) -> &mut Self {
+        self.seed = seed_from_u64(seed);
+        self.unix_time = Some(self.account_state.sync_utime.max(0) as u64);
+        self
+    }
+
+    fn build_c7(&self, config: &[u8], balance: u64) -> TvmEmulatorC7 {
+        let mut builder = TvmEmulatorC7Builder::new(&self.address, config, balance);
+        builder.with_seed(self.seed);
+        if let Some(unix_time) = self.unix_time {
+            builder.with_unix_time(unix_time);
+        }
+        builder.build()
+    }
+
     pub fn get_account_state(&self) -> &Arc<RawFullAccountState> {
         &self.account_state
     }
@@ -48,6 +82,8 @@ impl TonContractState {
 
         match run_result {
             Ok(result) => Ok(result),
+            // errors of the contract itself are not retried, the liteserver would return the same
+            Err(e) if !e.is_emulation_failure() => Err(e),
             Err(e) => {
                 log::warn!(
                     "Contract emulator returned error: {} \n Falling back to tonlib_run_get_method",
@@ -83,12 +119,7 @@ impl TonContractState {
         let method_id = &method.into();
         let stack_ref = stack.as_ref();
         let state = self.account_state.clone();
-        let c7 = TvmEmulatorC7Builder::new(
-            &self.address,
-            self.factory.get_config_cell_serial().await?,
-            0,
-        )
-        .build();
+        let c7 = self.build_c7(self.factory.get_config_cell_serial().await?, 0);
 
         let libs = self
             .factory
@@ -132,12 +163,7 @@ impl TonContractState {
         amount: u64,
     ) -> Result<TvmMsgSuccess, TonContractError> {
         let state = self.account_state.clone();
-        let c7 = TvmEmulatorC7Builder::new(
-            &self.address,
-            self.factory.get_config_cell_serial().await?,
-            0,
-        )
-        .build();
+        let c7 = self.build_c7(self.factory.get_config_cell_serial().await?, 0);
         let run_result = tokio::task::spawn_blocking(move || {
             let code = state.code.as_slice();
             let data = state.data.as_slice();
@@ -154,6 +180,31 @@ impl TonContractState {
         run_result
     }
 
+    pub async fn emulate_external_message(
+        &self,
+        message: Cell,
+    ) -> Result<TvmMsgSuccess, TonContractError> {
+        let state = self.account_state.clone();
+        let c7 = self.build_c7(
+            self.factory.get_config_cell_serial().await?,
+            state.balance.max(0) as u64,
+        );
+        let run_result = tokio::task::spawn_blocking(move || {
+            let code = state.code.as_slice();
+            let data = state.data.as_slice();
+            let mut emulator = TvmEmulator::new(code, data)?;
+            emulator.set_c7(&c7)?;
+            emulator.send_external_message(message)
+        })
+        .await
+        .map_err(|e| TonContractError::InternalError(e.to_string()))?
+        .map_err(|e| TonContractError::MessageEmulationError {
+            address: self.address().clone(),
+            error: e,
+        });
+        run_result
+    }
+
     pub async fn tonlib_run_get_method<M, S>(
         &self,
         method: M,
@@ -195,23 +246,20 @@ impl TonContractState {
             .smc_run_get_method(state.id, &method.into(), &stack_tl)
             .await?;
 
-        let stack = run_result
-            .stack
-            .elements
-            .iter()
-            .map(|e| e.try_into())
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| TonContractError::TvmStackParseError {
+        let stack = TvmStack::try_from(&run_result.stack).map_err(|e| {
+            TonContractError::TvmStackParseError {
                 method: method.into(),
                 address: self.address().clone(),
                 error: e,
-            })?;
+            }
+        })?;
         let result = TvmSuccess {
             vm_log: None,
             vm_exit_code: run_result.exit_code,
-            stack,
+            stack: stack.elements,
             missing_library: None,
             gas_used: run_result.gas_used as i32,
+            source: TvmRunSource::Liteserver,
         };
         Self::raise_exit_error(self.address(), &method.into(), result)
     }
@@ -221,13 +269,14 @@ impl TonContractState {
         method: &TonMethodId,
         run_result: TvmSuccess,
     ) -> Result<TvmSuccess, TonContractError> {
-        if run_result.exit_error() {
+        if let Some(tvm_error) = run_result.tvm_error() {
             Err(TonContractError::TvmRunError {
                 method: method.clone(),
                 address: address.clone(),
                 gas_used: run_result.gas_used.into(),
                 stack: run_result.stack,
                 exit_code: run_result.vm_exit_code,
+                tvm_error,
                 vm_log: run_result.vm_log,
                 missing_library: run_result.missing_library,
             })
@@ -260,6 +309,60 @@ impl TonContractInterface for TonContractState {
         M: Into<TonMethodId> + Send + Copy,
         S: AsRef<[TvmStackEntry]> + Send,
     {
-        self.do_run_get_method(method, stack).await
+        let run = self.run_get_method_cached(method, stack);
+        #[cfg(feature = "tracing")]
+        let run = tracing::Instrument::instrument(run, self.get_method_span(&method.into()));
+        run.await
+    }
+}
+
+impl TonContractState {
+    async fn run_get_method_cached<M, S>(
+        &self,
+        method: M,
+        stack: S,
+    ) -> Result<TvmSuccess, TonContractError>
+    where
+        M: Into<TonMethodId> + Send + Copy,
+        S: AsRef<[TvmStackEntry]> + Send,
+    {
+        // results depend on c7, so only runs with the default seed are cached
+        let cache = self.factory.get_method_cache();
+        let Some(cache) = cache.filter(|_| self.seed == [0; 32]) else {
+            return self.do_run_get_method(method, stack).await;
+        };
+        // unless pinned, `now` is the sync time of the state, so that the cached result
+        // does not depend on the moment of the run
+        let mut pinned = self.clone();
+        let now = *pinned
+            .unix_time
+            .get_or_insert(self.account_state.sync_utime.max(0) as u64);
+        let method_id = method.into();
+        let lt = self.account_state.last_transaction_id.lt;
+        let cached = cache
+            .get(&self.address, lt, now, &method_id, stack.as_ref())
+            .await;
+        #[cfg(feature = "metrics")]
+        self.factory
+            .record_cache_access("get_method", cached.is_some());
+        if let Some(result) = cached {
+            return Ok(result);
+        }
+        let result = pinned.do_run_get_method(method, stack.as_ref()).await?;
+        cache
+            .insert(&self.address, lt, now, &method_id, stack.as_ref(), &result)
+            .await;
+        Ok(result)
+    }
+
+    #[cfg(feature = "tracing")]
+    fn get_method_span(&self, method: &TonMethodId) -> tracing::Span {
+        tracing::debug_span!(
+            "get_method",
+            address = %self.address,
+            method = %method,
+            seqno = self.account_state.block_id.seqno,
+            lt = self.account_state.last_transaction_id.lt,
+        )
     }
 }
@@ -0,0 +1,148 @@
+use crate::address::TonAddress;
+use crate::cell::{BagOfCells, TonCellError};
+use crate::contract::{AccountStatus, MapCellError, TonContractError, TonContractFactory};
+use crate::tl::{InternalTransactionId, RawFullAccountState};
+use crate::types::TonHash;
+
+/// Change of a code or data hash, `None` if the code or data is absent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HashChange {
+    pub old: Option<TonHash>,
+    pub new: Option<TonHash>,
+}
+
+/// Difference between two states of the account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountStateDiff {
+    pub balance_delta: i64,
+    pub old_status: AccountStatus,
+    pub new_status: AccountStatus,
+    /// Set if the code changed.
+    pub code: Option<HashChange>,
+    /// Set if the data changed.
+    pub data: Option<HashChange>,
+}
+
+impl AccountStateDiff {
+    pub fn status_changed(&self) -> bool {
+        self.old_status != self.new_status
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.balance_delta == 0
+            && !self.status_changed()
+            && self.code.is_none()
+            && self.data.is_none()
+    }
+}
+
+/// Compares two states of the account, e.g. before and after a transaction.
+pub fn diff_states(
+    old: &RawFullAccountState,
+    new: &RawFullAccountState,
+) -> Result<AccountStateDiff, TonCellError> {
+    Ok(AccountStateDiff {
+        balance_delta: new.balance - old.balance,
+        old_status: AccountStatus::of(old),
+        new_status: AccountStatus::of(new),
+        code: diff_hashes(&old.code, &new.code)?,
+        data: diff_hashes(&old.data, &new.data)?,
+    })
+}
+
+fn diff_hashes(old: &[u8], new: &[u8]) -> Result<Option<HashChange>, TonCellError> {
+    if old == new {
+        return Ok(None);
+    }
+    let change = HashChange {
+        old: root_hash(old)?,
+        new: root_hash(new)?,
+    };
+    Ok((change.old != change.new).then_some(change))
+}
+
+fn root_hash(boc: &[u8]) -> Result<Option<TonHash>, TonCellError> {
+    if boc.is_empty() {
+        return Ok(None);
+    }
+    let boc = BagOfCells::parse(boc)?;
+    Ok(Some(boc.single_root()?.cell_hash()))
+}
+
+impl TonContractFactory {
+    /// Compares states of the account right after two transactions.
+    pub async fn diff_states_by_transactions(
+        &self,
+        address: &TonAddress,
+        old: &InternalTransactionId,
+        new: &InternalTransactionId,
+    ) -> Result<AccountStateDiff, TonContractError> {
+        const METHOD: &str = "diff_states_by_transactions";
+        let old = self.get_account_state_by_transaction(address, old).await?;
+        let new = self.get_account_state_by_transaction(address, new).await?;
+        diff_states(&old, &new).map_cell_error(METHOD, address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cell::{BagOfCells, CellBuilder};
+    use crate::contract::{diff_states, AccountStatus, HashChange};
+    use crate::tl::{BlockIdExt, RawFullAccountState, NULL_TRANSACTION_ID};
+
+    fn boc(value: u32) -> anyhow::Result<Vec<u8>> {
+        let cell = CellBuilder::new().store_u32(32, value)?.build()?;
+        Ok(BagOfCells::from_root(cell).serialize(false)?)
+    }
+
+    #[test]
+    fn test_diff_states() -> anyhow::Result<()> {
+        let old = RawFullAccountState {
+            balance: 1_000,
+            code: vec![],
+            data: vec![],
+            last_transaction_id: NULL_TRANSACTION_ID.clone(),
+            block_id: BlockIdExt {
+                workchain: -1,
+                shard: i64::MIN,
+                seqno: 1,
+                root_hash: String::new(),
+                file_hash: String::new(),
+            },
+            frozen_hash: vec![],
+            sync_utime: 0,
+        };
+        let diff = diff_states(&old, &old)?;
+        assert!(diff.is_empty());
+
+        let new = RawFullAccountState {
+            balance: 400,
+            code: boc(1)?,
+            data: boc(2)?,
+            ..old.clone()
+        };
+        let diff = diff_states(&old, &new)?;
+        assert_eq!(diff.balance_delta, -600);
+        assert_eq!(diff.old_status, AccountStatus::Uninit);
+        assert_eq!(diff.new_status, AccountStatus::Active);
+        assert!(diff.status_changed());
+        let code_hash = BagOfCells::parse(&new.code)?.single_root()?.cell_hash();
+        assert_eq!(
+            diff.code,
+            Some(HashChange {
+                old: None,
+                new: Some(code_hash)
+            })
+        );
+
+        let newer = RawFullAccountState {
+            data: boc(3)?,
+            ..new.clone()
+        };
+        let diff = diff_states(&new, &newer)?;
+        assert!(diff.code.is_none());
+        assert!(diff.data.is_some());
+        assert!(!diff.status_changed());
+        Ok(())
+    }
+}
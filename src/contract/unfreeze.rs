@@ -0,0 +1,98 @@
+use num_bigint::BigUint;
+
+use crate::address::TonAddress;
+use crate::cell::BagOfCells;
+use crate::client::TonClientInterface;
+use crate::contract::{
+    AccountStatus, MapCellError, TonContractError, TonContractFactory, TonContractState,
+};
+use crate::transaction::{ParsedTx, StoragePrices, CONFIG_PARAM_STORAGE_PRICES};
+
+/// Approximate bit length of the account cell of a frozen account: address, storage stat,
+/// balance, last transaction lt and state hash.
+const FROZEN_ACCOUNT_BITS: u64 = 700;
+
+/// Storage debt of a frozen account, which has to be paid to unfreeze it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnfreezeEstimate {
+    /// Debt left by the last transaction of the account.
+    pub recorded_due: BigUint,
+    /// Storage fee accrued since the last transaction.
+    pub accrued_due: BigUint,
+    /// Time of the last transaction.
+    pub last_paid: i64,
+}
+
+impl UnfreezeEstimate {
+    /// Value, which the message with the state init of the account must carry besides
+    /// processing fees.
+    pub fn due_payment(&self) -> BigUint {
+        &self.recorded_due + &self.accrued_due
+    }
+}
+
+impl TonContractState {
+    pub fn account_status(&self) -> AccountStatus {
+        AccountStatus::of(self.get_account_state())
+    }
+}
+
+impl TonContractFactory {
+    /// Estimates storage debt of the account, `None` if the account is not frozen.
+    ///
+    /// The debt is taken from the storage phase of the last transaction, the fee accrued since
+    /// then is computed from storage prices of config param 18.
+    pub async fn estimate_unfreeze(
+        &self,
+        address: &TonAddress,
+    ) -> Result<Option<UnfreezeEstimate>, TonContractError> {
+        const METHOD: &str = "estimate_unfreeze";
+        let state = self.get_latest_account_state(address).await?;
+        if AccountStatus::of(&state) != AccountStatus::Frozen {
+            return Ok(None);
+        }
+        let txs = self
+            .client()
+            .get_raw_transactions_v2(address, &state.last_transaction_id, 1, false)
+            .await?;
+        let (recorded_due, last_paid) = match txs.transactions.first() {
+            Some(tx) => {
+                let parsed = ParsedTx::try_from(tx).map_cell_error(METHOD, address)?;
+                let due = parsed
+                    .storage_phase
+                    .and_then(|s| s.storage_fees_due)
+                    .unwrap_or_default();
+                let collected = parsed
+                    .credit_phase
+                    .and_then(|c| c.due_fees_collected)
+                    .unwrap_or_default();
+                let left = if due > collected {
+                    due - collected
+                } else {
+                    BigUint::default()
+                };
+                (left, tx.utime)
+            }
+            None => (BigUint::default(), state.sync_utime),
+        };
+
+        let config = self
+            .client()
+            .get_config_param(0, CONFIG_PARAM_STORAGE_PRICES)
+            .await?;
+        let prices = BagOfCells::parse(&config.config.bytes)
+            .and_then(|boc| {
+                StoragePrices::parse_config(boc.single_root()?, state.sync_utime as u32)
+            })
+            .map_cell_error(METHOD, address)?;
+        let seconds = (state.sync_utime - last_paid).max(0) as u64;
+        let accrued_due = prices
+            .map(|p| p.compute_storage_fee(address.workchain, 1, FROZEN_ACCOUNT_BITS, seconds))
+            .unwrap_or_default();
+        Ok(Some(UnfreezeEstimate {
+            recorded_due,
+            accrued_due,
+            last_paid,
+        }))
+    }
+}
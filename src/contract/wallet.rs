@@ -1,3 +1,7 @@
+mod discovery;
+mod standard_wallet;
 mod wallet_contract;
 
+pub use discovery::*;
+pub use standard_wallet::*;
 pub use wallet_contract::*;
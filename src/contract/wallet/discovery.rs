@@ -0,0 +1,71 @@
+use futures::future::try_join_all;
+
+use crate::address::TonAddress;
+use crate::contract::{AccountStatus, TonContractError, TonContractFactory};
+use crate::mnemonic::{KeyPair, Mnemonic};
+use crate::tl::InternalTransactionId;
+use crate::wallet::{TonWallet, WalletVersion};
+
+/// Wallet versions checked by `discover_wallets`, as wallet apps do when importing a mnemonic.
+pub const DISCOVERED_WALLET_VERSIONS: [WalletVersion; 4] = [
+    WalletVersion::V3R1,
+    WalletVersion::V3R2,
+    WalletVersion::V4R2,
+    WalletVersion::V5R1,
+];
+
+/// On-chain state of the wallet derived from the key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredWallet {
+    pub version: WalletVersion,
+    pub address: TonAddress,
+    pub wallet_id: i32,
+    pub status: AccountStatus,
+    pub balance: i64,
+    pub last_transaction_id: InternalTransactionId,
+}
+
+impl DiscoveredWallet {
+    /// Checks if the wallet was ever used: it is deployed or has received any transaction.
+    pub fn exists(&self) -> bool {
+        self.status != AccountStatus::Uninit || self.last_transaction_id.lt != 0
+    }
+}
+
+impl TonContractFactory {
+    /// Derives wallets of all `DISCOVERED_WALLET_VERSIONS` from the mnemonic and loads their states.
+    pub async fn discover_wallets(
+        &self,
+        mnemonic: &Mnemonic,
+    ) -> Result<Vec<DiscoveredWallet>, TonContractError> {
+        let key_pair = mnemonic
+            .to_key_pair()
+            .map_err(|e| TonContractError::IllegalArgument(e.to_string()))?;
+        self.discover_wallets_by_key(&key_pair, &DISCOVERED_WALLET_VERSIONS)
+            .await
+    }
+
+    pub async fn discover_wallets_by_key(
+        &self,
+        key_pair: &KeyPair,
+        versions: &[WalletVersion],
+    ) -> Result<Vec<DiscoveredWallet>, TonContractError> {
+        let wallets = versions
+            .iter()
+            .map(|version| TonWallet::derive_default(version.clone(), key_pair))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| TonContractError::InternalError(e.to_string()))?;
+        let f = wallets.into_iter().map(|wallet| async move {
+            let state = self.get_latest_account_state(&wallet.address).await?;
+            Ok::<_, TonContractError>(DiscoveredWallet {
+                version: wallet.version,
+                address: wallet.address,
+                wallet_id: wallet.wallet_id,
+                status: AccountStatus::of(&state),
+                balance: state.balance,
+                last_transaction_id: state.last_transaction_id.clone(),
+            })
+        });
+        try_join_all(f).await
+    }
+}
@@ -0,0 +1,141 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+
+use crate::address::TonAddress;
+use crate::cell::{ArcCell, Cell, TonCellError};
+use crate::contract::{
+    AccountStatus, TonContract, TonContractError, TonContractFactory, TonContractInterface,
+    TonWalletContract,
+};
+use crate::mnemonic::KeyPair;
+use crate::tl::RawFullAccountState;
+use crate::types::{TonMethodId, TvmStackEntry, TvmSuccess};
+use crate::wallet::{TonWallet, TxBuilder, TxBuilderError, WalletVersion};
+
+/// Standard wallet contract controlled by the key pair: reads the wallet state through the
+/// factory and sends signed transfers.
+///
+/// ```ignore
+/// let wallet = WalletContract::v4r2(&factory, &key_pair)?;
+/// let hash = wallet
+///     .send_transfer(wallet.tx().transfer(&dest, &amount).with_comment("hello"))
+///     .await?;
+/// ```
+pub struct WalletContract {
+    contract: TonContract,
+    wallet: TonWallet,
+}
+
+impl WalletContract {
+    pub fn new(factory: &TonContractFactory, wallet: &TonWallet) -> WalletContract {
+        WalletContract {
+            contract: factory.get_contract(&wallet.address),
+            wallet: wallet.clone(),
+        }
+    }
+
+    /// Derives the wallet of the basechain with the default wallet id.
+    pub fn from_key(
+        factory: &TonContractFactory,
+        version: WalletVersion,
+        key_pair: &KeyPair,
+    ) -> Result<WalletContract, TonCellError> {
+        let wallet = TonWallet::derive_default(version, key_pair)?;
+        Ok(Self::new(factory, &wallet))
+    }
+
+    pub fn v3r2(
+        factory: &TonContractFactory,
+        key_pair: &KeyPair,
+    ) -> Result<WalletContract, TonCellError> {
+        Self::from_key(factory, WalletVersion::V3R2, key_pair)
+    }
+
+    pub fn v4r2(
+        factory: &TonContractFactory,
+        key_pair: &KeyPair,
+    ) -> Result<WalletContract, TonCellError> {
+        Self::from_key(factory, WalletVersion::V4R2, key_pair)
+    }
+
+    pub fn v5r1(
+        factory: &TonContractFactory,
+        key_pair: &KeyPair,
+    ) -> Result<WalletContract, TonCellError> {
+        Self::from_key(factory, WalletVersion::V5R1, key_pair)
+    }
+
+    pub fn wallet(&self) -> &TonWallet {
+        &self.wallet
+    }
+
+    pub fn version(&self) -> &WalletVersion {
+        &self.wallet.version
+    }
+
+    /// Builds signed external message with the internal messages, valid for `ttl_secs`.
+    ///
+    /// Seqno is read from the wallet. If the wallet is not deployed yet, seqno 0 is used and
+    /// the state init is attached to deploy it.
+    pub async fn create_transfer<T: AsRef<[ArcCell]>>(
+        &self,
+        internal_messages: T,
+        ttl_secs: u32,
+    ) -> Result<Cell, TonContractError> {
+        let state = self.get_account_state().await?;
+        let deployed = AccountStatus::of(&state) == AccountStatus::Active;
+        let seqno = if deployed { self.seqno().await? } else { 0 };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| TonContractError::InternalError(e.to_string()))?
+            .as_secs() as u32;
+        self.wallet
+            .create_external_message(now + ttl_secs, seqno, internal_messages, !deployed)
+            .map_err(|e| TonContractError::InternalError(e.to_string()))
+    }
+
+    /// Returns builder of transactions sent by the wallet.
+    pub fn tx(&self) -> TxBuilder {
+        TxBuilder::new(&self.wallet)
+    }
+
+    /// Sends the transaction, returns hash of the external message.
+    ///
+    /// If the wallet is not deployed yet, the state init is attached to deploy it.
+    pub async fn send_transfer(&self, tx: &TxBuilder) -> Result<Vec<u8>, TxBuilderError> {
+        let state = self.get_account_state().await?;
+        if AccountStatus::of(&state) == AccountStatus::Active {
+            return tx.send(self.factory()).await;
+        }
+        tx.clone().with_state_init(true).send(self.factory()).await
+    }
+}
+
+#[async_trait]
+impl TonContractInterface for WalletContract {
+    fn factory(&self) -> &TonContractFactory {
+        self.contract.factory()
+    }
+
+    fn address(&self) -> &TonAddress {
+        self.contract.address()
+    }
+
+    async fn get_account_state(&self) -> Result<Arc<RawFullAccountState>, TonContractError> {
+        self.contract.get_account_state().await
+    }
+
+    async fn run_get_method<M, S>(
+        &self,
+        method: M,
+        stack: S,
+    ) -> Result<TvmSuccess, TonContractError>
+    where
+        M: Into<TonMethodId> + Send + Copy,
+        S: AsRef<[TvmStackEntry]> + Send,
+    {
+        self.contract.run_get_method(method, stack).await
+    }
+}
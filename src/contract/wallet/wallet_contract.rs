@@ -8,6 +8,7 @@ use crate::contract::{MapStackError, TonContractError, TonContractInterface};
 enum WalletContractMethods {
     Seqno,
     GetPublicKey,
+    GetSubwalletId,
 }
 
 #[async_trait]
@@ -47,6 +48,30 @@ pub trait TonWalletContract: TonContractInterface {
             Ok(pub_key.to_bytes_be())
         }
     }
+
+    /// Returns wallet id, supported by V4 and V5 wallets.
+    async fn get_subwallet_id(&self) -> Result<u32, TonContractError> {
+        let method: &str = WalletContractMethods::GetSubwalletId.into();
+        let res = self.run_get_method(method, Vec::new()).await?;
+        let stack = res.stack;
+        if stack.len() != 1 {
+            Err(TonContractError::InvalidMethodResultStackSize {
+                method: method.to_string(),
+                address: self.address().clone(),
+                actual: stack.len(),
+                expected: 1,
+            })
+        } else {
+            let wallet_id = stack[0].get_i64().map_stack_error(method, self.address())?;
+            u32::try_from(wallet_id).map_err(|_| {
+                TonContractError::InternalError(format!(
+                    "Wallet id {} of {} is out of u32 range",
+                    wallet_id,
+                    self.address()
+                ))
+            })
+        }
+    }
 }
 
 impl<T> TonWalletContract for T where T: TonContractInterface {}
@@ -3,17 +3,26 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 pub use error::*;
 use num_bigint::Sign;
+use sha2::{Digest, Sha256};
+pub use storage_stat::*;
+pub use trace::*;
+pub use tx_emulator::*;
 pub use unsafe_emulator::*;
+pub use unsafe_tx_emulator::*;
 
 use self::types::TvmEmulatorMessageResponse;
 use crate::address::TonAddress;
 use crate::cell::{BagOfCells, Cell, CellBuilder};
 use crate::emulator::types::TvmEmulatorResponse;
-use crate::types::{TonMethodId, TvmMsgSuccess, TvmStackEntry, TvmSuccess};
+use crate::types::{TonMethodId, TvmMsgSuccess, TvmStackEntry, TvmSuccess, TVM_MAX_TUPLE_LEN};
 
 mod error;
+mod storage_stat;
+mod trace;
+mod tx_emulator;
 mod types;
 mod unsafe_emulator;
+mod unsafe_tx_emulator;
 
 #[derive(Debug)]
 pub struct TvmEmulator {
@@ -60,6 +69,19 @@ impl<'a> TvmEmulatorC7Builder<'a> {
         self
     }
 
+    /// Derives `rand_seed` from a number, so that emulations of contracts using `RANDOM`
+    /// can be reproduced.
+    pub fn with_seed_from_u64(&mut self, seed: u64) -> &mut Self {
+        self.seed = seed_from_u64(seed);
+        self
+    }
+
+    /// Sets random `rand_seed`, as the validator would do.
+    pub fn with_random_seed(&mut self) -> &mut Self {
+        self.seed = rand::random();
+        self
+    }
+
     pub fn with_unix_time(&mut self, unix_time: u64) -> &mut Self {
         self.unix_time = unix_time;
         self
@@ -76,6 +98,11 @@ impl<'a> TvmEmulatorC7Builder<'a> {
     }
 }
 
+/// Returns sha256 of big-endian representation of the number.
+pub fn seed_from_u64(seed: u64) -> [u8; 32] {
+    Sha256::digest(seed.to_be_bytes()).into()
+}
+
 impl TvmEmulator {
     pub fn new(code: &[u8], data: &[u8]) -> Result<TvmEmulator, TvmEmulatorError> {
         let emulator = TvmEmulatorUnsafe::create(code, data, DEFAULT_VM_LOG_VERBOSITY)?;
@@ -83,6 +110,14 @@ impl TvmEmulator {
         Ok(ton_contract_emulator)
     }
 
+    /// Creates emulator from code and data cells, e.g. loaded from a stored account state,
+    /// to run get-methods without requests to liteservers.
+    pub fn from_cells(code: &Cell, data: &Cell) -> Result<TvmEmulator, TvmEmulatorError> {
+        let code = BagOfCells::from_root(code.clone()).serialize(false)?;
+        let data = BagOfCells::from_root(data.clone()).serialize(false)?;
+        Self::new(&code, &data)
+    }
+
     pub fn set_c7(&mut self, c7: &TvmEmulatorC7) -> Result<&mut Self, TvmEmulatorError> {
         let addr_str = c7.address.to_hex();
         let hex_str = hex::encode(c7.seed);
@@ -245,9 +280,90 @@ impl TvmEmulator {
                 builder.store_u8(3, slice.end_ref as u8)?; // en_ref
                 Ok(())
             }
+            TvmStackEntry::Tuple(elements) => {
+                if elements.len() > TVM_MAX_TUPLE_LEN {
+                    return Err(TvmEmulatorError::EmulatorError(format!(
+                        "Tuple of {} elements exceeds {}",
+                        elements.len(),
+                        TVM_MAX_TUPLE_LEN
+                    )));
+                }
+                // vm_stk_tuple#07 len:(## 16) data:(VmTuple len)
+                builder
+                    .store_byte(7)?
+                    .store_u32(16, elements.len() as u32)?;
+                if !elements.is_empty() {
+                    Self::store_vm_tuple(builder, elements)?;
+                }
+                Ok(())
+            }
+            TvmStackEntry::List(_) => Self::store_stack_entry(builder, &entry.to_pairs()),
             TvmStackEntry::Unsupported => Err(TvmEmulatorError::EmulatorError(
                 "EmulatorStackEntry::Unsupported is not supported".to_string(),
             )),
         }
     }
+
+    /// Stores non-empty `VmTuple`: head `VmTupleRef` of all elements but the last one and
+    /// the reference to the last element.
+    fn store_vm_tuple(
+        builder: &mut CellBuilder,
+        elements: &[TvmStackEntry],
+    ) -> Result<(), TvmEmulatorError> {
+        let (last, head) = elements
+            .split_last()
+            .ok_or_else(|| TvmEmulatorError::InternalError("Empty VmTuple".to_string()))?;
+        match head.len() {
+            0 => {}
+            1 => {
+                builder.store_child(Self::stack_value_cell(&head[0])?)?;
+            }
+            _ => {
+                let mut head_builder = CellBuilder::new();
+                Self::store_vm_tuple(&mut head_builder, head)?;
+                builder.store_child(head_builder.build()?)?;
+            }
+        }
+        builder.store_child(Self::stack_value_cell(last)?)?;
+        Ok(())
+    }
+
+    fn stack_value_cell(entry: &TvmStackEntry) -> Result<Cell, TvmEmulatorError> {
+        let mut builder = CellBuilder::new();
+        Self::store_stack_entry(&mut builder, entry)?;
+        Ok(builder.build()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigInt;
+
+    use crate::cell::{BagOfCells, CellBuilder};
+    use crate::emulator::types::TvmEmulatorResponse;
+    use crate::emulator::TvmEmulator;
+    use crate::types::TvmStackEntry;
+
+    #[test]
+    fn test_nested_tuple_stack_round_trip() -> anyhow::Result<()> {
+        let cell = CellBuilder::new().store_u32(32, 7)?.build()?;
+        let stack = vec![
+            TvmStackEntry::Int64(1),
+            TvmStackEntry::Tuple(vec![]),
+            TvmStackEntry::Tuple(vec![TvmStackEntry::Null]),
+            TvmStackEntry::Tuple(vec![
+                TvmStackEntry::Int257(BigInt::from(2)),
+                cell.into(),
+                TvmStackEntry::Tuple(vec![TvmStackEntry::Int64(3), TvmStackEntry::Int64(4)]),
+            ]),
+            TvmStackEntry::List(vec![TvmStackEntry::Int64(5), TvmStackEntry::Int64(6)]),
+        ];
+        let boc = TvmEmulator::build_stack_boc(&stack)?;
+        let parsed = TvmEmulatorResponse::extract_stack(&BagOfCells::parse(&boc)?)?;
+
+        assert_eq!(parsed[..4], stack[..4]);
+        assert_eq!(parsed[4], stack[4].to_pairs());
+        assert_eq!(parsed[4].get_list()?, stack[4].get_list()?);
+        Ok(())
+    }
 }
@@ -14,6 +14,12 @@ pub enum TvmEmulatorError {
     #[error("Emulator error({0})")]
     EmulatorError(String),
 
+    #[error("External message is not accepted (exit code: {vm_exit_code:?}): {error}")]
+    ExternalNotAccepted {
+        vm_exit_code: Option<i32>,
+        error: String,
+    },
+
     #[error("Internal error({0})")]
     InternalError(String),
 
@@ -0,0 +1,190 @@
+use std::collections::HashSet;
+
+use num_bigint::BigUint;
+
+use crate::cell::{ArcCell, BagOfCells, Cell, CellParser, TonCellError};
+use crate::tl::RawFullAccountState;
+use crate::types::TonHash;
+
+/// Storage statistics of the account (`StorageInfo`), required to model the storage phase:
+/// storage fees accumulated since `last_paid` and possible freezing of the account.
+///
+/// `RawFullAccountState` doesn't expose these values. They are parsed from `ShardAccount`
+/// returned by `get_shard_account_cell`, or usage is computed from the account code and data
+/// and `last_paid` is supplied by the caller.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AccountStorageStat {
+    pub used_cells: u64,
+    pub used_bits: u64,
+    pub used_public_cells: u64,
+    pub last_paid: u32,
+    pub due_payment: Option<BigUint>,
+}
+
+impl AccountStorageStat {
+    /// Counts distinct cells of the code and data of the account. The cell of the account
+    /// itself is not counted.
+    pub fn from_account_state(
+        state: &RawFullAccountState,
+        last_paid: u32,
+    ) -> Result<AccountStorageStat, TonCellError> {
+        let mut roots = vec![];
+        for boc in [&state.code, &state.data] {
+            if !boc.is_empty() {
+                roots.extend(BagOfCells::parse(boc)?.roots);
+            }
+        }
+        let (used_cells, used_bits) = count_unique_cells(&roots);
+        Ok(AccountStorageStat {
+            used_cells,
+            used_bits,
+            used_public_cells: 0,
+            last_paid,
+            due_payment: None,
+        })
+    }
+
+    /// Parses storage stat of the account from `ShardAccount`:
+    ///
+    /// ```raw
+    /// storage_used$_ cells:(VarUInteger 7) bits:(VarUInteger 7) = StorageUsed;
+    /// storage_extra_none$000 = StorageExtraInfo;
+    /// storage_extra_info$001 dict_hash:uint256 = StorageExtraInfo;
+    /// storage_info$_ used:StorageUsed storage_extra:StorageExtraInfo last_paid:uint32
+    ///   due_payment:(Maybe Grams) = StorageInfo;
+    /// ```
+    ///
+    /// `storage_extra_none` has the layout of zero `public_cells` of the former `StorageUsed`,
+    /// so states of both versions are parsed.
+    pub fn from_shard_account(shard_account: &Cell) -> Result<AccountStorageStat, TonCellError> {
+        let account = shard_account.reference(0)?;
+        let mut parser = account.parser();
+        // account_none$0
+        if !parser.load_bit()? {
+            return Err(TonCellError::cell_parser_error("Account doesn't exist"));
+        }
+        let _address = parser.load_address()?;
+        let used_cells = load_var_uint7(&mut parser)?;
+        let used_bits = load_var_uint7(&mut parser)?;
+        match parser.load_u8(3)? {
+            0b000 => {}
+            0b001 => parser.skip_bits(256)?,
+            tag => {
+                return Err(TonCellError::cell_parser_error(format!(
+                    "Unexpected StorageExtraInfo tag {:03b}",
+                    tag
+                )))
+            }
+        }
+        let last_paid = parser.load_u32(32)?;
+        let due_payment = match parser.load_bit()? {
+            true => Some(parser.load_coins()?),
+            false => None,
+        };
+        Ok(AccountStorageStat {
+            used_cells,
+            used_bits,
+            used_public_cells: 0,
+            last_paid,
+            due_payment,
+        })
+    }
+
+    pub fn with_due_payment(&mut self, due_payment: BigUint) -> &mut Self {
+        self.due_payment = Some(due_payment);
+        self
+    }
+}
+
+/// Loads `VarUInteger 7`: 3-bit length in bytes followed by the value.
+fn load_var_uint7(parser: &mut CellParser) -> Result<u64, TonCellError> {
+    let num_bytes = parser.load_u8(3)? as usize;
+    parser.load_u64(num_bytes * 8)
+}
+
+/// Returns number of distinct cells and their total bit length.
+fn count_unique_cells(roots: &[ArcCell]) -> (u64, u64) {
+    let mut visited: HashSet<TonHash> = HashSet::new();
+    let mut stack: Vec<&ArcCell> = roots.iter().collect();
+    let (mut cells, mut bits) = (0, 0);
+    while let Some(cell) = stack.pop() {
+        if !visited.insert(cell.cell_hash()) {
+            continue;
+        }
+        cells += 1;
+        bits += cell.bit_len() as u64;
+        stack.extend(cell.references());
+    }
+    (cells, bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use num_bigint::BigUint;
+
+    use crate::address::TonAddress;
+    use crate::cell::CellBuilder;
+    use crate::emulator::storage_stat::count_unique_cells;
+    use crate::emulator::{build_shard_account, AccountStorageStat};
+    use crate::tl::{BlockIdExt, InternalTransactionId, RawFullAccountState};
+
+    #[test]
+    fn test_count_unique_cells() {
+        let leaf = Arc::new(
+            CellBuilder::new()
+                .store_u32(32, 1)
+                .unwrap()
+                .build()
+                .unwrap(),
+        );
+        let root = CellBuilder::new()
+            .store_u8(8, 2)
+            .unwrap()
+            .store_reference(&leaf)
+            .unwrap()
+            .store_reference(&leaf)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(count_unique_cells(&[Arc::new(root), leaf]), (2, 40));
+    }
+
+    #[test]
+    fn test_storage_stat_from_shard_account() -> anyhow::Result<()> {
+        let state = RawFullAccountState {
+            balance: 1_000_000_000,
+            code: vec![],
+            data: vec![],
+            last_transaction_id: InternalTransactionId {
+                lt: 42,
+                hash: vec![7; 32],
+            },
+            block_id: BlockIdExt {
+                workchain: 0,
+                shard: i64::MIN,
+                seqno: 1,
+                root_hash: "".to_string(),
+                file_hash: "".to_string(),
+            },
+            frozen_hash: vec![],
+            sync_utime: 1_700_000_000,
+        };
+        let mut stat = AccountStorageStat {
+            used_cells: 3,
+            used_bits: 1023,
+            used_public_cells: 0,
+            last_paid: 1_600_000_000,
+            due_payment: None,
+        };
+        stat.with_due_payment(BigUint::from(12345u32));
+        let address = TonAddress::new(0, &[1; 32]);
+        let shard_account = build_shard_account(&address, &state, &stat)?;
+        assert_eq!(
+            AccountStorageStat::from_shard_account(&shard_account)?,
+            stat
+        );
+        Ok(())
+    }
+}
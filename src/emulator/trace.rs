@@ -0,0 +1,420 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use futures::future::{join_all, BoxFuture};
+use futures::FutureExt;
+use num_traits::ToPrimitive;
+
+use crate::address::TonAddress;
+use crate::cell::{ArcCell, BagOfCells, Cell, CellParser, TonCellError};
+use crate::client::{TonClientError, TonClientInterface};
+use crate::contract::{TonContractError, TonContractFactory};
+use crate::emulator::{TransactionEmulator, TxEmulationSuccess};
+use crate::transaction::TxMessageInfo;
+
+const ACTION_SEND_MSG: u32 = 0x0ec3c86d;
+const DEFAULT_MAX_DEPTH: usize = 4;
+
+/// Internal message emitted by `action_send_msg`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutMessage {
+    pub mode: u8,
+    pub bounce: bool,
+    pub destination: TonAddress,
+    /// Value stated in the message. With modes 64 and 128 the actual value is computed by
+    /// the action phase, which is not emulated, so it may differ.
+    pub value: u64,
+    pub body: ArcCell,
+}
+
+#[derive(Debug)]
+pub enum TraceOutcome {
+    /// Transaction emulated with all phases, including the storage, action and bounce ones.
+    Executed(Box<TxEmulationSuccess>),
+    /// Depth limit is reached, the message is not emulated.
+    Skipped,
+    /// The message is not emulated, e.g. the account state can't be loaded or an external
+    /// message is not accepted.
+    Failed(TonContractError),
+}
+
+/// Emulated transaction caused by the message and transactions caused by the messages it sent.
+#[derive(Debug)]
+pub struct TraceNode {
+    pub address: TonAddress,
+    /// Message processed by the account.
+    pub message: ArcCell,
+    /// Value of the internal message after the action phase of the sender, zero for external
+    /// messages.
+    pub amount: u64,
+    /// Whether the internal message bounces if it is not processed successfully.
+    pub bounce: bool,
+    pub outcome: TraceOutcome,
+    /// Nodes of internal messages sent by the transaction in the order of sending.
+    pub children: Vec<TraceNode>,
+}
+
+impl TraceNode {
+    /// Returns true if every emulated transaction in the trace is not aborted.
+    pub fn is_success(&self) -> bool {
+        let success = match &self.outcome {
+            TraceOutcome::Executed(result) => !result.transaction.aborted,
+            TraceOutcome::Skipped => true,
+            TraceOutcome::Failed(_) => false,
+        };
+        success && self.children.iter().all(TraceNode::is_success)
+    }
+}
+
+/// Approximates a trace of a would-be action by chaining transaction emulations: the message
+/// is executed on the account by `TransactionEmulator`, internal messages sent by the
+/// transaction are executed on their destinations and so on, up to the depth limit.
+///
+/// Every hop is emulated against the latest on-chain state (`ShardAccount`) of the destination
+/// with the current blockchain config and the libraries of the destination code, so state
+/// changes made by previous hops are not accounted for.
+pub struct TraceEmulator {
+    factory: TonContractFactory,
+    max_depth: usize,
+}
+
+impl TraceEmulator {
+    pub fn new(factory: &TonContractFactory) -> TraceEmulator {
+        TraceEmulator {
+            factory: factory.clone(),
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+
+    /// Sets the number of hops emulated after the initial message.
+    pub fn with_max_depth(&mut self, max_depth: usize) -> &mut Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Emulates the inbound external or internal message (`Message`) on its destination and
+    /// the messages sent by the resulting transactions.
+    pub async fn emulate_message(&self, message: Cell) -> Result<TraceNode, TonContractError> {
+        let message = message.to_arc();
+        let header = MessageHeader::parse(&message).map_err(TonClientError::from)?;
+        let config = self.factory.client().get_config_all(0).await?;
+        let config = Arc::new(config.config.bytes);
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| TonContractError::InternalError(e.to_string()))?
+            .as_secs() as u32;
+        Ok(self.emulate_hop(&config, now, message, header, 0).await)
+    }
+
+    fn emulate_hop<'a>(
+        &'a self,
+        config: &'a Arc<Vec<u8>>,
+        now: u32,
+        message: ArcCell,
+        header: MessageHeader,
+        depth: usize,
+    ) -> BoxFuture<'a, TraceNode> {
+        async move {
+            let outcome = if depth > self.max_depth {
+                TraceOutcome::Skipped
+            } else {
+                match self
+                    .emulate_transaction(config, now, &header.address, &message)
+                    .await
+                {
+                    Ok(result) => TraceOutcome::Executed(Box::new(result)),
+                    Err(e) => TraceOutcome::Failed(e),
+                }
+            };
+            let children = match &outcome {
+                TraceOutcome::Executed(result) => {
+                    let hops = result
+                        .out_msgs
+                        .iter()
+                        .filter_map(|msg| match MessageHeader::parse(msg) {
+                            Ok(header) if header.internal => Some((msg.clone(), header)),
+                            Ok(_) => None,
+                            Err(e) => {
+                                log::warn!(
+                                    "Failed to parse message sent by {}: {}",
+                                    header.address,
+                                    e
+                                );
+                                None
+                            }
+                        });
+                    let f = hops
+                        .map(|(msg, header)| self.emulate_hop(config, now, msg, header, depth + 1));
+                    join_all(f).await
+                }
+                TraceOutcome::Skipped | TraceOutcome::Failed(_) => vec![],
+            };
+            TraceNode {
+                address: header.address,
+                message,
+                amount: header.amount,
+                bounce: header.bounce,
+                outcome,
+                children,
+            }
+        }
+        .boxed()
+    }
+
+    async fn emulate_transaction(
+        &self,
+        config: &Arc<Vec<u8>>,
+        now: u32,
+        address: &TonAddress,
+        message: &ArcCell,
+    ) -> Result<TxEmulationSuccess, TonContractError> {
+        let state = self.factory.get_latest_account_state(address).await?;
+        let libs = match state.code.is_empty() {
+            true => None,
+            false => Some(
+                self.factory
+                    .library_provider()
+                    .get_contract_libraries(address, &state)
+                    .await?,
+            ),
+        };
+        let shard_account = self
+            .factory
+            .client()
+            .get_shard_account_cell(address)
+            .await?;
+        let shard_account = BagOfCells::parse(&shard_account.bytes)
+            .and_then(|boc| Ok(boc.single_root()?.clone()))
+            .map_err(TonClientError::from)?;
+        let config = config.clone();
+        let message = message.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut emulator = TransactionEmulator::new(&config)?;
+            emulator.set_unixtime(now)?;
+            if let Some(libs) = libs {
+                emulator.set_libraries(libs.dict_boc.as_slice())?;
+            }
+            emulator.emulate_transaction(&shard_account, &message)
+        })
+        .await
+        .map_err(|e| TonContractError::InternalError(e.to_string()))?
+        .map_err(|error| TonContractError::MessageEmulationError {
+            address: address.clone(),
+            error,
+        })
+    }
+}
+
+/// Destination, value and bounce flag of an inbound message.
+struct MessageHeader {
+    address: TonAddress,
+    amount: u64,
+    bounce: bool,
+    internal: bool,
+}
+
+impl MessageHeader {
+    fn parse(message: &Cell) -> Result<MessageHeader, TonCellError> {
+        let mut parser = message.parser();
+        // int_msg_info$0 ihr_disabled:Bool bounce:Bool
+        let bounce = !parser.load_bit()? && {
+            parser.load_bit()?;
+            parser.load_bit()?
+        };
+        match TxMessageInfo::parse(message)? {
+            TxMessageInfo::Internal { dest, value, .. } => Ok(MessageHeader {
+                address: dest,
+                amount: value
+                    .to_u64()
+                    .ok_or_else(|| TonCellError::cell_parser_error("Message value exceeds u64"))?,
+                bounce,
+                internal: true,
+            }),
+            TxMessageInfo::ExternalIn { dest, .. } => Ok(MessageHeader {
+                address: dest,
+                amount: 0,
+                bounce: false,
+                internal: false,
+            }),
+            TxMessageInfo::ExternalOut { .. } => Err(TonCellError::cell_parser_error(
+                "Outbound external message has no destination",
+            )),
+        }
+    }
+}
+
+/// Returns internal messages sent by the action list (`OutList`) in the order of sending.
+pub fn parse_out_messages(actions: &Cell) -> Result<Vec<OutMessage>, TonCellError> {
+    let mut out_msgs = vec![];
+    let mut cell = actions.clone();
+    while !cell.references().is_empty() {
+        let mut parser = cell.parser();
+        let prev = parser.next_reference()?;
+        if parser.remaining_bits() >= 32 + 8 && parser.load_u32(32)? == ACTION_SEND_MSG {
+            let mode = parser.load_u8(8)?;
+            let message = parser.next_reference()?;
+            if let Some(msg) = parse_internal_message(mode, &message)? {
+                out_msgs.push(msg);
+            }
+        }
+        cell = prev.as_ref().clone();
+    }
+    out_msgs.reverse();
+    Ok(out_msgs)
+}
+
+/// Parses `MessageRelaxed`, returns `None` for external messages.
+fn parse_internal_message(mode: u8, message: &Cell) -> Result<Option<OutMessage>, TonCellError> {
+    let mut parser = message.parser();
+    if parser.load_bit()? {
+        return Ok(None);
+    }
+    let _ihr_disabled = parser.load_bit()?;
+    let bounce = parser.load_bit()?;
+    let _bounced = parser.load_bit()?;
+    let _src = parser.load_address()?;
+    let destination = parser.load_address()?;
+    let value = parser
+        .load_coins()?
+        .to_u64()
+        .ok_or_else(|| TonCellError::cell_parser_error("Message value exceeds u64"))?;
+    // extra currencies
+    parser.load_maybe_cell_ref()?;
+    let _ihr_fee = parser.load_coins()?;
+    let _fwd_fee = parser.load_coins()?;
+    // created_lt, created_at
+    parser.skip_bits(64 + 32)?;
+    if parser.load_bit()? {
+        skip_state_init(&mut parser)?;
+    }
+    let body = parser.load_either_cell_or_cell_ref()?;
+    Ok(Some(OutMessage {
+        mode,
+        bounce,
+        destination,
+        value,
+        body,
+    }))
+}
+
+/// Skips `init:(Maybe (Either StateInit ^StateInit))` after the maybe bit.
+fn skip_state_init(parser: &mut CellParser) -> Result<(), TonCellError> {
+    if parser.load_bit()? {
+        parser.next_reference()?;
+        return Ok(());
+    }
+    // split_depth:(Maybe (## 5)) special:(Maybe TickTock)
+    if parser.load_bit()? {
+        parser.skip_bits(5)?;
+    }
+    if parser.load_bit()? {
+        parser.skip_bits(2)?;
+    }
+    // code, data, library
+    for _ in 0..3 {
+        parser.load_maybe_cell_ref()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use num_bigint::BigUint;
+
+    use crate::address::TonAddress;
+    use crate::cell::{Cell, CellBuilder};
+    use crate::emulator::parse_out_messages;
+    use crate::emulator::trace::MessageHeader;
+
+    fn build_message(destination: &TonAddress, value: u64, body: &Cell) -> Cell {
+        let mut builder = CellBuilder::new();
+        builder
+            .store_u8(4, 0b0110)
+            .unwrap()
+            .store_address(&TonAddress::NULL)
+            .unwrap()
+            .store_address(destination)
+            .unwrap()
+            .store_coins(&BigUint::from(value))
+            .unwrap()
+            .store_bit(false)
+            .unwrap()
+            .store_coins(&BigUint::from(0u32))
+            .unwrap()
+            .store_coins(&BigUint::from(0u32))
+            .unwrap()
+            .store_u64(64, 0)
+            .unwrap()
+            .store_u32(32, 0)
+            .unwrap()
+            .store_bit(false)
+            .unwrap()
+            .store_bit(true)
+            .unwrap()
+            .store_child(body.clone())
+            .unwrap();
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_parse_out_messages() {
+        let first = TonAddress::new(0, &[1; 32]);
+        let second = TonAddress::new(0, &[2; 32]);
+        let body = CellBuilder::new()
+            .store_u32(32, 0x0f8a7ea5)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let mut actions = Cell::default();
+        for (destination, value, mode) in [(&first, 100, 1), (&second, 200, 64)] {
+            let action = CellBuilder::new()
+                .store_reference(&Arc::new(actions))
+                .unwrap()
+                .store_u32(32, 0x0ec3c86d)
+                .unwrap()
+                .store_u8(8, mode)
+                .unwrap()
+                .store_child(build_message(destination, value, &body))
+                .unwrap()
+                .build()
+                .unwrap();
+            actions = action;
+        }
+
+        let out_msgs = parse_out_messages(&actions).unwrap();
+        assert_eq!(out_msgs.len(), 2);
+        assert_eq!(out_msgs[0].destination, first);
+        assert_eq!((out_msgs[0].value, out_msgs[0].mode), (100, 1));
+        assert!(out_msgs[0].bounce);
+        assert_eq!(out_msgs[1].destination, second);
+        assert_eq!((out_msgs[1].value, out_msgs[1].mode), (200, 64));
+        assert_eq!(out_msgs[1].body.as_ref(), &body);
+    }
+
+    #[test]
+    fn test_message_header() {
+        let destination = TonAddress::new(0, &[1; 32]);
+        let message = build_message(&destination, 100, &Cell::default());
+        let header = MessageHeader::parse(&message).unwrap();
+        assert_eq!(header.address, destination);
+        assert_eq!(header.amount, 100);
+        assert!(header.bounce && header.internal);
+
+        let external = CellBuilder::new()
+            // ext_in_msg_info$10 src:addr_none
+            .store_u8(4, 0b1000)
+            .unwrap()
+            .store_address(&destination)
+            .unwrap()
+            .store_coins(&BigUint::from(0u32))
+            .unwrap()
+            .build()
+            .unwrap();
+        let header = MessageHeader::parse(&external).unwrap();
+        assert_eq!(header.address, destination);
+        assert!(!header.bounce && !header.internal);
+    }
+}
@@ -0,0 +1,350 @@
+use num_bigint::BigUint;
+use serde::Deserialize;
+
+use crate::address::TonAddress;
+use crate::cell::{ArcCell, BagOfCells, Cell, CellBuilder, StateInitBuilder, TonCellError};
+use crate::emulator::{AccountStorageStat, TvmEmulatorError, TxEmulatorUnsafe};
+use crate::tl::RawFullAccountState;
+use crate::transaction::{ParsedTx, TxActionPhase, TxComputePhase, TxFees};
+
+const DEFAULT_VM_LOG_VERBOSITY: u32 = 1;
+
+/// Executes messages on the account with all phases of the transaction, as validators do.
+///
+/// Unlike `TvmEmulator`, which runs the code only, fees are computed according to the
+/// blockchain config, so the result can be used to estimate fees before sending the message.
+#[derive(Debug)]
+pub struct TransactionEmulator {
+    emulator: TxEmulatorUnsafe,
+}
+
+impl TransactionEmulator {
+    /// Creates emulator for the blockchain config (`ConfigParams` dictionary), e.g. returned by
+    /// `get_config_all`.
+    pub fn new(config: &[u8]) -> Result<TransactionEmulator, TvmEmulatorError> {
+        let emulator = TxEmulatorUnsafe::create(config, DEFAULT_VM_LOG_VERBOSITY)?;
+        Ok(TransactionEmulator { emulator })
+    }
+
+    pub fn set_unixtime(&mut self, unixtime: u32) -> Result<&mut Self, TvmEmulatorError> {
+        match self.emulator.set_unixtime(unixtime) {
+            true => Ok(self),
+            false => Err(TvmEmulatorError::InternalError(
+                "Unable to set unixtime".to_string(),
+            )),
+        }
+    }
+
+    pub fn set_lt(&mut self, lt: u64) -> Result<&mut Self, TvmEmulatorError> {
+        match self.emulator.set_lt(lt) {
+            true => Ok(self),
+            false => Err(TvmEmulatorError::InternalError(
+                "Unable to set lt".to_string(),
+            )),
+        }
+    }
+
+    pub fn set_rand_seed(&mut self, seed: &[u8; 32]) -> Result<&mut Self, TvmEmulatorError> {
+        let seed_hex = hex::encode(seed);
+        match self.emulator.set_rand_seed(seed_hex.as_bytes())? {
+            true => Ok(self),
+            false => Err(TvmEmulatorError::InternalError(
+                "Unable to set rand seed".to_string(),
+            )),
+        }
+    }
+
+    /// Makes signature checks always succeed, so that unsigned messages can be emulated.
+    /// Gas used by the checks is still accounted.
+    pub fn set_ignore_chksig(
+        &mut self,
+        ignore_chksig: bool,
+    ) -> Result<&mut Self, TvmEmulatorError> {
+        match self.emulator.set_ignore_chksig(ignore_chksig) {
+            true => Ok(self),
+            false => Err(TvmEmulatorError::InternalError(
+                "Unable to set ignore_chksig".to_string(),
+            )),
+        }
+    }
+
+    pub fn set_libraries(&mut self, libraries: &[u8]) -> Result<&mut Self, TvmEmulatorError> {
+        if libraries.is_empty() {
+            return Ok(self);
+        }
+        match self.emulator.set_libraries(libraries)? {
+            true => Ok(self),
+            false => Err(TvmEmulatorError::EmulatorError(
+                "Couldn't set libraries".to_string(),
+            )),
+        }
+    }
+
+    pub fn set_debug_enable(&mut self) -> Result<&mut Self, TvmEmulatorError> {
+        match self.emulator.set_debug_enabled(true) {
+            true => Ok(self),
+            false => Err(TvmEmulatorError::InternalError(
+                "Unable to set debug enable".to_string(),
+            )),
+        }
+    }
+
+    /// Emulates the transaction of the account caused by the internal or external message.
+    /// The account is passed as `ShardAccount` cell, see `get_shard_account_cell` and
+    /// `build_shard_account`.
+    pub fn emulate_transaction(
+        &mut self,
+        shard_account: &Cell,
+        message: &Cell,
+    ) -> Result<TxEmulationSuccess, TvmEmulatorError> {
+        let shard_account = BagOfCells::from_root(shard_account.clone()).serialize(false)?;
+        let message = BagOfCells::from_root(message.clone()).serialize(false)?;
+        let result = self
+            .emulator
+            .emulate_transaction(&shard_account, &message)?;
+        TxEmulationSuccess::from_json(&result)
+    }
+}
+
+/// Transaction produced by `TransactionEmulator`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TxEmulationSuccess {
+    pub transaction: ParsedTx,
+    /// Messages sent by the transaction in the order of sending.
+    pub out_msgs: Vec<ArcCell>,
+    /// `ShardAccount` after the transaction, can be used to emulate the next one.
+    pub shard_account: ArcCell,
+    pub vm_log: Option<String>,
+    /// Actions (`OutList`) of the compute phase.
+    pub actions: Option<ArcCell>,
+    /// time of the emulation in seconds.
+    pub elapsed_time: f64,
+}
+
+impl TxEmulationSuccess {
+    pub fn compute_phase(&self) -> Option<&TxComputePhase> {
+        self.transaction.compute_phase.as_ref()
+    }
+
+    pub fn action_phase(&self) -> Option<&TxActionPhase> {
+        self.transaction.action_phase.as_ref()
+    }
+
+    /// Gas used in the compute phase, 0 if the phase is skipped.
+    pub fn gas_used(&self) -> BigUint {
+        match self.compute_phase() {
+            Some(TxComputePhase::Vm { gas_used, .. }) => gas_used.clone(),
+            _ => BigUint::default(),
+        }
+    }
+
+    pub fn total_fees(&self) -> &BigUint {
+        &self.transaction.total_fees
+    }
+
+    pub fn fees(&self) -> TxFees {
+        self.transaction.fees()
+    }
+
+    fn from_json(json_str: &str) -> Result<TxEmulationSuccess, TvmEmulatorError> {
+        let response: TxEmulatorResponse = serde_json::from_str(json_str)?;
+        if !response.success {
+            let error = response.error.unwrap_or_default();
+            return match response.external_not_accepted {
+                Some(true) => Err(TvmEmulatorError::ExternalNotAccepted {
+                    vm_exit_code: response.vm_exit_code,
+                    error,
+                }),
+                _ => Err(TvmEmulatorError::EmulatorError(error)),
+            };
+        }
+        let transaction = response
+            .transaction
+            .ok_or(TvmEmulatorError::MissingJsonField("transaction"))?;
+        let shard_account = response
+            .shard_account
+            .ok_or(TvmEmulatorError::MissingJsonField("shard_account"))?;
+        let transaction = BagOfCells::parse_base64(&transaction)?;
+        let transaction = transaction.single_root()?;
+        let actions = match response.actions {
+            Some(actions) => Some(BagOfCells::parse_base64(&actions)?.single_root()?.clone()),
+            None => None,
+        };
+        Ok(TxEmulationSuccess {
+            transaction: ParsedTx::parse(transaction)?,
+            out_msgs: ParsedTx::parse_out_msg_cells(transaction)?,
+            shard_account: BagOfCells::parse_base64(&shard_account)?
+                .single_root()?
+                .clone(),
+            vm_log: response.vm_log,
+            actions,
+            elapsed_time: response.elapsed_time.unwrap_or_default(),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct TxEmulatorResponse {
+    success: bool,
+    transaction: Option<String>,
+    shard_account: Option<String>,
+    vm_log: Option<String>,
+    actions: Option<String>,
+    elapsed_time: Option<f64>,
+    error: Option<String>,
+    external_not_accepted: Option<bool>,
+    vm_exit_code: Option<i32>,
+}
+
+/// Builds `ShardAccount` of the account state returned by tonlib:
+///
+/// ```raw
+/// account_storage$_ last_trans_lt:uint64 balance:CurrencyCollection state:AccountState
+///   = AccountStorage;
+/// account$1 addr:MsgAddressInt storage_stat:StorageInfo storage:AccountStorage = Account;
+/// shard_account$_ account:^Account last_trans_hash:bits256 last_trans_lt:uint64 = ShardAccount;
+/// ```
+///
+/// Extra currencies and libraries of the account are not included.
+pub fn build_shard_account(
+    address: &TonAddress,
+    state: &RawFullAccountState,
+    storage_stat: &AccountStorageStat,
+) -> Result<Cell, TonCellError> {
+    let last_trans_lt = state.last_transaction_id.lt as u64;
+    let last_trans_hash = if state.last_transaction_id.hash.is_empty() {
+        vec![0; 32]
+    } else {
+        state.last_transaction_id.hash.clone()
+    };
+    let balance = BigUint::from(state.balance.max(0) as u64);
+
+    let mut account = CellBuilder::new();
+    account.store_bit(true)?.store_raw_address(address)?;
+    // storage_used$_ cells:(VarUInteger 7) bits:(VarUInteger 7) public_cells:(VarUInteger 7)
+    store_var_uint7(&mut account, storage_stat.used_cells)?;
+    store_var_uint7(&mut account, storage_stat.used_bits)?;
+    store_var_uint7(&mut account, storage_stat.used_public_cells)?;
+    account.store_u32(32, storage_stat.last_paid)?;
+    match &storage_stat.due_payment {
+        Some(due_payment) => account.store_bit(true)?.store_coins(due_payment)?,
+        None => account.store_bit(false)?,
+    };
+    account
+        .store_u64(64, last_trans_lt)?
+        .store_coins(&balance)?
+        // no extra currencies
+        .store_bit(false)?;
+    if !state.code.is_empty() {
+        let code = BagOfCells::parse(&state.code)?.single_root()?.clone();
+        let data = if state.data.is_empty() {
+            Cell::default().to_arc()
+        } else {
+            BagOfCells::parse(&state.data)?.single_root()?.clone()
+        };
+        // account_active$1 _:StateInit
+        account
+            .store_bit(true)?
+            .store_cell(&StateInitBuilder::new(&code, &data).build()?)?;
+    } else if !state.frozen_hash.is_empty() {
+        // account_frozen$01 state_hash:bits256
+        account.store_u8(2, 0b01)?.store_slice(&state.frozen_hash)?;
+    } else {
+        // account_uninit$00
+        account.store_u8(2, 0b00)?;
+    }
+
+    CellBuilder::new()
+        .store_child(account.build()?)?
+        .store_slice(&last_trans_hash)?
+        .store_u64(64, last_trans_lt)?
+        .build()
+}
+
+/// Stores `VarUInteger 7`: 3-bit length in bytes followed by the value.
+fn store_var_uint7(builder: &mut CellBuilder, val: u64) -> Result<(), TonCellError> {
+    let num_bytes = (64 - val.leading_zeros() as usize).div_ceil(8);
+    builder.store_u8(3, num_bytes as u8)?;
+    builder.store_u64(num_bytes * 8, val)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use crate::address::TonAddress;
+    use crate::emulator::tx_emulator::TxEmulationSuccess;
+    use crate::emulator::{build_shard_account, AccountStorageStat, TvmEmulatorError};
+    use crate::tl::{BlockIdExt, InternalTransactionId, RawFullAccountState};
+
+    #[test]
+    fn test_build_shard_account() -> anyhow::Result<()> {
+        let address = TonAddress::new(0, &[1; 32]);
+        let state = RawFullAccountState {
+            balance: 1_000_000_000,
+            code: vec![],
+            data: vec![],
+            last_transaction_id: InternalTransactionId {
+                lt: 42,
+                hash: vec![7; 32],
+            },
+            block_id: BlockIdExt {
+                workchain: -1,
+                shard: i64::MIN,
+                seqno: 1,
+                root_hash: "".to_string(),
+                file_hash: "".to_string(),
+            },
+            frozen_hash: vec![],
+            sync_utime: 1_700_000_000,
+        };
+        let stat = AccountStorageStat {
+            used_cells: 1,
+            used_bits: 300,
+            used_public_cells: 0,
+            last_paid: 1_700_000_000,
+            due_payment: None,
+        };
+        let shard_account = build_shard_account(&address, &state, &stat)?;
+
+        let mut parser = shard_account.parser();
+        assert_eq!(parser.load_bytes(32)?, vec![7; 32]);
+        assert_eq!(parser.load_u64(64)?, 42);
+        let account = shard_account.reference(0)?;
+        let mut parser = account.parser();
+        assert!(parser.load_bit()?);
+        assert_eq!(parser.load_address()?, address);
+        // cells: 1 byte, bits: 2 bytes, public_cells: 0 bytes
+        assert_eq!(parser.load_u8(3)?, 1);
+        assert_eq!(parser.load_u8(8)?, 1);
+        assert_eq!(parser.load_u8(3)?, 2);
+        assert_eq!(parser.load_u32(16)?, 300);
+        assert_eq!(parser.load_u8(3)?, 0);
+        assert_eq!(parser.load_u32(32)?, 1_700_000_000);
+        assert!(!parser.load_bit()?);
+        assert_eq!(parser.load_u64(64)?, 42);
+        assert_eq!(parser.load_coins()?, BigUint::from(1_000_000_000u64));
+        assert!(!parser.load_bit()?);
+        assert_eq!(parser.load_u8(2)?, 0);
+        assert_eq!(parser.remaining_bits(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tx_emulation_failure() {
+        let json = r#"{"success":false,"error":"External message not accepted by smart contract","external_not_accepted":true,"vm_exit_code":33,"vm_log":"","elapsed_time":0.001}"#;
+        let result = TxEmulationSuccess::from_json(json);
+        assert!(matches!(
+            result,
+            Err(TvmEmulatorError::ExternalNotAccepted {
+                vm_exit_code: Some(33),
+                ..
+            })
+        ));
+
+        let json = r#"{"success":false,"error":"Can't deserialize message boc","external_not_accepted":false}"#;
+        let result = TxEmulationSuccess::from_json(json);
+        assert!(matches!(result, Err(TvmEmulatorError::EmulatorError(_))));
+    }
+}
@@ -4,8 +4,8 @@ use num_bigint::BigInt;
 use serde::{Deserialize, Serialize};
 
 use super::TvmEmulatorError;
-use crate::cell::{BagOfCells, CellSlice};
-use crate::types::{TvmMsgSuccess, TvmStackEntry, TvmSuccess};
+use crate::cell::{ArcCell, BagOfCells, CellParser, CellSlice};
+use crate::types::{TvmMsgSuccess, TvmRunSource, TvmStackEntry, TvmSuccess};
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -51,6 +51,7 @@ impl TvmEmulatorResponse {
                     stack,
                     missing_library,
                     gas_used,
+                    source: TvmRunSource::Emulator,
                 })
             }
             false => {
@@ -64,10 +65,10 @@ impl TvmEmulatorResponse {
         result
     }
 
-    fn extract_stack(boc: &BagOfCells) -> Result<Vec<TvmStackEntry>, TvmEmulatorError> {
+    pub(crate) fn extract_stack(boc: &BagOfCells) -> Result<Vec<TvmStackEntry>, TvmEmulatorError> {
         let mut stack = vec![];
 
-        let mut current_cell = boc.single_root()?;
+        let mut current_cell = boc.single_root()?.clone();
         log::trace!("Parsing stack:\n{:?}", current_cell);
 
         let mut parser = current_cell.parser();
@@ -75,47 +76,12 @@ impl TvmEmulatorResponse {
         let elements_count = parser.load_u32(24)?;
 
         for element in 0..elements_count {
-            let element_type = parser.load_byte()?;
-
-            let stack_entry = match element_type {
-                0 => TvmStackEntry::Null,
-                1 => TvmStackEntry::Int64(parser.load_i64(64)?),
-                2 => match parser.load_byte()? {
-                    0 => {
-                        let bit_len = parser.remaining_bits();
-                        let num = BigInt::from(parser.load_uint(bit_len)?);
-                        TvmStackEntry::Int257(num)
-                    }
-                    1 => {
-                        let bit_len = parser.remaining_bits();
-                        let num = BigInt::from(parser.load_uint(bit_len)?).neg();
-                        TvmStackEntry::Int257(num)
-                    }
-                    0xff => TvmStackEntry::Nan,
-                    _ => TvmStackEntry::Unsupported,
-                },
-                3 => TvmStackEntry::Cell(current_cell.reference(1)?.clone()),
-                4 => {
-                    let st_bits = parser.load_u32(10)? as usize;
-                    let end_bits = parser.load_u32(10)? as usize;
-                    let st_ref = parser.load_u32(3)? as usize;
-                    let end_ref = parser.load_u32(3)? as usize;
-
-                    let cell = current_cell.reference(1)?;
-                    let slice = CellSlice::new(cell, st_bits, end_bits, st_ref, end_ref)?;
-                    TvmStackEntry::Slice(slice)
-                }
-                _ => TvmStackEntry::Unsupported,
-            };
+            // the first reference is the rest of the stack
+            let stack_entry = Self::parse_stack_value(&current_cell, &mut parser, 1)?;
             // TODO: Remove trace when feature emulator is stable
-            log::trace!(
-                "element#{:?} ,type: {:?}:: {:?}",
-                element,
-                element_type,
-                stack_entry
-            );
+            log::trace!("element#{:?}: {:?}", element, stack_entry);
             if element != elements_count - 1 {
-                current_cell = current_cell.reference(0)?;
+                current_cell = current_cell.reference(0)?.clone();
                 parser = current_cell.parser();
             }
             stack.push(stack_entry);
@@ -123,6 +89,79 @@ impl TvmEmulatorResponse {
         stack.reverse();
         Ok(stack)
     }
+
+    /// Parses `VmStackValue`, which references of `cell` start from `first_ref`.
+    fn parse_stack_value(
+        cell: &ArcCell,
+        parser: &mut CellParser,
+        first_ref: usize,
+    ) -> Result<TvmStackEntry, TvmEmulatorError> {
+        let element_type = parser.load_byte()?;
+        let stack_entry = match element_type {
+            0 => TvmStackEntry::Null,
+            1 => TvmStackEntry::Int64(parser.load_i64(64)?),
+            2 => match parser.load_byte()? {
+                0 => {
+                    let bit_len = parser.remaining_bits();
+                    let num = BigInt::from(parser.load_uint(bit_len)?);
+                    TvmStackEntry::Int257(num)
+                }
+                1 => {
+                    let bit_len = parser.remaining_bits();
+                    let num = BigInt::from(parser.load_uint(bit_len)?).neg();
+                    TvmStackEntry::Int257(num)
+                }
+                0xff => TvmStackEntry::Nan,
+                _ => TvmStackEntry::Unsupported,
+            },
+            3 => TvmStackEntry::Cell(cell.reference(first_ref)?.clone()),
+            4 => {
+                let st_bits = parser.load_u32(10)? as usize;
+                let end_bits = parser.load_u32(10)? as usize;
+                let st_ref = parser.load_u32(3)? as usize;
+                let end_ref = parser.load_u32(3)? as usize;
+
+                let cell = cell.reference(first_ref)?;
+                let slice = CellSlice::new(cell, st_bits, end_bits, st_ref, end_ref)?;
+                TvmStackEntry::Slice(slice)
+            }
+            7 => {
+                let len = parser.load_u32(16)? as usize;
+                TvmStackEntry::Tuple(Self::parse_vm_tuple(cell, first_ref, len)?)
+            }
+            _ => TvmStackEntry::Unsupported,
+        };
+        Ok(stack_entry)
+    }
+
+    /// Parses `VmTuple` of `len` elements, which references of `cell` start from `first_ref`.
+    fn parse_vm_tuple(
+        cell: &ArcCell,
+        first_ref: usize,
+        len: usize,
+    ) -> Result<Vec<TvmStackEntry>, TvmEmulatorError> {
+        if len == 0 {
+            return Ok(vec![]);
+        }
+        let (mut elements, last_ref) = match len - 1 {
+            0 => (vec![], first_ref),
+            1 => (
+                vec![Self::parse_stack_value_cell(cell.reference(first_ref)?)?],
+                first_ref + 1,
+            ),
+            head_len => (
+                Self::parse_vm_tuple(cell.reference(first_ref)?, 0, head_len)?,
+                first_ref + 1,
+            ),
+        };
+        elements.push(Self::parse_stack_value_cell(cell.reference(last_ref)?)?);
+        Ok(elements)
+    }
+
+    fn parse_stack_value_cell(cell: &ArcCell) -> Result<TvmStackEntry, TvmEmulatorError> {
+        let mut parser = cell.parser();
+        Self::parse_stack_value(cell, &mut parser, 0)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -0,0 +1,103 @@
+use std::ffi::CString;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use tonlib_sys::{
+    transaction_emulator_create, transaction_emulator_destroy,
+    transaction_emulator_emulate_transaction, transaction_emulator_set_debug_enabled,
+    transaction_emulator_set_ignore_chksig, transaction_emulator_set_libs,
+    transaction_emulator_set_lt, transaction_emulator_set_rand_seed,
+    transaction_emulator_set_unixtime,
+};
+
+use super::TvmEmulatorError;
+
+#[derive(Debug)]
+pub struct TxEmulatorUnsafe {
+    ptr: *mut ::std::os::raw::c_void,
+}
+
+unsafe impl Send for TxEmulatorUnsafe {}
+
+unsafe impl Sync for TxEmulatorUnsafe {}
+
+impl TxEmulatorUnsafe {
+    pub fn create(
+        config: &[u8],
+        vm_log_verbosity: u32,
+    ) -> Result<TxEmulatorUnsafe, TvmEmulatorError> {
+        log::trace!("tx_emulator_unsafe: creating...");
+        let config = CString::new(STANDARD.encode(config))?;
+
+        let emulator = unsafe {
+            let ptr = transaction_emulator_create(config.as_ptr(), vm_log_verbosity);
+            TxEmulatorUnsafe { ptr }
+        };
+        if emulator.ptr.is_null() {
+            log::trace!("tx_emulator_unsafe: creating failed");
+            Err(TvmEmulatorError::CreationFailed())
+        } else {
+            log::trace!("tx_emulator_unsafe: created");
+            Ok(emulator)
+        }
+    }
+
+    pub fn emulate_transaction(
+        &mut self,
+        shard_account: &[u8],
+        message: &[u8],
+    ) -> Result<String, TvmEmulatorError> {
+        log::trace!("emulate_transaction_req: msg: {:?}", message);
+        let shard_account = CString::new(STANDARD.encode(shard_account))?;
+        let message_encoded = CString::new(STANDARD.encode(message))?;
+        let c_str = unsafe {
+            transaction_emulator_emulate_transaction(
+                self.ptr,
+                shard_account.as_ptr(),
+                message_encoded.as_ptr(),
+            )
+        };
+        let json_str = unsafe { std::ffi::CStr::from_ptr(c_str).to_str() }?;
+        log::trace!(
+            "emulate_transaction_rsp: msg: {:?}, rsp: {}",
+            message,
+            json_str
+        );
+        Ok(json_str.to_string())
+    }
+
+    pub fn set_unixtime(&mut self, unixtime: u32) -> bool {
+        unsafe { transaction_emulator_set_unixtime(self.ptr, unixtime) }
+    }
+
+    pub fn set_lt(&mut self, lt: u64) -> bool {
+        unsafe { transaction_emulator_set_lt(self.ptr, lt) }
+    }
+
+    pub fn set_rand_seed(&mut self, rand_seed_hex: &[u8]) -> Result<bool, TvmEmulatorError> {
+        let rand_seed_hex_encoded = CString::new(rand_seed_hex)?;
+        let success =
+            unsafe { transaction_emulator_set_rand_seed(self.ptr, rand_seed_hex_encoded.as_ptr()) };
+        Ok(success)
+    }
+
+    pub fn set_ignore_chksig(&mut self, ignore_chksig: bool) -> bool {
+        unsafe { transaction_emulator_set_ignore_chksig(self.ptr, ignore_chksig) }
+    }
+
+    pub fn set_libraries(&mut self, libs_boc: &[u8]) -> Result<bool, TvmEmulatorError> {
+        let libs_encoded = CString::new(STANDARD.encode(libs_boc))?;
+        let success = unsafe { transaction_emulator_set_libs(self.ptr, libs_encoded.as_ptr()) };
+        Ok(success)
+    }
+
+    pub fn set_debug_enabled(&mut self, enable: bool) -> bool {
+        unsafe { transaction_emulator_set_debug_enabled(self.ptr, enable) }
+    }
+}
+
+impl Drop for TxEmulatorUnsafe {
+    fn drop(&mut self) {
+        unsafe { transaction_emulator_destroy(self.ptr) }
+    }
+}
@@ -0,0 +1,21 @@
+pub use boc_store::*;
+pub use error::*;
+pub use ledger::*;
+#[cfg(feature = "parquet")]
+pub use parquet_writer::*;
+pub use pipeline::*;
+#[cfg(feature = "postgres")]
+pub use postgres_sink::*;
+pub use shard_router::*;
+pub use writer::*;
+
+mod boc_store;
+mod error;
+mod ledger;
+#[cfg(feature = "parquet")]
+mod parquet_writer;
+mod pipeline;
+#[cfg(feature = "postgres")]
+mod postgres_sink;
+mod shard_router;
+mod writer;
@@ -0,0 +1,154 @@
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+
+use crate::cell::BagOfCells;
+use crate::export::ExportError;
+use crate::tl::RawTransaction;
+use crate::types::TonHash;
+
+/// Content-addressable storage of raw BoCs keyed by the hash of their root cell, e.g.
+/// transactions loaded by the streams, which can be re-processed later without downloading
+/// them again. Transactions are keyed by the transaction hash.
+#[async_trait]
+pub trait BocStore: Send + Sync {
+    async fn get(&self, hash: &TonHash) -> Result<Option<Vec<u8>>, ExportError>;
+
+    /// Stores the BoC, `hash` must be the hash of its root cell.
+    async fn insert(&self, hash: &TonHash, boc: &[u8]) -> Result<(), ExportError>;
+
+    /// Stores the BoC and returns the hash of its root cell.
+    async fn put(&self, boc: &[u8]) -> Result<TonHash, ExportError> {
+        let hash = BagOfCells::parse(boc)?.single_root()?.cell_hash();
+        self.insert(&hash, boc).await?;
+        Ok(hash)
+    }
+
+    async fn contains(&self, hash: &TonHash) -> Result<bool, ExportError> {
+        Ok(self.get(hash).await?.is_some())
+    }
+}
+
+/// Stores the transaction, logging failures instead of returning them, so that streams
+/// keep going if the store is unavailable.
+pub(crate) async fn store_transaction(store: &dyn BocStore, tx: &RawTransaction) {
+    if let Err(e) = store.put(&tx.data).await {
+        log::warn!(
+            "[BocStore] Failed to store transaction {} of {}: {}",
+            tx.transaction_id.lt,
+            tx.address.account_address,
+            e
+        );
+    }
+}
+
+/// Keeps BoCs in memory, e.g. for tests.
+#[derive(Default)]
+pub struct InMemoryBocStore {
+    bocs: DashMap<TonHash, Vec<u8>>,
+}
+
+impl InMemoryBocStore {
+    pub fn new() -> InMemoryBocStore {
+        InMemoryBocStore::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.bocs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bocs.is_empty()
+    }
+}
+
+#[async_trait]
+impl BocStore for InMemoryBocStore {
+    async fn get(&self, hash: &TonHash) -> Result<Option<Vec<u8>>, ExportError> {
+        Ok(self.bocs.get(hash).map(|b| b.value().clone()))
+    }
+
+    async fn insert(&self, hash: &TonHash, boc: &[u8]) -> Result<(), ExportError> {
+        self.bocs.insert(*hash, boc.to_vec());
+        Ok(())
+    }
+}
+
+/// Keeps every BoC in a file of the directory named by the hex hash, in subdirectories named
+/// by the first byte of the hash.
+pub struct FileBocStore {
+    dir: PathBuf,
+}
+
+impl FileBocStore {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Result<FileBocStore, ExportError> {
+        std::fs::create_dir_all(dir.as_ref())?;
+        Ok(FileBocStore {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    fn path(&self, hash: &TonHash) -> PathBuf {
+        let name = hex::encode(hash);
+        self.dir.join(&name[..2]).join(format!("{}.boc", name))
+    }
+}
+
+#[async_trait]
+impl BocStore for FileBocStore {
+    async fn get(&self, hash: &TonHash) -> Result<Option<Vec<u8>>, ExportError> {
+        match std::fs::read(self.path(hash)) {
+            Ok(boc) => Ok(Some(boc)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn insert(&self, hash: &TonHash, boc: &[u8]) -> Result<(), ExportError> {
+        let path = self.path(hash);
+        // content never changes, so existing files are kept
+        if path.exists() {
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // write and rename, so that a crash never leaves a partial BoC
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, boc)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::cell::{BagOfCells, CellBuilder};
+    use crate::export::{BocStore, FileBocStore, InMemoryBocStore};
+
+    #[tokio::test]
+    async fn test_boc_stores() -> anyhow::Result<()> {
+        let cell = CellBuilder::new().store_u32(32, 0xdeadbeef)?.build()?;
+        let hash = cell.cell_hash();
+        let boc = BagOfCells::from_root(cell).serialize(false)?;
+
+        let dir = std::env::temp_dir().join(format!("tonlib-boc-store-{}", std::process::id()));
+        let stores: [Arc<dyn BocStore>; 2] = [
+            Arc::new(InMemoryBocStore::new()),
+            Arc::new(FileBocStore::new(&dir)?),
+        ];
+        for store in stores {
+            assert_eq!(store.get(&hash).await?, None);
+            assert_eq!(store.put(&boc).await?, hash);
+            assert_eq!(store.put(&boc).await?, hash);
+            assert!(store.contains(&hash).await?);
+            assert_eq!(store.get(&hash).await?, Some(boc.clone()));
+            assert!(store.put(&[1, 2, 3]).await.is_err());
+        }
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
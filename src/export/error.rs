@@ -0,0 +1,34 @@
+use thiserror::Error;
+
+use crate::address::TonAddressParseError;
+use crate::cell::TonCellError;
+use crate::client::TonClientError;
+
+#[derive(Error, Debug)]
+pub enum ExportError {
+    #[error("IO error ({0})")]
+    Io(#[from] std::io::Error),
+
+    #[error("Serde_json Error ({0})")]
+    SerdeJsonError(#[from] serde_json::Error),
+
+    #[cfg(feature = "parquet")]
+    #[error("Parquet error ({0})")]
+    ParquetError(#[from] parquet::errors::ParquetError),
+
+    #[cfg(feature = "postgres")]
+    #[error("Sqlx error ({0})")]
+    SqlxError(#[from] sqlx::Error),
+
+    #[error("Schema mismatch ({0})")]
+    SchemaMismatch(String),
+
+    #[error("TonAddressParseError ({0})")]
+    TonAddressParseError(#[from] TonAddressParseError),
+
+    #[error("TonCellError ({0})")]
+    TonCellError(#[from] TonCellError),
+
+    #[error("TonClientError ({0})")]
+    TonClientError(#[from] TonClientError),
+}
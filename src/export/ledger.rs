@@ -0,0 +1,212 @@
+use std::fmt;
+
+use num_bigint::BigUint;
+use num_traits::Zero;
+use serde::{Serialize, Serializer};
+
+use crate::address::TonAddress;
+use crate::transaction::{JettonTransferAction, ParsedTx, TxMessageInfo};
+use crate::types::TonHash;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LedgerAsset {
+    Ton,
+    /// Jetton identified by its master contract.
+    Jetton(TonAddress),
+}
+
+impl fmt::Display for LedgerAsset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LedgerAsset::Ton => f.write_str("TON"),
+            LedgerAsset::Jetton(master) => write!(f, "{}", master),
+        }
+    }
+}
+
+impl Serialize for LedgerAsset {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Normalized ledger entry. Amounts and fees are in minimal units of the asset and of TON
+/// respectively.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LedgerRow {
+    pub timestamp: u32,
+    pub from: TonAddress,
+    pub to: TonAddress,
+    pub asset: LedgerAsset,
+    #[serde(serialize_with = "serialize_biguint")]
+    pub amount: BigUint,
+    #[serde(serialize_with = "serialize_biguint")]
+    pub fee: BigUint,
+    #[serde(serialize_with = "serialize_hash")]
+    pub tx_hash: TonHash,
+}
+
+/// Source of ledger rows, e.g. a parsed transaction or a decoded jetton transfer.
+pub trait ToLedgerRows {
+    fn ledger_rows(&self) -> Vec<LedgerRow>;
+}
+
+impl ToLedgerRows for ParsedTx {
+    /// Returns TON transfers of incoming and outgoing internal messages. Bounced messages are
+    /// skipped, since they return the value of a failed transfer. Fees of outgoing messages are
+    /// attributed to them; transaction fees are attributed to the first outgoing transfer, or
+    /// to the incoming one if there is none.
+    fn ledger_rows(&self) -> Vec<LedgerRow> {
+        let row = |from: &TonAddress, to: &TonAddress, amount: &BigUint, fee: BigUint| LedgerRow {
+            timestamp: self.now,
+            from: from.clone(),
+            to: to.clone(),
+            asset: LedgerAsset::Ton,
+            amount: amount.clone(),
+            fee,
+            tx_hash: self.hash,
+        };
+
+        let mut incoming = match &self.in_msg {
+            Some(TxMessageInfo::Internal {
+                src,
+                dest,
+                value,
+                bounced: false,
+                ..
+            }) => Some(row(src, dest, value, BigUint::zero())),
+            _ => None,
+        };
+        let mut outgoing: Vec<_> = self
+            .out_msgs
+            .iter()
+            .filter_map(|msg| match msg {
+                TxMessageInfo::Internal {
+                    src,
+                    dest,
+                    value,
+                    ihr_fee,
+                    fwd_fee,
+                    bounced: false,
+                } => Some(row(src, dest, value, ihr_fee + fwd_fee)),
+                _ => None,
+            })
+            .collect();
+
+        match (outgoing.first_mut(), incoming.as_mut()) {
+            (Some(first), _) => first.fee += &self.total_fees,
+            (None, Some(incoming)) => incoming.fee += &self.total_fees,
+            (None, None) => {}
+        }
+        incoming.into_iter().chain(outgoing).collect()
+    }
+}
+
+impl ToLedgerRows for JettonTransferAction {
+    /// Returns the jetton transfer. Its fee is zero, since TON fees of the transaction are
+    /// reported by the rows of the transaction itself.
+    fn ledger_rows(&self) -> Vec<LedgerRow> {
+        vec![LedgerRow {
+            timestamp: self.timestamp,
+            from: self.from.clone(),
+            to: self.to.clone(),
+            asset: LedgerAsset::Jetton(self.master.clone()),
+            amount: self.amount.clone(),
+            fee: BigUint::zero(),
+            tx_hash: self.tx_hash,
+        }]
+    }
+}
+
+fn serialize_biguint<S: Serializer>(value: &BigUint, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+fn serialize_hash<S: Serializer>(value: &TonHash, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&hex::encode(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use crate::address::TonAddress;
+    use crate::export::{LedgerAsset, ToLedgerRows};
+    use crate::transaction::{JettonTransferAction, ParsedTx, TxMessageInfo};
+
+    fn internal(src: &TonAddress, dest: &TonAddress, value: u64, fwd_fee: u64) -> TxMessageInfo {
+        TxMessageInfo::Internal {
+            src: src.clone(),
+            dest: dest.clone(),
+            value: BigUint::from(value),
+            ihr_fee: BigUint::from(0u32),
+            fwd_fee: BigUint::from(fwd_fee),
+            bounced: false,
+        }
+    }
+
+    #[test]
+    fn test_parsed_tx_ledger_rows() {
+        let sender = TonAddress::new(0, &[1; 32]);
+        let wallet = TonAddress::new(0, &[2; 32]);
+        let receiver = TonAddress::new(0, &[3; 32]);
+        let mut tx = ParsedTx {
+            hash: [4; 32],
+            account: wallet.hash_part,
+            lt: 1,
+            prev_trans_hash: [0; 32],
+            prev_trans_lt: 0,
+            now: 1_700_000_000,
+            in_msg: Some(internal(&sender, &wallet, 500, 100)),
+            in_msg_hash: None,
+            out_msgs: vec![internal(&wallet, &receiver, 300, 50)],
+            total_fees: BigUint::from(20u32),
+            storage_phase: None,
+            credit_phase: None,
+            compute_phase: None,
+            action_phase: None,
+            aborted: false,
+        };
+
+        let rows = tx.ledger_rows();
+        assert_eq!(rows.len(), 2);
+        assert_eq!((&rows[0].from, &rows[0].to), (&sender, &wallet));
+        assert_eq!(rows[0].fee, BigUint::from(0u32));
+        assert_eq!((&rows[1].from, &rows[1].to), (&wallet, &receiver));
+        assert_eq!(rows[1].amount, BigUint::from(300u32));
+        assert_eq!(rows[1].fee, BigUint::from(70u32));
+        assert_eq!(rows[1].asset, LedgerAsset::Ton);
+        assert_eq!(rows[1].tx_hash, [4; 32]);
+
+        tx.out_msgs.clear();
+        let rows = tx.ledger_rows();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].fee, BigUint::from(20u32));
+
+        if let Some(TxMessageInfo::Internal { bounced, .. }) = &mut tx.in_msg {
+            *bounced = true;
+        }
+        assert!(tx.ledger_rows().is_empty());
+    }
+
+    #[test]
+    fn test_jetton_transfer_ledger_rows() {
+        let master = TonAddress::new(0, &[1; 32]);
+        let action = JettonTransferAction {
+            tx_hash: [4; 32],
+            timestamp: 1_700_000_000,
+            master: master.clone(),
+            jetton_wallet: TonAddress::new(0, &[2; 32]),
+            from: TonAddress::new(0, &[3; 32]),
+            to: TonAddress::new(0, &[5; 32]),
+            amount: BigUint::from(100u32),
+            query_id: 0,
+        };
+        let rows = action.ledger_rows();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].asset, LedgerAsset::Jetton(master));
+        assert_eq!((&rows[0].from, &rows[0].to), (&action.from, &action.to));
+        assert_eq!(rows[0].amount, BigUint::from(100u32));
+        assert_eq!(rows[0].fee, BigUint::from(0u32));
+    }
+}
@@ -0,0 +1,407 @@
+use std::io::Write;
+use std::sync::Arc;
+
+use num_traits::ToPrimitive;
+use parquet::basic::Compression;
+use parquet::column::writer::ColumnWriterImpl;
+use parquet::data_type::{BoolType, ByteArray, ByteArrayType, DataType, Int32Type, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::{SerializedColumnWriter, SerializedFileWriter};
+use parquet::schema::parser::parse_message_type;
+
+use crate::client::BlockHeader;
+use crate::export::{ExportError, LedgerRow};
+use crate::transaction::{ParsedTx, TxComputePhase, TxMessageInfo};
+
+/// Schema of blocks. Hashes are base64 as returned by tonlib.
+pub const PARQUET_BLOCKS_SCHEMA: &str = "
+message block {
+    required int32 workchain;
+    required int64 shard;
+    required int64 seqno;
+    required binary root_hash (STRING);
+    required binary file_hash (STRING);
+    required int32 global_id;
+    required int64 gen_utime;
+    required int64 start_lt;
+    required int64 end_lt;
+    required boolean is_key_block;
+    required int64 prev_key_block_seqno;
+    required int64 min_ref_mc_seqno;
+    required boolean after_merge;
+    required boolean after_split;
+    required boolean before_split;
+}";
+
+/// Schema of transactions. Hashes are hex, amounts are decimal strings in nanotons.
+/// `in_msg_type` is one of `internal`, `external_in`, `external_out`, null for tick-tock
+/// transactions. Compute columns are null if the compute phase is skipped.
+pub const PARQUET_TRANSACTIONS_SCHEMA: &str = "
+message transaction {
+    required binary hash (STRING);
+    required binary account (STRING);
+    required int64 lt;
+    required binary prev_trans_hash (STRING);
+    required int64 prev_trans_lt;
+    required int64 now;
+    optional binary in_msg_type (STRING);
+    optional binary in_msg_src (STRING);
+    optional binary in_msg_value (STRING);
+    required int32 out_msgs_count;
+    required binary total_fees (STRING);
+    optional boolean compute_success;
+    optional int32 compute_exit_code;
+    optional int64 compute_gas_used;
+    optional int32 action_result_code;
+    required boolean aborted;
+}";
+
+/// Schema of ledger rows (transfers), see `LedgerRow`. Amounts are decimal strings in
+/// minimal units, `asset` is `TON` or the address of the jetton master.
+pub const PARQUET_LEDGER_SCHEMA: &str = "
+message ledger_row {
+    required int64 timestamp;
+    required binary from (STRING);
+    required binary to (STRING);
+    required binary asset (STRING);
+    required binary amount (STRING);
+    required binary fee (STRING);
+    required binary tx_hash (STRING);
+}";
+
+/// Values of a column for a batch of rows. `None` is written as null.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParquetColumn {
+    Boolean(Vec<Option<bool>>),
+    Int32(Vec<Option<i32>>),
+    Int64(Vec<Option<i64>>),
+    String(Vec<Option<String>>),
+}
+
+/// Row which can be written by `ParquetWriter`.
+pub trait ParquetRow: Clone {
+    /// Parquet message type of the row.
+    fn schema() -> &'static str;
+
+    /// Returns columns of the rows in the order of the schema fields.
+    fn columns(rows: &[Self]) -> Vec<ParquetColumn>;
+}
+
+impl ParquetRow for BlockHeader {
+    fn schema() -> &'static str {
+        PARQUET_BLOCKS_SCHEMA
+    }
+
+    fn columns(rows: &[Self]) -> Vec<ParquetColumn> {
+        vec![
+            int32(rows, |b| Some(b.workchain())),
+            int64(rows, |b| Some(b.shard())),
+            int64(rows, |b| Some(b.seqno() as i64)),
+            string(rows, |b| Some(b.id.root_hash.clone())),
+            string(rows, |b| Some(b.id.file_hash.clone())),
+            int32(rows, |b| Some(b.global_id)),
+            int64(rows, |b| Some(b.gen_utime as i64)),
+            int64(rows, |b| Some(b.start_lt as i64)),
+            int64(rows, |b| Some(b.end_lt as i64)),
+            boolean(rows, |b| Some(b.is_key_block)),
+            int64(rows, |b| Some(b.prev_key_block_seqno as i64)),
+            int64(rows, |b| Some(b.min_ref_mc_seqno as i64)),
+            boolean(rows, |b| Some(b.after_merge)),
+            boolean(rows, |b| Some(b.after_split)),
+            boolean(rows, |b| Some(b.before_split)),
+        ]
+    }
+}
+
+impl ParquetRow for ParsedTx {
+    fn schema() -> &'static str {
+        PARQUET_TRANSACTIONS_SCHEMA
+    }
+
+    fn columns(rows: &[Self]) -> Vec<ParquetColumn> {
+        let compute = |tx: &ParsedTx| match &tx.compute_phase {
+            Some(TxComputePhase::Vm {
+                success,
+                gas_used,
+                exit_code,
+                ..
+            }) => Some((*success, *exit_code, gas_used.to_i64())),
+            _ => None,
+        };
+        vec![
+            string(rows, |tx| Some(hex::encode(tx.hash))),
+            string(rows, |tx| Some(hex::encode(tx.account))),
+            int64(rows, |tx| Some(tx.lt as i64)),
+            string(rows, |tx| Some(hex::encode(tx.prev_trans_hash))),
+            int64(rows, |tx| Some(tx.prev_trans_lt as i64)),
+            int64(rows, |tx| Some(tx.now as i64)),
+            string(rows, |tx| {
+                tx.in_msg.as_ref().map(|msg| match msg {
+                    TxMessageInfo::Internal { .. } => "internal".to_string(),
+                    TxMessageInfo::ExternalIn { .. } => "external_in".to_string(),
+                    TxMessageInfo::ExternalOut { .. } => "external_out".to_string(),
+                })
+            }),
+            string(rows, |tx| match &tx.in_msg {
+                Some(TxMessageInfo::Internal { src, .. })
+                | Some(TxMessageInfo::ExternalOut { src }) => Some(src.to_string()),
+                _ => None,
+            }),
+            string(rows, |tx| match &tx.in_msg {
+                Some(TxMessageInfo::Internal { value, .. }) => Some(value.to_string()),
+                _ => None,
+            }),
+            int32(rows, |tx| Some(tx.out_msgs.len() as i32)),
+            string(rows, |tx| Some(tx.total_fees.to_string())),
+            boolean(rows, |tx| compute(tx).map(|c| c.0)),
+            int32(rows, |tx| compute(tx).map(|c| c.1)),
+            int64(rows, |tx| compute(tx).and_then(|c| c.2)),
+            int32(rows, |tx| tx.action_phase.as_ref().map(|a| a.result_code)),
+            boolean(rows, |tx| Some(tx.aborted)),
+        ]
+    }
+}
+
+impl ParquetRow for LedgerRow {
+    fn schema() -> &'static str {
+        PARQUET_LEDGER_SCHEMA
+    }
+
+    fn columns(rows: &[Self]) -> Vec<ParquetColumn> {
+        vec![
+            int64(rows, |r| Some(r.timestamp as i64)),
+            string(rows, |r| Some(r.from.to_string())),
+            string(rows, |r| Some(r.to.to_string())),
+            string(rows, |r| Some(r.asset.to_string())),
+            string(rows, |r| Some(r.amount.to_string())),
+            string(rows, |r| Some(r.fee.to_string())),
+            string(rows, |r| Some(hex::encode(r.tx_hash))),
+        ]
+    }
+}
+
+pub type ParquetBlockWriter<W> = ParquetWriter<W, BlockHeader>;
+pub type ParquetTxWriter<W> = ParquetWriter<W, ParsedTx>;
+pub type ParquetLedgerWriter<W> = ParquetWriter<W, LedgerRow>;
+
+/// Writes rows into a snappy-compressed parquet file, a row group per `row_group_size` rows.
+///
+/// The file is readable only after `into_inner`, which writes the footer.
+pub struct ParquetWriter<W: Write + Send, R: ParquetRow> {
+    writer: SerializedFileWriter<W>,
+    rows: Vec<R>,
+    row_group_size: usize,
+}
+
+impl<W: Write + Send, R: ParquetRow> ParquetWriter<W, R> {
+    pub const DEFAULT_ROW_GROUP_SIZE: usize = 100_000;
+
+    pub fn new(writer: W) -> Result<ParquetWriter<W, R>, ExportError> {
+        let schema = Arc::new(parse_message_type(R::schema())?);
+        let props = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        Ok(ParquetWriter {
+            writer: SerializedFileWriter::new(writer, schema, Arc::new(props))?,
+            rows: vec![],
+            row_group_size: Self::DEFAULT_ROW_GROUP_SIZE,
+        })
+    }
+
+    pub fn with_row_group_size(&mut self, row_group_size: usize) -> &mut Self {
+        self.row_group_size = row_group_size.max(1);
+        self
+    }
+
+    pub fn write_row(&mut self, row: &R) -> Result<(), ExportError> {
+        self.rows.push(row.clone());
+        if self.rows.len() >= self.row_group_size {
+            self.flush_row_group()?;
+        }
+        Ok(())
+    }
+
+    pub fn write_rows<'a, I>(&mut self, rows: I) -> Result<(), ExportError>
+    where
+        I: IntoIterator<Item = &'a R>,
+        R: 'a,
+    {
+        for row in rows {
+            self.write_row(row)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the buffered rows and the footer of the file.
+    pub fn into_inner(mut self) -> Result<W, ExportError> {
+        self.flush_row_group()?;
+        Ok(self.writer.into_inner()?)
+    }
+
+    fn flush_row_group(&mut self) -> Result<(), ExportError> {
+        if self.rows.is_empty() {
+            return Ok(());
+        }
+        let mut columns = R::columns(&self.rows).into_iter();
+        let mut row_group = self.writer.next_row_group()?;
+        while let Some(mut column) = row_group.next_column()? {
+            let values = columns.next().ok_or_else(|| {
+                ExportError::SchemaMismatch("Less columns than schema fields".to_string())
+            })?;
+            write_column(&mut column, values)?;
+            column.close()?;
+        }
+        row_group.close()?;
+        self.rows.clear();
+        Ok(())
+    }
+}
+
+fn write_column(
+    column: &mut SerializedColumnWriter,
+    values: ParquetColumn,
+) -> Result<(), ExportError> {
+    match values {
+        ParquetColumn::Boolean(values) => write_values::<BoolType>(column.typed(), values),
+        ParquetColumn::Int32(values) => write_values::<Int32Type>(column.typed(), values),
+        ParquetColumn::Int64(values) => write_values::<Int64Type>(column.typed(), values),
+        ParquetColumn::String(values) => write_values::<ByteArrayType>(
+            column.typed(),
+            values
+                .into_iter()
+                .map(|v| v.map(|s| ByteArray::from(s.into_bytes())))
+                .collect(),
+        ),
+    }
+}
+
+fn write_values<T: DataType>(
+    writer: &mut ColumnWriterImpl<T>,
+    values: Vec<Option<T::T>>,
+) -> Result<(), ExportError> {
+    let optional = writer.get_descriptor().max_def_level() > 0;
+    if !optional && values.iter().any(Option::is_none) {
+        return Err(ExportError::SchemaMismatch(format!(
+            "Null value in required column {}",
+            writer.get_descriptor().name()
+        )));
+    }
+    let def_levels: Vec<i16> = values.iter().map(|v| v.is_some() as i16).collect();
+    let values: Vec<T::T> = values.into_iter().flatten().collect();
+    writer.write_batch(&values, optional.then_some(def_levels.as_slice()), None)?;
+    Ok(())
+}
+
+fn boolean<R, F: Fn(&R) -> Option<bool>>(rows: &[R], f: F) -> ParquetColumn {
+    ParquetColumn::Boolean(rows.iter().map(f).collect())
+}
+
+fn int32<R, F: Fn(&R) -> Option<i32>>(rows: &[R], f: F) -> ParquetColumn {
+    ParquetColumn::Int32(rows.iter().map(f).collect())
+}
+
+fn int64<R, F: Fn(&R) -> Option<i64>>(rows: &[R], f: F) -> ParquetColumn {
+    ParquetColumn::Int64(rows.iter().map(f).collect())
+}
+
+fn string<R, F: Fn(&R) -> Option<String>>(rows: &[R], f: F) -> ParquetColumn {
+    ParquetColumn::String(rows.iter().map(f).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::RowAccessor;
+
+    use crate::address::TonAddress;
+    use crate::export::{
+        LedgerAsset, LedgerRow, ParquetLedgerWriter, ParquetTxWriter, ToLedgerRows,
+    };
+    use crate::transaction::{ParsedTx, TxComputePhase, TxMessageInfo};
+
+    fn tx(lt: u64) -> ParsedTx {
+        let sender = TonAddress::new(0, &[1; 32]);
+        let wallet = TonAddress::new(0, &[2; 32]);
+        ParsedTx {
+            hash: [lt as u8; 32],
+            account: wallet.hash_part,
+            lt,
+            prev_trans_hash: [0; 32],
+            prev_trans_lt: 0,
+            now: 1_700_000_000,
+            in_msg: Some(TxMessageInfo::Internal {
+                src: sender,
+                dest: wallet,
+                value: BigUint::from(500u32),
+                ihr_fee: BigUint::from(0u32),
+                fwd_fee: BigUint::from(0u32),
+                bounced: false,
+            }),
+            in_msg_hash: None,
+            out_msgs: vec![],
+            total_fees: BigUint::from(20u32),
+            storage_phase: None,
+            credit_phase: None,
+            compute_phase: Some(TxComputePhase::Skipped),
+            action_phase: None,
+            aborted: false,
+        }
+    }
+
+    fn read(path: &std::path::Path) -> anyhow::Result<Vec<parquet::record::Row>> {
+        let reader = SerializedFileReader::new(std::fs::File::open(path)?)?;
+        let rows = reader.get_row_iter(None)?.collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    #[test]
+    fn test_parquet_tx_writer() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join(format!("tonlib-tx-{}.parquet", std::process::id()));
+        let mut exec = tx(2);
+        exec.compute_phase = Some(TxComputePhase::Vm {
+            success: true,
+            gas_fees: BigUint::from(10u32),
+            gas_used: BigUint::from(2_000u32),
+            exit_code: 0,
+        });
+
+        let mut writer = ParquetTxWriter::new(std::fs::File::create(&path)?)?;
+        writer.with_row_group_size(2);
+        writer.write_rows(&[tx(1), exec, tx(3)])?;
+        writer.into_inner()?;
+
+        let rows = read(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].get_long(2)?, 1);
+        assert_eq!(rows[0].get_string(6)?, "internal");
+        assert_eq!(rows[0].get_string(8)?, "500");
+        assert_eq!(rows[0].get_string(10)?, "20");
+        assert!(rows[0].get_bool(11).is_err());
+        assert!(rows[1].get_bool(11)?);
+        assert_eq!(rows[1].get_int(12)?, 0);
+        assert_eq!(rows[1].get_long(13)?, 2_000);
+        assert_eq!(rows[2].get_long(2)?, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parquet_ledger_writer() -> anyhow::Result<()> {
+        let path =
+            std::env::temp_dir().join(format!("tonlib-ledger-{}.parquet", std::process::id()));
+        let rows: Vec<LedgerRow> = tx(1).ledger_rows();
+        let mut writer = ParquetLedgerWriter::new(std::fs::File::create(&path)?)?;
+        writer.write_rows(&rows)?;
+        writer.into_inner()?;
+
+        let read_rows = read(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(read_rows.len(), 1);
+        assert_eq!(read_rows[0].get_long(0)?, 1_700_000_000);
+        assert_eq!(read_rows[0].get_string(3)?, &LedgerAsset::Ton.to_string());
+        assert_eq!(read_rows[0].get_string(4)?, "500");
+        assert_eq!(read_rows[0].get_string(6)?, &"01".repeat(32));
+        Ok(())
+    }
+}
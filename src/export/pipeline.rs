@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use futures::future::try_join_all;
+
+use crate::address::TonAddress;
+use crate::client::{
+    BlockHeader, BlockStream, TonBlockFunctions, TonClientError, TonClientInterface,
+};
+use crate::export::{store_transaction, BocStore, ExportError, LedgerRow, ToLedgerRows};
+use crate::tl::BlockIdExt;
+use crate::transaction::ParsedTx;
+use crate::types::{ErrorContext, WithErrorContext};
+
+/// Transaction of an indexed block with the transfers decoded from it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexedTx {
+    pub address: TonAddress,
+    pub tx: ParsedTx,
+    pub ledger_rows: Vec<LedgerRow>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexedBlock {
+    pub header: BlockHeader,
+    pub transactions: Vec<IndexedTx>,
+}
+
+/// Destination of blocks produced by `IndexerPipeline`.
+#[async_trait]
+pub trait Sink: Send + Sync {
+    /// Stores the blocks finalized by the masterchain block `mc_seqno` and moves the resume
+    /// marker to `mc_seqno`, both or nothing. Blocks may be written again after a restart, so
+    /// writes must be idempotent.
+    async fn write(&self, mc_seqno: u32, blocks: &[IndexedBlock]) -> Result<(), ExportError>;
+
+    /// Returns seqno of the last masterchain block written.
+    async fn resume_marker(&self) -> Result<Option<u32>, ExportError>;
+}
+
+/// Keeps blocks in memory, e.g. for tests or short-lived analysis.
+#[derive(Default)]
+pub struct InMemorySink {
+    blocks: Mutex<HashMap<(i32, i64, u32), IndexedBlock>>,
+    resume_marker: Mutex<Option<u32>>,
+}
+
+impl InMemorySink {
+    pub fn new() -> InMemorySink {
+        InMemorySink::default()
+    }
+
+    /// Returns stored blocks in the order of `BlockHeader::cmp_by_lt`.
+    pub fn blocks(&self) -> Vec<IndexedBlock> {
+        let mut blocks: Vec<_> = self.blocks.lock().unwrap().values().cloned().collect();
+        blocks.sort_by(|a, b| a.header.cmp_by_lt(&b.header));
+        blocks
+    }
+}
+
+#[async_trait]
+impl Sink for InMemorySink {
+    async fn write(&self, mc_seqno: u32, blocks: &[IndexedBlock]) -> Result<(), ExportError> {
+        let mut stored = self.blocks.lock().unwrap();
+        for block in blocks {
+            let key = (
+                block.header.workchain(),
+                block.header.shard(),
+                block.header.seqno(),
+            );
+            stored.insert(key, block.clone());
+        }
+        *self.resume_marker.lock().unwrap() = Some(mc_seqno);
+        Ok(())
+    }
+
+    async fn resume_marker(&self) -> Result<Option<u32>, ExportError> {
+        Ok(*self.resume_marker.lock().unwrap())
+    }
+}
+
+/// Reads sealed blocks with `BlockStream`, decodes their transactions and writes them to the
+/// sink, one masterchain block at a time.
+pub struct IndexerPipeline<C: TonClientInterface + Clone> {
+    client: C,
+    sink: Arc<dyn Sink>,
+    stream: BlockStream<C>,
+    boc_store: Option<Arc<dyn BocStore>>,
+}
+
+impl<C: TonClientInterface + Clone> IndexerPipeline<C> {
+    /// Creates pipeline starting after the resume marker of the sink, or from the masterchain
+    /// block `from_seqno` if the sink is empty.
+    pub async fn new(
+        client: &C,
+        sink: Arc<dyn Sink>,
+        from_seqno: i32,
+    ) -> Result<IndexerPipeline<C>, ExportError> {
+        let start_seqno = match sink.resume_marker().await? {
+            Some(marker) => marker as i32 + 1,
+            None => from_seqno,
+        };
+        log::info!("[IndexerPipeline] Starting from masterchain block {start_seqno}");
+        Ok(IndexerPipeline {
+            client: client.clone(),
+            stream: BlockStream::new(client, start_seqno),
+            sink,
+            boc_store: None,
+        })
+    }
+
+    /// Sets client used to backfill blocks missed during downtime, see
+    /// `BlockStream::with_archive_client`.
+    pub fn with_archive_client(&mut self, archive_client: &C) -> &mut Self {
+        self.stream.with_archive_client(archive_client);
+        self
+    }
+
+    /// Stores raw transactions of indexed blocks, so that they can be re-processed without
+    /// downloading them again.
+    pub fn with_boc_store(&mut self, boc_store: Arc<dyn BocStore>) -> &mut Self {
+        self.boc_store = Some(boc_store);
+        self
+    }
+
+    /// Indexes the next masterchain block and returns its seqno. Waits for the block if it is
+    /// not generated yet.
+    pub async fn index_next(&mut self) -> Result<u32, ExportError> {
+        let item = self.stream.next().await?;
+        let ids: Vec<_> = item.shards.iter().chain([&item.master_shard]).collect();
+        let blocks = try_join_all(ids.into_iter().map(|id| self.load_block(id))).await?;
+        let mc_seqno = item.master_shard.seqno as u32;
+        self.sink.write(mc_seqno, &blocks).await?;
+        Ok(mc_seqno)
+    }
+
+    /// Indexes blocks until an error occurs.
+    pub async fn run(&mut self) -> Result<(), ExportError> {
+        loop {
+            let mc_seqno = self.index_next().await?;
+            log::debug!("[IndexerPipeline] Indexed masterchain block {mc_seqno}");
+        }
+    }
+
+    async fn load_block(&self, id: &BlockIdExt) -> Result<IndexedBlock, ExportError> {
+        let header = self.client.get_typed_block_header(id).await?;
+        let raw_txs = self.client.get_shard_transactions(id).await?;
+        let mut transactions = Vec::with_capacity(raw_txs.len());
+        for raw_tx in raw_txs.iter() {
+            if let Some(store) = &self.boc_store {
+                store_transaction(store.as_ref(), raw_tx).await;
+            }
+            let address = raw_tx.address.account_address.parse::<TonAddress>()?;
+            let tx = ParsedTx::try_from(raw_tx)
+                .map_err(TonClientError::from)
+                .with_context(|| ErrorContext {
+                    address: Some(address.clone()),
+                    block_id: Some(id.clone()),
+                    ..Default::default()
+                })?;
+            let ledger_rows = tx.ledger_rows();
+            transactions.push(IndexedTx {
+                address,
+                tx,
+                ledger_rows,
+            });
+        }
+        Ok(IndexedBlock {
+            header,
+            transactions,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::BlockHeader;
+    use crate::export::{InMemorySink, IndexedBlock, Sink};
+    use crate::tl::{BlockIdExt, BlocksHeader};
+
+    fn block(shard: i64, seqno: i32, start_lt: i64) -> IndexedBlock {
+        let header = BlocksHeader {
+            id: BlockIdExt {
+                workchain: 0,
+                shard,
+                seqno,
+                root_hash: "".to_string(),
+                file_hash: "".to_string(),
+            },
+            global_id: -239,
+            version: 0,
+            flags: 1,
+            after_merge: false,
+            after_split: false,
+            before_split: false,
+            want_merge: false,
+            want_split: false,
+            validator_list_hash_short: -1,
+            catchain_seqno: 1,
+            min_ref_mc_seqno: 1,
+            is_key_block: false,
+            prev_key_block_seqno: 1,
+            start_lt,
+            end_lt: start_lt + 10,
+            gen_utime: 1_700_000_000,
+            vert_seqno: None,
+            prev_blocks: None,
+        };
+        IndexedBlock {
+            header: BlockHeader::from(header),
+            transactions: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_sink() -> anyhow::Result<()> {
+        let sink = InMemorySink::new();
+        assert_eq!(sink.resume_marker().await?, None);
+        sink.write(10, &[block(i64::MIN, 5, 200), block(i64::MIN, 4, 100)])
+            .await?;
+        // repeated write after restart replaces the blocks
+        sink.write(10, &[block(i64::MIN, 5, 200)]).await?;
+        sink.write(11, &[block(i64::MIN, 6, 300)]).await?;
+        assert_eq!(sink.resume_marker().await?, Some(11));
+        let seqnos: Vec<_> = sink.blocks().iter().map(|b| b.header.seqno()).collect();
+        assert_eq!(seqnos, vec![4, 5, 6]);
+        Ok(())
+    }
+}
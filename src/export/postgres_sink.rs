@@ -0,0 +1,290 @@
+use async_trait::async_trait;
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::{Postgres, Row, Transaction};
+
+use crate::export::{ExportError, IndexedBlock, IndexedTx, Sink};
+use crate::transaction::{TxComputePhase, TxMessageInfo};
+
+/// Migrations of the schema, the version of the schema is the number of applied migrations.
+///
+/// Hashes are hex, amounts are `NUMERIC` in minimal units, addresses are user-friendly.
+/// `messages.direction` is `in` or `out`, `actions` are the ledger rows of the transaction.
+const MIGRATIONS: &[&str] = &["
+CREATE TABLE blocks (
+    workchain INTEGER NOT NULL,
+    shard BIGINT NOT NULL,
+    seqno BIGINT NOT NULL,
+    root_hash TEXT NOT NULL,
+    file_hash TEXT NOT NULL,
+    mc_seqno BIGINT NOT NULL,
+    gen_utime BIGINT NOT NULL,
+    start_lt BIGINT NOT NULL,
+    end_lt BIGINT NOT NULL,
+    is_key_block BOOLEAN NOT NULL,
+    PRIMARY KEY (workchain, shard, seqno)
+);
+CREATE TABLE transactions (
+    hash TEXT PRIMARY KEY,
+    address TEXT NOT NULL,
+    lt BIGINT NOT NULL,
+    now BIGINT NOT NULL,
+    workchain INTEGER NOT NULL,
+    shard BIGINT NOT NULL,
+    block_seqno BIGINT NOT NULL,
+    total_fees NUMERIC NOT NULL,
+    compute_exit_code INTEGER,
+    action_result_code INTEGER,
+    aborted BOOLEAN NOT NULL
+);
+CREATE INDEX transactions_address_lt ON transactions (address, lt);
+CREATE TABLE messages (
+    tx_hash TEXT NOT NULL,
+    direction TEXT NOT NULL,
+    idx INTEGER NOT NULL,
+    src TEXT,
+    dest TEXT,
+    value NUMERIC,
+    fwd_fee NUMERIC,
+    bounced BOOLEAN,
+    PRIMARY KEY (tx_hash, direction, idx)
+);
+CREATE TABLE actions (
+    tx_hash TEXT NOT NULL,
+    idx INTEGER NOT NULL,
+    timestamp BIGINT NOT NULL,
+    from_address TEXT NOT NULL,
+    to_address TEXT NOT NULL,
+    asset TEXT NOT NULL,
+    amount NUMERIC NOT NULL,
+    fee NUMERIC NOT NULL,
+    PRIMARY KEY (tx_hash, idx)
+);
+CREATE TABLE resume_markers (
+    name TEXT PRIMARY KEY,
+    mc_seqno BIGINT NOT NULL
+);
+"];
+
+/// Stores indexed blocks in PostgreSQL. Rows are upserted, so blocks written again after a
+/// restart replace the stored ones.
+///
+/// Several pipelines may share the database, each with its own resume marker `name`.
+pub struct PostgresSink {
+    pool: PgPool,
+    name: String,
+}
+
+impl PostgresSink {
+    pub fn new(pool: PgPool, name: &str) -> PostgresSink {
+        PostgresSink {
+            pool,
+            name: name.to_string(),
+        }
+    }
+
+    /// Connects to the database and applies missing migrations.
+    pub async fn connect(url: &str, name: &str) -> Result<PostgresSink, ExportError> {
+        let pool = PgPoolOptions::new().connect(url).await?;
+        let sink = PostgresSink::new(pool, name);
+        sink.migrate().await?;
+        Ok(sink)
+    }
+
+    pub fn schema_version() -> usize {
+        MIGRATIONS.len()
+    }
+
+    /// Applies migrations missing in the database. Migrations are serialized by an advisory
+    /// lock, so concurrent pipelines may call it on start.
+    pub async fn migrate(&self) -> Result<(), ExportError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('tonlib_schema_version'))")
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("CREATE TABLE IF NOT EXISTS tonlib_schema_version (version INTEGER NOT NULL)")
+            .execute(&mut *tx)
+            .await?;
+        let version: Option<i32> = sqlx::query("SELECT max(version) FROM tonlib_schema_version")
+            .fetch_one(&mut *tx)
+            .await?
+            .try_get(0)?;
+        let version = version.unwrap_or(0) as usize;
+        if version > MIGRATIONS.len() {
+            return Err(ExportError::SchemaMismatch(format!(
+                "Database schema version {} is newer than {}",
+                version,
+                MIGRATIONS.len()
+            )));
+        }
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            log::info!("[PostgresSink] Applying migration {}", i + 1);
+            sqlx::raw_sql(migration).execute(&mut *tx).await?;
+            sqlx::query("INSERT INTO tonlib_schema_version (version) VALUES ($1)")
+                .bind(i as i32 + 1)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn write_block(
+        tx: &mut Transaction<'_, Postgres>,
+        mc_seqno: u32,
+        block: &IndexedBlock,
+    ) -> Result<(), ExportError> {
+        let header = &block.header;
+        sqlx::query(
+            "INSERT INTO blocks (workchain, shard, seqno, root_hash, file_hash, mc_seqno, \
+             gen_utime, start_lt, end_lt, is_key_block) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) \
+             ON CONFLICT (workchain, shard, seqno) DO UPDATE SET root_hash = $4, \
+             file_hash = $5, mc_seqno = $6, gen_utime = $7, start_lt = $8, end_lt = $9, \
+             is_key_block = $10",
+        )
+        .bind(header.workchain())
+        .bind(header.shard())
+        .bind(header.seqno() as i64)
+        .bind(&header.id.root_hash)
+        .bind(&header.id.file_hash)
+        .bind(mc_seqno as i64)
+        .bind(header.gen_utime as i64)
+        .bind(header.start_lt as i64)
+        .bind(header.end_lt as i64)
+        .bind(header.is_key_block)
+        .execute(&mut **tx)
+        .await?;
+        for indexed in block.transactions.iter() {
+            Self::write_transaction(tx, block, indexed).await?;
+        }
+        Ok(())
+    }
+
+    async fn write_transaction(
+        tx: &mut Transaction<'_, Postgres>,
+        block: &IndexedBlock,
+        indexed: &IndexedTx,
+    ) -> Result<(), ExportError> {
+        let parsed = &indexed.tx;
+        let hash = hex::encode(parsed.hash);
+        let compute_exit_code = match &parsed.compute_phase {
+            Some(TxComputePhase::Vm { exit_code, .. }) => Some(*exit_code),
+            _ => None,
+        };
+        sqlx::query(
+            "INSERT INTO transactions (hash, address, lt, now, workchain, shard, block_seqno, \
+             total_fees, compute_exit_code, action_result_code, aborted) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8::numeric, $9, $10, $11) \
+             ON CONFLICT (hash) DO NOTHING",
+        )
+        .bind(&hash)
+        .bind(indexed.address.to_string())
+        .bind(parsed.lt as i64)
+        .bind(parsed.now as i64)
+        .bind(block.header.workchain())
+        .bind(block.header.shard())
+        .bind(block.header.seqno() as i64)
+        .bind(parsed.total_fees.to_string())
+        .bind(compute_exit_code)
+        .bind(parsed.action_phase.as_ref().map(|a| a.result_code))
+        .bind(parsed.aborted)
+        .execute(&mut **tx)
+        .await?;
+
+        let messages = parsed.in_msg.iter().map(|msg| ("in", 0, msg)).chain(
+            parsed
+                .out_msgs
+                .iter()
+                .enumerate()
+                .map(|(i, msg)| ("out", i, msg)),
+        );
+        for (direction, idx, msg) in messages {
+            let (src, dest, value, fwd_fee, bounced) = match msg {
+                TxMessageInfo::Internal {
+                    src,
+                    dest,
+                    value,
+                    fwd_fee,
+                    bounced,
+                    ..
+                } => (
+                    Some(src.to_string()),
+                    Some(dest.to_string()),
+                    Some(value.to_string()),
+                    Some(fwd_fee.to_string()),
+                    Some(*bounced),
+                ),
+                TxMessageInfo::ExternalIn { dest, .. } => {
+                    (None, Some(dest.to_string()), None, None, None)
+                }
+                TxMessageInfo::ExternalOut { src } => {
+                    (Some(src.to_string()), None, None, None, None)
+                }
+            };
+            sqlx::query(
+                "INSERT INTO messages (tx_hash, direction, idx, src, dest, value, fwd_fee, \
+                 bounced) VALUES ($1, $2, $3, $4, $5, $6::numeric, $7::numeric, $8) \
+                 ON CONFLICT (tx_hash, direction, idx) DO NOTHING",
+            )
+            .bind(&hash)
+            .bind(direction)
+            .bind(idx as i32)
+            .bind(src)
+            .bind(dest)
+            .bind(value)
+            .bind(fwd_fee)
+            .bind(bounced)
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        for (idx, row) in indexed.ledger_rows.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO actions (tx_hash, idx, timestamp, from_address, to_address, asset, \
+                 amount, fee) VALUES ($1, $2, $3, $4, $5, $6, $7::numeric, $8::numeric) \
+                 ON CONFLICT (tx_hash, idx) DO UPDATE SET timestamp = $3, from_address = $4, \
+                 to_address = $5, asset = $6, amount = $7::numeric, fee = $8::numeric",
+            )
+            .bind(&hash)
+            .bind(idx as i32)
+            .bind(row.timestamp as i64)
+            .bind(row.from.to_string())
+            .bind(row.to.to_string())
+            .bind(row.asset.to_string())
+            .bind(row.amount.to_string())
+            .bind(row.fee.to_string())
+            .execute(&mut **tx)
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Sink for PostgresSink {
+    async fn write(&self, mc_seqno: u32, blocks: &[IndexedBlock]) -> Result<(), ExportError> {
+        let mut tx = self.pool.begin().await?;
+        for block in blocks {
+            Self::write_block(&mut tx, mc_seqno, block).await?;
+        }
+        sqlx::query(
+            "INSERT INTO resume_markers (name, mc_seqno) VALUES ($1, $2) \
+             ON CONFLICT (name) DO UPDATE SET mc_seqno = $2",
+        )
+        .bind(&self.name)
+        .bind(mc_seqno as i64)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn resume_marker(&self) -> Result<Option<u32>, ExportError> {
+        let marker: Option<i64> =
+            sqlx::query_scalar("SELECT mc_seqno FROM resume_markers WHERE name = $1")
+                .bind(&self.name)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(marker.map(|seqno| seqno as u32))
+    }
+}
@@ -0,0 +1,102 @@
+use std::ops::RangeInclusive;
+
+use crate::address::TonAddress;
+use crate::export::IndexedTx;
+
+/// Number of leading bits of the account id, which the lanes are assigned by.
+const SHARD_ROUTER_PREFIX_BITS: u32 = 16;
+
+/// Assigns transactions to consumer lanes by shard prefix of the account, e.g. to process
+/// blocks with several workers.
+///
+/// Every lane owns a contiguous range of account id prefixes, so all transactions of an
+/// account go to the same lane, and a shard maps to the lanes owning its range. Shard splits
+/// and merges do not change the assignment, since it does not depend on the current shards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShardRouter {
+    lanes: usize,
+}
+
+impl ShardRouter {
+    /// Creates router to `lanes` lanes, at least one.
+    pub fn new(lanes: usize) -> ShardRouter {
+        ShardRouter {
+            lanes: lanes.clamp(1, 1 << SHARD_ROUTER_PREFIX_BITS),
+        }
+    }
+
+    pub fn lanes(&self) -> usize {
+        self.lanes
+    }
+
+    /// Returns lane of the account.
+    pub fn lane_of(&self, address: &TonAddress) -> usize {
+        let prefix = u16::from_be_bytes([address.hash_part[0], address.hash_part[1]]);
+        self.lane_of_prefix(prefix as u64)
+    }
+
+    /// Returns lanes of the accounts of the shard, given as in `BlockIdExt::shard`.
+    pub fn lanes_of_shard(&self, shard: i64) -> RangeInclusive<usize> {
+        let shard = shard as u64;
+        let low_bit = shard & shard.wrapping_neg();
+        let first = (shard - low_bit) >> (64 - SHARD_ROUTER_PREFIX_BITS);
+        let last = (shard - 1 + low_bit) >> (64 - SHARD_ROUTER_PREFIX_BITS);
+        self.lane_of_prefix(first)..=self.lane_of_prefix(last)
+    }
+
+    /// Splits transactions into lanes keeping their order.
+    pub fn route(&self, txs: Vec<IndexedTx>) -> Vec<Vec<IndexedTx>> {
+        let mut lanes = vec![vec![]; self.lanes];
+        for tx in txs {
+            lanes[self.lane_of(&tx.address)].push(tx);
+        }
+        lanes
+    }
+
+    fn lane_of_prefix(&self, prefix: u64) -> usize {
+        ((prefix * self.lanes as u64) >> SHARD_ROUTER_PREFIX_BITS) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::address::TonAddress;
+    use crate::client::shard_children;
+    use crate::export::ShardRouter;
+
+    fn address(first_byte: u8) -> TonAddress {
+        let mut hash_part = [0xff; 32];
+        hash_part[0] = first_byte;
+        TonAddress::new(0, &hash_part)
+    }
+
+    #[test]
+    fn test_shard_router_lanes() {
+        let router = ShardRouter::new(4);
+        let lanes: Vec<_> = [0x00, 0x3f, 0x40, 0x80, 0xc0, 0xff]
+            .into_iter()
+            .map(|b| router.lane_of(&address(b)))
+            .collect();
+        assert_eq!(lanes, vec![0, 0, 1, 2, 3, 3]);
+        assert_eq!(ShardRouter::new(0).lanes(), 1);
+        assert_eq!(ShardRouter::new(1).lane_of(&address(0xff)), 0);
+    }
+
+    #[test]
+    fn test_shard_router_stable_across_splits() {
+        let router = ShardRouter::new(3);
+        let root = i64::MIN;
+        assert_eq!(router.lanes_of_shard(root), 0..=2);
+        let [left, right] = shard_children(root).unwrap();
+        assert_eq!(router.lanes_of_shard(left), 0..=1);
+        assert_eq!(router.lanes_of_shard(right), 1..=2);
+
+        // accounts stay in their lane, which belongs to the lanes of their shard
+        for b in [0x00, 0x55, 0x7f, 0x80, 0xaa, 0xff] {
+            let address = address(b);
+            let lane = router.lane_of(&address);
+            let shard = if b < 0x80 { left } else { right };
+            assert!(router.lanes_of_shard(shard).contains(&lane));
+        }
+    }
+}
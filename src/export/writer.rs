@@ -0,0 +1,143 @@
+use std::io::Write;
+
+use crate::export::{ExportError, LedgerRow};
+
+const CSV_HEADER: &str = "timestamp,from,to,asset,amount,fee,tx_hash";
+
+/// Writes ledger rows as CSV, emitting the header before the first row.
+pub struct CsvLedgerWriter<W: Write> {
+    writer: W,
+    header_written: bool,
+}
+
+impl<W: Write> CsvLedgerWriter<W> {
+    pub fn new(writer: W) -> CsvLedgerWriter<W> {
+        CsvLedgerWriter {
+            writer,
+            header_written: false,
+        }
+    }
+
+    pub fn write_row(&mut self, row: &LedgerRow) -> Result<(), ExportError> {
+        if !self.header_written {
+            writeln!(self.writer, "{}", CSV_HEADER)?;
+            self.header_written = true;
+        }
+        writeln!(
+            self.writer,
+            "{},{},{},{},{},{},{}",
+            row.timestamp,
+            row.from,
+            row.to,
+            row.asset,
+            row.amount,
+            row.fee,
+            hex::encode(row.tx_hash)
+        )?;
+        Ok(())
+    }
+
+    pub fn write_rows<'a, I>(&mut self, rows: I) -> Result<(), ExportError>
+    where
+        I: IntoIterator<Item = &'a LedgerRow>,
+    {
+        for row in rows {
+            self.write_row(row)?;
+        }
+        Ok(())
+    }
+
+    pub fn into_inner(mut self) -> Result<W, ExportError> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Writes ledger rows as JSON lines, one object per row.
+pub struct JsonLedgerWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> JsonLedgerWriter<W> {
+    pub fn new(writer: W) -> JsonLedgerWriter<W> {
+        JsonLedgerWriter { writer }
+    }
+
+    pub fn write_row(&mut self, row: &LedgerRow) -> Result<(), ExportError> {
+        serde_json::to_writer(&mut self.writer, row)?;
+        writeln!(self.writer)?;
+        Ok(())
+    }
+
+    pub fn write_rows<'a, I>(&mut self, rows: I) -> Result<(), ExportError>
+    where
+        I: IntoIterator<Item = &'a LedgerRow>,
+    {
+        for row in rows {
+            self.write_row(row)?;
+        }
+        Ok(())
+    }
+
+    pub fn into_inner(mut self) -> Result<W, ExportError> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use crate::address::TonAddress;
+    use crate::export::{CsvLedgerWriter, JsonLedgerWriter, LedgerAsset, LedgerRow};
+
+    fn row() -> LedgerRow {
+        LedgerRow {
+            timestamp: 1_700_000_000,
+            from: TonAddress::NULL,
+            to: "EQDk2VTvn04SUKJrW7rXahzdF8_Qi6utb0wj43InCu9vdjrR"
+                .parse()
+                .unwrap(),
+            asset: LedgerAsset::Ton,
+            amount: BigUint::from(1_000_000_000u64),
+            fee: BigUint::from(1_500u64),
+            tx_hash: [0xab; 32],
+        }
+    }
+
+    #[test]
+    fn test_csv_ledger_writer() -> anyhow::Result<()> {
+        let mut writer = CsvLedgerWriter::new(vec![]);
+        writer.write_rows(&[row(), row()])?;
+        let csv = String::from_utf8(writer.into_inner()?)?;
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "timestamp,from,to,asset,amount,fee,tx_hash");
+        assert_eq!(
+            lines[1],
+            format!(
+                "1700000000,{},EQDk2VTvn04SUKJrW7rXahzdF8_Qi6utb0wj43InCu9vdjrR,TON,1000000000,1500,{}",
+                TonAddress::NULL,
+                "ab".repeat(32)
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_ledger_writer() -> anyhow::Result<()> {
+        let mut writer = JsonLedgerWriter::new(vec![]);
+        writer.write_row(&row())?;
+        let json = String::from_utf8(writer.into_inner()?)?;
+        let value: serde_json::Value = serde_json::from_str(json.trim_end())?;
+        assert_eq!(value["amount"], "1000000000");
+        assert_eq!(value["fee"], "1500");
+        assert_eq!(value["asset"], "TON");
+        assert_eq!(
+            value["to"],
+            "EQDk2VTvn04SUKJrW7rXahzdF8_Qi6utb0wj43InCu9vdjrR"
+        );
+        Ok(())
+    }
+}
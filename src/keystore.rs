@@ -0,0 +1,320 @@
+mod error;
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD;
+use base64_serde::base64_serde_type;
+use chacha20poly1305::aead::{Aead, Payload};
+use chacha20poly1305::{KeyInit, XChaCha20Poly1305, XNonce};
+pub use error::*;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::mnemonic::KeyPair;
+
+base64_serde_type!(Base64Standard, STANDARD);
+
+pub const KEY_STORE_VERSION: u32 = 1;
+
+const KDF_ARGON2ID: &str = "argon2id";
+const CIPHER_XCHACHA20_POLY1305: &str = "xchacha20-poly1305";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const KEY_LEN: usize = 32;
+
+/// Upper bounds of KDF parameters accepted from a key store, so a crafted file can't make
+/// decryption exhaust memory or CPU.
+pub const MAX_KDF_PARAMS: KdfParams = KdfParams {
+    m_cost: 1024 * 1024,
+    t_cost: 64,
+    p_cost: 16,
+};
+
+/// Argon2id parameters used to derive the encryption key from the password.
+///
+/// Parameters above `MAX_KDF_PARAMS` are rejected.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KdfParams {
+    /// Memory cost in KiB.
+    pub m_cost: u32,
+    /// Number of iterations.
+    pub t_cost: u32,
+    /// Degree of parallelism.
+    pub p_cost: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        KdfParams {
+            m_cost: Params::DEFAULT_M_COST,
+            t_cost: Params::DEFAULT_T_COST,
+            p_cost: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+/// Key pair encrypted at rest: the secret key is encrypted by XChaCha20-Poly1305 with a key
+/// derived from the password by Argon2id. Public key is stored in plain text and authenticated
+/// as associated data.
+///
+/// Serialized as JSON, so it can be kept in a file or in a config value.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EncryptedKeyStore {
+    pub version: u32,
+    pub kdf: String,
+    pub kdf_params: KdfParams,
+    #[serde(with = "Base64Standard")]
+    pub salt: Vec<u8>,
+    pub cipher: String,
+    #[serde(with = "Base64Standard")]
+    pub nonce: Vec<u8>,
+    #[serde(with = "Base64Standard")]
+    pub public_key: Vec<u8>,
+    #[serde(with = "Base64Standard")]
+    pub ciphertext: Vec<u8>,
+}
+
+impl EncryptedKeyStore {
+    pub fn encrypt(key_pair: &KeyPair, password: &str) -> Result<EncryptedKeyStore, KeyStoreError> {
+        Self::encrypt_with_params(key_pair, password, KdfParams::default())
+    }
+
+    pub fn encrypt_with_params(
+        key_pair: &KeyPair,
+        password: &str,
+        kdf_params: KdfParams,
+    ) -> Result<EncryptedKeyStore, KeyStoreError> {
+        let mut rng = rand::thread_rng();
+        let mut salt = vec![0; SALT_LEN];
+        rng.fill_bytes(&mut salt);
+        let mut nonce = vec![0; NONCE_LEN];
+        rng.fill_bytes(&mut nonce);
+
+        let cipher = new_cipher(password, &salt, &kdf_params)?;
+        let ciphertext = cipher
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &key_pair.secret_key,
+                    aad: &key_pair.public_key,
+                },
+            )
+            .map_err(|_| KeyStoreError::EncryptionFailed)?;
+        Ok(EncryptedKeyStore {
+            version: KEY_STORE_VERSION,
+            kdf: KDF_ARGON2ID.to_string(),
+            kdf_params,
+            salt,
+            cipher: CIPHER_XCHACHA20_POLY1305.to_string(),
+            nonce,
+            public_key: key_pair.public_key.clone(),
+            ciphertext,
+        })
+    }
+
+    pub fn decrypt(&self, password: &str) -> Result<KeyPair, KeyStoreError> {
+        if self.version != KEY_STORE_VERSION
+            || self.kdf != KDF_ARGON2ID
+            || self.cipher != CIPHER_XCHACHA20_POLY1305
+        {
+            return Err(KeyStoreError::UnsupportedFormat {
+                version: self.version,
+                kdf: self.kdf.clone(),
+                cipher: self.cipher.clone(),
+            });
+        }
+        if self.salt.len() != SALT_LEN {
+            return Err(KeyStoreError::InvalidKeyStore(format!(
+                "Invalid salt length: {}",
+                self.salt.len()
+            )));
+        }
+        if self.nonce.len() != NONCE_LEN {
+            return Err(KeyStoreError::InvalidKeyStore(format!(
+                "Invalid nonce length: {}",
+                self.nonce.len()
+            )));
+        }
+        let cipher = new_cipher(password, &self.salt, &self.kdf_params)?;
+        let mut secret_key = Zeroizing::new(
+            cipher
+                .decrypt(
+                    XNonce::from_slice(&self.nonce),
+                    Payload {
+                        msg: &self.ciphertext,
+                        aad: &self.public_key,
+                    },
+                )
+                .map_err(|_| KeyStoreError::DecryptionFailed)?,
+        );
+        // nacl secret key is seed followed by public key
+        if secret_key.len() != 64 || secret_key[32..] != self.public_key[..] {
+            return Err(KeyStoreError::InvalidKeyStore(
+                "Secret key doesn't match public key".to_string(),
+            ));
+        }
+        Ok(KeyPair {
+            public_key: self.public_key.clone(),
+            secret_key: std::mem::take(&mut *secret_key),
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String, KeyStoreError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<EncryptedKeyStore, KeyStoreError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Saves key store to the file, readable and writable by the owner only.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), KeyStoreError> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+            options.mode(0o600);
+            let file = options.open(path)?;
+            // mode is applied to new files only
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+            write_file(file, self.to_json()?.as_bytes())
+        }
+        #[cfg(not(unix))]
+        write_file(options.open(path)?, self.to_json()?.as_bytes())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<EncryptedKeyStore, KeyStoreError> {
+        let json = fs::read_to_string(path)?;
+        Self::from_json(&json)
+    }
+}
+
+/// Loads key store from the file and decrypts the key pair, which can be used as `Signer`.
+pub fn load_key_pair<P: AsRef<Path>>(path: P, password: &str) -> Result<KeyPair, KeyStoreError> {
+    EncryptedKeyStore::load(path)?.decrypt(password)
+}
+
+/// Encrypts the key pair with the password and saves it to the file.
+pub fn save_key_pair<P: AsRef<Path>>(
+    path: P,
+    key_pair: &KeyPair,
+    password: &str,
+) -> Result<(), KeyStoreError> {
+    EncryptedKeyStore::encrypt(key_pair, password)?.save(path)
+}
+
+fn write_file(mut file: fs::File, data: &[u8]) -> Result<(), KeyStoreError> {
+    file.write_all(data)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+fn new_cipher(
+    password: &str,
+    salt: &[u8],
+    kdf_params: &KdfParams,
+) -> Result<XChaCha20Poly1305, KeyStoreError> {
+    if kdf_params.m_cost > MAX_KDF_PARAMS.m_cost
+        || kdf_params.t_cost > MAX_KDF_PARAMS.t_cost
+        || kdf_params.p_cost > MAX_KDF_PARAMS.p_cost
+    {
+        return Err(KeyStoreError::KdfError(format!(
+            "KDF params {:?} exceed {:?}",
+            kdf_params, MAX_KDF_PARAMS
+        )));
+    }
+    let params = Params::new(
+        kdf_params.m_cost,
+        kdf_params.t_cost,
+        kdf_params.p_cost,
+        Some(KEY_LEN),
+    )
+    .map_err(|e| KeyStoreError::KdfError(e.to_string()))?;
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), salt, key.as_mut())
+        .map_err(|e| KeyStoreError::KdfError(e.to_string()))?;
+    XChaCha20Poly1305::new_from_slice(key.as_ref())
+        .map_err(|e| KeyStoreError::KdfError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::keystore::{EncryptedKeyStore, KdfParams, KeyStoreError};
+    use crate::mnemonic::Mnemonic;
+    use crate::wallet::Signer;
+
+    const FAST_KDF_PARAMS: KdfParams = KdfParams {
+        m_cost: 256,
+        t_cost: 1,
+        p_cost: 1,
+    };
+
+    #[test]
+    fn test_key_store_roundtrip() -> anyhow::Result<()> {
+        let mnemonic_str = "fancy carpet hello mandate penalty trial consider \
+        property top vicious exit rebuild tragic profit urban major total month holiday \
+        sudden rib gather media vicious";
+        let key_pair = Mnemonic::from_str(mnemonic_str, &None)?.to_key_pair()?;
+
+        let key_store =
+            EncryptedKeyStore::encrypt_with_params(&key_pair, "password", FAST_KDF_PARAMS)?;
+        assert!(!key_store
+            .to_json()?
+            .contains(&hex::encode(&key_pair.secret_key)));
+        let key_store = EncryptedKeyStore::from_json(&key_store.to_json()?)?;
+        let decrypted = key_store.decrypt("password")?;
+        assert!(decrypted == key_pair);
+        assert_eq!(decrypted.sign(b"data")?, key_pair.sign(b"data")?);
+
+        assert!(matches!(
+            key_store.decrypt("wrong password"),
+            Err(KeyStoreError::DecryptionFailed)
+        ));
+
+        let mut tampered = key_store.clone();
+        tampered.public_key[0] ^= 1;
+        assert!(matches!(
+            tampered.decrypt("password"),
+            Err(KeyStoreError::DecryptionFailed)
+        ));
+
+        let mut expensive = key_store.clone();
+        expensive.kdf_params.m_cost = u32::MAX;
+        assert!(matches!(
+            expensive.decrypt("password"),
+            Err(KeyStoreError::KdfError(_))
+        ));
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_key_store_file_permissions() -> anyhow::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let key_pair = Mnemonic::from_str(
+            "fancy carpet hello mandate penalty trial consider property top vicious exit \
+            rebuild tragic profit urban major total month holiday sudden rib gather media vicious",
+            &None,
+        )?
+        .to_key_pair()?;
+        let path =
+            std::env::temp_dir().join(format!("tonlib-keystore-{}.json", std::process::id()));
+        std::fs::write(&path, "")?;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644))?;
+        EncryptedKeyStore::encrypt_with_params(&key_pair, "password", FAST_KDF_PARAMS)?
+            .save(&path)?;
+        let mode = std::fs::metadata(&path)?.permissions().mode();
+        let decrypted = EncryptedKeyStore::load(&path)?.decrypt("password");
+        std::fs::remove_file(&path)?;
+        assert_eq!(mode & 0o777, 0o600);
+        assert!(decrypted? == key_pair);
+        Ok(())
+    }
+}
@@ -0,0 +1,31 @@
+use std::io;
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum KeyStoreError {
+    #[error("Unsupported key store (version: {version}, kdf: {kdf}, cipher: {cipher})")]
+    UnsupportedFormat {
+        version: u32,
+        kdf: String,
+        cipher: String,
+    },
+
+    #[error("Invalid key store ({0})")]
+    InvalidKeyStore(String),
+
+    #[error("Key derivation error ({0})")]
+    KdfError(String),
+
+    #[error("Decryption failed: invalid password or corrupted key store")]
+    DecryptionFailed,
+
+    #[error("Encryption failed")]
+    EncryptionFailed,
+
+    #[error("IO error ({0})")]
+    Io(#[from] io::Error),
+
+    #[error("Serialization error ({0})")]
+    SerializationError(#[from] serde_json::Error),
+}
@@ -1,15 +1,28 @@
 extern crate core;
 
 pub mod address;
+#[cfg(feature = "blocking")]
+pub mod blocking;
 pub mod cell;
 pub mod client;
+#[cfg(feature = "compat_tests")]
+pub mod compat;
 pub mod config;
 pub mod contract;
 pub mod emulator;
+pub mod export;
+#[cfg(feature = "keystore")]
+pub mod keystore;
 pub mod message;
 pub mod meta;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod mnemonic;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod testing;
 pub mod tl;
+pub mod transaction;
 pub mod types;
 pub mod wallet;
 
@@ -1,9 +1,13 @@
+pub use elector::*;
 pub use error::*;
+pub use ext_message::*;
 pub use jetton::*;
 pub use transfer::*;
 pub use util::*;
 
+mod elector;
 mod error;
+mod ext_message;
 mod jetton;
 mod transfer;
 mod util;
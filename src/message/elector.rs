@@ -0,0 +1,86 @@
+use crate::cell::{Cell, CellBuilder};
+use crate::message::{InvalidMessage, TonMessageError};
+
+// Constants from elector-code.fc
+// https://github.com/ton-blockchain/ton/blob/master/crypto/smartcont/elector-code.fc
+
+pub const ELECTOR_RECOVER_STAKE: u32 = 0x47657424;
+pub const ELECTOR_RECOVER_STAKE_OK: u32 = 0xf96f7324;
+pub const ELECTOR_RECOVER_STAKE_ERROR: u32 = 0xfffffffe;
+
+/// Creates a body for stake recovery request to the elector:
+///
+/// ```raw
+/// recover_stake#47657424 query_id:uint64 = InternalMsgBody;
+/// ```
+///
+/// The elector responds with `recover_stake_ok#f96f7324 query_id:uint64` carrying the stake,
+/// or with `0xfffffffe query_id:uint64 op:uint32` if there is nothing to recover.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecoverStakeMessage {
+    /// arbitrary request number, returned in the response of the elector.
+    pub query_id: u64,
+}
+
+impl RecoverStakeMessage {
+    pub fn new() -> Self {
+        RecoverStakeMessage { query_id: 0 }
+    }
+
+    pub fn with_query_id(&mut self, query_id: u64) -> &mut Self {
+        self.query_id = query_id;
+        self
+    }
+
+    pub fn build(&self) -> Result<Cell, TonMessageError> {
+        let mut message = CellBuilder::new();
+        message.store_u32(32, ELECTOR_RECOVER_STAKE)?;
+        message.store_u64(64, self.query_id)?;
+        Ok(message.build()?)
+    }
+
+    pub fn parse(cell: &Cell) -> Result<Self, TonMessageError> {
+        let mut parser = cell.parser();
+        let opcode: u32 = parser.load_u32(32)?;
+        let query_id = parser.load_u64(64)?;
+        if opcode != ELECTOR_RECOVER_STAKE {
+            let invalid = InvalidMessage {
+                opcode: Some(opcode),
+                query_id: Some(query_id),
+                message: format!(
+                    "Unexpected opcode.  {0:08x} expected",
+                    ELECTOR_RECOVER_STAKE
+                ),
+            };
+            return Err(TonMessageError::InvalidMessage(invalid));
+        }
+        parser.ensure_empty()?;
+        Ok(RecoverStakeMessage { query_id })
+    }
+}
+
+impl Default for RecoverStakeMessage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::{RecoverStakeMessage, ELECTOR_RECOVER_STAKE};
+
+    #[test]
+    fn test_recover_stake_message() -> anyhow::Result<()> {
+        let cell = RecoverStakeMessage::new()
+            .with_query_id(1_700_000_000)
+            .build()?;
+        assert_eq!(cell.parser().load_u32(32)?, ELECTOR_RECOVER_STAKE);
+        assert_eq!(
+            RecoverStakeMessage::parse(&cell)?,
+            RecoverStakeMessage {
+                query_id: 1_700_000_000
+            }
+        );
+        Ok(())
+    }
+}
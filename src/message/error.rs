@@ -17,6 +17,9 @@ pub enum TonMessageError {
 
     #[error("Invalid message ({0})")]
     InvalidMessage(InvalidMessage),
+
+    #[error("Signer public key doesn't match the wallet public key")]
+    SignerKeyMismatch,
 }
 
 #[derive(Debug)]
@@ -0,0 +1,155 @@
+use std::sync::Arc;
+
+use num_bigint::BigUint;
+
+use crate::address::TonAddress;
+use crate::cell::{ArcCell, BagOfCells, Cell, CellBuilder, CellParser, TonCellError};
+use crate::types::TonHash;
+
+/// Hashes identifying an external inbound message.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExtMessageHashes {
+    pub dest: TonAddress,
+    /// Hash of the message cell, as returned by `raw.sendMessageReturnHash`.
+    pub hash: TonHash,
+    /// Normalized hash (TEP-467), which doesn't depend on the source address, import fee,
+    /// state init and on the body being inline or a reference.
+    pub hash_norm: TonHash,
+    pub body_hash: TonHash,
+}
+
+impl ExtMessageHashes {
+    pub fn parse(message: &Cell) -> Result<ExtMessageHashes, TonCellError> {
+        let mut parser = message.parser();
+        if parser.load_u8(2)? != 0b10 {
+            return Err(TonCellError::CellParserError(
+                "Message is not an external inbound message".to_string(),
+            ));
+        }
+        // src:MsgAddressExt
+        match parser.load_u8(2)? {
+            0b00 => {}
+            0b01 => {
+                let len = parser.load_u16(9)? as usize;
+                parser.skip_bits(len)?;
+            }
+            tp => return Err(TonCellError::InvalidAddressType(tp)),
+        }
+        let dest = parser.load_address()?;
+        let _import_fee = parser.load_coins()?;
+        if parser.load_bit()? {
+            if parser.load_bit()? {
+                parser.next_reference()?;
+            } else {
+                skip_state_init(&mut parser)?;
+            }
+        }
+        let body = if parser.load_bit()? {
+            parser.next_reference()?
+        } else {
+            let bit_len = parser.remaining_bits();
+            let data = parser.load_bits(bit_len)?;
+            let references = (0..parser.remaining_refs())
+                .map(|_| parser.next_reference())
+                .collect::<Result<_, _>>()?;
+            Arc::new(Cell::new(data, bit_len, references, false)?)
+        };
+        let normalized = normalized_message(&dest, &body)?;
+        Ok(ExtMessageHashes {
+            dest,
+            hash: message.cell_hash(),
+            hash_norm: normalized.cell_hash(),
+            body_hash: body.cell_hash(),
+        })
+    }
+
+    pub fn parse_boc(boc: &[u8]) -> Result<ExtMessageHashes, TonCellError> {
+        ExtMessageHashes::parse(BagOfCells::parse(boc)?.single_root()?)
+    }
+}
+
+fn normalized_message(dest: &TonAddress, body: &ArcCell) -> Result<Cell, TonCellError> {
+    CellBuilder::new()
+        .store_u8(2, 0b10)?
+        .store_u8(2, 0b00)?
+        .store_address(dest)?
+        .store_coins(&BigUint::from(0u32))?
+        .store_bit(false)?
+        .store_bit(true)?
+        .store_reference(body)?
+        .build()
+}
+
+fn skip_state_init(parser: &mut CellParser) -> Result<(), TonCellError> {
+    // split_depth:(Maybe (## 5)) special:(Maybe TickTock)
+    if parser.load_bit()? {
+        parser.skip_bits(5)?;
+    }
+    if parser.load_bit()? {
+        parser.skip_bits(2)?;
+    }
+    // code:(Maybe ^Cell) data:(Maybe ^Cell) library:(HashmapE 256 SimpleLib)
+    for _ in 0..3 {
+        if parser.load_bit()? {
+            parser.next_reference()?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use num_bigint::BigUint;
+
+    use crate::address::TonAddress;
+    use crate::cell::{CellBuilder, TonCellError};
+    use crate::message::ExtMessageHashes;
+
+    #[test]
+    fn test_ext_message_hashes() -> anyhow::Result<()> {
+        let dest = TonAddress::new(0, &[6; 32]);
+        let body = CellBuilder::new().store_u32(32, 42)?.build()?;
+        let code = Arc::new(CellBuilder::new().store_u8(8, 1)?.build()?);
+        let inline = CellBuilder::new()
+            .store_u8(2, 0b10)?
+            .store_u8(2, 0b00)?
+            .store_address(&dest)?
+            .store_coins(&BigUint::from(0u32))?
+            .store_bit(false)?
+            .store_bit(false)?
+            .store_u32(32, 42)?
+            .build()?;
+        let with_init = CellBuilder::new()
+            .store_u8(2, 0b10)?
+            .store_u8(2, 0b00)?
+            .store_address(&dest)?
+            .store_coins(&BigUint::from(5u32))?
+            .store_bit(true)?
+            .store_bit(false)?
+            .store_u8(2, 0)?
+            .store_bit(true)?
+            .store_reference(&code)?
+            .store_u8(2, 0)?
+            .store_bit(true)?
+            .store_reference(&Arc::new(body.clone()))?
+            .build()?;
+
+        let inline_hashes = ExtMessageHashes::parse(&inline)?;
+        let init_hashes = ExtMessageHashes::parse(&with_init)?;
+        assert_eq!(inline_hashes.dest, dest);
+        assert_eq!(inline_hashes.hash, inline.cell_hash());
+        assert_ne!(inline_hashes.hash, init_hashes.hash);
+        assert_eq!(inline_hashes.hash_norm, init_hashes.hash_norm);
+        assert_eq!(inline_hashes.body_hash, body.cell_hash());
+        assert_eq!(init_hashes.body_hash, body.cell_hash());
+
+        let internal = CellBuilder::new().store_bit(false)?.build()?;
+        assert!(matches!(
+            ExtMessageHashes::parse(&internal),
+            Err(TonCellError::CellParserError(_))
+        ));
+        Ok(())
+    }
+}
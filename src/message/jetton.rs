@@ -8,17 +8,29 @@
 // crc32('internal_transfer query_id:uint64 amount:VarUInteger 16 from:MsgAddress response_address:MsgAddress forward_ton_amount:VarUInteger 16 forward_payload:Either Cell ^Cell = InternalMsgBody') = 0x978d4519 & 0x7fffffff = 0x178d4519
 // crc32('burn_notification query_id:uint64 amount:VarUInteger 16 sender:MsgAddress response_destination:MsgAddress = InternalMsgBody') = 0x7bdd97de & 0x7fffffff = 0x7bdd97de
 
+// Constants from discoverable jettons standard
+// https://github.com/ton-blockchain/TEPs/blob/master/text/0089-jetton-wallet-discovery.md
+
+// crc32('provide_wallet_address query_id:uint64 owner_address:MsgAddress include_address:Bool = InternalMsgBody') = 0x2c76b973 & 0x7fffffff = 0x2c76b973
+// crc32('take_wallet_address query_id:uint64 wallet_address:MsgAddress owner_address:Maybe ^MsgAddress = InternalMsgBody') = 0xd1735400 | 0x80000000 = 0xd1735400
+
 pub const JETTON_TRANSFER: u32 = 0x0f8a7ea5;
 pub const JETTON_TRANSFER_NOTIFICATION: u32 = 0x7362d09c;
 pub const JETTON_INTERNAL_TRANSFER: u32 = 0x178d4519;
 pub const JETTON_EXCESSES: u32 = 0xd53276db;
 pub const JETTON_BURN: u32 = 0x595f07bc;
 pub const JETTON_BURN_NOTIFICATION: u32 = 0x7bdd97de;
+pub const JETTON_PROVIDE_WALLET_ADDRESS: u32 = 0x2c76b973;
+pub const JETTON_TAKE_WALLET_ADDRESS: u32 = 0xd1735400;
 
 mod burn;
+mod burn_notification;
+mod internal_transfer;
 mod jetton_transfer;
 mod transfer_notification;
 
 pub use burn::*;
+pub use burn_notification::*;
+pub use internal_transfer::*;
 pub use jetton_transfer::*;
 pub use transfer_notification::*;
@@ -0,0 +1,115 @@
+use num_bigint::BigUint;
+
+use super::JETTON_BURN_NOTIFICATION;
+use crate::address::TonAddress;
+use crate::cell::{Cell, CellBuilder};
+use crate::message::{InvalidMessage, TonMessageError};
+
+/// Creates a body for jetton burn notification sent by jetton wallet to the master according to
+/// TL-B schema:
+///
+/// ```raw
+/// burn_notification#7bdd97de query_id:uint64 amount:(VarUInteger 16)
+///                            sender:MsgAddress response_destination:MsgAddress
+///                            = InternalMsgBody;
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct JettonBurnNotificationMessage {
+    /// should be equal with request's query_id.
+    pub query_id: u64,
+    /// amount of burned jettons.
+    pub amount: BigUint,
+    /// owner of the jetton wallet.
+    pub sender: TonAddress,
+    /// address where to send a response with the rest of the incoming message coins.
+    pub response_destination: TonAddress,
+}
+
+impl JettonBurnNotificationMessage {
+    pub fn new(sender: &TonAddress, amount: &BigUint) -> Self {
+        JettonBurnNotificationMessage {
+            query_id: 0,
+            amount: amount.clone(),
+            sender: sender.clone(),
+            response_destination: TonAddress::null(),
+        }
+    }
+
+    pub fn with_query_id(&mut self, query_id: u64) -> &mut Self {
+        self.query_id = query_id;
+        self
+    }
+
+    pub fn with_response_destination(&mut self, response_destination: &TonAddress) -> &mut Self {
+        self.response_destination = response_destination.clone();
+        self
+    }
+
+    pub fn build(&self) -> Result<Cell, TonMessageError> {
+        let mut message = CellBuilder::new();
+        message.store_u32(32, JETTON_BURN_NOTIFICATION)?;
+        message.store_u64(64, self.query_id)?;
+        message.store_coins(&self.amount)?;
+        message.store_address(&self.sender)?;
+        message.store_address(&self.response_destination)?;
+
+        Ok(message.build()?)
+    }
+
+    pub fn parse(cell: &Cell) -> Result<Self, TonMessageError> {
+        let mut parser = cell.parser();
+
+        let opcode: u32 = parser.load_u32(32)?;
+        let query_id = parser.load_u64(64)?;
+        if opcode != JETTON_BURN_NOTIFICATION {
+            let invalid = InvalidMessage {
+                opcode: Some(opcode),
+                query_id: Some(query_id),
+                message: format!(
+                    "Unexpected opcode.  {0:08x} expected",
+                    JETTON_BURN_NOTIFICATION
+                ),
+            };
+            return Err(TonMessageError::InvalidMessage(invalid));
+        }
+        let amount = parser.load_coins()?;
+        let sender = parser.load_address()?;
+        let response_destination = parser.load_address()?;
+        parser.ensure_empty()?;
+
+        let result = JettonBurnNotificationMessage {
+            query_id,
+            amount,
+            sender,
+            response_destination,
+        };
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use num_bigint::BigUint;
+
+    use crate::address::TonAddress;
+    use crate::message::{
+        JettonBurnNotificationMessage, TonMessageError, JETTON_BURN_NOTIFICATION,
+    };
+
+    #[test]
+    fn test_jetton_burn_notification() -> Result<(), TonMessageError> {
+        let sender =
+            TonAddress::from_str("EQBmmSYIpYH8IxubmmOlnhlD8NRhY5la9SsdC-MTt3pXmOSI").unwrap();
+        let msg = JettonBurnNotificationMessage::new(&sender, &BigUint::from(300000000000u64))
+            .with_query_id(1)
+            .with_response_destination(&sender)
+            .clone();
+
+        let cell = msg.build()?;
+        assert_eq!(cell.parser().load_u32(32)?, JETTON_BURN_NOTIFICATION);
+        assert_eq!(JettonBurnNotificationMessage::parse(&cell)?, msg);
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+use num_bigint::BigUint;
+use num_traits::Zero;
+
+use super::JETTON_INTERNAL_TRANSFER;
+use crate::address::TonAddress;
+use crate::cell::{ArcCell, Cell, CellBuilder, EMPTY_ARC_CELL};
+use crate::message::{InvalidMessage, TonMessageError};
+
+/// Creates a body for jetton internal transfer between jetton wallets according to TL-B schema:
+///
+/// ```raw
+/// internal_transfer#178d4519 query_id:uint64 amount:(VarUInteger 16) from:MsgAddress
+///                            response_address:MsgAddress
+///                            forward_ton_amount:(VarUInteger 16)
+///                            forward_payload:(Either Cell ^Cell)
+///                            = InternalMsgBody;
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct JettonInternalTransferMessage {
+    /// should be equal with request's query_id.
+    pub query_id: u64,
+    /// amount of transferred jettons.
+    pub amount: BigUint,
+    /// owner of the sending jetton wallet.
+    pub from: TonAddress,
+    /// address where to send a response with the rest of the incoming message coins.
+    pub response_address: TonAddress,
+    /// the amount of nanotons to be sent to the destination address with the notification.
+    pub forward_ton_amount: BigUint,
+    /// optional custom data that should be sent to the destination address.
+    pub forward_payload: ArcCell,
+}
+
+impl JettonInternalTransferMessage {
+    pub fn new(from: &TonAddress, amount: &BigUint) -> Self {
+        JettonInternalTransferMessage {
+            query_id: 0,
+            amount: amount.clone(),
+            from: from.clone(),
+            response_address: TonAddress::null(),
+            forward_ton_amount: BigUint::zero(),
+            forward_payload: EMPTY_ARC_CELL.clone(),
+        }
+    }
+
+    pub fn with_query_id(&mut self, query_id: u64) -> &mut Self {
+        self.query_id = query_id;
+        self
+    }
+
+    pub fn with_response_address(&mut self, response_address: &TonAddress) -> &mut Self {
+        self.response_address = response_address.clone();
+        self
+    }
+
+    pub fn with_forward_payload(
+        &mut self,
+        forward_ton_amount: &BigUint,
+        forward_payload: ArcCell,
+    ) -> &mut Self {
+        self.forward_ton_amount.clone_from(forward_ton_amount);
+        self.forward_payload = forward_payload;
+        self
+    }
+
+    pub fn build(&self) -> Result<Cell, TonMessageError> {
+        let mut message = CellBuilder::new();
+        message.store_u32(32, JETTON_INTERNAL_TRANSFER)?;
+        message.store_u64(64, self.query_id)?;
+        message.store_coins(&self.amount)?;
+        message.store_address(&self.from)?;
+        message.store_address(&self.response_address)?;
+        message.store_coins(&self.forward_ton_amount)?;
+        message.store_either_cell_or_cell_ref(&self.forward_payload)?;
+
+        Ok(message.build()?)
+    }
+
+    pub fn parse(cell: &Cell) -> Result<Self, TonMessageError> {
+        let mut parser = cell.parser();
+
+        let opcode: u32 = parser.load_u32(32)?;
+        let query_id = parser.load_u64(64)?;
+        if opcode != JETTON_INTERNAL_TRANSFER {
+            let invalid = InvalidMessage {
+                opcode: Some(opcode),
+                query_id: Some(query_id),
+                message: format!(
+                    "Unexpected opcode.  {0:08x} expected",
+                    JETTON_INTERNAL_TRANSFER
+                ),
+            };
+            return Err(TonMessageError::InvalidMessage(invalid));
+        }
+        let amount = parser.load_coins()?;
+        let from = parser.load_address()?;
+        let response_address = parser.load_address()?;
+        let forward_ton_amount = parser.load_coins()?;
+        let forward_payload = parser.load_either_cell_or_cell_ref()?;
+        parser.ensure_empty()?;
+
+        let result = JettonInternalTransferMessage {
+            query_id,
+            amount,
+            from,
+            response_address,
+            forward_ton_amount,
+            forward_payload,
+        };
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use num_bigint::BigUint;
+
+    use crate::address::TonAddress;
+    use crate::cell::CellBuilder;
+    use crate::message::{JettonInternalTransferMessage, TonMessageError, JETTON_TRANSFER};
+
+    #[test]
+    fn test_jetton_internal_transfer() -> Result<(), TonMessageError> {
+        let from =
+            TonAddress::from_str("EQAd8QRKoA5sKcug9bwK6vMdmhSAoAxr8vvABvC1TCeTude5").unwrap();
+        let response =
+            TonAddress::from_str("EQBYE3OMjPlkHPsc-Dxs9zXk66yXXvKr9vgbMIoOPi-XUa-f").unwrap();
+        let payload = CellBuilder::new()
+            .store_u32(32, 0)?
+            .store_string("hello")?
+            .build()?
+            .to_arc();
+        let msg = JettonInternalTransferMessage::new(&from, &BigUint::from(20000000u64))
+            .with_query_id(905295359779)
+            .with_response_address(&response)
+            .with_forward_payload(&BigUint::from(1u32), payload)
+            .clone();
+
+        let cell = msg.build()?;
+        assert_eq!(JettonInternalTransferMessage::parse(&cell)?, msg);
+
+        let not_internal_transfer = CellBuilder::new()
+            .store_u32(32, JETTON_TRANSFER)?
+            .store_u64(64, 0)?
+            .build()?;
+        assert!(JettonInternalTransferMessage::parse(&not_internal_transfer).is_err());
+        Ok(())
+    }
+}
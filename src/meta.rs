@@ -2,11 +2,16 @@ pub use error::*;
 pub use ipfs_loader::*;
 pub use loader::*;
 use serde_json::Value;
+pub use token_registry::*;
+pub use url_sanitizer::*;
 
 mod error;
 mod ipfs_loader;
 mod loader;
+mod token_registry;
+mod url_sanitizer;
 
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 
 use async_trait::async_trait;
@@ -65,6 +70,34 @@ lazy_static! {
     static ref META_ATTRIBUTES: MetaDataField = MetaDataField::new("attributes");
     static ref META_SOCIAL_LINKS: MetaDataField = MetaDataField::new("social_links");
     static ref META_MARKETPLACE: MetaDataField = MetaDataField::new("marketplace");
+    static ref STANDARD_META_KEYS: HashSet<[u8; 32]> = [
+        META_NAME.key,
+        META_DESCRIPTION.key,
+        META_IMAGE.key,
+        META_SYMBOL.key,
+        META_IMAGE_DATA.key,
+        META_DECIMALS.key,
+        META_URI.key,
+        META_CONTENT_URL.key,
+        META_ATTRIBUTES.key,
+        META_SOCIAL_LINKS.key,
+        META_MARKETPLACE.key,
+    ]
+    .into_iter()
+    .collect();
+}
+
+/// Returns the on-chain metadata dict key of the attribute name (sha256 of the name).
+pub fn metadata_key(name: &str) -> [u8; 32] {
+    MetaDataField::new(name).key
+}
+
+/// Returns entries of the on-chain metadata dict with keys not known to the library.
+pub fn extra_metadata(dict: &SnakeFormattedDict) -> HashMap<[u8; 32], Vec<u8>> {
+    dict.iter()
+        .filter(|(key, _)| !STANDARD_META_KEYS.contains(*key))
+        .map(|(key, value)| (*key, value.clone()))
+        .collect()
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
@@ -127,6 +160,20 @@ where
         })
     }
 
+    /// Creates loader sending all requests (including IPFS ones) through provided http client,
+    /// e.g. the one configured with proxy or custom DNS resolver.
+    pub fn new_with_client(
+        ipfs_loader_config: &IpfsLoaderConfig,
+        http_client: reqwest::Client,
+    ) -> MetaLoader<MetaData> {
+        let ipfs_loader = IpfsLoader::new_with_client(ipfs_loader_config, http_client.clone());
+        MetaLoader {
+            http_client,
+            ipfs_loader,
+            meta_data_marker: std::marker::PhantomData,
+        }
+    }
+
     #[allow(clippy::should_implement_trait)]
     pub fn default() -> Result<MetaLoader<MetaData>, MetaLoaderError> {
         let http_client = reqwest::Client::builder().build()?;
@@ -169,3 +216,24 @@ where
 {
     async fn load(&self, content: &MetaDataContent) -> Result<T, MetaLoaderError>;
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::cell::SnakeFormattedDict;
+    use crate::meta::{metadata_key, JettonMetaData};
+
+    #[test]
+    fn test_extra_metadata() {
+        let dict = SnakeFormattedDict::from([
+            (metadata_key("name"), b"Coin".to_vec()),
+            (metadata_key("website"), b"https://example.com".to_vec()),
+        ]);
+        let meta = JettonMetaData::from(&dict);
+        assert_eq!(meta.name, Some("Coin".to_string()));
+        assert_eq!(meta.extra.len(), 1);
+        assert_eq!(
+            meta.extra.get(&metadata_key("website")),
+            Some(&b"https://example.com".to_vec())
+        );
+    }
+}
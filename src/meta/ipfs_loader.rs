@@ -53,11 +53,17 @@ pub struct IpfsLoader {
 
 impl IpfsLoader {
     pub fn new(config: &IpfsLoaderConfig) -> Result<Self, IpfsLoaderError> {
-        Ok(Self {
+        let client = reqwest::Client::builder().build()?;
+        Ok(Self::new_with_client(config, client))
+    }
+
+    /// Creates loader using provided http client, e.g. the one configured with proxy.
+    pub fn new_with_client(config: &IpfsLoaderConfig, client: reqwest::Client) -> Self {
+        Self {
             connection_type: config.connection_type.clone(),
             base_url: config.base_url.clone(),
-            client: reqwest::Client::builder().build()?,
-        })
+            client,
+        }
     }
 
     #[allow(clippy::should_implement_trait)]
@@ -106,7 +112,7 @@ impl IpfsLoader {
 
 #[cfg(test)]
 mod tests {
-    use crate::meta::{IpfsConnectionType, IpfsLoaderConfig};
+    use crate::meta::{IpfsConnectionType, IpfsLoader, IpfsLoaderConfig};
 
     static CONFIG_JSON: &str = r#"
     {
@@ -122,4 +128,16 @@ mod tests {
         assert_eq!(config.base_url, "http://example.com/");
         Ok(())
     }
+
+    #[test]
+    fn test_loader_with_custom_client() -> anyhow::Result<()> {
+        let client = reqwest::Client::builder()
+            .proxy(reqwest::Proxy::all("socks5h://127.0.0.1:9050")?)
+            .build()?;
+        let config = IpfsLoaderConfig::ipfs_node("http://127.0.0.1:5001");
+        let loader = IpfsLoader::new_with_client(&config, client);
+        assert_eq!(loader.connection_type, IpfsConnectionType::IpfsNode);
+        assert_eq!(loader.base_url, "http://127.0.0.1:5001");
+        Ok(())
+    }
 }
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_aux::prelude::*;
@@ -28,6 +30,9 @@ pub struct JettonMetaData {
     ///on-chain and that the smart contract code ensures that this parameter is immutable.
     #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
     pub decimals: Option<u8>,
+    /// Values of non-standard on-chain keys (sha256 of the key name, see `metadata_key`).
+    #[serde(skip)]
+    pub extra: HashMap<[u8; 32], Vec<u8>>,
 }
 
 #[async_trait]
@@ -36,8 +41,8 @@ impl LoadMeta<JettonMetaData> for MetaLoader<JettonMetaData> {
         match content {
             MetaDataContent::External { uri } => self.load_meta_from_uri(uri.as_str()).await,
             MetaDataContent::Internal { dict } => {
-                if dict.contains_key(&META_URI.key) {
-                    let uri = String::from_utf8_lossy(dict.get(&META_URI.key).unwrap()).to_string();
+                if let Some(uri) = dict.get(&META_URI.key) {
+                    let uri = String::from_utf8_lossy(uri).to_string();
                     let result = self.load_meta_from_uri(uri.as_str()).await;
 
                     match result {
@@ -53,7 +58,8 @@ impl LoadMeta<JettonMetaData> for MetaLoader<JettonMetaData> {
                                 .or(dict.get(&META_IMAGE_DATA.key).cloned()),
                             decimals: META_DECIMALS
                                 .use_string_or(None, dict)
-                                .map(|v| v.parse::<u8>().unwrap()),
+                                .and_then(|v| v.parse::<u8>().ok()),
+                            extra: extra_metadata(dict),
                         }),
                         Err(_) => Ok(dict.into()),
                     }
@@ -78,7 +84,8 @@ impl From<&SnakeFormattedDict> for JettonMetaData {
             image_data: dict.get(&META_IMAGE_DATA.key).cloned(),
             decimals: META_DECIMALS
                 .use_string_or(None, dict)
-                .map(|v| v.parse::<u8>().unwrap()),
+                .and_then(|v| v.parse::<u8>().ok()),
+            extra: extra_metadata(dict),
         }
     }
 }
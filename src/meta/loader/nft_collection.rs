@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -16,6 +18,9 @@ pub struct NftCollectionMetaData {
     pub social_links: Option<Value>,
     /// Optional. No description in TEP64 yet
     pub marketplace: Option<String>,
+    /// Values of non-standard on-chain keys (sha256 of the key name, see `metadata_key`).
+    #[serde(skip)]
+    pub extra: HashMap<[u8; 32], Vec<u8>>,
 }
 
 #[async_trait]
@@ -27,8 +32,8 @@ impl LoadMeta<NftCollectionMetaData> for MetaLoader<NftCollectionMetaData> {
         match content {
             MetaDataContent::External { uri } => self.load_meta_from_uri(uri.as_str()).await,
             MetaDataContent::Internal { dict } => {
-                if dict.contains_key(&META_URI.key) {
-                    let uri = String::from_utf8_lossy(dict.get(&META_URI.key).unwrap()).to_string();
+                if let Some(uri) = dict.get(&META_URI.key) {
+                    let uri = String::from_utf8_lossy(uri).to_string();
                     let external_meta = self.load_meta_from_uri(uri.as_str()).await?;
                     Ok(NftCollectionMetaData {
                         image: META_IMAGE.use_string_or(external_meta.image, dict),
@@ -39,6 +44,7 @@ impl LoadMeta<NftCollectionMetaData> for MetaLoader<NftCollectionMetaData> {
                             .use_value_or(external_meta.social_links, dict),
                         marketplace: META_MARKETPLACE
                             .use_string_or(external_meta.marketplace, dict),
+                        extra: extra_metadata(dict),
                     })
                 } else {
                     Ok(NftCollectionMetaData {
@@ -47,6 +53,7 @@ impl LoadMeta<NftCollectionMetaData> for MetaLoader<NftCollectionMetaData> {
                         description: META_DESCRIPTION.use_string_or(None, dict),
                         social_links: META_SOCIAL_LINKS.use_value_or(None, dict),
                         marketplace: META_MARKETPLACE.use_string_or(None, dict),
+                        extra: extra_metadata(dict),
                     })
                 }
             }
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -16,6 +18,9 @@ pub struct NftItemMetaData {
     pub content_url: Option<String>,
     /// Optional. No description in TEP64 yet
     pub attributes: Option<Value>,
+    /// Values of non-standard on-chain keys (sha256 of the key name, see `metadata_key`).
+    #[serde(skip)]
+    pub extra: HashMap<[u8; 32], Vec<u8>>,
 }
 
 #[async_trait]
@@ -24,8 +29,8 @@ impl LoadMeta<NftItemMetaData> for MetaLoader<NftItemMetaData> {
         match content {
             MetaDataContent::External { uri } => self.load_meta_from_uri(uri.as_str()).await,
             MetaDataContent::Internal { dict } => {
-                if dict.contains_key(&META_URI.key) {
-                    let uri = String::from_utf8_lossy(dict.get(&META_URI.key).unwrap()).to_string();
+                if let Some(uri) = dict.get(&META_URI.key) {
+                    let uri = String::from_utf8_lossy(uri).to_string();
                     let external_meta = self.load_meta_from_uri(uri.as_str()).await?;
                     Ok(NftItemMetaData {
                         name: META_NAME.use_string_or(external_meta.name, dict),
@@ -34,6 +39,7 @@ impl LoadMeta<NftItemMetaData> for MetaLoader<NftItemMetaData> {
                             .use_string_or(external_meta.description, dict),
                         image: META_IMAGE.use_string_or(external_meta.image, dict),
                         attributes: META_ATTRIBUTES.use_value_or(external_meta.attributes, dict),
+                        extra: extra_metadata(dict),
                     })
                 } else {
                     Ok(NftItemMetaData {
@@ -42,6 +48,7 @@ impl LoadMeta<NftItemMetaData> for MetaLoader<NftItemMetaData> {
                         description: META_DESCRIPTION.use_string_or(None, dict),
                         image: META_IMAGE.use_string_or(None, dict),
                         attributes: META_ATTRIBUTES.use_value_or(None, dict),
+                        extra: extra_metadata(dict),
                     })
                 }
             }
@@ -0,0 +1,243 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+use crate::address::{AddressClass, AddressClassifier, AddressRole, TonAddress};
+use crate::contract::DEFAULT_JETTON_DECIMALS;
+use crate::meta::MetaLoaderError;
+
+/// Token information from a community token list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenInfo {
+    /// Address of the jetton master.
+    pub address: TonAddress,
+    pub symbol: String,
+    pub name: String,
+    pub decimals: u8,
+    pub image: Option<String>,
+}
+
+/// Format of the token list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenListFormat {
+    /// `jettons.json` of tonkeeper/ton-assets: array of `{address, name, symbol, decimals, image}`.
+    Tonkeeper,
+    /// Response of ston.fi `/v1/assets`: `{asset_list: [{contract_address, symbol, ...}]}`.
+    /// Blacklisted and non-jetton assets are skipped.
+    Stonfi,
+}
+
+const STONFI_JETTON_KIND: &str = "Jetton";
+
+#[derive(Deserialize)]
+struct TonkeeperToken {
+    address: String,
+    name: String,
+    symbol: String,
+    #[serde(default)]
+    decimals: Option<u8>,
+    #[serde(default)]
+    image: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct StonfiAssetList {
+    asset_list: Vec<StonfiAsset>,
+}
+
+#[derive(Deserialize)]
+struct StonfiAsset {
+    contract_address: String,
+    symbol: String,
+    #[serde(default)]
+    display_name: Option<String>,
+    decimals: u8,
+    #[serde(default)]
+    image_url: Option<String>,
+    #[serde(default)]
+    kind: Option<String>,
+    #[serde(default)]
+    blacklisted: bool,
+}
+
+/// Registry of known tokens, allowing to attach symbols and decimals without loading metadata.
+///
+/// When several lists contain the same token, the first loaded entry wins.
+#[derive(Debug, Clone, Default)]
+pub struct TokenRegistry {
+    tokens: HashMap<TonAddress, TokenInfo>,
+}
+
+impl TokenRegistry {
+    pub fn new() -> TokenRegistry {
+        TokenRegistry::default()
+    }
+
+    /// Loads token list and returns number of added tokens. Entries with invalid addresses
+    /// are skipped.
+    pub fn load_list(
+        &mut self,
+        json: &str,
+        format: TokenListFormat,
+    ) -> Result<usize, MetaLoaderError> {
+        let tokens: Vec<(String, TokenInfo)> = match format {
+            TokenListFormat::Tonkeeper => {
+                let list: Vec<TonkeeperToken> = serde_json::from_str(json)?;
+                list.into_iter()
+                    .map(|t| {
+                        let info = TokenInfo {
+                            address: TonAddress::NULL,
+                            symbol: t.symbol,
+                            name: t.name,
+                            decimals: t.decimals.unwrap_or(DEFAULT_JETTON_DECIMALS),
+                            image: t.image,
+                        };
+                        (t.address, info)
+                    })
+                    .collect()
+            }
+            TokenListFormat::Stonfi => {
+                let list: StonfiAssetList = serde_json::from_str(json)?;
+                list.asset_list
+                    .into_iter()
+                    .filter(|a| !a.blacklisted)
+                    .filter(|a| {
+                        a.kind.as_deref().unwrap_or(STONFI_JETTON_KIND) == STONFI_JETTON_KIND
+                    })
+                    .map(|a| {
+                        let info = TokenInfo {
+                            address: TonAddress::NULL,
+                            name: a.display_name.unwrap_or_else(|| a.symbol.clone()),
+                            symbol: a.symbol,
+                            decimals: a.decimals,
+                            image: a.image_url,
+                        };
+                        (a.contract_address, info)
+                    })
+                    .collect()
+            }
+        };
+
+        let mut added = 0;
+        for (address, mut info) in tokens {
+            let Ok(address) = address.parse::<TonAddress>() else {
+                log::debug!(
+                    "Skipping token {} with invalid address {}",
+                    info.symbol,
+                    address
+                );
+                continue;
+            };
+            if let Entry::Vacant(entry) = self.tokens.entry(address) {
+                info.address = entry.key().clone();
+                entry.insert(info);
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+
+    /// Downloads and loads token list, returns number of added tokens.
+    pub async fn load_list_from_url(
+        &mut self,
+        client: &reqwest::Client,
+        url: &str,
+        format: TokenListFormat,
+    ) -> Result<usize, MetaLoaderError> {
+        let response = client.get(url).send().await?;
+        let status = response.status();
+        if status != StatusCode::OK {
+            return Err(MetaLoaderError::LoadMetaDataFailed {
+                uri: url.to_string(),
+                status,
+            });
+        }
+        let json = response.text().await?;
+        self.load_list(&json, format)
+    }
+
+    pub fn insert(&mut self, token: TokenInfo) -> Option<TokenInfo> {
+        self.tokens.insert(token.address.clone(), token)
+    }
+
+    pub fn get(&self, address: &TonAddress) -> Option<&TokenInfo> {
+        self.tokens.get(address)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+}
+
+/// Classifies jetton masters listed in the registry as trusted, other addresses as unknown.
+impl AddressClassifier for TokenRegistry {
+    fn classify(&self, address: &TonAddress, role: AddressRole) -> AddressClass {
+        if role == AddressRole::JettonMaster && self.tokens.contains_key(address) {
+            AddressClass::Trusted
+        } else {
+            AddressClass::Unknown
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::address::{AddressClass, AddressClassifier, AddressRole, TonAddress};
+    use crate::meta::{TokenListFormat, TokenRegistry};
+
+    const USDT: &str = "EQCxE6mUtQJKFnGfaROTKOt1lZbDiiX1kCixRv7Nw2Id_sDs";
+    const STON: &str = "EQA2kCVNwVsil2EM2mB0SkXytxCqQjS4mttjDpnXmwG9T6bO";
+
+    const TONKEEPER_LIST: &str = r#"[
+        {"name": "Tether USD", "address": "0:b113a994b5024a16719f69139328eb759596c38a25f59028b146fecdc3621dfe",
+         "symbol": "USD₮", "decimals": 6, "image": "https://tether.to/images/logoCircle.png"},
+        {"name": "STON", "address": "EQA2kCVNwVsil2EM2mB0SkXytxCqQjS4mttjDpnXmwG9T6bO", "symbol": "STON"},
+        {"name": "Broken", "address": "not an address", "symbol": "BRK"}
+    ]"#;
+
+    const STONFI_LIST: &str = r#"{"asset_list": [
+        {"contract_address": "EQA2kCVNwVsil2EM2mB0SkXytxCqQjS4mttjDpnXmwG9T6bO", "symbol": "STON",
+         "display_name": "STON", "decimals": 9, "kind": "Jetton", "blacklisted": false},
+        {"contract_address": "EQAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAM9c", "symbol": "TON",
+         "decimals": 9, "kind": "Ton"},
+        {"contract_address": "EQBlqsm144Dq6SjbPI4jjZvA1hqTIP3CvHovbIfW_t-SCALE", "symbol": "SCALE",
+         "decimals": 9, "kind": "Jetton", "blacklisted": true}
+    ]}"#;
+
+    #[test]
+    fn test_token_registry_load_lists() -> anyhow::Result<()> {
+        let mut registry = TokenRegistry::new();
+        assert_eq!(
+            registry.load_list(TONKEEPER_LIST, TokenListFormat::Tonkeeper)?,
+            2
+        );
+        assert_eq!(registry.load_list(STONFI_LIST, TokenListFormat::Stonfi)?, 0);
+        assert_eq!(registry.len(), 2);
+
+        let usdt = registry.get(&USDT.parse::<TonAddress>()?).unwrap();
+        assert_eq!(usdt.symbol, "USD₮");
+        assert_eq!(usdt.decimals, 6);
+        let ston = registry.get(&STON.parse::<TonAddress>()?).unwrap();
+        assert_eq!(ston.decimals, 9);
+        assert_eq!(ston.image, None);
+
+        let usdt = USDT.parse::<TonAddress>()?;
+        let class = registry.classify(&usdt, AddressRole::JettonMaster);
+        assert_eq!(class, AddressClass::Trusted);
+        let class = registry.classify(&usdt, AddressRole::JettonWallet);
+        assert_eq!(class, AddressClass::Unknown);
+
+        let mut registry = TokenRegistry::new();
+        assert_eq!(registry.load_list(STONFI_LIST, TokenListFormat::Stonfi)?, 1);
+        assert!(registry
+            .load_list("{}", TokenListFormat::Tonkeeper)
+            .is_err());
+        Ok(())
+    }
+}
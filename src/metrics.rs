@@ -0,0 +1,247 @@
+//! Metrics of `TonClient` and `TonContractFactory` operations.
+//!
+//! Metrics are reported to a `MetricsRegistry` set by `TonClientBuilder::with_metrics` and
+//! `TonContractFactoryBuilder::with_metrics`. `PrometheusRegistry` keeps them in memory and
+//! renders them in Prometheus text format, other registries may forward them to an existing
+//! metrics library.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Mutex, PoisonError};
+
+/// Calls of tonlib functions, labeled by `method`.
+pub const METRIC_REQUESTS_TOTAL: &str = "tonlib_requests_total";
+/// Failed calls of tonlib functions, labeled by `method`.
+pub const METRIC_REQUEST_ERRORS_TOTAL: &str = "tonlib_request_errors_total";
+/// Latency of tonlib function calls (including retries), labeled by `method`.
+pub const METRIC_REQUEST_DURATION_SECONDS: &str = "tonlib_request_duration_seconds";
+/// Number of pool connections.
+pub const METRIC_POOL_SIZE: &str = "tonlib_pool_size";
+/// Number of calls in flight over all pool connections.
+pub const METRIC_POOL_IN_FLIGHT: &str = "tonlib_pool_in_flight";
+/// Cache lookups, labeled by `cache` and `result` (`hit` or `miss`).
+pub const METRIC_CACHE_REQUESTS_TOTAL: &str = "tonlib_cache_requests_total";
+
+/// Upper bounds of histogram buckets, in seconds.
+pub const DEFAULT_HISTOGRAM_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Receives metrics. Labels are passed as `(name, value)` pairs.
+pub trait MetricsRegistry: Send + Sync {
+    fn increment_counter(&self, name: &str, labels: &[(&str, &str)]);
+
+    fn observe_histogram(&self, name: &str, labels: &[(&str, &str)], value: f64);
+
+    fn set_gauge(&self, name: &str, labels: &[(&str, &str)], value: f64);
+}
+
+/// Registry keeping metrics in memory, which are rendered by `render` in Prometheus text
+/// exposition format, e.g. to be served on `/metrics`.
+pub struct PrometheusRegistry {
+    buckets: Vec<f64>,
+    families: Mutex<BTreeMap<String, MetricFamily>>,
+}
+
+type Labels = Vec<(String, String)>;
+
+enum MetricFamily {
+    Counter(BTreeMap<Labels, u64>),
+    Gauge(BTreeMap<Labels, f64>),
+    Histogram(BTreeMap<Labels, Histogram>),
+}
+
+struct Histogram {
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl PrometheusRegistry {
+    pub fn new() -> PrometheusRegistry {
+        Self::with_buckets(DEFAULT_HISTOGRAM_BUCKETS)
+    }
+
+    pub fn with_buckets(buckets: &[f64]) -> PrometheusRegistry {
+        PrometheusRegistry {
+            buckets: buckets.to_vec(),
+            families: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Returns value of the counter, 0 if it was not incremented yet.
+    pub fn counter(&self, name: &str, labels: &[(&str, &str)]) -> u64 {
+        match self.families().get(name) {
+            Some(MetricFamily::Counter(series)) => {
+                series.get(&to_labels(labels)).copied().unwrap_or(0)
+            }
+            _ => 0,
+        }
+    }
+
+    pub fn gauge(&self, name: &str, labels: &[(&str, &str)]) -> Option<f64> {
+        match self.families().get(name) {
+            Some(MetricFamily::Gauge(series)) => series.get(&to_labels(labels)).copied(),
+            _ => None,
+        }
+    }
+
+    /// Renders all metrics in Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for (name, family) in self.families().iter() {
+            match family {
+                MetricFamily::Counter(series) => {
+                    let _ = writeln!(out, "# TYPE {} counter", name);
+                    for (labels, value) in series {
+                        let _ = writeln!(out, "{}{} {}", name, format_labels(labels, None), value);
+                    }
+                }
+                MetricFamily::Gauge(series) => {
+                    let _ = writeln!(out, "# TYPE {} gauge", name);
+                    for (labels, value) in series {
+                        let _ = writeln!(out, "{}{} {}", name, format_labels(labels, None), value);
+                    }
+                }
+                MetricFamily::Histogram(series) => {
+                    let _ = writeln!(out, "# TYPE {} histogram", name);
+                    for (labels, histogram) in series {
+                        let bounds = self.buckets.iter().map(|b| b.to_string());
+                        let bounds = bounds.chain(std::iter::once("+Inf".to_string()));
+                        let mut cumulative = 0;
+                        for (bound, count) in bounds.zip(&histogram.counts) {
+                            cumulative += count;
+                            let labels = format_labels(labels, Some(&bound));
+                            let _ = writeln!(out, "{}_bucket{} {}", name, labels, cumulative);
+                        }
+                        let labels = format_labels(labels, None);
+                        let _ = writeln!(out, "{}_sum{} {}", name, labels, histogram.sum);
+                        let _ = writeln!(out, "{}_count{} {}", name, labels, histogram.count);
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    fn families(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, MetricFamily>> {
+        self.families.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for PrometheusRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsRegistry for PrometheusRegistry {
+    fn increment_counter(&self, name: &str, labels: &[(&str, &str)]) {
+        let mut families = self.families();
+        let family = families
+            .entry(name.to_string())
+            .or_insert_with(|| MetricFamily::Counter(BTreeMap::new()));
+        if let MetricFamily::Counter(series) = family {
+            *series.entry(to_labels(labels)).or_default() += 1;
+        }
+    }
+
+    fn observe_histogram(&self, name: &str, labels: &[(&str, &str)], value: f64) {
+        let mut families = self.families();
+        let family = families
+            .entry(name.to_string())
+            .or_insert_with(|| MetricFamily::Histogram(BTreeMap::new()));
+        if let MetricFamily::Histogram(series) = family {
+            let histogram = series
+                .entry(to_labels(labels))
+                .or_insert_with(|| Histogram {
+                    counts: vec![0; self.buckets.len() + 1],
+                    sum: 0.0,
+                    count: 0,
+                });
+            let bucket = self
+                .buckets
+                .iter()
+                .position(|b| value <= *b)
+                .unwrap_or(self.buckets.len());
+            histogram.counts[bucket] += 1;
+            histogram.sum += value;
+            histogram.count += 1;
+        }
+    }
+
+    fn set_gauge(&self, name: &str, labels: &[(&str, &str)], value: f64) {
+        let mut families = self.families();
+        let family = families
+            .entry(name.to_string())
+            .or_insert_with(|| MetricFamily::Gauge(BTreeMap::new()));
+        if let MetricFamily::Gauge(series) = family {
+            series.insert(to_labels(labels), value);
+        }
+    }
+}
+
+fn to_labels(labels: &[(&str, &str)]) -> Labels {
+    labels
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn format_labels(labels: &Labels, le: Option<&str>) -> String {
+    let pairs: Vec<_> = labels
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .chain(le.map(|le| ("le", le)))
+        .map(|(k, v)| {
+            let v = v
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{}=\"{}\"", k, v)
+        })
+        .collect();
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::metrics::{MetricsRegistry, PrometheusRegistry};
+
+    #[test]
+    fn test_prometheus_registry_render() {
+        let registry = PrometheusRegistry::with_buckets(&[0.1, 1.0]);
+        let labels = [("method", "raw.getAccountState")];
+        registry.increment_counter("requests_total", &labels);
+        registry.increment_counter("requests_total", &labels);
+        registry.set_gauge("pool_size", &[], 3.0);
+        registry.observe_histogram("duration_seconds", &labels, 0.05);
+        registry.observe_histogram("duration_seconds", &labels, 0.5);
+        registry.observe_histogram("duration_seconds", &labels, 2.0);
+        registry.increment_counter("errors_total", &[("message", "a \"b\"")]);
+
+        assert_eq!(registry.counter("requests_total", &labels), 2);
+        assert_eq!(registry.counter("requests_total", &[]), 0);
+        assert_eq!(registry.gauge("pool_size", &[]), Some(3.0));
+
+        let expected = "\
+# TYPE duration_seconds histogram
+duration_seconds_bucket{method=\"raw.getAccountState\",le=\"0.1\"} 1
+duration_seconds_bucket{method=\"raw.getAccountState\",le=\"1\"} 2
+duration_seconds_bucket{method=\"raw.getAccountState\",le=\"+Inf\"} 3
+duration_seconds_sum{method=\"raw.getAccountState\"} 2.55
+duration_seconds_count{method=\"raw.getAccountState\"} 3
+# TYPE errors_total counter
+errors_total{message=\"a \\\"b\\\"\"} 1
+# TYPE pool_size gauge
+pool_size 3
+# TYPE requests_total counter
+requests_total{method=\"raw.getAccountState\"} 2
+";
+        assert_eq!(registry.render(), expected);
+    }
+}
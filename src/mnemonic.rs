@@ -1,8 +1,10 @@
+mod bip39;
 mod error;
 
 use std::cmp;
 use std::collections::HashMap;
 
+pub use bip39::*;
 pub use error::*;
 use hmac::{Hmac, Mac};
 use lazy_static::lazy_static;
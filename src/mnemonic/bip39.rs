@@ -0,0 +1,228 @@
+use hmac::{Hmac, Mac};
+use nacl::sign::generate_keypair;
+use pbkdf2::pbkdf2_hmac;
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::mnemonic::{KeyPair, Mnemonic, MnemonicError, WORDLIST_EN_SET};
+
+/// Derivation path used for TON by wallets based on BIP39 seeds.
+pub const TON_BIP44_PATH: &str = "m/44'/607'/0'";
+
+const BIP39_PBKDF_ITERATIONS: u32 = 2048;
+const SLIP10_ED25519_CURVE: &[u8] = b"ed25519 seed";
+const HARDENED_OFFSET: u32 = 0x80000000;
+
+/// Key derivation mode of the mnemonic phrase.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum DerivationMode {
+    /// Native TON mnemonic.
+    #[default]
+    Ton,
+    /// BIP39 seed with ed25519 SLIP-10 derivation by the path (hardened indexes only,
+    /// e.g. `TON_BIP44_PATH`).
+    Bip39 { path: String },
+}
+
+impl DerivationMode {
+    pub fn bip39_default() -> DerivationMode {
+        DerivationMode::Bip39 {
+            path: TON_BIP44_PATH.to_string(),
+        }
+    }
+
+    /// Derives key pair from the phrase. `password` is TON mnemonic password or BIP39 passphrase.
+    pub fn key_pair(
+        &self,
+        phrase: &str,
+        password: &Option<String>,
+    ) -> Result<KeyPair, MnemonicError> {
+        match self {
+            DerivationMode::Ton => Mnemonic::from_str(phrase, password)?.to_key_pair(),
+            DerivationMode::Bip39 { path } => {
+                Bip39Mnemonic::from_str(phrase, password)?.to_key_pair(path)
+            }
+        }
+    }
+}
+
+/// BIP39 mnemonic (English wordlist).
+///
+/// Passphrase is used as is, without NFKD normalization, so only ASCII passphrases are
+/// guaranteed to be compatible.
+pub struct Bip39Mnemonic {
+    words: Vec<String>,
+    passphrase: Option<String>,
+}
+
+impl Bip39Mnemonic {
+    pub fn new(
+        words: Vec<&str>,
+        passphrase: &Option<String>,
+    ) -> Result<Bip39Mnemonic, MnemonicError> {
+        let normalized_words: Vec<String> = words.iter().map(|w| w.trim().to_lowercase()).collect();
+        if !matches!(normalized_words.len(), 12 | 15 | 18 | 21 | 24) {
+            return Err(MnemonicError::UnexpectedWordCount(normalized_words.len()));
+        }
+        let mut bits: Vec<bool> = Vec::with_capacity(normalized_words.len() * 11);
+        for word in &normalized_words {
+            let index = *WORDLIST_EN_SET
+                .get(word.as_str())
+                .ok_or_else(|| MnemonicError::InvalidWord(word.clone()))?;
+            bits.extend((0..11).rev().map(|i| (index >> i) & 1 == 1));
+        }
+        let checksum_len = bits.len() / 33;
+        let entropy: Vec<u8> = bits[..bits.len() - checksum_len]
+            .chunks(8)
+            .map(|byte| byte.iter().fold(0u8, |acc, bit| (acc << 1) | *bit as u8))
+            .collect();
+        let hash = Sha256::digest(&entropy);
+        let expected = (0..checksum_len).map(|i| (hash[0] >> (7 - i)) & 1 == 1);
+        if !bits[bits.len() - checksum_len..]
+            .iter()
+            .copied()
+            .eq(expected)
+        {
+            return Err(MnemonicError::InvalidChecksum);
+        }
+        Ok(Bip39Mnemonic {
+            words: normalized_words,
+            passphrase: passphrase.clone(),
+        })
+    }
+
+    pub fn from_str(s: &str, passphrase: &Option<String>) -> Result<Bip39Mnemonic, MnemonicError> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        Bip39Mnemonic::new(words, passphrase)
+    }
+
+    /// Returns 64-byte BIP39 seed.
+    pub fn to_seed(&self) -> Vec<u8> {
+        let salt = format!("mnemonic{}", self.passphrase.as_deref().unwrap_or(""));
+        let mut seed = vec![0; 64];
+        pbkdf2_hmac::<Sha512>(
+            self.words.join(" ").as_bytes(),
+            salt.as_bytes(),
+            BIP39_PBKDF_ITERATIONS,
+            &mut seed,
+        );
+        seed
+    }
+
+    pub fn to_key_pair(&self, path: &str) -> Result<KeyPair, MnemonicError> {
+        let private_key = slip10_derive_ed25519(&self.to_seed(), path)?;
+        let key_pair = generate_keypair(&private_key);
+        Ok(KeyPair {
+            public_key: key_pair.pkey.to_vec(),
+            secret_key: key_pair.skey.to_vec(),
+        })
+    }
+}
+
+/// Derives ed25519 private key from the seed by SLIP-10 path (e.g. `m/44'/607'/0'`).
+///
+/// Ed25519 supports hardened derivation only, so all indexes are treated as hardened.
+pub fn slip10_derive_ed25519(seed: &[u8], path: &str) -> Result<[u8; 32], MnemonicError> {
+    let (mut key, mut chain_code) = hmac_sha512_split(SLIP10_ED25519_CURVE, &[seed])?;
+    for index in parse_derivation_path(path)? {
+        let index = (index | HARDENED_OFFSET).to_be_bytes();
+        (key, chain_code) = hmac_sha512_split(&chain_code, &[&[0], &key, &index])?;
+    }
+    Ok(key)
+}
+
+fn parse_derivation_path(path: &str) -> Result<Vec<u32>, MnemonicError> {
+    let invalid = || MnemonicError::InvalidDerivationPath(path.to_string());
+    let mut segments = path.trim().split('/');
+    if segments.next() != Some("m") {
+        return Err(invalid());
+    }
+    segments
+        .map(|segment| {
+            let index = segment
+                .strip_suffix('\'')
+                .or_else(|| segment.strip_suffix('h'))
+                .unwrap_or(segment);
+            match index.parse::<u32>() {
+                Ok(index) if index < HARDENED_OFFSET => Ok(index),
+                _ => Err(invalid()),
+            }
+        })
+        .collect()
+}
+
+fn hmac_sha512_split(key: &[u8], data: &[&[u8]]) -> Result<([u8; 32], [u8; 32]), MnemonicError> {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key)?;
+    for d in data {
+        mac.update(d);
+    }
+    let result = mac.finalize().into_bytes();
+    let mut left = [0u8; 32];
+    let mut right = [0u8; 32];
+    left.copy_from_slice(&result[..32]);
+    right.copy_from_slice(&result[32..]);
+    Ok((left, right))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mnemonic::{
+        slip10_derive_ed25519, Bip39Mnemonic, DerivationMode, MnemonicError, TON_BIP44_PATH,
+    };
+
+    const ABANDON_ABOUT: &str = "abandon abandon abandon abandon abandon abandon \
+        abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn bip39_seed_works() -> anyhow::Result<()> {
+        let mnemonic = Bip39Mnemonic::from_str(ABANDON_ABOUT, &None)?;
+        assert_eq!(
+            hex::encode(mnemonic.to_seed()),
+            "5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc1\
+            9a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4"
+        );
+        let with_passphrase = Bip39Mnemonic::from_str(ABANDON_ABOUT, &Some("TREZOR".to_string()))?;
+        assert_ne!(with_passphrase.to_seed(), mnemonic.to_seed());
+        Ok(())
+    }
+
+    #[test]
+    fn bip39_validation_works() {
+        let invalid_checksum = ABANDON_ABOUT.replace("about", "abandon");
+        assert!(matches!(
+            Bip39Mnemonic::from_str(&invalid_checksum, &None),
+            Err(MnemonicError::InvalidChecksum)
+        ));
+        assert!(matches!(
+            Bip39Mnemonic::from_str("abandon about", &None),
+            Err(MnemonicError::UnexpectedWordCount(2))
+        ));
+    }
+
+    #[test]
+    fn slip10_derivation_works() -> anyhow::Result<()> {
+        // SLIP-10 test vector 1 for ed25519
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f")?;
+        assert_eq!(
+            hex::encode(slip10_derive_ed25519(&seed, "m")?),
+            "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7"
+        );
+        assert_eq!(
+            hex::encode(slip10_derive_ed25519(&seed, "m/0'")?),
+            "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3"
+        );
+        assert!(slip10_derive_ed25519(&seed, "44'/607'").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn derivation_mode_works() -> anyhow::Result<()> {
+        let bip39 = DerivationMode::bip39_default().key_pair(ABANDON_ABOUT, &None)?;
+        let explicit = DerivationMode::Bip39 {
+            path: TON_BIP44_PATH.to_string(),
+        }
+        .key_pair(ABANDON_ABOUT, &None)?;
+        assert!(bip39 == explicit);
+        assert!(DerivationMode::Ton.key_pair(ABANDON_ABOUT, &None).is_err());
+        Ok(())
+    }
+}
@@ -17,6 +17,12 @@ pub enum MnemonicError {
     #[error("Invalid password (hash: {0})")]
     PasswordHashError(pbkdf2::password_hash::Error),
 
+    #[error("Invalid mnemonic checksum")]
+    InvalidChecksum,
+
+    #[error("Invalid derivation path (path: {0})")]
+    InvalidDerivationPath(String),
+
     #[error("Invalid length of sha digest (length: {0})")]
     ShaDigestLengthInvalid(#[from] sha2::digest::InvalidLength),
 }
@@ -0,0 +1,289 @@
+//! gRPC facade over `TonClientInterface`, see `scheme/ton_service.proto`, and
+//! toncenter-compatible HTTP API, see `ToncenterApi`.
+//!
+//! ```ignore
+//! let service = TonGrpcService::new(client).into_server();
+//! tonic::transport::Server::builder()
+//!     .add_service(service)
+//!     .serve("127.0.0.1:50051".parse()?)
+//!     .await?;
+//! ```
+
+use tonic::{Request, Response, Status};
+
+use crate::address::TonAddress;
+use crate::client::{TonClientError, TonClientInterface};
+use crate::tl::{
+    InternalTransactionId, RawTransaction, TvmCell, TvmList, TvmNumber, TvmSlice, TvmStackEntry,
+    TvmTuple,
+};
+use crate::types::TonMethodId;
+
+pub mod proto {
+    tonic::include_proto!("tonlib.v1");
+}
+
+mod toncenter;
+use proto::stack_entry::Entry;
+use proto::ton_service_server::{TonService, TonServiceServer};
+pub use toncenter::*;
+
+/// Number of transactions returned by `GetTransactions` if the limit is not set.
+pub const DEFAULT_GRPC_TRANSACTIONS_LIMIT: u32 = 16;
+
+/// Serves calls of `TonService` with the client, e.g. to share one connection pool with
+/// services written in other languages.
+pub struct TonGrpcService<C> {
+    client: C,
+}
+
+impl<C: TonClientInterface + 'static> TonGrpcService<C> {
+    pub fn new(client: C) -> TonGrpcService<C> {
+        TonGrpcService { client }
+    }
+
+    pub fn into_server(self) -> TonServiceServer<TonGrpcService<C>> {
+        TonServiceServer::new(self)
+    }
+}
+
+#[tonic::async_trait]
+impl<C: TonClientInterface + 'static> TonService for TonGrpcService<C> {
+    async fn get_account_state(
+        &self,
+        request: Request<proto::GetAccountStateRequest>,
+    ) -> Result<Response<proto::AccountState>, Status> {
+        let address = parse_address(&request.get_ref().address)?;
+        let state = self
+            .client
+            .get_raw_account_state(&address)
+            .await
+            .map_err(client_error_status)?;
+        Ok(Response::new(proto::AccountState {
+            balance: state.balance,
+            code: state.code,
+            data: state.data,
+            last_transaction_id: Some(state.last_transaction_id.into()),
+            frozen_hash: state.frozen_hash,
+            sync_utime: state.sync_utime,
+        }))
+    }
+
+    async fn get_transactions(
+        &self,
+        request: Request<proto::GetTransactionsRequest>,
+    ) -> Result<Response<proto::Transactions>, Status> {
+        let request = request.into_inner();
+        let address = parse_address(&request.address)?;
+        let from = match request.from_transaction_id {
+            Some(id) => id.into(),
+            None => {
+                self.client
+                    .get_raw_account_state(&address)
+                    .await
+                    .map_err(client_error_status)?
+                    .last_transaction_id
+            }
+        };
+        let limit = match request.limit {
+            0 => DEFAULT_GRPC_TRANSACTIONS_LIMIT,
+            limit => limit,
+        };
+        let txs = self
+            .client
+            .get_raw_transactions_v2(&address, &from, limit as usize, false)
+            .await
+            .map_err(client_error_status)?;
+        Ok(Response::new(proto::Transactions {
+            transactions: txs.transactions.into_iter().map(Into::into).collect(),
+            previous_transaction_id: Some(txs.previous_transaction_id.into()),
+        }))
+    }
+
+    async fn run_get_method(
+        &self,
+        request: Request<proto::RunGetMethodRequest>,
+    ) -> Result<Response<proto::RunGetMethodResponse>, Status> {
+        let request = request.into_inner();
+        let address = parse_address(&request.address)?;
+        let method = match request.method.parse::<i32>() {
+            Ok(id) => TonMethodId::Number(id),
+            Err(_) => TonMethodId::from(request.method),
+        };
+        let stack = request
+            .stack
+            .into_iter()
+            .map(TvmStackEntry::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        let state = self
+            .client
+            .smc_load(&address)
+            .await
+            .map_err(client_error_status)?;
+        let result = state
+            .conn
+            .smc_run_get_method(state.id, &method, &stack)
+            .await;
+        if let Err(e) = state.conn.smc_forget(state.id).await {
+            log::warn!("Failed to forget smc {}: {}", state.id, e);
+        }
+        let result = result.map_err(client_error_status)?;
+        Ok(Response::new(proto::RunGetMethodResponse {
+            exit_code: result.exit_code,
+            gas_used: result.gas_used,
+            stack: result.stack.elements.iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn send_message(
+        &self,
+        request: Request<proto::SendMessageRequest>,
+    ) -> Result<Response<proto::SendMessageResponse>, Status> {
+        let hash = self
+            .client
+            .send_raw_message_return_hash(&request.get_ref().boc)
+            .await
+            .map_err(client_error_status)?;
+        Ok(Response::new(proto::SendMessageResponse { hash }))
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn parse_address(address: &str) -> Result<TonAddress, Status> {
+    address
+        .parse()
+        .map_err(|e| Status::invalid_argument(format!("Invalid address {}: {}", address, e)))
+}
+
+fn client_error_status(error: TonClientError) -> Status {
+    match &error {
+        TonClientError::TonAddressParseError(_) => Status::invalid_argument(error.to_string()),
+        TonClientError::QuotaExceeded { .. } => Status::resource_exhausted(error.to_string()),
+        TonClientError::UnsupportedByBackend { .. } => Status::unimplemented(error.to_string()),
+        TonClientError::TonlibError { .. } if error.is_retryable() => {
+            Status::unavailable(error.to_string())
+        }
+        TonClientError::TonlibError { .. } => Status::failed_precondition(error.to_string()),
+        _ => Status::internal(error.to_string()),
+    }
+}
+
+impl From<InternalTransactionId> for proto::TransactionId {
+    fn from(id: InternalTransactionId) -> Self {
+        proto::TransactionId {
+            lt: id.lt,
+            hash: id.hash,
+        }
+    }
+}
+
+impl From<proto::TransactionId> for InternalTransactionId {
+    fn from(id: proto::TransactionId) -> Self {
+        InternalTransactionId {
+            lt: id.lt,
+            hash: id.hash,
+        }
+    }
+}
+
+impl From<RawTransaction> for proto::Transaction {
+    fn from(tx: RawTransaction) -> Self {
+        proto::Transaction {
+            transaction_id: Some(tx.transaction_id.into()),
+            utime: tx.utime,
+            data: tx.data,
+            storage_fee: tx.storage_fee,
+            other_fee: tx.other_fee,
+        }
+    }
+}
+
+impl From<&TvmStackEntry> for proto::StackEntry {
+    fn from(entry: &TvmStackEntry) -> Self {
+        let entry = match entry {
+            TvmStackEntry::Number { number } => Some(Entry::Number(number.number.clone())),
+            TvmStackEntry::Cell { cell } => Some(Entry::Cell(cell.bytes.clone())),
+            TvmStackEntry::Slice { slice } => Some(Entry::Slice(slice.bytes.clone())),
+            TvmStackEntry::Tuple { tuple } => Some(Entry::Tuple(proto::Tuple {
+                elements: tuple.elements.iter().map(Into::into).collect(),
+            })),
+            TvmStackEntry::List { list } => Some(Entry::List(proto::Tuple {
+                elements: list.elements.iter().map(Into::into).collect(),
+            })),
+            TvmStackEntry::Unsupported {} => None,
+        };
+        proto::StackEntry { entry }
+    }
+}
+
+impl TryFrom<proto::StackEntry> for TvmStackEntry {
+    type Error = Status;
+
+    #[allow(clippy::result_large_err)]
+    fn try_from(entry: proto::StackEntry) -> Result<Self, Self::Error> {
+        let elements = |tuple: proto::Tuple| {
+            tuple
+                .elements
+                .into_iter()
+                .map(TvmStackEntry::try_from)
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let entry = match entry.entry {
+            Some(Entry::Number(number)) => TvmStackEntry::Number {
+                number: TvmNumber { number },
+            },
+            Some(Entry::Cell(bytes)) => TvmStackEntry::Cell {
+                cell: TvmCell { bytes },
+            },
+            Some(Entry::Slice(bytes)) => TvmStackEntry::Slice {
+                slice: TvmSlice { bytes },
+            },
+            Some(Entry::Tuple(tuple)) => TvmStackEntry::Tuple {
+                tuple: TvmTuple {
+                    elements: elements(tuple)?,
+                },
+            },
+            Some(Entry::List(list)) => TvmStackEntry::List {
+                list: TvmList {
+                    elements: elements(list)?,
+                },
+            },
+            None => return Err(Status::invalid_argument("Empty stack entry")),
+        };
+        Ok(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tonic::Code;
+
+    use crate::server::proto;
+    use crate::tl::{TvmCell, TvmNumber, TvmStackEntry, TvmTuple};
+
+    #[test]
+    fn test_stack_entry_conversion() -> anyhow::Result<()> {
+        let entry = TvmStackEntry::Tuple {
+            tuple: TvmTuple {
+                elements: vec![
+                    TvmStackEntry::Number {
+                        number: TvmNumber {
+                            number: "-42".to_string(),
+                        },
+                    },
+                    TvmStackEntry::Cell {
+                        cell: TvmCell {
+                            bytes: vec![1, 2, 3],
+                        },
+                    },
+                ],
+            },
+        };
+        let proto_entry = proto::StackEntry::from(&entry);
+        assert_eq!(TvmStackEntry::try_from(proto_entry)?, entry);
+
+        let empty = TvmStackEntry::try_from(proto::StackEntry { entry: None });
+        assert_eq!(empty.unwrap_err().code(), Code::InvalidArgument);
+        Ok(())
+    }
+}
@@ -0,0 +1,478 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use num_bigint::{BigInt, Sign};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::address::TonAddress;
+use crate::client::{TonClientError, TonClientInterface};
+use crate::tl::{
+    InternalTransactionId, RawFullAccountState, TvmCell, TvmNumber, TvmSlice, TvmStackEntry,
+};
+use crate::types::TonMethodId;
+
+pub const DEFAULT_TONCENTER_TRANSACTIONS_LIMIT: usize = 10;
+const TRANSACTIONS_PAGE_SIZE: usize = 16;
+
+#[derive(Error, Debug)]
+pub enum ToncenterError {
+    #[error("Unknown method {0}")]
+    UnknownMethod(String),
+
+    #[error("Invalid parameter {name}: {message}")]
+    InvalidParameter { name: &'static str, message: String },
+
+    #[error("{0}")]
+    TonClientError(#[from] TonClientError),
+}
+
+impl ToncenterError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ToncenterError::UnknownMethod(_) => StatusCode::NOT_FOUND,
+            ToncenterError::InvalidParameter { .. } => StatusCode::BAD_REQUEST,
+            ToncenterError::TonClientError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn invalid(name: &'static str, message: impl ToString) -> ToncenterError {
+        ToncenterError::InvalidParameter {
+            name,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Subset of toncenter v2 HTTP API backed by the client, so that toncenter clients can use a
+/// self-hosted instance.
+///
+/// Methods are served both as `/api/v2/<method>` (GET with query parameters or POST with JSON
+/// body) and via `/api/v2/jsonRPC`. Supported methods: `getAddressInformation`,
+/// `getAddressBalance`, `getAddressState`, `getMasterchainInfo`, `getTransactions`,
+/// `runGetMethod`, `sendBoc` and `sendBocReturnHash`.
+pub struct ToncenterApi<C> {
+    client: C,
+}
+
+#[derive(Deserialize)]
+struct JsonRpcRequest {
+    method: String,
+    #[serde(default)]
+    params: Value,
+    #[serde(default)]
+    id: Value,
+}
+
+impl<C: TonClientInterface + 'static> ToncenterApi<C> {
+    pub fn new(client: C) -> ToncenterApi<C> {
+        ToncenterApi { client }
+    }
+
+    /// Returns router, which can be served with `axum::serve`.
+    pub fn router(self) -> Router {
+        Router::new()
+            .route("/api/v2/jsonRPC", post(handle_json_rpc::<C>))
+            .route(
+                "/api/v2/:method",
+                get(handle_get::<C>).post(handle_post::<C>),
+            )
+            .with_state(Arc::new(self))
+    }
+
+    /// Calls the method with parameters given as JSON object and returns the `result` field
+    /// of the toncenter response.
+    pub async fn call(&self, method: &str, params: &Value) -> Result<Value, ToncenterError> {
+        match method {
+            "getAddressInformation" => {
+                let state = self.account_state(params).await?;
+                Ok(json!({
+                    "@type": "raw.fullAccountState",
+                    "balance": state.balance.to_string(),
+                    "code": STANDARD.encode(&state.code),
+                    "data": STANDARD.encode(&state.data),
+                    "last_transaction_id": transaction_id_json(&state.last_transaction_id),
+                    "block_id": state.block_id,
+                    "frozen_hash": STANDARD.encode(&state.frozen_hash),
+                    "sync_utime": state.sync_utime,
+                    "state": account_status(&state),
+                }))
+            }
+            "getAddressBalance" => Ok(json!(self.account_state(params).await?.balance.to_string())),
+            "getAddressState" => Ok(json!(account_status(&self.account_state(params).await?))),
+            "getMasterchainInfo" => {
+                let (_, info) = self.client.get_masterchain_info().await?;
+                Ok(json!({
+                    "@type": "blocks.masterchainInfo",
+                    "last": info.last,
+                    "state_root_hash": STANDARD.encode(&info.state_root_hash),
+                    "init": info.init,
+                }))
+            }
+            "getTransactions" => self.get_transactions(params).await,
+            "runGetMethod" => self.run_get_method(params).await,
+            "sendBoc" => {
+                let boc = bytes_param(params, "boc")?;
+                self.client.send_raw_message(&boc).await?;
+                Ok(json!({"@type": "ok"}))
+            }
+            "sendBocReturnHash" => {
+                let boc = bytes_param(params, "boc")?;
+                let hash = self.client.send_raw_message_return_hash(&boc).await?;
+                Ok(json!({"@type": "raw.extMessageInfo", "hash": STANDARD.encode(hash)}))
+            }
+            _ => Err(ToncenterError::UnknownMethod(method.to_string())),
+        }
+    }
+
+    async fn account_state(&self, params: &Value) -> Result<RawFullAccountState, ToncenterError> {
+        let address = address_param(params)?;
+        Ok(self.client.get_raw_account_state(&address).await?)
+    }
+
+    async fn get_transactions(&self, params: &Value) -> Result<Value, ToncenterError> {
+        let address = address_param(params)?;
+        let limit = match opt_str_param(params, "limit")? {
+            Some(limit) => limit
+                .parse::<usize>()
+                .map_err(|e| ToncenterError::invalid("limit", e))?,
+            None => DEFAULT_TONCENTER_TRANSACTIONS_LIMIT,
+        };
+        let to_lt = match opt_str_param(params, "to_lt")? {
+            Some(lt) => lt
+                .parse::<i64>()
+                .map_err(|e| ToncenterError::invalid("to_lt", e))?,
+            None => 0,
+        };
+        let mut from = match (opt_str_param(params, "lt")?, opt_str_param(params, "hash")?) {
+            (Some(lt), Some(hash)) => InternalTransactionId {
+                lt: lt.parse().map_err(|e| ToncenterError::invalid("lt", e))?,
+                hash: decode_hash(&hash).ok_or_else(|| ToncenterError::invalid("hash", hash))?,
+            },
+            _ => {
+                self.client
+                    .get_raw_account_state(&address)
+                    .await?
+                    .last_transaction_id
+            }
+        };
+        let mut txs = Vec::with_capacity(limit);
+        while txs.len() < limit && from.lt > to_lt {
+            let count = (limit - txs.len()).min(TRANSACTIONS_PAGE_SIZE);
+            let page = self
+                .client
+                .get_raw_transactions_v2(&address, &from, count, false)
+                .await?;
+            if page.transactions.is_empty() {
+                break;
+            }
+            txs.extend(
+                page.transactions
+                    .into_iter()
+                    .take_while(|tx| tx.transaction_id.lt > to_lt),
+            );
+            from = page.previous_transaction_id;
+        }
+        txs.truncate(limit);
+        Ok(json!(txs))
+    }
+
+    async fn run_get_method(&self, params: &Value) -> Result<Value, ToncenterError> {
+        let address = address_param(params)?;
+        let method = str_param(params, "method")?;
+        let method = match method.parse::<i32>() {
+            Ok(id) => TonMethodId::Number(id),
+            Err(_) => TonMethodId::from(method),
+        };
+        let stack = match params.get("stack") {
+            Some(Value::String(s)) => {
+                serde_json::from_str(s).map_err(|e| ToncenterError::invalid("stack", e))?
+            }
+            Some(stack) => stack.clone(),
+            None => json!([]),
+        };
+        let stack = stack
+            .as_array()
+            .ok_or_else(|| ToncenterError::invalid("stack", "not an array"))?
+            .iter()
+            .map(parse_stack_entry)
+            .collect::<Result<Vec<_>, _>>()?;
+        let state = self.client.smc_load(&address).await?;
+        let result = state
+            .conn
+            .smc_run_get_method(state.id, &method, &stack)
+            .await;
+        if let Err(e) = state.conn.smc_forget(state.id).await {
+            log::warn!("Failed to forget smc {}: {}", state.id, e);
+        }
+        let result = result?;
+        Ok(json!({
+            "@type": "smc.runResult",
+            "gas_used": result.gas_used,
+            "stack": result.stack.elements.iter().map(stack_entry_json).collect::<Vec<_>>(),
+            "exit_code": result.exit_code,
+        }))
+    }
+
+    async fn respond(&self, method: &str, params: &Value) -> Response {
+        let (status, body) = envelope(self.call(method, params).await);
+        (status, Json(body)).into_response()
+    }
+}
+
+async fn handle_get<C: TonClientInterface + 'static>(
+    State(api): State<Arc<ToncenterApi<C>>>,
+    Path(method): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    let params = params
+        .into_iter()
+        .map(|(k, v)| (k, Value::String(v)))
+        .collect();
+    api.respond(&method, &Value::Object(params)).await
+}
+
+async fn handle_post<C: TonClientInterface + 'static>(
+    State(api): State<Arc<ToncenterApi<C>>>,
+    Path(method): Path<String>,
+    Json(params): Json<Value>,
+) -> Response {
+    api.respond(&method, &params).await
+}
+
+async fn handle_json_rpc<C: TonClientInterface + 'static>(
+    State(api): State<Arc<ToncenterApi<C>>>,
+    Json(request): Json<JsonRpcRequest>,
+) -> Response {
+    let (status, mut body) = envelope(api.call(&request.method, &request.params).await);
+    body["jsonrpc"] = json!("2.0");
+    body["id"] = request.id;
+    (status, Json(body)).into_response()
+}
+
+fn envelope(result: Result<Value, ToncenterError>) -> (StatusCode, Value) {
+    match result {
+        Ok(result) => (StatusCode::OK, json!({"ok": true, "result": result})),
+        Err(e) => {
+            let status = e.status();
+            let body = json!({"ok": false, "error": e.to_string(), "code": status.as_u16()});
+            (status, body)
+        }
+    }
+}
+
+fn account_status(state: &RawFullAccountState) -> &'static str {
+    if !state.frozen_hash.is_empty() {
+        "frozen"
+    } else if state.code.is_empty() {
+        "uninitialized"
+    } else {
+        "active"
+    }
+}
+
+fn transaction_id_json(id: &InternalTransactionId) -> Value {
+    json!({
+        "@type": "internal.transactionId",
+        "lt": id.lt.to_string(),
+        "hash": STANDARD.encode(&id.hash),
+    })
+}
+
+fn opt_str_param(params: &Value, name: &'static str) -> Result<Option<String>, ToncenterError> {
+    match params.get(name) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s.clone())),
+        Some(Value::Number(n)) => Ok(Some(n.to_string())),
+        Some(v) => Err(ToncenterError::invalid(name, v)),
+    }
+}
+
+fn str_param(params: &Value, name: &'static str) -> Result<String, ToncenterError> {
+    opt_str_param(params, name)?.ok_or_else(|| ToncenterError::invalid(name, "missing"))
+}
+
+fn address_param(params: &Value) -> Result<TonAddress, ToncenterError> {
+    str_param(params, "address")?
+        .parse()
+        .map_err(|e| ToncenterError::invalid("address", e))
+}
+
+fn bytes_param(params: &Value, name: &'static str) -> Result<Vec<u8>, ToncenterError> {
+    STANDARD
+        .decode(str_param(params, name)?)
+        .map_err(|e| ToncenterError::invalid(name, e))
+}
+
+/// Decodes transaction hash given in base64 or hex.
+fn decode_hash(hash: &str) -> Option<Vec<u8>> {
+    STANDARD
+        .decode(hash)
+        .ok()
+        .filter(|h| h.len() == 32)
+        .or_else(|| hex::decode(hash).ok())
+}
+
+/// Parses stack entry given as `[type, value]`, e.g. `["num", "0x2a"]` or `["cell", <BoC>]`.
+fn parse_stack_entry(entry: &Value) -> Result<TvmStackEntry, ToncenterError> {
+    let invalid = || ToncenterError::invalid("stack", entry);
+    let (kind, value) = match entry.as_array().map(|a| a.as_slice()) {
+        Some([Value::String(kind), value]) => (kind.as_str(), value),
+        _ => return Err(invalid()),
+    };
+    let bytes = || match value {
+        Value::String(s) => STANDARD.decode(s).map_err(|_| invalid()),
+        Value::Object(o) => o
+            .get("bytes")
+            .and_then(Value::as_str)
+            .and_then(|s| STANDARD.decode(s).ok())
+            .ok_or_else(invalid),
+        _ => Err(invalid()),
+    };
+    let entry = match kind {
+        "num" | "number" | "int" => {
+            let number = match value {
+                Value::String(s) => parse_number(s).ok_or_else(invalid)?,
+                Value::Number(n) => n.to_string(),
+                _ => return Err(invalid()),
+            };
+            TvmStackEntry::Number {
+                number: TvmNumber { number },
+            }
+        }
+        "cell" | "tvm.Cell" => TvmStackEntry::Cell {
+            cell: TvmCell { bytes: bytes()? },
+        },
+        "slice" | "tvm.Slice" => TvmStackEntry::Slice {
+            slice: TvmSlice { bytes: bytes()? },
+        },
+        _ => return Err(invalid()),
+    };
+    Ok(entry)
+}
+
+/// Converts decimal or `0x` prefixed hexadecimal number to decimal.
+fn parse_number(number: &str) -> Option<String> {
+    let (negative, digits) = match number.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, number),
+    };
+    let n = match digits.strip_prefix("0x") {
+        Some(hex) => BigInt::parse_bytes(hex.as_bytes(), 16)?,
+        None => BigInt::parse_bytes(digits.as_bytes(), 10)?,
+    };
+    Some(if negative { -n } else { n }.to_string())
+}
+
+fn stack_entry_json(entry: &TvmStackEntry) -> Value {
+    match entry {
+        TvmStackEntry::Number { number } => match number.number.parse::<BigInt>() {
+            Ok(n) if n.sign() == Sign::Minus => json!(["num", format!("-0x{:x}", -n)]),
+            Ok(n) => json!(["num", format!("0x{:x}", n)]),
+            Err(_) => json!(["num", number.number]),
+        },
+        TvmStackEntry::Cell { cell } => json!(["cell", {"bytes": STANDARD.encode(&cell.bytes)}]),
+        TvmStackEntry::Slice { slice } => {
+            json!(["slice", {"bytes": STANDARD.encode(&slice.bytes)}])
+        }
+        TvmStackEntry::Tuple { tuple } => {
+            json!(["tuple", {"@type": "tvm.tuple", "elements": tuple.elements}])
+        }
+        TvmStackEntry::List { list } => {
+            json!(["list", {"@type": "tvm.list", "elements": list.elements}])
+        }
+        TvmStackEntry::Unsupported {} => json!(["unsupported", null]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use serde_json::json;
+
+    use crate::client::{TonClientError, TonClientInterface, TonConnection};
+    use crate::server::toncenter::{parse_stack_entry, stack_entry_json};
+    use crate::server::{ToncenterApi, ToncenterError};
+    use crate::tl::{TonFunction, TonResult, TvmNumber, TvmStackEntry};
+
+    struct FailingClient;
+
+    #[async_trait]
+    impl TonClientInterface for FailingClient {
+        async fn get_connection(&self) -> Result<TonConnection, TonClientError> {
+            Err(TonClientError::InternalError("No connection".to_string()))
+        }
+
+        async fn invoke_on_connection(
+            &self,
+            _function: &TonFunction,
+        ) -> Result<(TonConnection, TonResult), TonClientError> {
+            Err(TonClientError::InternalError("Endpoint".to_string()))
+        }
+    }
+
+    #[test]
+    fn test_stack_entry_json() -> anyhow::Result<()> {
+        let entry = parse_stack_entry(&json!(["num", "-0x2a"]))?;
+        let expected = TvmStackEntry::Number {
+            number: TvmNumber {
+                number: "-42".to_string(),
+            },
+        };
+        assert_eq!(entry, expected);
+        assert_eq!(parse_stack_entry(&json!(["num", 42]))?, {
+            TvmStackEntry::Number {
+                number: TvmNumber {
+                    number: "42".to_string(),
+                },
+            }
+        });
+        assert_eq!(stack_entry_json(&expected), json!(["num", "-0x2a"]));
+
+        let cell = parse_stack_entry(&json!(["tvm.Cell", "AQID"]))?;
+        assert_eq!(stack_entry_json(&cell), json!(["cell", {"bytes": "AQID"}]));
+        assert!(parse_stack_entry(&json!(["num"])).is_err());
+        assert!(parse_stack_entry(&json!(["dict", "AQID"])).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_toncenter_api_errors() {
+        let api = ToncenterApi::new(FailingClient);
+        let r = api.call("getBlockHeader", &json!({})).await;
+        assert!(matches!(r, Err(ToncenterError::UnknownMethod(_))));
+        let r = api.call("getAddressBalance", &json!({})).await;
+        assert!(matches!(
+            r,
+            Err(ToncenterError::InvalidParameter {
+                name: "address",
+                ..
+            })
+        ));
+        let r = api
+            .call("getAddressBalance", &json!({"address": "invalid"}))
+            .await;
+        assert!(matches!(
+            r,
+            Err(ToncenterError::InvalidParameter {
+                name: "address",
+                ..
+            })
+        ));
+        let r = api
+            .call(
+                "getAddressBalance",
+                &json!({"address": "EQDk2VTvn04SUKJrW7rXahzdF8_Qi6utb0wj43InCu9vdjrR"}),
+            )
+            .await;
+        assert!(matches!(r, Err(ToncenterError::TonClientError(_))));
+    }
+}
@@ -0,0 +1,179 @@
+//! Helpers for obtaining test TON in testnet (or local network) from integration tests and examples.
+
+use std::env;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub use error::*;
+use nacl::sign::generate_keypair;
+use num_bigint::BigUint;
+use rand::RngCore;
+
+use crate::address::TonAddress;
+use crate::cell::BagOfCells;
+use crate::client::{TonClient, TonClientInterface};
+use crate::contract::{TonContractFactory, TonWalletContract};
+use crate::message::TransferMessage;
+use crate::mnemonic::{KeyPair, Mnemonic};
+use crate::wallet::{TonWallet, WalletVersion};
+
+mod error;
+
+/// Environment variable holding space separated mnemonic of the prefunded giver wallet (V4R2).
+pub const GIVER_MNEMONIC_ENV: &str = "TONLIB_TESTNET_GIVER_MNEMONIC";
+
+/// Amount sent by `fund_from_faucet`, 1 TON.
+pub const DEFAULT_FAUCET_AMOUNT: u64 = 1_000_000_000;
+
+const MESSAGE_TTL_SECS: u32 = 60;
+const FUNDING_TIMEOUT_SECS: u64 = 120;
+const FUNDING_POLL_INTERVAL_MS: u64 = 2000;
+
+/// Prefunded wallet used to transfer test TON to other accounts.
+pub struct TestnetGiver {
+    factory: TonContractFactory,
+    wallet: TonWallet,
+}
+
+impl TestnetGiver {
+    pub fn new(factory: &TonContractFactory, wallet: &TonWallet) -> TestnetGiver {
+        TestnetGiver {
+            factory: factory.clone(),
+            wallet: wallet.clone(),
+        }
+    }
+
+    /// Creates giver from V4R2 wallet defined by mnemonic in `TONLIB_TESTNET_GIVER_MNEMONIC`.
+    pub fn from_env(factory: &TonContractFactory) -> Result<TestnetGiver, FaucetError> {
+        let mnemonic_str = env::var(GIVER_MNEMONIC_ENV)
+            .map_err(|_| FaucetError::MissingGiverMnemonic(GIVER_MNEMONIC_ENV))?;
+        let mnemonic = Mnemonic::from_str(&mnemonic_str, &None)?;
+        let key_pair = mnemonic.to_key_pair()?;
+        let wallet = TonWallet::derive_default(WalletVersion::V4R2, &key_pair)?;
+        Ok(Self::new(factory, &wallet))
+    }
+
+    pub fn address(&self) -> &TonAddress {
+        &self.wallet.address
+    }
+
+    /// Sends `amount` nanotons to `dest` as non-bounceable message and returns hash of the external message.
+    pub async fn fund(&self, dest: &TonAddress, amount: &BigUint) -> Result<Vec<u8>, FaucetError> {
+        let seqno = self
+            .factory
+            .get_contract(&self.wallet.address)
+            .seqno()
+            .await?;
+        let transfer = TransferMessage::new(dest, amount)
+            .with_bounce(false)
+            .build()?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| FaucetError::InternalError(e.to_string()))?
+            .as_secs() as u32;
+        let message = self.wallet.create_external_message(
+            now + MESSAGE_TTL_SECS,
+            seqno,
+            vec![transfer.to_arc()],
+            false,
+        )?;
+        let boc = BagOfCells::from_root(message).serialize(true)?;
+        let hash = self
+            .factory
+            .client()
+            .send_raw_message_return_hash(boc.as_slice())
+            .await?;
+        log::info!(
+            "Sent {} nanotons from giver {} to {}",
+            amount,
+            self.wallet.address,
+            dest
+        );
+        Ok(hash)
+    }
+
+    /// Funds `address` and waits until its balance becomes positive.
+    pub async fn fund_and_wait(
+        &self,
+        address: &TonAddress,
+        amount: &BigUint,
+    ) -> Result<(), FaucetError> {
+        let client = self.factory.client();
+        let initial_balance = client.get_raw_account_state(address).await?.balance;
+        self.fund(address, amount).await?;
+        wait_balance_change(client, address, initial_balance).await
+    }
+
+    /// Creates a new wallet with random keys and funds it.
+    ///
+    /// The wallet is not deployed, it will be deployed by the first outgoing message with state init.
+    pub async fn new_funded_wallet(
+        &self,
+        version: WalletVersion,
+        amount: &BigUint,
+    ) -> Result<TonWallet, FaucetError> {
+        let key_pair = random_key_pair();
+        let wallet = TonWallet::derive_default(version, &key_pair)?;
+        self.fund_and_wait(&wallet.address, amount).await?;
+        Ok(wallet)
+    }
+}
+
+/// Sends `DEFAULT_FAUCET_AMOUNT` to `address` from the giver configured via `TONLIB_TESTNET_GIVER_MNEMONIC`
+/// and waits until the funds arrive.
+pub async fn fund_from_faucet(client: &TonClient, address: &TonAddress) -> Result<(), FaucetError> {
+    let factory = TonContractFactory::builder(client).build().await?;
+    let giver = TestnetGiver::from_env(&factory)?;
+    giver
+        .fund_and_wait(address, &BigUint::from(DEFAULT_FAUCET_AMOUNT))
+        .await
+}
+
+pub fn random_key_pair() -> KeyPair {
+    let mut seed = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut seed);
+    let key_pair = generate_keypair(&seed);
+    KeyPair {
+        public_key: key_pair.pkey.to_vec(),
+        secret_key: key_pair.skey.to_vec(),
+    }
+}
+
+async fn wait_balance_change(
+    client: &TonClient,
+    address: &TonAddress,
+    initial_balance: i64,
+) -> Result<(), FaucetError> {
+    let started = SystemTime::now();
+    loop {
+        let balance = client.get_raw_account_state(address).await?.balance;
+        if balance > initial_balance {
+            return Ok(());
+        }
+        let elapsed = started.elapsed().unwrap_or_default();
+        if elapsed > Duration::from_secs(FUNDING_TIMEOUT_SECS) {
+            return Err(FaucetError::FundingTimeout {
+                address: address.clone(),
+                timeout_secs: FUNDING_TIMEOUT_SECS,
+            });
+        }
+        tokio::time::sleep(Duration::from_millis(FUNDING_POLL_INTERVAL_MS)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::random_key_pair;
+    use crate::wallet::{TonWallet, WalletVersion};
+
+    #[test]
+    fn test_random_key_pair() -> anyhow::Result<()> {
+        let first = random_key_pair();
+        let second = random_key_pair();
+        assert_eq!(first.public_key.len(), 32);
+        assert_eq!(first.secret_key.len(), 64);
+        assert_ne!(first.public_key, second.public_key);
+        let wallet = TonWallet::derive_default(WalletVersion::V4R2, &first)?;
+        assert_eq!(wallet.address.workchain, 0);
+        Ok(())
+    }
+}
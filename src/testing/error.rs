@@ -0,0 +1,44 @@
+use thiserror::Error;
+
+use crate::address::TonAddress;
+use crate::cell::TonCellError;
+use crate::client::TonClientError;
+use crate::contract::TonContractError;
+use crate::message::TonMessageError;
+use crate::mnemonic::MnemonicError;
+
+#[derive(Error, Debug)]
+pub enum FaucetError {
+    #[error("Giver mnemonic is not set (env variable: {0})")]
+    MissingGiverMnemonic(&'static str),
+
+    #[error("Funding timed out (address: {address}, timeout: {timeout_secs}s)")]
+    FundingTimeout {
+        address: TonAddress,
+        timeout_secs: u64,
+    },
+
+    #[error("Internal error ({0})")]
+    InternalError(String),
+
+    #[error("MnemonicError ({0})")]
+    MnemonicError(#[from] MnemonicError),
+
+    #[error("TonCellError ({0})")]
+    TonCellError(#[from] TonCellError),
+
+    #[error("TonMessageError ({0})")]
+    TonMessageError(#[from] TonMessageError),
+
+    #[error("TonContractError ({0})")]
+    TonContractError(Box<TonContractError>),
+
+    #[error("TonClientError ({0})")]
+    TonClientError(#[from] TonClientError),
+}
+
+impl From<TonContractError> for FaucetError {
+    fn from(error: TonContractError) -> Self {
+        FaucetError::TonContractError(Box::new(error))
+    }
+}
@@ -1,7 +1,12 @@
+#![cfg_attr(not(test), deny(clippy::unwrap_used, clippy::expect_used))]
+
+mod binary;
 mod error;
 mod function;
 mod notification;
 mod result;
+mod scheme;
+mod scheme_parser;
 mod serial;
 mod stack;
 mod types;
@@ -10,10 +15,12 @@ use std::ffi::{c_char, CStr};
 
 use base64::engine::general_purpose::STANDARD;
 use base64_serde::base64_serde_type;
+pub use binary::*;
 pub use error::*;
 pub use function::*;
 pub use notification::*;
 pub use result::*;
+pub use scheme::*;
 pub use stack::*;
 use tonlib_sys::*;
 pub use types::*;
@@ -0,0 +1,500 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use lazy_static::lazy_static;
+use serde_json::{Map, Number, Value};
+
+use crate::tl::function::TonFunction;
+use crate::tl::result::TonResult;
+use crate::tl::scheme::{combinator_id, TlCombinator, TlScheme, TlTypeRef, TONLIB_API_SCHEME};
+use crate::tl::TlError;
+
+lazy_static! {
+    static ref BOOL_TRUE_ID: u32 = combinator_id("boolTrue = Bool");
+    static ref BOOL_FALSE_ID: u32 = combinator_id("boolFalse = Bool");
+}
+
+const TYPE_TAG: &str = "@type";
+/// Maximum nesting of objects accepted by the decoder.
+const MAX_DECODE_DEPTH: usize = 64;
+
+/// Serializes function into TL binary representation using the bundled tonlib scheme.
+///
+/// Note that `tonlib_client_json_*` interface accepts JSON only, so binary form is intended for
+/// persisting and transferring requests outside of tonlib.
+pub fn serialize_function_binary(function: &TonFunction) -> Result<Vec<u8>, TlError> {
+    let value = serde_json::to_value(function)?;
+    TONLIB_API_SCHEME.encode_boxed(&value)
+}
+
+/// Deserializes result from TL binary representation using the bundled tonlib scheme.
+pub fn deserialize_result_binary(bytes: &[u8]) -> Result<TonResult, TlError> {
+    let value = TONLIB_API_SCHEME.decode_boxed(bytes)?;
+    let result = serde_json::from_value(value)?;
+    Ok(result)
+}
+
+impl TlScheme {
+    /// Encodes JSON representation of TL object (tagged with `@type`) into boxed binary form.
+    pub fn encode_boxed(&self, value: &Value) -> Result<Vec<u8>, TlError> {
+        let mut writer = TlWriter {
+            scheme: self,
+            buffer: vec![],
+        };
+        writer.write_boxed(value, None)?;
+        Ok(writer.buffer)
+    }
+
+    /// Decodes boxed binary TL object into its JSON representation (tagged with `@type`).
+    pub fn decode_boxed(&self, bytes: &[u8]) -> Result<Value, TlError> {
+        let mut reader = TlReader {
+            scheme: self,
+            data: bytes,
+            pos: 0,
+            depth: 0,
+        };
+        let value = reader.read_boxed(None)?;
+        if reader.pos != bytes.len() {
+            return Err(codec_error(format!(
+                "{} trailing bytes after object",
+                bytes.len() - reader.pos
+            )));
+        }
+        Ok(value)
+    }
+}
+
+fn codec_error<S: ToString>(message: S) -> TlError {
+    TlError::BinaryCodecError(message.to_string())
+}
+
+fn check_result_type(combinator: &TlCombinator, expected: Option<&str>) -> Result<(), TlError> {
+    match expected {
+        Some("Object") | Some("Function") | None => Ok(()),
+        Some(t) if t == combinator.result_type => Ok(()),
+        Some(t) => Err(codec_error(format!(
+            "Constructor {} is not of type {}",
+            combinator.name, t
+        ))),
+    }
+}
+
+fn flag_is_set(object: &Map<String, Value>, field: &str, bit: u32) -> Result<bool, TlError> {
+    let mask = object
+        .get(field)
+        .and_then(Value::as_u64)
+        .map(|v| v as u32)
+        .ok_or_else(|| codec_error(format!("Missing flags field {}", field)))?;
+    let flag = 1u32
+        .checked_shl(bit)
+        .ok_or_else(|| codec_error(format!("Flag bit {} of {} is out of range", bit, field)))?;
+    Ok(mask & flag != 0)
+}
+
+struct TlWriter<'a> {
+    scheme: &'a TlScheme,
+    buffer: Vec<u8>,
+}
+
+impl TlWriter<'_> {
+    fn write_boxed(&mut self, value: &Value, expected_type: Option<&str>) -> Result<(), TlError> {
+        let name = value
+            .get(TYPE_TAG)
+            .and_then(Value::as_str)
+            .ok_or_else(|| codec_error(format!("Missing {} in {}", TYPE_TAG, value)))?;
+        let combinator = self
+            .scheme
+            .get_by_name(name)
+            .ok_or_else(|| codec_error(format!("Unknown constructor {}", name)))?;
+        check_result_type(combinator, expected_type)?;
+        self.buffer.extend_from_slice(&combinator.id.to_le_bytes());
+        self.write_fields(combinator, value)
+    }
+
+    fn write_bare(&mut self, value: &Value, name: &str) -> Result<(), TlError> {
+        let combinator = self
+            .scheme
+            .get_by_name(name)
+            .ok_or_else(|| codec_error(format!("Unknown constructor {}", name)))?;
+        self.write_fields(combinator, value)
+    }
+
+    fn write_fields(&mut self, combinator: &TlCombinator, value: &Value) -> Result<(), TlError> {
+        let object = value
+            .as_object()
+            .ok_or_else(|| codec_error(format!("Expected object {}", combinator.name)))?;
+        for field in combinator.fields.iter() {
+            let type_ref = match &field.type_ref {
+                TlTypeRef::Conditional {
+                    field: flags,
+                    bit,
+                    inner,
+                } => {
+                    if !flag_is_set(object, flags, *bit)? {
+                        continue;
+                    }
+                    inner.as_ref()
+                }
+                type_ref => type_ref,
+            };
+            let field_value = object.get(&field.name).ok_or_else(|| {
+                codec_error(format!(
+                    "Missing field {} of {}",
+                    field.name, combinator.name
+                ))
+            })?;
+            self.write_value(field_value, type_ref)?;
+        }
+        Ok(())
+    }
+
+    fn write_value(&mut self, value: &Value, type_ref: &TlTypeRef) -> Result<(), TlError> {
+        match type_ref {
+            TlTypeRef::Int32 => {
+                let v = as_i64(value)?;
+                let v = i32::try_from(v).map_err(|e| codec_error(format!("{}: {}", v, e)))?;
+                self.buffer.extend_from_slice(&v.to_le_bytes());
+            }
+            TlTypeRef::Nat => {
+                let v = as_i64(value)?;
+                let v = u32::try_from(v).map_err(|e| codec_error(format!("{}: {}", v, e)))?;
+                self.buffer.extend_from_slice(&v.to_le_bytes());
+            }
+            TlTypeRef::Int53 | TlTypeRef::Int64 => {
+                let v = as_i64(value)?;
+                self.buffer.extend_from_slice(&v.to_le_bytes());
+            }
+            TlTypeRef::Double => {
+                let v = value
+                    .as_f64()
+                    .ok_or_else(|| codec_error(format!("Expected double, got {}", value)))?;
+                self.buffer.extend_from_slice(&v.to_le_bytes());
+            }
+            TlTypeRef::Int256 => {
+                let bytes = as_base64(value)?;
+                if bytes.len() != 32 {
+                    return Err(codec_error(format!(
+                        "Expected 32 bytes for int256, got {}",
+                        bytes.len()
+                    )));
+                }
+                self.buffer.extend_from_slice(&bytes);
+            }
+            TlTypeRef::Bool => {
+                let v = value
+                    .as_bool()
+                    .ok_or_else(|| codec_error(format!("Expected bool, got {}", value)))?;
+                let id = if v { *BOOL_TRUE_ID } else { *BOOL_FALSE_ID };
+                self.buffer.extend_from_slice(&id.to_le_bytes());
+            }
+            TlTypeRef::String => {
+                let v = value
+                    .as_str()
+                    .ok_or_else(|| codec_error(format!("Expected string, got {}", value)))?;
+                self.write_bytes(v.as_bytes());
+            }
+            TlTypeRef::Bytes => {
+                let bytes = as_base64(value)?;
+                self.write_bytes(&bytes);
+            }
+            TlTypeRef::Vector(inner) => {
+                let items = value
+                    .as_array()
+                    .ok_or_else(|| codec_error(format!("Expected array, got {}", value)))?;
+                self.buffer
+                    .extend_from_slice(&(items.len() as u32).to_le_bytes());
+                for item in items {
+                    self.write_value(item, inner)?;
+                }
+            }
+            TlTypeRef::Bare(name) => self.write_bare(value, name)?,
+            TlTypeRef::Boxed(name) => self.write_boxed(value, Some(name))?,
+            TlTypeRef::Conditional { .. } => {
+                return Err(codec_error("Nested conditional fields are not supported"))
+            }
+        }
+        Ok(())
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        let header_len = if bytes.len() < 254 {
+            self.buffer.push(bytes.len() as u8);
+            1
+        } else {
+            self.buffer.push(254);
+            self.buffer
+                .extend_from_slice(&(bytes.len() as u32).to_le_bytes()[0..3]);
+            4
+        };
+        self.buffer.extend_from_slice(bytes);
+        let padding = (4 - (header_len + bytes.len()) % 4) % 4;
+        self.buffer.resize(self.buffer.len() + padding, 0);
+    }
+}
+
+fn as_i64(value: &Value) -> Result<i64, TlError> {
+    match value {
+        Value::Number(n) => n.as_i64(),
+        Value::String(s) => s.parse::<i64>().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| codec_error(format!("Expected integer, got {}", value)))
+}
+
+fn as_base64(value: &Value) -> Result<Vec<u8>, TlError> {
+    let s = value
+        .as_str()
+        .ok_or_else(|| codec_error(format!("Expected base64 string, got {}", value)))?;
+    STANDARD.decode(s).map_err(codec_error)
+}
+
+struct TlReader<'a> {
+    scheme: &'a TlScheme,
+    data: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl TlReader<'_> {
+    fn read_boxed(&mut self, expected_type: Option<&str>) -> Result<Value, TlError> {
+        let id = self.read_u32()?;
+        let combinator = self
+            .scheme
+            .get_by_id(id)
+            .ok_or_else(|| codec_error(format!("Unknown constructor id {:08x}", id)))?;
+        check_result_type(combinator, expected_type)?;
+        self.read_fields(combinator)
+    }
+
+    fn read_bare(&mut self, name: &str) -> Result<Value, TlError> {
+        let combinator = self
+            .scheme
+            .get_by_name(name)
+            .ok_or_else(|| codec_error(format!("Unknown constructor {}", name)))?;
+        self.read_fields(combinator)
+    }
+
+    fn read_fields(&mut self, combinator: &TlCombinator) -> Result<Value, TlError> {
+        if self.depth >= MAX_DECODE_DEPTH {
+            return Err(codec_error(format!(
+                "Object nesting exceeds {}",
+                MAX_DECODE_DEPTH
+            )));
+        }
+        self.depth += 1;
+        let object = self.read_object(combinator);
+        self.depth -= 1;
+        object
+    }
+
+    fn read_object(&mut self, combinator: &TlCombinator) -> Result<Value, TlError> {
+        let mut object = Map::new();
+        object.insert(TYPE_TAG.to_string(), Value::from(combinator.name.as_str()));
+        for field in combinator.fields.iter() {
+            let type_ref = match &field.type_ref {
+                TlTypeRef::Conditional {
+                    field: flags,
+                    bit,
+                    inner,
+                } => {
+                    if !flag_is_set(&object, flags, *bit)? {
+                        continue;
+                    }
+                    inner.as_ref()
+                }
+                type_ref => type_ref,
+            };
+            let value = self.read_value(type_ref)?;
+            object.insert(field.name.clone(), value);
+        }
+        Ok(Value::Object(object))
+    }
+
+    fn read_value(&mut self, type_ref: &TlTypeRef) -> Result<Value, TlError> {
+        let value = match type_ref {
+            TlTypeRef::Int32 => Value::from(i32::from_le_bytes(self.read_array()?)),
+            TlTypeRef::Nat => Value::from(self.read_u32()?),
+            TlTypeRef::Int53 => Value::from(i64::from_le_bytes(self.read_array()?)),
+            // tonlib json represents int64 as strings
+            TlTypeRef::Int64 => Value::from(i64::from_le_bytes(self.read_array()?).to_string()),
+            TlTypeRef::Double => {
+                let v = f64::from_le_bytes(self.read_array()?);
+                Number::from_f64(v)
+                    .map(Value::Number)
+                    .ok_or_else(|| codec_error(format!("Invalid double {}", v)))?
+            }
+            TlTypeRef::Int256 => Value::from(STANDARD.encode(self.read_slice(32)?)),
+            TlTypeRef::Bool => {
+                let id = self.read_u32()?;
+                if id == *BOOL_TRUE_ID {
+                    Value::Bool(true)
+                } else if id == *BOOL_FALSE_ID {
+                    Value::Bool(false)
+                } else {
+                    return Err(codec_error(format!("Invalid Bool constructor {:08x}", id)));
+                }
+            }
+            TlTypeRef::String => {
+                let bytes = self.read_bytes()?;
+                let s = String::from_utf8(bytes).map_err(codec_error)?;
+                Value::from(s)
+            }
+            TlTypeRef::Bytes => Value::from(STANDARD.encode(self.read_bytes()?)),
+            TlTypeRef::Vector(inner) => {
+                let len = self.read_u32()? as usize;
+                let mut items = Vec::with_capacity(len.min(self.data.len() - self.pos));
+                for _ in 0..len {
+                    items.push(self.read_value(inner)?);
+                }
+                Value::Array(items)
+            }
+            TlTypeRef::Bare(name) => self.read_bare(name)?,
+            TlTypeRef::Boxed(name) => self.read_boxed(Some(name))?,
+            TlTypeRef::Conditional { .. } => {
+                return Err(codec_error("Nested conditional fields are not supported"))
+            }
+        };
+        Ok(value)
+    }
+
+    fn read_slice(&mut self, len: usize) -> Result<&[u8], TlError> {
+        let end = self.pos + len;
+        if end > self.data.len() {
+            return Err(codec_error(format!(
+                "Unexpected end of data: need {} bytes at {}, total {}",
+                len,
+                self.pos,
+                self.data.len()
+            )));
+        }
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], TlError> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.read_slice(N)?);
+        Ok(array)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, TlError> {
+        Ok(u32::from_le_bytes(self.read_array()?))
+    }
+
+    fn read_bytes(&mut self) -> Result<Vec<u8>, TlError> {
+        let first = self.read_slice(1)?[0];
+        let (header_len, len) = if first < 254 {
+            (1, first as usize)
+        } else {
+            let len_bytes = self.read_slice(3)?;
+            let len = u32::from_le_bytes([len_bytes[0], len_bytes[1], len_bytes[2], 0]);
+            (4, len as usize)
+        };
+        let bytes = self.read_slice(len)?.to_vec();
+        let padding = (4 - (header_len + len) % 4) % 4;
+        self.read_slice(padding)?;
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::tl::binary::{deserialize_result_binary, serialize_function_binary};
+    use crate::tl::scheme::{TlScheme, TONLIB_API_SCHEME};
+    use crate::tl::{BlockId, BlockIdExt, TonFunction, TonResult};
+
+    #[test]
+    fn it_serializes_function_binary() {
+        let func = TonFunction::SetLogVerbosityLevel {
+            new_verbosity_level: 0x01020304,
+        };
+        let bytes = serialize_function_binary(&func).unwrap();
+        let id = TONLIB_API_SCHEME
+            .get_by_name("setLogVerbosityLevel")
+            .unwrap()
+            .id;
+        let mut expected = id.to_le_bytes().to_vec();
+        expected.extend_from_slice(&[4, 3, 2, 1]);
+        assert_eq!(bytes, expected);
+    }
+
+    #[test]
+    fn it_roundtrips_function_binary() {
+        let func = TonFunction::BlocksLookupBlock {
+            mode: 1,
+            id: BlockId {
+                workchain: 0,
+                shard: i64::MIN,
+                seqno: 42000000,
+            },
+            lt: 36046846000003,
+            utime: 0,
+        };
+        let bytes = serialize_function_binary(&func).unwrap();
+        let value = TONLIB_API_SCHEME.decode_boxed(&bytes).unwrap();
+        assert_eq!(value["@type"], "blocks.lookupBlock");
+        assert_eq!(value["id"]["shard"], "-9223372036854775808");
+        assert_eq!(value["lt"], "36046846000003");
+        assert_eq!(value["utime"], 0);
+        assert_eq!(
+            TONLIB_API_SCHEME.encode_boxed(&value).unwrap(),
+            bytes,
+            "re-encoded bytes must match"
+        );
+    }
+
+    #[test]
+    fn it_deserializes_result_binary() {
+        let value = json!({
+            "@type": "ton.blockIdExt",
+            "workchain": -1,
+            "shard": "-9223372036854775808",
+            "seqno": 34000000,
+            "root_hash": "AQIDBAUGBwgJCgsMDQ4PEBESExQVFhcYGRobHB0eHyA=",
+            "file_hash": "AQIDBAUGBwgJCgsMDQ4PEBESExQVFhcYGRobHB0eHyA=",
+        });
+        let bytes = TONLIB_API_SCHEME.encode_boxed(&value).unwrap();
+        // id + workchain + shard + seqno + 2 * (1 byte length + 32 bytes + 3 bytes padding)
+        assert_eq!(bytes.len(), 4 + 4 + 8 + 4 + 2 * 36);
+        match deserialize_result_binary(&bytes).unwrap() {
+            TonResult::BlockIdExt(block_id) => assert_eq!(
+                block_id,
+                BlockIdExt {
+                    workchain: -1,
+                    shard: i64::MIN,
+                    seqno: 34000000,
+                    root_hash: "AQIDBAUGBwgJCgsMDQ4PEBESExQVFhcYGRobHB0eHyA=".to_string(),
+                    file_hash: "AQIDBAUGBwgJCgsMDQ4PEBESExQVFhcYGRobHB0eHyA=".to_string(),
+                }
+            ),
+            r => panic!("Unexpected result: {:?}", r),
+        }
+    }
+
+    #[test]
+    fn it_rejects_truncated_data() {
+        let func = TonFunction::SetLogVerbosityLevel {
+            new_verbosity_level: 1,
+        };
+        let bytes = serialize_function_binary(&func).unwrap();
+        assert!(TONLIB_API_SCHEME
+            .decode_boxed(&bytes[0..bytes.len() - 1])
+            .is_err());
+    }
+
+    #[test]
+    fn it_limits_decoding_depth() {
+        let scheme = TlScheme::parse("nested next:Node = Node; leaf = Node;").unwrap();
+        let nested = scheme.get_by_name("nested").unwrap().id.to_le_bytes();
+        let leaf = scheme.get_by_name("leaf").unwrap().id.to_le_bytes();
+        let encode = |depth: usize| {
+            let mut bytes = nested.repeat(depth);
+            bytes.extend_from_slice(&leaf);
+            bytes
+        };
+        assert!(scheme.decode_boxed(&encode(10)).is_ok());
+        assert!(scheme.decode_boxed(&encode(100_000)).is_err());
+    }
+}
@@ -43,6 +43,12 @@ pub enum TlError {
 
     #[error("CString is null ({0})")]
     NulError(#[from] NulError),
+
+    #[error("TL scheme error ({0})")]
+    SchemeError(String),
+
+    #[error("TL binary codec error ({0})")]
+    BinaryCodecError(String),
 }
 
 #[derive(Error, Debug)]
@@ -110,6 +110,19 @@ pub enum TonFunction {
         account_address: AccountAddress,
     },
 
+    // tonlib_api.tl, line 289
+    #[serde(rename = "getAccountStateByTransaction")]
+    GetAccountStateByTransaction {
+        account_address: AccountAddress,
+        transaction_id: InternalTransactionId,
+    },
+
+    // tonlib_api.tl, line 290
+    #[serde(rename = "getShardAccountCell")]
+    GetShardAccountCell {
+        account_address: AccountAddress,
+    },
+
     // tonlib_api.tl, line 294
     #[serde(rename = "getConfigParam")]
     GetConfigParam {
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+
+pub(crate) use crate::tl::scheme_parser::combinator_id;
+use crate::tl::scheme_parser::parse_combinators;
+pub use crate::tl::scheme_parser::{TlCombinator, TlField, TlTypeRef};
+use crate::tl::TlError;
+
+// Generated by `build.rs` from `scheme/tonlib_api.tl`.
+include!(concat!(env!("OUT_DIR"), "/tonlib_api_scheme.rs"));
+
+lazy_static! {
+    /// Scheme generated from `scheme/tonlib_api.tl`, bundled with the crate.
+    pub static ref TONLIB_API_SCHEME: TlScheme = TlScheme::from_combinators(bundled_combinators());
+}
+
+/// TL scheme holding all constructors and functions of the api, indexed by name and by id.
+#[derive(Debug, Clone)]
+pub struct TlScheme {
+    combinators: Vec<TlCombinator>,
+    by_name: HashMap<String, usize>,
+    by_id: HashMap<u32, usize>,
+}
+
+impl TlScheme {
+    pub fn parse(scheme: &str) -> Result<TlScheme, TlError> {
+        let combinators = parse_combinators(scheme).map_err(TlError::SchemeError)?;
+        Ok(Self::from_combinators(combinators))
+    }
+
+    fn from_combinators(combinators: Vec<TlCombinator>) -> TlScheme {
+        let mut by_name = HashMap::new();
+        let mut by_id = HashMap::new();
+        for (idx, combinator) in combinators.iter().enumerate() {
+            by_name.insert(combinator.name.clone(), idx);
+            by_id.insert(combinator.id, idx);
+        }
+        TlScheme {
+            combinators,
+            by_name,
+            by_id,
+        }
+    }
+
+    pub fn combinators(&self) -> &[TlCombinator] {
+        &self.combinators
+    }
+
+    pub fn get_by_name(&self, name: &str) -> Option<&TlCombinator> {
+        self.by_name.get(name).map(|idx| &self.combinators[*idx])
+    }
+
+    pub fn get_by_id(&self, id: u32) -> Option<&TlCombinator> {
+        self.by_id.get(&id).map(|idx| &self.combinators[*idx])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tl::scheme::{combinator_id, TlScheme, TlTypeRef, TONLIB_API_SCHEME};
+
+    #[test]
+    fn it_computes_combinator_ids() {
+        assert_eq!(combinator_id("boolTrue = Bool"), 0x997275b5);
+        assert_eq!(
+            combinator_id("vector {t:Type} # [ t ] = Vector t"),
+            0x1cb5c415
+        );
+    }
+
+    #[test]
+    fn it_parses_bundled_scheme() {
+        let combinator = TONLIB_API_SCHEME
+            .get_by_name("blocks.shortTxId")
+            .expect("blocks.shortTxId");
+        assert!(!combinator.is_function);
+        assert_eq!(combinator.result_type, "liteServer.TransactionId");
+        assert_eq!(combinator.fields[0].type_ref, TlTypeRef::Nat);
+        assert_eq!(
+            combinator.fields[1].type_ref,
+            TlTypeRef::Conditional {
+                field: "mode".to_string(),
+                bit: 0,
+                inner: Box::new(TlTypeRef::Bytes)
+            }
+        );
+
+        let combinator = TONLIB_API_SCHEME
+            .get_by_name("smc.getLibraries")
+            .expect("smc.getLibraries");
+        assert!(combinator.is_function);
+        assert_eq!(
+            combinator.fields[0].type_ref,
+            TlTypeRef::Vector(Box::new(TlTypeRef::Int256))
+        );
+        assert_eq!(
+            TONLIB_API_SCHEME.get_by_id(combinator.id).map(|c| &c.name),
+            Some(&combinator.name)
+        );
+    }
+
+    #[test]
+    fn it_generates_bundled_scheme() {
+        let parsed = TlScheme::parse(include_str!("../../scheme/tonlib_api.tl")).unwrap();
+        assert_eq!(parsed.combinators(), TONLIB_API_SCHEME.combinators());
+    }
+
+    #[test]
+    fn it_rejects_out_of_range_condition_bits() {
+        assert!(TlScheme::parse("a mode:# x:mode.31?int32 = A;").is_ok());
+        assert!(TlScheme::parse("a mode:# x:mode.32?int32 = A;").is_err());
+    }
+
+    #[test]
+    fn it_parses_multiline_declarations() {
+        let scheme =
+            TlScheme::parse("a x:int32\n y:Foo = A; // comment\n---functions---\nb = A;").unwrap();
+        let a = scheme.get_by_name("a").unwrap();
+        assert_eq!(a.fields.len(), 2);
+        assert_eq!(a.fields[1].type_ref, TlTypeRef::Boxed("Foo".to_string()));
+        assert!(scheme.get_by_name("b").unwrap().is_function);
+    }
+}
@@ -0,0 +1,232 @@
+//! Parser of TL schemes, shared with the build script, which generates the bundled scheme.
+
+use crc::{Crc, CRC_32_ISO_HDLC};
+
+const FUNCTIONS_SECTION: &str = "---functions---";
+const TYPES_SECTION: &str = "---types---";
+
+/// Type of a single field of TL combinator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TlTypeRef {
+    Int32,
+    Int53,
+    Int64,
+    Int256,
+    Double,
+    /// `#` — unsigned 32-bit integer, used as a bit mask for conditional fields.
+    Nat,
+    Bool,
+    String,
+    Bytes,
+    Vector(Box<TlTypeRef>),
+    /// Bare type, referenced by the constructor name (e.g. `ton.blockIdExt`).
+    Bare(String),
+    /// Boxed type, referenced by the type name (e.g. `tvm.StackEntry`).
+    Boxed(String),
+    /// Field that is present only when bit `bit` of the field `field` is set (e.g. `mode.0?bytes`).
+    Conditional {
+        field: String,
+        bit: u32,
+        inner: Box<TlTypeRef>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlField {
+    pub name: String,
+    pub type_ref: TlTypeRef,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlCombinator {
+    pub name: String,
+    pub id: u32,
+    pub fields: Vec<TlField>,
+    pub result_type: String,
+    pub is_function: bool,
+}
+
+/// Parses all declarations of the scheme, skipping builtin types.
+pub fn parse_combinators(scheme: &str) -> Result<Vec<TlCombinator>, String> {
+    let mut combinators = vec![];
+    let mut is_function = false;
+    let mut declaration = String::new();
+    for line in scheme.lines() {
+        let line = match line.find("//") {
+            Some(pos) => &line[..pos],
+            None => line,
+        }
+        .trim();
+        if line == FUNCTIONS_SECTION {
+            is_function = true;
+            continue;
+        }
+        if line == TYPES_SECTION {
+            is_function = false;
+            continue;
+        }
+        if line.is_empty() {
+            continue;
+        }
+        declaration.push(' ');
+        declaration.push_str(line);
+        while let Some(pos) = declaration.find(';') {
+            let decl = declaration[..pos].trim().to_string();
+            declaration = declaration[pos + 1..].to_string();
+            if let Some(combinator) = parse_combinator(&decl, is_function)? {
+                combinators.push(combinator);
+            }
+        }
+    }
+    if !declaration.trim().is_empty() {
+        return Err(format!("Unterminated declaration: {}", declaration.trim()));
+    }
+    Ok(combinators)
+}
+
+fn parse_combinator(decl: &str, is_function: bool) -> Result<Option<TlCombinator>, String> {
+    let (lhs, rhs) = decl
+        .split_once('=')
+        .ok_or_else(|| format!("Missing result type: {}", decl))?;
+    let mut tokens = tokenize(lhs).into_iter();
+    let name = tokens
+        .next()
+        .ok_or_else(|| format!("Missing name: {}", decl))?;
+    if is_builtin(&name) {
+        return Ok(None);
+    }
+    let result_type = rhs.trim().to_string();
+    let mut fields = vec![];
+    for token in tokens {
+        let (field_name, type_str) = token
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid field `{}` in: {}", token, decl))?;
+        fields.push(TlField {
+            name: field_name.to_string(),
+            type_ref: parse_type_ref(type_str)?,
+        });
+    }
+    let id = combinator_id(decl);
+    Ok(Some(TlCombinator {
+        name,
+        id,
+        fields,
+        result_type,
+        is_function,
+    }))
+}
+
+fn is_builtin(name: &str) -> bool {
+    matches!(
+        name,
+        "double"
+            | "string"
+            | "int32"
+            | "int53"
+            | "int64"
+            | "int256"
+            | "bytes"
+            | "secureString"
+            | "secureBytes"
+            | "object"
+            | "function"
+            | "boolFalse"
+            | "boolTrue"
+            | "vector"
+    )
+}
+
+/// Splits the left hand side of declaration into name and fields, joining `(vector t)` groups
+/// into `vector<t>`.
+fn tokenize(lhs: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    let mut current = String::new();
+    let mut depth = 0;
+    for c in lhs.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            c if c.is_whitespace() && depth > 0 => {
+                if !current.ends_with('<') && !current.ends_with(':') {
+                    current.push('<');
+                }
+            }
+            c if c.is_whitespace() => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+        .into_iter()
+        .map(|t| {
+            let opened = t.matches('<').count();
+            let closed = t.matches('>').count();
+            t + &">".repeat(opened.saturating_sub(closed))
+        })
+        .collect()
+}
+
+fn parse_type_ref(type_str: &str) -> Result<TlTypeRef, String> {
+    if let Some((condition, inner)) = type_str.split_once('?') {
+        let (field, bit) = condition
+            .split_once('.')
+            .ok_or_else(|| format!("Invalid condition in type: {}", type_str))?;
+        let bit = bit
+            .parse::<u32>()
+            .map_err(|e| format!("Invalid condition bit in {}: {}", type_str, e))?;
+        if bit >= u32::BITS {
+            return Err(format!("Condition bit out of range in {}", type_str));
+        }
+        return Ok(TlTypeRef::Conditional {
+            field: field.to_string(),
+            bit,
+            inner: Box::new(parse_type_ref(inner)?),
+        });
+    }
+    if let Some(inner) = type_str
+        .strip_prefix("vector<")
+        .and_then(|s| s.strip_suffix('>'))
+    {
+        return Ok(TlTypeRef::Vector(Box::new(parse_type_ref(inner)?)));
+    }
+    let type_ref = match type_str {
+        "int32" => TlTypeRef::Int32,
+        "int53" => TlTypeRef::Int53,
+        "int64" => TlTypeRef::Int64,
+        "int256" => TlTypeRef::Int256,
+        "double" => TlTypeRef::Double,
+        "#" => TlTypeRef::Nat,
+        "Bool" => TlTypeRef::Bool,
+        "string" | "secureString" => TlTypeRef::String,
+        "bytes" | "secureBytes" => TlTypeRef::Bytes,
+        "" => {
+            return Err("Empty field type".to_string());
+        }
+        s => {
+            let short_name = s.rsplit('.').next().unwrap_or(s);
+            if short_name.starts_with(|c: char| c.is_ascii_uppercase()) {
+                TlTypeRef::Boxed(s.to_string())
+            } else {
+                TlTypeRef::Bare(s.to_string())
+            }
+        }
+    };
+    Ok(type_ref)
+}
+
+/// Computes constructor id as crc32 of the normalized declaration, as the reference TL compiler does.
+pub fn combinator_id(decl: &str) -> u32 {
+    let normalized = decl
+        .replace(['<', '>', '(', ')', '{', '}'], " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let crc = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+    crc.checksum(normalized.as_bytes())
+}
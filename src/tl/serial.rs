@@ -19,7 +19,9 @@ pub(crate) fn serialize_function_extra(
     extra: &str,
 ) -> Result<CString, TlError> {
     let mut value = serde_json::to_value(function)?;
-    let obj = value.as_object_mut().unwrap();
+    let obj = value
+        .as_object_mut()
+        .ok_or_else(|| TlError::SchemeError("Function is not serialized as object".to_string()))?;
     obj.insert(String::from("@extra"), serde_json::Value::from(extra));
     // TODO: Optimize to avoid copying
     let str = serde_json::to_string(&value)?;
@@ -31,7 +33,7 @@ pub(crate) unsafe fn deserialize_result(c_str: *const c_char) -> Result<TonResul
     let cstr = CStr::from_ptr(c_str);
     // TODO: Optimize to avoid copying
     let str = cstr.to_str()?;
-    let r = serde_json::from_str(str).unwrap(); // TODO: Transform error
+    let r = serde_json::from_str(str)?;
     Ok(r)
 }
 
@@ -40,16 +42,14 @@ pub(crate) unsafe fn deserialize_result_extra(
 ) -> (Result<TonResult, TlError>, Option<String>) {
     let cstr = CStr::from_ptr(c_str);
     // TODO: Optimize to avoid copying
-    let str_result = cstr.to_str();
-    if let Err(err) = str_result {
-        return (Err(TlError::Utf8Error(err)), None);
-    }
-    let str = str_result.unwrap();
-    let value_result: Result<Value, serde_json::Error> = serde_json::from_str(str);
-    if let Err(err) = value_result {
-        return (Err(TlError::SerdeJsonError(err)), None);
-    }
-    let value = value_result.unwrap();
+    let str = match cstr.to_str() {
+        Ok(str) => str,
+        Err(err) => return (Err(TlError::Utf8Error(err)), None),
+    };
+    let value: Value = match serde_json::from_str(str) {
+        Ok(value) => value,
+        Err(err) => return (Err(TlError::SerdeJsonError(err)), None),
+    };
     let extra: Option<String> = value
         .as_object()
         .and_then(|m| m.get("@extra"))
@@ -67,7 +67,9 @@ mod tests {
 
     use crate::tl::function::TonFunction;
     use crate::tl::result::TonResult;
-    use crate::tl::serial::{deserialize_result_extra, serialize_function_extra};
+    use crate::tl::serial::{
+        deserialize_result, deserialize_result_extra, serialize_function_extra,
+    };
 
     #[test]
     fn it_serializes_function_extra() {
@@ -102,4 +104,13 @@ mod tests {
         let (_, extra) = unsafe { deserialize_result_extra(cstr.as_ptr()) };
         assert_eq!(extra, Some(String::from("0")));
     }
+
+    #[test]
+    fn it_fails_to_deserialize_invalid_json() {
+        let cstr = CString::new("{\"@type\":").unwrap();
+        assert!(unsafe { deserialize_result(cstr.as_ptr()) }.is_err());
+        let (result, extra) = unsafe { deserialize_result_extra(cstr.as_ptr()) };
+        assert!(result.is_err());
+        assert_eq!(extra, None);
+    }
 }
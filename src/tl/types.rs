@@ -11,8 +11,10 @@ use serde::{Deserialize, Serialize};
 use serde_aux::prelude::*;
 
 use super::TonLibraryId;
+use crate::address::{TonAddress, TonAddressParseError};
 use crate::tl::stack::{TvmCell, TvmStack};
 use crate::tl::{Base64Standard, InternalTransactionIdParseError};
+use crate::types::TonHash;
 
 // tonlib_api.tl, line 23
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
@@ -537,11 +539,11 @@ pub struct LogVerbosityLevel {
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct LiteServerInfo {
     #[serde(deserialize_with = "deserialize_number_from_string")]
-    now: i64,
+    pub now: i64,
     #[serde(deserialize_with = "deserialize_number_from_string")]
-    version: i32,
+    pub version: i32,
     #[serde(deserialize_with = "deserialize_number_from_string")]
-    capabilities: i64,
+    pub capabilities: i64,
 }
 
 // tonlib_api.tl, line 219
@@ -588,6 +590,35 @@ pub struct BlocksShortTxId {
     pub hash: Vec<u8>,
 }
 
+impl BlocksShortTxId {
+    /// Returns address of the account in the given workchain (`account` holds only the hash part).
+    pub fn address(&self, workchain: i32) -> Result<TonAddress, TonAddressParseError> {
+        let hash_part: &TonHash = self.account.as_slice().try_into().map_err(|_| {
+            TonAddressParseError::new(
+                hex::encode(&self.account),
+                "Invalid account id: length is not equal to 32",
+            )
+        })?;
+        Ok(TonAddress::new(workchain, hash_part))
+    }
+
+    pub fn tx_hash(&self) -> Result<TonHash, InternalTransactionIdParseError> {
+        self.hash.as_slice().try_into().map_err(|_| {
+            InternalTransactionIdParseError::new(
+                format!("{}, {}", self.lt, hex::encode(&self.hash)),
+                "Invalid transaction hash: length is not equal to 32",
+            )
+        })
+    }
+
+    pub fn transaction_id(&self) -> InternalTransactionId {
+        InternalTransactionId {
+            lt: self.lt,
+            hash: self.hash.clone(),
+        }
+    }
+}
+
 // tonlib_api.tl, line 223
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct BlocksTransactions {
@@ -645,8 +676,9 @@ mod tests {
 
     use tokio_test::assert_err;
 
+    use crate::address::TonAddress;
     use crate::tl::types::InternalTransactionId;
-    use crate::tl::{InternalTransactionIdParseError, SmcMethodId};
+    use crate::tl::{BlocksShortTxId, InternalTransactionIdParseError, SmcMethodId};
 
     #[test]
     fn internal_transaction_id_parse_format_works() -> anyhow::Result<()> {
@@ -732,4 +764,31 @@ mod tests {
         assert_eq!(method_id, result);
         Ok(())
     }
+
+    #[test]
+    fn blocks_short_tx_id_typed_accessors_work() {
+        let tx_id = BlocksShortTxId {
+            mode: 7,
+            account: vec![1; 32],
+            lt: 42,
+            hash: vec![2; 32],
+        };
+        assert_eq!(tx_id.address(-1).unwrap(), TonAddress::new(-1, &[1; 32]));
+        assert_eq!(tx_id.tx_hash().unwrap(), [2; 32]);
+        assert_eq!(
+            tx_id.transaction_id(),
+            InternalTransactionId {
+                lt: 42,
+                hash: vec![2; 32]
+            }
+        );
+
+        let invalid = BlocksShortTxId {
+            account: vec![1; 31],
+            hash: vec![],
+            ..tx_id
+        };
+        assert_err!(invalid.address(0));
+        assert_err!(invalid.tx_hash());
+    }
 }
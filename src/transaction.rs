@@ -0,0 +1,11 @@
+pub use fees::*;
+pub use gas_prices::*;
+pub use jetton_transfer::*;
+pub use parsed_tx::*;
+pub use storage_prices::*;
+
+mod fees;
+mod gas_prices;
+mod jetton_transfer;
+mod parsed_tx;
+mod storage_prices;
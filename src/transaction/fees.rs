@@ -0,0 +1,329 @@
+use num_bigint::{BigInt, BigUint};
+
+use crate::transaction::{ParsedTx, TxComputePhase, TxMessageInfo};
+
+/// Fees and values of a transaction, in nanotons. Extra currencies are ignored.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TxFees {
+    /// Fees collected by validators (`total_fees` of the transaction).
+    pub total_fees: BigUint,
+    /// Storage fees collected in storage phase and storage debt collected in credit phase.
+    pub storage_fee: BigUint,
+    pub gas_fee: BigUint,
+    /// Forward fees of outgoing messages (`total_fwd_fees` of action phase).
+    pub fwd_fee: BigUint,
+    /// Part of `fwd_fee` collected by validators (`total_action_fees` of action phase).
+    pub action_fee: BigUint,
+    pub import_fee: BigUint,
+    /// Value of incoming internal message.
+    pub value_in: BigUint,
+    /// Total value of outgoing internal messages.
+    pub value_out: BigUint,
+    /// Forward and ihr fees carried by outgoing internal messages.
+    pub out_msgs_fee: BigUint,
+}
+
+impl TxFees {
+    /// Change of the account balance made by the transaction.
+    pub fn balance_delta(&self) -> BigInt {
+        BigInt::from(self.value_in.clone())
+            - BigInt::from(self.value_out.clone())
+            - BigInt::from(self.out_msgs_fee.clone())
+            - BigInt::from(self.total_fees.clone())
+    }
+}
+
+impl ParsedTx {
+    pub fn fees(&self) -> TxFees {
+        let mut fees = TxFees {
+            total_fees: self.total_fees.clone(),
+            ..TxFees::default()
+        };
+        if let Some(storage) = &self.storage_phase {
+            fees.storage_fee += &storage.storage_fees_collected;
+        }
+        if let Some(due) = self
+            .credit_phase
+            .as_ref()
+            .and_then(|c| c.due_fees_collected.as_ref())
+        {
+            fees.storage_fee += due;
+        }
+        if let Some(TxComputePhase::Vm { gas_fees, .. }) = &self.compute_phase {
+            fees.gas_fee = gas_fees.clone();
+        }
+        if let Some(action) = &self.action_phase {
+            fees.fwd_fee = action.total_fwd_fees.clone().unwrap_or_default();
+            fees.action_fee = action.total_action_fees.clone().unwrap_or_default();
+        }
+        match &self.in_msg {
+            Some(TxMessageInfo::Internal { value, .. }) => fees.value_in = value.clone(),
+            Some(TxMessageInfo::ExternalIn { import_fee, .. }) => {
+                fees.import_fee = import_fee.clone()
+            }
+            _ => {}
+        }
+        for msg in &self.out_msgs {
+            if let TxMessageInfo::Internal {
+                value,
+                ihr_fee,
+                fwd_fee,
+                ..
+            } = msg
+            {
+                fees.value_out += value;
+                fees.out_msgs_fee += ihr_fee + fwd_fee;
+            }
+        }
+        fees
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use num_bigint::{BigInt, BigUint};
+
+    use crate::address::TonAddress;
+    use crate::cell::{ArcCell, BagOfCells, Cell, CellBuilder};
+    use crate::transaction::{ParsedTx, TxComputePhase, TxFees, TxMessageInfo};
+
+    const WALLET: &str = "EQDk2VTvn04SUKJrW7rXahzdF8_Qi6utb0wj43InCu9vdjrR";
+    const DESTINATION: &str = "EQCDM_QGggZ3qMa_f3lRPk4_qLDnLTqdi6OkMAV2NB9r5TG3";
+
+    fn coins(value: u64) -> BigUint {
+        BigUint::from(value)
+    }
+
+    fn build_internal_msg(src: &TonAddress, dest: &TonAddress, value: u64, fwd_fee: u64) -> Cell {
+        let mut builder = CellBuilder::new();
+        builder
+            .store_bit(false)
+            .unwrap()
+            .store_u8(3, 0b110)
+            .unwrap()
+            .store_address(src)
+            .unwrap()
+            .store_address(dest)
+            .unwrap()
+            .store_coins(&coins(value))
+            .unwrap()
+            .store_bit(false)
+            .unwrap()
+            .store_coins(&coins(0))
+            .unwrap()
+            .store_coins(&coins(fwd_fee))
+            .unwrap()
+            .store_u64(64, 1)
+            .unwrap()
+            .store_u32(32, 1)
+            .unwrap()
+            // no init, body in place
+            .store_u8(2, 0)
+            .unwrap();
+        builder.build().unwrap()
+    }
+
+    /// Builds ordinary transaction with external incoming message and one outgoing transfer.
+    fn build_transaction(wallet: &TonAddress, destination: &TonAddress) -> Cell {
+        let mut in_msg = CellBuilder::new();
+        in_msg
+            .store_u8(2, 0b10)
+            .unwrap()
+            .store_u8(2, 0)
+            .unwrap()
+            .store_address(wallet)
+            .unwrap()
+            .store_coins(&coins(0))
+            .unwrap()
+            .store_u8(2, 0)
+            .unwrap();
+        let in_msg = in_msg.build().unwrap();
+
+        let out_msg = build_internal_msg(wallet, destination, 1_000_000_000, 266_669);
+        // HashmapE 15 with the single key 0: hml_same$11 v:0 n:15
+        let mut out_msgs_dict = CellBuilder::new();
+        out_msgs_dict
+            .store_u8(2, 0b11)
+            .unwrap()
+            .store_bit(false)
+            .unwrap()
+            .store_u8(4, 15)
+            .unwrap()
+            .store_child(out_msg)
+            .unwrap();
+        let out_msgs_dict = out_msgs_dict.build().unwrap();
+
+        let mut msgs = CellBuilder::new();
+        msgs.store_bit(true)
+            .unwrap()
+            .store_child(in_msg)
+            .unwrap()
+            .store_bit(true)
+            .unwrap()
+            .store_child(out_msgs_dict)
+            .unwrap();
+
+        let mut compute_details = CellBuilder::new();
+        compute_details
+            .store_u8(3, 2)
+            .unwrap()
+            .store_u32(16, 3_308)
+            .unwrap()
+            .store_u8(3, 0)
+            .unwrap()
+            .store_bit(true)
+            .unwrap()
+            .store_u8(2, 2)
+            .unwrap()
+            .store_u32(16, 10_000)
+            .unwrap()
+            .store_i8(8, 0)
+            .unwrap()
+            .store_i32(32, 0)
+            .unwrap();
+
+        let mut action = CellBuilder::new();
+        action
+            .store_u8(3, 0b110)
+            .unwrap()
+            .store_bit(false)
+            .unwrap()
+            .store_bit(true)
+            .unwrap()
+            .store_coins(&coins(400_000))
+            .unwrap()
+            .store_bit(true)
+            .unwrap()
+            .store_coins(&coins(133_331))
+            .unwrap()
+            .store_i32(32, 0)
+            .unwrap();
+
+        let mut description = CellBuilder::new();
+        description
+            .store_u8(4, 0)
+            .unwrap()
+            .store_bit(false)
+            .unwrap()
+            // storage phase
+            .store_bit(true)
+            .unwrap()
+            .store_coins(&coins(1_234))
+            .unwrap()
+            .store_bit(false)
+            .unwrap()
+            .store_bit(false)
+            .unwrap()
+            // no credit phase
+            .store_bit(false)
+            .unwrap()
+            // compute phase
+            .store_u8(4, 0b1100)
+            .unwrap()
+            .store_coins(&coins(1_323_200))
+            .unwrap()
+            .store_child(compute_details.build().unwrap())
+            .unwrap()
+            .store_bit(true)
+            .unwrap()
+            .store_child(action.build().unwrap())
+            .unwrap()
+            .store_bit(false)
+            .unwrap()
+            .store_bit(false)
+            .unwrap()
+            .store_bit(false)
+            .unwrap();
+
+        let state_update: ArcCell = Arc::new(Cell::default());
+        let mut tx = CellBuilder::new();
+        tx.store_u8(4, 0b0111)
+            .unwrap()
+            .store_slice(&wallet.hash_part)
+            .unwrap()
+            .store_u64(64, 100)
+            .unwrap()
+            .store_slice(&[1; 32])
+            .unwrap()
+            .store_u64(64, 90)
+            .unwrap()
+            .store_u32(32, 1_700_000_000)
+            .unwrap()
+            .store_u32(15, 1)
+            .unwrap()
+            .store_u8(4, 0b1010)
+            .unwrap()
+            .store_child(msgs.build().unwrap())
+            .unwrap()
+            // 1234 + 1323200 + 133331
+            .store_coins(&coins(1_457_765))
+            .unwrap()
+            .store_bit(false)
+            .unwrap()
+            .store_reference(&state_update)
+            .unwrap()
+            .store_child(description.build().unwrap())
+            .unwrap();
+        tx.build().unwrap()
+    }
+
+    #[test]
+    fn test_parsed_tx_fees() -> anyhow::Result<()> {
+        let wallet: TonAddress = WALLET.parse()?;
+        let destination: TonAddress = DESTINATION.parse()?;
+        let cell = build_transaction(&wallet, &destination);
+        let in_msg_hash = cell.reference(0)?.reference(0)?.cell_hash();
+        let boc = BagOfCells::from_root(cell).serialize(false)?;
+        let tx = ParsedTx::parse_boc(&boc)?;
+
+        assert_eq!(tx.account, wallet.hash_part);
+        assert_eq!(tx.lt, 100);
+        assert_eq!(tx.prev_trans_lt, 90);
+        assert_eq!(tx.now, 1_700_000_000);
+        assert_eq!(
+            tx.in_msg,
+            Some(TxMessageInfo::ExternalIn {
+                dest: wallet.clone(),
+                import_fee: coins(0),
+            })
+        );
+        assert_eq!(tx.in_msg_hash, Some(in_msg_hash));
+        assert_eq!(tx.out_msgs.len(), 1);
+        assert_eq!(
+            tx.compute_phase,
+            Some(TxComputePhase::Vm {
+                success: true,
+                gas_fees: coins(1_323_200),
+                gas_used: coins(3_308),
+                exit_code: 0,
+            })
+        );
+        assert!(!tx.aborted);
+
+        let fees = tx.fees();
+        assert_eq!(
+            fees,
+            TxFees {
+                total_fees: coins(1_457_765),
+                storage_fee: coins(1_234),
+                gas_fee: coins(1_323_200),
+                fwd_fee: coins(400_000),
+                action_fee: coins(133_331),
+                import_fee: coins(0),
+                value_in: coins(0),
+                value_out: coins(1_000_000_000),
+                out_msgs_fee: coins(266_669),
+            }
+        );
+        assert_eq!(fees.balance_delta(), BigInt::from(-1_001_724_434));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parsed_tx_invalid_tag() {
+        let cell = CellBuilder::new().store_u8(4, 0).unwrap().build().unwrap();
+        assert!(ParsedTx::parse(&cell).is_err());
+    }
+}
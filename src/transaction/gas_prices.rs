@@ -0,0 +1,74 @@
+use crate::cell::{Cell, TonCellError};
+
+/// Config param of masterchain gas prices.
+pub const CONFIG_PARAM_MASTERCHAIN_GAS_PRICES: u32 = 20;
+/// Config param of basechain gas prices.
+pub const CONFIG_PARAM_BASECHAIN_GAS_PRICES: u32 = 21;
+
+const GAS_FLAT_PFX: u8 = 0xd1;
+const GAS_PRICES_EXT: u8 = 0xde;
+const GAS_PRICES: u8 = 0xdd;
+
+/// Gas prices of the workchain from `GasLimitsPrices` (config params 20 and 21).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GasPrices {
+    pub flat_gas_limit: u64,
+    pub flat_gas_price: u64,
+    /// price of gas unit in 1/65536 of nanoton.
+    pub gas_price: u64,
+}
+
+impl GasPrices {
+    pub fn parse(cell: &Cell) -> Result<GasPrices, TonCellError> {
+        let mut parser = cell.parser();
+        let mut prices = GasPrices::default();
+        let mut tag = parser.load_u8(8)?;
+        if tag == GAS_FLAT_PFX {
+            prices.flat_gas_limit = parser.load_u64(64)?;
+            prices.flat_gas_price = parser.load_u64(64)?;
+            tag = parser.load_u8(8)?;
+        }
+        if tag != GAS_PRICES_EXT && tag != GAS_PRICES {
+            return Err(TonCellError::cell_parser_error(format!(
+                "Unexpected GasLimitsPrices tag {:02x}",
+                tag
+            )));
+        }
+        prices.gas_price = parser.load_u64(64)?;
+        Ok(prices)
+    }
+
+    /// Returns fee in nanotons for the gas used by the compute phase.
+    pub fn compute_gas_fee(&self, gas_used: u64) -> u64 {
+        let charged = gas_used.saturating_sub(self.flat_gas_limit) as u128;
+        let variable = (charged * self.gas_price as u128).div_ceil(1 << 16);
+        self.flat_gas_price.saturating_add(variable as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cell::CellBuilder;
+    use crate::transaction::GasPrices;
+
+    #[test]
+    fn test_gas_prices() -> anyhow::Result<()> {
+        // basechain config param 21 of the mainnet
+        let cell = CellBuilder::new()
+            .store_u8(8, 0xd1)?
+            .store_u64(64, 100)?
+            .store_u64(64, 40_000)?
+            .store_u8(8, 0xde)?
+            .store_u64(64, 26_214_400)?
+            .store_u64(64, 1_000_000)?
+            .build()?;
+        let prices = GasPrices::parse(&cell)?;
+        assert_eq!(prices.gas_price, 26_214_400);
+        assert_eq!(prices.compute_gas_fee(50), 40_000);
+        assert_eq!(prices.compute_gas_fee(3_408), 40_000 + 3_308 * 400);
+
+        let invalid = CellBuilder::new().store_u8(8, 0xaa)?.build()?;
+        assert!(GasPrices::parse(&invalid).is_err());
+        Ok(())
+    }
+}
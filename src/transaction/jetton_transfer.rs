@@ -0,0 +1,409 @@
+use num_bigint::BigUint;
+
+use crate::address::{AddressClassifier, AddressRole, TonAddress};
+use crate::cell::{BagOfCells, Cell, TonCellError};
+use crate::message::{
+    JettonTransferMessage, JettonTransferNotificationMessage, TonMessageError, JETTON_TRANSFER,
+    JETTON_TRANSFER_NOTIFICATION,
+};
+use crate::meta::{TokenInfo, TokenRegistry};
+use crate::tl::RawTransaction;
+use crate::transaction::{ParsedTx, TxMessage, TxMessageInfo};
+use crate::types::TonHash;
+
+/// Jetton transfer of an owner, decoded from a transaction of the owner's wallet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct JettonTransferAction {
+    pub tx_hash: TonHash,
+    pub timestamp: u32,
+    pub master: TonAddress,
+    /// Jetton wallet of the owner, which sent the notification or received the transfer request.
+    pub jetton_wallet: TonAddress,
+    pub from: TonAddress,
+    pub to: TonAddress,
+    pub amount: BigUint,
+    pub query_id: u64,
+}
+
+impl JettonTransferAction {
+    /// Decodes jetton transfers of the owner's transaction: transfer notifications received
+    /// from its jetton wallets and transfer requests sent to them.
+    ///
+    /// `master_of` returns the jetton master of a jetton wallet of the owner. Messages of other
+    /// contracts are skipped, so a notification sent by a contract, which only pretends to be
+    /// a jetton wallet, is not reported. Bounced messages are skipped too. A transfer request
+    /// may still be rejected by the jetton wallet in a later transaction.
+    pub fn decode<F>(
+        tx: &RawTransaction,
+        master_of: F,
+    ) -> Result<Vec<JettonTransferAction>, TonMessageError>
+    where
+        F: Fn(&TonAddress) -> Option<TonAddress>,
+    {
+        let boc = BagOfCells::parse(&tx.data)?;
+        let (in_msg, out_msgs) = ParsedTx::parse_messages(boc.single_root()?)?;
+        let tx_hash = tx.transaction_id.hash.as_slice().try_into().map_err(|_| {
+            TonCellError::cell_parser_error(format!(
+                "Invalid transaction hash length {}",
+                tx.transaction_id.hash.len()
+            ))
+        })?;
+        let action = |master: TonAddress, jetton_wallet: &TonAddress| JettonTransferAction {
+            tx_hash,
+            timestamp: tx.utime as u32,
+            master,
+            jetton_wallet: jetton_wallet.clone(),
+            from: TonAddress::NULL,
+            to: TonAddress::NULL,
+            amount: BigUint::default(),
+            query_id: 0,
+        };
+
+        let mut actions = vec![];
+        for msg in in_msg.iter() {
+            let Some((src, dest)) = internal_message_of(msg, JETTON_TRANSFER_NOTIFICATION) else {
+                continue;
+            };
+            let Some(master) = master_of(src) else {
+                continue;
+            };
+            let notification = JettonTransferNotificationMessage::parse(&msg.body)?;
+            actions.push(JettonTransferAction {
+                from: notification.sender,
+                to: dest.clone(),
+                amount: notification.amount,
+                query_id: notification.query_id,
+                ..action(master, src)
+            });
+        }
+        for msg in out_msgs.iter() {
+            let Some((src, dest)) = internal_message_of(msg, JETTON_TRANSFER) else {
+                continue;
+            };
+            let Some(master) = master_of(dest) else {
+                continue;
+            };
+            let transfer = JettonTransferMessage::parse(&msg.body)?;
+            actions.push(JettonTransferAction {
+                from: src.clone(),
+                to: transfer.destination,
+                amount: transfer.amount,
+                query_id: transfer.query_id,
+                ..action(master, dest)
+            });
+        }
+        Ok(actions)
+    }
+
+    /// Decodes jetton transfers like `decode` and drops transfers, in which the jetton master,
+    /// the jetton wallet, the sender or the recipient is classified as suspicious.
+    pub fn decode_classified<F>(
+        tx: &RawTransaction,
+        master_of: F,
+        classifier: &dyn AddressClassifier,
+    ) -> Result<Vec<JettonTransferAction>, TonMessageError>
+    where
+        F: Fn(&TonAddress) -> Option<TonAddress>,
+    {
+        let mut actions = Self::decode(tx, master_of)?;
+        actions.retain(|action| !action.is_suspicious(classifier));
+        Ok(actions)
+    }
+
+    /// Decodes jetton transfers like `decode` and returns them with information of their
+    /// tokens. Transfers of jettons missing in the registry, i.e. of unverified masters, are
+    /// dropped.
+    pub fn decode_listed<'a, F>(
+        tx: &RawTransaction,
+        master_of: F,
+        token_registry: &'a TokenRegistry,
+    ) -> Result<Vec<(JettonTransferAction, &'a TokenInfo)>, TonMessageError>
+    where
+        F: Fn(&TonAddress) -> Option<TonAddress>,
+    {
+        let actions = Self::decode(tx, master_of)?
+            .into_iter()
+            .filter_map(|action| {
+                let token = token_registry.get(&action.master)?;
+                Some((action, token))
+            })
+            .collect();
+        Ok(actions)
+    }
+
+    fn is_suspicious(&self, classifier: &dyn AddressClassifier) -> bool {
+        [
+            (&self.master, AddressRole::JettonMaster),
+            (&self.jetton_wallet, AddressRole::JettonWallet),
+            (&self.from, AddressRole::Account),
+            (&self.to, AddressRole::Account),
+        ]
+        .into_iter()
+        .any(|(address, role)| classifier.classify(address, role).is_suspicious())
+    }
+}
+
+/// Returns source and destination of the internal message, which is not bounced and has the
+/// opcode.
+fn internal_message_of(msg: &TxMessage, opcode: u32) -> Option<(&TonAddress, &TonAddress)> {
+    match &msg.info {
+        TxMessageInfo::Internal {
+            src,
+            dest,
+            bounced: false,
+            ..
+        } if body_opcode(&msg.body) == Some(opcode) => Some((src, dest)),
+        _ => None,
+    }
+}
+
+fn body_opcode(body: &Cell) -> Option<u32> {
+    body.parser().load_u32(32).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use crate::address::{AddressClass, AddressClassifier, AddressRole, TonAddress};
+    use crate::cell::{ArcCell, BagOfCells, Cell, CellBuilder, EMPTY_ARC_CELL};
+    use crate::message::{JettonTransferMessage, JettonTransferNotificationMessage};
+    use crate::meta::{TokenInfo, TokenRegistry};
+    use crate::tl::{AccountAddress, InternalTransactionId, RawTransaction};
+    use crate::transaction::JettonTransferAction;
+
+    fn internal_message(src: &TonAddress, dest: &TonAddress, bounced: bool, body: Cell) -> Cell {
+        let mut builder = CellBuilder::new();
+        builder
+            // int_msg_info$0 ihr_disabled bounce bounced
+            .store_bits(4, &[if bounced { 0b0111_0000 } else { 0b0110_0000 }])
+            .unwrap()
+            .store_address(src)
+            .unwrap()
+            .store_address(dest)
+            .unwrap()
+            // value, no extra currencies, ihr_fee, fwd_fee, created_lt, created_at
+            .store_coins(&BigUint::from(50_000_000u32))
+            .unwrap()
+            .store_bit(false)
+            .unwrap()
+            .store_coins(&BigUint::from(0u32))
+            .unwrap()
+            .store_coins(&BigUint::from(0u32))
+            .unwrap()
+            .store_u64(64, 1)
+            .unwrap()
+            .store_u32(32, 1_700_000_000)
+            .unwrap()
+            // no init, body in a reference
+            .store_bit(false)
+            .unwrap()
+            .store_bit(true)
+            .unwrap()
+            .store_child(body)
+            .unwrap();
+        builder.build().unwrap()
+    }
+
+    fn transaction(in_msg: Option<Cell>, out_msgs: Vec<Cell>) -> RawTransaction {
+        let mut msgs = CellBuilder::new();
+        msgs.store_maybe_cell_ref(&in_msg.map(|m| m.to_arc()))
+            .unwrap();
+        let out_msgs_dict: Option<ArcCell> = match out_msgs.as_slice() {
+            [] => None,
+            [msg] => {
+                // hme_leaf with 15-bit key 0: hml_same$11 v:0 n:(#<= 15) = 15
+                let leaf = CellBuilder::new()
+                    .store_bits(2, &[0b1100_0000])
+                    .unwrap()
+                    .store_bit(false)
+                    .unwrap()
+                    .store_u8(4, 15)
+                    .unwrap()
+                    .store_child(msg.clone())
+                    .unwrap()
+                    .build()
+                    .unwrap();
+                Some(leaf.to_arc())
+            }
+            _ => unimplemented!(),
+        };
+        msgs.store_maybe_cell_ref(&out_msgs_dict).unwrap();
+        let tx = CellBuilder::new()
+            .store_u8(4, 0b0111)
+            .unwrap()
+            .store_child(msgs.build().unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        RawTransaction {
+            address: AccountAddress {
+                account_address: String::new(),
+            },
+            utime: 1_700_000_000,
+            data: BagOfCells::from_root(tx).serialize(false).unwrap(),
+            transaction_id: InternalTransactionId {
+                lt: 1,
+                hash: vec![7; 32],
+            },
+            storage_fee: 0,
+            other_fee: 0,
+            in_msg: None,
+            out_msgs: vec![],
+        }
+    }
+
+    #[test]
+    fn test_decode_jetton_transfers() {
+        let master = TonAddress::new(0, &[1; 32]);
+        let owner = TonAddress::new(0, &[2; 32]);
+        let jetton_wallet = TonAddress::new(0, &[3; 32]);
+        let fake_wallet = TonAddress::new(0, &[4; 32]);
+        let other = TonAddress::new(0, &[5; 32]);
+        let master_of = |wallet: &TonAddress| (wallet == &jetton_wallet).then(|| master.clone());
+
+        let notification = JettonTransferNotificationMessage::new(&other, &BigUint::from(100u32))
+            .build()
+            .unwrap();
+        let tx = transaction(
+            Some(internal_message(
+                &jetton_wallet,
+                &owner,
+                false,
+                notification.clone(),
+            )),
+            vec![],
+        );
+        let actions = JettonTransferAction::decode(&tx, master_of).unwrap();
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].master, master);
+        assert_eq!(actions[0].jetton_wallet, jetton_wallet);
+        assert_eq!((&actions[0].from, &actions[0].to), (&other, &owner));
+        assert_eq!(actions[0].amount, BigUint::from(100u32));
+        assert_eq!(actions[0].tx_hash, [7; 32]);
+
+        // notifications of unknown wallets and bounced messages are skipped
+        let fake = internal_message(&fake_wallet, &owner, false, notification.clone());
+        let tx = transaction(Some(fake), vec![]);
+        assert!(JettonTransferAction::decode(&tx, master_of)
+            .unwrap()
+            .is_empty());
+        let bounced = internal_message(&jetton_wallet, &owner, true, notification);
+        let tx = transaction(Some(bounced), vec![]);
+        assert!(JettonTransferAction::decode(&tx, master_of)
+            .unwrap()
+            .is_empty());
+
+        let transfer = JettonTransferMessage::new(&other, &BigUint::from(30u32))
+            .with_forward_payload(&BigUint::from(1u32), EMPTY_ARC_CELL.clone())
+            .build()
+            .unwrap();
+        let tx = transaction(
+            None,
+            vec![internal_message(&owner, &jetton_wallet, false, transfer)],
+        );
+        let actions = JettonTransferAction::decode(&tx, master_of).unwrap();
+        assert_eq!(actions.len(), 1);
+        assert_eq!((&actions[0].from, &actions[0].to), (&owner, &other));
+        assert_eq!(actions[0].amount, BigUint::from(30u32));
+    }
+
+    struct SpamMasters(Vec<TonAddress>);
+
+    impl AddressClassifier for SpamMasters {
+        fn classify(&self, address: &TonAddress, role: AddressRole) -> AddressClass {
+            if role == AddressRole::JettonMaster && self.0.contains(address) {
+                AddressClass::Spam
+            } else {
+                AddressClass::Unknown
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_classified_jetton_transfers() {
+        let master = TonAddress::new(0, &[1; 32]);
+        let spam_master = TonAddress::new(0, &[6; 32]);
+        let owner = TonAddress::new(0, &[2; 32]);
+        let jetton_wallet = TonAddress::new(0, &[3; 32]);
+        let spam_wallet = TonAddress::new(0, &[4; 32]);
+        let master_of = |wallet: &TonAddress| {
+            if wallet == &jetton_wallet {
+                Some(master.clone())
+            } else if wallet == &spam_wallet {
+                Some(spam_master.clone())
+            } else {
+                None
+            }
+        };
+        let classifier = SpamMasters(vec![spam_master.clone()]);
+
+        let notification = JettonTransferNotificationMessage::new(&owner, &BigUint::from(100u32))
+            .build()
+            .unwrap();
+        let tx = transaction(
+            Some(internal_message(
+                &jetton_wallet,
+                &owner,
+                false,
+                notification.clone(),
+            )),
+            vec![],
+        );
+        let actions = JettonTransferAction::decode_classified(&tx, master_of, &classifier).unwrap();
+        assert_eq!(actions.len(), 1);
+
+        let tx = transaction(
+            Some(internal_message(&spam_wallet, &owner, false, notification)),
+            vec![],
+        );
+        assert_eq!(
+            JettonTransferAction::decode(&tx, master_of).unwrap().len(),
+            1
+        );
+        assert!(
+            JettonTransferAction::decode_classified(&tx, master_of, &classifier)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_decode_listed_jetton_transfers() {
+        let master = TonAddress::new(0, &[1; 32]);
+        let owner = TonAddress::new(0, &[2; 32]);
+        let jetton_wallet = TonAddress::new(0, &[3; 32]);
+        let master_of = |wallet: &TonAddress| (wallet == &jetton_wallet).then(|| master.clone());
+        let notification = JettonTransferNotificationMessage::new(&owner, &BigUint::from(100u32))
+            .build()
+            .unwrap();
+        let tx = transaction(
+            Some(internal_message(
+                &jetton_wallet,
+                &owner,
+                false,
+                notification,
+            )),
+            vec![],
+        );
+
+        let mut registry = TokenRegistry::new();
+        assert!(
+            JettonTransferAction::decode_listed(&tx, master_of, &registry)
+                .unwrap()
+                .is_empty()
+        );
+
+        registry.insert(TokenInfo {
+            address: master.clone(),
+            symbol: "TKN".to_string(),
+            name: "Token".to_string(),
+            decimals: 6,
+            image: None,
+        });
+        let actions = JettonTransferAction::decode_listed(&tx, master_of, &registry).unwrap();
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].0.master, master);
+        assert_eq!(actions[0].1.decimals, 6);
+    }
+}
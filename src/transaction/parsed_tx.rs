@@ -0,0 +1,470 @@
+use num_bigint::BigUint;
+use num_traits::Zero;
+
+use crate::address::TonAddress;
+use crate::cell::{
+    key_extractor_uint, value_extractor_cell, ArcCell, BagOfCells, Cell, CellParser,
+    GenericDictLoader, TonCellError,
+};
+use crate::tl::RawTransaction;
+use crate::types::TonHash;
+
+/// Transaction parsed according to TL-B schema:
+///
+/// ```raw
+/// transaction$0111 account_addr:bits256 lt:uint64 prev_trans_hash:bits256 prev_trans_lt:uint64
+///   now:uint32 outmsg_cnt:uint15 orig_status:AccountStatus end_status:AccountStatus
+///   ^[ in_msg:(Maybe ^(Message Any)) out_msgs:(HashmapE 15 ^(Message Any)) ]
+///   total_fees:CurrencyCollection state_update:^(HASH_UPDATE Account)
+///   description:^TransactionDescr = Transaction;
+/// ```
+///
+/// Only the parts needed to account for fees and values are kept. Phases are present for
+/// ordinary, storage and tick-tock transactions only.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParsedTx {
+    /// Hash of the transaction cell.
+    pub hash: TonHash,
+    pub account: TonHash,
+    pub lt: u64,
+    pub prev_trans_hash: TonHash,
+    pub prev_trans_lt: u64,
+    pub now: u32,
+    pub in_msg: Option<TxMessageInfo>,
+    /// Hash of the incoming message cell, as returned by `send_raw_message_return_hash`.
+    pub in_msg_hash: Option<TonHash>,
+    /// Outgoing messages ordered by their index.
+    pub out_msgs: Vec<TxMessageInfo>,
+    pub total_fees: BigUint,
+    pub storage_phase: Option<TxStoragePhase>,
+    pub credit_phase: Option<TxCreditPhase>,
+    pub compute_phase: Option<TxComputePhase>,
+    pub action_phase: Option<TxActionPhase>,
+    pub aborted: bool,
+}
+
+/// Header (`CommonMsgInfo`) of a message. Extra currencies are ignored.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TxMessageInfo {
+    Internal {
+        src: TonAddress,
+        dest: TonAddress,
+        value: BigUint,
+        ihr_fee: BigUint,
+        fwd_fee: BigUint,
+        bounced: bool,
+    },
+    ExternalIn {
+        dest: TonAddress,
+        import_fee: BigUint,
+    },
+    ExternalOut {
+        src: TonAddress,
+    },
+}
+
+/// Message of a transaction with its body.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TxMessage {
+    pub info: TxMessageInfo,
+    pub body: ArcCell,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TxStoragePhase {
+    pub storage_fees_collected: BigUint,
+    pub storage_fees_due: Option<BigUint>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TxCreditPhase {
+    pub due_fees_collected: Option<BigUint>,
+    pub credit: BigUint,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TxComputePhase {
+    Skipped,
+    Vm {
+        success: bool,
+        gas_fees: BigUint,
+        gas_used: BigUint,
+        exit_code: i32,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TxActionPhase {
+    pub success: bool,
+    pub total_fwd_fees: Option<BigUint>,
+    pub total_action_fees: Option<BigUint>,
+    pub result_code: i32,
+}
+
+impl ParsedTx {
+    pub fn parse(cell: &Cell) -> Result<ParsedTx, TonCellError> {
+        let mut parser = cell.parser();
+        let tag = parser.load_u8(4)?;
+        if tag != 0b0111 {
+            return Err(TonCellError::cell_parser_error(format!(
+                "Invalid transaction tag: {:04b}",
+                tag
+            )));
+        }
+        let account = load_hash(&mut parser)?;
+        let lt = parser.load_u64(64)?;
+        let prev_trans_hash = load_hash(&mut parser)?;
+        let prev_trans_lt = parser.load_u64(64)?;
+        let now = parser.load_u32(32)?;
+        // outmsg_cnt, orig_status, end_status
+        parser.skip_bits(15 + 2 + 2)?;
+
+        let msgs = parser.next_reference()?;
+        let mut msgs_parser = msgs.parser();
+        let (in_msg, in_msg_hash) = match msgs_parser.load_maybe_cell_ref()? {
+            Some(msg) => (Some(TxMessageInfo::parse(&msg)?), Some(msg.cell_hash())),
+            None => (None, None),
+        };
+        let out_msgs = load_out_msgs(&mut msgs_parser, TxMessageInfo::parse)?;
+
+        let total_fees = load_currency_collection(&mut parser)?;
+        let _state_update = parser.next_reference()?;
+        let description = parser.next_reference()?;
+
+        let mut tx = ParsedTx {
+            hash: cell.cell_hash(),
+            account,
+            lt,
+            prev_trans_hash,
+            prev_trans_lt,
+            now,
+            in_msg,
+            in_msg_hash,
+            out_msgs,
+            total_fees,
+            storage_phase: None,
+            credit_phase: None,
+            compute_phase: None,
+            action_phase: None,
+            aborted: false,
+        };
+        tx.load_description(&description)?;
+        Ok(tx)
+    }
+
+    pub fn parse_boc(boc: &[u8]) -> Result<ParsedTx, TonCellError> {
+        let boc = BagOfCells::parse(boc)?;
+        ParsedTx::parse(boc.single_root()?)
+    }
+
+    /// Returns incoming and outgoing (ordered by their index) messages of the transaction
+    /// with their bodies.
+    pub fn parse_messages(
+        cell: &Cell,
+    ) -> Result<(Option<TxMessage>, Vec<TxMessage>), TonCellError> {
+        let msgs = load_messages(cell)?;
+        let mut msgs_parser = msgs.parser();
+        let in_msg = match msgs_parser.load_maybe_cell_ref()? {
+            Some(msg) => Some(TxMessage::parse(&msg)?),
+            None => None,
+        };
+        let out_msgs = load_out_msgs(&mut msgs_parser, TxMessage::parse)?;
+        Ok((in_msg, out_msgs))
+    }
+
+    /// Returns cells of the outgoing messages of the transaction ordered by their index, e.g.
+    /// to emulate their processing.
+    pub fn parse_out_msg_cells(cell: &Cell) -> Result<Vec<ArcCell>, TonCellError> {
+        let msgs = load_messages(cell)?;
+        let mut msgs_parser = msgs.parser();
+        msgs_parser.load_maybe_cell_ref()?;
+        load_out_msgs(&mut msgs_parser, |msg| Ok(msg.clone().to_arc()))
+    }
+
+    fn load_description(&mut self, description: &Cell) -> Result<(), TonCellError> {
+        let mut parser = description.parser();
+        match parser.load_u8(3)? {
+            // trans_ord$0000
+            0b000 if !parser.load_bit()? => {
+                let _credit_first = parser.load_bit()?;
+                if parser.load_bit()? {
+                    self.storage_phase = Some(TxStoragePhase::load(&mut parser)?);
+                }
+                if parser.load_bit()? {
+                    self.credit_phase = Some(TxCreditPhase::load(&mut parser)?);
+                }
+                self.compute_phase = Some(TxComputePhase::load(&mut parser)?);
+                self.load_action_and_aborted(&mut parser)?;
+            }
+            // trans_storage$0001
+            0b000 => {
+                self.storage_phase = Some(TxStoragePhase::load(&mut parser)?);
+            }
+            // trans_tick_tock$001
+            0b001 => {
+                let _is_tock = parser.load_bit()?;
+                self.storage_phase = Some(TxStoragePhase::load(&mut parser)?);
+                self.compute_phase = Some(TxComputePhase::load(&mut parser)?);
+                self.load_action_and_aborted(&mut parser)?;
+            }
+            // split and merge transactions
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn load_action_and_aborted(&mut self, parser: &mut CellParser) -> Result<(), TonCellError> {
+        if let Some(action) = parser.load_maybe_cell_ref()? {
+            self.action_phase = Some(TxActionPhase::load(&mut action.parser())?);
+        }
+        self.aborted = parser.load_bit()?;
+        Ok(())
+    }
+}
+
+impl TryFrom<&RawTransaction> for ParsedTx {
+    type Error = TonCellError;
+
+    fn try_from(value: &RawTransaction) -> Result<Self, Self::Error> {
+        ParsedTx::parse_boc(&value.data)
+    }
+}
+
+impl TxMessageInfo {
+    pub fn parse(message: &Cell) -> Result<TxMessageInfo, TonCellError> {
+        TxMessageInfo::load(&mut message.parser())
+    }
+
+    fn load(parser: &mut CellParser) -> Result<TxMessageInfo, TonCellError> {
+        if !parser.load_bit()? {
+            // int_msg_info$0
+            let _ihr_disabled = parser.load_bit()?;
+            let _bounce = parser.load_bit()?;
+            let bounced = parser.load_bit()?;
+            let src = parser.load_address()?;
+            let dest = parser.load_address()?;
+            let value = load_currency_collection(parser)?;
+            let ihr_fee = parser.load_coins()?;
+            let fwd_fee = parser.load_coins()?;
+            // created_lt, created_at
+            parser.skip_bits(64 + 32)?;
+            Ok(TxMessageInfo::Internal {
+                src,
+                dest,
+                value,
+                ihr_fee,
+                fwd_fee,
+                bounced,
+            })
+        } else if !parser.load_bit()? {
+            // ext_in_msg_info$10
+            skip_ext_address(parser)?;
+            let dest = parser.load_address()?;
+            let import_fee = parser.load_coins()?;
+            Ok(TxMessageInfo::ExternalIn { dest, import_fee })
+        } else {
+            // ext_out_msg_info$11
+            let src = parser.load_address()?;
+            skip_ext_address(parser)?;
+            // created_lt, created_at
+            parser.skip_bits(64 + 32)?;
+            Ok(TxMessageInfo::ExternalOut { src })
+        }
+    }
+}
+
+impl TxMessage {
+    pub fn parse(message: &Cell) -> Result<TxMessage, TonCellError> {
+        let mut parser = message.parser();
+        let info = TxMessageInfo::load(&mut parser)?;
+        // init:(Maybe (Either StateInit ^StateInit))
+        if parser.load_bit()? {
+            if parser.load_bit()? {
+                parser.next_reference()?;
+            } else {
+                skip_state_init(&mut parser)?;
+            }
+        }
+        // body:(Either X ^X)
+        let body = if parser.load_bit()? {
+            parser.next_reference()?
+        } else {
+            let bit_len = parser.remaining_bits();
+            let data = parser.load_bits(bit_len)?;
+            let mut references = vec![];
+            while let Ok(reference) = parser.next_reference() {
+                references.push(reference);
+            }
+            Cell::new(data, bit_len, references, false)?.to_arc()
+        };
+        Ok(TxMessage { info, body })
+    }
+}
+
+impl TxStoragePhase {
+    fn load(parser: &mut CellParser) -> Result<TxStoragePhase, TonCellError> {
+        let storage_fees_collected = parser.load_coins()?;
+        let storage_fees_due = load_maybe_coins(parser)?;
+        skip_acc_status_change(parser)?;
+        Ok(TxStoragePhase {
+            storage_fees_collected,
+            storage_fees_due,
+        })
+    }
+}
+
+impl TxCreditPhase {
+    fn load(parser: &mut CellParser) -> Result<TxCreditPhase, TonCellError> {
+        let due_fees_collected = load_maybe_coins(parser)?;
+        let credit = load_currency_collection(parser)?;
+        Ok(TxCreditPhase {
+            due_fees_collected,
+            credit,
+        })
+    }
+}
+
+impl TxComputePhase {
+    fn load(parser: &mut CellParser) -> Result<TxComputePhase, TonCellError> {
+        if !parser.load_bit()? {
+            // tr_phase_compute_skipped$0 reason:ComputeSkipReason
+            if parser.load_u8(2)? == 0b11 {
+                parser.skip_bits(1)?;
+            }
+            return Ok(TxComputePhase::Skipped);
+        }
+        let success = parser.load_bit()?;
+        let _msg_state_used = parser.load_bit()?;
+        let _account_activated = parser.load_bit()?;
+        let gas_fees = parser.load_coins()?;
+
+        let details = parser.next_reference()?;
+        let mut details_parser = details.parser();
+        let gas_used = load_var_uint(&mut details_parser, 3)?;
+        let _gas_limit = load_var_uint(&mut details_parser, 3)?;
+        if details_parser.load_bit()? {
+            let _gas_credit = load_var_uint(&mut details_parser, 2)?;
+        }
+        // mode:int8
+        details_parser.skip_bits(8)?;
+        let exit_code = details_parser.load_i32(32)?;
+        Ok(TxComputePhase::Vm {
+            success,
+            gas_fees,
+            gas_used,
+            exit_code,
+        })
+    }
+}
+
+impl TxActionPhase {
+    fn load(parser: &mut CellParser) -> Result<TxActionPhase, TonCellError> {
+        let success = parser.load_bit()?;
+        let _valid = parser.load_bit()?;
+        let _no_funds = parser.load_bit()?;
+        skip_acc_status_change(parser)?;
+        let total_fwd_fees = load_maybe_coins(parser)?;
+        let total_action_fees = load_maybe_coins(parser)?;
+        let result_code = parser.load_i32(32)?;
+        Ok(TxActionPhase {
+            success,
+            total_fwd_fees,
+            total_action_fees,
+            result_code,
+        })
+    }
+}
+
+fn load_hash(parser: &mut CellParser) -> Result<TonHash, TonCellError> {
+    let mut hash = [0; 32];
+    parser.load_slice(&mut hash)?;
+    Ok(hash)
+}
+
+/// Returns the cell with incoming and outgoing messages of the transaction.
+fn load_messages(cell: &Cell) -> Result<ArcCell, TonCellError> {
+    let mut parser = cell.parser();
+    let tag = parser.load_u8(4)?;
+    if tag != 0b0111 {
+        return Err(TonCellError::cell_parser_error(format!(
+            "Invalid transaction tag: {:04b}",
+            tag
+        )));
+    }
+    parser.next_reference()
+}
+
+fn load_out_msgs<T>(
+    parser: &mut CellParser,
+    parse: fn(&Cell) -> Result<T, TonCellError>,
+) -> Result<Vec<T>, TonCellError> {
+    let Some(dict) = parser.load_maybe_cell_ref()? else {
+        return Ok(vec![]);
+    };
+    let loader = GenericDictLoader::new(key_extractor_uint, value_extractor_cell, 15);
+    let mut msgs: Vec<_> = dict.load_generic_dict(&loader)?.into_iter().collect();
+    msgs.sort_by(|(a, _), (b, _)| a.cmp(b));
+    msgs.into_iter()
+        .map(|(_, value)| parse(value.reference(0)?))
+        .collect()
+}
+
+/// Loads grams of `CurrencyCollection`, skipping extra currencies.
+fn load_currency_collection(parser: &mut CellParser) -> Result<BigUint, TonCellError> {
+    let grams = parser.load_coins()?;
+    let _other = parser.load_maybe_cell_ref()?;
+    Ok(grams)
+}
+
+fn load_maybe_coins(parser: &mut CellParser) -> Result<Option<BigUint>, TonCellError> {
+    if parser.load_bit()? {
+        Ok(Some(parser.load_coins()?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Loads `VarUInteger n`, where `len_bits` is the size of its length prefix.
+fn load_var_uint(parser: &mut CellParser, len_bits: usize) -> Result<BigUint, TonCellError> {
+    let len = parser.load_u8(len_bits)? as usize;
+    if len == 0 {
+        Ok(BigUint::zero())
+    } else {
+        parser.load_uint(len * 8)
+    }
+}
+
+/// Skips `acst_unchanged$0`, `acst_frozen$10` or `acst_deleted$11`.
+fn skip_acc_status_change(parser: &mut CellParser) -> Result<(), TonCellError> {
+    if parser.load_bit()? {
+        parser.skip_bits(1)?;
+    }
+    Ok(())
+}
+
+/// Skips `StateInit` stored in place: `split_depth:(Maybe (## 5)) special:(Maybe TickTock)
+/// code:(Maybe ^Cell) data:(Maybe ^Cell) library:(HashmapE 256 SimpleLib)`.
+fn skip_state_init(parser: &mut CellParser) -> Result<(), TonCellError> {
+    if parser.load_bit()? {
+        parser.skip_bits(5)?;
+    }
+    if parser.load_bit()? {
+        parser.skip_bits(2)?;
+    }
+    for _ in 0..3 {
+        parser.load_maybe_cell_ref()?;
+    }
+    Ok(())
+}
+
+/// Skips `addr_none$00` or `addr_extern$01 len:(## 9) external_address:(bits len)`.
+fn skip_ext_address(parser: &mut CellParser) -> Result<(), TonCellError> {
+    match parser.load_u8(2)? {
+        0b00 => Ok(()),
+        0b01 => {
+            let len = parser.load_u16(9)? as usize;
+            parser.skip_bits(len)
+        }
+        tp => Err(TonCellError::InvalidAddressType(tp)),
+    }
+}
@@ -0,0 +1,110 @@
+use num_bigint::BigUint;
+
+use crate::cell::{key_extractor_u32, value_extractor_cell, Cell, GenericDictLoader, TonCellError};
+
+/// Config param of storage prices.
+pub const CONFIG_PARAM_STORAGE_PRICES: u32 = 18;
+
+const STORAGE_PRICES_TAG: u8 = 0xcc;
+
+/// Storage prices from `StoragePrices` of config param 18, in 1/65536 of nanoton per bit or
+/// cell per second.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StoragePrices {
+    pub utime_since: u32,
+    pub bit_price_ps: u64,
+    pub cell_price_ps: u64,
+    pub mc_bit_price_ps: u64,
+    pub mc_cell_price_ps: u64,
+}
+
+impl StoragePrices {
+    pub fn parse(cell: &Cell) -> Result<StoragePrices, TonCellError> {
+        let mut parser = cell.parser();
+        let tag = parser.load_u8(8)?;
+        if tag != STORAGE_PRICES_TAG {
+            return Err(TonCellError::cell_parser_error(format!(
+                "Unexpected StoragePrices tag {:02x}",
+                tag
+            )));
+        }
+        Ok(StoragePrices {
+            utime_since: parser.load_u32(32)?,
+            bit_price_ps: parser.load_u64(64)?,
+            cell_price_ps: parser.load_u64(64)?,
+            mc_bit_price_ps: parser.load_u64(64)?,
+            mc_cell_price_ps: parser.load_u64(64)?,
+        })
+    }
+
+    /// Returns prices in effect at `utime` from the dictionary of config param 18.
+    pub fn parse_config(cell: &Cell, utime: u32) -> Result<Option<StoragePrices>, TonCellError> {
+        let loader = GenericDictLoader::new(key_extractor_u32, value_extractor_cell, 32);
+        let mut current: Option<StoragePrices> = None;
+        for value in cell.load_generic_dict(&loader)?.values() {
+            let prices = StoragePrices::parse(value)?;
+            let newer = match &current {
+                Some(c) => c.utime_since < prices.utime_since,
+                None => true,
+            };
+            if prices.utime_since <= utime && newer {
+                current = Some(prices);
+            }
+        }
+        Ok(current)
+    }
+
+    /// Returns storage fee in nanotons for storing `cells` cells of `bits` bits in total for
+    /// `seconds` in the workchain.
+    pub fn compute_storage_fee(
+        &self,
+        workchain: i32,
+        cells: u64,
+        bits: u64,
+        seconds: u64,
+    ) -> BigUint {
+        let (bit_price, cell_price) = if workchain == -1 {
+            (self.mc_bit_price_ps, self.mc_cell_price_ps)
+        } else {
+            (self.bit_price_ps, self.cell_price_ps)
+        };
+        let per_second = BigUint::from(bits) * bit_price + BigUint::from(cells) * cell_price;
+        let total: BigUint = per_second * seconds;
+        (total + 0xffffu32) >> 16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use crate::cell::CellBuilder;
+    use crate::transaction::StoragePrices;
+
+    #[test]
+    fn test_storage_prices() -> anyhow::Result<()> {
+        // storage prices of the mainnet
+        let cell = CellBuilder::new()
+            .store_u8(8, 0xcc)?
+            .store_u32(32, 0)?
+            .store_u64(64, 1)?
+            .store_u64(64, 500)?
+            .store_u64(64, 1000)?
+            .store_u64(64, 500_000)?
+            .build()?;
+        let prices = StoragePrices::parse(&cell)?;
+        assert_eq!(prices.cell_price_ps, 500);
+        assert_eq!(
+            prices.compute_storage_fee(0, 1, 700, 86_400),
+            BigUint::from(1_583u32)
+        );
+        assert_eq!(
+            prices.compute_storage_fee(-1, 1, 700, 86_400),
+            BigUint::from(1_582_032u32)
+        );
+
+        let invalid = CellBuilder::new().store_u8(8, 0xaa)?.build()?;
+        assert!(StoragePrices::parse(&invalid).is_err());
+        Ok(())
+    }
+}
@@ -2,10 +2,20 @@ mod ton_method_id;
 pub use ton_method_id::*;
 mod tvm_success;
 pub use tvm_success::*;
+mod tvm_error;
+pub use tvm_error::*;
+mod tvm_stack;
+pub use tvm_stack::*;
 mod tvm_stack_entry;
 pub use tvm_stack_entry::*;
 mod error;
 pub use error::*;
+mod error_context;
+pub use error_context::*;
+mod stack_codec;
+pub use stack_codec::*;
+mod stack_decoder;
+pub use stack_decoder::*;
 
 pub const TON_HASH_BYTES: usize = 32;
 pub const ZERO_HASH: TonHash = [0; 32];
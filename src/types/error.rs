@@ -16,6 +16,9 @@ pub enum StackParseError {
     #[error("Invalid stack size({0})")]
     InvalidStackSize(usize),
 
+    #[error("Invalid stack index {index} (stack size: {len})")]
+    InvalidStackIndex { index: usize, len: usize },
+
     #[error("Invalid stack entry({0})")]
     InvalidEntryValue(String),
 
@@ -0,0 +1,191 @@
+use std::fmt;
+
+use crate::address::TonAddress;
+use crate::tl::BlockIdExt;
+
+/// Annotations attached to an error on its way up: account, method, block and connection
+/// the failed operation was related to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErrorContext {
+    pub address: Option<TonAddress>,
+    pub method: Option<String>,
+    pub block_id: Option<BlockIdExt>,
+    pub connection: Option<String>,
+}
+
+impl ErrorContext {
+    pub fn address(address: &TonAddress) -> ErrorContext {
+        ErrorContext {
+            address: Some(address.clone()),
+            ..Default::default()
+        }
+    }
+
+    pub fn method(method: &str) -> ErrorContext {
+        ErrorContext {
+            method: Some(method.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn block(block_id: &BlockIdExt) -> ErrorContext {
+        ErrorContext {
+            block_id: Some(block_id.clone()),
+            ..Default::default()
+        }
+    }
+
+    pub fn connection(connection: &str) -> ErrorContext {
+        ErrorContext {
+            connection: Some(connection.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self == &ErrorContext::default()
+    }
+
+    /// Fills missing fields from the outer context. Fields already set are kept, since
+    /// the innermost context is the most specific one.
+    pub fn merge(mut self, outer: ErrorContext) -> ErrorContext {
+        self.address = self.address.or(outer.address);
+        self.method = self.method.or(outer.method);
+        self.block_id = self.block_id.or(outer.block_id);
+        self.connection = self.connection.or(outer.connection);
+        self
+    }
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = vec![];
+        if let Some(address) = &self.address {
+            parts.push(format!("address: {}", address));
+        }
+        if let Some(method) = &self.method {
+            parts.push(format!("method: {}", method));
+        }
+        if let Some(block_id) = &self.block_id {
+            parts.push(format!(
+                "block: ({},{:016x},{})",
+                block_id.workchain, block_id.shard as u64, block_id.seqno
+            ));
+        }
+        if let Some(connection) = &self.connection {
+            parts.push(format!("connection: {}", connection));
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// Error able to carry `ErrorContext`. Variants without a context field keep the error as is.
+pub trait ContextualError: Sized {
+    fn add_context(self, context: ErrorContext) -> Self;
+}
+
+/// Attaches context to the error of the result.
+pub trait WithErrorContext<R>: Sized {
+    fn with_context<F>(self, f: F) -> Self
+    where
+        F: FnOnce() -> ErrorContext;
+
+    fn with_address(self, address: &TonAddress) -> Self {
+        self.with_context(|| ErrorContext::address(address))
+    }
+
+    fn with_method(self, method: &str) -> Self {
+        self.with_context(|| ErrorContext::method(method))
+    }
+
+    fn with_block(self, block_id: &BlockIdExt) -> Self {
+        self.with_context(|| ErrorContext::block(block_id))
+    }
+
+    fn with_connection(self, connection: &str) -> Self {
+        self.with_context(|| ErrorContext::connection(connection))
+    }
+}
+
+impl<R, E> WithErrorContext<R> for Result<R, E>
+where
+    E: ContextualError,
+{
+    fn with_context<F>(self, f: F) -> Self
+    where
+        F: FnOnce() -> ErrorContext,
+    {
+        self.map_err(|e| e.add_context(f()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::address::TonAddress;
+    use crate::cell::TonCellError;
+    use crate::client::TonClientError;
+    use crate::contract::TonContractError;
+    use crate::tl::BlockIdExt;
+    use crate::types::{ErrorContext, WithErrorContext};
+
+    #[test]
+    fn test_error_context() {
+        let address = TonAddress::null();
+        let result: Result<(), TonClientError> = Err(TonClientError::tonlib_error(
+            "raw.getAccountState",
+            500,
+            "timeout".to_string(),
+        ));
+        let error = result
+            .with_connection("conn-1")
+            .with_address(&address)
+            .with_method("get_wallet_data")
+            .with_context(|| ErrorContext::connection("conn-2"))
+            .unwrap_err();
+        let context = error.context().unwrap();
+        assert_eq!(context.address, Some(address.clone()));
+        assert_eq!(context.method, Some("get_wallet_data".to_string()));
+        assert_eq!(context.connection, Some("conn-1".to_string()));
+        assert!(matches!(
+            error,
+            TonClientError::TonlibError { code: 500, .. }
+        ));
+        assert!(error.to_string().contains("connection: conn-1"));
+
+        let contract_error = Err::<(), _>(TonContractError::from(error))
+            .with_method("other")
+            .unwrap_err();
+        assert!(matches!(contract_error, TonContractError::ClientError(_)));
+        assert_eq!(
+            contract_error.context().unwrap().method,
+            Some("get_wallet_data".to_string())
+        );
+        assert!(matches!(
+            contract_error,
+            TonContractError::ClientError(TonClientError::TonlibError { .. })
+        ));
+
+        let block_id = BlockIdExt {
+            workchain: 0,
+            shard: i64::MIN,
+            seqno: 42,
+            root_hash: String::new(),
+            file_hash: String::new(),
+        };
+        let cell_error = Err::<(), _>(TonClientError::from(TonCellError::cell_parser_error(
+            "not enough bits",
+        )))
+        .with_block(&block_id)
+        .with_address(&address)
+        .unwrap_err();
+        assert_eq!(cell_error.context().unwrap().address, Some(address));
+        assert_eq!(cell_error.context().unwrap().block_id, Some(block_id));
+        assert!(cell_error
+            .to_string()
+            .contains("block: (0,8000000000000000,42)"));
+
+        let io_error = TonClientError::Io(std::io::Error::other("closed"));
+        let io_error = Err::<(), _>(io_error).with_method("m").unwrap_err();
+        assert!(io_error.context().is_none());
+    }
+}
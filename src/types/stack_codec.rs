@@ -0,0 +1,300 @@
+use std::sync::Arc;
+
+use num_bigint::{BigInt, BigUint};
+
+use crate::address::TonAddress;
+use crate::cell::{ArcCell, Cell, CellSlice};
+use crate::types::{StackParseError, TvmStack, TvmStackEntry};
+
+/// Value, which can be passed to a get-method as a stack entry.
+pub trait ToStackEntry {
+    fn to_stack_entry(&self) -> Result<TvmStackEntry, StackParseError>;
+}
+
+/// Value, which can be read from a stack entry of a get-method result.
+pub trait FromStackEntry: Sized {
+    fn from_stack_entry(entry: &TvmStackEntry) -> Result<Self, StackParseError>;
+}
+
+/// Arguments of a get-method: `()`, a tuple of `ToStackEntry` values, or a whole stack.
+pub trait ToTvmStack {
+    fn to_tvm_stack(&self) -> Result<TvmStack, StackParseError>;
+}
+
+/// Result of a get-method: a `FromStackEntry` value, a tuple of them, or a whole stack.
+pub trait FromTvmStack: Sized {
+    /// Expected stack size, `None` if any size is accepted.
+    const STACK_LEN: Option<usize>;
+
+    fn from_tvm_stack(stack: &[TvmStackEntry]) -> Result<Self, StackParseError>;
+}
+
+impl<T: ToStackEntry + ?Sized> ToStackEntry for &T {
+    fn to_stack_entry(&self) -> Result<TvmStackEntry, StackParseError> {
+        (**self).to_stack_entry()
+    }
+}
+
+impl ToStackEntry for TvmStackEntry {
+    fn to_stack_entry(&self) -> Result<TvmStackEntry, StackParseError> {
+        Ok(self.clone())
+    }
+}
+
+impl ToStackEntry for bool {
+    fn to_stack_entry(&self) -> Result<TvmStackEntry, StackParseError> {
+        Ok((*self).into())
+    }
+}
+
+impl ToStackEntry for i64 {
+    fn to_stack_entry(&self) -> Result<TvmStackEntry, StackParseError> {
+        Ok(TvmStackEntry::Int64(*self))
+    }
+}
+
+impl ToStackEntry for u64 {
+    fn to_stack_entry(&self) -> Result<TvmStackEntry, StackParseError> {
+        Ok(TvmStackEntry::int(*self))
+    }
+}
+
+impl ToStackEntry for BigInt {
+    fn to_stack_entry(&self) -> Result<TvmStackEntry, StackParseError> {
+        Ok(TvmStackEntry::Int257(self.clone()))
+    }
+}
+
+impl ToStackEntry for BigUint {
+    fn to_stack_entry(&self) -> Result<TvmStackEntry, StackParseError> {
+        Ok(TvmStackEntry::int(self.clone()))
+    }
+}
+
+impl ToStackEntry for Cell {
+    fn to_stack_entry(&self) -> Result<TvmStackEntry, StackParseError> {
+        Ok(TvmStackEntry::Cell(Arc::new(self.clone())))
+    }
+}
+
+impl ToStackEntry for ArcCell {
+    fn to_stack_entry(&self) -> Result<TvmStackEntry, StackParseError> {
+        Ok(TvmStackEntry::Cell(self.clone()))
+    }
+}
+
+impl ToStackEntry for CellSlice {
+    fn to_stack_entry(&self) -> Result<TvmStackEntry, StackParseError> {
+        Ok(TvmStackEntry::Slice(self.clone()))
+    }
+}
+
+impl ToStackEntry for TonAddress {
+    fn to_stack_entry(&self) -> Result<TvmStackEntry, StackParseError> {
+        TvmStackEntry::from_address(self)
+    }
+}
+
+impl<T: ToStackEntry> ToStackEntry for [T] {
+    fn to_stack_entry(&self) -> Result<TvmStackEntry, StackParseError> {
+        let elements = self
+            .iter()
+            .map(ToStackEntry::to_stack_entry)
+            .collect::<Result<_, _>>()?;
+        Ok(TvmStackEntry::Tuple(elements))
+    }
+}
+
+impl<T: ToStackEntry> ToStackEntry for Vec<T> {
+    fn to_stack_entry(&self) -> Result<TvmStackEntry, StackParseError> {
+        self.as_slice().to_stack_entry()
+    }
+}
+
+impl FromStackEntry for TvmStackEntry {
+    fn from_stack_entry(entry: &TvmStackEntry) -> Result<Self, StackParseError> {
+        Ok(entry.clone())
+    }
+}
+
+impl FromStackEntry for bool {
+    fn from_stack_entry(entry: &TvmStackEntry) -> Result<Self, StackParseError> {
+        entry.get_bool()
+    }
+}
+
+impl FromStackEntry for i64 {
+    fn from_stack_entry(entry: &TvmStackEntry) -> Result<Self, StackParseError> {
+        entry.get_i64()
+    }
+}
+
+impl FromStackEntry for u64 {
+    fn from_stack_entry(entry: &TvmStackEntry) -> Result<Self, StackParseError> {
+        entry.get_big_number()
+    }
+}
+
+impl FromStackEntry for BigInt {
+    fn from_stack_entry(entry: &TvmStackEntry) -> Result<Self, StackParseError> {
+        entry.get_bigint()
+    }
+}
+
+impl FromStackEntry for BigUint {
+    fn from_stack_entry(entry: &TvmStackEntry) -> Result<Self, StackParseError> {
+        entry.get_biguint()
+    }
+}
+
+impl FromStackEntry for ArcCell {
+    fn from_stack_entry(entry: &TvmStackEntry) -> Result<Self, StackParseError> {
+        entry.get_cell()
+    }
+}
+
+impl FromStackEntry for TonAddress {
+    fn from_stack_entry(entry: &TvmStackEntry) -> Result<Self, StackParseError> {
+        entry.get_address()
+    }
+}
+
+impl FromStackEntry for String {
+    fn from_stack_entry(entry: &TvmStackEntry) -> Result<Self, StackParseError> {
+        entry.get_string()
+    }
+}
+
+/// `Null` is read as `None`.
+impl<T: FromStackEntry> FromStackEntry for Option<T> {
+    fn from_stack_entry(entry: &TvmStackEntry) -> Result<Self, StackParseError> {
+        match entry {
+            TvmStackEntry::Null => Ok(None),
+            e => T::from_stack_entry(e).map(Some),
+        }
+    }
+}
+
+/// Elements of a tuple or of a list.
+impl<T: FromStackEntry> FromStackEntry for Vec<T> {
+    fn from_stack_entry(entry: &TvmStackEntry) -> Result<Self, StackParseError> {
+        let elements = match entry {
+            TvmStackEntry::Tuple(elements) => elements.clone(),
+            e => e.get_list()?,
+        };
+        elements.iter().map(T::from_stack_entry).collect()
+    }
+}
+
+impl ToTvmStack for TvmStack {
+    fn to_tvm_stack(&self) -> Result<TvmStack, StackParseError> {
+        Ok(self.clone())
+    }
+}
+
+impl ToTvmStack for () {
+    fn to_tvm_stack(&self) -> Result<TvmStack, StackParseError> {
+        Ok(TvmStack::new())
+    }
+}
+
+impl FromTvmStack for TvmStack {
+    const STACK_LEN: Option<usize> = None;
+
+    fn from_tvm_stack(stack: &[TvmStackEntry]) -> Result<Self, StackParseError> {
+        Ok(TvmStack::from(stack.to_vec()))
+    }
+}
+
+impl FromTvmStack for () {
+    const STACK_LEN: Option<usize> = Some(0);
+
+    fn from_tvm_stack(stack: &[TvmStackEntry]) -> Result<Self, StackParseError> {
+        check_stack_len(stack, 0)
+    }
+}
+
+impl<T: FromStackEntry> FromTvmStack for T {
+    const STACK_LEN: Option<usize> = Some(1);
+
+    fn from_tvm_stack(stack: &[TvmStackEntry]) -> Result<Self, StackParseError> {
+        check_stack_len(stack, 1)?;
+        T::from_stack_entry(&stack[0])
+    }
+}
+
+fn check_stack_len(stack: &[TvmStackEntry], len: usize) -> Result<(), StackParseError> {
+    if stack.len() == len {
+        Ok(())
+    } else {
+        Err(StackParseError::InvalidStackSize(stack.len()))
+    }
+}
+
+macro_rules! impl_stack_tuple {
+    ($len:expr; $($t:ident $i:tt),+) => {
+        impl<$($t: ToStackEntry),+> ToTvmStack for ($($t,)+) {
+            fn to_tvm_stack(&self) -> Result<TvmStack, StackParseError> {
+                Ok(TvmStack::from(vec![$(self.$i.to_stack_entry()?),+]))
+            }
+        }
+
+        impl<$($t: FromStackEntry),+> FromTvmStack for ($($t,)+) {
+            const STACK_LEN: Option<usize> = Some($len);
+
+            fn from_tvm_stack(stack: &[TvmStackEntry]) -> Result<Self, StackParseError> {
+                check_stack_len(stack, $len)?;
+                Ok(($($t::from_stack_entry(&stack[$i])?,)+))
+            }
+        }
+    };
+}
+
+impl_stack_tuple!(1; A 0);
+impl_stack_tuple!(2; A 0, B 1);
+impl_stack_tuple!(3; A 0, B 1, C 2);
+impl_stack_tuple!(4; A 0, B 1, C 2, D 3);
+impl_stack_tuple!(5; A 0, B 1, C 2, D 3, E 4);
+impl_stack_tuple!(6; A 0, B 1, C 2, D 3, E 4, F 5);
+impl_stack_tuple!(7; A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_stack_tuple!(8; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use crate::address::TonAddress;
+    use crate::types::{FromTvmStack, StackParseError, ToTvmStack, TvmStackEntry};
+
+    #[test]
+    fn test_stack_codec() -> anyhow::Result<()> {
+        let owner = TonAddress::new(0, &[5; 32]);
+        let stack = (&owner, 7u64, vec![1i64, 2]).to_tvm_stack()?;
+        assert_eq!(stack.get_address(0)?, owner);
+        assert_eq!(stack.get_i64(1)?, 7);
+        assert_eq!(
+            stack[2],
+            TvmStackEntry::Tuple(vec![TvmStackEntry::Int64(1), TvmStackEntry::Int64(2)])
+        );
+
+        let (address, amount, values, missing) =
+            <(TonAddress, BigUint, Vec<i64>, Option<i64>)>::from_tvm_stack(&[
+                stack[0].clone(),
+                stack[1].clone(),
+                stack[2].clone(),
+                TvmStackEntry::Null,
+            ])?;
+        assert_eq!(address, owner);
+        assert_eq!(amount, BigUint::from(7u32));
+        assert_eq!(values, vec![1, 2]);
+        assert_eq!(missing, None);
+
+        assert_eq!(TonAddress::from_tvm_stack(&stack[..1])?, owner);
+        assert!(matches!(
+            TonAddress::from_tvm_stack(&stack),
+            Err(StackParseError::InvalidStackSize(3))
+        ));
+        assert_eq!(<(i64, i64)>::STACK_LEN, Some(2));
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+use std::fmt;
+
+use crate::types::{StackParseError, TvmStackEntry};
+
+/// Handling of get-method results not matching the expected stack layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum StackDecodeMode {
+    /// Any mismatch is an error.
+    #[default]
+    Strict,
+    /// Mismatched or missing entries are decoded as `None` and recorded as warnings.
+    Lax,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StackDecodeWarning {
+    /// Index of the stack entry, `None` for warnings about the whole stack.
+    pub index: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for StackDecodeWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.index {
+            Some(index) => write!(f, "Stack entry {}: {}", index, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Decodes get-method result stack entry by entry in strict or lax mode.
+pub struct StackDecoder<'a> {
+    stack: &'a [TvmStackEntry],
+    mode: StackDecodeMode,
+    warnings: Vec<StackDecodeWarning>,
+}
+
+impl<'a> StackDecoder<'a> {
+    /// Checks stack size: in strict mode it must be equal to `expected_len`.
+    pub fn new(
+        stack: &'a [TvmStackEntry],
+        expected_len: usize,
+        mode: StackDecodeMode,
+    ) -> Result<StackDecoder<'a>, StackParseError> {
+        let mut decoder = StackDecoder {
+            stack,
+            mode,
+            warnings: vec![],
+        };
+        if stack.len() != expected_len {
+            match mode {
+                StackDecodeMode::Strict => {
+                    return Err(StackParseError::InvalidStackSize(stack.len()))
+                }
+                StackDecodeMode::Lax => decoder.warnings.push(StackDecodeWarning {
+                    index: None,
+                    message: format!(
+                        "Unexpected stack size (expected: {}, actual: {})",
+                        expected_len,
+                        stack.len()
+                    ),
+                }),
+            }
+        }
+        Ok(decoder)
+    }
+
+    /// Decodes the entry. Returns `Ok(None)` only in lax mode, recording a warning.
+    pub fn decode<T, F>(&mut self, index: usize, f: F) -> Result<Option<T>, StackParseError>
+    where
+        F: FnOnce(&TvmStackEntry) -> Result<T, StackParseError>,
+    {
+        let result = match self.stack.get(index) {
+            Some(entry) => f(entry),
+            None => Err(StackParseError::InvalidStackSize(self.stack.len())),
+        };
+        match (result, self.mode) {
+            (Ok(value), _) => Ok(Some(value)),
+            (Err(e), StackDecodeMode::Strict) => Err(e),
+            (Err(e), StackDecodeMode::Lax) => {
+                self.warnings.push(StackDecodeWarning {
+                    index: Some(index),
+                    message: e.to_string(),
+                });
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn mode(&self) -> StackDecodeMode {
+        self.mode
+    }
+
+    pub fn warnings(&self) -> &[StackDecodeWarning] {
+        &self.warnings
+    }
+
+    pub fn into_warnings(self) -> Vec<StackDecodeWarning> {
+        self.warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::{StackDecodeMode, StackDecoder, StackParseError, TvmStackEntry};
+
+    #[test]
+    fn test_stack_decoder() -> anyhow::Result<()> {
+        let stack = vec![TvmStackEntry::Int64(1), TvmStackEntry::Null];
+        assert!(matches!(
+            StackDecoder::new(&stack, 3, StackDecodeMode::Strict),
+            Err(StackParseError::InvalidStackSize(2))
+        ));
+
+        let mut decoder = StackDecoder::new(&stack, 2, StackDecodeMode::Strict)?;
+        assert_eq!(decoder.decode(0, |e| e.get_i64())?, Some(1));
+        assert!(decoder.decode(1, |e| e.get_i64()).is_err());
+
+        let mut decoder = StackDecoder::new(&stack, 3, StackDecodeMode::Lax)?;
+        assert_eq!(decoder.decode(0, |e| e.get_i64())?, Some(1));
+        assert_eq!(decoder.decode(1, |e| e.get_i64())?, None);
+        assert_eq!(decoder.decode(2, |e| e.get_i64())?, None);
+        let warnings = decoder.into_warnings();
+        assert_eq!(warnings.len(), 3);
+        assert_eq!(warnings[0].index, None);
+        assert_eq!(warnings[2].index, Some(2));
+        Ok(())
+    }
+}
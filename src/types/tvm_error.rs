@@ -0,0 +1,140 @@
+use std::fmt;
+
+/// Exit code of TVM, which does not indicate success (0 or 1).
+///
+/// Codes reserved by TVM and by the action phase are mapped to dedicated variants, other
+/// codes are thrown by the contract itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TvmError {
+    StackUnderflow,
+    StackOverflow,
+    IntegerOverflow,
+    RangeCheck,
+    InvalidOpcode,
+    TypeCheck,
+    CellOverflow,
+    CellUnderflow,
+    Dictionary,
+    /// Code 11, thrown by get-method selector if the method is not found.
+    UnknownMethod,
+    Fatal,
+    /// Code 13, or -14 as reported by get-method executor.
+    OutOfGas,
+    InvalidActionList,
+    ActionListTooLong,
+    InvalidAction,
+    InvalidSourceAddress,
+    InvalidDestinationAddress,
+    NotEnoughTon,
+    NotEnoughExtraCurrencies,
+    OutboundMessageTooLarge,
+    /// Exit code thrown by the contract.
+    Contract(i32),
+}
+
+impl TvmError {
+    /// Returns the error of the exit code, `None` for success codes 0 and 1.
+    pub fn from_exit_code(exit_code: i32) -> Option<TvmError> {
+        let error = match exit_code {
+            0 | 1 => return None,
+            2 => TvmError::StackUnderflow,
+            3 => TvmError::StackOverflow,
+            4 => TvmError::IntegerOverflow,
+            5 => TvmError::RangeCheck,
+            6 => TvmError::InvalidOpcode,
+            7 => TvmError::TypeCheck,
+            8 => TvmError::CellOverflow,
+            9 => TvmError::CellUnderflow,
+            10 => TvmError::Dictionary,
+            11 => TvmError::UnknownMethod,
+            12 => TvmError::Fatal,
+            13 | -14 => TvmError::OutOfGas,
+            32 => TvmError::InvalidActionList,
+            33 => TvmError::ActionListTooLong,
+            34 => TvmError::InvalidAction,
+            35 => TvmError::InvalidSourceAddress,
+            36 => TvmError::InvalidDestinationAddress,
+            37 => TvmError::NotEnoughTon,
+            38 => TvmError::NotEnoughExtraCurrencies,
+            40 => TvmError::OutboundMessageTooLarge,
+            code => TvmError::Contract(code),
+        };
+        Some(error)
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            TvmError::StackUnderflow => 2,
+            TvmError::StackOverflow => 3,
+            TvmError::IntegerOverflow => 4,
+            TvmError::RangeCheck => 5,
+            TvmError::InvalidOpcode => 6,
+            TvmError::TypeCheck => 7,
+            TvmError::CellOverflow => 8,
+            TvmError::CellUnderflow => 9,
+            TvmError::Dictionary => 10,
+            TvmError::UnknownMethod => 11,
+            TvmError::Fatal => 12,
+            TvmError::OutOfGas => 13,
+            TvmError::InvalidActionList => 32,
+            TvmError::ActionListTooLong => 33,
+            TvmError::InvalidAction => 34,
+            TvmError::InvalidSourceAddress => 35,
+            TvmError::InvalidDestinationAddress => 36,
+            TvmError::NotEnoughTon => 37,
+            TvmError::NotEnoughExtraCurrencies => 38,
+            TvmError::OutboundMessageTooLarge => 40,
+            TvmError::Contract(code) => *code,
+        }
+    }
+}
+
+impl fmt::Display for TvmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            TvmError::StackUnderflow => "stack underflow",
+            TvmError::StackOverflow => "stack overflow",
+            TvmError::IntegerOverflow => "integer overflow",
+            TvmError::RangeCheck => "range check error",
+            TvmError::InvalidOpcode => "invalid opcode",
+            TvmError::TypeCheck => "type check error",
+            TvmError::CellOverflow => "cell overflow",
+            TvmError::CellUnderflow => "cell underflow",
+            TvmError::Dictionary => "dictionary error",
+            TvmError::UnknownMethod => "unknown method",
+            TvmError::Fatal => "fatal error",
+            TvmError::OutOfGas => "out of gas",
+            TvmError::InvalidActionList => "invalid action list",
+            TvmError::ActionListTooLong => "action list too long",
+            TvmError::InvalidAction => "invalid action",
+            TvmError::InvalidSourceAddress => "invalid source address",
+            TvmError::InvalidDestinationAddress => "invalid destination address",
+            TvmError::NotEnoughTon => "not enough TON",
+            TvmError::NotEnoughExtraCurrencies => "not enough extra currencies",
+            TvmError::OutboundMessageTooLarge => "outbound message too large",
+            TvmError::Contract(code) => return write!(f, "contract error {}", code),
+        };
+        f.write_str(description)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::TvmError;
+
+    #[test]
+    fn test_tvm_error_from_exit_code() {
+        assert_eq!(TvmError::from_exit_code(0), None);
+        assert_eq!(TvmError::from_exit_code(1), None);
+        assert_eq!(TvmError::from_exit_code(11), Some(TvmError::UnknownMethod));
+        assert_eq!(TvmError::from_exit_code(-14), Some(TvmError::OutOfGas));
+        assert_eq!(TvmError::from_exit_code(705), Some(TvmError::Contract(705)));
+        for code in 2..=40 {
+            if let Some(error) = TvmError::from_exit_code(code) {
+                assert_eq!(error.exit_code(), code);
+            }
+        }
+        assert_eq!(TvmError::OutOfGas.to_string(), "out of gas");
+        assert_eq!(TvmError::Contract(705).to_string(), "contract error 705");
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::Deref;
+
+use num_bigint::{BigInt, BigUint};
+
+use crate::address::TonAddress;
+use crate::cell::{ArcCell, BigNumber, CellSlice, DictLoader};
+use crate::tl::TvmStack as TlTvmStack;
+use crate::types::{StackParseError, TvmStackEntry};
+
+/// Typed TVM stack of get-method arguments or results, the first element is the bottom of the
+/// stack.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TvmStack {
+    pub elements: Vec<TvmStackEntry>,
+}
+
+impl TvmStack {
+    pub fn new() -> TvmStack {
+        TvmStack::default()
+    }
+
+    pub fn push<T: Into<TvmStackEntry>>(&mut self, entry: T) -> &mut Self {
+        self.elements.push(entry.into());
+        self
+    }
+
+    pub fn get_bool(&self, index: usize) -> Result<bool, StackParseError> {
+        self.entry(index)?.get_bool()
+    }
+
+    pub fn get_i64(&self, index: usize) -> Result<i64, StackParseError> {
+        self.entry(index)?.get_i64()
+    }
+
+    pub fn get_bigint(&self, index: usize) -> Result<BigInt, StackParseError> {
+        self.entry(index)?.get_bigint()
+    }
+
+    pub fn get_biguint(&self, index: usize) -> Result<BigUint, StackParseError> {
+        self.entry(index)?.get_biguint()
+    }
+
+    pub fn get_big_number<T: BigNumber>(&self, index: usize) -> Result<T, StackParseError> {
+        self.entry(index)?.get_big_number()
+    }
+
+    pub fn get_cell(&self, index: usize) -> Result<ArcCell, StackParseError> {
+        self.entry(index)?.get_cell()
+    }
+
+    pub fn get_slice(&self, index: usize) -> Result<CellSlice, StackParseError> {
+        self.entry(index)?.get_slice()
+    }
+
+    pub fn get_address(&self, index: usize) -> Result<TonAddress, StackParseError> {
+        self.entry(index)?.get_address()
+    }
+
+    pub fn get_string(&self, index: usize) -> Result<String, StackParseError> {
+        self.entry(index)?.get_string()
+    }
+
+    pub fn get_tuple(&self, index: usize) -> Result<&[TvmStackEntry], StackParseError> {
+        self.entry(index)?.get_tuple()
+    }
+
+    pub fn get_list(&self, index: usize) -> Result<Vec<TvmStackEntry>, StackParseError> {
+        self.entry(index)?.get_list()
+    }
+
+    pub fn get_dict<K, V, L>(
+        &self,
+        index: usize,
+        loader: &L,
+    ) -> Result<HashMap<K, V>, StackParseError>
+    where
+        K: Hash + Eq + Clone,
+        L: DictLoader<K, V>,
+    {
+        self.entry(index)?.get_dict(loader)
+    }
+
+    fn entry(&self, index: usize) -> Result<&TvmStackEntry, StackParseError> {
+        self.elements
+            .get(index)
+            .ok_or(StackParseError::InvalidStackIndex {
+                index,
+                len: self.elements.len(),
+            })
+    }
+}
+
+impl Deref for TvmStack {
+    type Target = [TvmStackEntry];
+
+    fn deref(&self) -> &Self::Target {
+        &self.elements
+    }
+}
+
+impl AsRef<[TvmStackEntry]> for TvmStack {
+    fn as_ref(&self) -> &[TvmStackEntry] {
+        &self.elements
+    }
+}
+
+impl From<Vec<TvmStackEntry>> for TvmStack {
+    fn from(elements: Vec<TvmStackEntry>) -> Self {
+        TvmStack { elements }
+    }
+}
+
+impl FromIterator<TvmStackEntry> for TvmStack {
+    fn from_iter<I: IntoIterator<Item = TvmStackEntry>>(iter: I) -> Self {
+        TvmStack {
+            elements: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl TryFrom<&TlTvmStack> for TvmStack {
+    type Error = StackParseError;
+
+    fn try_from(value: &TlTvmStack) -> Result<Self, Self::Error> {
+        value.elements.iter().map(TvmStackEntry::try_from).collect()
+    }
+}
+
+impl TryFrom<&TvmStack> for TlTvmStack {
+    type Error = StackParseError;
+
+    fn try_from(value: &TvmStack) -> Result<Self, Self::Error> {
+        let elements = value
+            .elements
+            .iter()
+            .map(|e| e.try_into())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(TlTvmStack { elements })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use crate::address::TonAddress;
+    use crate::cell::CellBuilder;
+    use crate::tl::TvmStack as TlTvmStack;
+    use crate::types::{StackParseError, TvmStack, TvmStackEntry};
+
+    #[test]
+    fn test_tvm_stack() -> anyhow::Result<()> {
+        let address = TonAddress::new(0, &[3; 32]);
+        let cell = CellBuilder::new().store_u8(8, 1)?.build()?;
+        let mut stack = TvmStack::new();
+        stack
+            .push(TvmStackEntry::int(1_000_000_000u64))
+            .push(TvmStackEntry::from_address(&address)?)
+            .push(TvmStackEntry::slice_from_cell(cell.clone())?)
+            .push(cell.clone());
+
+        let tl = TlTvmStack::try_from(&stack)?;
+        let stack = TvmStack::try_from(&tl)?;
+        assert_eq!(stack.len(), 4);
+        assert_eq!(stack.get_biguint(0)?, BigUint::from(1_000_000_000u64));
+        assert_eq!(stack.get_big_number::<u64>(0)?, 1_000_000_000);
+        assert_eq!(stack.get_address(1)?, address);
+        assert_eq!(stack.get_cell(3)?.as_ref(), &cell);
+        assert!(stack.get_cell(0).is_err());
+        assert!(matches!(
+            stack.get_i64(4),
+            Err(StackParseError::InvalidStackIndex { index: 4, len: 4 })
+        ));
+        Ok(())
+    }
+}
@@ -6,8 +6,10 @@ use num_bigint::{BigInt, BigUint};
 use strum::Display;
 
 use crate::address::TonAddress;
-use crate::cell::{ArcCell, BagOfCells, Cell, CellBuilder, CellSlice, DictLoader};
-use crate::tl::{TvmCell, TvmNumber, TvmSlice, TvmStackEntry as TlTvmStackEntry};
+use crate::cell::{ArcCell, BagOfCells, BigNumber, Cell, CellBuilder, CellSlice, DictLoader};
+use crate::tl::{
+    TvmCell, TvmList, TvmNumber, TvmSlice, TvmStackEntry as TlTvmStackEntry, TvmTuple,
+};
 use crate::types::StackParseError;
 
 #[derive(Debug, Display, Clone, PartialEq)]
@@ -18,10 +20,35 @@ pub enum TvmStackEntry {
     Int257(BigInt),
     Cell(ArcCell),
     Slice(CellSlice),
+    Tuple(Vec<TvmStackEntry>),
+    /// List of values, represented in TVM by nested pairs `[a, [b, [c, null]]]`.
+    List(Vec<TvmStackEntry>),
     Unsupported,
 }
 
+/// Maximal number of elements of a TVM tuple.
+pub const TVM_MAX_TUPLE_LEN: usize = 255;
+
 impl TvmStackEntry {
+    pub fn int<T: Into<BigInt>>(value: T) -> TvmStackEntry {
+        TvmStackEntry::Int257(value.into())
+    }
+
+    pub fn slice_from_cell(cell: Cell) -> Result<TvmStackEntry, StackParseError> {
+        Ok(TvmStackEntry::Slice(CellSlice::full_cell(cell)?))
+    }
+
+    /// Returns the address as a slice (`MsgAddress`), the way get-methods take addresses.
+    /// Use `get_address` for the reverse.
+    pub fn from_address(address: &TonAddress) -> Result<TvmStackEntry, StackParseError> {
+        TvmStackEntry::try_from(address)
+    }
+
+    /// Same as `from_address`.
+    pub fn address(address: &TonAddress) -> Result<TvmStackEntry, StackParseError> {
+        TvmStackEntry::from_address(address)
+    }
+
     pub fn get_bool(&self) -> Result<bool, StackParseError> {
         match self {
             TvmStackEntry::Int64(number) => match number {
@@ -84,6 +111,14 @@ impl TvmStackEntry {
             .map_err(|_| StackParseError::InvalidEntryValue("Positive number expected".to_string()))
     }
 
+    /// Returns unsigned number as any `BigNumber` type, e.g. `u128` for balances.
+    pub fn get_big_number<T: BigNumber>(&self) -> Result<T, StackParseError> {
+        let value = self.get_biguint()?;
+        T::from_biguint(&value).ok_or_else(|| {
+            StackParseError::InvalidEntryValue(format!("Number {} is out of range", value))
+        })
+    }
+
     pub fn get_cell(&self) -> Result<ArcCell, StackParseError> {
         match self {
             TvmStackEntry::Cell(cell) => Ok(cell.clone()),
@@ -94,6 +129,18 @@ impl TvmStackEntry {
         }
     }
 
+    /// Returns the slice, or the whole cell as a slice.
+    pub fn get_slice(&self) -> Result<CellSlice, StackParseError> {
+        match self {
+            TvmStackEntry::Slice(slice) => Ok(slice.clone()),
+            TvmStackEntry::Cell(cell) => Ok(CellSlice::full_cell(cell.as_ref().clone())?),
+            t => Err(StackParseError::InvalidEntryType {
+                expected: "Slice".to_string(),
+                found: t.clone(),
+            }),
+        }
+    }
+
     pub fn get_address(&self) -> Result<TonAddress, StackParseError> {
         match self {
             TvmStackEntry::Cell(cell) => cell
@@ -123,6 +170,51 @@ impl TvmStackEntry {
         }
     }
 
+    pub fn get_tuple(&self) -> Result<&[TvmStackEntry], StackParseError> {
+        match self {
+            TvmStackEntry::Tuple(elements) => Ok(elements),
+            t => Err(StackParseError::InvalidEntryType {
+                expected: "Tuple".to_string(),
+                found: t.clone(),
+            }),
+        }
+    }
+
+    /// Returns elements of the list, which is either `List` or nested pairs ending with `Null`.
+    pub fn get_list(&self) -> Result<Vec<TvmStackEntry>, StackParseError> {
+        let mut elements = vec![];
+        let mut entry = self;
+        loop {
+            match entry {
+                TvmStackEntry::List(list) if elements.is_empty() => return Ok(list.clone()),
+                TvmStackEntry::Null => return Ok(elements),
+                TvmStackEntry::Tuple(pair) if pair.len() == 2 => {
+                    elements.push(pair[0].clone());
+                    entry = &pair[1];
+                }
+                t => {
+                    return Err(StackParseError::InvalidEntryType {
+                        expected: "List".to_string(),
+                        found: t.clone(),
+                    })
+                }
+            }
+        }
+    }
+
+    /// Returns the list as nested pairs `[a, [b, [c, null]]]`, other entries are returned as is.
+    pub fn to_pairs(&self) -> TvmStackEntry {
+        match self {
+            TvmStackEntry::List(list) => list.iter().rev().fold(TvmStackEntry::Null, |tail, e| {
+                TvmStackEntry::Tuple(vec![e.to_pairs(), tail])
+            }),
+            TvmStackEntry::Tuple(elements) => {
+                TvmStackEntry::Tuple(elements.iter().map(TvmStackEntry::to_pairs).collect())
+            }
+            e => e.clone(),
+        }
+    }
+
     pub fn get_dict<K, V, L>(&self, loader: &L) -> Result<HashMap<K, V>, StackParseError>
     where
         K: Hash + Eq + Clone,
@@ -173,6 +265,12 @@ impl From<Cell> for TvmStackEntry {
     }
 }
 
+impl From<Vec<TvmStackEntry>> for TvmStackEntry {
+    fn from(value: Vec<TvmStackEntry>) -> Self {
+        TvmStackEntry::Tuple(value)
+    }
+}
+
 impl TryFrom<&TonAddress> for TvmStackEntry {
     type Error = StackParseError;
 
@@ -222,6 +320,19 @@ impl TryFrom<&TvmStackEntry> for TlTvmStackEntry {
                     number: number.to_string(),
                 },
             },
+            TvmStackEntry::Tuple(elements) => TlTvmStackEntry::Tuple {
+                tuple: TvmTuple {
+                    elements: tl_tuple_elements(elements)?,
+                },
+            },
+            TvmStackEntry::List(elements) => TlTvmStackEntry::List {
+                list: TvmList {
+                    elements: elements
+                        .iter()
+                        .map(TlTvmStackEntry::try_from)
+                        .collect::<Result<_, _>>()?,
+                },
+            },
             TvmStackEntry::Unsupported => TlTvmStackEntry::Unsupported {},
             TvmStackEntry::Null => TlTvmStackEntry::Unsupported {},
             TvmStackEntry::Nan => TlTvmStackEntry::Unsupported {},
@@ -261,12 +372,88 @@ impl TryFrom<&TlTvmStackEntry> for TvmStackEntry {
                 TvmStackEntry::Int257(number)
             }
 
-            TlTvmStackEntry::Tuple { tuple: _ } => TvmStackEntry::Unsupported,
+            TlTvmStackEntry::Tuple { tuple } => TvmStackEntry::Tuple(
+                tuple
+                    .elements
+                    .iter()
+                    .map(TvmStackEntry::try_from)
+                    .collect::<Result<_, _>>()?,
+            ),
 
-            TlTvmStackEntry::List { list: _ } => TvmStackEntry::Unsupported,
+            TlTvmStackEntry::List { list } => TvmStackEntry::List(
+                list.elements
+                    .iter()
+                    .map(TvmStackEntry::try_from)
+                    .collect::<Result<_, _>>()?,
+            ),
 
             TlTvmStackEntry::Unsupported {} => TvmStackEntry::Unsupported,
         };
         Ok(entry)
     }
 }
+
+fn tl_tuple_elements(elements: &[TvmStackEntry]) -> Result<Vec<TlTvmStackEntry>, StackParseError> {
+    if elements.len() > TVM_MAX_TUPLE_LEN {
+        return Err(StackParseError::InvalidEntryValue(format!(
+            "Tuple of {} elements exceeds {}",
+            elements.len(),
+            TVM_MAX_TUPLE_LEN
+        )));
+    }
+    elements.iter().map(TlTvmStackEntry::try_from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::address::TonAddress;
+    use crate::tl::TvmStackEntry as TlTvmStackEntry;
+    use crate::types::{TvmStackEntry, TVM_MAX_TUPLE_LEN};
+
+    #[test]
+    fn test_tuple_tl_round_trip() -> anyhow::Result<()> {
+        let entry = TvmStackEntry::Tuple(vec![
+            TvmStackEntry::Int257(1.into()),
+            TvmStackEntry::List(vec![TvmStackEntry::Int257(2.into())]),
+            TvmStackEntry::Tuple(vec![]),
+        ]);
+        let tl = TlTvmStackEntry::try_from(&entry)?;
+        assert_eq!(TvmStackEntry::try_from(&tl)?, entry);
+
+        let too_long = TvmStackEntry::Tuple(vec![TvmStackEntry::Int64(0); TVM_MAX_TUPLE_LEN + 1]);
+        assert!(TlTvmStackEntry::try_from(&too_long).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_address_slice() -> anyhow::Result<()> {
+        let address = TonAddress::new(-1, &[9; 32]);
+        let entry = TvmStackEntry::from_address(&address)?;
+        assert_eq!(TvmStackEntry::address(&address)?, entry);
+        let slice = entry.get_slice()?;
+        assert_eq!(slice.end_bit, 267);
+        assert_eq!(entry.get_address()?, address);
+        let cell = TvmStackEntry::Cell(slice.into_cell()?.into());
+        assert_eq!(cell.get_address()?, address);
+        assert_eq!(cell.get_slice()?, slice);
+        assert!(TvmStackEntry::Null.get_slice().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_pairs() -> anyhow::Result<()> {
+        let list = TvmStackEntry::List(vec![TvmStackEntry::Int64(1), TvmStackEntry::Int64(2)]);
+        let pairs = list.to_pairs();
+        assert_eq!(
+            pairs,
+            TvmStackEntry::Tuple(vec![
+                TvmStackEntry::Int64(1),
+                TvmStackEntry::Tuple(vec![TvmStackEntry::Int64(2), TvmStackEntry::Null]),
+            ])
+        );
+        assert_eq!(pairs.get_list()?, list.get_list()?);
+        assert!(TvmStackEntry::Null.get_list()?.is_empty());
+        assert!(TvmStackEntry::Int64(1).get_list().is_err());
+        Ok(())
+    }
+}
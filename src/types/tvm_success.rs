@@ -1,13 +1,24 @@
 use crate::cell::ArcCell;
-use crate::types::TvmStackEntry;
+use crate::types::{TvmError, TvmStackEntry};
 
-#[derive(Debug)]
+/// Where the get-method was executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TvmRunSource {
+    /// Local TVM emulator.
+    #[default]
+    Emulator,
+    /// Liteserver, via tonlib `smc.runGetMethod`.
+    Liteserver,
+}
+
+#[derive(Debug, Clone)]
 pub struct TvmSuccess {
     pub vm_log: Option<String>,
     pub vm_exit_code: i32,
     pub stack: Vec<TvmStackEntry>,
     pub missing_library: Option<String>,
     pub gas_used: i32,
+    pub source: TvmRunSource,
 }
 
 impl TvmSuccess {
@@ -20,6 +31,10 @@ impl TvmSuccess {
     pub fn exit_error(&self) -> bool {
         !self.exit_success()
     }
+
+    pub fn tvm_error(&self) -> Option<TvmError> {
+        TvmError::from_exit_code(self.vm_exit_code)
+    }
 }
 
 #[derive(Debug)]
@@ -1,20 +1,41 @@
+mod send_queue;
+mod signer;
+mod stake_recovery;
+mod store;
+mod tx_builder;
 mod types;
 
 use std::sync::Arc;
 
 use lazy_static::lazy_static;
-use nacl::sign::signature;
+pub use send_queue::*;
+pub use signer::*;
+pub use stake_recovery::*;
+pub use store::*;
+pub use tx_builder::*;
 pub use types::*;
 
 use crate::address::TonAddress;
 use crate::cell::{
     ArcCell, BagOfCells, Cell, CellBuilder, StateInit, StateInitBuilder, TonCellError,
 };
+use crate::contract::{ContractKind, STANDARD_CODE_REGISTRY};
+use crate::include_boc;
 use crate::message::{TonMessageError, ZERO_COINS};
 use crate::mnemonic::KeyPair;
+use crate::types::TonHash;
 
 pub const DEFAULT_WALLET_ID: i32 = 0x29a9a317;
+/// Default wallet id of V5R1 wallet in mainnet basechain.
+pub const DEFAULT_WALLET_ID_V5R1: i32 = 0x7FFFFF11;
+pub const MAINNET_GLOBAL_ID: i32 = -239;
+pub const TESTNET_GLOBAL_ID: i32 = -3;
 
+const WALLET_V5R1_SIGNED_EXTERNAL: u32 = 0x7369676e;
+const WALLET_V5R1_ACTION_SEND_MSG: u32 = 0x0ec3c86d;
+
+// Bags of cells of the wallet codes, kept for compatibility. `WalletVersion::code` embeds the
+// same files with `include_boc!` and verifies their hashes.
 lazy_static! {
     pub static ref WALLET_V1R1_CODE: BagOfCells = {
         let code = include_str!("../resources/wallet/wallet_v1r1.code");
@@ -52,6 +73,10 @@ lazy_static! {
         let code = include_str!("../resources/wallet/wallet_v4r2.code");
         BagOfCells::parse_base64(code).unwrap()
     };
+    pub static ref WALLET_V5R1_CODE: BagOfCells = {
+        let code = include_str!("../resources/wallet/wallet_v5r1.code");
+        BagOfCells::parse_base64(code).unwrap()
+    };
     pub static ref HIGHLOAD_V1R1_CODE: BagOfCells = {
         let code = include_str!("../resources/wallet/highload_v1r1.code");
         BagOfCells::parse_base64(code).unwrap()
@@ -74,7 +99,7 @@ lazy_static! {
     };
 }
 
-#[derive(PartialEq, Eq, Clone, Hash)]
+#[derive(PartialEq, Eq, Clone, Hash, Debug)]
 pub enum WalletVersion {
     V1R1,
     V1R2,
@@ -85,6 +110,7 @@ pub enum WalletVersion {
     V3R2,
     V4R1,
     V4R2,
+    V5R1,
     HighloadV1R1,
     HighloadV1R2,
     HighloadV2,
@@ -94,23 +120,68 @@ pub enum WalletVersion {
 
 impl WalletVersion {
     pub fn code(&self) -> Result<&ArcCell, TonCellError> {
-        let code: &BagOfCells = match self {
-            WalletVersion::V1R1 => &WALLET_V1R1_CODE,
-            WalletVersion::V1R2 => &WALLET_V1R2_CODE,
-            WalletVersion::V1R3 => &WALLET_V1R3_CODE,
-            WalletVersion::V2R1 => &WALLET_V2R1_CODE,
-            WalletVersion::V2R2 => &WALLET_V2R2_CODE,
-            WalletVersion::V3R1 => &WALLET_V3R1_CODE,
-            WalletVersion::V3R2 => &WALLET_V3R2_CODE,
-            WalletVersion::V4R1 => &WALLET_V4R1_CODE,
-            WalletVersion::V4R2 => &WALLET_V4R2_CODE,
-            WalletVersion::HighloadV1R1 => &HIGHLOAD_V1R1_CODE,
-            WalletVersion::HighloadV1R2 => &HIGHLOAD_V1R2_CODE,
-            WalletVersion::HighloadV2 => &HIGHLOAD_V2_CODE,
-            WalletVersion::HighloadV2R1 => &HIGHLOAD_V2R1_CODE,
-            WalletVersion::HighloadV2R2 => &HIGHLOAD_V2R2_CODE,
-        };
-        code.single_root()
+        match self {
+            WalletVersion::V1R1 => include_boc!(
+                "../resources/wallet/wallet_v1r1.code",
+                "a0cfc2c48aee16a271f2cfc0b7382d81756cecb1017d077faaab3bb602f6868c"
+            ),
+            WalletVersion::V1R2 => include_boc!(
+                "../resources/wallet/wallet_v1r2.code",
+                "d4902fcc9fad74698fa8e353220a68da0dcf72e32bcb2eb9ee04217c17d3062c"
+            ),
+            WalletVersion::V1R3 => include_boc!(
+                "../resources/wallet/wallet_v1r3.code",
+                "587cc789eff1c84f46ec3797e45fc809a14ff5ae24f1e0c7a6a99cc9dc9061ff"
+            ),
+            WalletVersion::V2R1 => include_boc!(
+                "../resources/wallet/wallet_v2r1.code",
+                "5c9a5e68c108e18721a07c42f9956bfb39ad77ec6d624b60c576ec88eee65329"
+            ),
+            WalletVersion::V2R2 => include_boc!(
+                "../resources/wallet/wallet_v2r2.code",
+                "fe9530d3243853083ef2ef0b4c2908c0abf6fa1c31ea243aacaa5bf8c7d753f1"
+            ),
+            WalletVersion::V3R1 => include_boc!(
+                "../resources/wallet/wallet_v3r1.code",
+                "b61041a58a7980b946e8fb9e198e3c904d24799ffa36574ea4251c41a566f581"
+            ),
+            WalletVersion::V3R2 => include_boc!(
+                "../resources/wallet/wallet_v3r2.code",
+                "84dafa449f98a6987789ba232358072bc0f76dc4524002a5d0918b9a75d2d599"
+            ),
+            WalletVersion::V4R1 => include_boc!(
+                "../resources/wallet/wallet_v4r1.code",
+                "64dd54805522c5be8a9db59cea0105ccf0d08786ca79beb8cb79e880a8d7322d"
+            ),
+            WalletVersion::V4R2 => include_boc!(
+                "../resources/wallet/wallet_v4r2.code",
+                "feb5ff6820e2ff0d9483e7e0d62c817d846789fb4ae580c878866d959dabd5c0"
+            ),
+            WalletVersion::V5R1 => include_boc!(
+                "../resources/wallet/wallet_v5r1.code",
+                "20834b7b72b112147e1b2fb457b84e74d1a30f04f737d4f62a668e9552d2b72f"
+            ),
+            WalletVersion::HighloadV1R1 => include_boc!(
+                "../resources/wallet/highload_v1r1.code",
+                "d8cdbbb79f2c5caa677ac450770be0351be21e1250486de85cc52aa33dd16484"
+            ),
+            WalletVersion::HighloadV1R2 => include_boc!(
+                "../resources/wallet/highload_v1r2.code",
+                "0dceed21269d66013e95b19fbb5c55a6f01adad40837baa8e521cde3a02aa46c"
+            ),
+            WalletVersion::HighloadV2 => include_boc!(
+                "../resources/wallet/highload_v2.code",
+                "9494d1cc8edf12f05671a1a9ba09921096eb50811e1924ec65c3c629fbb80812"
+            ),
+            WalletVersion::HighloadV2R1 => include_boc!(
+                "../resources/wallet/highload_v2r1.code",
+                "8ceb45b3cd4b5cc60eaae1c13b9c092392677fe536b2e9b2d801b62eff931fe1"
+            ),
+            WalletVersion::HighloadV2R2 => include_boc!(
+                "../resources/wallet/highload_v2r2.code",
+                "203dd4f358adb49993129aa925cac39916b68a0e4f78d26e8f2c2b69eafa5679"
+            ),
+        }
     }
 
     pub fn initial_data(
@@ -146,6 +217,13 @@ impl WalletVersion {
                 public_key,
             }
             .try_into()?,
+            WalletVersion::V5R1 => WalletDataV5 {
+                signature_allowed: true,
+                seqno: 0,
+                wallet_id,
+                public_key,
+            }
+            .try_into()?,
             WalletVersion::HighloadV2R2 => WalletDataHighloadV2R2 {
                 wallet_id,
                 last_cleaned_time: 0,
@@ -165,9 +243,42 @@ impl WalletVersion {
         Ok(Arc::new(data_cell))
     }
 
+    /// Detects wallet version by the hash of the contract code.
+    pub fn from_code_hash(code_hash: &TonHash) -> Option<WalletVersion> {
+        match STANDARD_CODE_REGISTRY.kind_of(code_hash) {
+            Some(ContractKind::Wallet(version)) => Some(version.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns wallet id of the subwallet in the workchain, as computed by wallet apps.
+    ///
+    /// For V5R1 the id also depends on the network (see `MAINNET_GLOBAL_ID`, `TESTNET_GLOBAL_ID`)
+    /// and `subwallet` is limited to 15 bits. V1 and V2 wallets have no wallet id.
+    pub fn wallet_id(&self, workchain: i32, subwallet: u32, network_global_id: i32) -> i32 {
+        match self {
+            WalletVersion::V5R1 => {
+                let context =
+                    (1u32 << 31) | (((workchain as u32) & 0xff) << 23) | (subwallet & 0x7fff);
+                network_global_id ^ context as i32
+            }
+            _ => DEFAULT_WALLET_ID
+                .wrapping_add(workchain)
+                .wrapping_add(subwallet as i32),
+        }
+    }
+
     pub fn has_op(&self) -> bool {
         matches!(self, WalletVersion::V4R2)
     }
+
+    /// Maximum number of internal messages sent by a single external message.
+    pub fn max_messages(&self) -> usize {
+        match self {
+            WalletVersion::V5R1 => 255,
+            _ => 4,
+        }
+    }
 }
 
 #[derive(PartialEq, Eq, Clone, Hash)]
@@ -209,7 +320,10 @@ impl TonWallet {
         version: WalletVersion,
         key_pair: &KeyPair,
     ) -> Result<TonWallet, TonCellError> {
-        let wallet_id = DEFAULT_WALLET_ID;
+        let wallet_id = match version {
+            WalletVersion::V5R1 => DEFAULT_WALLET_ID_V5R1,
+            _ => DEFAULT_WALLET_ID,
+        };
         let data = version.initial_data(key_pair, wallet_id)?;
         let code = version.code()?;
         let state_init_hash = StateInit::create_account_id(code, &data)?;
@@ -230,6 +344,17 @@ impl TonWallet {
         })
     }
 
+    /// Derives wallet with the wallet id of the subwallet in mainnet, see `WalletVersion::wallet_id`.
+    pub fn derive_subwallet(
+        workchain: i32,
+        version: WalletVersion,
+        key_pair: &KeyPair,
+        subwallet: u32,
+    ) -> Result<TonWallet, TonCellError> {
+        let wallet_id = version.wallet_id(workchain, subwallet, MAINNET_GLOBAL_ID);
+        Self::derive(workchain, version, key_pair, wallet_id)
+    }
+
     pub fn create_external_message<T: AsRef<[ArcCell]>>(
         &self,
         expire_at: u32,
@@ -249,6 +374,9 @@ impl TonWallet {
         seqno: u32,
         internal_messages: T,
     ) -> Result<Cell, TonCellError> {
+        if self.version == WalletVersion::V5R1 {
+            return self.create_external_body_v5(expire_at, seqno, internal_messages);
+        }
         let mut builder = CellBuilder::new();
         builder
             .store_i32(32, self.wallet_id)?
@@ -264,13 +392,63 @@ impl TonWallet {
         builder.build()
     }
 
+    fn create_external_body_v5<T: AsRef<[ArcCell]>>(
+        &self,
+        expire_at: u32,
+        seqno: u32,
+        internal_messages: T,
+    ) -> Result<Cell, TonCellError> {
+        let mut builder = CellBuilder::new();
+        builder
+            .store_u32(32, WALLET_V5R1_SIGNED_EXTERNAL)?
+            .store_i32(32, self.wallet_id)?
+            .store_u32(32, expire_at)?
+            .store_u32(32, seqno)?;
+        if internal_messages.as_ref().is_empty() {
+            builder.store_bit(false)?; // no out actions
+        } else {
+            let mut out_list = CellBuilder::new().build()?;
+            for internal_message in internal_messages.as_ref() {
+                out_list = CellBuilder::new()
+                    .store_child(out_list)?
+                    .store_u32(32, WALLET_V5R1_ACTION_SEND_MSG)?
+                    .store_u8(8, 3)? // send_mode
+                    .store_reference(internal_message)?
+                    .build()?;
+            }
+            builder.store_bit(true)?;
+            builder.store_child(out_list)?;
+        }
+        builder.store_bit(false)?; // no extended actions
+        builder.build()
+    }
+
     pub fn sign_external_body(&self, external_body: &Cell) -> Result<Cell, TonMessageError> {
+        self.sign_external_body_with(external_body, &self.key_pair)
+    }
+
+    /// Signs external body by the signer instead of the wallet key pair.
+    ///
+    /// The signer must hold the key of the wallet, otherwise the message would be rejected.
+    pub fn sign_external_body_with(
+        &self,
+        external_body: &Cell,
+        signer: &dyn Signer,
+    ) -> Result<Cell, TonMessageError> {
+        if signer.public_key() != self.key_pair.public_key.as_slice() {
+            return Err(TonMessageError::SignerKeyMismatch);
+        }
         let message_hash = external_body.cell_hash();
-        let sig = signature(message_hash.as_slice(), self.key_pair.secret_key.as_slice())
-            .map_err(|e| TonMessageError::NaclCryptographicError(e.message))?;
+        let sig = signer.sign(message_hash.as_slice())?;
         let mut body_builder = CellBuilder::new();
-        body_builder.store_slice(sig.as_slice())?;
-        body_builder.store_cell(external_body)?;
+        if self.version == WalletVersion::V5R1 {
+            // V5 expects signature at the end of the body
+            body_builder.store_cell(external_body)?;
+            body_builder.store_slice(sig.as_slice())?;
+        } else {
+            body_builder.store_slice(sig.as_slice())?;
+            body_builder.store_cell(external_body)?;
+        }
         Ok(body_builder.build()?)
     }
 
@@ -289,7 +467,7 @@ impl TonWallet {
             wrap_builder.store_bit(true)?; // state init present
             wrap_builder.store_bit(true)?; // state init in ref
             let initial_data = self.version.initial_data(&self.key_pair, self.wallet_id)?;
-            let code = WALLET_V4R2_CODE.single_root()?.clone();
+            let code = self.version.code()?.clone();
             let state_init = StateInitBuilder::new(&code, &initial_data).build()?;
             wrap_builder.store_child(state_init)?;
         } else {
@@ -304,8 +482,13 @@ impl TonWallet {
 #[cfg(test)]
 mod tests {
     use crate::address::TonAddress;
+    use crate::cell::CellBuilder;
+    use crate::message::TonMessageError;
     use crate::mnemonic::Mnemonic;
-    use crate::wallet::{TonWallet, WalletVersion};
+    use crate::wallet::{
+        TonWallet, WalletVersion, DEFAULT_WALLET_ID, DEFAULT_WALLET_ID_V5R1, MAINNET_GLOBAL_ID,
+        TESTNET_GLOBAL_ID,
+    };
 
     #[test]
     fn derive_wallet_works() -> anyhow::Result<()> {
@@ -325,6 +508,118 @@ mod tests {
         let expected_v4r2: TonAddress =
             "EQCDM_QGggZ3qMa_f3lRPk4_qLDnLTqdi6OkMAV2NB9r5TG3".parse()?;
         assert_eq!(wallet_v4r2.address, expected_v4r2);
+
+        let v5_mnemonic_str = "section garden tomato dinner season dice renew length useful \
+        spin trade intact use universe what post spike keen mandate behind concert egg doll rug";
+        let key_pair_v5 = Mnemonic::from_str(v5_mnemonic_str, &None)?.to_key_pair()?;
+        let wallet_v5 = TonWallet::derive_default(WalletVersion::V5R1, &key_pair_v5)?;
+        let expected_v5: TonAddress = "UQDv2YSmlrlLH3hLNOVxC8FcQf4F9eGNs4vb2zKma4txo6i3".parse()?;
+        assert_eq!(wallet_v5.address, expected_v5);
         Ok(())
     }
+
+    #[test]
+    fn wallet_id_works() {
+        assert_eq!(
+            WalletVersion::V4R2.wallet_id(0, 0, MAINNET_GLOBAL_ID),
+            DEFAULT_WALLET_ID
+        );
+        assert_eq!(
+            WalletVersion::V3R2.wallet_id(-1, 0, MAINNET_GLOBAL_ID),
+            698983190
+        );
+        assert_eq!(
+            WalletVersion::V3R2.wallet_id(0, 5, MAINNET_GLOBAL_ID),
+            698983196
+        );
+        assert_eq!(
+            WalletVersion::V5R1.wallet_id(0, 0, MAINNET_GLOBAL_ID),
+            DEFAULT_WALLET_ID_V5R1
+        );
+        assert_eq!(
+            WalletVersion::V5R1.wallet_id(0, 0, TESTNET_GLOBAL_ID),
+            0x7FFFFFFD
+        );
+        assert_eq!(
+            WalletVersion::V5R1.wallet_id(0, 1, MAINNET_GLOBAL_ID),
+            DEFAULT_WALLET_ID_V5R1 ^ 1
+        );
+    }
+
+    #[test]
+    fn sign_external_body_with_checks_signer() -> anyhow::Result<()> {
+        let key_pair = Mnemonic::from_str(
+            "fancy carpet hello mandate penalty trial consider \
+            property top vicious exit rebuild tragic profit urban major total month holiday \
+            sudden rib gather media vicious",
+            &None,
+        )?
+        .to_key_pair()?;
+        let mut other = key_pair.clone();
+        other.public_key[0] ^= 1;
+        let wallet = TonWallet::derive_default(WalletVersion::V4R2, &key_pair)?;
+        let body = CellBuilder::new().store_u32(32, 42)?.build()?;
+        assert!(wallet.sign_external_body_with(&body, &key_pair).is_ok());
+        assert!(matches!(
+            wallet.sign_external_body_with(&body, &other),
+            Err(TonMessageError::SignerKeyMismatch)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn subwallet_external_message_works() -> anyhow::Result<()> {
+        let key_pair = Mnemonic::from_str(
+            "fancy carpet hello mandate penalty trial consider \
+            property top vicious exit rebuild tragic profit urban major total month holiday \
+            sudden rib gather media vicious",
+            &None,
+        )?
+        .to_key_pair()?;
+        let internal = CellBuilder::new().store_u32(32, 42)?.build()?.to_arc();
+        for version in [
+            WalletVersion::V3R2,
+            WalletVersion::V4R2,
+            WalletVersion::V5R1,
+        ] {
+            let default = TonWallet::derive_subwallet(0, version.clone(), &key_pair, 0)?;
+            let subwallet = TonWallet::derive_subwallet(0, version.clone(), &key_pair, 1)?;
+            assert_ne!(default.address, subwallet.address);
+            assert_eq!(
+                subwallet.wallet_id,
+                version.wallet_id(0, 1, MAINNET_GLOBAL_ID)
+            );
+
+            let message = subwallet.create_external_message(100, 0, [internal.clone()], true)?;
+            let state_init = message.reference(0)?;
+            assert_eq!(
+                state_init.reference(0)?.cell_hash(),
+                version.code()?.cell_hash()
+            );
+
+            let body = message.reference(1)?;
+            let mut parser = body.parser();
+            if version == WalletVersion::V5R1 {
+                assert_eq!(parser.load_u32(32)?, 0x7369676e);
+                assert_eq!(parser.load_i32(32)?, subwallet.wallet_id);
+                let out_list = body.reference(0)?;
+                assert_eq!(out_list.reference(0)?.bit_len(), 0);
+                assert_eq!(out_list.reference(1)?.cell_hash(), internal.cell_hash());
+            } else {
+                parser.skip_bits(512)?; // signature
+                assert_eq!(parser.load_i32(32)?, subwallet.wallet_id);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_wallet_version_from_code_hash() {
+        let code_hash = WalletVersion::V4R2.code().unwrap().cell_hash();
+        assert_eq!(
+            WalletVersion::from_code_hash(&code_hash),
+            Some(WalletVersion::V4R2)
+        );
+        assert_eq!(WalletVersion::from_code_hash(&[0; 32]), None);
+    }
 }
@@ -0,0 +1,438 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+
+use crate::address::TonAddress;
+use crate::contract::TonContractFactory;
+use crate::wallet::{IdempotencyStatus, TonWallet, TxBuilder, TxBuilderError, WalletStore};
+
+/// Transfer sent by `SendQueue` not earlier than `send_after` and after all transfers it
+/// depends on.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledTransfer {
+    pub id: String,
+    pub dest: TonAddress,
+    pub amount: BigUint,
+    pub bounce: bool,
+    pub comment: Option<String>,
+    /// unix time of the earliest send.
+    pub send_after: u64,
+    /// ids of the transfers to be confirmed before sending this one.
+    pub depends_on: Vec<String>,
+}
+
+impl ScheduledTransfer {
+    pub fn new(id: &str, dest: &TonAddress, amount: &BigUint) -> ScheduledTransfer {
+        ScheduledTransfer {
+            id: id.to_string(),
+            dest: dest.clone(),
+            amount: amount.clone(),
+            bounce: true,
+            comment: None,
+            send_after: 0,
+            depends_on: vec![],
+        }
+    }
+
+    pub fn with_bounce(&mut self, bounce: bool) -> &mut Self {
+        self.bounce = bounce;
+        self
+    }
+
+    pub fn with_comment(&mut self, comment: &str) -> &mut Self {
+        self.comment = Some(comment.to_string());
+        self
+    }
+
+    pub fn with_send_after(&mut self, send_after: u64) -> &mut Self {
+        self.send_after = send_after;
+        self
+    }
+
+    pub fn with_dependency(&mut self, id: &str) -> &mut Self {
+        self.depends_on.push(id.to_string());
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ScheduledTransferStatus {
+    Pending,
+    /// assigned to the batch, which may be sent but not confirmed yet.
+    InFlight {
+        batch: u64,
+    },
+    /// confirmed by the wallet seqno.
+    Sent {
+        message_hash: Vec<u8>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SendQueueEntry {
+    pub transfer: ScheduledTransfer,
+    pub status: ScheduledTransferStatus,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct SendQueueState {
+    entries: Vec<SendQueueEntry>,
+    next_batch: u64,
+}
+
+const KEY_PREFIX: &str = "send_queue/";
+
+/// Queue of scheduled transfers of the wallet, e.g. payouts.
+///
+/// `dispatch` sends ready transfers in batches of `WalletVersion::max_messages`, waiting for
+/// the confirmation of every batch, so the seqno of the wallet is never reused. The queue is
+/// saved to the store under `send_queue/<wallet address>` after every change.
+///
+/// Every batch is saved before sending and sent with the idempotency key of its number, so a
+/// batch interrupted by a crash is completed after restart with the same message. A batch
+/// whose message is expired without being executed, e.g. after a confirmation timeout, is
+/// returned to pending transfers and sent in a new batch.
+pub struct SendQueue {
+    wallet: TonWallet,
+    store: Arc<dyn WalletStore>,
+    state: SendQueueState,
+}
+
+impl SendQueue {
+    /// Creates the queue of the wallet with the entries loaded from the store.
+    pub async fn load(
+        wallet: &TonWallet,
+        store: Arc<dyn WalletStore>,
+    ) -> Result<SendQueue, TxBuilderError> {
+        let state = match store.get(&Self::store_key(wallet)).await? {
+            Some(data) => serde_json::from_slice(&data)
+                .map_err(|e| TxBuilderError::StoreError(e.to_string()))?,
+            None => SendQueueState::default(),
+        };
+        Ok(SendQueue {
+            wallet: wallet.clone(),
+            store,
+            state,
+        })
+    }
+
+    pub fn entries(&self) -> &[SendQueueEntry] {
+        &self.state.entries
+    }
+
+    /// Adds the transfer to the queue. Ids must be unique and dependencies must be added
+    /// before the dependent transfers.
+    pub async fn push(&mut self, transfer: ScheduledTransfer) -> Result<(), TxBuilderError> {
+        if self.entry(&transfer.id).is_some() {
+            return Err(TxBuilderError::IllegalArgument(format!(
+                "Transfer {} is already queued",
+                transfer.id
+            )));
+        }
+        if let Some(dep) = transfer.depends_on.iter().find(|d| self.entry(d).is_none()) {
+            return Err(TxBuilderError::IllegalArgument(format!(
+                "Transfer {} depends on unknown transfer {}",
+                transfer.id, dep
+            )));
+        }
+        self.state.entries.push(SendQueueEntry {
+            transfer,
+            status: ScheduledTransferStatus::Pending,
+        });
+        self.save().await
+    }
+
+    /// Returns pending transfers which can be sent at `now`, in the order of adding.
+    pub fn ready(&self, now: u64) -> Vec<&ScheduledTransfer> {
+        self.state
+            .entries
+            .iter()
+            .filter(|e| e.status == ScheduledTransferStatus::Pending)
+            .map(|e| &e.transfer)
+            .filter(|t| t.send_after <= now && self.dependencies_sent(t))
+            .collect()
+    }
+
+    /// Returns the earliest send time of pending transfers with sent dependencies.
+    pub fn next_send_after(&self) -> Option<u64> {
+        self.state
+            .entries
+            .iter()
+            .filter(|e| e.status == ScheduledTransferStatus::Pending)
+            .filter(|e| self.dependencies_sent(&e.transfer))
+            .map(|e| e.transfer.send_after)
+            .min()
+    }
+
+    /// Sends all transfers ready at the moment, including those whose dependencies are sent
+    /// by this call. Returns ids of the sent transfers.
+    pub async fn dispatch(
+        &mut self,
+        factory: &TonContractFactory,
+    ) -> Result<Vec<String>, TxBuilderError> {
+        if let Some((number, batch)) = self.in_flight_batch() {
+            let builder = self.batch_builder(number, &batch);
+            if builder.idempotency_status(factory).await? == IdempotencyStatus::Expired {
+                log::info!(
+                    "Batch {} of {} is expired without being executed, requeueing it",
+                    number,
+                    self.wallet.address
+                );
+                self.release_batch(number).await?;
+            }
+        }
+
+        let mut dispatched = vec![];
+        loop {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|e| TxBuilderError::InternalError(e.to_string()))?
+                .as_secs();
+            let Some((number, batch)) = self.take_batch(now).await? else {
+                return Ok(dispatched);
+            };
+
+            let builder = self.batch_builder(number, &batch);
+            let message_hash = builder.send_and_confirm(factory).await?;
+
+            let ids: Vec<String> = batch.into_iter().map(|t| t.id).collect();
+            for entry in self.state.entries.iter_mut() {
+                if ids.contains(&entry.transfer.id) {
+                    entry.status = ScheduledTransferStatus::Sent {
+                        message_hash: message_hash.clone(),
+                    };
+                }
+            }
+            self.save().await?;
+            dispatched.extend(ids);
+        }
+    }
+
+    /// Returns the batch left in flight by the previous run.
+    fn in_flight_batch(&self) -> Option<(u64, Vec<ScheduledTransfer>)> {
+        let number = self.state.entries.iter().find_map(|e| match e.status {
+            ScheduledTransferStatus::InFlight { batch } => Some(batch),
+            _ => None,
+        })?;
+        let batch = self
+            .state
+            .entries
+            .iter()
+            .filter(|e| e.status == ScheduledTransferStatus::InFlight { batch: number })
+            .map(|e| e.transfer.clone())
+            .collect();
+        Some((number, batch))
+    }
+
+    /// Returns the transfers of the batch to pending, so that they are sent in a new batch.
+    async fn release_batch(&mut self, number: u64) -> Result<(), TxBuilderError> {
+        for entry in self.state.entries.iter_mut() {
+            if entry.status == (ScheduledTransferStatus::InFlight { batch: number }) {
+                entry.status = ScheduledTransferStatus::Pending;
+            }
+        }
+        self.save().await
+    }
+
+    fn batch_builder(&self, number: u64, batch: &[ScheduledTransfer]) -> TxBuilder {
+        let mut builder = TxBuilder::new(&self.wallet);
+        for transfer in batch {
+            builder
+                .transfer(&transfer.dest, &transfer.amount)
+                .with_bounce(transfer.bounce);
+            if let Some(comment) = &transfer.comment {
+                builder.with_comment(comment);
+            }
+        }
+        let key = format!("{}#{}", Self::store_key(&self.wallet), number);
+        builder.with_idempotency_key(self.store.clone(), &key);
+        builder
+    }
+
+    /// Returns the batch left in flight by the previous run, or assigns ready transfers to a
+    /// new batch and saves it.
+    async fn take_batch(
+        &mut self,
+        now: u64,
+    ) -> Result<Option<(u64, Vec<ScheduledTransfer>)>, TxBuilderError> {
+        if let Some(in_flight) = self.in_flight_batch() {
+            return Ok(Some(in_flight));
+        }
+
+        let ids: Vec<String> = self
+            .ready(now)
+            .into_iter()
+            .take(self.wallet.version.max_messages())
+            .map(|t| t.id.clone())
+            .collect();
+        if ids.is_empty() {
+            return Ok(None);
+        }
+        let number = self.state.next_batch;
+        self.state.next_batch += 1;
+        let mut batch = vec![];
+        for entry in self.state.entries.iter_mut() {
+            if ids.contains(&entry.transfer.id) {
+                entry.status = ScheduledTransferStatus::InFlight { batch: number };
+                batch.push(entry.transfer.clone());
+            }
+        }
+        self.save().await?;
+        Ok(Some((number, batch)))
+    }
+
+    async fn save(&self) -> Result<(), TxBuilderError> {
+        let data = serde_json::to_vec(&self.state)
+            .map_err(|e| TxBuilderError::StoreError(e.to_string()))?;
+        self.store.put(&Self::store_key(&self.wallet), &data).await
+    }
+
+    fn store_key(wallet: &TonWallet) -> String {
+        format!("{}{}", KEY_PREFIX, wallet.address.to_hex())
+    }
+
+    fn entry(&self, id: &str) -> Option<&SendQueueEntry> {
+        self.state.entries.iter().find(|e| e.transfer.id == id)
+    }
+
+    fn dependencies_sent(&self, transfer: &ScheduledTransfer) -> bool {
+        transfer.depends_on.iter().all(|id| {
+            self.entry(id)
+                .is_some_and(|e| matches!(e.status, ScheduledTransferStatus::Sent { .. }))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use num_bigint::BigUint;
+
+    use crate::address::TonAddress;
+    use crate::mnemonic::Mnemonic;
+    use crate::wallet::{
+        FileWalletStore, InMemoryWalletStore, ScheduledTransfer, ScheduledTransferStatus,
+        SendQueue, TonWallet, WalletVersion,
+    };
+
+    fn wallet() -> anyhow::Result<TonWallet> {
+        let mnemonic_str = "fancy carpet hello mandate penalty trial consider \
+        property top vicious exit rebuild tragic profit urban major total month holiday \
+        sudden rib gather media vicious";
+        let key_pair = Mnemonic::from_str(mnemonic_str, &None)?.to_key_pair()?;
+        Ok(TonWallet::derive_default(WalletVersion::V4R2, &key_pair)?)
+    }
+
+    #[tokio::test]
+    async fn test_send_queue_ready() -> anyhow::Result<()> {
+        let dest = TonAddress::new(0, &[1; 32]);
+        let amount = BigUint::from(100u32);
+        let store = Arc::new(InMemoryWalletStore::new());
+        let mut queue = SendQueue::load(&wallet()?, store.clone()).await?;
+        queue
+            .push(ScheduledTransfer::new("a", &dest, &amount))
+            .await?;
+        queue
+            .push(
+                ScheduledTransfer::new("b", &dest, &amount)
+                    .with_send_after(1000)
+                    .clone(),
+            )
+            .await?;
+        queue
+            .push(
+                ScheduledTransfer::new("c", &dest, &amount)
+                    .with_dependency("a")
+                    .clone(),
+            )
+            .await?;
+        assert!(queue
+            .push(ScheduledTransfer::new("a", &dest, &amount))
+            .await
+            .is_err());
+        assert!(queue
+            .push(
+                ScheduledTransfer::new("d", &dest, &amount)
+                    .with_dependency("x")
+                    .clone()
+            )
+            .await
+            .is_err());
+
+        let ids = |ready: Vec<&ScheduledTransfer>| -> Vec<String> {
+            ready.into_iter().map(|t| t.id.clone()).collect()
+        };
+        assert_eq!(ids(queue.ready(999)), ["a"]);
+        assert_eq!(ids(queue.ready(1000)), ["a", "b"]);
+        assert_eq!(queue.next_send_after(), Some(0));
+
+        queue.state.entries[0].status = ScheduledTransferStatus::Sent {
+            message_hash: vec![0; 32],
+        };
+        assert_eq!(ids(queue.ready(999)), ["c"]);
+        let restored = SendQueue::load(&wallet()?, store).await?;
+        assert_eq!(restored.entries().len(), 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_send_queue_restores_in_flight_batch() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("tonlib-queue-{}", std::process::id()));
+        let store = Arc::new(FileWalletStore::new(&dir)?);
+        let mut queue = SendQueue::load(&wallet()?, store.clone()).await?;
+        queue
+            .push(
+                ScheduledTransfer::new("a", &TonAddress::NULL, &BigUint::from(1u32))
+                    .with_comment("payout")
+                    .clone(),
+            )
+            .await?;
+        let (number, batch) = queue.take_batch(0).await?.unwrap();
+        assert_eq!(batch.len(), 1);
+
+        // crash after taking the batch, new transfers do not change it
+        let mut restored = SendQueue::load(&wallet()?, store).await?;
+        assert_eq!(restored.entries(), queue.entries());
+        restored
+            .push(ScheduledTransfer::new(
+                "b",
+                &TonAddress::NULL,
+                &BigUint::from(2u32),
+            ))
+            .await?;
+        assert_eq!(restored.take_batch(0).await?, Some((number, batch)));
+        let ready: Vec<&str> = restored.ready(0).iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ready, ["b"]);
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_send_queue_releases_expired_batch() -> anyhow::Result<()> {
+        let dest = TonAddress::new(0, &[1; 32]);
+        let store = Arc::new(InMemoryWalletStore::new());
+        let mut queue = SendQueue::load(&wallet()?, store.clone()).await?;
+        queue
+            .push(ScheduledTransfer::new("a", &dest, &BigUint::from(1u32)))
+            .await?;
+        let (number, batch) = queue.take_batch(0).await?.unwrap();
+        queue
+            .push(ScheduledTransfer::new("b", &dest, &BigUint::from(2u32)))
+            .await?;
+
+        queue.release_batch(number).await?;
+        assert_eq!(queue.in_flight_batch(), None);
+        let restored = SendQueue::load(&wallet()?, store).await?;
+        assert_eq!(restored.entries(), queue.entries());
+
+        // the released transfers are sent in a new batch with a new idempotency key
+        let (next, next_batch) = queue.take_batch(0).await?.unwrap();
+        assert_eq!(next, number + 1);
+        assert_eq!(next_batch[0], batch[0]);
+        assert_eq!(next_batch.len(), 2);
+        Ok(())
+    }
+}
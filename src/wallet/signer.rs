@@ -0,0 +1,25 @@
+use nacl::sign::signature;
+
+use crate::message::TonMessageError;
+use crate::mnemonic::KeyPair;
+
+/// Source of ed25519 signatures for wallet messages.
+///
+/// Allows signing without holding the raw secret key in the wallet, e.g. by a key
+/// loaded from an encrypted key store or by an external signing service.
+pub trait Signer: Send + Sync {
+    fn public_key(&self) -> &[u8];
+
+    fn sign(&self, data: &[u8]) -> Result<Vec<u8>, TonMessageError>;
+}
+
+impl Signer for KeyPair {
+    fn public_key(&self) -> &[u8] {
+        self.public_key.as_slice()
+    }
+
+    fn sign(&self, data: &[u8]) -> Result<Vec<u8>, TonMessageError> {
+        signature(data, self.secret_key.as_slice())
+            .map_err(|e| TonMessageError::NaclCryptographicError(e.message))
+    }
+}
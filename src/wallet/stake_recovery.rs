@@ -0,0 +1,155 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use num_bigint::BigUint;
+use num_traits::Zero;
+
+use crate::address::TonAddress;
+use crate::cell::BagOfCells;
+use crate::client::TonClientInterface;
+use crate::contract::{ElectorContract, TonContractFactory};
+use crate::message::{RecoverStakeMessage, ELECTOR_RECOVER_STAKE_ERROR, ELECTOR_RECOVER_STAKE_OK};
+use crate::tl::{InternalTransactionId, MsgData, RawTransaction};
+use crate::wallet::{TonWallet, TxBuilder, TxBuilderError};
+
+/// Amount attached to `recover_stake` request to pay the fees, the excess is returned with the
+/// stake.
+const RECOVER_STAKE_REQUEST_AMOUNT: u64 = 1_000_000_000;
+const RECOVERY_TIMEOUT_SECS: u64 = 180;
+const RECOVERY_POLL_INTERVAL_MS: u64 = 2000;
+const RECOVERY_TX_PAGE_SIZE: usize = 16;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StakeRecovery {
+    /// amount reported by `compute_returned_stake` before sending the request.
+    pub amount: BigUint,
+    /// hash of the external message sent to the wallet.
+    pub message_hash: Vec<u8>,
+    /// transaction of the wallet receiving the refund from the elector.
+    pub refund_transaction_id: InternalTransactionId,
+}
+
+/// Recovers the stake of the validator `wallet` from the `elector` (usually `ELECTOR_ADDRESS`):
+/// checks the recoverable amount, sends `recover_stake` request and waits for the
+/// `recover_stake_ok` response of the elector.
+pub async fn recover_stake(
+    factory: &TonContractFactory,
+    wallet: &TonWallet,
+    elector: &TonAddress,
+) -> Result<StakeRecovery, TxBuilderError> {
+    let amount = factory
+        .get_contract(elector)
+        .compute_returned_stake(&wallet.address)
+        .await?;
+    if amount.is_zero() {
+        return Err(TxBuilderError::NothingToRecover {
+            address: wallet.address.clone(),
+        });
+    }
+
+    let start_lt = factory
+        .get_latest_account_state(&wallet.address)
+        .await?
+        .last_transaction_id
+        .lt;
+    let query_id = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| TxBuilderError::InternalError(e.to_string()))?
+        .as_secs();
+    let body = RecoverStakeMessage::new().with_query_id(query_id).build()?;
+    let message_hash = TxBuilder::new(wallet)
+        .transfer(elector, &BigUint::from(RECOVER_STAKE_REQUEST_AMOUNT))
+        .with_body(body.to_arc())
+        .send(factory)
+        .await?;
+
+    let started = SystemTime::now();
+    loop {
+        tokio::time::sleep(Duration::from_millis(RECOVERY_POLL_INTERVAL_MS)).await;
+        let response =
+            find_recover_stake_response(factory, &wallet.address, elector, query_id, start_lt)
+                .await?;
+        match response {
+            Some((refund_transaction_id, ELECTOR_RECOVER_STAKE_OK)) => {
+                return Ok(StakeRecovery {
+                    amount,
+                    message_hash,
+                    refund_transaction_id,
+                })
+            }
+            Some(_) => {
+                return Err(TxBuilderError::StakeRecoveryRejected {
+                    address: wallet.address.clone(),
+                    query_id,
+                })
+            }
+            None => {}
+        }
+        if started.elapsed().unwrap_or_default() > Duration::from_secs(RECOVERY_TIMEOUT_SECS) {
+            return Err(TxBuilderError::StakeRecoveryTimeout {
+                address: wallet.address.clone(),
+                query_id,
+                timeout_secs: RECOVERY_TIMEOUT_SECS,
+            });
+        }
+    }
+}
+
+/// Looks for the response of the elector among the latest wallet transactions after `start_lt`,
+/// returns the transaction and the response opcode.
+async fn find_recover_stake_response(
+    factory: &TonContractFactory,
+    wallet: &TonAddress,
+    elector: &TonAddress,
+    query_id: u64,
+    start_lt: i64,
+) -> Result<Option<(InternalTransactionId, u32)>, TxBuilderError> {
+    let state = factory.get_latest_account_state(wallet).await?;
+    if state.last_transaction_id.lt <= start_lt {
+        return Ok(None);
+    }
+    let txs = factory
+        .client()
+        .get_raw_transactions_v2(
+            wallet,
+            &state.last_transaction_id,
+            RECOVERY_TX_PAGE_SIZE,
+            false,
+        )
+        .await?;
+    let response = txs
+        .transactions
+        .iter()
+        .filter(|tx| tx.transaction_id.lt > start_lt)
+        .find_map(|tx| {
+            recover_stake_response_op(tx, elector, query_id)
+                .map(|op| (tx.transaction_id.clone(), op))
+        });
+    Ok(response)
+}
+
+fn recover_stake_response_op(
+    tx: &RawTransaction,
+    elector: &TonAddress,
+    query_id: u64,
+) -> Option<u32> {
+    let in_msg = tx.in_msg.as_ref()?;
+    if in_msg
+        .source
+        .account_address
+        .parse::<TonAddress>()
+        .ok()
+        .as_ref()
+        != Some(elector)
+    {
+        return None;
+    }
+    let MsgData::Raw { body, .. } = &in_msg.msg_data else {
+        return None;
+    };
+    let boc = BagOfCells::parse(body).ok()?;
+    let mut parser = boc.single_root().ok()?.parser();
+    let op = parser.load_u32(32).ok()?;
+    let response_query_id = parser.load_u64(64).ok()?;
+    let is_response = op == ELECTOR_RECOVER_STAKE_OK || op == ELECTOR_RECOVER_STAKE_ERROR;
+    (is_response && response_query_id == query_id).then_some(op)
+}
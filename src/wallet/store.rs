@@ -0,0 +1,177 @@
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use async_trait::async_trait;
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use sha2::{Digest, Sha256};
+
+use crate::wallet::TxBuilderError;
+
+/// Persistent key-value storage of the sending state: idempotency records and checkpoints of
+/// send queues. Must survive restarts of the application to protect against double-sends
+/// after crashes.
+#[async_trait]
+pub trait WalletStore: Send + Sync {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, TxBuilderError>;
+
+    /// Stores the value, replacing the previous one.
+    async fn put(&self, key: &str, value: &[u8]) -> Result<(), TxBuilderError>;
+
+    /// Atomically stores the value if the key is absent. Returns `None` if the value is stored,
+    /// otherwise the value stored for the key before.
+    async fn insert_if_absent(
+        &self,
+        key: &str,
+        value: &[u8],
+    ) -> Result<Option<Vec<u8>>, TxBuilderError>;
+}
+
+/// Keeps values in memory, protects against repeated sends within the process only.
+#[derive(Default)]
+pub struct InMemoryWalletStore {
+    values: DashMap<String, Vec<u8>>,
+}
+
+impl InMemoryWalletStore {
+    pub fn new() -> InMemoryWalletStore {
+        InMemoryWalletStore::default()
+    }
+}
+
+#[async_trait]
+impl WalletStore for InMemoryWalletStore {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, TxBuilderError> {
+        Ok(self.values.get(key).map(|v| v.value().clone()))
+    }
+
+    async fn put(&self, key: &str, value: &[u8]) -> Result<(), TxBuilderError> {
+        self.values.insert(key.to_string(), value.to_vec());
+        Ok(())
+    }
+
+    async fn insert_if_absent(
+        &self,
+        key: &str,
+        value: &[u8],
+    ) -> Result<Option<Vec<u8>>, TxBuilderError> {
+        match self.values.entry(key.to_string()) {
+            Entry::Occupied(entry) => Ok(Some(entry.get().clone())),
+            Entry::Vacant(entry) => {
+                entry.insert(value.to_vec());
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Keeps every value in a file of the directory, named by sha256 of the key.
+///
+/// Values are written to a temporary file and then moved in place, so that a crash never
+/// leaves a partial value.
+pub struct FileWalletStore {
+    dir: PathBuf,
+    tmp_counter: AtomicU64,
+}
+
+impl FileWalletStore {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Result<FileWalletStore, TxBuilderError> {
+        std::fs::create_dir_all(dir.as_ref()).map_err(store_error)?;
+        Ok(FileWalletStore {
+            dir: dir.as_ref().to_path_buf(),
+            tmp_counter: AtomicU64::new(0),
+        })
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        let name = hex::encode(Sha256::digest(key.as_bytes()));
+        self.dir.join(format!("{}.json", name))
+    }
+
+    /// Writes the value to a new temporary file of the directory.
+    async fn write_tmp(&self, value: &[u8]) -> Result<PathBuf, TxBuilderError> {
+        let n = self.tmp_counter.fetch_add(1, Ordering::Relaxed);
+        let tmp = self.dir.join(format!("{}-{}.tmp", std::process::id(), n));
+        tokio::fs::write(&tmp, value).await.map_err(store_error)?;
+        Ok(tmp)
+    }
+}
+
+#[async_trait]
+impl WalletStore for FileWalletStore {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, TxBuilderError> {
+        match tokio::fs::read(self.path(key)).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(store_error(e)),
+        }
+    }
+
+    async fn put(&self, key: &str, value: &[u8]) -> Result<(), TxBuilderError> {
+        let tmp = self.write_tmp(value).await?;
+        tokio::fs::rename(&tmp, self.path(key))
+            .await
+            .map_err(store_error)
+    }
+
+    async fn insert_if_absent(
+        &self,
+        key: &str,
+        value: &[u8],
+    ) -> Result<Option<Vec<u8>>, TxBuilderError> {
+        let tmp = self.write_tmp(value).await?;
+        // unlike rename, linking fails if the file exists
+        let linked = tokio::fs::hard_link(&tmp, self.path(key)).await;
+        if let Err(e) = tokio::fs::remove_file(&tmp).await {
+            log::warn!("[FileWalletStore] Failed to remove {:?}: {}", tmp, e);
+        }
+        match linked {
+            Ok(()) => Ok(None),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => match self.get(key).await? {
+                Some(stored) => Ok(Some(stored)),
+                None => Err(TxBuilderError::StoreError(format!(
+                    "Value of {} is removed concurrently",
+                    key
+                ))),
+            },
+            Err(e) => Err(store_error(e)),
+        }
+    }
+}
+
+fn store_error(e: std::io::Error) -> TxBuilderError {
+    TxBuilderError::StoreError(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::wallet::{FileWalletStore, InMemoryWalletStore, WalletStore};
+
+    #[tokio::test]
+    async fn test_wallet_stores() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("tonlib-wallet-store-{}", std::process::id()));
+        let stores: [Arc<dyn WalletStore>; 2] = [
+            Arc::new(InMemoryWalletStore::new()),
+            Arc::new(FileWalletStore::new(&dir)?),
+        ];
+        for store in stores {
+            assert_eq!(store.get("key-1").await?, None);
+            assert_eq!(store.insert_if_absent("key-1", b"first").await?, None);
+            assert_eq!(
+                store.insert_if_absent("key-1", b"second").await?,
+                Some(b"first".to_vec())
+            );
+            assert_eq!(store.get("key-1").await?, Some(b"first".to_vec()));
+            store.put("key-1", b"third").await?;
+            assert_eq!(store.get("key-1").await?, Some(b"third".to_vec()));
+            assert_eq!(store.get("key-2").await?, None);
+        }
+        // temporary files are removed
+        assert_eq!(std::fs::read_dir(&dir)?.count(), 1);
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
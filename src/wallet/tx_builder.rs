@@ -0,0 +1,533 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub use error::*;
+pub use guard::*;
+pub use idempotency::*;
+use num_bigint::BigUint;
+
+use crate::address::TonAddress;
+use crate::cell::{ArcCell, BagOfCells, Cell, CellBuilder};
+use crate::client::TonClientInterface;
+use crate::contract::{TonContractFactory, TonWalletContract};
+use crate::message::{JettonTransferMessage, TransferMessage};
+use crate::types::TvmMsgSuccess;
+use crate::wallet::TonWallet;
+
+mod error;
+mod fee_estimate;
+mod guard;
+mod idempotency;
+
+const DEFAULT_TTL_SECS: u32 = 60;
+const DEFAULT_CONFIRMATION_TIMEOUT_SECS: u64 = 120;
+const CONFIRMATION_POLL_INTERVAL_MS: u64 = 1000;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TxJetton {
+    /// jetton wallet of the sender.
+    pub jetton_wallet: TonAddress,
+    /// amount of jettons in elementary units.
+    pub amount: BigUint,
+}
+
+/// Part of the transfers sent by a single external message.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TxBatch {
+    pub seqno: u32,
+    pub transfers: Vec<TxTransfer>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TxTransfer {
+    pub dest: TonAddress,
+    pub amount: BigUint,
+    pub bounce: bool,
+    pub comment: Option<String>,
+    /// custom body of the message, replaces the comment.
+    pub body: Option<ArcCell>,
+    pub jetton: Option<TxJetton>,
+}
+
+/// Fluent builder of wallet transactions:
+///
+/// ```ignore
+/// TxBuilder::new(&wallet)
+///     .transfer(&dest, &ton_amount)
+///     .with_comment("hello")
+///     .transfer(&jetton_dest, &forward_amount)
+///     .with_jetton(&own_jetton_wallet, &jetton_amount)
+///     .send_and_confirm(&factory)
+///     .await?;
+/// ```
+#[derive(Clone)]
+pub struct TxBuilder {
+    wallet: TonWallet,
+    transfers: Vec<TxTransfer>,
+    seqno: Option<u32>,
+    ttl_secs: u32,
+    state_init: bool,
+    confirmation_timeout_secs: u64,
+    idempotency: Option<Idempotency>,
+    error: Option<String>,
+}
+
+impl TxBuilder {
+    pub fn new(wallet: &TonWallet) -> TxBuilder {
+        TxBuilder {
+            wallet: wallet.clone(),
+            transfers: vec![],
+            seqno: None,
+            ttl_secs: DEFAULT_TTL_SECS,
+            state_init: false,
+            confirmation_timeout_secs: DEFAULT_CONFIRMATION_TIMEOUT_SECS,
+            idempotency: None,
+            error: None,
+        }
+    }
+
+    /// Adds a transfer of `amount` nanotons to `dest`.
+    ///
+    /// Subsequent `with_*` calls modify this transfer.
+    pub fn transfer(&mut self, dest: &TonAddress, amount: &BigUint) -> &mut Self {
+        self.transfers.push(TxTransfer {
+            dest: dest.clone(),
+            amount: amount.clone(),
+            bounce: true,
+            comment: None,
+            body: None,
+            jetton: None,
+        });
+        self
+    }
+
+    /// Attaches text comment to the last transfer.
+    pub fn with_comment(&mut self, comment: &str) -> &mut Self {
+        self.modify_last("with_comment", |t| t.comment = Some(comment.to_string()))
+    }
+
+    /// Attaches custom body, e.g. a contract request, to the last transfer.
+    pub fn with_body(&mut self, body: ArcCell) -> &mut Self {
+        self.modify_last("with_body", |t| t.body = Some(body))
+    }
+
+    /// Turns the last transfer into jetton transfer: `amount` of jettons are sent from `jetton_wallet`
+    /// to `dest` of the transfer, while its TON amount is attached to pay the fees.
+    pub fn with_jetton(&mut self, jetton_wallet: &TonAddress, amount: &BigUint) -> &mut Self {
+        self.modify_last("with_jetton", |t| {
+            t.jetton = Some(TxJetton {
+                jetton_wallet: jetton_wallet.clone(),
+                amount: amount.clone(),
+            })
+        })
+    }
+
+    /// Sets bounce flag of the last transfer.
+    pub fn with_bounce(&mut self, bounce: bool) -> &mut Self {
+        self.modify_last("with_bounce", |t| t.bounce = bounce)
+    }
+
+    /// Uses provided seqno instead of requesting it from the wallet contract.
+    pub fn with_seqno(&mut self, seqno: u32) -> &mut Self {
+        self.seqno = Some(seqno);
+        self
+    }
+
+    pub fn with_ttl(&mut self, ttl_secs: u32) -> &mut Self {
+        self.ttl_secs = ttl_secs;
+        self
+    }
+
+    /// Attaches state init of the wallet to deploy it with this transaction.
+    pub fn with_state_init(&mut self, state_init: bool) -> &mut Self {
+        self.state_init = state_init;
+        self
+    }
+
+    pub fn with_confirmation_timeout(&mut self, timeout_secs: u64) -> &mut Self {
+        self.confirmation_timeout_secs = timeout_secs;
+        self
+    }
+
+    pub fn transfers(&self) -> &[TxTransfer] {
+        &self.transfers
+    }
+
+    fn modify_last<F>(&mut self, method: &str, f: F) -> &mut Self
+    where
+        F: FnOnce(&mut TxTransfer),
+    {
+        match self.transfers.last_mut() {
+            Some(transfer) => f(transfer),
+            None => {
+                self.error = Some(format!("{} called before transfer", method));
+            }
+        }
+        self
+    }
+
+    /// Builds internal messages to be sent by the wallet.
+    pub fn build_internal_messages(&self) -> Result<Vec<ArcCell>, TxBuilderError> {
+        if let Some(error) = &self.error {
+            return Err(TxBuilderError::IllegalArgument(error.clone()));
+        }
+        let max_transfers = self.wallet.version.max_messages();
+        if self.transfers.is_empty() || self.transfers.len() > max_transfers {
+            return Err(TxBuilderError::IllegalArgument(format!(
+                "Expected 1 to {} transfers, got {}",
+                max_transfers,
+                self.transfers.len()
+            )));
+        }
+        self.transfers
+            .iter()
+            .map(|t| self.build_internal_message(t).map(Cell::to_arc))
+            .collect()
+    }
+
+    fn build_internal_message(&self, transfer: &TxTransfer) -> Result<Cell, TxBuilderError> {
+        let comment = match &transfer.comment {
+            Some(comment) => Some(
+                CellBuilder::new()
+                    .store_u32(32, 0)?
+                    .store_string(comment)?
+                    .build()?
+                    .to_arc(),
+            ),
+            None => None,
+        };
+        let message = match &transfer.jetton {
+            Some(_) if transfer.body.is_some() => {
+                return Err(TxBuilderError::IllegalArgument(
+                    "Custom body is not supported by jetton transfers".to_string(),
+                ));
+            }
+            Some(jetton) => {
+                let mut jetton_transfer =
+                    JettonTransferMessage::new(&transfer.dest, &jetton.amount);
+                jetton_transfer.with_response_destination(&self.wallet.address);
+                // one nanoton is forwarded to notify the receiver
+                let forward_ton_amount = BigUint::from(1u32);
+                if let Some(comment) = comment {
+                    jetton_transfer.with_forward_payload(&forward_ton_amount, comment);
+                } else {
+                    jetton_transfer.forward_ton_amount = forward_ton_amount;
+                }
+                TransferMessage::new(&jetton.jetton_wallet, &transfer.amount)
+                    .with_bounce(true)
+                    .with_data(jetton_transfer.build()?)
+                    .build()?
+            }
+            None => {
+                let mut message = TransferMessage::new(&transfer.dest, &transfer.amount);
+                message.with_bounce(transfer.bounce);
+                if let Some(body) = transfer.body.as_ref().or(comment.as_ref()) {
+                    message.with_data_ref(body);
+                }
+                message.build()?
+            }
+        };
+        Ok(message)
+    }
+
+    /// Builds signed external message with the given seqno.
+    pub fn build(&self, seqno: u32) -> Result<Cell, TxBuilderError> {
+        self.build_until(seqno, self.valid_until()?)
+    }
+
+    fn build_until(&self, seqno: u32, valid_until: u32) -> Result<Cell, TxBuilderError> {
+        let messages = self.build_internal_messages()?;
+        let message =
+            self.wallet
+                .create_external_message(valid_until, seqno, messages, self.state_init)?;
+        Ok(message)
+    }
+
+    /// Returns expiration time of the external message built now.
+    fn valid_until(&self) -> Result<u32, TxBuilderError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| TxBuilderError::InternalError(e.to_string()))?
+            .as_secs() as u32;
+        Ok(now + self.ttl_secs)
+    }
+
+    async fn resolve_seqno(&self, factory: &TonContractFactory) -> Result<u32, TxBuilderError> {
+        match self.seqno {
+            Some(seqno) => Ok(seqno),
+            None => self.fetch_seqno(factory).await,
+        }
+    }
+
+    /// Returns the current seqno of the wallet, ignoring `with_seqno`.
+    async fn fetch_seqno(&self, factory: &TonContractFactory) -> Result<u32, TxBuilderError> {
+        let seqno = factory.get_contract(&self.wallet.address).seqno().await;
+        match seqno {
+            Ok(seqno) => Ok(seqno),
+            // Wallet is not deployed yet, it will be deployed by this message
+            Err(_) if self.state_init => Ok(0),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Emulates the external message on the latest state of the wallet without sending it.
+    pub async fn dry_run(
+        &self,
+        factory: &TonContractFactory,
+    ) -> Result<TvmMsgSuccess, TxBuilderError> {
+        let seqno = self.resolve_seqno(factory).await?;
+        let message = self.build(seqno)?;
+        let state = factory
+            .get_latest_contract_state(&self.wallet.address)
+            .await?;
+        let result = state.emulate_external_message(message).await?;
+        Ok(result)
+    }
+
+    /// Sends the external message and returns its hash.
+    pub async fn send(&self, factory: &TonContractFactory) -> Result<Vec<u8>, TxBuilderError> {
+        let seqno = self.resolve_seqno(factory).await?;
+        let (hash, _) = self.send_with_seqno(factory, seqno).await?;
+        Ok(hash)
+    }
+
+    /// Returns hash and seqno of the sent message, which differs from `seqno` if the message
+    /// persisted for the idempotency key is sent.
+    async fn send_with_seqno(
+        &self,
+        factory: &TonContractFactory,
+        seqno: u32,
+    ) -> Result<(Vec<u8>, u32), TxBuilderError> {
+        if let Some(idempotency) = &self.idempotency {
+            return self.send_idempotent(factory, idempotency, seqno).await;
+        }
+        let message = self.build(seqno)?;
+        let boc = BagOfCells::from_root(message).serialize(true)?;
+        let hash = factory
+            .client()
+            .send_raw_message_return_hash(boc.as_slice())
+            .await?;
+        Ok((hash, seqno))
+    }
+
+    /// Sends the external message and waits until the wallet seqno is incremented.
+    pub async fn send_and_confirm(
+        &self,
+        factory: &TonContractFactory,
+    ) -> Result<Vec<u8>, TxBuilderError> {
+        let seqno = self.resolve_seqno(factory).await?;
+        self.send_and_confirm_with_seqno(factory, seqno).await
+    }
+
+    /// Splits the transfers into batches fitting into a single external message of the wallet,
+    /// starting from `seqno`.
+    pub fn split(&self, seqno: u32) -> Result<Vec<TxBatch>, TxBuilderError> {
+        if let Some(error) = &self.error {
+            return Err(TxBuilderError::IllegalArgument(error.clone()));
+        }
+        if self.transfers.is_empty() {
+            return Err(TxBuilderError::IllegalArgument(
+                "Expected at least 1 transfer".to_string(),
+            ));
+        }
+        let batches = self
+            .transfers
+            .chunks(self.wallet.version.max_messages())
+            .zip(seqno..)
+            .map(|(transfers, seqno)| TxBatch {
+                seqno,
+                transfers: transfers.to_vec(),
+            })
+            .collect();
+        Ok(batches)
+    }
+
+    /// Returns batches of transfers that `send_batches` would send, so that the plan can be
+    /// reviewed before sending.
+    pub async fn plan(&self, factory: &TonContractFactory) -> Result<Vec<TxBatch>, TxBuilderError> {
+        let seqno = self.resolve_seqno(factory).await?;
+        self.split(seqno)
+    }
+
+    /// Sends the batches one by one, waiting for confirmation of each batch before sending the
+    /// next one. Returns hashes of the sent external messages.
+    ///
+    /// If a batch is not confirmed, the error is returned and the remaining batches are not sent.
+    pub async fn send_batches(
+        &self,
+        factory: &TonContractFactory,
+        batches: &[TxBatch],
+    ) -> Result<Vec<Vec<u8>>, TxBuilderError> {
+        let mut hashes = Vec::with_capacity(batches.len());
+        for (i, batch) in batches.iter().enumerate() {
+            log::info!(
+                "Sending batch {}/{} of {} transfers from {} with seqno {}",
+                i + 1,
+                batches.len(),
+                batch.transfers.len(),
+                self.wallet.address,
+                batch.seqno
+            );
+            let mut builder = self.clone();
+            builder.transfers = batch.transfers.clone();
+            // the wallet is deployed by the first batch
+            builder.state_init = self.state_init && i == 0;
+            if let Some(idempotency) = &mut builder.idempotency {
+                idempotency.key = format!("{}#{}", idempotency.key, i);
+            }
+            let hash = builder
+                .send_and_confirm_with_seqno(factory, batch.seqno)
+                .await?;
+            hashes.push(hash);
+        }
+        Ok(hashes)
+    }
+
+    async fn send_and_confirm_with_seqno(
+        &self,
+        factory: &TonContractFactory,
+        seqno: u32,
+    ) -> Result<Vec<u8>, TxBuilderError> {
+        let (hash, seqno) = self.send_with_seqno(factory, seqno).await?;
+        let started = SystemTime::now();
+        loop {
+            tokio::time::sleep(Duration::from_millis(CONFIRMATION_POLL_INTERVAL_MS)).await;
+            let contract = factory.get_contract(&self.wallet.address);
+            if let Ok(current) = contract.seqno().await {
+                if current > seqno {
+                    return Ok(hash);
+                }
+            }
+            if started.elapsed().unwrap_or_default()
+                > Duration::from_secs(self.confirmation_timeout_secs)
+            {
+                return Err(TxBuilderError::ConfirmationTimeout {
+                    address: self.wallet.address.clone(),
+                    seqno,
+                    timeout_secs: self.confirmation_timeout_secs,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use crate::address::TonAddress;
+    use crate::contract::ELECTOR_ADDRESS;
+    use crate::message::{JettonTransferMessage, RecoverStakeMessage, JETTON_TRANSFER};
+    use crate::mnemonic::Mnemonic;
+    use crate::wallet::{TonWallet, TxBuilder, TxBuilderError, WalletVersion};
+
+    fn wallet() -> anyhow::Result<TonWallet> {
+        let mnemonic_str = "fancy carpet hello mandate penalty trial consider \
+        property top vicious exit rebuild tragic profit urban major total month holiday \
+        sudden rib gather media vicious";
+        let key_pair = Mnemonic::from_str(mnemonic_str, &None)?.to_key_pair()?;
+        Ok(TonWallet::derive_default(WalletVersion::V4R2, &key_pair)?)
+    }
+
+    #[test]
+    fn test_tx_builder_messages() -> anyhow::Result<()> {
+        let wallet = wallet()?;
+        let dest: TonAddress = "EQAd8QRKoA5sKcug9bwK6vMdmhSAoAxr8vvABvC1TCeTude5".parse()?;
+        let jetton_wallet: TonAddress =
+            "EQDk2VTvn04SUKJrW7rXahzdF8_Qi6utb0wj43InCu9vdjrR".parse()?;
+        let messages = TxBuilder::new(&wallet)
+            .transfer(&dest, &BigUint::from(1000u32))
+            .with_comment("hello")
+            .with_bounce(false)
+            .transfer(&dest, &BigUint::from(50_000_000u32))
+            .with_jetton(&jetton_wallet, &BigUint::from(777u32))
+            .build_internal_messages()?;
+        assert_eq!(messages.len(), 2);
+
+        let mut parser = messages[0].parser();
+        parser.skip_bits(2)?;
+        assert!(!parser.load_bit()?); // bounce
+        let comment = messages[0].reference(0)?;
+        let mut comment_parser = comment.parser();
+        assert_eq!(comment_parser.load_u32(32)?, 0);
+        assert_eq!(comment_parser.load_utf8(5)?, "hello");
+
+        let mut parser = messages[1].parser();
+        parser.skip_bits(4)?;
+        assert_eq!(parser.load_address()?, TonAddress::NULL);
+        assert_eq!(parser.load_address()?, jetton_wallet);
+        let body = messages[1].reference(0)?;
+        assert_eq!(body.parser().load_u32(32)?, JETTON_TRANSFER);
+        let jetton_transfer = JettonTransferMessage::parse(body)?;
+        assert_eq!(jetton_transfer.destination, dest);
+        assert_eq!(jetton_transfer.amount, BigUint::from(777u32));
+        assert_eq!(jetton_transfer.response_destination, wallet.address);
+
+        let external = TxBuilder::new(&wallet)
+            .transfer(&dest, &BigUint::from(1000u32))
+            .build(7)?;
+        assert_eq!(external.references().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tx_builder_body() -> anyhow::Result<()> {
+        let wallet = wallet()?;
+        let body = RecoverStakeMessage::new()
+            .with_query_id(7)
+            .build()?
+            .to_arc();
+        let messages = TxBuilder::new(&wallet)
+            .transfer(&ELECTOR_ADDRESS, &BigUint::from(1_000_000_000u32))
+            .with_comment("ignored")
+            .with_body(body.clone())
+            .build_internal_messages()?;
+        assert_eq!(messages[0].reference(0)?, &body);
+
+        let result = TxBuilder::new(&wallet)
+            .transfer(&ELECTOR_ADDRESS, &BigUint::from(1u32))
+            .with_jetton(&wallet.address, &BigUint::from(1u32))
+            .with_body(body)
+            .build_internal_messages();
+        assert!(matches!(result, Err(TxBuilderError::IllegalArgument(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_tx_builder_split() -> anyhow::Result<()> {
+        let wallet = wallet()?;
+        let mut builder = TxBuilder::new(&wallet);
+        for i in 0..9u32 {
+            builder.transfer(&wallet.address, &BigUint::from(i));
+        }
+        let batches = builder.split(5)?;
+        assert_eq!(batches.len(), 3);
+        assert_eq!(
+            batches.iter().map(|b| b.seqno).collect::<Vec<_>>(),
+            vec![5, 6, 7]
+        );
+        assert_eq!(
+            batches
+                .iter()
+                .map(|b| b.transfers.len())
+                .collect::<Vec<_>>(),
+            vec![4, 4, 1]
+        );
+        assert_eq!(batches[2].transfers[0].amount, BigUint::from(8u32));
+        assert!(matches!(
+            builder.build(5),
+            Err(TxBuilderError::IllegalArgument(_))
+        ));
+        assert!(TxBuilder::new(&wallet).split(0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_tx_builder_errors() -> anyhow::Result<()> {
+        let wallet = wallet()?;
+        let result = TxBuilder::new(&wallet).with_comment("hello").build(0);
+        assert!(matches!(result, Err(TxBuilderError::IllegalArgument(_))));
+        let result = TxBuilder::new(&wallet).build(0);
+        assert!(matches!(result, Err(TxBuilderError::IllegalArgument(_))));
+        Ok(())
+    }
+}
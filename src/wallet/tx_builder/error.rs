@@ -0,0 +1,67 @@
+use thiserror::Error;
+
+use crate::address::TonAddress;
+use crate::cell::TonCellError;
+use crate::client::TonClientError;
+use crate::contract::TonContractError;
+use crate::emulator::TvmEmulatorError;
+use crate::message::TonMessageError;
+
+#[derive(Error, Debug)]
+pub enum TxBuilderError {
+    #[error("Illegal argument ({0})")]
+    IllegalArgument(String),
+
+    #[error("Internal error ({0})")]
+    InternalError(String),
+
+    #[error("Transaction is not confirmed (wallet: {address}, seqno: {seqno}, timeout: {timeout_secs}s)")]
+    ConfirmationTimeout {
+        address: TonAddress,
+        seqno: u32,
+        timeout_secs: u64,
+    },
+
+    #[error("Simulation does not meet the guard (wallet: {address}): {reason}")]
+    GuardViolation { address: TonAddress, reason: String },
+
+    #[error("Idempotency key {key} is already used for other transfers")]
+    IdempotencyKeyConflict { key: String },
+
+    #[error("Wallet store error ({0})")]
+    StoreError(String),
+
+    #[error("Nothing to recover from the elector (wallet: {address})")]
+    NothingToRecover { address: TonAddress },
+
+    #[error("Stake recovery is rejected by the elector (wallet: {address}, query_id: {query_id})")]
+    StakeRecoveryRejected { address: TonAddress, query_id: u64 },
+
+    #[error("Stake refund is not received (wallet: {address}, query_id: {query_id}, timeout: {timeout_secs}s)")]
+    StakeRecoveryTimeout {
+        address: TonAddress,
+        query_id: u64,
+        timeout_secs: u64,
+    },
+
+    #[error("TonCellError ({0})")]
+    TonCellError(#[from] TonCellError),
+
+    #[error("TonMessageError ({0})")]
+    TonMessageError(#[from] TonMessageError),
+
+    #[error("TonContractError ({0})")]
+    TonContractError(Box<TonContractError>),
+
+    #[error("TonClientError ({0})")]
+    TonClientError(#[from] TonClientError),
+
+    #[error("TvmEmulatorError ({0})")]
+    TvmEmulatorError(#[from] TvmEmulatorError),
+}
+
+impl From<TonContractError> for TxBuilderError {
+    fn from(error: TonContractError) -> Self {
+        TxBuilderError::TonContractError(Box::new(error))
+    }
+}
@@ -0,0 +1,50 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::cell::BagOfCells;
+use crate::client::TonClientInterface;
+use crate::contract::TonContractFactory;
+use crate::emulator::{TransactionEmulator, TxEmulationSuccess};
+use crate::transaction::TxFees;
+use crate::wallet::{TxBuilder, TxBuilderError};
+
+impl TxBuilder {
+    /// Emulates the wallet transaction on the latest state of the wallet (`ShardAccount`,
+    /// including its storage stat) with the current blockchain config and the libraries used
+    /// by the wallet code. Messages sent by the wallet are not executed.
+    pub async fn emulate_transaction(
+        &self,
+        factory: &TonContractFactory,
+    ) -> Result<TxEmulationSuccess, TxBuilderError> {
+        let address = &self.wallet.address;
+        let seqno = self.resolve_seqno(factory).await?;
+        let message = self.build(seqno)?;
+        let state = factory.get_latest_account_state(address).await?;
+        let libs = factory
+            .library_provider()
+            .get_contract_libraries(address, &state)
+            .await?;
+        let shard_account = factory.client().get_shard_account_cell(address).await?;
+        let shard_account = BagOfCells::parse(&shard_account.bytes)?
+            .single_root()?
+            .clone();
+        let config = factory.client().get_config_all(0).await?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| TxBuilderError::InternalError(e.to_string()))?
+            .as_secs() as u32;
+
+        let mut emulator = TransactionEmulator::new(&config.config.bytes)?;
+        emulator.set_unixtime(now)?;
+        emulator.set_libraries(libs.dict_boc.as_slice())?;
+        let result = emulator.emulate_transaction(&shard_account, &message)?;
+        Ok(result)
+    }
+
+    /// Returns fees of the wallet transaction, see `emulate_transaction`.
+    pub async fn estimate_fees(
+        &self,
+        factory: &TonContractFactory,
+    ) -> Result<TxFees, TxBuilderError> {
+        Ok(self.emulate_transaction(factory).await?.fees())
+    }
+}
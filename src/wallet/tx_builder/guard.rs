@@ -0,0 +1,305 @@
+use num_bigint::BigUint;
+
+use crate::contract::TonContractFactory;
+use crate::emulator::{TraceEmulator, TraceNode, TraceOutcome};
+use crate::transaction::TxComputePhase;
+use crate::wallet::{TxBuilder, TxBuilderError};
+
+/// Expectations the emulated transaction must meet to be sent by
+/// `TxBuilder::send_and_confirm_with_guard`.
+///
+/// Non-zero exit code of the wallet is always a violation, bounces of the sent messages are
+/// violations unless allowed by `with_allow_bounces`. Messages, which couldn't be emulated, are
+/// inconclusive and are not treated as bounces, see `TxSimulation::inconclusive`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TxGuard {
+    pub min_out_value: Option<BigUint>,
+    pub max_fee: Option<BigUint>,
+    pub allow_bounces: bool,
+}
+
+impl TxGuard {
+    pub fn new() -> TxGuard {
+        TxGuard::default()
+    }
+
+    /// Sets the minimal total value of messages sent by the wallet.
+    pub fn with_min_out_value(&mut self, min_out_value: &BigUint) -> &mut Self {
+        self.min_out_value = Some(min_out_value.clone());
+        self
+    }
+
+    /// Sets the maximal total fee of the wallet transaction, see `TxSimulation::fee`.
+    pub fn with_max_fee(&mut self, max_fee: &BigUint) -> &mut Self {
+        self.max_fee = Some(max_fee.clone());
+        self
+    }
+
+    pub fn with_allow_bounces(&mut self, allow_bounces: bool) -> &mut Self {
+        self.allow_bounces = allow_bounces;
+        self
+    }
+
+    /// Returns the description of the first unmet expectation, if any.
+    pub fn check(&self, simulation: &TxSimulation) -> Option<String> {
+        match &simulation.trace.outcome {
+            TraceOutcome::Executed(result) => {
+                if let Some(TxComputePhase::Vm { exit_code, .. }) = result.compute_phase() {
+                    if !is_success_exit_code(*exit_code) {
+                        return Some(format!("Wallet exited with code {}", exit_code));
+                    }
+                }
+                if result.transaction.aborted {
+                    return Some("Wallet transaction is aborted".to_string());
+                }
+            }
+            outcome => return Some(format!("Wallet is not executed: {:?}", outcome)),
+        }
+        if !self.allow_bounces {
+            if let Some(node) = simulation.bounced().first() {
+                return Some(format!("Message to {} would bounce", node.address));
+            }
+        }
+        if let Some(min_out_value) = &self.min_out_value {
+            if &simulation.out_value < min_out_value {
+                return Some(format!(
+                    "Out value {} is less than {}",
+                    simulation.out_value, min_out_value
+                ));
+            }
+        }
+        if let Some(max_fee) = &self.max_fee {
+            if &simulation.fee > max_fee {
+                return Some(format!("Fee {} exceeds {}", simulation.fee, max_fee));
+            }
+        }
+        None
+    }
+}
+
+/// Emulated wallet transaction and the first hop of the messages it sends.
+#[derive(Debug)]
+pub struct TxSimulation {
+    pub trace: TraceNode,
+    /// total fee paid by the wallet: storage, gas, import and action fees of the transaction
+    /// and forward fees of the sent messages.
+    pub fee: BigUint,
+    /// total value of the internal messages sent by the wallet.
+    pub out_value: BigUint,
+}
+
+impl TxSimulation {
+    /// Returns nodes of bounceable messages failed or not delivered to the destination.
+    pub fn bounced(&self) -> Vec<&TraceNode> {
+        self.trace
+            .children
+            .iter()
+            .filter(|node| node.bounce && would_bounce(&node.outcome) == Some(true))
+            .collect()
+    }
+
+    /// Returns nodes of bounceable messages, which couldn't be emulated, so it is unknown
+    /// whether they would bounce.
+    pub fn inconclusive(&self) -> Vec<&TraceNode> {
+        self.trace
+            .children
+            .iter()
+            .filter(|node| node.bounce && would_bounce(&node.outcome).is_none())
+            .collect()
+    }
+}
+
+impl TxBuilder {
+    /// Emulates the external message on the latest state of the wallet and the messages it
+    /// sends on the latest states of their destinations.
+    pub async fn simulate(
+        &self,
+        factory: &TonContractFactory,
+    ) -> Result<TxSimulation, TxBuilderError> {
+        let seqno = self.resolve_seqno(factory).await?;
+        self.simulate_with_seqno(factory, seqno).await
+    }
+
+    /// Emulates the transaction and sends it only if the simulation meets the `guard`,
+    /// otherwise `TxBuilderError::GuardViolation` is returned.
+    pub async fn send_and_confirm_with_guard(
+        &self,
+        factory: &TonContractFactory,
+        guard: &TxGuard,
+    ) -> Result<Vec<u8>, TxBuilderError> {
+        let seqno = self.resolve_seqno(factory).await?;
+        let simulation = self.simulate_with_seqno(factory, seqno).await?;
+        for node in simulation.inconclusive() {
+            if let TraceOutcome::Failed(e) = &node.outcome {
+                log::warn!(
+                    "[TxGuard] Message of {} to {} is not emulated: {}",
+                    self.wallet.address,
+                    node.address,
+                    e
+                );
+            }
+        }
+        if let Some(reason) = guard.check(&simulation) {
+            return Err(TxBuilderError::GuardViolation {
+                address: self.wallet.address.clone(),
+                reason,
+            });
+        }
+        self.send_and_confirm_with_seqno(factory, seqno).await
+    }
+
+    async fn simulate_with_seqno(
+        &self,
+        factory: &TonContractFactory,
+        seqno: u32,
+    ) -> Result<TxSimulation, TxBuilderError> {
+        let message = self.build(seqno)?;
+        let trace = TraceEmulator::new(factory)
+            .with_max_depth(1)
+            .emulate_message(message)
+            .await?;
+        let fee = match &trace.outcome {
+            TraceOutcome::Executed(result) => {
+                let fees = result.fees();
+                fees.total_fees + fees.out_msgs_fee
+            }
+            _ => BigUint::default(),
+        };
+        let out_value = trace
+            .children
+            .iter()
+            .map(|node| BigUint::from(node.amount))
+            .sum();
+        Ok(TxSimulation {
+            fee,
+            out_value,
+            trace,
+        })
+    }
+}
+
+fn is_success_exit_code(exit_code: i32) -> bool {
+    exit_code == 0 || exit_code == 1
+}
+
+/// Returns `None` if the message is not emulated.
+fn would_bounce(outcome: &TraceOutcome) -> Option<bool> {
+    match outcome {
+        TraceOutcome::Executed(result) => Some(result.transaction.aborted),
+        TraceOutcome::Failed(_) => None,
+        TraceOutcome::Skipped => Some(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use crate::address::TonAddress;
+    use crate::cell::Cell;
+    use crate::contract::TonContractError;
+    use crate::emulator::{TraceNode, TraceOutcome, TxEmulationSuccess};
+    use crate::transaction::{ParsedTx, TxComputePhase};
+    use crate::wallet::{TxGuard, TxSimulation};
+
+    fn executed(exit_code: i32, aborted: bool) -> TraceOutcome {
+        let transaction = ParsedTx {
+            hash: [0; 32],
+            account: [0; 32],
+            lt: 1,
+            prev_trans_hash: [0; 32],
+            prev_trans_lt: 0,
+            now: 1_700_000_000,
+            in_msg: None,
+            in_msg_hash: None,
+            out_msgs: vec![],
+            total_fees: BigUint::from(1_200_000u32),
+            storage_phase: None,
+            credit_phase: None,
+            compute_phase: Some(TxComputePhase::Vm {
+                success: exit_code == 0,
+                gas_fees: BigUint::from(1_200_000u32),
+                gas_used: BigUint::from(3_000u32),
+                exit_code,
+            }),
+            action_phase: None,
+            aborted,
+        };
+        TraceOutcome::Executed(Box::new(TxEmulationSuccess {
+            transaction,
+            out_msgs: vec![],
+            shard_account: Cell::default().to_arc(),
+            vm_log: None,
+            actions: None,
+            elapsed_time: 0.0,
+        }))
+    }
+
+    fn node(address: TonAddress, bounce: bool, outcome: TraceOutcome) -> TraceNode {
+        TraceNode {
+            address,
+            message: Cell::default().to_arc(),
+            amount: 1_000,
+            bounce,
+            outcome,
+            children: vec![],
+        }
+    }
+
+    fn simulation(bounce: bool, child: TraceOutcome) -> TxSimulation {
+        let mut trace = node(TonAddress::new(0, &[2; 32]), false, executed(0, false));
+        trace.amount = 0;
+        trace
+            .children
+            .push(node(TonAddress::new(0, &[1; 32]), bounce, child));
+        TxSimulation {
+            trace,
+            fee: BigUint::from(1_200_000u32),
+            out_value: BigUint::from(1_000u32),
+        }
+    }
+
+    #[test]
+    fn test_tx_guard() {
+        let guard = TxGuard::new();
+        assert_eq!(guard.check(&simulation(true, executed(0, false))), None);
+        assert_eq!(guard.check(&simulation(false, executed(35, true))), None);
+        assert!(guard.check(&simulation(true, executed(35, true))).is_some());
+        // message to an uninitialized account: compute phase is skipped, transaction is aborted
+        let mut not_deployed = executed(0, true);
+        if let TraceOutcome::Executed(result) = &mut not_deployed {
+            result.transaction.compute_phase = Some(TxComputePhase::Skipped);
+        }
+        assert!(guard.check(&simulation(true, not_deployed)).is_some());
+        let not_emulated = TraceOutcome::Failed(TonContractError::InternalError("".to_string()));
+        let sim = simulation(true, not_emulated);
+        assert_eq!(guard.check(&sim), None);
+        assert!(sim.bounced().is_empty());
+        assert_eq!(sim.inconclusive().len(), 1);
+        assert_eq!(
+            TxGuard::new()
+                .with_allow_bounces(true)
+                .check(&simulation(true, executed(35, true))),
+            None
+        );
+
+        let mut failed = simulation(true, executed(0, false));
+        failed.trace.outcome = executed(33, true);
+        assert!(guard.check(&failed).is_some());
+
+        let sim = simulation(true, executed(0, false));
+        let value_guard = TxGuard::new()
+            .with_min_out_value(&BigUint::from(1_001u32))
+            .clone();
+        assert!(value_guard.check(&sim).is_some());
+        let fee_guard = TxGuard::new()
+            .with_max_fee(&BigUint::from(1_000_000u32))
+            .clone();
+        assert!(fee_guard.check(&sim).is_some());
+        let guard = TxGuard::new()
+            .with_min_out_value(&BigUint::from(1_000u32))
+            .with_max_fee(&BigUint::from(1_200_000u32))
+            .clone();
+        assert_eq!(guard.check(&sim), None);
+    }
+}
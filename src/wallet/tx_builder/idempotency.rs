@@ -0,0 +1,329 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::cell::BagOfCells;
+use crate::client::{TonClientInterface, TonMessageFunctions};
+use crate::contract::TonContractFactory;
+use crate::wallet::{TxBuilder, TxBuilderError, WalletStore};
+
+/// External message produced for an idempotency key.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IdempotencyRecord {
+    /// sha256 of the hashes of the internal messages, identifies the transfers.
+    pub fingerprint: Vec<u8>,
+    pub seqno: u32,
+    /// expiration time of the message.
+    pub valid_until: u32,
+    /// lt of the last transaction of the wallet before the message was built, bounds the
+    /// lookup of the transaction processing the message.
+    pub after_lt: i64,
+    pub message_hash: Vec<u8>,
+    /// serialized external message, broadcasted again on retries.
+    pub boc: Vec<u8>,
+}
+
+impl IdempotencyRecord {
+    fn to_bytes(&self) -> Result<Vec<u8>, TxBuilderError> {
+        serde_json::to_vec(self).map_err(|e| TxBuilderError::StoreError(e.to_string()))
+    }
+
+    fn from_bytes(data: &[u8]) -> Result<IdempotencyRecord, TxBuilderError> {
+        serde_json::from_slice(data).map_err(|e| TxBuilderError::StoreError(e.to_string()))
+    }
+}
+
+/// State of the message persisted for the idempotency key.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IdempotencyStatus {
+    /// no message is persisted for the key.
+    Absent,
+    /// processed by the wallet.
+    Executed,
+    /// not processed yet, but may still be.
+    Pending,
+    /// not processed and can never be, as the network time is past `valid_until`.
+    Expired,
+}
+
+impl IdempotencyStatus {
+    fn of(executed: bool, sync_utime: i64, record: &IdempotencyRecord) -> IdempotencyStatus {
+        if executed {
+            IdempotencyStatus::Executed
+        } else if sync_utime > record.valid_until as i64 {
+            IdempotencyStatus::Expired
+        } else {
+            IdempotencyStatus::Pending
+        }
+    }
+}
+
+/// Returns store key of the record. The record of every next generation replaces the expired
+/// record of the previous one.
+fn record_key(key: &str, generation: u32) -> String {
+    match generation {
+        0 => format!("idempotency/{}", key),
+        n => format!("idempotency.{}/{}", n, key),
+    }
+}
+
+/// Atomically persists the record of the generation, returns the record persisted before.
+async fn reserve(
+    store: &dyn WalletStore,
+    key: &str,
+    generation: u32,
+    record: &IdempotencyRecord,
+) -> Result<Option<IdempotencyRecord>, TxBuilderError> {
+    let stored = store
+        .insert_if_absent(&record_key(key, generation), &record.to_bytes()?)
+        .await?;
+    stored
+        .map(|s| IdempotencyRecord::from_bytes(&s))
+        .transpose()
+}
+
+#[derive(Clone)]
+pub(super) struct Idempotency {
+    pub(super) store: Arc<dyn WalletStore>,
+    pub(super) key: String,
+}
+
+impl TxBuilder {
+    /// Attaches idempotency key to the transaction.
+    ///
+    /// The external message is persisted in the `store` under `idempotency/<key>` before
+    /// sending. Sending again with the same key broadcasts the persisted message instead of
+    /// building a new one, so the transfers are executed at most once, and fails with
+    /// `TxBuilderError::IdempotencyKeyConflict` if the transfers differ. Once the persisted
+    /// message is expired without being processed, it is replaced by a new one built with the
+    /// current seqno of the wallet.
+    ///
+    /// `send_batches` appends `#<batch index>` to the key of every batch.
+    pub fn with_idempotency_key(&mut self, store: Arc<dyn WalletStore>, key: &str) -> &mut Self {
+        self.idempotency = Some(Idempotency {
+            store,
+            key: key.to_string(),
+        });
+        self
+    }
+
+    /// Returns sha256 of the hashes of internal messages, which does not depend on seqno and
+    /// expiration time of the external message.
+    pub fn fingerprint(&self) -> Result<Vec<u8>, TxBuilderError> {
+        let mut hasher = Sha256::new();
+        for message in self.build_internal_messages()? {
+            hasher.update(message.cell_hash());
+        }
+        Ok(hasher.finalize().to_vec())
+    }
+
+    /// Sends the message persisted for the key, or builds, persists and sends a new one.
+    /// Returns hash and seqno of the sent message.
+    pub(super) async fn send_idempotent(
+        &self,
+        factory: &TonContractFactory,
+        idempotency: &Idempotency,
+        seqno: u32,
+    ) -> Result<(Vec<u8>, u32), TxBuilderError> {
+        let mut record = self.idempotency_record(factory, seqno).await?;
+        let mut generation = 0;
+        loop {
+            let store = idempotency.store.as_ref();
+            let Some(stored) = reserve(store, &idempotency.key, generation, &record).await? else {
+                let hash = factory
+                    .client()
+                    .send_raw_message_return_hash(&record.boc)
+                    .await?;
+                return Ok((hash, record.seqno));
+            };
+            if stored.fingerprint != record.fingerprint {
+                return Err(TxBuilderError::IdempotencyKeyConflict {
+                    key: idempotency.key.clone(),
+                });
+            }
+            let Err(e) = factory.client().send_raw_message(&stored.boc).await else {
+                return Ok((stored.message_hash, stored.seqno));
+            };
+            match self.stored_status(factory, &stored).await? {
+                IdempotencyStatus::Executed => return Ok((stored.message_hash, stored.seqno)),
+                IdempotencyStatus::Absent | IdempotencyStatus::Pending => return Err(e.into()),
+                IdempotencyStatus::Expired => {
+                    log::debug!(
+                        "Persisted message for key {} is expired (seqno: {}), building a new one",
+                        idempotency.key,
+                        stored.seqno
+                    );
+                    let seqno = self.fetch_seqno(factory).await?;
+                    record = self.idempotency_record(factory, seqno).await?;
+                    generation += 1;
+                }
+            }
+        }
+    }
+
+    /// Returns the state of the latest message persisted for the idempotency key, e.g. to
+    /// decide whether the transfers of an interrupted send are executed.
+    pub async fn idempotency_status(
+        &self,
+        factory: &TonContractFactory,
+    ) -> Result<IdempotencyStatus, TxBuilderError> {
+        let Some(idempotency) = &self.idempotency else {
+            return Err(TxBuilderError::IllegalArgument(
+                "Idempotency key is not set".to_string(),
+            ));
+        };
+        let mut latest = None;
+        for generation in 0.. {
+            let key = record_key(&idempotency.key, generation);
+            match idempotency.store.get(&key).await? {
+                Some(data) => latest = Some(IdempotencyRecord::from_bytes(&data)?),
+                None => break,
+            }
+        }
+        match latest {
+            Some(stored) => self.stored_status(factory, &stored).await,
+            None => Ok(IdempotencyStatus::Absent),
+        }
+    }
+
+    async fn idempotency_record(
+        &self,
+        factory: &TonContractFactory,
+        seqno: u32,
+    ) -> Result<IdempotencyRecord, TxBuilderError> {
+        let after_lt = factory
+            .client()
+            .get_raw_account_state(&self.wallet.address)
+            .await?
+            .last_transaction_id
+            .lt;
+        let valid_until = self.valid_until()?;
+        let message = self.build_until(seqno, valid_until)?;
+        Ok(IdempotencyRecord {
+            fingerprint: self.fingerprint()?,
+            seqno,
+            valid_until,
+            after_lt,
+            message_hash: message.cell_hash().to_vec(),
+            boc: BagOfCells::from_root(message).serialize(true)?,
+        })
+    }
+
+    /// Looks up the transaction of the wallet processing the persisted message.
+    async fn stored_status(
+        &self,
+        factory: &TonContractFactory,
+        stored: &IdempotencyRecord,
+    ) -> Result<IdempotencyStatus, TxBuilderError> {
+        let client = factory.client();
+        let address = &self.wallet.address;
+        // the state covers all blocks up to its sync time, so the transaction is found if the
+        // message is processed before the sync time
+        let state = client.get_raw_account_state(address).await?;
+        let tx = client
+            .find_transaction_by_in_msg_hash(
+                address,
+                &state.last_transaction_id,
+                stored.after_lt,
+                &stored.message_hash,
+            )
+            .await?;
+        Ok(IdempotencyStatus::of(
+            tx.is_some(),
+            state.sync_utime,
+            stored,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use crate::address::TonAddress;
+    use crate::mnemonic::Mnemonic;
+    use crate::wallet::tx_builder::idempotency::{record_key, reserve, IdempotencyStatus};
+    use crate::wallet::{
+        IdempotencyRecord, InMemoryWalletStore, TonWallet, TxBuilder, WalletVersion,
+    };
+
+    fn record(seqno: u32, valid_until: u32) -> IdempotencyRecord {
+        IdempotencyRecord {
+            fingerprint: vec![1; 32],
+            seqno,
+            valid_until,
+            after_lt: 100,
+            message_hash: vec![seqno as u8; 32],
+            boc: vec![3; 10],
+        }
+    }
+
+    #[test]
+    fn test_idempotency_record_serde() -> anyhow::Result<()> {
+        let record = record(7, 1_700_000_060);
+        let restored = IdempotencyRecord::from_bytes(&record.to_bytes()?)?;
+        assert_eq!(restored, record);
+        assert!(IdempotencyRecord::from_bytes(b"{}").is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_expired_record_is_replaced() -> anyhow::Result<()> {
+        let store = InMemoryWalletStore::new();
+        let expired = record(7, 1_700_000_060);
+        assert_eq!(reserve(&store, "payout", 0, &expired).await?, None);
+
+        assert_eq!(
+            IdempotencyStatus::of(false, 1_700_000_060, &expired),
+            IdempotencyStatus::Pending
+        );
+        assert_eq!(
+            IdempotencyStatus::of(true, 1_700_000_061, &expired),
+            IdempotencyStatus::Executed
+        );
+        assert_eq!(
+            IdempotencyStatus::of(false, 1_700_000_061, &expired),
+            IdempotencyStatus::Expired
+        );
+
+        // the expired record is kept, the new one is reserved by the next generation once
+        let rebuilt = record(8, 1_700_000_200);
+        assert_eq!(reserve(&store, "payout", 0, &rebuilt).await?, Some(expired));
+        assert_eq!(reserve(&store, "payout", 1, &rebuilt).await?, None);
+        let concurrent = record(9, 1_700_000_201);
+        assert_eq!(
+            reserve(&store, "payout", 1, &concurrent).await?,
+            Some(rebuilt)
+        );
+        assert_eq!(record_key("payout", 1), "idempotency.1/payout");
+        Ok(())
+    }
+
+    #[test]
+    fn test_tx_builder_fingerprint() -> anyhow::Result<()> {
+        let mnemonic_str = "fancy carpet hello mandate penalty trial consider \
+        property top vicious exit rebuild tragic profit urban major total month holiday \
+        sudden rib gather media vicious";
+        let key_pair = Mnemonic::from_str(mnemonic_str, &None)?.to_key_pair()?;
+        let wallet = TonWallet::derive_default(WalletVersion::V4R2, &key_pair)?;
+        let dest = TonAddress::new(0, &[1; 32]);
+
+        let fingerprint = TxBuilder::new(&wallet)
+            .transfer(&dest, &BigUint::from(100u32))
+            .with_comment("payout")
+            .fingerprint()?;
+        let same = TxBuilder::new(&wallet)
+            .transfer(&dest, &BigUint::from(100u32))
+            .with_comment("payout")
+            .with_seqno(5)
+            .fingerprint()?;
+        let other = TxBuilder::new(&wallet)
+            .transfer(&dest, &BigUint::from(101u32))
+            .with_comment("payout")
+            .fingerprint()?;
+        assert_eq!(fingerprint, same);
+        assert_ne!(fingerprint, other);
+        Ok(())
+    }
+}
@@ -104,6 +104,49 @@ impl TryFrom<WalletDataV4> for Cell {
     }
 }
 
+/// WalletVersion::V5R1
+pub struct WalletDataV5 {
+    pub signature_allowed: bool,
+    pub seqno: u32,
+    pub wallet_id: i32,
+    pub public_key: [u8; 32],
+}
+
+impl TryFrom<Cell> for WalletDataV5 {
+    type Error = TonCellError;
+
+    fn try_from(value: Cell) -> Result<Self, Self::Error> {
+        let mut parser = value.parser();
+        let signature_allowed = parser.load_bit()?;
+        let seqno = parser.load_u32(32)?;
+        let wallet_id = parser.load_i32(32)?;
+        let mut public_key = [0u8; 32];
+        parser.load_slice(&mut public_key)?;
+        // TODO: handle extensions dict
+        Ok(Self {
+            signature_allowed,
+            seqno,
+            wallet_id,
+            public_key,
+        })
+    }
+}
+
+impl TryFrom<WalletDataV5> for Cell {
+    type Error = TonCellError;
+
+    fn try_from(value: WalletDataV5) -> Result<Self, Self::Error> {
+        CellBuilder::new()
+            .store_bit(value.signature_allowed)?
+            .store_u32(32, value.seqno)?
+            .store_i32(32, value.wallet_id)?
+            .store_slice(&value.public_key)?
+            // empty extensions dict
+            .store_bit(false)?
+            .build()
+    }
+}
+
 /// WalletVersion::HighloadV2R2
 pub struct WalletDataHighloadV2R2 {
     pub wallet_id: i32,
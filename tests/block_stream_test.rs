@@ -1,7 +1,10 @@
+use std::collections::HashSet;
+
+use futures::StreamExt;
 use tokio_test::assert_ok;
 use tonlib::client::{
-    BlockStream, TonBlockFunctions, TonClientInterface, TonConnection, TonConnectionParams,
-    LOGGING_CONNECTION_CALLBACK,
+    BlockStream, TonBlockFunctions, TonBlockStream, TonClientInterface, TonConnection,
+    TonConnectionParams, LOGGING_CONNECTION_CALLBACK,
 };
 use tonlib::tl::InternalTransactionId;
 
@@ -30,6 +33,48 @@ pub async fn block_listener_works() {
     }
 }
 
+#[tokio::test]
+pub async fn ton_block_stream_works() {
+    common::init_logging();
+    let client = common::new_mainnet_client().await;
+    let (_, mc_info) = assert_ok!(client.get_masterchain_info().await);
+    let seqno = mc_info.last.seqno - 5;
+    let mut stream = TonBlockStream::new(&client, seqno);
+    let mut master_seqnos = vec![];
+    while master_seqnos.len() < 3 {
+        let block = assert_ok!(stream.next().await.unwrap());
+        log::info!("{:?}", block.to_block_id());
+        if block.workchain == -1 {
+            master_seqnos.push(block.seqno);
+        }
+    }
+    assert_eq!(master_seqnos, vec![seqno, seqno + 1, seqno + 2]);
+}
+
+#[tokio::test]
+pub async fn get_shard_blocks_between_works() {
+    common::init_logging();
+    let client = common::new_mainnet_client().await;
+    let (_, mc_info) = assert_ok!(client.get_masterchain_info().await);
+    let seqno = mc_info.last.seqno - 10;
+    let blocks = assert_ok!(client.get_shard_blocks_between(seqno, seqno + 3).await);
+
+    let mut listener = BlockStream::new(&client, seqno + 1);
+    let mut expected = vec![];
+    for _ in 0..3 {
+        expected.extend(assert_ok!(listener.next().await).shards);
+    }
+    // blocks are ordered by lt over the whole range, the stream orders them per masterchain block
+    assert_eq!(blocks.len(), expected.len());
+    assert_eq!(
+        blocks.iter().collect::<HashSet<_>>(),
+        expected.iter().collect::<HashSet<_>>()
+    );
+
+    let prev = assert_ok!(client.get_prev_blocks(&blocks[blocks.len() - 1]).await);
+    assert!(!prev.is_empty());
+}
+
 #[tokio::test]
 pub async fn block_listener_get_block_header() {
     common::init_logging();
@@ -0,0 +1,28 @@
+#![cfg(feature = "blocking")]
+
+use tokio_test::assert_ok;
+use tonlib::address::TonAddress;
+use tonlib::blocking::TonClientBlocking;
+use tonlib::client::TonClient;
+use tonlib::types::TonMethodId;
+
+mod common;
+
+#[test]
+fn test_blocking_client() {
+    common::init_logging();
+    let client = assert_ok!(TonClientBlocking::new(
+        TonClient::builder().with_config(&common::MAINNET_CONFIG)
+    ));
+    let info = assert_ok!(client.get_masterchain_info());
+    log::info!("{:?}", info);
+
+    let address = assert_ok!(TonAddress::from_base64_url(
+        "EQDk2VTvn04SUKJrW7rXahzdF8_Qi6utb0wj43InCu9vdjrR"
+    ));
+    let state = assert_ok!(client.get_raw_account_state(&address));
+    log::info!("{:?}", state);
+    let result =
+        assert_ok!(client.smc_run_get_method(&address, &TonMethodId::from("get_jetton_data"), &[]));
+    assert_eq!(result.exit_code, 0);
+}
@@ -11,10 +11,17 @@ use tokio::time::timeout;
 use tokio::{self};
 use tokio_test::assert_ok;
 use tonlib::address::TonAddress;
-use tonlib::cell::{key_extractor_256bit, value_extractor_cell, BagOfCells, GenericDictLoader};
-use tonlib::client::{TonBlockFunctions, TonClient, TonClientBuilder, TonClientInterface, TxId};
+use tonlib::cell::{
+    key_extractor_256bit, value_extractor_cell, BagOfCells, CellBuilder, GenericDictLoader,
+};
+use tonlib::client::{
+    AccountFilter, ArchiveRouting, ConnectionMode, PoolAutoscaling, StickyFailover,
+    TonBlockFunctions, TonClient, TonClientBuilder, TonClientInterface, TxId, WatchSet,
+    TONLIB_VERSION,
+};
 use tonlib::config::{MAINNET_CONFIG, TESTNET_CONFIG};
 use tonlib::contract::{TonContractFactory, TonContractInterface};
+use tonlib::emulator::AccountStorageStat;
 use tonlib::tl::{
     BlockId, BlockIdExt, BlocksShards, BlocksTransactions, BlocksTransactionsExt,
     InternalTransactionId, LiteServerInfo, SmcLibraryQueryExt, TonLibraryId,
@@ -64,28 +71,23 @@ async fn client_get_raw_transactions_works() {
     let address = &assert_ok!(TonAddress::from_base64_url(
         "EQDk2VTvn04SUKJrW7rXahzdF8_Qi6utb0wj43InCu9vdjrR"
     ));
-    let max_retries = 3;
-    let mut retries = 0;
-    while retries < max_retries {
-        retries += 1;
-        let client = common::new_mainnet_client().await;
-        let state = client.get_raw_account_state(address).await.unwrap();
-        let r = client
+    // transient liteserver errors are retried by the client
+    let client = common::new_mainnet_client().await;
+    let state = assert_ok!(client.get_raw_account_state(address).await);
+    let r = assert_ok!(
+        client
             .get_raw_transactions(address, &state.last_transaction_id)
-            .await;
-        println!("{:?}", r);
-        if r.is_ok() {
-            let cnt = 1;
-            let r = client
-                .get_raw_transactions_v2(address, &state.last_transaction_id, cnt, false)
-                .await;
-            println!("{:?}", r);
-            if r.is_ok() {
-                assert_eq!(r.unwrap().transactions.len(), cnt);
-                return;
-            }
-        }
-    }
+            .await
+    );
+    println!("{:?}", r);
+    let cnt = 1;
+    let r = assert_ok!(
+        client
+            .get_raw_transactions_v2(address, &state.last_transaction_id, cnt, false)
+            .await
+    );
+    println!("{:?}", r);
+    assert_eq!(r.transactions.len(), cnt);
 }
 
 #[tokio::test]
@@ -127,28 +129,58 @@ async fn client_smc_load_by_transaction_works() {
         "32016630000001:91485a21ba6eaaa91827e357378fe332228d11f3644e802f7e0f873a11ce9c6f",
     ));
 
-    let max_retries = 3;
-    let mut retries = 0;
-    while retries < max_retries {
-        retries += 1;
-        let client = common::new_mainnet_client().await;
+    let client = common::new_mainnet_client().await;
 
-        let state = client.get_raw_account_state(address).await.unwrap();
+    let state = assert_ok!(client.get_raw_account_state(address).await);
 
-        log::info!("TRANSACTION_ID{}", &state.last_transaction_id);
+    log::info!("TRANSACTION_ID{}", &state.last_transaction_id);
 
-        let tx_id = Arc::new(TxId {
-            address: address.clone(),
-            internal_transaction_id: internal_transaction_id.clone(),
-        });
-        let res = client
+    let tx_id = Arc::new(TxId {
+        address: address.clone(),
+        internal_transaction_id: internal_transaction_id.clone(),
+    });
+    assert_ok!(
+        client
             .smc_load_by_transaction(&tx_id.address, &tx_id.internal_transaction_id)
-            .await;
+            .await
+    );
+}
 
-        if res.is_ok() {
-            return;
-        }
-    }
+#[tokio::test]
+async fn client_get_account_state_by_transaction_works() {
+    common::init_logging();
+
+    let address = &assert_ok!(TonAddress::from_base64_url(
+        "EQCVx4vipWfDkf2uNhTUkpT97wkzRXHm-N1cNn_kqcLxecxT"
+    ));
+    let internal_transaction_id = assert_ok!(InternalTransactionId::from_str(
+        "32016630000001:91485a21ba6eaaa91827e357378fe332228d11f3644e802f7e0f873a11ce9c6f",
+    ));
+
+    let client = common::new_mainnet_client().await;
+    let state = assert_ok!(
+        client
+            .get_account_state_by_transaction(address, &internal_transaction_id)
+            .await
+    );
+    assert_eq!(state.last_transaction_id, internal_transaction_id);
+}
+
+#[tokio::test]
+async fn client_get_shard_account_cell_works() {
+    common::init_logging();
+    let client = common::new_mainnet_client().await;
+    let address = &assert_ok!(TonAddress::from_base64_url(
+        "EQCVx4vipWfDkf2uNhTUkpT97wkzRXHm-N1cNn_kqcLxecxT"
+    ));
+    let cell = assert_ok!(client.get_shard_account_cell(address).await);
+    let shard_account = assert_ok!(BagOfCells::parse(&cell.bytes));
+    let stat = assert_ok!(AccountStorageStat::from_shard_account(assert_ok!(
+        shard_account.single_root()
+    )));
+    log::info!("{:?}", stat);
+    assert!(stat.used_cells > 0);
+    assert!(stat.last_paid > 0);
 }
 
 #[tokio::test]
@@ -234,13 +266,8 @@ async fn test_client_blocks_get_transactions() {
             txs.incomplete
         );
         for tx_id in txs.transactions {
-            let mut t: [u8; 32] = [0; 32];
-            t.clone_from_slice(tx_id.account.as_slice());
-            let addr = TonAddress::new(workchain, &t);
-            let id = InternalTransactionId {
-                hash: tx_id.hash.clone(),
-                lt: tx_id.lt,
-            };
+            let addr = assert_ok!(tx_id.address(workchain));
+            let id = tx_id.transaction_id();
             let tx = assert_ok!(client.get_raw_transactions_v2(&addr, &id, 1, false).await);
             log::info!("Tx: {:?}", tx.transactions[0])
         }
@@ -296,6 +323,61 @@ async fn test_client_lite_server_get_info() {
     log::info!("{:?}", info);
 }
 
+#[tokio::test]
+async fn test_client_get_masterchain_info_ext() {
+    common::init_logging();
+    let client = common::new_mainnet_client().await;
+    let info = assert_ok!(client.get_masterchain_info_ext().await);
+    log::info!("{:?}", info);
+    assert_eq!(info.last.workchain, -1);
+    assert_eq!(info.state_root_hash.len(), 32);
+    assert!(info.last_utime > 0);
+    assert!(info.last_block_age() < 60);
+}
+
+#[tokio::test]
+async fn test_client_broadcast_message() {
+    common::init_logging();
+    let client = common::new_testnet_client().await;
+    let dest = assert_ok!(TonAddress::from_str(
+        "EQDk2VTvn04SUKJrW7rXahzdF8_Qi6utb0wj43InCu9vdjrR"
+    ));
+    let message = assert_ok!(CellBuilder::new()
+        .store_u8(4, 0b1000)
+        .and_then(|b| b.store_address(&dest))
+        .and_then(|b| b.store_u8(6, 0))
+        .and_then(|b| b.store_u32(32, 0))
+        .and_then(|b| b.build()));
+    let boc = assert_ok!(BagOfCells::from_root(message).serialize(true));
+    let broadcast = assert_ok!(client.broadcast_message(&boc).await);
+    log::info!("{:?}", broadcast);
+    assert_eq!(broadcast.attempts.len(), client.pool_size());
+    assert_eq!(broadcast.hashes.dest, dest);
+}
+
+#[tokio::test]
+async fn test_client_doctor() {
+    common::init_logging();
+    let client = common::new_mainnet_client().await;
+    let diagnostics = client.doctor().await;
+    assert_eq!(diagnostics.len(), client.pool_size());
+    for d in &diagnostics {
+        log::info!("{:?}, issues: {:?}", d, d.issues());
+        assert!(d.mc_seqno.is_some() || d.error.is_some());
+    }
+    assert!(diagnostics.iter().any(|d| d.head_lag == Some(0)));
+}
+
+#[tokio::test]
+async fn test_client_capabilities() {
+    common::init_logging();
+    let client = common::new_mainnet_client().await;
+    let capabilities = assert_ok!(client.capabilities().await);
+    log::info!("{:?}", capabilities);
+    assert_eq!(capabilities.tonlib_version, TONLIB_VERSION);
+    assert!(capabilities.liteserver_version > 0);
+}
+
 #[tokio::test]
 async fn test_get_config_param() {
     common::init_logging();
@@ -360,6 +442,67 @@ async fn test_get_shard_transactions_parse_address_correctly() {
     log::info!("{:?}", txs);
 }
 
+#[tokio::test]
+async fn test_get_shard_transactions_filtered() {
+    common::init_logging();
+    let client = &common::new_mainnet_client().await;
+    assert_ok!(client.sync().await);
+    let block_shard = BlockIdExt {
+        workchain: 0,
+        shard: -4611686018427387904,
+        seqno: 43256197,
+        root_hash: "yEteKr1hD3d20O/ZL+Y7AB2YD9xL1NZ9r0fXPwYlbYA=".to_string(),
+        file_hash: "VrzW8+EtGDYiaSiYQEou9N5+YWF2CeBzxmAMXUOZ5mE=".to_string(),
+    };
+    let masterchain_only = AccountFilter::Workchain(-1);
+    let txs = assert_ok!(
+        client
+            .get_shard_transactions_filtered(&block_shard, &masterchain_only)
+            .await
+    );
+    assert!(txs.is_empty());
+
+    let ids = assert_ok!(client.get_shard_tx_ids(&block_shard).await);
+    assert!(!ids.is_empty());
+    let address = ids[0].address.clone();
+    let filter: AccountFilter = WatchSet::from_iter([address.clone()]).into();
+    let filtered_ids = assert_ok!(
+        client
+            .get_shard_tx_ids_filtered(&block_shard, &filter)
+            .await
+    );
+    assert!(filtered_ids.iter().all(|id| id.address == address));
+    let txs = assert_ok!(
+        client
+            .get_shard_transactions_filtered(&block_shard, &filter)
+            .await
+    );
+    assert_eq!(txs.len(), filtered_ids.len());
+    for tx in txs {
+        assert_eq!(
+            assert_ok!(tx.address.account_address.parse::<TonAddress>()),
+            address
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_find_block_by_time() {
+    common::init_logging();
+    let client = &common::new_mainnet_client().await;
+    let (_, info) = assert_ok!(client.get_masterchain_info().await);
+    let last_utime = assert_ok!(client.get_block_header(&info.last).await).gen_utime;
+    let utime = last_utime - 600;
+    let block = assert_ok!(client.find_block_by_time(utime).await);
+    assert_eq!(block.workchain, -1);
+    assert!(block.seqno < info.last.seqno);
+    let block_utime = assert_ok!(client.get_block_header(&block).await).gen_utime;
+    assert!(block_utime <= utime);
+    let next = assert_ok!(client.masterchain_block_by_seqno(block.seqno + 1).await);
+    let next_utime = assert_ok!(client.get_block_header(&next).await).gen_utime;
+    assert!(next_utime > utime);
+}
+
 #[tokio::test]
 async fn test_get_shards_transactions() {
     common::init_logging();
@@ -367,9 +510,11 @@ async fn test_get_shards_transactions() {
     let (_, info) = assert_ok!(client.get_masterchain_info().await);
     let shards = assert_ok!(client.get_block_shards(&info.last).await);
     assert!(!shards.shards.is_empty());
-    let shards_txs = assert_ok!(client.get_shards_transactions(&shards.shards).await);
-    for s in shards_txs {
-        log::info!("{:?} : {:?}", s.0, s.1);
+    let shards_txs = client.get_shards_transactions(&shards.shards).await;
+    assert_eq!(shards_txs.len(), shards.shards.len());
+    let shards_txs = client.retry_failed_shards_transactions(shards_txs).await;
+    for (shard, txs) in shards_txs {
+        log::info!("{:?} : {:?}", shard, assert_ok!(txs));
     }
 }
 
@@ -446,9 +591,10 @@ async fn client_mainnet_works() {
     let shards = assert_ok!(client.get_block_shards(&info.last).await);
     let blocks_header = assert_ok!(client.get_block_header(&info.last).await);
     assert!(!shards.shards.is_empty());
-    let shards_txs = assert_ok!(client.get_shards_transactions(&shards.shards).await);
-    for s in shards_txs {
-        log::info!(" BlockId: {:?}\n Transactions: {:?}", s.0, s.1.len());
+    let shards_txs = client.get_shards_transactions(&shards.shards).await;
+    for (shard, txs) in shards_txs {
+        let txs = assert_ok!(txs);
+        log::info!(" BlockId: {:?}\n Transactions: {:?}", shard, txs.len());
     }
     log::info!(
         "MAINNET: Blocks header for  {} seqno : {:?}",
@@ -457,6 +603,84 @@ async fn client_mainnet_works() {
     );
 }
 
+#[tokio::test]
+async fn client_eager_connection_mode_works() {
+    common::init_logging();
+    let client = assert_ok!(
+        TonClient::builder()
+            .with_pool_size(2)
+            .with_config(MAINNET_CONFIG)
+            .with_connection_mode(ConnectionMode::Eager)
+            .build()
+            .await
+    );
+    assert_ok!(client.warm_up().await);
+    let (_, info) = assert_ok!(client.get_masterchain_info().await);
+    assert!(info.last.seqno > 0);
+}
+
+#[tokio::test]
+async fn client_autoscaling_works() {
+    common::init_logging();
+    let mut autoscaling = PoolAutoscaling::new(1, 3);
+    autoscaling.queue_threshold = 2;
+    let client = assert_ok!(
+        TonClient::builder()
+            .with_config(MAINNET_CONFIG)
+            .with_autoscaling(&autoscaling)
+            .build()
+            .await
+    );
+    assert_eq!(client.pool_size(), 1);
+    let futures = (0..20).map(|_| client.get_masterchain_info());
+    for result in join_all(futures).await {
+        assert_ok!(result);
+    }
+    assert!(client.pool_size() > 1);
+    assert!(client.pool_size() <= 3);
+}
+
+#[tokio::test]
+async fn client_failover_works() {
+    common::init_logging();
+    let client = assert_ok!(
+        TonClient::builder()
+            .with_config("{}")
+            .with_failover_config(MAINNET_CONFIG)
+            .with_failover_strategy(Arc::new(StickyFailover::default()))
+            .build()
+            .await
+    );
+    assert_ok!(client.get_masterchain_info().await);
+    let status = client.failover_status();
+    assert_eq!(status[0].failures, 1);
+    assert!(status[1].latency.is_some());
+    assert_eq!(client.pool_status()[0].endpoint, 1);
+}
+
+#[tokio::test]
+async fn client_archive_routing_works() {
+    common::init_logging();
+    let client = assert_ok!(
+        TonClient::builder()
+            .with_config(MAINNET_CONFIG)
+            .with_pool_size(3)
+            .with_archive_routing(&ArchiveRouting::new(1))
+            .build()
+            .await
+    );
+    let archive: Vec<_> = client.pool_status().iter().map(|s| s.archive).collect();
+    assert_eq!(archive, vec![true, false, false]);
+    assert_ok!(client.get_masterchain_info().await);
+    let block_id = BlockId {
+        workchain: -1,
+        shard: i64::MIN,
+        seqno: 1000,
+    };
+    let block = assert_ok!(client.lookup_block(1, &block_id, 0, 0).await);
+    assert_eq!(block.seqno, 1000);
+}
+
 #[tokio::test]
 async fn client_testnet_works() {
     common::init_logging();
@@ -470,10 +694,14 @@ async fn client_testnet_works() {
     let (_, info) = assert_ok!(client.get_masterchain_info().await);
     let shards = assert_ok!(client.get_block_shards(&info.last).await);
     assert!(!shards.shards.is_empty());
-    let shards_txs = assert_ok!(client.get_shards_transactions(&shards.shards).await);
+    let shards_txs = client.get_shards_transactions(&shards.shards).await;
     let blocks_header = assert_ok!(client.get_block_header(&info.last).await);
-    for s in shards_txs {
-        log::info!(" BlockId: {:?}\n Transactions: {:?}", s.0, s.1);
+    for (shard, txs) in shards_txs {
+        log::info!(
+            " BlockId: {:?}\n Transactions: {:?}",
+            shard,
+            assert_ok!(txs)
+        );
     }
 
     log::info!(
@@ -0,0 +1,65 @@
+#![cfg(feature = "compat_tests")]
+
+use std::sync::Arc;
+
+use num_bigint::BigUint;
+use num_traits::Zero;
+use tokio_test::assert_ok;
+use tonlib::address::TonAddress;
+use tonlib::cell::{BagOfCells, CellBuilder, StateInit};
+use tonlib::compat::{ADDRESS_VECTORS, BOC_VECTORS, STATE_INIT_VECTORS, WALLET_VECTORS};
+use tonlib::mnemonic::Mnemonic;
+use tonlib::wallet::TonWallet;
+
+#[test]
+fn test_address_vectors() {
+    for vector in ADDRESS_VECTORS.iter() {
+        let address = assert_ok!(TonAddress::from_hex_str(vector.hex));
+        assert_eq!(address.to_hex(), vector.hex);
+        assert_eq!(address.to_base64_url(), vector.base64_url);
+        assert_eq!(address.to_base64_std(), vector.base64_std);
+    }
+}
+
+#[test]
+fn test_wallet_vectors() {
+    for vector in WALLET_VECTORS.iter() {
+        let key_pair =
+            assert_ok!(assert_ok!(Mnemonic::from_str(vector.mnemonic, &None)).to_key_pair());
+        let wallet = assert_ok!(TonWallet::derive_default(vector.version.clone(), &key_pair));
+        let expected: TonAddress = assert_ok!(vector.address.parse());
+        assert_eq!(wallet.address, expected, "{:?}", vector.version);
+    }
+}
+
+#[test]
+fn test_boc_vectors() {
+    for vector in BOC_VECTORS.iter() {
+        let boc = assert_ok!(BagOfCells::parse_hex(vector.boc_hex));
+        let root = assert_ok!(boc.single_root());
+        assert_eq!(hex::encode(root.cell_hash()), vector.root_hash);
+        assert_eq!(
+            hex::encode(assert_ok!(boc.serialize(false))),
+            vector.boc_hex
+        );
+    }
+}
+
+#[test]
+fn test_state_init_vectors() {
+    for vector in STATE_INIT_VECTORS.iter() {
+        let boc = assert_ok!(BagOfCells::parse_hex(vector.code_boc_hex));
+        let code = assert_ok!(boc.single_root());
+        let mut builder = CellBuilder::new();
+        for address in vector.data_addresses {
+            let address: TonAddress = assert_ok!(address.parse());
+            assert_ok!(builder.store_address(&address));
+        }
+        assert_ok!(builder.store_coins(&BigUint::zero()));
+        assert_ok!(builder.store_coins(&BigUint::zero()));
+        let data = assert_ok!(builder.build());
+        let account_id = assert_ok!(StateInit::create_account_id(code, &Arc::new(data)));
+        let expected: TonAddress = assert_ok!(vector.address.parse());
+        assert_eq!(TonAddress::new(0, &account_id), expected);
+    }
+}
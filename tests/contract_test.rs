@@ -8,10 +8,12 @@ use num_bigint::BigUint;
 use tokio_test::assert_ok;
 use tonlib::address::TonAddress;
 use tonlib::contract::{
-    TonContractError, TonContractFactory, TonContractInterface, TonContractState,
+    AccountStatus, ContractInterface, TonContractError, TonContractFactory, TonContractInterface,
+    TonContractState, WalletContract, DISCOVERED_WALLET_VERSIONS,
 };
+use tonlib::message::TransferMessage;
 use tonlib::mnemonic::Mnemonic;
-use tonlib::types::TvmSuccess;
+use tonlib::types::{TvmError, TvmSuccess};
 use tonlib::wallet::{TonWallet, WalletVersion};
 
 mod common;
@@ -80,7 +82,14 @@ async fn contract_get_pool_data_works() {
     match invalid_result {
         Ok(_) => panic!(),
         Err(err) => match err {
-            TonContractError::TvmRunError { exit_code, .. } => assert_eq!(exit_code, 11),
+            TonContractError::TvmRunError {
+                exit_code,
+                tvm_error,
+                ..
+            } => {
+                assert_eq!(exit_code, 11);
+                assert_eq!(tvm_error, TvmError::UnknownMethod);
+            }
             _ => assert_eq!(0, 1),
         },
     }
@@ -102,6 +111,28 @@ async fn state_get_pool_data_works() {
     assert!(invalid_result.is_err());
 }
 
+#[tokio::test]
+async fn state_get_method_cache_works() {
+    common::init_logging();
+    let client = common::new_mainnet_client().await;
+    let factory = assert_ok!(
+        TonContractFactory::builder(&client)
+            .with_get_method_cache(100)
+            .build()
+            .await
+    );
+    let contract = factory.get_contract(&assert_ok!(
+        "EQD9b5pxv6nptJmD1-c771oRV98h_mky-URkDn5BJpY2sTJ-".parse()
+    ));
+    let state = assert_ok!(contract.get_state().await);
+    let pool_data1 = assert_ok!(state.get_pool_data().await);
+    let pool_data2 = assert_ok!(state.get_pool_data().await);
+    assert_eq!(pool_data1.reserve0, pool_data2.reserve0);
+    let stats = factory.get_method_cache().unwrap().get_stats();
+    assert_eq!(stats.misses, 1);
+    assert_eq!(stats.hits, 1);
+}
+
 #[tokio::test]
 async fn state_clone_works() {
     common::init_logging();
@@ -232,3 +263,69 @@ async fn test_string_in_tvm_success() -> anyhow::Result<()> {
     assert_eq!("constant_product", pool_type);
     Ok(())
 }
+
+#[tokio::test]
+async fn test_account_summary() {
+    common::init_logging();
+    let client = common::new_mainnet_client().await;
+    let factory = assert_ok!(TonContractFactory::builder(&client).build().await);
+
+    let jetton_master = assert_ok!("EQDk2VTvn04SUKJrW7rXahzdF8_Qi6utb0wj43InCu9vdjrR".parse());
+    let summary = assert_ok!(factory.account_summary(&jetton_master).await);
+    log::info!("{:?}", summary);
+    assert_eq!(summary.status, AccountStatus::Active);
+    assert!(summary.code_hash.is_some());
+    assert!(summary.tx_count > 0);
+    assert!(summary.last_activity.is_some());
+    assert!(summary
+        .interfaces
+        .contains(&ContractInterface::JettonMaster));
+
+    let mnemonic_str = "mechanic sudden cannon bind monkey brown moment able street pride struggle team outdoor canyon coin tourist service second crazy tank sell regret sample attitude";
+    let mnemonic = assert_ok!(Mnemonic::from_str(mnemonic_str, &None));
+    let key_pair = assert_ok!(mnemonic.to_key_pair());
+    let wallet = assert_ok!(TonWallet::derive_default(WalletVersion::V4R2, &key_pair));
+    let summary = assert_ok!(factory.account_summary(&wallet.address).await);
+    assert_eq!(summary.status, AccountStatus::Uninit);
+    assert_eq!(summary.code_hash, None);
+    assert!(summary.interfaces.is_empty());
+}
+
+#[tokio::test]
+async fn test_discover_wallets() {
+    common::init_logging();
+    let client = common::new_mainnet_client().await;
+    let factory = assert_ok!(TonContractFactory::builder(&client).build().await);
+
+    let mnemonic_str = "mechanic sudden cannon bind monkey brown moment able street pride struggle team outdoor canyon coin tourist service second crazy tank sell regret sample attitude";
+    let mnemonic = assert_ok!(Mnemonic::from_str(mnemonic_str, &None));
+    let key_pair = assert_ok!(mnemonic.to_key_pair());
+    let wallets = assert_ok!(factory.discover_wallets(&mnemonic).await);
+    assert_eq!(wallets.len(), DISCOVERED_WALLET_VERSIONS.len());
+    for (wallet, version) in wallets.iter().zip(DISCOVERED_WALLET_VERSIONS.iter()) {
+        log::info!("{:?}", wallet);
+        assert_eq!(&wallet.version, version);
+        let derived = assert_ok!(TonWallet::derive_default(version.clone(), &key_pair));
+        assert_eq!(wallet.address, derived.address);
+    }
+}
+
+#[tokio::test]
+async fn test_wallet_contract() {
+    common::init_logging();
+    let client = common::new_mainnet_client().await;
+    let factory = assert_ok!(TonContractFactory::builder(&client).build().await);
+
+    let mnemonic_str = "mechanic sudden cannon bind monkey brown moment able street pride struggle team outdoor canyon coin tourist service second crazy tank sell regret sample attitude";
+    let mnemonic = assert_ok!(Mnemonic::from_str(mnemonic_str, &None));
+    let key_pair = assert_ok!(mnemonic.to_key_pair());
+    let wallet = assert_ok!(WalletContract::v4r2(&factory, &key_pair));
+    let derived = assert_ok!(TonWallet::derive_default(WalletVersion::V4R2, &key_pair));
+    assert_eq!(wallet.address(), &derived.address);
+
+    // the wallet is not deployed, the transfer deploys it
+    let message = assert_ok!(TransferMessage::new(&derived.address, &BigUint::from(1u32)).build());
+    let transfer = assert_ok!(wallet.create_transfer([message.to_arc()], 60).await);
+    log::info!("transfer: {:?}", transfer);
+    assert_eq!(transfer.references().len(), 2);
+}
@@ -18,7 +18,7 @@ use tonlib::tl::{
     InternalTransactionIdParseError, TlError, TonResultDiscriminants, TvmCell,
     TvmStackEntry as TlTvmStackEntry, TvmStackError,
 };
-use tonlib::types::TvmStackEntry;
+use tonlib::types::{TvmError, TvmStackEntry};
 
 mod common;
 
@@ -111,11 +111,7 @@ fn test_ton_client_error_output() {
     );
     log::error!(
         "{}",
-        TonClientError::TonlibError {
-            method: "some_get_method",
-            code: 300,
-            message: "Some error message".to_string(),
-        }
+        TonClientError::tonlib_error("some_get_method", 300, "Some error message".to_string())
     );
     log::error!(
         "{}",
@@ -164,11 +160,11 @@ fn test_ton_contract_error_output() {
 
     log::error!(
         "{}",
-        TonContractError::ClientError(TonClientError::TonlibError {
-            method: "some_get_method",
-            code: 300,
-            message: "Some error message".to_string(),
-        })
+        TonContractError::ClientError(TonClientError::tonlib_error(
+            "some_get_method",
+            300,
+            "Some error message".to_string()
+        ))
     );
 
     log::error!(
@@ -210,6 +206,7 @@ fn test_ton_contract_error_output() {
                 TvmStackEntry::Int257(BigInt::from(1234566789)),
             ],
             exit_code: -123,
+            tvm_error: TvmError::Contract(-123),
             vm_log: None,
             missing_library: None,
             address: TonAddress::null(),
@@ -1,7 +1,9 @@
+use std::sync::Arc;
+
 use sha2::{Digest, Sha256};
 use tokio_test::assert_ok;
 use tonlib::address::TonAddress;
-use tonlib::contract::{JettonMasterContract, TonContractFactory};
+use tonlib::contract::{JettonMasterContract, PortfolioLoader, TonContractFactory};
 use tonlib::meta::*;
 
 mod common;
@@ -57,6 +59,21 @@ async fn test_get_jetton_content_internal_uri_jusdt() {
     assert_eq!(content_res.decimals, Some(6));
 }
 
+#[tokio::test]
+async fn test_get_jetton_data_lax() {
+    common::init_logging();
+    let client = common::new_mainnet_client().await;
+    let factory = assert_ok!(TonContractFactory::builder(&client).build().await);
+    let contract = factory.get_contract(&assert_ok!(
+        "EQDk2VTvn04SUKJrW7rXahzdF8_Qi6utb0wj43InCu9vdjrR".parse()
+    )); // Moon jetton
+    let strict = assert_ok!(contract.get_jetton_data().await);
+    let lax = assert_ok!(contract.get_jetton_data_lax().await);
+    assert!(lax.warnings.is_empty());
+    assert_eq!(lax.total_supply, Some(strict.total_supply));
+    assert_eq!(lax.content, Some(strict.content));
+}
+
 #[tokio::test]
 async fn test_get_jetton_content_empty_external_meta() {
     common::init_logging();
@@ -181,3 +198,35 @@ async fn test_jetton_image_data() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_portfolio() {
+    common::init_logging();
+    let client = common::new_mainnet_client().await;
+    let factory = assert_ok!(TonContractFactory::builder(&client).build().await);
+    let master_address: TonAddress =
+        assert_ok!("EQDk2VTvn04SUKJrW7rXahzdF8_Qi6utb0wj43InCu9vdjrR".parse()); // Moon jetton
+    let owner_address = assert_ok!(TonAddress::from_base64_url(
+        "EQB2BtXDXaQuIcMYW7JEWhHmwHfPPwa-eoCdefiAxOhU3pQg"
+    ));
+    let meta_loader = Arc::new(assert_ok!(JettonMetaLoader::default()));
+    let portfolio = assert_ok!(
+        PortfolioLoader::new(&factory)
+            .with_jetton_masters(std::slice::from_ref(&master_address))
+            .with_transaction_scan(32)
+            .with_meta_loader(meta_loader)
+            .portfolio(&owner_address)
+            .await
+    );
+    log::info!("{:?}", portfolio);
+    assert_eq!(portfolio.address, owner_address);
+    assert!(portfolio.failed_jettons.is_empty());
+    for jetton in portfolio.jettons.iter() {
+        if jetton.master_address == master_address {
+            assert_eq!(
+                jetton.wallet_address.to_base64_std(),
+                "EQCGY3OVLtD9KRcOsP2ldQDtuY0FMzV7wPoxjrFbayBXc23c"
+            );
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use num_bigint::BigUint;
+use tokio_test::assert_ok;
+use tonlib::client::TonClientInterface;
+use tonlib::contract::TonContractFactory;
+use tonlib::testing::TestnetGiver;
+use tonlib::wallet::WalletVersion;
+
+mod common;
+
+#[tokio::test]
+#[ignore]
+async fn test_new_funded_wallet() {
+    // Requires TONLIB_TESTNET_GIVER_MNEMONIC with the mnemonic of funded testnet V4R2 wallet
+    common::init_logging();
+    let client = common::new_testnet_client().await;
+    let factory = assert_ok!(TonContractFactory::builder(&client).build().await);
+    let giver = assert_ok!(TestnetGiver::from_env(&factory));
+    let amount = BigUint::from(10_000_000u64); // 0.01 TON
+    let wallet = assert_ok!(giver.new_funded_wallet(WalletVersion::V4R2, &amount).await);
+    let state = assert_ok!(client.get_raw_account_state(&wallet.address).await);
+    log::info!("Funded wallet {}: {:?}", wallet.address, state);
+    assert!(state.balance > 0);
+}
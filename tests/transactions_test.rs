@@ -6,6 +6,7 @@ use std::{thread, time};
 
 use anyhow::anyhow;
 use futures::future::join_all;
+use futures::StreamExt;
 use tokio_test::assert_ok;
 use tonlib::address::TonAddress;
 use tonlib::contract::{LatestContractTransactionsCache, TonContractFactory};
@@ -129,6 +130,69 @@ async fn get_txs_for_empty_works() {
     );
 }
 
+#[tokio::test]
+async fn subscribe_account_works() {
+    common::init_logging();
+    let config: &TonAddress =
+        &assert_ok!("Ef9VVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVbxn".parse());
+
+    let client = common::new_mainnet_client().await;
+    let factory = assert_ok!(TonContractFactory::builder(&client).build().await);
+    let trans = LatestContractTransactionsCache::new(&factory, config, 100, true, None);
+    let trs = assert_ok!(trans.get(21).await);
+    let after = &trs.last().unwrap().transaction_id;
+
+    let stream = client.subscribe_account_after(config, after);
+    let received: Vec<_> = stream.take(20).collect().await;
+    let expected: Vec<_> = trs
+        .iter()
+        .take(20)
+        .rev()
+        .map(|t| &t.transaction_id)
+        .collect();
+    assert_eq!(
+        received
+            .iter()
+            .map(|t| &t.transaction_id)
+            .collect::<Vec<_>>(),
+        expected
+    );
+}
+
+#[tokio::test]
+async fn iter_raw_transactions_works() {
+    common::init_logging();
+    let config: &TonAddress =
+        &assert_ok!("Ef9VVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVVbxn".parse());
+
+    let client = common::new_mainnet_client().await;
+    let received: Vec<_> = client
+        .iter_raw_transactions(config)
+        .take(40)
+        .collect()
+        .await;
+    assert_eq!(received.len(), 40);
+    let trs: Vec<_> = received.into_iter().map(|t| Arc::new(t.unwrap())).collect();
+    assert_ok!(check_order(trs.clone()));
+
+    let from = &trs[20].transaction_id;
+    let resumed: Vec<_> = client
+        .iter_raw_transactions_from(config, from)
+        .take(20)
+        .collect()
+        .await;
+    assert_eq!(
+        resumed
+            .iter()
+            .map(|t| t.as_ref().unwrap().transaction_id.clone())
+            .collect::<Vec<_>>(),
+        trs[20..]
+            .iter()
+            .map(|t| t.transaction_id.clone())
+            .collect::<Vec<_>>()
+    );
+}
+
 fn check_order(trs: Vec<Arc<RawTransaction>>) -> anyhow::Result<()> {
     let mut lt = 0;
     for t in trs.iter() {
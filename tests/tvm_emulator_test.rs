@@ -10,12 +10,12 @@ mod contract_emulator_tests {
     use tokio::{self};
     use tokio_test::assert_ok;
     use tonlib::address::TonAddress;
-    use tonlib::cell::{BagOfCells, CellBuilder, CellSlice};
+    use tonlib::cell::{BagOfCells, CellBuilder};
     use tonlib::client::TonClientInterface;
     use tonlib::contract::{
         JettonData, JettonMasterContract, TonContractFactory, TonContractInterface,
     };
-    use tonlib::emulator::{TvmEmulator, TvmEmulatorC7Builder};
+    use tonlib::emulator::{seed_from_u64, TvmEmulator, TvmEmulatorC7Builder};
     use tonlib::message::JettonTransferMessage;
     use tonlib::meta::MetaDataContent;
     use tonlib::types::TvmStackEntry;
@@ -122,6 +122,20 @@ mod contract_emulator_tests {
         assert_eq!(emulator_result.stack[0], TvmStackEntry::Nan);
     }
 
+    #[test]
+    fn test_emulator_c7_seed() {
+        let address = TonAddress::NULL;
+        let config = [];
+        let mut builder = TvmEmulatorC7Builder::new(&address, &config, 0);
+        let c7 = builder
+            .with_seed_from_u64(42)
+            .with_unix_time(1_700_000_000)
+            .build();
+        assert_eq!(c7.seed, seed_from_u64(42));
+        assert_eq!(c7.unix_time, 1_700_000_000);
+        assert_ne!(c7.seed, seed_from_u64(43));
+    }
+
     #[tokio::test]
     async fn test_emulator_empty_contract_code() {
         common::init_logging();
@@ -225,6 +239,23 @@ mod contract_emulator_tests {
         assert_eq!(blockchain_data.content, emulated_data.content);
     }
 
+    #[tokio::test]
+    async fn test_emulator_from_cells() {
+        common::init_logging();
+        let code = assert_ok!(BagOfCells::parse(&TEST_CONTRACT_CODE));
+        let data = assert_ok!(BagOfCells::parse(&TEST_CONTRACT_DATA));
+        let mut emulator = assert_ok!(TvmEmulator::from_cells(
+            assert_ok!(code.single_root()),
+            assert_ok!(data.single_root())
+        ));
+        let stack = vec![6i64.into(), 7i64.into()];
+        let emulator_result =
+            assert_ok!(emulator.run_get_method(&"get_val".into(), stack.as_slice()));
+        assert_eq!(emulator_result.vm_exit_code, 0);
+        let result = assert_ok!(emulator_result.stack[0].get_bigint());
+        assert_eq!(result, BigInt::from(42));
+    }
+
     #[tokio::test]
     async fn test_emulator_get_jetton_data_long_total_supply() {
         common::init_logging();
@@ -345,10 +376,9 @@ mod contract_emulator_tests {
         let amount = BigUint::from(100_500_000u32);
         let emulated_result = emulate_get_expected_outputs(code, data, &amount, &addr1);
         log::info!("Emulated result: {}", emulated_result);
-        let addr_cell = assert_ok!(assert_ok!(CellBuilder::new().store_address(&addr1)).build());
         let stack = vec![
             TvmStackEntry::Int257(BigInt::from(amount)),
-            TvmStackEntry::Slice(assert_ok!(CellSlice::full_cell(addr_cell))),
+            assert_ok!(TvmStackEntry::from_address(&addr1)),
         ];
         let run_result = assert_ok!(
             assert_ok!(